@@ -0,0 +1,19 @@
+use std::fs;
+
+// Exposes the locked versions of a few dependencies, shown by the `stats` command.
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    // `sqlx::migrate!` embeds the migrations, so adding one has to trigger a rebuild
+    println!("cargo:rerun-if-changed=migrations");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+
+    for (name, variable) in [("serenity", "SERENITY_VERSION"), ("sqlx", "SQLX_VERSION")] {
+        let version = lock.split("[[package]]")
+            .find(|package| package.lines().any(|line| line.trim() == format!("name = \"{name}\"")))
+            .and_then(|package| package.lines().find_map(|line| line.trim().strip_prefix("version = \"")))
+            .map_or("unknown", |version| version.trim_end_matches('"'));
+
+        println!("cargo:rustc-env={variable}={version}");
+    }
+}
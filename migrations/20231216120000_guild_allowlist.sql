@@ -0,0 +1,16 @@
+-- bot-wide settings schema (single row, id is always 0)
+CREATE TABLE IF NOT EXISTS bot_settings (
+    id INTEGER NOT NULL DEFAULT 0,
+    allowlist_enabled INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (id)
+);
+
+INSERT OR IGNORE INTO bot_settings (id) VALUES (0);
+
+-- guild allowlist schema
+CREATE TABLE IF NOT EXISTS guild_allowlist (
+    guild_id BIGINT NOT NULL,
+    added_by BIGINT NOT NULL,
+    added_at TEXT NOT NULL,
+    PRIMARY KEY (guild_id)
+);
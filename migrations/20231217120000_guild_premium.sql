@@ -0,0 +1,9 @@
+-- guild premium schema
+CREATE TABLE IF NOT EXISTS guild_premium (
+    guild_id BIGINT NOT NULL,
+    tier INTEGER NOT NULL DEFAULT 0,
+    source TEXT NOT NULL DEFAULT "manual", -- "manual" when set by the bot owner, "entitlement" when granted through Discord
+    entitlement_id BIGINT,
+    expires_at TEXT,
+    PRIMARY KEY (guild_id)
+);
@@ -0,0 +1,18 @@
+-- bot list votes schema
+CREATE TABLE IF NOT EXISTS votes (
+    id INTEGER NOT NULL,
+    user_id BIGINT NOT NULL,
+    is_weekend INTEGER NOT NULL DEFAULT 0,
+    voted_at TEXT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+-- vote streak schema
+CREATE TABLE IF NOT EXISTS vote_streaks (
+    user_id BIGINT NOT NULL,
+    streak INTEGER NOT NULL DEFAULT 0,
+    best_streak INTEGER NOT NULL DEFAULT 0,
+    total_votes INTEGER NOT NULL DEFAULT 0,
+    last_voted_at TEXT NOT NULL,
+    PRIMARY KEY (user_id)
+);
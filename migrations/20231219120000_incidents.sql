@@ -0,0 +1,32 @@
+-- channel that receives bot status updates (incidents, changelogs)
+ALTER TABLE guild_settings ADD COLUMN updates_channel_id BIGINT;
+
+-- incident schema
+CREATE TABLE IF NOT EXISTS incidents (
+    id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT "investigating",
+    automatic INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    resolved_at TEXT,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+-- incident update schema
+CREATE TABLE IF NOT EXISTS incident_updates (
+    id INTEGER NOT NULL,
+    incident_id INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+-- posted incident embeds, kept so they can be edited as the incident progresses
+CREATE TABLE IF NOT EXISTS incident_messages (
+    incident_id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    PRIMARY KEY (incident_id, guild_id)
+);
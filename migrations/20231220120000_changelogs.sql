@@ -0,0 +1,9 @@
+-- changelog schema
+CREATE TABLE IF NOT EXISTS changelogs (
+    id INTEGER NOT NULL,
+    version TEXT NOT NULL,
+    notes TEXT NOT NULL,
+    published_by BIGINT NOT NULL,
+    published_at TEXT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
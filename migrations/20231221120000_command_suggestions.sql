@@ -0,0 +1,2 @@
+-- whether unknown commands get a "did you mean" suggestion
+ALTER TABLE guild_settings ADD COLUMN command_suggestions INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,9 @@
+-- command macro schema
+CREATE TABLE IF NOT EXISTS command_macros (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    steps TEXT NOT NULL, -- one command per line, without the prefix
+    created_by BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (guild_id, name)
+);
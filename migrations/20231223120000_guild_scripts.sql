@@ -0,0 +1,10 @@
+-- custom command script schema
+CREATE TABLE IF NOT EXISTS guild_scripts (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    source TEXT NOT NULL,
+    created_by BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, name)
+);
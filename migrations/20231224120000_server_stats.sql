@@ -0,0 +1,29 @@
+-- hourly message count schema
+CREATE TABLE IF NOT EXISTS message_stats (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    day TEXT NOT NULL, -- YYYY-MM-DD in UTC
+    hour INTEGER NOT NULL,
+    messages INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, channel_id, day, hour)
+);
+
+-- active member schema, only kept until the day has been rolled up
+CREATE TABLE IF NOT EXISTS active_members (
+    guild_id BIGINT NOT NULL,
+    day TEXT NOT NULL,
+    user_id BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, day, user_id)
+);
+
+-- daily rollup schema
+CREATE TABLE IF NOT EXISTS daily_stats (
+    guild_id BIGINT NOT NULL,
+    day TEXT NOT NULL,
+    messages INTEGER NOT NULL DEFAULT 0,
+    active_members INTEGER NOT NULL DEFAULT 0,
+    joins INTEGER NOT NULL DEFAULT 0,
+    leaves INTEGER NOT NULL DEFAULT 0,
+    member_count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, day)
+);
@@ -0,0 +1,9 @@
+-- member count channel schema
+CREATE TABLE IF NOT EXISTS counter_channels (
+    channel_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    template TEXT NOT NULL, -- rendered with {count}
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (channel_id)
+);
@@ -0,0 +1,12 @@
+-- auto-responder schema
+CREATE TABLE IF NOT EXISTS auto_responses (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    pattern TEXT NOT NULL,
+    match_mode TEXT NOT NULL DEFAULT 'contains',
+    response TEXT NOT NULL,
+    cooldown INTEGER NOT NULL DEFAULT 0, -- seconds between responses
+    channels TEXT NOT NULL DEFAULT '', -- comma separated channel IDs, empty for every channel
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
@@ -0,0 +1,14 @@
+-- job scheduler schema
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    id INTEGER NOT NULL,
+    guild_id BIGINT, -- NULL for jobs that don't belong to a guild
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL, -- JSON, depends on the kind
+    next_run TEXT NOT NULL, -- RFC 3339 in UTC with whole seconds, so it can be compared as text
+    repeat TEXT, -- "every <duration>" or a cron expression, NULL for one-off jobs
+    paused INTEGER NOT NULL DEFAULT 0,
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE INDEX IF NOT EXISTS scheduled_jobs_next_run ON scheduled_jobs (next_run);
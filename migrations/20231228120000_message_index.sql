@@ -0,0 +1,27 @@
+-- per-user hourly message count schema
+CREATE TABLE IF NOT EXISTS user_message_stats (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    day TEXT NOT NULL, -- YYYY-MM-DD in UTC
+    hour INTEGER NOT NULL,
+    messages INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id, day, hour)
+);
+
+-- word frequency schema, only words are kept and never whole messages
+CREATE TABLE IF NOT EXISTS word_stats (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    day TEXT NOT NULL,
+    word TEXT NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, channel_id, user_id, day, word)
+);
+
+-- message statistics opt-out schema
+CREATE TABLE IF NOT EXISTS privacy_opt_outs (
+    user_id BIGINT NOT NULL,
+    opted_out_at TEXT NOT NULL,
+    PRIMARY KEY (user_id)
+);
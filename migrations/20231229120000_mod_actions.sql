@@ -0,0 +1,13 @@
+-- moderation action log schema
+CREATE TABLE IF NOT EXISTS mod_actions (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    target_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT, -- NULL when no reason was given
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE INDEX IF NOT EXISTS mod_actions_target ON mod_actions (guild_id, target_id);
@@ -0,0 +1,21 @@
+-- warnings schema
+CREATE TABLE IF NOT EXISTS warnings (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT, -- NULL when no reason was given
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE INDEX IF NOT EXISTS warnings_user ON warnings (guild_id, user_id);
+
+-- warning escalation schema
+CREATE TABLE IF NOT EXISTS warning_escalations (
+    guild_id BIGINT NOT NULL,
+    warnings INTEGER NOT NULL, -- taken once a user reaches exactly this many warnings
+    action TEXT NOT NULL, -- mute, kick or ban
+    duration INTEGER, -- seconds a mute lasts, NULL for mutes until unmuted
+    PRIMARY KEY (guild_id, warnings)
+);
@@ -0,0 +1,12 @@
+-- moderation case schema, cases are numbered per guild
+ALTER TABLE mod_actions ADD COLUMN case_number INTEGER;
+ALTER TABLE mod_actions ADD COLUMN modlog_message_id BIGINT; -- the case's embed in the modlog channel, if posted
+
+UPDATE mod_actions SET case_number = (
+    SELECT COUNT(*) FROM mod_actions AS earlier
+    WHERE earlier.guild_id = mod_actions.guild_id AND earlier.id <= mod_actions.id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS mod_actions_case ON mod_actions (guild_id, case_number);
+
+ALTER TABLE guild_settings ADD COLUMN modlog_channel_id BIGINT;
@@ -0,0 +1,11 @@
+-- automod rule schema, one rule of each kind per guild
+CREATE TABLE IF NOT EXISTS automod_rules (
+    guild_id BIGINT NOT NULL,
+    kind TEXT NOT NULL, -- words, invites, mentions, caps or zalgo
+    action TEXT NOT NULL DEFAULT 'delete', -- delete, warn, mute or ban
+    words TEXT NOT NULL DEFAULT '', -- comma separated banned words, only used by the words rule
+    threshold INTEGER NOT NULL DEFAULT 0, -- mentions per message, percent capitals or combining marks
+    exempt_channels TEXT NOT NULL DEFAULT '', -- comma separated channel IDs
+    exempt_roles TEXT NOT NULL DEFAULT '', -- comma separated role IDs
+    PRIMARY KEY (guild_id, kind)
+);
@@ -0,0 +1,10 @@
+-- anti-spam settings schema
+CREATE TABLE IF NOT EXISTS antispam_settings (
+    guild_id BIGINT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    max_messages INTEGER NOT NULL DEFAULT 6, -- messages allowed within the window
+    window_seconds INTEGER NOT NULL DEFAULT 5,
+    max_duplicates INTEGER NOT NULL DEFAULT 3, -- identical messages allowed within 30 seconds
+    timeout_seconds INTEGER NOT NULL DEFAULT 300,
+    PRIMARY KEY (guild_id)
+);
@@ -0,0 +1,10 @@
+-- anti-raid settings schema
+CREATE TABLE IF NOT EXISTS antiraid_settings (
+    guild_id BIGINT NOT NULL,
+    mode TEXT NOT NULL DEFAULT 'off', -- off, on or auto
+    max_joins INTEGER NOT NULL DEFAULT 10, -- joins allowed within the window before raid mode starts
+    window_seconds INTEGER NOT NULL DEFAULT 10,
+    min_account_age INTEGER NOT NULL DEFAULT 604800, -- seconds, younger accounts are acted on during a raid
+    action TEXT NOT NULL DEFAULT 'kick', -- kick or quarantine
+    PRIMARY KEY (guild_id)
+);
@@ -0,0 +1,4 @@
+-- goodbye message schema, the counterpart of the welcome columns
+ALTER TABLE guild_settings ADD COLUMN goodbye_channel_id BIGINT;
+ALTER TABLE guild_settings ADD COLUMN goodbye_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE guild_settings ADD COLUMN goodbye_message TEXT;
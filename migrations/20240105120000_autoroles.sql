@@ -0,0 +1,8 @@
+-- autorole schema, roles handed to members when they join
+CREATE TABLE IF NOT EXISTS autoroles (
+    guild_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, role_id)
+);
+
+ALTER TABLE guild_settings ADD COLUMN autorole_after_screening INTEGER NOT NULL DEFAULT 0; -- wait until members pass membership screening
@@ -0,0 +1,15 @@
+-- role menu schema, messages with a select menu of self-assignable roles
+CREATE TABLE IF NOT EXISTS role_menus (
+    message_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (message_id)
+);
+
+CREATE TABLE IF NOT EXISTS role_menu_roles (
+    message_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+    PRIMARY KEY (message_id, role_id)
+);
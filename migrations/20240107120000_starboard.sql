@@ -0,0 +1,12 @@
+-- starboard schema, messages reposted to the starboard channel once they have enough stars
+ALTER TABLE guild_settings ADD COLUMN starboard_channel_id BIGINT;
+ALTER TABLE guild_settings ADD COLUMN starboard_threshold INTEGER NOT NULL DEFAULT 3;
+
+CREATE TABLE IF NOT EXISTS starboard_entries (
+    message_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    starboard_message_id BIGINT, -- unset while the repost is being sent
+    stars INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (message_id)
+);
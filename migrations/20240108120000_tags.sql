@@ -0,0 +1,12 @@
+-- tag schema
+CREATE TABLE IF NOT EXISTS tags (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    content TEXT NOT NULL,
+    embed INTEGER NOT NULL DEFAULT 0,
+    created_by BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, name)
+);
@@ -0,0 +1,11 @@
+-- custom command schema
+CREATE TABLE IF NOT EXISTS custom_commands (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    response TEXT NOT NULL, -- a template, see `template`
+    required_role_id BIGINT, -- only members with this role can use the command
+    created_by BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, name)
+);
@@ -0,0 +1,20 @@
+-- poll schema
+CREATE TABLE IF NOT EXISTS polls (
+    message_id BIGINT NOT NULL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    question TEXT NOT NULL,
+    options TEXT NOT NULL, -- one option per line, in button order
+    multiple_choice INTEGER NOT NULL DEFAULT 0,
+    closes_at TEXT, -- NULL for polls that stay open until closed by hand
+    closed INTEGER NOT NULL DEFAULT 0,
+    created_by BIGINT NOT NULL
+);
+
+-- the primary key keeps anyone from voting for the same option twice
+CREATE TABLE IF NOT EXISTS poll_votes (
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    option INTEGER NOT NULL,
+    PRIMARY KEY (message_id, user_id, option)
+);
@@ -0,0 +1,29 @@
+-- leveling schema
+ALTER TABLE guild_settings ADD COLUMN leveling_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE guild_settings ADD COLUMN level_up_announcements INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE guild_settings ADD COLUMN level_up_channel_id BIGINT; -- NULL announces in the channel the member levelled up in
+ALTER TABLE guild_settings ADD COLUMN level_up_message TEXT; -- a template, NULL for the default
+
+CREATE TABLE IF NOT EXISTS levels (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    xp INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS levels_xp ON levels (guild_id, xp);
+
+CREATE TABLE IF NOT EXISTS level_rewards (
+    guild_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+    level INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, role_id)
+);
+
+-- 0 stops a channel from giving XP at all
+CREATE TABLE IF NOT EXISTS level_multipliers (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    multiplier REAL NOT NULL,
+    PRIMARY KEY (guild_id, channel_id)
+);
@@ -0,0 +1,27 @@
+-- economy schema
+CREATE TABLE IF NOT EXISTS economy_balances (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    balance INTEGER NOT NULL DEFAULT 0 CHECK (balance >= 0),
+    last_daily_at TEXT, -- RFC 3339 in UTC with whole seconds, so it can be compared as text
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS shop_items (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    price INTEGER NOT NULL,
+    role_id BIGINT, -- NULL for items that are only kept in inventories
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE TABLE IF NOT EXISTS economy_inventory (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    item_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id, item_id)
+);
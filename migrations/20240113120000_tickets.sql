@@ -0,0 +1,17 @@
+-- ticket schema
+ALTER TABLE guild_settings ADD COLUMN ticket_support_role_id BIGINT;
+ALTER TABLE guild_settings ADD COLUMN ticket_category_id BIGINT; -- NULL creates tickets outside any category
+ALTER TABLE guild_settings ADD COLUMN ticket_log_channel_id BIGINT; -- NULL archives closed tickets without posting transcripts
+
+CREATE TABLE IF NOT EXISTS tickets (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL UNIQUE,
+    user_id BIGINT NOT NULL,
+    opened_at TEXT NOT NULL,
+    closed_at TEXT,
+    closed_by BIGINT,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE INDEX IF NOT EXISTS tickets_user ON tickets (guild_id, user_id);
@@ -0,0 +1,16 @@
+-- modmail schema
+ALTER TABLE guild_settings ADD COLUMN modmail_channel_id BIGINT; -- NULL turns modmail off
+ALTER TABLE guild_settings ADD COLUMN modmail_anonymous INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS modmail_threads (
+    thread_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    opened_at TEXT NOT NULL,
+    closed_at TEXT,
+    closed_by BIGINT,
+    PRIMARY KEY (thread_id)
+);
+
+-- a user has at most one open conversation, so their DMs always go to the same thread
+CREATE UNIQUE INDEX IF NOT EXISTS modmail_threads_open ON modmail_threads (user_id) WHERE closed_at IS NULL;
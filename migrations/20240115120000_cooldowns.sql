@@ -0,0 +1,8 @@
+-- command cooldown schema
+CREATE TABLE IF NOT EXISTS command_cooldowns (
+    guild_id BIGINT NOT NULL,
+    command TEXT NOT NULL,
+    seconds INTEGER NOT NULL, -- 0 turns a default cooldown off
+    scope TEXT NOT NULL DEFAULT 'user', -- 'user' or 'guild'
+    PRIMARY KEY (guild_id, command)
+);
@@ -0,0 +1,8 @@
+-- per-guild command toggles and channel restrictions
+CREATE TABLE IF NOT EXISTS command_rules (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL, -- a command's first name, a group name, or 'all'
+    channel_id BIGINT NOT NULL DEFAULT 0, -- 0 applies the rule to the whole server
+    allowed INTEGER NOT NULL, -- 1 limits the name to its allowed channels, 0 blocks it
+    PRIMARY KEY (guild_id, name, channel_id)
+);
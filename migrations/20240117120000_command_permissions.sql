@@ -0,0 +1,7 @@
+-- roles required to use commands or command groups
+CREATE TABLE IF NOT EXISTS command_permissions (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL, -- a command's first name, a group name, or 'all'
+    role_id BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, name, role_id)
+);
@@ -0,0 +1,2 @@
+-- where role, channel, emoji, webhook and member role changes are posted
+ALTER TABLE guild_settings ADD COLUMN server_log_channel_id BIGINT; -- NULL turns the server log off
@@ -0,0 +1,11 @@
+-- voice activity schema
+ALTER TABLE guild_settings ADD COLUMN voice_log_channel_id BIGINT; -- NULL turns the voice log off
+
+CREATE TABLE IF NOT EXISTS voice_time (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    seconds INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS voice_time_seconds ON voice_time (guild_id, seconds);
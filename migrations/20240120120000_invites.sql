@@ -0,0 +1,11 @@
+-- which invite each member joined with, replaced if they rejoin
+CREATE TABLE IF NOT EXISTS invite_joins (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    inviter_id BIGINT, -- NULL when the invite couldn't be worked out, or had no inviter
+    code TEXT,
+    joined_at TEXT NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS invite_joins_inviter ON invite_joins (guild_id, inviter_id);
@@ -0,0 +1,8 @@
+-- members who are away, cleared when they next talk in the server
+CREATE TABLE IF NOT EXISTS afk_statuses (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    reason TEXT,
+    since TEXT NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
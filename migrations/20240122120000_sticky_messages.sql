@@ -0,0 +1,10 @@
+-- messages kept at the bottom of a channel by reposting them
+CREATE TABLE IF NOT EXISTS sticky_messages (
+    channel_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    message_id BIGINT, -- the latest copy, deleted when it's reposted
+    every_messages INTEGER NOT NULL DEFAULT 5,
+    every_seconds INTEGER NOT NULL DEFAULT 0, -- 0 to only repost after messages
+    PRIMARY KEY (channel_id)
+);
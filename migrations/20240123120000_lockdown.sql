@@ -0,0 +1,16 @@
+-- channels locked by the lock and lockdown commands, with the @everyone overwrite they had before
+CREATE TABLE IF NOT EXISTS locked_channels (
+    channel_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    previous_allow BIGINT, -- both NULL if the channel had no @everyone overwrite
+    previous_deny BIGINT,
+    lockdown INTEGER NOT NULL DEFAULT 0, -- 1 if locked by a lockdown, which unlocks it again when it ends
+    PRIMARY KEY (channel_id)
+);
+
+-- the channels a guild's lockdown locks
+CREATE TABLE IF NOT EXISTS lockdown_channels (
+    channel_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    PRIMARY KEY (channel_id)
+);
@@ -0,0 +1,11 @@
+-- decancer members' nicknames as they join or change them
+ALTER TABLE guild_settings ADD COLUMN auto_decancer INTEGER NOT NULL DEFAULT 0;
+
+-- nicknames set with forcenick, put back whenever the member changes them or rejoins
+CREATE TABLE IF NOT EXISTS forced_nicknames (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    nickname TEXT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,9 @@
+-- guilds allowed to import another guild's ban list, approved by the owner of the guild sharing it
+CREATE TABLE IF NOT EXISTS ban_sync_links (
+    source_guild_id BIGINT NOT NULL, -- the guild whose bans are shared
+    target_guild_id BIGINT NOT NULL, -- the guild allowed to import them
+    approved_by BIGINT NOT NULL,
+    approved_at TEXT NOT NULL,
+    last_synced_at TEXT,
+    PRIMARY KEY (source_guild_id, target_guild_id)
+);
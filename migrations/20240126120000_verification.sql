@@ -0,0 +1,21 @@
+-- members have to press a button, or answer a captcha by DM, before they lose the unverified role
+CREATE TABLE IF NOT EXISTS verification_settings (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL, -- the message with the verify button
+    unverified_role_id BIGINT NOT NULL,
+    verified_role_id BIGINT,
+    mode TEXT NOT NULL DEFAULT 'button', -- 'button' or 'captcha'
+    PRIMARY KEY (guild_id)
+);
+
+-- members who joined and haven't verified yet
+CREATE TABLE IF NOT EXISTS pending_verifications (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    joined_at TEXT NOT NULL,
+    answer TEXT, -- the answer to the captcha they were sent, if any
+    attempts INTEGER NOT NULL DEFAULT 0,
+    asked_at TEXT,
+    PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,10 @@
+-- requirements every new member has to meet, unlike anti-raid which only applies during a raid
+CREATE TABLE IF NOT EXISTS join_gate_settings (
+    guild_id BIGINT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    min_account_age INTEGER NOT NULL DEFAULT 0, -- seconds, 0 for no minimum
+    require_avatar INTEGER NOT NULL DEFAULT 0,
+    action TEXT NOT NULL DEFAULT 'kick', -- kick or quarantine
+    quarantine_role_id BIGINT, -- the role members failing the gate are held in
+    PRIMARY KEY (guild_id)
+);
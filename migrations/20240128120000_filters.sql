@@ -0,0 +1,20 @@
+-- word filter schema, banned words and phrases enforced on new and edited messages
+CREATE TABLE IF NOT EXISTS filters (
+    id INTEGER NOT NULL,
+    guild_id BIGINT NOT NULL,
+    pattern TEXT NOT NULL,
+    match_mode TEXT NOT NULL DEFAULT 'exact', -- exact, wildcard or regex
+    action TEXT NOT NULL DEFAULT 'delete', -- delete, warn, mute or ban
+    created_by BIGINT NOT NULL,
+    PRIMARY KEY (id AUTOINCREMENT)
+);
+
+CREATE INDEX IF NOT EXISTS filters_guild_id ON filters (guild_id);
+
+-- channels and roles the word filter ignores, shared by every filter in the guild
+CREATE TABLE IF NOT EXISTS filter_exemptions (
+    guild_id BIGINT NOT NULL,
+    exempt_channels TEXT NOT NULL DEFAULT '', -- comma separated channel IDs
+    exempt_roles TEXT NOT NULL DEFAULT '', -- comma separated role IDs
+    PRIMARY KEY (guild_id)
+);
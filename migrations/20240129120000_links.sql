@@ -0,0 +1,10 @@
+-- link control settings, one row per guild
+CREATE TABLE IF NOT EXISTS link_settings (
+    guild_id BIGINT NOT NULL,
+    block_invites INTEGER NOT NULL DEFAULT 0,
+    allowed_invites TEXT NOT NULL DEFAULT '', -- comma separated invite codes that aren't blocked
+    blocked_domains TEXT NOT NULL DEFAULT '', -- comma separated domains, subdomains are blocked too
+    link_channels TEXT NOT NULL DEFAULT '', -- comma separated channel IDs links are limited to, empty for every channel
+    action TEXT NOT NULL DEFAULT 'warn', -- delete, warn, mute or ban
+    PRIMARY KEY (guild_id)
+);
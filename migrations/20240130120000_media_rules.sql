@@ -0,0 +1,8 @@
+-- what can be posted in a channel, for art, meme and discussion channels
+CREATE TABLE IF NOT EXISTS media_rules (
+    channel_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    mode TEXT, -- media-only, images-only or no-attachments, NULL to allow anything
+    max_file_size INTEGER, -- bytes per attachment, NULL for no limit
+    PRIMARY KEY (channel_id)
+);
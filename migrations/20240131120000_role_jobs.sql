@@ -0,0 +1,14 @@
+-- bulk role changes in progress, one per guild, so they can pick up where they left off after a restart
+CREATE TABLE IF NOT EXISTS role_jobs (
+    guild_id BIGINT NOT NULL,
+    role_id BIGINT NOT NULL,
+    target TEXT NOT NULL, -- all or bots
+    channel_id BIGINT NOT NULL, -- where progress is reported
+    message_id BIGINT NOT NULL,
+    requested_by BIGINT NOT NULL,
+    last_user_id BIGINT NOT NULL DEFAULT 0, -- members are walked in ID order, this is the last one done
+    processed INTEGER NOT NULL DEFAULT 0,
+    changed INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id)
+);
@@ -0,0 +1,12 @@
+-- the role members are held in by the quarantine command
+ALTER TABLE guild_settings ADD COLUMN quarantine_role_id BIGINT;
+
+-- quarantined members and the roles they had, given back when they're let out
+CREATE TABLE IF NOT EXISTS quarantined_members (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    roles TEXT NOT NULL, -- comma separated role IDs
+    moderator_id BIGINT NOT NULL,
+    quarantined_at TEXT NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
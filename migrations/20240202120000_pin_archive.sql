@@ -0,0 +1,2 @@
+-- where pins are copied to when a channel runs out of room for them, NULL to leave pins alone
+ALTER TABLE guild_settings ADD COLUMN pin_archive_channel_id BIGINT;
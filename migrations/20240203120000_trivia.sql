@@ -0,0 +1,9 @@
+-- lifetime trivia results per member, counted across every game played in the guild
+CREATE TABLE IF NOT EXISTS trivia_scores (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    points INTEGER NOT NULL DEFAULT 0,
+    correct INTEGER NOT NULL DEFAULT 0,
+    answered INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,8 @@
+-- wins and losses in the channel games, per user across every server
+CREATE TABLE IF NOT EXISTS game_stats (
+    user_id BIGINT NOT NULL,
+    game TEXT NOT NULL,
+    wins INTEGER NOT NULL DEFAULT 0,
+    losses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, game)
+);
@@ -0,0 +1,11 @@
+-- sound effect clips, saved on disk under SFX_DIRECTORY/<guild_id>/<file_name>
+CREATE TABLE IF NOT EXISTS sfx_clips (
+    guild_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    uploaded_by BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, name)
+);
@@ -0,0 +1,8 @@
+-- text-to-speech voice per guild, and the channel whose messages are read out, if any
+CREATE TABLE IF NOT EXISTS tts_settings (
+    guild_id BIGINT NOT NULL PRIMARY KEY,
+    voice TEXT, -- NULL for the engine's default voice
+    language TEXT NOT NULL DEFAULT 'en',
+    read_channel_id BIGINT,
+    voice_channel_id BIGINT
+);
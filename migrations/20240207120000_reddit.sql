@@ -0,0 +1,12 @@
+-- subreddits mirrored into channels, with how far into each feed has been posted
+CREATE TABLE IF NOT EXISTS reddit_subscriptions (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    subreddit TEXT NOT NULL,
+    sort TEXT NOT NULL DEFAULT 'new', -- 'new' or 'hot'
+    flairs TEXT, -- comma separated, lowercase; NULL posts every flair
+    last_post_created REAL NOT NULL DEFAULT 0, -- created_utc of the newest post seen
+    PRIMARY KEY (channel_id, subreddit)
+);
+
+CREATE INDEX IF NOT EXISTS reddit_subscriptions_guild ON reddit_subscriptions (guild_id);
@@ -0,0 +1,11 @@
+-- GitHub repositories whose events are posted into channels
+CREATE TABLE IF NOT EXISTS github_subscriptions (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    repository TEXT NOT NULL, -- owner/name, lowercase
+    secret TEXT NOT NULL, -- signs the repository's webhook deliveries
+    PRIMARY KEY (channel_id, repository)
+);
+
+CREATE INDEX IF NOT EXISTS github_subscriptions_repository ON github_subscriptions (repository);
+CREATE INDEX IF NOT EXISTS github_subscriptions_guild ON github_subscriptions (guild_id);
@@ -0,0 +1,5 @@
+-- the units each user sees the weather in
+CREATE TABLE IF NOT EXISTS weather_preferences (
+    user_id BIGINT NOT NULL PRIMARY KEY,
+    units TEXT NOT NULL -- 'metric' or 'imperial'
+);
@@ -0,0 +1,19 @@
+-- members' birthdays in each guild, and where and how they're celebrated
+CREATE TABLE IF NOT EXISTS birthdays (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    month INTEGER NOT NULL,
+    day INTEGER NOT NULL,
+    year INTEGER, -- only if the member gave it
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS birthdays_date ON birthdays (guild_id, month, day);
+
+CREATE TABLE IF NOT EXISTS birthday_settings (
+    guild_id BIGINT NOT NULL PRIMARY KEY,
+    channel_id BIGINT,
+    role_id BIGINT, -- given to members for the 24 hours after their birthday is announced
+    utc_offset INTEGER NOT NULL DEFAULT 0, -- seconds, the guild's birthdays start at its midnight
+    last_announced TEXT -- the guild's local date birthdays were last announced for
+);
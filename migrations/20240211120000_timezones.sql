@@ -0,0 +1,17 @@
+-- offsets from UTC that users and guilds read and give times in
+CREATE TABLE IF NOT EXISTS user_timezones (
+    user_id BIGINT NOT NULL PRIMARY KEY,
+    utc_offset INTEGER NOT NULL -- seconds
+);
+
+CREATE TABLE IF NOT EXISTS guild_timezones (
+    guild_id BIGINT NOT NULL PRIMARY KEY,
+    utc_offset INTEGER NOT NULL -- seconds
+);
+
+-- birthdays follow the guild's timezone now
+INSERT INTO guild_timezones (guild_id, utc_offset)
+    SELECT guild_id, utc_offset FROM birthday_settings WHERE utc_offset != 0
+    ON CONFLICT (guild_id) DO NOTHING;
+
+ALTER TABLE birthday_settings DROP COLUMN utc_offset;
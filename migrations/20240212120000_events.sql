@@ -0,0 +1,7 @@
+-- where a guild's Discord scheduled events are announced and reminded about
+CREATE TABLE IF NOT EXISTS event_settings (
+    guild_id BIGINT NOT NULL PRIMARY KEY,
+    channel_id BIGINT,
+    announce INTEGER NOT NULL DEFAULT 1, -- whether newly created events are announced
+    reminder_minutes INTEGER -- how long before an event starts to remind about it, none for no reminders
+);
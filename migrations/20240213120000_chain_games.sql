@@ -0,0 +1,40 @@
+-- channels where members count up together or chain words, each continuing the last message
+CREATE TABLE IF NOT EXISTS chain_games (
+    channel_id BIGINT NOT NULL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    kind TEXT NOT NULL, -- counting or words
+    streak INTEGER NOT NULL DEFAULT 0, -- the current number, or how many words the chain has
+    last_user_id BIGINT,
+    last_word TEXT,
+    record INTEGER NOT NULL DEFAULT 0
+);
+
+-- words already used in a channel's current chain, which can't be used again until it breaks
+CREATE TABLE IF NOT EXISTS chain_game_words (
+    channel_id BIGINT NOT NULL,
+    word TEXT NOT NULL,
+    PRIMARY KEY (channel_id, word)
+);
+
+-- finished streaks, for the leaderboard
+CREATE TABLE IF NOT EXISTS chain_game_streaks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    length INTEGER NOT NULL,
+    broken_by BIGINT NOT NULL,
+    ended_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS chain_game_streaks_guild ON chain_game_streaks (guild_id, kind, length);
+
+-- how many times each member continued or broke a chain
+CREATE TABLE IF NOT EXISTS chain_game_players (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    correct INTEGER NOT NULL DEFAULT 0,
+    mistakes INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id, kind)
+);
@@ -0,0 +1,17 @@
+-- memorable messages members saved, kept even if the original is deleted
+CREATE TABLE IF NOT EXISTS quotes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    author_id BIGINT NOT NULL,
+    author_name TEXT NOT NULL, -- as it was when quoted, in case they leave
+    content TEXT NOT NULL,
+    image_url TEXT,
+    link TEXT NOT NULL, -- jump link to the original message
+    quoted_by BIGINT NOT NULL,
+    sent_at TEXT NOT NULL,
+    UNIQUE (guild_id, message_id)
+);
+
+-- where new quotes are mirrored to, NULL to only keep them in the database
+ALTER TABLE guild_settings ADD COLUMN quotes_channel_id BIGINT;
@@ -0,0 +1,19 @@
+-- messages members reported to the server's staff
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    author_id BIGINT NOT NULL,
+    reporter_id BIGINT NOT NULL,
+    content TEXT NOT NULL, -- as it was when reported, in case it's edited or deleted
+    created_at TEXT NOT NULL,
+    message_deleted INTEGER NOT NULL DEFAULT 0,
+    outcome TEXT, -- warned, banned or dismissed once a moderator closes the report
+    handled_by BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS reports_message ON reports (guild_id, message_id, reporter_id);
+
+-- where reports are sent, NULL if the guild doesn't take them
+ALTER TABLE guild_settings ADD COLUMN reports_channel_id BIGINT;
@@ -0,0 +1,11 @@
+-- free-form notes staff keep about members, only shown to moderators
+CREATE TABLE IF NOT EXISTS mod_notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS mod_notes_user ON mod_notes (guild_id, user_id);
@@ -0,0 +1,19 @@
+-- accounts staff want to keep an eye on, alerted about in the watchlist channel
+CREATE TABLE IF NOT EXISTS watchlist (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    moderator_id BIGINT NOT NULL,
+    reason TEXT,
+    created_at TEXT NOT NULL,
+    expires_at TEXT, -- NULL until they're unwatched by hand
+    PRIMARY KEY (guild_id, user_id)
+);
+
+-- channels where watched users posting is alerted about
+CREATE TABLE IF NOT EXISTS watchlist_channels (
+    channel_id BIGINT PRIMARY KEY,
+    guild_id BIGINT NOT NULL
+);
+
+-- where watchlist alerts are posted, NULL to not alert at all
+ALTER TABLE guild_settings ADD COLUMN watchlist_channel_id BIGINT;
@@ -0,0 +1,9 @@
+-- users and guilds the bot ignores everywhere, managed by the bot's owners
+CREATE TABLE IF NOT EXISTS blacklist (
+    kind TEXT NOT NULL, -- user or guild
+    id BIGINT NOT NULL,
+    reason TEXT,
+    added_by BIGINT NOT NULL,
+    added_at TEXT NOT NULL,
+    PRIMARY KEY (kind, id)
+);
@@ -0,0 +1,2 @@
+-- the language the bot answers in, as a code from the catalogs in `locales`
+ALTER TABLE guild_settings ADD COLUMN language TEXT NOT NULL DEFAULT 'en';
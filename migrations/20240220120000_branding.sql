@@ -0,0 +1,4 @@
+-- how the bot's embeds look in a guild, the default color and no footer or thumbnail when unset
+ALTER TABLE guild_settings ADD COLUMN embed_color INTEGER;
+ALTER TABLE guild_settings ADD COLUMN embed_footer TEXT;
+ALTER TABLE guild_settings ADD COLUMN embed_thumbnail TEXT;
@@ -0,0 +1,4 @@
+-- timezones can be named zones from the IANA database, which follow summer time. utc_offset is
+-- kept up to date alongside, and is what's used when the zone isn't named.
+ALTER TABLE user_timezones ADD COLUMN timezone TEXT;
+ALTER TABLE guild_timezones ADD COLUMN timezone TEXT;
@@ -0,0 +1,42 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::afk::{AfkStatus, set_afk};
+
+const MAX_REASON_LENGTH: usize = 200;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Marks you as AFK in this server. Anyone who mentions you is told you're away, and why, until you next send a message here."]
+#[usage = "[reason]"]
+#[example = "Lunch, back in 30 minutes"]
+async fn afk(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+
+    if reason.is_some_and(|reason| reason.chars().count() > MAX_REASON_LENGTH) {
+        msg.reply(ctx, format!("AFK reasons can be at most {MAX_REASON_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    // statuses are cleared by messages sent after the one setting them
+    let status = AfkStatus { reason: reason.map(str::to_string), since: *msg.timestamp };
+
+    set_afk(ctx, msg.guild_id.unwrap(), msg.author.id, status).await?;
+
+    let reply = match reason {
+        Some(reason) => format!("You're now AFK: {reason}"),
+        None => "You're now AFK.".to_string()
+    };
+
+    let builder = CreateMessage::new()
+        .content(reply)
+        .allowed_mentions(CreateAllowedMentions::new())
+        .reference_message(msg);
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
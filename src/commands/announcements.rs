@@ -0,0 +1,273 @@
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::Utc;
+use tracing::warn;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::{DEFAULT_EMBED_COLOR, branded_embed};
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::incidents::{IncidentStatus, declare_incident, add_incident_update, open_incident};
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows which channel receives bot status updates such as incidents and changelogs."]
+#[sub_commands(updates_subscribe, updates_unsubscribe)]
+async fn updates(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let channel = sqlx::query!(
+        "SELECT updates_channel_id FROM guild_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(&database).await?.and_then(|row| row.updates_channel_id);
+
+    let description = match channel {
+        Some(channel_id) => format!("Bot status updates are posted in <#{channel_id}>."),
+        None => "This server isn't subscribed to bot status updates.".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Status Updates")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("subscribe")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts bot status updates into the given channel."]
+#[usage = "<#channel>"]
+#[num_args(1)]
+async fn updates_subscribe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    set_updates_channel(ctx, msg.guild_id.unwrap(), Some(channel_id)).await?;
+
+    msg.reply(ctx, format!("Bot status updates will now be posted in <#{channel_id}>.")).await?;
+
+    Ok(())
+}
+
+#[command("unsubscribe")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Stops posting bot status updates in this server."]
+async fn updates_unsubscribe(ctx: &Context, msg: &Message) -> CommandResult {
+    set_updates_channel(ctx, msg.guild_id.unwrap(), None).await?;
+
+    msg.reply(ctx, "This server will no longer receive bot status updates.").await?;
+
+    Ok(())
+}
+
+async fn set_updates_channel(ctx: &Context, guild_id: GuildId, channel_id: Option<ChannelId>) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let guild_id = guild_id.get() as i64;
+    let channel_id = channel_id.map(|id| id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET updates_channel_id = ? WHERE guild_id = ?",
+        channel_id,
+        guild_id
+    ).execute(&database).await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Manages incident announcements posted to every subscribed server."]
+#[sub_commands(incident_declare, incident_update, incident_resolve)]
+async fn incident(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let response = match open_incident(&database, false).await? {
+        Some(incident_id) => format!("Incident #{incident_id} is currently open."),
+        None => "There are no open incidents.".to_string()
+    };
+
+    msg.reply(ctx, response).await?;
+
+    Ok(())
+}
+
+#[command("declare")]
+#[owners_only]
+#[description = "Declares a new incident and announces it to every subscribed server."]
+#[usage = "<title> | <message>"]
+#[min_args(1)]
+async fn incident_declare(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((title, message)) = args.rest().split_once('|') else {
+        msg.reply(ctx, "Please separate the title and the message with `|`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let incident_id = declare_incident(&ctx.http, &database, title.trim(), message.trim(), false).await?;
+
+    msg.reply(ctx, format!("Declared incident #{incident_id}.")).await?;
+
+    Ok(())
+}
+
+#[command("update")]
+#[owners_only]
+#[description = "Posts a status update on the open incident. Status is one of investigating, identified, monitoring or resolved."]
+#[usage = "<status> <message>"]
+#[min_args(2)]
+async fn incident_update(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(status) = IncidentStatus::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "The status must be one of `investigating`, `identified`, `monitoring` or `resolved`.").await?;
+        return Ok(());
+    };
+
+    update_open_incident(ctx, msg, status, args.rest()).await
+}
+
+#[command("resolve")]
+#[owners_only]
+#[description = "Marks the open incident as resolved."]
+#[usage = "[message]"]
+async fn incident_resolve(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let message = match args.rest() {
+        "" => "This incident has been resolved.",
+        message => message
+    };
+
+    update_open_incident(ctx, msg, IncidentStatus::Resolved, message).await
+}
+
+async fn update_open_incident(ctx: &Context, msg: &Message, status: IncidentStatus, message: &str) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(incident_id) = open_incident(&database, false).await? else {
+        msg.reply(ctx, "There are no open incidents.").await?;
+        return Ok(());
+    };
+
+    add_incident_update(&ctx.http, &database, incident_id, status, message).await?;
+
+    msg.reply(ctx, format!("Updated incident #{incident_id}.")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the latest changelog. Servers can subscribe to have new changelogs posted automatically."]
+#[sub_commands(changelog_latest, changelog_subscribe, changelog_publish)]
+async fn changelog(ctx: &Context, msg: &Message) -> CommandResult {
+    send_latest_changelog(ctx, msg).await
+}
+
+#[command("latest")]
+#[description = "Shows the latest changelog along with the previous versions."]
+async fn changelog_latest(ctx: &Context, msg: &Message) -> CommandResult {
+    send_latest_changelog(ctx, msg).await
+}
+
+#[command("subscribe")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts new changelogs (and other bot status updates) into the given channel."]
+#[usage = "<#channel>"]
+#[num_args(1)]
+async fn changelog_subscribe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    set_updates_channel(ctx, msg.guild_id.unwrap(), Some(channel_id)).await?;
+
+    msg.reply(ctx, format!("New changelogs will now be posted in <#{channel_id}>.")).await?;
+
+    Ok(())
+}
+
+#[command("publish")]
+#[owners_only]
+#[description = "Stores a changelog and pushes it to every subscribed server."]
+#[usage = "<version> <notes>"]
+#[min_args(2)]
+async fn changelog_publish(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let version = args.single::<String>()?;
+    let notes = args.rest().to_string();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let published_by = msg.author.id.get() as i64;
+    let published_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO changelogs (version, notes, published_by, published_at) VALUES (?, ?, ?, ?)",
+        version,
+        notes,
+        published_by,
+        published_at
+    ).execute(&database).await?;
+
+    let subscribers = sqlx::query!(
+        "SELECT guild_id, updates_channel_id FROM guild_settings WHERE updates_channel_id IS NOT NULL"
+    ).fetch_all(&database).await?;
+
+    let embed = changelog_embed(&version, &notes);
+    let mut delivered = 0;
+
+    for subscriber in &subscribers {
+        let Some(channel_id) = subscriber.updates_channel_id else {
+            continue;
+        };
+
+        let channel = ChannelId::new(channel_id as u64);
+
+        match channel.send_message(ctx, CreateMessage::new().embed(embed.clone())).await {
+            Ok(_) => delivered += 1,
+            Err(err) => warn!("Couldn't post changelog {version} in guild {}: {err}", subscriber.guild_id)
+        }
+    }
+
+    msg.reply(ctx, format!("Published changelog `{version}` to {delivered}/{} subscribed server(s).", subscribers.len())).await?;
+
+    Ok(())
+}
+
+async fn send_latest_changelog(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let changelogs = sqlx::query!(
+        "SELECT version, notes, published_at FROM changelogs ORDER BY id DESC LIMIT 6"
+    ).fetch_all(&database).await?;
+
+    let Some(latest) = changelogs.first() else {
+        msg.reply(ctx, "No changelogs have been published yet.").await?;
+        return Ok(());
+    };
+
+    let mut embed = changelog_embed(&latest.version, &latest.notes);
+
+    if changelogs.len() > 1 {
+        let previous = changelogs[1..].iter()
+            .map(|changelog| format!("`{}` - {}", changelog.version, changelog.published_at.get(..10).unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        embed = embed.field("Previous versions", previous, false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn changelog_embed(version: &str, notes: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .color(DEFAULT_EMBED_COLOR)
+        .title(format!("Changelog - {version}"))
+        .description(notes)
+        .footer(CreateEmbedFooter::new("Subscribe with the changelog subscribe command to receive these automatically."))
+}
@@ -0,0 +1,220 @@
+use chrono::Duration;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::antiraid::{RaidAction, RaidMode, reload_antiraid};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{AntiraidContainer, DatabaseConnectionContainer};
+use crate::utilities::parsing::{format_duration, parse_duration};
+use crate::utilities::quarantine::quarantine_role;
+
+/// Longest window joins can be counted over.
+const MAX_WINDOW_SECONDS: u32 = 300;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's anti-raid settings. While raid mode is on, new accounts younger than the minimum age are kicked or quarantined. In `auto` mode, raid mode turns itself on for 10 minutes when too many members join too quickly, and alerts the modlog channel."]
+#[sub_commands(raidmode_on, raidmode_off, raidmode_auto, raidmode_joins, raidmode_age, raidmode_action)]
+async fn raidmode(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let state = get_data::<AntiraidContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT mode, max_joins, window_seconds, min_account_age, action FROM antiraid_settings WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(&database).await?;
+
+    let raid_mode = state.lock().await.is_raid_mode(guild_id);
+
+    let description = match settings {
+        Some(row) => format!(
+            "Anti-raid is set to **{}**, and raid mode is currently **{}**.\n\nMore than **{}** joins in **{} seconds** count as a raid. During a raid, accounts younger than **{}** are **{}**.",
+            row.mode,
+            if raid_mode { "on" } else { "off" },
+            row.max_joins,
+            row.window_seconds,
+            format_duration(Duration::seconds(row.min_account_age)),
+            RaidAction::parse(&row.action).map_or_else(|| row.action.clone(), |action| action.past_tense().to_string())
+        ),
+        None => "Anti-raid is **off**. Use `raidmode auto` to detect raids, or `raidmode on` to turn raid mode on now.".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Anti-raid")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("on")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns raid mode on until it's turned off again."]
+#[num_args(0)]
+async fn raidmode_on(ctx: &Context, msg: &Message) -> CommandResult {
+    set_mode(ctx, msg, RaidMode::On).await
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns raid mode and raid detection off."]
+#[num_args(0)]
+async fn raidmode_off(ctx: &Context, msg: &Message) -> CommandResult {
+    set_mode(ctx, msg, RaidMode::Off).await
+}
+
+#[command("auto")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns raid mode on automatically whenever a raid is detected."]
+#[num_args(0)]
+async fn raidmode_auto(ctx: &Context, msg: &Message) -> CommandResult {
+    set_mode(ctx, msg, RaidMode::Auto).await
+}
+
+async fn set_mode(ctx: &Context, msg: &Message, mode: RaidMode) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let mode_name = mode.as_str();
+
+    sqlx::query!(
+        "INSERT INTO antiraid_settings (guild_id, mode) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET mode = excluded.mode",
+        db_guild_id,
+        mode_name
+    ).execute(&database).await?;
+
+    reload_antiraid(ctx, guild_id).await?;
+
+    let reply = match mode {
+        RaidMode::On => "Raid mode is now on. Use `raidmode off` or `raidmode auto` once the raid is over.",
+        RaidMode::Off => "Raid mode and raid detection are now off.",
+        RaidMode::Auto => "Raid mode will now turn on automatically when a raid is detected."
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("joins")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how many members can join within a number of seconds before it counts as a raid."]
+#[usage = "<joins> <seconds>"]
+#[example = "10 10"]
+#[num_args(2)]
+async fn raidmode_joins(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(max_joins) = args.single::<u32>().ok().filter(|count| *count > 0) else {
+        msg.reply(ctx, "Please give the number of members that can join.").await?;
+        return Ok(());
+    };
+
+    let Some(window_seconds) = args.single::<u32>().ok().filter(|seconds| (1..=MAX_WINDOW_SECONDS).contains(seconds)) else {
+        msg.reply(ctx, format!("Please give the number of seconds to count joins over, up to {MAX_WINDOW_SECONDS}.")).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO antiraid_settings (guild_id, max_joins, window_seconds) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET max_joins = excluded.max_joins, window_seconds = excluded.window_seconds",
+        db_guild_id,
+        max_joins,
+        window_seconds
+    ).execute(&database).await?;
+
+    reload_antiraid(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("More than {max_joins} joins in {window_seconds} seconds will now count as a raid.")).await?;
+
+    Ok(())
+}
+
+#[command("age")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how old accounts have to be to join during a raid."]
+#[usage = "<duration>"]
+#[example = "7d"]
+#[num_args(1)]
+async fn raidmode_age(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(age) = parse_duration(&args.single::<String>()?).filter(|duration| *duration > Duration::zero()) else {
+        msg.reply(ctx, "Please give a duration, like `12h`, `7d` or `30d`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let min_account_age = age.num_seconds();
+
+    sqlx::query!(
+        "INSERT INTO antiraid_settings (guild_id, min_account_age) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET min_account_age = excluded.min_account_age",
+        db_guild_id,
+        min_account_age
+    ).execute(&database).await?;
+
+    reload_antiraid(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("During a raid, accounts younger than {} will now be acted on.", format_duration(age))).await?;
+
+    Ok(())
+}
+
+#[command("action")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether young accounts joining during a raid are kicked, or quarantined with the server's quarantine role until a moderator runs `unquarantine`. Accounts are kicked instead while no quarantine role is set."]
+#[usage = "<kick|quarantine>"]
+#[example = "quarantine"]
+#[num_args(1)]
+async fn raidmode_action(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(action) = RaidAction::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "Young accounts can be `kick`ed or put in `quarantine`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let action_name = action.as_str();
+
+    sqlx::query!(
+        "INSERT INTO antiraid_settings (guild_id, action) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET action = excluded.action",
+        db_guild_id,
+        action_name
+    ).execute(&database).await?;
+
+    reload_antiraid(ctx, guild_id).await?;
+
+    let mut reply = format!("Young accounts joining during a raid will now be {}.", action.past_tense());
+
+    if action == RaidAction::Quarantine && quarantine_role(&database, guild_id).await?.is_none() {
+        reply.push_str(" This server has no quarantine role yet, so they'll be kicked until one is set with `quarantine role <role>`.");
+    }
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use chrono::Duration;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::antispam::reload_antispam;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::moderation::MAX_TIMEOUT;
+use crate::utilities::parsing::{format_duration, parse_duration};
+
+/// Longest window messages can be counted over.
+const MAX_WINDOW_SECONDS: u32 = 60;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's anti-spam settings. Members sending too many messages too quickly, or the same message over and over, have their messages deleted and are timed out."]
+#[sub_commands(antispam_on, antispam_off, antispam_messages, antispam_duplicates, antispam_timeout)]
+async fn antispam(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT enabled, max_messages, window_seconds, max_duplicates, timeout_seconds FROM antispam_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(&database).await?;
+
+    let description = match settings {
+        Some(row) => format!(
+            "Anti-spam is **{}**.\n\nMore than **{}** messages in **{} seconds**, or the same message more than **{}** times in 30 seconds, times members out for **{}**.",
+            if row.enabled != 0 { "on" } else { "off" },
+            row.max_messages,
+            row.window_seconds,
+            row.max_duplicates,
+            format_duration(Duration::seconds(row.timeout_seconds))
+        ),
+        None => "Anti-spam is **off**. Use `antispam on` to turn it on.".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Anti-spam")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("on")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns anti-spam on."]
+#[num_args(0)]
+async fn antispam_on(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, msg, true).await
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns anti-spam off."]
+#[num_args(0)]
+async fn antispam_off(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, msg, false).await
+}
+
+async fn set_enabled(ctx: &Context, msg: &Message, enabled: bool) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO antispam_settings (guild_id, enabled) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET enabled = excluded.enabled",
+        db_guild_id,
+        enabled
+    ).execute(&database).await?;
+
+    reload_antispam(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Anti-spam is now {}.", if enabled { "on" } else { "off" })).await?;
+
+    Ok(())
+}
+
+#[command("messages")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how many messages members can send within a number of seconds before they're timed out."]
+#[usage = "<messages> <seconds>"]
+#[example = "6 5"]
+#[num_args(2)]
+async fn antispam_messages(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(max_messages) = args.single::<u32>().ok().filter(|count| *count > 0) else {
+        msg.reply(ctx, "Please give the number of messages members can send.").await?;
+        return Ok(());
+    };
+
+    let Some(window_seconds) = args.single::<u32>().ok().filter(|seconds| (1..=MAX_WINDOW_SECONDS).contains(seconds)) else {
+        msg.reply(ctx, format!("Please give the number of seconds to count messages over, up to {MAX_WINDOW_SECONDS}.")).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    // configuring a server for the first time doesn't turn anti-spam on by itself
+    sqlx::query!(
+        "INSERT INTO antispam_settings (guild_id, enabled, max_messages, window_seconds) VALUES (?, 0, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET max_messages = excluded.max_messages, window_seconds = excluded.window_seconds",
+        db_guild_id,
+        max_messages,
+        window_seconds
+    ).execute(&database).await?;
+
+    reload_antispam(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Members sending more than {max_messages} messages in {window_seconds} seconds will now be timed out.")).await?;
+
+    Ok(())
+}
+
+#[command("duplicates")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how many times members can send the same message within 30 seconds before they're timed out."]
+#[usage = "<messages>"]
+#[example = "3"]
+#[num_args(1)]
+async fn antispam_duplicates(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(max_duplicates) = args.single::<u32>().ok().filter(|count| *count > 0) else {
+        msg.reply(ctx, "Please give the number of identical messages members can send.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO antispam_settings (guild_id, enabled, max_duplicates) VALUES (?, 0, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET max_duplicates = excluded.max_duplicates",
+        db_guild_id,
+        max_duplicates
+    ).execute(&database).await?;
+
+    reload_antispam(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Members sending the same message more than {max_duplicates} times will now be timed out.")).await?;
+
+    Ok(())
+}
+
+#[command("timeout")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how long spammers are timed out for."]
+#[usage = "<duration>"]
+#[example = "10m"]
+#[num_args(1)]
+async fn antispam_timeout(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(timeout) = parse_duration(&args.single::<String>()?).filter(|duration| *duration > Duration::zero() && *duration <= MAX_TIMEOUT) else {
+        msg.reply(ctx, format!("Please give a duration of at most {}, like `10m`, `2h` or `3d`.", format_duration(MAX_TIMEOUT))).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let timeout_seconds = timeout.num_seconds();
+
+    sqlx::query!(
+        "INSERT INTO antispam_settings (guild_id, enabled, timeout_seconds) VALUES (?, 0, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET timeout_seconds = excluded.timeout_seconds",
+        db_guild_id,
+        timeout_seconds
+    ).execute(&database).await?;
+
+    reload_antispam(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Spammers will now be timed out for {}.", format_duration(timeout))).await?;
+
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::{parse_channel_mention, parse_role_mention};
+
+use crate::utilities::automod::{AutomodAction, RuleKind, reload_automod_rules};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most banned words a guild's `words` rule can hold.
+const MAX_BANNED_WORDS: usize = 100;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's automod rules. Rules are `words` (banned words), `invites` (invite links), `mentions` (mass mentions), `caps` (excessive capitals) and `zalgo`, and each one can `delete` the message, or also `warn`, `mute` or `ban` the author."]
+#[sub_commands(automod_enable, automod_disable, automod_action, automod_threshold, automod_words, automod_exempt)]
+async fn automod(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let rules = sqlx::query!(
+        "SELECT kind, action, words, threshold, exempt_channels, exempt_roles FROM automod_rules WHERE guild_id = ? ORDER BY kind",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if rules.is_empty() {
+        "This server has no automod rules yet. Use `automod enable <rule>` to add one.".to_string()
+    } else {
+        rules.iter()
+            .map(|row| {
+                let setting = match RuleKind::parse(&row.kind) {
+                    Some(RuleKind::Words) => format!("{} banned words", row.words.split(',').filter(|word| !word.is_empty()).count()),
+                    Some(kind) => kind.threshold_unit().map_or_else(String::new, |unit| format!("{} {unit}", row.threshold)),
+                    None => String::new()
+                };
+
+                let exemptions = row.exempt_channels.split(',')
+                    .filter_map(|id| id.parse::<u64>().ok().filter(|id| *id != 0))
+                    .map(|id| ChannelId::new(id).mention().to_string())
+                    .chain(row.exempt_roles.split(',')
+                        .filter_map(|id| id.parse::<u64>().ok().filter(|id| *id != 0))
+                        .map(|id| RoleId::new(id).mention().to_string()))
+                    .collect::<Vec<_>>();
+
+                let exemptions = if exemptions.is_empty() { "no exemptions".to_string() } else { format!("except {}", exemptions.join(" ")) };
+
+                format!("**{}** → {} ({setting})\n-# {exemptions}", row.kind, row.action)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Automod rules")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("enable")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns on an automod rule, optionally with its action (`delete` by default)."]
+#[usage = "<rule> [delete|warn|mute|ban]"]
+#[example = "invites warn"]
+#[min_args(1)]
+#[max_args(2)]
+async fn automod_enable(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = parse_kind(ctx, msg, &args.single::<String>()?).await? else {
+        return Ok(());
+    };
+
+    let action = match args.single::<String>().ok() {
+        Some(action) => match AutomodAction::parse(&action) {
+            Some(action) => action,
+            None => {
+                msg.reply(ctx, "Actions are `delete`, `warn`, `mute` and `ban`.").await?;
+                return Ok(());
+            }
+        },
+        None => AutomodAction::Delete
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let (kind_name, action_name) = (kind.as_str(), action.as_str());
+    let threshold = kind.default_threshold().unwrap_or(0);
+
+    sqlx::query!(
+        "INSERT INTO automod_rules (guild_id, kind, action, threshold) VALUES (?, ?, ?, ?)
+        ON CONFLICT (guild_id, kind) DO UPDATE SET action = excluded.action",
+        db_guild_id,
+        kind_name,
+        action_name,
+        threshold
+    ).execute(&database).await?;
+
+    reload_automod_rules(ctx, guild_id).await?;
+
+    let hint = match kind {
+        RuleKind::Words => " Use `automod words <words...>` to set the banned words.",
+        _ => ""
+    };
+
+    msg.reply(ctx, format!("Enabled the `{kind_name}` rule, which will {action_name} breaking messages.{hint}")).await?;
+
+    Ok(())
+}
+
+#[command("disable")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns off an automod rule, forgetting its settings."]
+#[usage = "<rule>"]
+#[num_args(1)]
+async fn automod_disable(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = parse_kind(ctx, msg, &args.single::<String>()?).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let kind_name = kind.as_str();
+
+    let deleted = sqlx::query!(
+        "DELETE FROM automod_rules WHERE guild_id = ? AND kind = ?",
+        db_guild_id,
+        kind_name
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("The `{kind_name}` rule isn't enabled.")).await?;
+        return Ok(());
+    }
+
+    reload_automod_rules(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Disabled the `{kind_name}` rule.")).await?;
+
+    Ok(())
+}
+
+#[command("action")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Changes what an automod rule does to breaking messages: `delete` them, or also `warn`, `mute` (for 10 minutes) or `ban` their author."]
+#[usage = "<rule> <delete|warn|mute|ban>"]
+#[example = "caps warn"]
+#[num_args(2)]
+async fn automod_action(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = parse_kind(ctx, msg, &args.single::<String>()?).await? else {
+        return Ok(());
+    };
+
+    let Some(action) = AutomodAction::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "Actions are `delete`, `warn`, `mute` and `ban`.").await?;
+        return Ok(());
+    };
+
+    let action_name = action.as_str();
+    update_rule(ctx, msg, kind, "action", |guild_id, kind_name, database| async move {
+        sqlx::query!(
+            "UPDATE automod_rules SET action = ? WHERE guild_id = ? AND kind = ?",
+            action_name,
+            guild_id,
+            kind_name
+        ).execute(&database).await.map(|result| result.rows_affected())
+    }).await
+}
+
+#[command("threshold")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Changes when the `mentions` (mentions per message), `caps` (percent capital letters) or `zalgo` (combining marks per message) rule is broken."]
+#[usage = "<rule> <number>"]
+#[example = "mentions 8"]
+#[num_args(2)]
+async fn automod_threshold(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = parse_kind(ctx, msg, &args.single::<String>()?).await? else {
+        return Ok(());
+    };
+
+    if kind.threshold_unit().is_none() {
+        msg.reply(ctx, format!("The `{}` rule has no threshold.", kind.as_str())).await?;
+        return Ok(());
+    }
+
+    let Some(threshold) = args.single::<u32>().ok().filter(|threshold| *threshold > 0 && (kind != RuleKind::Caps || *threshold <= 100)) else {
+        msg.reply(ctx, "Please give a positive number, at most 100 for `caps`.").await?;
+        return Ok(());
+    };
+
+    let threshold = i64::from(threshold);
+    update_rule(ctx, msg, kind, "threshold", |guild_id, kind_name, database| async move {
+        sqlx::query!(
+            "UPDATE automod_rules SET threshold = ? WHERE guild_id = ? AND kind = ?",
+            threshold,
+            guild_id,
+            kind_name
+        ).execute(&database).await.map(|result| result.rows_affected())
+    }).await
+}
+
+#[command("words")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Replaces the banned words of the `words` rule. Words are matched whole and ignoring case."]
+#[usage = "<words...>"]
+#[example = "heck dang"]
+#[min_args(1)]
+async fn automod_words(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let mut words: Vec<String> = args.rest()
+        .split(|character: char| character == ',' || character.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    words.sort();
+    words.dedup();
+
+    if words.len() > MAX_BANNED_WORDS {
+        msg.reply(ctx, format!("The `words` rule can hold at most {MAX_BANNED_WORDS} words.")).await?;
+        return Ok(());
+    }
+
+    let words = words.join(",");
+    update_rule(ctx, msg, RuleKind::Words, "banned words", |guild_id, kind_name, database| async move {
+        sqlx::query!(
+            "UPDATE automod_rules SET words = ? WHERE guild_id = ? AND kind = ?",
+            words,
+            guild_id,
+            kind_name
+        ).execute(&database).await.map(|result| result.rows_affected())
+    }).await
+}
+
+#[command("exempt")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channels and roles an automod rule ignores, or `none` to apply it everywhere."]
+#[usage = "<rule> <channels and roles...|none>"]
+#[example = "caps #memes @Moderators"]
+#[min_args(2)]
+async fn automod_exempt(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = parse_kind(ctx, msg, &args.single::<String>()?).await? else {
+        return Ok(());
+    };
+
+    let mut channels = Vec::new();
+    let mut roles = Vec::new();
+
+    if !args.rest().trim().eq_ignore_ascii_case("none") {
+        for mention in args.rest().split_whitespace() {
+            if let Some(channel_id) = parse_channel_mention(mention) {
+                channels.push(channel_id.to_string());
+            } else if let Some(role_id) = parse_role_mention(mention) {
+                roles.push(role_id.to_string());
+            } else {
+                msg.reply(ctx, format!("`{mention}` isn't a channel or role mention.")).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let (channels, roles) = (channels.join(","), roles.join(","));
+    update_rule(ctx, msg, kind, "exemptions", |guild_id, kind_name, database| async move {
+        sqlx::query!(
+            "UPDATE automod_rules SET exempt_channels = ?, exempt_roles = ? WHERE guild_id = ? AND kind = ?",
+            channels,
+            roles,
+            guild_id,
+            kind_name
+        ).execute(&database).await.map(|result| result.rows_affected())
+    }).await
+}
+
+/// Parses a rule name, replying with the available rules if it isn't one.
+async fn parse_kind(ctx: &Context, msg: &Message, kind: &str) -> Result<Option<RuleKind>, serenity::Error> {
+    let parsed = RuleKind::parse(kind);
+
+    if parsed.is_none() {
+        let kinds = RuleKind::ALL.iter().map(|kind| format!("`{}`", kind.as_str())).collect::<Vec<_>>().join(", ");
+        msg.reply(ctx, format!("`{kind}` isn't an automod rule. Available rules are {kinds}.")).await?;
+    }
+
+    Ok(parsed)
+}
+
+/// Runs an update against an enabled rule, then reloads the guild's rules and reports back.
+async fn update_rule<F, Fut>(ctx: &Context, msg: &Message, kind: RuleKind, field: &str, update: F) -> CommandResult
+where
+    F: FnOnce(i64, &'static str, sqlx::SqlitePool) -> Fut,
+    Fut: std::future::Future<Output = Result<u64, sqlx::Error>>
+{
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let kind_name = kind.as_str();
+
+    if update(guild_id.get() as i64, kind_name, database).await? == 0 {
+        msg.reply(ctx, format!("The `{kind_name}` rule isn't enabled. Use `automod enable {kind_name}` first.")).await?;
+        return Ok(());
+    }
+
+    reload_automod_rules(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Updated the {field} of the `{kind_name}` rule.")).await?;
+
+    Ok(())
+}
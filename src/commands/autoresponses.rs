@@ -0,0 +1,256 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::autoresponses::{MatchMode, reload_auto_responses};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::parse_channel;
+
+/// Most auto-responses a single guild can have.
+const MAX_AUTO_RESPONSES: i32 = 50;
+
+#[command]
+#[aliases("ar")]
+#[only_in(guilds)]
+#[description = "Lists this server's auto-responses. Auto-responses reply to messages matching a trigger, and their responses are templates (see `template`)."]
+#[sub_commands(autoresponse_add, autoresponse_edit, autoresponse_delete, autoresponse_list)]
+async fn autoresponse(ctx: &Context, msg: &Message) -> CommandResult {
+    list_auto_responses(ctx, msg).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Adds an auto-response. Match modes are `exact`, `contains` (the default), `regex` and `wildcard` (`*` and `?`). Quote triggers with spaces."]
+#[usage = "[mode] <trigger> <response>"]
+#[example = "wildcard \"good morning*\" Good morning {user.mention}!"]
+#[min_args(2)]
+async fn autoresponse_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let first = args.single_quoted::<String>()?;
+
+    // the mode is optional, a lone mode name followed by a response is treated as the trigger
+    let (mode, pattern) = match MatchMode::parse(&first) {
+        Some(mode) if args.remaining() >= 2 => (mode, args.single_quoted::<String>()?),
+        _ => (MatchMode::Contains, first)
+    };
+
+    let response = args.rest().trim().to_string();
+
+    if response.is_empty() {
+        msg.reply(ctx, "Please give a response for the trigger.").await?;
+        return Ok(());
+    }
+
+    if let Err(why) = mode.compile(&pattern) {
+        msg.reply(ctx, format!("That trigger isn't valid:\n```{why}```")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM auto_responses WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_AUTO_RESPONSES {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_AUTO_RESPONSES} auto-responses.")).await?;
+        return Ok(());
+    }
+
+    let mode_name = mode.as_str();
+    let created_by = msg.author.id.get() as i64;
+
+    let id = sqlx::query!(
+        "INSERT INTO auto_responses (guild_id, pattern, match_mode, response, created_by) VALUES (?, ?, ?, ?, ?)",
+        db_guild_id,
+        pattern,
+        mode_name,
+        response,
+        created_by
+    ).execute(&database).await?.last_insert_rowid();
+
+    reload_auto_responses(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Added auto-response #{id}, replying to messages that match `{pattern}` ({mode_name}).")).await?;
+
+    Ok(())
+}
+
+#[command("edit")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Changes part of an auto-response: its `trigger`, `response`, `mode`, `cooldown` (in seconds) or `channels` (`all` for every channel)."]
+#[usage = "<id> <trigger|response|mode|cooldown|channels> <value>"]
+#[example = "3 channels #general #memes"]
+#[min_args(3)]
+async fn autoresponse_edit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+    let field = args.single::<String>()?.to_lowercase();
+    let value = args.rest().trim();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let Some(mut row) = sqlx::query!(
+        "SELECT pattern, match_mode, response, cooldown, channels FROM auto_responses WHERE id = ? AND guild_id = ?",
+        id,
+        db_guild_id
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, format!("There is no auto-response #{id}.")).await?;
+        return Ok(());
+    };
+
+    match field.as_str() {
+        "trigger" => row.pattern = value.trim_matches('"').to_string(),
+        "response" => row.response = value.to_string(),
+        "mode" => match MatchMode::parse(value) {
+            Some(mode) => row.match_mode = mode.as_str().to_string(),
+            None => {
+                msg.reply(ctx, "Match modes are `exact`, `contains`, `regex` and `wildcard`.").await?;
+                return Ok(());
+            }
+        },
+        "cooldown" => match value.parse::<u32>() {
+            Ok(seconds) => row.cooldown = i64::from(seconds),
+            Err(_) => {
+                msg.reply(ctx, "Please give the cooldown in seconds.").await?;
+                return Ok(());
+            }
+        },
+        "channels" if value.eq_ignore_ascii_case("all") => row.channels = String::new(),
+        "channels" => {
+            let channels: Option<Vec<String>> = value.split_whitespace()
+                .map(|channel| parse_channel(channel).map(|id| id.to_string()))
+                .collect();
+
+            match channels {
+                Some(channels) => row.channels = channels.join(","),
+                None => {
+                    msg.reply(ctx, "Please mention the channels to respond in, or use `all`.").await?;
+                    return Ok(());
+                }
+            }
+        }
+        _ => {
+            msg.reply(ctx, "You can edit an auto-response's `trigger`, `response`, `mode`, `cooldown` or `channels`.").await?;
+            return Ok(());
+        }
+    }
+
+    // a new trigger or mode has to still compile together
+    if let Err(why) = MatchMode::parse(&row.match_mode).unwrap_or(MatchMode::Contains).compile(&row.pattern) {
+        msg.reply(ctx, format!("That trigger isn't valid:\n```{why}```")).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "UPDATE auto_responses SET pattern = ?, match_mode = ?, response = ?, cooldown = ?, channels = ? WHERE id = ?",
+        row.pattern,
+        row.match_mode,
+        row.response,
+        row.cooldown,
+        row.channels,
+        id
+    ).execute(&database).await?;
+
+    reload_auto_responses(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Updated the {field} of auto-response #{id}.")).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Deletes an auto-response."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn autoresponse_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM auto_responses WHERE id = ? AND guild_id = ?",
+        id,
+        db_guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no auto-response #{id}.")).await?;
+        return Ok(());
+    }
+
+    reload_auto_responses(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Deleted auto-response #{id}.")).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's auto-responses."]
+async fn autoresponse_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_auto_responses(ctx, msg).await
+}
+
+async fn list_auto_responses(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let responses = sqlx::query!(
+        "SELECT id, pattern, match_mode, response, cooldown, channels FROM auto_responses WHERE guild_id = ? ORDER BY id",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if responses.is_empty() {
+        "This server has no auto-responses yet.".to_string()
+    } else {
+        responses.iter()
+            .map(|row| {
+                let channels = if row.channels.is_empty() {
+                    "all channels".to_string()
+                } else {
+                    row.channels.split(',')
+                        .filter_map(|channel| channel.parse::<u64>().ok().filter(|id| *id != 0))
+                        .map(|channel| ChannelId::new(channel).mention().to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+
+                // keep long responses from pushing the list past the embed limit
+                let response: String = row.response.chars().take(80).collect();
+
+                format!(
+                    "**#{}** `{}` ({}) → {response}\n-# {}s cooldown, {channels}",
+                    row.id, row.pattern, row.match_mode, row.cooldown
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Auto-responses")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most autoroles a single guild can have.
+const MAX_AUTOROLES: i32 = 10;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists the roles new members are given when they join."]
+#[sub_commands(autorole_add, autorole_remove, autorole_list, autorole_screening)]
+async fn autorole(ctx: &Context, msg: &Message) -> CommandResult {
+    list_autoroles(ctx, msg).await
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists the roles new members are given when they join."]
+#[num_args(0)]
+async fn autorole_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_autoroles(ctx, msg).await
+}
+
+async fn list_autoroles(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let roles = sqlx::query!("SELECT role_id FROM autoroles WHERE guild_id = ?", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let after_screening = sqlx::query!("SELECT autorole_after_screening FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(&database)
+        .await?
+        .is_some_and(|row| row.autorole_after_screening != 0);
+
+    let description = if roles.is_empty() {
+        "This server has no autoroles yet. Use `autorole add <role>` to add one.".to_string()
+    } else {
+        let roles = roles.iter().map(|row| RoleId::new(row.role_id as u64).mention().to_string()).collect::<Vec<_>>().join(" ");
+        let when = if after_screening { "once they pass membership screening" } else { "as soon as they join" };
+
+        format!("New members are given {roles} {when}.")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Autoroles")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives new members a role when they join."]
+#[usage = "<role>"]
+#[example = "@Member"]
+#[num_args(1)]
+async fn autorole_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM autoroles WHERE guild_id = ?", db_guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count >= MAX_AUTOROLES {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_AUTOROLES} autoroles.")).await?;
+        return Ok(());
+    }
+
+    let added = sqlx::query!(
+        "INSERT INTO autoroles (guild_id, role_id) VALUES (?, ?) ON CONFLICT DO NOTHING",
+        db_guild_id,
+        db_role_id
+    ).execute(&database).await?.rows_affected();
+
+    if added == 0 {
+        msg.reply(ctx, format!("{} is already an autorole.", role_id.mention())).await?;
+    } else {
+        msg.reply(ctx, format!("New members will now be given {}.", role_id.mention())).await?;
+    }
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Stops giving new members a role."]
+#[usage = "<role>"]
+#[example = "@Member"]
+#[num_args(1)]
+async fn autorole_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = args.role_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_role_id) = (msg.guild_id.unwrap().get() as i64, role_id.get() as i64);
+
+    let deleted = sqlx::query!(
+        "DELETE FROM autoroles WHERE guild_id = ? AND role_id = ?",
+        guild_id,
+        db_role_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("{} isn't an autorole.", role_id.mention())).await?;
+    } else {
+        msg.reply(ctx, format!("New members will no longer be given {}.", role_id.mention())).await?;
+    }
+
+    Ok(())
+}
+
+#[command("screening")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Sets whether autoroles wait until new members pass membership screening, so they can't use the roles before agreeing to the rules."]
+#[usage = "<on|off>"]
+#[num_args(1)]
+async fn autorole_screening(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let after_screening = match args.single::<String>()?.to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!(
+        "UPDATE guild_settings SET autorole_after_screening = ? WHERE guild_id = ?",
+        after_screening,
+        guild_id
+    ).execute(&database).await?;
+
+    if after_screening {
+        msg.reply(ctx, "Autoroles will now be given once new members pass membership screening.").await?;
+    } else {
+        msg.reply(ctx, "Autoroles will now be given as soon as new members join.").await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::http::UserPagination;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::moderation::{ModAction, apply_action, check_target};
+use crate::utilities::pagination::{Paginator, embed_pages, paginate_lines};
+use crate::utilities::parsing::parse_user;
+
+/// Most users a single hackban bans.
+const MAX_HACKBANS: usize = 20;
+
+/// Bans Discord returns per request when no limit is given.
+const BANS_PER_REQUEST: usize = 1000;
+
+/// Most bans read from a single guild.
+const MAX_BANS: usize = 10_000;
+
+/// Most users Discord bans in one bulk ban.
+const BULK_BAN_SIZE: usize = 200;
+
+const BANS_PER_PAGE: usize = 10;
+
+/// Every ban in a guild, up to `MAX_BANS`, fetched a request's worth at a time.
+async fn fetch_bans(ctx: &Context, guild_id: GuildId) -> Result<Vec<Ban>, SerenityError> {
+    let mut bans: Vec<Ban> = Vec::new();
+
+    loop {
+        let after = bans.last().map(|ban| UserPagination::After(ban.user.id));
+        let page = guild_id.bans(ctx, after, None).await?;
+        let full = page.len() == BANS_PER_REQUEST;
+
+        bans.extend(page);
+
+        if !full || bans.len() >= MAX_BANS {
+            return Ok(bans);
+        }
+    }
+}
+
+/// A guild's name if the bot is in it, otherwise its ID.
+fn guild_name(ctx: &Context, guild_id: GuildId) -> String {
+    guild_id.name(&ctx.cache).unwrap_or_else(|| guild_id.to_string())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Bans users by their ID, whether or not they're in the server, e.g. to keep out accounts known from raids elsewhere."]
+#[usage = "<user IDs...> [reason]"]
+#[example = "123456789012345678 234567890123456789 Raid accounts"]
+#[min_args(1)]
+async fn hackban(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut targets = Vec::new();
+
+    while let Some(user_id) = args.current().and_then(parse_user) {
+        if !targets.contains(&user_id) {
+            targets.push(user_id);
+        }
+
+        args.advance();
+    }
+
+    if targets.is_empty() {
+        msg.reply(ctx, "Please give the IDs of the users to ban.").await?;
+        return Ok(());
+    }
+
+    if targets.len() > MAX_HACKBANS {
+        msg.reply(ctx, format!("You can ban at most {MAX_HACKBANS} users at once.")).await?;
+        return Ok(());
+    }
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    let prompt = format!("Ban {} user{}?", targets.len(), if targets.len() == 1 { "" } else { "s" });
+
+    if !confirm(ctx, msg, &prompt, CONFIRM_TIMEOUT).await? {
+        return Ok(());
+    }
+
+    let mut outcomes = Vec::new();
+
+    for target_id in targets {
+        if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Ban, Permissions::BAN_MEMBERS).await {
+            outcomes.push(format!("`{target_id}`: {why}"));
+            continue;
+        }
+
+        if guild_id.get_ban(ctx, target_id).await?.is_some() {
+            outcomes.push(format!("`{target_id}`: Already banned."));
+            continue;
+        }
+
+        let Ok(target) = target_id.to_user(ctx).await else {
+            outcomes.push(format!("`{target_id}`: There's no such user."));
+            continue;
+        };
+
+        let case = apply_action(ctx, guild_id, &target, &msg.author, ModAction::Ban, None, reason).await?;
+        outcomes.push(format!("`{target_id}`: Banned **{}** (case #{case}).", target.tag()));
+    }
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Hackban")
+        .description(outcomes.join("\n"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Lists this server's bans, with buttons to flip through them. Give a search to only list bans whose name, ID or reason contains it."]
+#[usage = "[search]"]
+#[example = "spam"]
+async fn banlist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let search = args.rest().trim().to_lowercase();
+
+    let bans: Vec<Ban> = fetch_bans(ctx, msg.guild_id.unwrap()).await?
+        .into_iter()
+        .filter(|ban| {
+            search.is_empty()
+                || ban.user.name.to_lowercase().contains(&search)
+                || ban.user.id.to_string() == search
+                || ban.reason.as_ref().is_some_and(|reason| reason.to_lowercase().contains(&search))
+        })
+        .collect();
+
+    if bans.is_empty() {
+        let reply = if search.is_empty() { "This server has no bans." } else { "No bans match that search." };
+        msg.reply(ctx, reply).await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = bans.iter()
+        .map(|ban| {
+            let reason: String = ban.reason.as_deref().unwrap_or("No reason given.").chars().take(100).collect();
+            format!("**{}** (`{}`)\n{reason}", ban.user.tag(), ban.user.id)
+        })
+        .collect();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Bans ({})", bans.len()));
+
+    let pages = embed_pages(embed, paginate_lines(&lines, BANS_PER_PAGE, "\n\n"));
+    Paginator::new(pages).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Shows which servers this one may import bans from, and which it shares its bans with. A server's owner has to allow sharing with `bansync allow` before another server can import its bans with `bansync import`."]
+#[sub_commands(bansync_allow, bansync_revoke, bansync_import)]
+async fn bansync(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let imports = sqlx::query!("SELECT source_guild_id, last_synced_at FROM ban_sync_links WHERE target_guild_id = ?", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let shares = sqlx::query!("SELECT target_guild_id FROM ban_sync_links WHERE source_guild_id = ?", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let imports = if imports.is_empty() {
+        "None".to_string()
+    } else {
+        imports.iter()
+            .map(|row| {
+                let synced = row.last_synced_at.as_deref()
+                    .and_then(|synced_at| Timestamp::parse(synced_at).ok())
+                    .map_or_else(|| "never imported".to_string(), |synced_at| format!("last imported <t:{}:R>", synced_at.unix_timestamp()));
+
+                format!("{} (`{}`), {synced}", guild_name(ctx, GuildId::new(row.source_guild_id as u64)), row.source_guild_id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let shares = if shares.is_empty() {
+        "None".to_string()
+    } else {
+        shares.iter()
+            .map(|row| format!("{} (`{}`)", guild_name(ctx, GuildId::new(row.target_guild_id as u64)), row.target_guild_id))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Ban list sync")
+        .field("Can import bans from", imports, false)
+        .field("Shares bans with", shares, false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// The other guild named by an argument, if the bot is in it, or `None` after telling the author.
+async fn other_guild(ctx: &Context, msg: &Message, args: &mut Args) -> Result<Option<GuildId>, SerenityError> {
+    let guild_id = args.single::<u64>().ok()
+        .filter(|guild_id| *guild_id != 0)
+        .map(GuildId::new)
+        .filter(|guild_id| Some(*guild_id) != msg.guild_id && ctx.cache.guild(*guild_id).is_some());
+
+    if guild_id.is_none() {
+        msg.reply(ctx, "Please give the ID of another server I'm in.").await?;
+    }
+
+    Ok(guild_id)
+}
+
+/// Whether the author owns the guild the command was used in, after telling them if they don't.
+async fn check_owner(ctx: &Context, msg: &Message) -> Result<bool, SerenityError> {
+    let owner_id = msg.guild_id.unwrap().to_guild_cached(&ctx.cache).map(|guild| guild.owner_id);
+
+    if owner_id != Some(msg.author.id) {
+        msg.reply(ctx, "Only the server owner can choose who this server's bans are shared with.").await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+#[command("allow")]
+#[only_in(guilds)]
+#[description = "Lets another server import this server's bans. Only the server owner can do this."]
+#[usage = "<server ID>"]
+#[example = "123456789012345678"]
+#[num_args(1)]
+async fn bansync_allow(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if !check_owner(ctx, msg).await? {
+        return Ok(());
+    }
+
+    let Some(target_id) = other_guild(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (source_guild_id, target_guild_id, approved_by) = (msg.guild_id.unwrap().get() as i64, target_id.get() as i64, msg.author.id.get() as i64);
+    let approved_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO ban_sync_links (source_guild_id, target_guild_id, approved_by, approved_at) VALUES (?, ?, ?, ?)
+        ON CONFLICT (source_guild_id, target_guild_id) DO NOTHING",
+        source_guild_id,
+        target_guild_id,
+        approved_by,
+        approved_at
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!(
+        "**{}** can now import this server's bans with `bansync import {}`.",
+        guild_name(ctx, target_id),
+        source_guild_id
+    )).await?;
+
+    Ok(())
+}
+
+#[command("revoke")]
+#[only_in(guilds)]
+#[description = "Stops another server from importing this server's bans. Bans it already imported stay. Only the server owner can do this."]
+#[usage = "<server ID>"]
+#[example = "123456789012345678"]
+#[num_args(1)]
+async fn bansync_revoke(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if !check_owner(ctx, msg).await? {
+        return Ok(());
+    }
+
+    // servers the bot has left can still be revoked
+    let Ok(target_guild_id) = args.single::<u64>().map(|guild_id| guild_id as i64) else {
+        msg.reply(ctx, "Please give the ID of the server to stop sharing bans with.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let source_guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let removed = sqlx::query!(
+        "DELETE FROM ban_sync_links WHERE source_guild_id = ? AND target_guild_id = ?",
+        source_guild_id,
+        target_guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if removed == 0 {
+        msg.reply(ctx, "This server doesn't share its bans with that server.").await?;
+    } else {
+        msg.reply(ctx, "That server can no longer import this server's bans.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("import")]
+#[only_in(guilds)]
+#[required_permissions(ADMINISTRATOR)]
+#[description = "Bans everyone banned in another server that shares its bans with this one. Can be run again to import new bans."]
+#[usage = "<server ID>"]
+#[example = "123456789012345678"]
+#[num_args(1)]
+async fn bansync_import(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(source_id) = other_guild(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (source_guild_id, target_guild_id) = (source_id.get() as i64, guild_id.get() as i64);
+
+    let link = sqlx::query!(
+        "SELECT approved_by FROM ban_sync_links WHERE source_guild_id = ? AND target_guild_id = ?",
+        source_guild_id,
+        target_guild_id
+    ).fetch_optional(&database).await?;
+
+    if link.is_none() {
+        msg.reply(ctx, format!(
+            "The owner of **{}** has to allow it first, with `bansync allow {}` in that server.",
+            guild_name(ctx, source_id),
+            guild_id
+        )).await?;
+        return Ok(());
+    }
+
+    let existing: HashSet<UserId> = fetch_bans(ctx, guild_id).await?.into_iter().map(|ban| ban.user.id).collect();
+
+    let missing: Vec<UserId> = fetch_bans(ctx, source_id).await?
+        .into_iter()
+        .map(|ban| ban.user.id)
+        .filter(|user_id| !existing.contains(user_id))
+        .collect();
+
+    let reason = format!("Ban list imported from {} by {}", guild_name(ctx, source_id), msg.author.tag());
+    let (mut banned, mut failed) = (0, 0);
+
+    for chunk in missing.chunks(BULK_BAN_SIZE) {
+        let response = guild_id.bulk_ban(&ctx.http, chunk, 0, Some(&reason)).await?;
+
+        banned += response.banned_users.len();
+        failed += response.failed_users.len();
+    }
+
+    let synced_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "UPDATE ban_sync_links SET last_synced_at = ? WHERE source_guild_id = ? AND target_guild_id = ?",
+        synced_at,
+        source_guild_id,
+        target_guild_id
+    ).execute(&database).await?;
+
+    let failures = if failed == 0 { String::new() } else { format!(" {failed} couldn't be banned.") };
+
+    msg.reply(ctx, format!("Imported {banned} bans from **{}**.{failures}", guild_name(ctx, source_id))).await?;
+
+    Ok(())
+}
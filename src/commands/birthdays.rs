@@ -0,0 +1,201 @@
+use chrono::Utc;
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::birthdays::{Birthday, get_birthday};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{Paginator, embed_pages, paginate_lines};
+use crate::utilities::timezones::guild_timezone_or_utc;
+
+const LINES_PER_PAGE: usize = 15;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows your birthday, or another member's. `birthday set` saves yours so the server can celebrate it."]
+#[usage = "[member]"]
+#[example = "@Kanzoey"]
+#[sub_commands(birthday_set, birthday_remove, birthday_list, birthday_channel, birthday_role)]
+#[max_args(1)]
+async fn birthday(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.member(ctx, msg).await?.user.id };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let reply = match (get_birthday(&database, msg.guild_id.unwrap(), user_id).await?, user_id == msg.author.id) {
+        (Some(birthday), true) => format!("Your birthday is on {birthday}."),
+        (Some(birthday), false) => format!("{}'s birthday is on {birthday}.", user_id.mention()),
+        (None, true) => "You haven't set your birthday yet, use `birthday set`.".to_string(),
+        (None, false) => format!("{} hasn't set their birthday.", user_id.mention())
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().content(reply).allowed_mentions(CreateAllowedMentions::new())).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[description = "Saves your birthday in this server. The year is optional and is never shown to other members."]
+#[usage = "<date>"]
+#[example = "March 14"]
+#[min_args(1)]
+async fn birthday_set(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(birthday) = Birthday::parse(args.rest()) else {
+        msg.reply(ctx, "Please give your birthday as `March 14`, `14 March`, `03-14` or `2000-03-14`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (msg.guild_id.unwrap().get() as i64, msg.author.id.get() as i64);
+    let (month, day, year) = (birthday.month as i64, birthday.day as i64, birthday.year.map(i64::from));
+
+    sqlx::query!(
+        "INSERT INTO birthdays (guild_id, user_id, month, day, year) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET month = excluded.month, day = excluded.day, year = excluded.year",
+        guild_id,
+        user_id,
+        month,
+        day,
+        year
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Your birthday is set to {birthday}.")).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[description = "Removes your birthday from this server."]
+#[num_args(0)]
+async fn birthday_remove(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (msg.guild_id.unwrap().get() as i64, msg.author.id.get() as i64);
+
+    sqlx::query!("DELETE FROM birthdays WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, "Your birthday was removed.").await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's upcoming birthdays."]
+#[num_args(0)]
+async fn birthday_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let today = Utc::now().with_timezone(&guild_timezone_or_utc(&database, guild_id).await?).date_naive();
+    let id = guild_id.get() as i64;
+
+    let rows = sqlx::query!("SELECT user_id, month, day FROM birthdays WHERE guild_id = ?", id)
+        .fetch_all(&database)
+        .await?;
+
+    if rows.is_empty() {
+        msg.reply(ctx, "Nobody in this server has set their birthday yet, use `birthday set`.").await?;
+        return Ok(());
+    }
+
+    // years are left out, since they're private
+    let mut upcoming = rows.iter()
+        .map(|row| {
+            let birthday = Birthday { month: row.month as u32, day: row.day as u32, year: None };
+            (birthday.next_after(today), birthday, row.user_id)
+        })
+        .collect::<Vec<_>>();
+
+    upcoming.sort_by_key(|(next, _, _)| *next);
+
+    let lines = upcoming.iter()
+        .map(|(next, birthday, user_id)| match (*next - today).num_days() {
+            0 => format!("<@{user_id}> - **today** 🎂"),
+            1 => format!("<@{user_id}> - {birthday} (tomorrow)"),
+            days => format!("<@{user_id}> - {birthday} (in {days} days)")
+        })
+        .collect::<Vec<_>>();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Upcoming birthdays");
+
+    Paginator::new(embed_pages(embed, paginate_lines(&lines, LINES_PER_PAGE, "\n"))).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channel birthdays are announced in when the day starts in the server's timezone (see `timezone server`). Use `off` to stop announcing them."]
+#[usage = "<channel|off>"]
+#[example = "#general"]
+#[num_args(1)]
+async fn birthday_channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.map(|channel_id| channel_id.get() as i64));
+
+    sqlx::query!(
+        "INSERT INTO birthday_settings (guild_id, channel_id) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET channel_id = excluded.channel_id",
+        db_guild_id,
+        db_channel_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Birthdays will be announced in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Birthdays won't be announced anymore.").await?
+    };
+
+    Ok(())
+}
+
+#[command("role")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets a role members get for the 24 hours after their birthday starts. Use `off` to stop giving it."]
+#[usage = "<role|off>"]
+#[example = "@Birthday"]
+#[num_args(1)]
+async fn birthday_role(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = if args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("off")) {
+        None
+    } else {
+        Some(args.role(ctx, msg).await?.id)
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_role_id) = (msg.guild_id.unwrap().get() as i64, role_id.map(|role_id| role_id.get() as i64));
+
+    sqlx::query!(
+        "INSERT INTO birthday_settings (guild_id, role_id) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET role_id = excluded.role_id",
+        guild_id,
+        db_role_id
+    ).execute(&database).await?;
+
+    let reply = match role_id {
+        Some(role_id) => format!("Members will get {} on their birthday.", role_id.mention()),
+        None => "Members won't get a role on their birthday anymore.".to_string()
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().content(reply).reference_message(msg).allowed_mentions(CreateAllowedMentions::new())).await?;
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::chain_games::{ChainKind, reload_chain_game};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most game channels a single guild can have.
+const MAX_GAME_CHANNELS: i32 = 5;
+
+/// Streaks and players shown on the leaderboard.
+const LEADERBOARD_SIZE: i64 = 10;
+
+#[command]
+#[aliases("gamechannels")]
+#[only_in(guilds)]
+#[description = "Lists this server's counting and word chain channels. In a counting channel members count up from 1 one number each, and in a word chain channel each word starts with the last letter of the previous one. Mistakes are deleted and end the streak."]
+#[sub_commands(gamechannel_set, gamechannel_remove, gamechannel_leaderboard)]
+#[num_args(0)]
+async fn gamechannel(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let games = sqlx::query!("SELECT channel_id, kind, streak, record FROM chain_games WHERE guild_id = ? ORDER BY channel_id", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if games.is_empty() {
+        msg.reply(ctx, "This server has no game channels. Add one with `gamechannel set`.").await?;
+        return Ok(());
+    }
+
+    let lines = games.iter()
+        .filter_map(|row| {
+            let kind = ChainKind::parse(&row.kind)?;
+            Some(format!("<#{}> - {}, currently at **{}** (record: **{}**)", row.channel_id, kind.title(), row.streak, row.record))
+        })
+        .collect::<Vec<_>>();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Game channels")
+        .description(lines.join("\n"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Makes a channel a counting or word chain channel, starting a fresh streak. Give me Manage Messages there so I can delete mistakes."]
+#[usage = "<counting|words> <channel>"]
+#[example = "counting #counting"]
+#[num_args(2)]
+async fn gamechannel_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(kind) = ChainKind::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "Please choose `counting` or `words`.").await?;
+        return Ok(());
+    };
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel = args.channel(ctx, msg).await?;
+
+    if channel.kind != ChannelType::Text {
+        msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+        return Ok(());
+    }
+
+    let channel_id = channel.id;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_channel_id, kind_name) = (guild_id.get() as i64, channel_id.get() as i64, kind.name());
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM chain_games WHERE guild_id = ? AND channel_id != ?",
+        db_guild_id,
+        db_channel_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_GAME_CHANNELS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_GAME_CHANNELS} game channels.")).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO chain_games (channel_id, guild_id, kind) VALUES (?, ?, ?)
+        ON CONFLICT (channel_id) DO UPDATE SET kind = excluded.kind, streak = 0, last_user_id = NULL, last_word = NULL, record = 0",
+        db_channel_id,
+        db_guild_id,
+        kind_name
+    ).execute(&database).await?;
+
+    sqlx::query!("DELETE FROM chain_game_words WHERE channel_id = ?", db_channel_id)
+        .execute(&database)
+        .await?;
+
+    reload_chain_game(ctx, channel_id).await?;
+
+    let start = match kind {
+        ChainKind::Counting => "Start counting from **1**!",
+        ChainKind::Words => "Start the chain with any word!"
+    };
+
+    msg.reply(ctx, format!("{} is now a {} channel. {start}", channel_id.mention(), kind.title().to_lowercase())).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Stops a channel being a game channel. Its finished streaks stay on the leaderboard."]
+#[usage = "<channel>"]
+#[example = "#counting"]
+#[num_args(1)]
+async fn gamechannel_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_channel_id) = (msg.guild_id.unwrap().get() as i64, channel_id.get() as i64);
+
+    let deleted = sqlx::query!("DELETE FROM chain_games WHERE channel_id = ? AND guild_id = ?", db_channel_id, guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("{} isn't a game channel.", channel_id.mention())).await?;
+        return Ok(());
+    }
+
+    sqlx::query!("DELETE FROM chain_game_words WHERE channel_id = ?", db_channel_id)
+        .execute(&database)
+        .await?;
+
+    reload_chain_game(ctx, channel_id).await?;
+
+    msg.reply(ctx, format!("{} isn't a game channel anymore.", channel_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("leaderboard")]
+#[aliases("top")]
+#[only_in(guilds)]
+#[description = "Shows this server's longest streaks and the members who've kept them going most for a game."]
+#[usage = "<counting|words>"]
+#[example = "counting"]
+#[num_args(1)]
+async fn gamechannel_leaderboard(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(kind) = ChainKind::parse(args.rest().trim()) else {
+        msg.reply(ctx, "Please choose `counting` or `words`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, kind_name) = (msg.guild_id.unwrap().get() as i64, kind.name());
+
+    // streaks still going count too, they just haven't been saved as finished yet
+    let streaks = sqlx::query!(
+        "SELECT channel_id, length, broken_by AS \"broken_by?\" FROM chain_game_streaks WHERE guild_id = ? AND kind = ?
+        UNION ALL SELECT channel_id, streak AS length, NULL AS broken_by FROM chain_games WHERE guild_id = ? AND kind = ? AND streak > 0
+        ORDER BY length DESC LIMIT ?",
+        guild_id,
+        kind_name,
+        guild_id,
+        kind_name,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let players = sqlx::query!(
+        "SELECT user_id, correct, mistakes FROM chain_game_players WHERE guild_id = ? AND kind = ? ORDER BY correct DESC LIMIT ?",
+        guild_id,
+        kind_name,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    if streaks.is_empty() && players.is_empty() {
+        msg.reply(ctx, format!("Nobody has played {} in this server yet.", kind.title().to_lowercase())).await?;
+        return Ok(());
+    }
+
+    let streak_lines = streaks.iter()
+        .enumerate()
+        .map(|(index, row)| match row.broken_by {
+            Some(user_id) => format!("{}. **{}** in <#{}>, broken by <@{user_id}>", index + 1, row.length, row.channel_id),
+            None => format!("{}. **{}** in <#{}>, still going", index + 1, row.length, row.channel_id)
+        })
+        .collect::<Vec<_>>();
+
+    let player_lines = players.iter()
+        .enumerate()
+        .map(|(index, row)| format!("{}. <@{}> - {} correct, {} mistakes", index + 1, row.user_id, row.correct, row.mistakes))
+        .collect::<Vec<_>>();
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("{} leaderboard", kind.title()));
+
+    if !streak_lines.is_empty() {
+        embed = embed.field("Longest streaks", streak_lines.join("\n"), false);
+    }
+
+    if !player_lines.is_empty() {
+        embed = embed.field("Top players", player_lines.join("\n"), false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandError, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::command_rules::{reload_command_rules, rule_name};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+#[command("command")]
+#[only_in(guilds)]
+#[description = "Lists the commands that are turned off or limited to certain channels in this server. Rules can name a command, a group such as `moderation`, or `all`."]
+#[sub_commands(command_enable, command_disable)]
+async fn command_rules(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let rules = sqlx::query!("SELECT name, channel_id, allowed FROM command_rules WHERE guild_id = ? ORDER BY name, allowed DESC", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if rules.is_empty() {
+        msg.reply(ctx, "Every command can be used anywhere in this server.").await?;
+        return Ok(());
+    }
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title("Command rules");
+
+    let mut names = rules.iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>();
+    names.dedup();
+
+    for name in names {
+        let channels = |allowed: i64| rules.iter()
+            .filter(|rule| rule.name == name && rule.allowed == allowed && rule.channel_id != 0)
+            .map(|rule| ChannelId::new(rule.channel_id as u64).mention().to_string())
+            .collect::<Vec<_>>();
+
+        let mut lines = Vec::new();
+
+        if rules.iter().any(|rule| rule.name == name && rule.allowed == 0 && rule.channel_id == 0) {
+            lines.push("Off in this server".to_string());
+        }
+
+        let (allowed, blocked) = (channels(1), channels(0));
+
+        if !allowed.is_empty() {
+            lines.push(format!("Only in {}", allowed.join(", ")));
+        }
+
+        if !blocked.is_empty() {
+            lines.push(format!("Off in {}", blocked.join(", ")));
+        }
+
+        embed = embed.field(format!("`{name}`"), lines.join("\n"), false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("enable")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns a command, group or `all` back on. With a channel, it's turned back on there, or if it isn't off there, limited to that channel and any others it's been enabled in."]
+#[usage = "<command|group|all> [channel]"]
+#[example = "all #bot-commands"]
+#[min_args(1)]
+#[max_args(2)]
+async fn command_enable(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some((name, channel_id)) = parse_rule_args(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let Some(channel_id) = channel_id else {
+        sqlx::query!("DELETE FROM command_rules WHERE guild_id = ? AND name = ?", db_guild_id, name)
+            .execute(&database)
+            .await?;
+
+        reload_command_rules(ctx, guild_id).await?;
+        msg.reply(ctx, format!("`{name}` can now be used anywhere.")).await?;
+
+        return Ok(());
+    };
+
+    let db_channel_id = channel_id.get() as i64;
+    let mut transaction = database.begin().await?;
+
+    let unblocked = sqlx::query!("DELETE FROM command_rules WHERE guild_id = ? AND name = ? AND channel_id = ? AND allowed = 0", db_guild_id, name, db_channel_id)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected() > 0;
+
+    let blocked_everywhere = sqlx::query!("SELECT name FROM command_rules WHERE guild_id = ? AND name = ? AND channel_id = 0", db_guild_id, name)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .is_some();
+
+    if unblocked && !blocked_everywhere {
+        transaction.commit().await?;
+        reload_command_rules(ctx, guild_id).await?;
+        msg.reply(ctx, format!("`{name}` can be used in {} again.", channel_id.mention())).await?;
+
+        return Ok(());
+    }
+
+    sqlx::query!("DELETE FROM command_rules WHERE guild_id = ? AND name = ? AND channel_id = 0", db_guild_id, name)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query!(
+        "INSERT INTO command_rules (guild_id, name, channel_id, allowed) VALUES (?, ?, ?, 1)
+        ON CONFLICT (guild_id, name, channel_id) DO UPDATE SET allowed = 1",
+        db_guild_id,
+        name,
+        db_channel_id
+    ).execute(&mut *transaction).await?;
+
+    let allowed = sqlx::query!("SELECT channel_id FROM command_rules WHERE guild_id = ? AND name = ? AND allowed = 1", db_guild_id, name)
+        .fetch_all(&mut *transaction)
+        .await?
+        .into_iter()
+        .map(|row| ChannelId::new(row.channel_id as u64).mention().to_string())
+        .collect::<Vec<_>>();
+
+    transaction.commit().await?;
+    reload_command_rules(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("`{name}` can now only be used in {}.", allowed.join(", "))).await?;
+
+    Ok(())
+}
+
+#[command("disable")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns a command, group or `all` off, in the whole server or just one channel. The `command` command always stays on."]
+#[usage = "<command|group|all> [channel]"]
+#[example = "fun"]
+#[min_args(1)]
+#[max_args(2)]
+async fn command_disable(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some((name, channel_id)) = parse_rule_args(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let db_channel_id = channel_id.map_or(0, |channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO command_rules (guild_id, name, channel_id, allowed) VALUES (?, ?, ?, 0)
+        ON CONFLICT (guild_id, name, channel_id) DO UPDATE SET allowed = 0",
+        db_guild_id,
+        name,
+        db_channel_id
+    ).execute(&database).await?;
+
+    reload_command_rules(ctx, guild_id).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("`{name}` is now off in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, format!("`{name}` is now off in this server.")).await?
+    };
+
+    Ok(())
+}
+
+/// Reads the rule name and optional channel both sub-commands take, replying if either is invalid.
+async fn parse_rule_args(ctx: &Context, msg: &Message, args: &mut Args) -> Result<Option<(String, Option<ChannelId>)>, CommandError> {
+    let given = args.single::<String>().unwrap_or_default();
+
+    let Some(name) = rule_name(&given) else {
+        msg.reply(ctx, format!("There's no command or group called `{given}`.")).await?;
+        return Ok(None);
+    };
+
+    if args.is_empty() {
+        return Ok(Some((name, None)));
+    }
+
+    Ok(Some((name, Some(args.channel(ctx, msg).await?.id))))
+}
@@ -0,0 +1,152 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::cooldowns::{CooldownScope, DEFAULT_COOLDOWNS, default_cooldown, find_command, reload_cooldowns};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Longest cooldown a server can give a command.
+const MAX_COOLDOWN_SECONDS: u64 = 86_400;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows the commands with a cooldown in this server, including the ones it has by default."]
+#[sub_commands(cooldown_set, cooldown_reset)]
+async fn cooldown(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let overrides = sqlx::query!("SELECT command, seconds, scope FROM command_cooldowns WHERE guild_id = ? ORDER BY command", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let defaults = DEFAULT_COOLDOWNS.iter()
+        .map(|(command, scope, seconds)| {
+            let overridden = if overrides.iter().any(|row| row.command == *command) { " (overridden)" } else { "" };
+            format!("`{command}`: {seconds}s per {}{overridden}", scope.name())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let overrides = if overrides.is_empty() {
+        "None".to_string()
+    } else {
+        overrides.iter()
+            .map(|row| match row.seconds {
+                0 => format!("`{}`: off", row.command),
+                seconds => format!("`{}`: {seconds}s per {}", row.command, row.scope)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Cooldowns")
+        .field("Defaults", defaults, true)
+        .field("This server", overrides, true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how long members have to wait between uses of a command, either each on their own (`user`, the default) or the whole server at once (`server`). 0 turns a command's cooldown off."]
+#[usage = "<command> <seconds> [user|server]"]
+#[example = "wordcloud 60 server"]
+#[min_args(2)]
+#[max_args(3)]
+async fn cooldown_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let seconds = args.single::<u64>()?;
+
+    let Some(command) = find_command(&name) else {
+        msg.reply(ctx, format!("There is no command called `{name}`.")).await?;
+        return Ok(());
+    };
+
+    if seconds > MAX_COOLDOWN_SECONDS {
+        msg.reply(ctx, format!("Cooldowns can be at most {MAX_COOLDOWN_SECONDS} seconds long.")).await?;
+        return Ok(());
+    }
+
+    // cooldowns are looked up by a command's first name, whichever alias was given
+    let command = command.options.names[0];
+
+    let scope = match args.single::<String>().ok().map(|scope| scope.to_lowercase()) {
+        Some(scope) => match CooldownScope::from_name(&scope).filter(|scope| *scope != CooldownScope::Global) {
+            Some(scope) => scope,
+            None => {
+                msg.reply(ctx, "The cooldown has to apply to each `user` or the whole `server`.").await?;
+                return Ok(());
+            }
+        },
+        None => default_cooldown(command).map_or(CooldownScope::User, |(_, scope)| scope)
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, db_seconds, scope_name) = (guild_id.get() as i64, seconds as i64, scope.name());
+
+    sqlx::query!(
+        "INSERT INTO command_cooldowns (guild_id, command, seconds, scope) VALUES (?, ?, ?, ?)
+        ON CONFLICT (guild_id, command) DO UPDATE SET seconds = excluded.seconds, scope = excluded.scope",
+        db_guild_id,
+        command,
+        db_seconds,
+        scope_name
+    ).execute(&database).await?;
+
+    reload_cooldowns(ctx, guild_id).await?;
+
+    match (seconds, scope) {
+        (0, _) => msg.reply(ctx, format!("`{command}` no longer has a cooldown here.")).await?,
+        (_, CooldownScope::Guild) => msg.reply(ctx, format!("`{command}` can now be used once every {seconds}s in this server.")).await?,
+        _ => msg.reply(ctx, format!("Members can now use `{command}` once every {seconds}s.")).await?
+    };
+
+    Ok(())
+}
+
+#[command("reset")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Goes back to a command's default cooldown, if it has one."]
+#[usage = "<command>"]
+#[num_args(1)]
+async fn cooldown_reset(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let command = find_command(&name).map_or(name.as_str(), |command| command.options.names[0]);
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let deleted = sqlx::query!("DELETE FROM command_cooldowns WHERE guild_id = ? AND command = ?", db_guild_id, command)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("`{command}` doesn't have a cooldown set in this server.")).await?;
+        return Ok(());
+    }
+
+    reload_cooldowns(ctx, guild_id).await?;
+
+    match default_cooldown(command) {
+        Some((seconds, scope)) => msg.reply(ctx, format!("`{command}` is back to its default cooldown of {seconds}s per {}.", scope.name())).await?,
+        None => msg.reply(ctx, format!("`{command}` no longer has a cooldown here.")).await?
+    };
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use serenity::builder::{CreateChannel, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::counters::{CounterKind, counter_name};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most counter channels a single guild can have.
+const MAX_COUNTERS: i32 = 5;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's member count channels. Counters are locked voice channels whose names are kept up to date every 10 minutes."]
+#[sub_commands(counter_create, counter_delete, counter_list)]
+async fn counter(ctx: &Context, msg: &Message) -> CommandResult {
+    list_counters(ctx, msg).await
+}
+
+#[command("create")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Creates a counter channel. Types are `members`, `humans`, `bots`, `online` and `boosts`, though `online` only works if the bot's host turned on presences. The name is a template where `{count}` is the counter's value."]
+#[usage = "<type> [name]"]
+#[example = "members 👥 Members: {count}"]
+#[min_args(1)]
+async fn counter_create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind_arg = args.single::<String>()?;
+
+    let Some(kind) = CounterKind::parse(&kind_arg) else {
+        let kinds = CounterKind::ALL.iter().filter(|kind| kind.is_available()).map(|kind| format!("`{}`", kind.as_str())).collect::<Vec<_>>().join(", ");
+        msg.reply(ctx, format!("`{kind_arg}` isn't a counter type. Available types are {kinds}.")).await?;
+        return Ok(());
+    };
+
+    if !kind.is_available() {
+        msg.reply(ctx, format!("The `{}` counter needs presences, which aren't turned on for this bot.", kind.as_str())).await?;
+        return Ok(());
+    }
+
+    let template = match args.rest().trim() {
+        "" => kind.default_template().to_string(),
+        template => template.to_string()
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM counter_channels WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_COUNTERS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_COUNTERS} counters.")).await?;
+        return Ok(());
+    }
+
+    let Some(value) = guild_id.to_guild_cached(&ctx.cache).map(|guild| kind.count(&guild)) else {
+        msg.reply(ctx, "This server isn't cached yet, please try again in a moment.").await?;
+        return Ok(());
+    };
+
+    // nobody can join a counter, it only exists to show its name
+    let locked = PermissionOverwrite {
+        allow: Permissions::empty(),
+        deny: Permissions::CONNECT,
+        kind: PermissionOverwriteType::Role(RoleId::new(guild_id.get()))
+    };
+
+    let builder = CreateChannel::new(counter_name(&template, value))
+        .kind(ChannelType::Voice)
+        .permissions(vec![locked]);
+
+    let channel = guild_id.create_channel(ctx, builder).await?;
+
+    let channel_id = channel.id.get() as i64;
+    let kind_name = kind.as_str();
+    let created_by = msg.author.id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO counter_channels (channel_id, guild_id, kind, template, created_by) VALUES (?, ?, ?, ?, ?)",
+        channel_id,
+        db_guild_id,
+        kind_name,
+        template,
+        created_by
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Created {} counting {kind_name}.", channel.mention())).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Deletes a counter and its channel. Deleting the channel directly removes the counter too."]
+#[usage = "<channel>"]
+#[num_args(1)]
+async fn counter_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_channel_id, db_guild_id) = (channel_id.get() as i64, msg.guild_id.unwrap().get() as i64);
+
+    let deleted = sqlx::query!(
+        "DELETE FROM counter_channels WHERE channel_id = ? AND guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, "That channel isn't a counter.").await?;
+        return Ok(());
+    }
+
+    channel_id.delete(ctx).await?;
+    msg.reply(ctx, "Deleted the counter.").await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's counters."]
+async fn counter_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_counters(ctx, msg).await
+}
+
+async fn list_counters(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let counters = sqlx::query!(
+        "SELECT channel_id, kind, template FROM counter_channels WHERE guild_id = ?",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if counters.is_empty() {
+        "This server has no counters yet.".to_string()
+    } else {
+        counters.iter()
+            .map(|row| format!("{} - {} (`{}`)", ChannelId::new(row.channel_id as u64).mention(), row.kind, row.template))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Counters")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
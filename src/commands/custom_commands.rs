@@ -0,0 +1,321 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::dispatch::resolve_command;
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::templates::{TemplateContext, message_context, render_template};
+
+/// Most custom commands a single guild can define.
+const MAX_CUSTOM_COMMANDS: i32 = 100;
+const MAX_RESPONSE_LENGTH: usize = 2000;
+
+/// Arguments past this many are only available through `{args}`.
+const MAX_NUMBERED_ARGS: usize = 9;
+
+#[command]
+#[aliases("cc")]
+#[only_in(guilds)]
+#[description = "Lists this server's custom commands. Their responses are templates (see `template`) that can also use `{args}`, `{args.1}`, `{args.2}` and so on, and they're run by name like any other command."]
+#[sub_commands(customcommand_set, customcommand_delete, customcommand_role, customcommand_list, customcommand_show)]
+async fn customcommand(ctx: &Context, msg: &Message) -> CommandResult {
+    list_custom_commands(ctx, msg).await
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Creates or replaces a custom command."]
+#[usage = "<name> <response>"]
+#[example = "hug {user} hugs {args}! {random:🤗|💕}"]
+#[min_args(2)]
+async fn customcommand_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let response = args.rest().trim().to_string();
+
+    if resolve_command(&name).is_some() {
+        msg.reply(ctx, format!("`{name}` is already the name of a built-in command.")).await?;
+        return Ok(());
+    }
+
+    if response.chars().count() > MAX_RESPONSE_LENGTH {
+        msg.reply(ctx, format!("Responses can be at most {MAX_RESPONSE_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let context = command_context(ctx, msg, "").await;
+
+    if let Err(why) = render_template(&response, &context) {
+        msg.reply(ctx, format!("That template couldn't be rendered: {why}.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    // scripts and tags are looked up first, so a custom command with the same name would never run
+    let taken = sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM guild_scripts WHERE guild_id = ?1 AND name = ?2)
+            OR EXISTS(SELECT 1 FROM tags WHERE guild_id = ?1 AND name = ?2) AS "taken!: bool""#,
+        guild_id,
+        name
+    ).fetch_one(&database).await?.taken;
+
+    if taken {
+        msg.reply(ctx, format!("`{name}` is already the name of a script or tag.")).await?;
+        return Ok(());
+    }
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM custom_commands WHERE guild_id = ? AND name != ?",
+        guild_id,
+        name
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_CUSTOM_COMMANDS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_CUSTOM_COMMANDS} custom commands.")).await?;
+        return Ok(());
+    }
+
+    let created_by = msg.author.id.get() as i64;
+    let created_at = Utc::now().to_rfc3339();
+
+    // replacing a command keeps its role restriction and use count
+    sqlx::query!(
+        "INSERT INTO custom_commands (guild_id, name, response, created_by, created_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, name) DO UPDATE SET
+            response = excluded.response,
+            created_by = excluded.created_by,
+            created_at = excluded.created_at",
+        guild_id,
+        name,
+        response,
+        created_by,
+        created_at
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Saved custom command `{name}`.")).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Deletes a custom command."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn customcommand_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM custom_commands WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no custom command named `{name}`.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted custom command `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("role")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Limits a custom command to members with a role. Use `none` to let everyone use it again."]
+#[usage = "<name> <role|none>"]
+#[example = "hug @Regulars"]
+#[num_args(2)]
+async fn customcommand_role(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let role_id = if args.keyword("none") { None } else { Some(args.role(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let db_role_id = role_id.map(|role_id| role_id.get() as i64);
+
+    let updated = sqlx::query!(
+        "UPDATE custom_commands SET required_role_id = ? WHERE guild_id = ? AND name = ?",
+        db_role_id,
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    match (updated, role_id) {
+        (0, _) => msg.reply(ctx, format!("There is no custom command named `{name}`.")).await?,
+        (_, Some(role_id)) => msg.reply(ctx, format!("Only members with {} can use `{name}` now.", role_id.mention())).await?,
+        (_, None) => msg.reply(ctx, format!("Everyone can use `{name}` now.")).await?
+    };
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's custom commands."]
+#[num_args(0)]
+async fn customcommand_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_custom_commands(ctx, msg).await
+}
+
+#[command("show")]
+#[only_in(guilds)]
+#[description = "Shows a custom command's response template and who can use it."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn customcommand_show(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let Some(command) = sqlx::query!(
+        "SELECT response, required_role_id, uses FROM custom_commands WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, format!("There is no custom command named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let allowed = command.required_role_id
+        .map_or_else(|| "Everyone".to_string(), |role_id| RoleId::new(role_id as u64).mention().to_string());
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Custom command: {name}"))
+        .description(format!("```\n{}\n```", command.response))
+        .field("Usable by", allowed, true)
+        .field("Uses", command.uses.to_string(), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Whether the guild has a custom command by this name.
+pub async fn custom_command_exists(database: &SqlitePool, guild_id: GuildId, name: &str) -> Result<bool, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let exists = sqlx::query!("SELECT name FROM custom_commands WHERE guild_id = ? AND name = ?", guild_id, name)
+        .fetch_optional(database)
+        .await?
+        .is_some();
+
+    Ok(exists)
+}
+
+/// Runs the guild's custom command with the given name, if there is one. Returns whether a
+/// command was found, so callers can fall back to other handling.
+pub async fn run_custom_command(ctx: &Context, msg: &Message, name: &str, args: &str) -> Result<bool, BotError> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(false);
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let Some(command) = sqlx::query!(
+        "SELECT response, required_role_id FROM custom_commands WHERE guild_id = ? AND name = ?",
+        db_guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        return Ok(false);
+    };
+
+    if let Some(role_id) = command.required_role_id.map(|role_id| RoleId::new(role_id as u64)) {
+        let allowed = msg.member(ctx).await.is_ok_and(|member| member.roles.contains(&role_id));
+
+        if !allowed {
+            let builder = CreateMessage::new()
+                .content(format!("Only members with {} can use `{name}`.", role_id.mention()))
+                .reference_message(msg)
+                .allowed_mentions(CreateAllowedMentions::new());
+
+            drop(msg.channel_id.send_message(ctx, builder).await);
+            return Ok(true);
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE custom_commands SET uses = uses + 1 WHERE guild_id = ? AND name = ?",
+        db_guild_id,
+        name
+    ).execute(&database).await?;
+
+    let context = command_context(ctx, msg, args).await;
+    let content = render_template(&command.response, &context).unwrap_or(command.response);
+
+    // custom commands can't ping everyone or roles, only the people they mention
+    let builder = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+    drop(msg.channel_id.send_message(ctx, builder).await);
+
+    Ok(true)
+}
+
+/// The message's template context, plus `{args}` and each argument as `{args.1}`, `{args.2}`, ...
+async fn command_context(ctx: &Context, msg: &Message, args: &str) -> TemplateContext {
+    let args = args.trim();
+
+    let context = message_context(ctx, msg).await.value("args", args);
+
+    args.split_whitespace()
+        .take(MAX_NUMBERED_ARGS)
+        .enumerate()
+        .fold(context, |context, (index, arg)| context.value(&format!("args.{}", index + 1), arg))
+}
+
+async fn list_custom_commands(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let commands = sqlx::query!(
+        "SELECT name, required_role_id, uses FROM custom_commands WHERE guild_id = ? ORDER BY name",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if commands.is_empty() {
+        "This server has no custom commands yet. Use `customcommand set <name> <response>` to make one.".to_string()
+    } else {
+        commands.iter()
+            .map(|row| match row.required_role_id {
+                Some(role_id) => format!("`{}` - used {} time(s), {} only", row.name, row.uses, RoleId::new(role_id as u64).mention()),
+                None => format!("`{}` - used {} time(s)", row.name, row.uses)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Custom commands")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,459 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serenity::builder::{CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::votes::{get_vote_streak, VOTER_DAILY_BONUS};
+
+const CURRENCY: &str = "🪙";
+
+const DAILY_AMOUNT: i64 = 100;
+const DAILY_COOLDOWN: Duration = Duration::hours(24);
+
+const MAX_SHOP_ITEMS: i32 = 25;
+const MAX_ITEM_NAME_LENGTH: usize = 64;
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+const LEADERBOARD_SIZE: i64 = 100;
+
+#[command]
+#[aliases("bal")]
+#[only_in(guilds)]
+#[description = "Shows your coins and items, or someone else's."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn balance(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_user_id) = (msg.guild_id.unwrap().get() as i64, user_id.get() as i64);
+
+    let balance = sqlx::query!("SELECT balance FROM economy_balances WHERE guild_id = ? AND user_id = ?", guild_id, db_user_id)
+        .fetch_optional(&database)
+        .await?
+        .map_or(0, |row| row.balance);
+
+    let items = sqlx::query!(
+        "SELECT name, quantity FROM economy_inventory JOIN shop_items ON shop_items.id = economy_inventory.item_id
+        WHERE economy_inventory.guild_id = ? AND user_id = ? AND quantity > 0 ORDER BY name",
+        guild_id,
+        db_user_id
+    ).fetch_all(&database).await?;
+
+    let user = user_id.to_user(ctx).await?;
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .author(CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+        .field("Balance", format!("{balance} {CURRENCY}"), false);
+
+    if !items.is_empty() {
+        let items = items.iter().map(|row| format!("{} x{}", row.name, row.quantity)).collect::<Vec<_>>().join("\n");
+        embed = embed.field("Items", items, false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Collects your daily coins. Can be used once every 24 hours, and pays extra while your voter perks are active."]
+#[num_args(0)]
+async fn daily(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (msg.guild_id.unwrap().get() as i64, msg.author.id.get() as i64);
+    let now = Utc::now();
+    let (collected_at, cutoff) = (format_time(now), format_time(now - DAILY_COOLDOWN));
+
+    let voted = get_vote_streak(&database, msg.author.id.get()).await?.is_some_and(|streak| streak.has_active_perk());
+    let amount = if voted { DAILY_AMOUNT + VOTER_DAILY_BONUS } else { DAILY_AMOUNT };
+
+    // the cooldown is checked in the same statement that pays out, so two claims at once can't both succeed
+    let collected = sqlx::query!(
+        "INSERT INTO economy_balances (guild_id, user_id, balance, last_daily_at) VALUES (?, ?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET
+            balance = balance + excluded.balance,
+            last_daily_at = excluded.last_daily_at
+        WHERE last_daily_at IS NULL OR last_daily_at <= ?
+        RETURNING balance",
+        guild_id,
+        user_id,
+        amount,
+        collected_at,
+        cutoff
+    ).fetch_optional(&database).await?;
+
+    if let Some(row) = collected {
+        let bonus = if voted { format!(" That includes {VOTER_DAILY_BONUS} {CURRENCY} for voting.") } else { String::new() };
+
+        msg.reply(ctx, format!("You collected {amount} {CURRENCY}!{bonus} You now have {} {CURRENCY}.", row.balance)).await?;
+        return Ok(());
+    }
+
+    let next = sqlx::query!("SELECT last_daily_at FROM economy_balances WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_optional(&database)
+        .await?
+        .and_then(|row| row.last_daily_at)
+        .and_then(|last| DateTime::parse_from_rfc3339(&last).ok())
+        .map(|last| last + DAILY_COOLDOWN);
+
+    match next {
+        Some(next) => msg.reply(ctx, format!("You've already collected your daily coins, come back <t:{}:R>.", next.timestamp())).await?,
+        None => msg.reply(ctx, "You've already collected your daily coins.").await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Gives some of your coins to another member."]
+#[usage = "<member> <amount>"]
+#[example = "@Kanzoey 50"]
+#[num_args(2)]
+async fn give(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = args.user_id()?;
+
+    let Some(amount) = args.single::<i64>().ok().filter(|amount| *amount > 0) else {
+        msg.reply(ctx, "Please give a positive amount of coins.").await?;
+        return Ok(());
+    };
+
+    if user_id == msg.author.id {
+        msg.reply(ctx, "You can't give coins to yourself.").await?;
+        return Ok(());
+    }
+
+    if user_id.to_user(ctx).await.is_ok_and(|user| user.bot) {
+        msg.reply(ctx, "Bots have no use for coins.").await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, sender_id, recipient_id) = (msg.guild_id.unwrap().get() as i64, msg.author.id.get() as i64, user_id.get() as i64);
+
+    let mut transaction = database.begin().await?;
+
+    let taken = sqlx::query!(
+        "UPDATE economy_balances SET balance = balance - ? WHERE guild_id = ? AND user_id = ? AND balance >= ?",
+        amount,
+        guild_id,
+        sender_id,
+        amount
+    ).execute(&mut *transaction).await?.rows_affected();
+
+    if taken == 0 {
+        msg.reply(ctx, format!("You don't have {amount} {CURRENCY}.")).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO economy_balances (guild_id, user_id, balance) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET balance = balance + excluded.balance",
+        guild_id,
+        recipient_id,
+        amount
+    ).execute(&mut *transaction).await?;
+
+    transaction.commit().await?;
+
+    msg.reply(ctx, format!("You gave {} {amount} {CURRENCY}.", user_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("coins")]
+#[only_in(guilds)]
+#[description = "Shows the members with the most coins in this server."]
+#[num_args(0)]
+async fn leaderboard_coins(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let top = sqlx::query!(
+        "SELECT user_id, balance FROM economy_balances WHERE guild_id = ? AND balance > 0 ORDER BY balance DESC LIMIT ?",
+        guild_id,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = top.iter()
+        .enumerate()
+        .map(|(index, row)| format!("**{}.** {} - {} {CURRENCY}", index + 1, UserId::new(row.user_id as u64).mention(), row.balance))
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody has any coins here yet. Collect some with `daily`!".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Richest members");
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists what can be bought in this server's shop."]
+#[sub_commands(shop_buy, shop_addrole, shop_additem, shop_remove)]
+async fn shop(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let items = sqlx::query!(
+        "SELECT id, name, description, price, role_id FROM shop_items WHERE guild_id = ? ORDER BY price, id",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if items.is_empty() {
+        "The shop is empty.".to_string()
+    } else {
+        items.iter()
+            .map(|row| {
+                let name = row.role_id.map_or_else(|| row.name.clone(), |role_id| RoleId::new(role_id as u64).mention().to_string());
+                let details = row.description.as_deref().map_or_else(String::new, |description| format!("\n{description}"));
+
+                format!("**#{}** {name} - {} {CURRENCY}{details}", row.id, row.price)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Shop")
+        .description(description)
+        .footer(CreateEmbedFooter::new("Buy something with `shop buy <id>`."));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("buy")]
+#[only_in(guilds)]
+#[description = "Buys something from the shop."]
+#[usage = "<id>"]
+#[example = "3"]
+#[num_args(1)]
+async fn shop_buy(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, user_id) = (guild_id.get() as i64, msg.author.id.get() as i64);
+
+    let Some(item) = sqlx::query!("SELECT name, price, role_id FROM shop_items WHERE id = ? AND guild_id = ?", id, db_guild_id)
+        .fetch_optional(&database)
+        .await? else {
+        msg.reply(ctx, format!("There is no shop item #{id}.")).await?;
+        return Ok(());
+    };
+
+    let role_id = item.role_id.map(|role_id| RoleId::new(role_id as u64));
+
+    if let Some(role_id) = role_id {
+        if msg.member(ctx).await.is_ok_and(|member| member.roles.contains(&role_id)) {
+            msg.reply(ctx, format!("You already have {}.", role_id.mention())).await?;
+            return Ok(());
+        }
+    }
+
+    let mut transaction = database.begin().await?;
+
+    let paid = sqlx::query!(
+        "UPDATE economy_balances SET balance = balance - ? WHERE guild_id = ? AND user_id = ? AND balance >= ?",
+        item.price,
+        db_guild_id,
+        user_id,
+        item.price
+    ).execute(&mut *transaction).await?.rows_affected();
+
+    if paid == 0 {
+        msg.reply(ctx, format!("You need {} {CURRENCY} to buy that.", item.price)).await?;
+        return Ok(());
+    }
+
+    if role_id.is_none() {
+        sqlx::query!(
+            "INSERT INTO economy_inventory (guild_id, user_id, item_id, quantity) VALUES (?, ?, ?, 1)
+            ON CONFLICT (guild_id, user_id, item_id) DO UPDATE SET quantity = quantity + 1",
+            db_guild_id,
+            user_id,
+            id
+        ).execute(&mut *transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    if let Some(role_id) = role_id {
+        if let Err(why) = ctx.http.add_member_role(guild_id, msg.author.id, role_id, Some("Bought from the shop")).await {
+            sqlx::query!(
+                "UPDATE economy_balances SET balance = balance + ? WHERE guild_id = ? AND user_id = ?",
+                item.price,
+                db_guild_id,
+                user_id
+            ).execute(&database).await?;
+
+            msg.reply(ctx, format!("I couldn't give you {}, so you've been refunded: {why}", role_id.mention())).await?;
+            return Ok(());
+        }
+    }
+
+    match role_id {
+        Some(role_id) => msg.reply(ctx, format!("You bought {} for {} {CURRENCY}.", role_id.mention(), item.price)).await?,
+        None => msg.reply(ctx, format!("You bought **{}** for {} {CURRENCY}.", item.name, item.price)).await?
+    };
+
+    Ok(())
+}
+
+#[command("addrole")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Puts a role up for sale in the shop."]
+#[usage = "<price> <role> [description]"]
+#[example = "500 @VIP Shows everyone how rich you are"]
+#[min_args(2)]
+async fn shop_addrole(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(price) = args.single::<i64>().ok().filter(|price| *price > 0) else {
+        msg.reply(ctx, "Please give a positive price.").await?;
+        return Ok(());
+    };
+
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let name = guild_id.to_guild_cached(&ctx.cache)
+        .and_then(|guild| guild.roles.get(&role_id).map(|role| role.name.clone()))
+        .unwrap_or_else(|| role_id.to_string());
+
+    add_item(ctx, msg, name, args.rest(), price, Some(role_id)).await
+}
+
+#[command("additem")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Puts an item up for sale in the shop. Bought items are kept in members' inventories, shown by `balance`."]
+#[usage = "<price> <name> [| description]"]
+#[example = "50 Cookie | A freshly baked cookie"]
+#[min_args(2)]
+async fn shop_additem(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(price) = args.single::<i64>().ok().filter(|price| *price > 0) else {
+        msg.reply(ctx, "Please give a positive price.").await?;
+        return Ok(());
+    };
+
+    let (name, description) = args.rest().split_once('|').unwrap_or((args.rest(), ""));
+    let name = name.trim().to_string();
+
+    if name.is_empty() || name.chars().count() > MAX_ITEM_NAME_LENGTH {
+        msg.reply(ctx, format!("Item names must be 1 to {MAX_ITEM_NAME_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    add_item(ctx, msg, name, description, price, None).await
+}
+
+async fn add_item(ctx: &Context, msg: &Message, name: String, description: &str, price: i64, role_id: Option<RoleId>) -> CommandResult {
+    let description = Some(description.trim().to_string()).filter(|description| !description.is_empty());
+
+    if description.as_ref().is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+        msg.reply(ctx, format!("Descriptions can be at most {MAX_DESCRIPTION_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM shop_items WHERE guild_id = ?", guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count >= MAX_SHOP_ITEMS {
+        msg.reply(ctx, format!("The shop already has the maximum of {MAX_SHOP_ITEMS} items.")).await?;
+        return Ok(());
+    }
+
+    let (db_role_id, created_by) = (role_id.map(|role_id| role_id.get() as i64), msg.author.id.get() as i64);
+
+    let id = sqlx::query!(
+        "INSERT INTO shop_items (guild_id, name, description, price, role_id, created_by) VALUES (?, ?, ?, ?, ?, ?)",
+        guild_id,
+        name,
+        description,
+        price,
+        db_role_id,
+        created_by
+    ).execute(&database).await?.last_insert_rowid();
+
+    msg.reply(ctx, format!("Added shop item #{id}, **{name}**, for {price} {CURRENCY}.")).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Takes an item out of the shop, and out of everyone's inventory. Members keep roles they already bought."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn shop_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let mut transaction = database.begin().await?;
+
+    let deleted = sqlx::query!("DELETE FROM shop_items WHERE id = ? AND guild_id = ?", id, guild_id)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+
+    sqlx::query!("DELETE FROM economy_inventory WHERE item_id = ? AND guild_id = ?", id, guild_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no shop item #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Removed shop item #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+/// Formats a time the way it's stored in `economy_balances.last_daily_at`. A fixed format keeps
+/// the stored times comparable as text.
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,170 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::events::{MAX_REMINDER_MINUTES, event_link, event_location, event_start, resync_event_reminders};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{Paginator, embed_pages, paginate_lines};
+
+const EVENTS_PER_PAGE: usize = 5;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's upcoming events and how many members are interested in each."]
+#[sub_commands(events_channel, events_announce, events_reminder)]
+#[num_args(0)]
+async fn events(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let mut events = guild_id.scheduled_events(ctx, true).await?
+        .into_iter()
+        .filter(|event| matches!(event.status, ScheduledEventStatus::Scheduled | ScheduledEventStatus::Active))
+        .collect::<Vec<_>>();
+
+    if events.is_empty() {
+        msg.reply(ctx, "This server has no upcoming events.").await?;
+        return Ok(());
+    }
+
+    events.sort_by_key(event_start);
+
+    let lines = events.iter()
+        .map(|event| {
+            let when = match event.status {
+                ScheduledEventStatus::Active => "**happening now**".to_string(),
+                _ => format!("<t:{}:R>", event_start(event).timestamp())
+            };
+
+            let mut line = format!("**[{}]({})**\n{when}", event.name.replace(['[', ']'], ""), event_link(guild_id, event.id));
+
+            if let Some(location) = event_location(event) {
+                line.push_str(&format!(" in {location}"));
+            }
+
+            line.push_str(&format!(" - {} interested", event.user_count.unwrap_or(0)));
+
+            line
+        })
+        .collect::<Vec<_>>();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Upcoming events");
+
+    Paginator::new(embed_pages(embed, paginate_lines(&lines, EVENTS_PER_PAGE, "\n\n"))).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channel new events are announced in and reminders are posted in. Use `off` to stop both."]
+#[usage = "<channel|off>"]
+#[example = "#events"]
+#[num_args(1)]
+async fn events_channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.map(|channel_id| channel_id.get() as i64));
+
+    sqlx::query!(
+        "INSERT INTO event_settings (guild_id, channel_id) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET channel_id = excluded.channel_id",
+        db_guild_id,
+        db_channel_id
+    ).execute(&database).await?;
+
+    resync_event_reminders(ctx, &database, guild_id).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Events will be announced and reminded about in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Events won't be announced or reminded about anymore.").await?
+    };
+
+    Ok(())
+}
+
+#[command("announce")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether newly created events are announced in the events channel. Reminders are set separately."]
+#[usage = "<on|off>"]
+#[example = "off"]
+#[num_args(1)]
+async fn events_announce(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let announce = match args.rest().trim() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, announce_value) = (msg.guild_id.unwrap().get() as i64, i64::from(announce));
+
+    sqlx::query!(
+        "INSERT INTO event_settings (guild_id, announce) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET announce = excluded.announce",
+        guild_id,
+        announce_value
+    ).execute(&database).await?;
+
+    if announce {
+        msg.reply(ctx, "New events will be announced.").await?;
+    } else {
+        msg.reply(ctx, "New events won't be announced anymore.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("reminder")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how many minutes before an event starts a reminder is posted in the events channel. Use `off` to stop reminders."]
+#[usage = "<minutes|off>"]
+#[example = "30"]
+#[num_args(1)]
+async fn events_reminder(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let minutes = match args.rest().trim() {
+        "off" => None,
+        minutes => match minutes.parse::<i64>() {
+            Ok(minutes) if (1..=MAX_REMINDER_MINUTES).contains(&minutes) => Some(minutes),
+            _ => {
+                msg.reply(ctx, format!("Please give a number of minutes from 1 to {MAX_REMINDER_MINUTES}, or `off`.")).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO event_settings (guild_id, reminder_minutes) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET reminder_minutes = excluded.reminder_minutes",
+        db_guild_id,
+        minutes
+    ).execute(&database).await?;
+
+    // events that already exist get their reminders moved too
+    resync_event_reminders(ctx, &database, guild_id).await?;
+
+    match minutes {
+        Some(minutes) => msg.reply(ctx, format!("Reminders will be posted {minutes} minutes before events start.")).await?,
+        None => msg.reply(ctx, "Reminders won't be posted for events anymore.").await?
+    };
+
+    Ok(())
+}
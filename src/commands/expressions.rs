@@ -0,0 +1,532 @@
+use std::future::Future;
+use std::time::Duration;
+
+use serenity::builder::{CreateAttachment, CreateMessage, CreateSticker};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::parse_emoji;
+
+use crate::utilities::branding::guild_branding;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::ReqwestClientContainer;
+
+/// Largest image Discord accepts for an emoji.
+const MAX_EMOJI_SIZE: usize = 256 * 1024;
+
+/// Largest image Discord accepts for a sticker.
+const MAX_STICKER_SIZE: usize = 512 * 1024;
+
+/// Most emojis that can be stolen with one command, since every upload counts towards the rate
+/// limit.
+const MAX_STEALS: usize = 5;
+
+/// How long an upload can take before the member is told Discord's rate limits are holding it up.
+const RATE_LIMIT_NOTICE: Duration = Duration::from_secs(5);
+
+struct Image {
+    data: Vec<u8>,
+    content_type: String
+}
+
+impl Image {
+    fn is_animated(&self) -> bool {
+        self.content_type == "image/gif"
+    }
+
+    fn extension(&self) -> &'static str {
+        match self.content_type.as_str() {
+            "image/gif" => "gif",
+            "image/jpeg" => "jpg",
+            "image/webp" => "webp",
+            _ => "png"
+        }
+    }
+
+    /// The image as a data URI, the way Discord takes emoji images.
+    fn to_data_uri(&self) -> String {
+        // serenity only encodes PNGs, so the prefix is swapped for the real type
+        let encoded = CreateAttachment::bytes(self.data.clone(), "emoji").to_base64();
+        encoded.replacen("image/png", &self.content_type, 1)
+    }
+}
+
+/// Downloads an image, making sure it's one Discord takes and isn't too large.
+async fn download_image(ctx: &Context, url: &str, max_size: usize, content_types: &[&str]) -> Result<Image, String> {
+    let client = get_data::<ReqwestClientContainer>(ctx).await.map_err(|_| "I couldn't download that image.".to_string())?;
+
+    let response = client.get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|_| "I couldn't download that image.".to_string())?;
+
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(|content_type| content_type.split(';').next().unwrap_or_default().trim().to_lowercase())
+        .unwrap_or_default();
+
+    if !content_types.contains(&content_type.as_str()) {
+        let kinds = content_types.iter().map(|kind| kind.trim_start_matches("image/").to_uppercase()).collect::<Vec<_>>().join(", ");
+        return Err(format!("That isn't a supported image, it has to be one of {kinds}."));
+    }
+
+    let too_large = || format!("That image is too large, it can be at most {} KB.", max_size / 1024);
+
+    if response.content_length().is_some_and(|length| length as usize > max_size) {
+        return Err(too_large());
+    }
+
+    let data = response.bytes().await.map_err(|_| "I couldn't download that image.".to_string())?;
+
+    if data.len() > max_size {
+        return Err(too_large());
+    }
+
+    Ok(Image { data: data.to_vec(), content_type })
+}
+
+/// Runs an upload, telling the member if Discord's rate limits hold it up. Serenity waits the
+/// limit out, so the upload still goes through once it's over.
+async fn upload<T>(ctx: &Context, msg: &Message, request: impl Future<Output = Result<T, SerenityError>>) -> Result<T, SerenityError> {
+    tokio::pin!(request);
+
+    match tokio::time::timeout(RATE_LIMIT_NOTICE, &mut request).await {
+        Ok(result) => result,
+        Err(_) => {
+            msg.reply(ctx, "Discord is rate limiting uploads to this server, so this may take a while. I'll reply once it's done.").await?;
+            request.await
+        }
+    }
+}
+
+/// Whether the bot can manage this guild's emojis and stickers, replying if it can't.
+async fn bot_can_manage(ctx: &Context, msg: &Message) -> Result<bool, SerenityError> {
+    let guild_id = msg.guild_id.unwrap();
+    let bot_id = ctx.cache.current_user().id;
+    let bot_member = guild_id.member(ctx, bot_id).await?;
+
+    let can_manage = guild_id.to_guild_cached(&ctx.cache)
+        .is_some_and(|guild| guild.member_permissions(&bot_member).manage_guild_expressions());
+
+    if !can_manage {
+        msg.reply(ctx, "I need the Manage Expressions permission to do that.").await?;
+    }
+
+    Ok(can_manage)
+}
+
+/// How many emojis of each kind the guild can have at its boost level.
+fn emoji_limit(tier: PremiumTier) -> usize {
+    match tier {
+        PremiumTier::Tier1 => 100,
+        PremiumTier::Tier2 => 150,
+        PremiumTier::Tier3 => 250,
+        _ => 50
+    }
+}
+
+/// How many stickers the guild can have at its boost level.
+fn sticker_limit(tier: PremiumTier) -> usize {
+    match tier {
+        PremiumTier::Tier1 => 15,
+        PremiumTier::Tier2 => 30,
+        PremiumTier::Tier3 => 60,
+        _ => 5
+    }
+}
+
+/// Why an emoji of this kind can't be added, if the guild is out of slots for it.
+fn emoji_slots_error(ctx: &Context, guild_id: GuildId, animated: bool) -> Option<String> {
+    let guild = guild_id.to_guild_cached(&ctx.cache)?;
+    let limit = emoji_limit(guild.premium_tier);
+    let used = guild.emojis.values().filter(|emoji| emoji.animated == animated).count();
+
+    (used >= limit).then(|| format!("This server already has the maximum of {limit} {} emojis.", if animated { "animated" } else { "static" }))
+}
+
+fn is_valid_emoji_name(name: &str) -> bool {
+    (2..=32).contains(&name.chars().count()) && name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Finds one of the guild's emojis by mention, ID or name.
+fn find_emoji(ctx: &Context, guild_id: GuildId, arg: &str) -> Option<Emoji> {
+    let guild = guild_id.to_guild_cached(&ctx.cache)?;
+    let id = parse_emoji(arg).map(|emoji| emoji.id).or_else(|| arg.parse::<u64>().ok().filter(|id| *id != 0).map(EmojiId::new));
+
+    match id {
+        Some(id) => guild.emojis.get(&id).cloned(),
+        None => guild.emojis.values().find(|emoji| emoji.name.eq_ignore_ascii_case(arg.trim_matches(':'))).cloned()
+    }
+}
+
+#[command]
+#[aliases("emote")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Lists this server's emojis, and how many more it can have at its boost level."]
+#[sub_commands(emoji_add, emoji_steal, emoji_remove, emoji_list)]
+async fn emoji(ctx: &Context, msg: &Message) -> CommandResult {
+    list_emojis(ctx, msg).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Adds an emoji from an image link or an attached image. Emoji images can be PNG, JPEG, GIF or WEBP and at most 256 KB."]
+#[usage = "<name> [image link]"]
+#[example = "party_parrot https://example.com/parrot.gif"]
+#[min_args(1)]
+#[max_args(2)]
+async fn emoji_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?;
+
+    if !is_valid_emoji_name(&name) {
+        msg.reply(ctx, "Emoji names have to be 2 to 32 letters, numbers or underscores.").await?;
+        return Ok(());
+    }
+
+    let Some(url) = args.single::<String>().ok().or_else(|| msg.attachments.first().map(|attachment| attachment.url.clone())) else {
+        msg.reply(ctx, "Please give an image link or attach an image.").await?;
+        return Ok(());
+    };
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    let url = url.trim_start_matches('<').trim_end_matches('>');
+
+    let image = match download_image(ctx, url, MAX_EMOJI_SIZE, &["image/png", "image/jpeg", "image/gif", "image/webp"]).await {
+        Ok(image) => image,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    add_emoji(ctx, msg, &name, &image).await
+}
+
+#[command("steal")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Adds custom emojis from another server to this one, keeping their names. Up to 5 at a time."]
+#[usage = "<emojis...>"]
+#[example = ":blobwave: :blobheart:"]
+#[min_args(1)]
+async fn emoji_steal(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let emojis: Vec<EmojiIdentifier> = args.rest().split_whitespace().filter_map(parse_emoji).collect();
+
+    if emojis.is_empty() {
+        msg.reply(ctx, "Please give custom emojis to steal, I can't add Discord's built-in ones.").await?;
+        return Ok(());
+    }
+
+    if emojis.len() > MAX_STEALS {
+        msg.reply(ctx, format!("I can steal at most {MAX_STEALS} emojis at a time.")).await?;
+        return Ok(());
+    }
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    for stolen in emojis {
+        let image = match download_image(ctx, &stolen.url(), MAX_EMOJI_SIZE, &["image/png", "image/gif", "image/webp"]).await {
+            Ok(image) => image,
+            Err(why) => {
+                msg.reply(ctx, format!("Couldn't steal `{}`: {why}", stolen.name)).await?;
+                continue;
+            }
+        };
+
+        add_emoji(ctx, msg, &stolen.name, &image).await?;
+    }
+
+    Ok(())
+}
+
+async fn add_emoji(ctx: &Context, msg: &Message, name: &str, image: &Image) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Some(why) = emoji_slots_error(ctx, guild_id, image.is_animated()) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let data_uri = image.to_data_uri();
+
+    match upload(ctx, msg, guild_id.create_emoji(ctx, name, &data_uri)).await {
+        Ok(emoji) => msg.reply(ctx, format!("Added {emoji} as `:{}:`.", emoji.name)).await?,
+        Err(why) => msg.reply(ctx, format!("Discord didn't accept `{name}`: {why}")).await?
+    };
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Removes one of this server's emojis."]
+#[usage = "<emoji|name|id>"]
+#[example = ":party_parrot:"]
+#[num_args(1)]
+async fn emoji_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let Some(emoji) = find_emoji(ctx, guild_id, &args.single::<String>()?) else {
+        msg.reply(ctx, "That isn't one of this server's emojis.").await?;
+        return Ok(());
+    };
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    guild_id.delete_emoji(ctx, emoji.id).await?;
+
+    msg.reply(ctx, format!("Removed the `:{}:` emoji.", emoji.name)).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Lists this server's emojis, and how many more it can have at its boost level."]
+#[num_args(0)]
+async fn emoji_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_emojis(ctx, msg).await
+}
+
+async fn list_emojis(ctx: &Context, msg: &Message) -> CommandResult {
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let embed = {
+        let Some(guild) = msg.guild(&ctx.cache) else {
+            return Ok(());
+        };
+
+        let limit = emoji_limit(guild.premium_tier);
+        let mut emojis: Vec<&Emoji> = guild.emojis.values().collect();
+        emojis.sort_by_key(|emoji| emoji.name.to_lowercase());
+
+        let (animated, still): (Vec<&Emoji>, Vec<&Emoji>) = emojis.into_iter().partition(|emoji| emoji.animated);
+
+        // keep large servers from pushing the list past the field limit
+        let render = |emojis: &[&Emoji]| {
+            let mut rendered = String::new();
+
+            for emoji in emojis {
+                let next = format!("{emoji} ");
+
+                if rendered.len() + next.len() > 1000 {
+                    rendered.push('…');
+                    break;
+                }
+
+                rendered.push_str(&next);
+            }
+
+            if rendered.is_empty() { "None".to_string() } else { rendered }
+        };
+
+        branding.embed()
+            .title(format!("Emojis in {}", guild.name))
+            .field(format!("Static ({}/{limit})", still.len()), render(&still), false)
+            .field(format!("Animated ({}/{limit})", animated.len()), render(&animated), false)
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Lists this server's stickers, and how many more it can have at its boost level."]
+#[sub_commands(sticker_add, sticker_steal, sticker_remove, sticker_list)]
+async fn sticker(ctx: &Context, msg: &Message) -> CommandResult {
+    list_stickers(ctx, msg).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Adds a sticker from an image link or an attached image. The emoji is the one it's suggested for. Sticker images can be PNG, APNG or GIF, at most 512 KB and ideally 320x320."]
+#[usage = "<name> <emoji> [image link]"]
+#[example = "wave 👋 https://example.com/wave.png"]
+#[min_args(2)]
+#[max_args(3)]
+async fn sticker_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single_quoted::<String>()?;
+    let tag = args.single::<String>()?;
+
+    let Some(url) = args.single::<String>().ok().or_else(|| msg.attachments.first().map(|attachment| attachment.url.clone())) else {
+        msg.reply(ctx, "Please give an image link or attach an image.").await?;
+        return Ok(());
+    };
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    let url = url.trim_start_matches('<').trim_end_matches('>');
+
+    let image = match download_image(ctx, url, MAX_STICKER_SIZE, &["image/png", "image/apng", "image/gif"]).await {
+        Ok(image) => image,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    add_sticker(ctx, msg, &name, &tag, &image).await
+}
+
+#[command("steal")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Adds the sticker of the message you're replying to, or the one sent with the command, to this server. Give a name to rename it."]
+#[usage = "[name]"]
+#[example = "wave"]
+async fn sticker_steal(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let item = msg.referenced_message.as_ref()
+        .and_then(|replied| replied.sticker_items.first())
+        .or_else(|| msg.sticker_items.first())
+        .cloned();
+
+    let Some(item) = item else {
+        msg.reply(ctx, "Please reply to a message with a sticker, or send one with the command.").await?;
+        return Ok(());
+    };
+
+    let Some(url) = item.image_url().filter(|_| item.format_type != StickerFormatType::Lottie) else {
+        msg.reply(ctx, "That sticker is animated in a format only Discord can upload.").await?;
+        return Ok(());
+    };
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    // the suggested emoji is kept if the sticker can still be looked up
+    let tag = item.to_sticker(ctx).await.ok()
+        .and_then(|sticker| sticker.tags.first().cloned())
+        .unwrap_or_else(|| "⭐".to_string());
+
+    let name = Some(args.rest().trim()).filter(|name| !name.is_empty()).unwrap_or(&item.name).to_string();
+
+    let image = match download_image(ctx, &url, MAX_STICKER_SIZE, &["image/png", "image/apng", "image/gif"]).await {
+        Ok(image) => image,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    add_sticker(ctx, msg, &name, &tag, &image).await
+}
+
+async fn add_sticker(ctx: &Context, msg: &Message, name: &str, tag: &str, image: &Image) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if !(2..=30).contains(&name.chars().count()) {
+        msg.reply(ctx, "Sticker names have to be 2 to 30 characters long.").await?;
+        return Ok(());
+    }
+
+    let full = guild_id.to_guild_cached(&ctx.cache)
+        .map(|guild| (guild.stickers.len(), sticker_limit(guild.premium_tier)))
+        .filter(|(used, limit)| used >= limit);
+
+    if let Some((_, limit)) = full {
+        msg.reply(ctx, format!("This server already has the maximum of {limit} stickers.")).await?;
+        return Ok(());
+    }
+
+    let file = CreateAttachment::bytes(image.data.clone(), format!("sticker.{}", image.extension()));
+    let builder = CreateSticker::new(name, file).tags(tag);
+
+    match upload(ctx, msg, guild_id.create_sticker(ctx, builder)).await {
+        Ok(sticker) => msg.reply(ctx, format!("Added the **{}** sticker.", sticker.name)).await?,
+        Err(why) => msg.reply(ctx, format!("Discord didn't accept the sticker: {why}")).await?
+    };
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Removes one of this server's stickers."]
+#[usage = "<name|id>"]
+#[example = "wave"]
+#[min_args(1)]
+async fn sticker_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let arg = args.rest().trim();
+
+    let sticker = guild_id.to_guild_cached(&ctx.cache).and_then(|guild| {
+        guild.stickers.values()
+            .find(|sticker| sticker.id.to_string() == arg || sticker.name.eq_ignore_ascii_case(arg))
+            .map(|sticker| (sticker.id, sticker.name.clone()))
+    });
+
+    let Some((sticker_id, name)) = sticker else {
+        msg.reply(ctx, "That isn't one of this server's stickers.").await?;
+        return Ok(());
+    };
+
+    if !bot_can_manage(ctx, msg).await? {
+        return Ok(());
+    }
+
+    guild_id.delete_sticker(ctx, sticker_id).await?;
+
+    msg.reply(ctx, format!("Removed the **{name}** sticker.")).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD_EXPRESSIONS)]
+#[description = "Lists this server's stickers, and how many more it can have at its boost level."]
+#[num_args(0)]
+async fn sticker_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_stickers(ctx, msg).await
+}
+
+async fn list_stickers(ctx: &Context, msg: &Message) -> CommandResult {
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let embed = {
+        let Some(guild) = msg.guild(&ctx.cache) else {
+            return Ok(());
+        };
+
+        let mut names: Vec<&str> = guild.stickers.values().map(|sticker| sticker.name.as_str()).collect();
+        names.sort_by_key(|name| name.to_lowercase());
+
+        let description = if names.is_empty() {
+            "This server has no stickers yet.".to_string()
+        } else {
+            names.iter().map(|name| format!("• {name}")).collect::<Vec<_>>().join("\n")
+        };
+
+        branding.embed()
+            .title(format!("Stickers in {} ({}/{})", guild.name, names.len(), sticker_limit(guild.premium_tier)))
+            .description(description)
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
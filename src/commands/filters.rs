@@ -0,0 +1,274 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::{parse_channel_mention, parse_role_mention};
+
+use crate::utilities::automod::{AutomodAction, parse_ids};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::filters::{FilterMode, reload_filters};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most word filters a single guild can have.
+const MAX_FILTERS: i32 = 100;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Lists this server's word filters. Messages using a filtered word, whether sent or edited in, are removed and their author is acted on as the filter says."]
+#[sub_commands(filter_add, filter_remove, filter_list, filter_action, filter_exempt)]
+async fn filter(ctx: &Context, msg: &Message) -> CommandResult {
+    list_filters(ctx, msg).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Adds a word filter. Match modes are `exact` (the default, whole words only), `wildcard` (`*` for any part of a word and `?` for any character) and `regex`. Actions are `delete` (the default), `warn`, `mute` and `ban`. Quote phrases with spaces."]
+#[usage = "[mode] <word or phrase> [action]"]
+#[example = "wildcard \"free nitro*\" ban"]
+#[min_args(1)]
+#[max_args(3)]
+async fn filter_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let first = args.single_quoted::<String>()?;
+
+    // the mode is optional, a lone mode name is treated as the word to filter
+    let (mode, pattern) = match FilterMode::parse(&first) {
+        Some(mode) if !args.is_empty() => (mode, args.single_quoted::<String>()?),
+        _ => (FilterMode::Exact, first)
+    };
+
+    let action = match args.single_quoted::<String>() {
+        Ok(action) => match AutomodAction::parse(&action) {
+            Some(action) => action,
+            None => {
+                msg.reply(ctx, format!("`{action}` isn't an action. Actions are `delete`, `warn`, `mute` and `ban`.")).await?;
+                return Ok(());
+            }
+        },
+        Err(_) => AutomodAction::Delete
+    };
+
+    if pattern.trim().is_empty() {
+        msg.reply(ctx, "Please give a word or phrase to filter.").await?;
+        return Ok(());
+    }
+
+    if let Err(why) = mode.compile(&pattern) {
+        msg.reply(ctx, format!("That filter isn't valid:\n```{why}```")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM filters WHERE guild_id = ?", db_guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count >= MAX_FILTERS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_FILTERS} word filters.")).await?;
+        return Ok(());
+    }
+
+    let (mode_name, action_name) = (mode.as_str(), action.as_str());
+    let created_by = msg.author.id.get() as i64;
+
+    let id = sqlx::query!(
+        "INSERT INTO filters (guild_id, pattern, match_mode, action, created_by) VALUES (?, ?, ?, ?, ?)",
+        db_guild_id,
+        pattern,
+        mode_name,
+        action_name,
+        created_by
+    ).execute(&database).await?.last_insert_rowid();
+
+    reload_filters(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Added word filter #{id} for `{pattern}` ({mode_name}, {action_name}).")).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Removes a word filter."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn filter_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM filters WHERE id = ? AND guild_id = ?", id, db_guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if removed == 0 {
+        msg.reply(ctx, format!("There is no word filter #{id}.")).await?;
+        return Ok(());
+    }
+
+    reload_filters(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Removed word filter #{id}.")).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Lists this server's word filters and the channels and roles they ignore."]
+#[num_args(0)]
+async fn filter_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_filters(ctx, msg).await
+}
+
+async fn list_filters(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let filters = sqlx::query!("SELECT id, pattern, match_mode, action FROM filters WHERE guild_id = ? ORDER BY id", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let exemptions = sqlx::query!("SELECT exempt_channels, exempt_roles FROM filter_exemptions WHERE guild_id = ?", guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let description = if filters.is_empty() {
+        "This server has no word filters yet. Use `filter add` to add one.".to_string()
+    } else {
+        let mut description = String::new();
+
+        for row in &filters {
+            let line = format!("**#{}** `{}` ({}) → {}\n", row.id, row.pattern, row.match_mode, row.action);
+
+            // long regexes could push the list past the embed limit
+            if description.len() + line.len() > 4000 {
+                description.push('…');
+                break;
+            }
+
+            description.push_str(&line);
+        }
+
+        description
+    };
+
+    let mut exempt: Vec<String> = Vec::new();
+
+    if let Some(exemptions) = exemptions {
+        exempt.extend(parse_ids(&exemptions.exempt_channels).into_iter().filter(|id| *id != 0).map(|id| ChannelId::new(id).mention().to_string()));
+        exempt.extend(parse_ids(&exemptions.exempt_roles).into_iter().filter(|id| *id != 0).map(|id| RoleId::new(id).mention().to_string()));
+    }
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Word filters")
+        .description(description)
+        .field("Exempt", if exempt.is_empty() { "Nothing".to_string() } else { exempt.join(" ") }, false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("action")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets what happens to the author of a message using a filtered word. Their message is always removed: `delete` does nothing more, `warn` warns them, `mute` mutes them for 10 minutes and `ban` bans them."]
+#[usage = "<id> <delete|warn|mute|ban>"]
+#[example = "3 warn"]
+#[num_args(2)]
+async fn filter_action(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let Some(action) = AutomodAction::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "Actions are `delete`, `warn`, `mute` and `ban`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let action_name = action.as_str();
+
+    let updated = sqlx::query!("UPDATE filters SET action = ? WHERE id = ? AND guild_id = ?", action_name, id, db_guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if updated == 0 {
+        msg.reply(ctx, format!("There is no word filter #{id}.")).await?;
+        return Ok(());
+    }
+
+    reload_filters(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Word filter #{id} now uses the `{action_name}` action.")).await?;
+
+    Ok(())
+}
+
+#[command("exempt")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channels and roles every word filter ignores, or `none` to filter everywhere."]
+#[usage = "<channels and roles...|none>"]
+#[example = "#staff-chat @Moderators"]
+#[min_args(1)]
+async fn filter_exempt(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let mut channels = Vec::new();
+    let mut roles = Vec::new();
+
+    if !args.rest().trim().eq_ignore_ascii_case("none") {
+        for mention in args.rest().split_whitespace() {
+            if let Some(channel_id) = parse_channel_mention(mention) {
+                channels.push(channel_id.to_string());
+            } else if let Some(role_id) = parse_role_mention(mention) {
+                roles.push(role_id.to_string());
+            } else {
+                msg.reply(ctx, format!("`{mention}` isn't a channel or role mention.")).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let (channels, roles) = (channels.join(","), roles.join(","));
+
+    sqlx::query!(
+        "INSERT INTO filter_exemptions (guild_id, exempt_channels, exempt_roles) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET exempt_channels = excluded.exempt_channels, exempt_roles = excluded.exempt_roles",
+        db_guild_id,
+        channels,
+        roles
+    ).execute(&database).await?;
+
+    reload_filters(ctx, guild_id).await?;
+
+    if channels.is_empty() && roles.is_empty() {
+        msg.reply(ctx, "Word filters now apply everywhere.").await?;
+    } else {
+        msg.reply(ctx, "Updated the channels and roles word filters ignore.").await?;
+    }
+
+    Ok(())
+}
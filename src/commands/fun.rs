@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serenity::builder::{CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::{Branding, branded_embed, guild_branding};
+use crate::utilities::dice::roll_dice;
+
+const EIGHT_BALL_ANSWERS: &[&str] = &[
+    "It is certain.",
+    "It is decidedly so.",
+    "Without a doubt.",
+    "Yes, definitely.",
+    "You may rely on it.",
+    "As I see it, yes.",
+    "Most likely.",
+    "Outlook good.",
+    "Yes.",
+    "Signs point to yes.",
+    "Reply hazy, try again.",
+    "Ask again later.",
+    "Better not tell you now.",
+    "Cannot predict now.",
+    "Concentrate and ask again.",
+    "Don't count on it.",
+    "My reply is no.",
+    "My sources say no.",
+    "Outlook not so good.",
+    "Very doubtful."
+];
+
+const MAX_CHOICES: usize = 50;
+
+/// How long the rock-paper-scissors buttons wait for a pick.
+const RPS_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hand {
+    Rock,
+    Paper,
+    Scissors
+}
+
+impl Hand {
+    const ALL: [Hand; 3] = [Hand::Rock, Hand::Paper, Hand::Scissors];
+
+    fn parse(arg: &str) -> Option<Hand> {
+        match arg.to_lowercase().as_str() {
+            "rock" | "r" => Some(Hand::Rock),
+            "paper" | "p" => Some(Hand::Paper),
+            "scissors" | "scissor" | "s" => Some(Hand::Scissors),
+            _ => None
+        }
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            Hand::Rock => "rps_rock",
+            Hand::Paper => "rps_paper",
+            Hand::Scissors => "rps_scissors"
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Hand::Rock => "Rock",
+            Hand::Paper => "Paper",
+            Hand::Scissors => "Scissors"
+        }
+    }
+
+    fn emoji(self) -> char {
+        match self {
+            Hand::Rock => '🪨',
+            Hand::Paper => '📄',
+            Hand::Scissors => '✂'
+        }
+    }
+
+    fn beats(self, other: Hand) -> bool {
+        matches!((self, other), (Hand::Rock, Hand::Scissors) | (Hand::Paper, Hand::Rock) | (Hand::Scissors, Hand::Paper))
+    }
+}
+
+#[command("8ball")]
+#[description = "Answers a yes or no question, with all the wisdom of a plastic ball."]
+#[usage = "<question>"]
+#[example = "Will it rain tomorrow?"]
+#[min_args(1)]
+async fn eight_ball(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let answer = *EIGHT_BALL_ANSWERS.choose(&mut rand::thread_rng()).unwrap();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("🎱 {}", args.rest().trim()))
+        .description(answer);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).reference_message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Flips a coin."]
+async fn coinflip(ctx: &Context, msg: &Message) -> CommandResult {
+    let side = if rand::thread_rng().gen_bool(0.5) { "Heads" } else { "Tails" };
+
+    msg.reply(ctx, format!("🪙 **{side}**!")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Rolls dice written in dice notation. `2d20` rolls two twenty-sided dice, `d%` a hundred-sided one, `4d6kh3` keeps the highest three of four and `kl` keeps the lowest. Dice and numbers can be added or subtracted. Rolls a six-sided die when no dice are given."]
+#[usage = "[dice]"]
+#[example = "2d20+3"]
+async fn roll(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let expression = Some(args.rest().trim()).filter(|expression| !expression.is_empty()).unwrap_or("d6");
+
+    let roll = match roll_dice(expression) {
+        Ok(roll) => roll,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("🎲 {expression}"))
+        .description(format!("**{}**\n{}", roll.total, roll.breakdown));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).reference_message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Picks one of the given options, separated by `|`."]
+#[usage = "<option> | <option> ..."]
+#[example = "pizza | tacos | sushi"]
+#[min_args(1)]
+async fn choose(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let choices = args.rest().split('|')
+        .map(str::trim)
+        .filter(|choice| !choice.is_empty())
+        .collect::<Vec<_>>();
+
+    if choices.len() < 2 {
+        msg.reply(ctx, "Please give at least two options separated by `|`, e.g. `pizza | tacos`.").await?;
+        return Ok(());
+    }
+
+    if choices.len() > MAX_CHOICES {
+        msg.reply(ctx, format!("I can choose between at most {MAX_CHOICES} options.")).await?;
+        return Ok(());
+    }
+
+    let choice = *choices.choose(&mut rand::thread_rng()).unwrap();
+
+    let builder = CreateMessage::new()
+        .content(format!("I choose **{choice}**."))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .reference_message(msg);
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+#[command]
+#[aliases(rockpaperscissors)]
+#[description = "Plays rock-paper-scissors against the bot. Leave out your pick to choose it with buttons instead."]
+#[usage = "[rock|paper|scissors]"]
+#[example = "rock"]
+async fn rps(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let choice = args.rest().trim();
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    if !choice.is_empty() {
+        let Some(hand) = Hand::parse(choice) else {
+            msg.reply(ctx, "Pick one of `rock`, `paper` or `scissors`.").await?;
+            return Ok(());
+        };
+
+        msg.channel_id.send_message(ctx, CreateMessage::new().embed(rps_result(&branding, hand)).reference_message(msg)).await?;
+        return Ok(());
+    }
+
+    let buttons = Hand::ALL.iter()
+        .map(|hand| CreateButton::new(hand.id()).label(hand.name()).emoji(hand.emoji()))
+        .collect::<Vec<_>>();
+
+    let builder = CreateMessage::new()
+        .content("Rock, paper or scissors?")
+        .components(vec![CreateActionRow::Buttons(buttons)])
+        .reference_message(msg);
+
+    let mut prompt = msg.channel_id.send_message(ctx, builder).await?;
+
+    let interaction = prompt.await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(RPS_TIMEOUT)
+        .await;
+
+    let Some(interaction) = interaction else {
+        drop(prompt.edit(ctx, EditMessage::new().content("Timed out, maybe next time.").components(vec![])).await);
+        return Ok(());
+    };
+
+    let hand = Hand::ALL.into_iter().find(|hand| hand.id() == interaction.data.custom_id).unwrap_or(Hand::Rock);
+
+    let response = CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(rps_result(&branding, hand))
+        .components(vec![]);
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+
+    Ok(())
+}
+
+/// Plays the bot's hand against the user's and describes who won.
+fn rps_result(branding: &Branding, hand: Hand) -> CreateEmbed {
+    let bot_hand = *Hand::ALL.choose(&mut rand::thread_rng()).unwrap();
+
+    let outcome = if hand == bot_hand {
+        "It's a tie!"
+    } else if hand.beats(bot_hand) {
+        "You win!"
+    } else {
+        "I win!"
+    };
+
+    branding.embed()
+        .title(outcome)
+        .description(format!("You picked {} **{}**, I picked {} **{}**.", hand.emoji(), hand.name(), bot_hand.emoji(), bot_hand.name()))
+}
@@ -0,0 +1,167 @@
+use std::env;
+
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+/// Most repositories a guild can be subscribed to.
+const MAX_SUBSCRIPTIONS: i32 = 10;
+const SECRET_LENGTH: usize = 32;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists the GitHub repositories whose pushes, releases, issues and pull requests are posted in this server. `github subscribe` adds one."]
+#[sub_commands(github_subscribe, github_unsubscribe)]
+#[num_args(0)]
+async fn github(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let subscriptions = sqlx::query!("SELECT channel_id, repository FROM github_subscriptions WHERE guild_id = ? ORDER BY repository", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let description = if subscriptions.is_empty() {
+        "This server isn't subscribed to any repositories yet, add one with `github subscribe`.".to_string()
+    } else {
+        subscriptions.iter()
+            .map(|subscription| format!("[{0}](https://github.com/{0}) in <#{1}>", subscription.repository, subscription.channel_id))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("GitHub subscriptions ({} of {MAX_SUBSCRIPTIONS})", subscriptions.len()))
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("subscribe")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts a GitHub repository's pushes, releases, issues and pull requests in a channel. I'll DM you the webhook to add to the repository, with a secret only it knows. Subscribing again makes a new secret."]
+#[usage = "<owner/repository> <#channel>"]
+#[example = "serenity-rs/serenity #development"]
+#[num_args(2)]
+async fn github_subscribe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let repository = args.text("repository")?.to_lowercase();
+    let channel = args.channel(ctx, msg).await?;
+
+    if !is_repository_name(&repository) {
+        msg.reply(ctx, "Please give a repository as `owner/name`, such as `serenity-rs/serenity`.").await?;
+        return Ok(());
+    }
+
+    if !matches!(channel.kind, ChannelType::Text | ChannelType::News) {
+        msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+        return Ok(());
+    }
+
+    // GitHub needs an address it can reach, which may be behind a proxy rather than the one bound
+    let (Ok(_), Ok(public_url)) = (env::var("WEBHOOK_ADDRESS"), env::var("WEBHOOK_PUBLIC_URL")) else {
+        msg.reply(ctx, "Webhooks aren't set up for this bot.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, channel_id) = (msg.guild_id.unwrap().get() as i64, channel.id.get() as i64);
+
+    let existing = sqlx::query!("SELECT COUNT(*) AS count FROM github_subscriptions WHERE guild_id = ? AND NOT (channel_id = ? AND repository = ?)", guild_id, channel_id, repository)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if existing >= MAX_SUBSCRIPTIONS {
+        msg.reply(ctx, format!("This server is already subscribed to the maximum of {MAX_SUBSCRIPTIONS} repositories.")).await?;
+        return Ok(());
+    }
+
+    let secret = rand::thread_rng().sample_iter(&Alphanumeric).take(SECRET_LENGTH).map(char::from).collect::<String>();
+
+    let instructions = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Webhook for {repository}"))
+        .description(format!(
+            "Add a webhook under **Settings > Webhooks** of [{repository}](https://github.com/{repository}/settings/hooks) with these settings. \
+            Events will be posted in {} once GitHub sends them.",
+            channel.mention()
+        ))
+        .field("Payload URL", format!("{}/github", public_url.trim_end_matches('/')), false)
+        .field("Content type", "`application/json`", false)
+        .field("Secret", format!("`{secret}`"), false)
+        .field("Events", "Pushes, Releases, Issues and Pull requests", false);
+
+    // the secret is sent before it's saved, so it's never in use without anyone knowing it
+    if msg.author.direct_message(ctx, CreateMessage::new().embed(instructions)).await.is_err() {
+        msg.reply(ctx, "I couldn't DM you the webhook's secret, please allow DMs from this server and try again.").await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO github_subscriptions (guild_id, channel_id, repository, secret) VALUES (?, ?, ?, ?)
+        ON CONFLICT (channel_id, repository) DO UPDATE SET secret = excluded.secret",
+        guild_id,
+        channel_id,
+        repository,
+        secret
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("I've sent you the webhook to add to {repository} in DMs.")).await?;
+
+    Ok(())
+}
+
+#[command("unsubscribe")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Stops posting a GitHub repository's events in a channel. Remember to remove the webhook from the repository too."]
+#[usage = "<owner/repository> <#channel>"]
+#[example = "serenity-rs/serenity #development"]
+#[num_args(2)]
+async fn github_unsubscribe(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let repository = args.text("repository")?.to_lowercase();
+    let channel = args.channel(ctx, msg).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channel_id = channel.id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM github_subscriptions WHERE channel_id = ? AND repository = ?", channel_id, repository)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    let reply = if removed == 0 {
+        format!("{} isn't subscribed to {repository}.", channel.mention())
+    } else {
+        format!("Events from {repository} won't be posted in {} anymore.", channel.mention())
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Whether an argument looks like a GitHub `owner/name` repository.
+fn is_repository_name(repository: &str) -> bool {
+    let Some((owner, name)) = repository.split_once('/') else {
+        return false;
+    };
+
+    let valid = |part: &str| !part.is_empty() && part.len() <= 100 && part.chars().all(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '.'));
+
+    valid(owner) && valid(name)
+}
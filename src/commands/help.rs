@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditMessage};
+use serenity::framework::standard::macros::help;
+use serenity::framework::standard::{Args, Command, CommandGroup, CommandResult, HelpOptions};
+use serenity::model::application::{ButtonStyle, ComponentInteractionDataKind};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::COMMAND_GROUPS;
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::command_rules::command_allowed_in;
+use crate::utilities::cooldowns::command_cooldown;
+use crate::utilities::dispatch::resolve_command;
+use crate::utilities::fuzzy::command_match_score;
+use crate::utilities::i18n::{guild_language, translate};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{PAGE_TIMEOUT, paginate_lines};
+use crate::utilities::parsing::format_duration;
+
+/// Commands listed per page of a category.
+const HELP_COMMANDS_PER_PAGE: usize = 15;
+
+/// Most results `help search` lists.
+const MAX_SEARCH_RESULTS: usize = 10;
+
+const CATEGORY_ID: &str = "help_category";
+const PREVIOUS_ID: &str = "help_previous";
+const NEXT_ID: &str = "help_next";
+
+/// The option in the category menu that goes back to the list of categories.
+const OVERVIEW: &str = "overview";
+
+#[help]
+#[no_help_available_text("No help information available.")]
+async fn help(ctx: &Context, msg: &Message, args: Args, _opts: &'static HelpOptions, _groups: &[&'static CommandGroup], owners: HashSet<UserId>) -> CommandResult {
+    let query = args.rest().trim();
+    run_help(ctx, &Invocation::Message(msg), Some(query), owners.contains(&msg.author.id)).await
+}
+
+/// Help for both prefix and slash commands. Lists the categories with a menu to pick one, shows
+/// a category or a command when one is named, and otherwise searches for the closest commands.
+/// Commands the user can't use in this channel, because of their permissions or the server's
+/// command rules, are left out.
+pub async fn run_help(ctx: &Context, invocation: &Invocation<'_>, query: Option<&str>, is_owner: bool) -> CommandResult {
+    let query = query.map(str::trim).filter(|query| !query.is_empty());
+    let visible = visible_commands(ctx, invocation, is_owner).await;
+
+    let view = HelpView {
+        prefix: invocation.prefix(ctx).await,
+        language: guild_language(ctx, invocation.guild_id()).await,
+        branding: guild_branding(ctx, invocation.guild_id()).await,
+        slash: matches!(invocation, Invocation::Slash(_)),
+        is_owner,
+        groups: visible
+    };
+
+    let Some(query) = query else {
+        return view.browse(ctx, invocation, None).await;
+    };
+
+    let (first, rest) = query.split_once(char::is_whitespace).unwrap_or((query, ""));
+
+    if first.eq_ignore_ascii_case("search") {
+        return help_search(ctx, invocation, &view, rest.trim()).await;
+    }
+
+    if let Some(index) = view.groups.iter().position(|(group, _)| group.name.eq_ignore_ascii_case(query)) {
+        return view.browse(ctx, invocation, Some(index)).await;
+    }
+
+    // an exact name wins, otherwise the closest match is shown along with the runners-up
+    let exact = resolve_command(query)
+        .filter(|resolved| resolved.args.is_empty())
+        .filter(|resolved| view.is_visible(resolved.root))
+        .map(|resolved| (resolved.group.name, query.to_lowercase(), resolved.command));
+
+    let matches = view.search(query);
+
+    let (embed, others) = match exact {
+        Some((category, name, command)) => (view.command_embed(ctx, invocation, category, &name, command).await, Vec::new()),
+        None => match matches.first() {
+            Some((_, category, name, command)) => {
+                let others = matches.iter().skip(1).take(5).map(|(_, _, name, _)| name.as_str()).collect::<Vec<_>>();
+                (view.command_embed(ctx, invocation, category, name, command).await, others)
+            }
+            None => {
+                let embed = view.branding.embed()
+                    .title(view.text("help.title", &[]))
+                    .description(view.text("help.no_match", &[("query", query)]));
+
+                invocation.respond(ctx, embed).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let embed = if others.is_empty() {
+        embed
+    } else {
+        embed.footer(CreateEmbedFooter::new(view.text("help.closest", &[("query", query), ("others", &others.join(", "))])))
+    };
+
+    invocation.respond(ctx, embed).await?;
+
+    Ok(())
+}
+
+/// The command groups and top-level commands the user can use where they asked for help.
+async fn visible_commands(ctx: &Context, invocation: &Invocation<'_>, is_owner: bool) -> Vec<(&'static CommandGroup, Vec<&'static Command>)> {
+    let permissions = invocation.permissions(ctx).await;
+    let mut visible = Vec::new();
+
+    for group in COMMAND_GROUPS {
+        if group.options.owners_only && !is_owner {
+            continue;
+        }
+
+        let mut commands = Vec::new();
+
+        for command in group.options.commands {
+            let options = command.options;
+
+            if !options.help_available || (options.owners_only && !is_owner) {
+                continue;
+            }
+
+            let required = group.options.required_permissions | options.required_permissions;
+
+            if !is_owner && !permissions.administrator() && !permissions.contains(required) {
+                continue;
+            }
+
+            if let Some(guild_id) = invocation.guild_id() {
+                if !command_allowed_in(ctx, guild_id, invocation.channel_id(), group, command).await {
+                    continue;
+                }
+            }
+
+            commands.push(*command);
+        }
+
+        if !commands.is_empty() {
+            visible.push((*group, commands));
+        }
+    }
+
+    visible
+}
+
+struct HelpView {
+    prefix: String,
+    /// The language of the guild help was asked for in. Command descriptions are only written in
+    /// English, so only what surrounds them is translated.
+    language: String,
+    branding: Branding,
+    slash: bool,
+    is_owner: bool,
+    groups: Vec<(&'static CommandGroup, Vec<&'static Command>)>
+}
+
+impl HelpView {
+    fn is_visible(&self, root: &Command) -> bool {
+        self.groups.iter().any(|(_, commands)| commands.iter().any(|command| std::ptr::eq(*command, root)))
+    }
+
+    fn text(&self, key: &str, args: &[(&str, &str)]) -> String {
+        translate(&self.language, key, args)
+    }
+
+    fn tip(&self) -> String {
+        let key = if self.slash { "help.tip_slash" } else { "help.tip" };
+        self.text(key, &[("prefix", &self.prefix)])
+    }
+
+    fn command_count(&self, count: usize) -> String {
+        let key = if count == 1 { "help.commands_one" } else { "help.commands_many" };
+        self.text(key, &[("count", &count.to_string())])
+    }
+
+    /// Pages of a category's commands, with the first sentence of each one's description.
+    fn category_pages(&self, index: usize) -> Vec<String> {
+        let no_help = self.text("help.no_help", &[]);
+
+        let lines = self.groups[index].1.iter()
+            .map(|command| {
+                let description = command.options.desc.unwrap_or(&no_help);
+                let summary = description.split_inclusive(". ").next().unwrap_or(description).trim_end();
+                format!("`{}{}` - {summary}", self.prefix, command.options.names[0])
+            })
+            .collect::<Vec<_>>();
+
+        paginate_lines(&lines, HELP_COMMANDS_PER_PAGE, "\n")
+    }
+
+    /// The overview or a page of a category, with the menu and page buttons that go with it.
+    fn render(&self, category: Option<usize>, page: usize) -> (CreateEmbed, Vec<CreateActionRow>) {
+        let options = std::iter::once(CreateSelectMenuOption::new(self.text("help.overview", &[]), OVERVIEW).default_selection(category.is_none()))
+            .chain(self.groups.iter().enumerate().map(|(index, (group, commands))| {
+                CreateSelectMenuOption::new(group.name, group.name)
+                    .description(self.command_count(commands.len()))
+                    .default_selection(category == Some(index))
+            }))
+            .collect::<Vec<_>>();
+
+        let menu = CreateSelectMenu::new(CATEGORY_ID, CreateSelectMenuKind::String { options })
+            .placeholder(self.text("help.choose_category", &[]));
+
+        let mut components = vec![CreateActionRow::SelectMenu(menu)];
+
+        let Some(index) = category else {
+            let categories = self.groups.iter()
+                .map(|(group, commands)| format!("**{}** - {}", group.name, self.command_count(commands.len())))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let embed = self.branding.embed()
+                .title(self.text("help.title", &[]))
+                .description(format!("{}\n\n{categories}", self.text("help.pick_category", &[])))
+                .footer(CreateEmbedFooter::new(self.tip()));
+
+            return (embed, components);
+        };
+
+        let pages = self.category_pages(index);
+        let page = page.min(pages.len().saturating_sub(1));
+
+        let mut footer = self.tip();
+
+        if pages.len() > 1 {
+            footer = format!("{} | {footer}", self.text("help.page", &[("page", &(page + 1).to_string()), ("pages", &pages.len().to_string())]));
+
+            components.push(CreateActionRow::Buttons(vec![
+                CreateButton::new(PREVIOUS_ID).label(self.text("help.previous", &[])).style(ButtonStyle::Secondary).disabled(page == 0),
+                CreateButton::new(NEXT_ID).label(self.text("help.next", &[])).style(ButtonStyle::Secondary).disabled(page + 1 >= pages.len())
+            ]));
+        }
+
+        let embed = self.branding.embed()
+            .title(self.text("help.category_title", &[("category", self.groups[index].0.name)]))
+            .description(pages.get(page).cloned().unwrap_or_default())
+            .footer(CreateEmbedFooter::new(footer));
+
+        (embed, components)
+    }
+
+    /// Shows the overview or a category and lets the user switch between them until the menu
+    /// hasn't been used for a while.
+    async fn browse(&self, ctx: &Context, invocation: &Invocation<'_>, category: Option<usize>) -> CommandResult {
+        let (mut category, mut page) = (category, 0);
+        let (embed, components) = self.render(category, page);
+
+        let mut view = invocation.respond_with_components(ctx, embed, components).await?;
+        let author = invocation.user_id();
+
+        while let Some(interaction) = view.await_component_interaction(&ctx.shard)
+            .timeout(PAGE_TIMEOUT)
+            .await
+        {
+            if interaction.user.id != author {
+                let response = CreateInteractionResponseMessage::new()
+                    .content(self.text("help.not_yours", &[]))
+                    .ephemeral(true);
+
+                interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+                continue;
+            }
+
+            match (&interaction.data.kind, interaction.data.custom_id.as_str()) {
+                (ComponentInteractionDataKind::StringSelect { values }, CATEGORY_ID) => {
+                    category = values.first().and_then(|value| self.groups.iter().position(|(group, _)| group.name == value));
+                    page = 0;
+                }
+                (_, PREVIOUS_ID) => page = page.saturating_sub(1),
+                (_, NEXT_ID) => page += 1,
+                _ => continue
+            }
+
+            let (embed, components) = self.render(category, page);
+            let response = CreateInteractionResponseMessage::new().embed(embed).components(components);
+
+            interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+        }
+
+        drop(view.edit(ctx, EditMessage::new().components(vec![])).await);
+
+        Ok(())
+    }
+
+    /// Ranks every visible command and sub-command against the query, best first.
+    fn search(&self, query: &str) -> Vec<(usize, &'static str, String, &'static Command)> {
+        let mut matches = Vec::new();
+
+        for (group, commands) in &self.groups {
+            collect_matches(query, group.name, "", commands, self.is_owner, &mut matches);
+        }
+
+        matches.sort_by_key(|(score, ..)| *score);
+        matches
+    }
+
+    /// Everything there is to know about a command: what it does, how to use it, who can and how
+    /// often.
+    async fn command_embed(&self, ctx: &Context, invocation: &Invocation<'_>, category: &str, name: &str, command: &'static Command) -> CreateEmbed {
+        let prefix = &self.prefix;
+        let options = command.options;
+
+        let mut embed = self.branding.embed()
+            .title(format!("{prefix}{name}"))
+            .description(options.desc.map_or_else(|| self.text("help.no_help", &[]), str::to_string))
+            .field(self.text("help.category", &[]), category, true);
+
+        if let Some(usage) = options.usage {
+            embed = embed.field(self.text("help.usage", &[]), format!("`{prefix}{name} {usage}`"), true);
+        }
+
+        if !options.examples.is_empty() {
+            let examples = options.examples.iter().map(|example| format!("`{prefix}{name} {example}`")).collect::<Vec<_>>();
+            embed = embed.field(self.text("help.examples", &[]), examples.join("\n"), false);
+        }
+
+        if options.names.len() > 1 {
+            embed = embed.field(self.text("help.aliases", &[]), options.names[1..].join(", "), true);
+        }
+
+        if !options.required_permissions.is_empty() {
+            embed = embed.field(self.text("help.permissions", &[]), options.required_permissions.get_permission_names().join(", "), true);
+        }
+
+        if let Some((seconds, scope)) = command_cooldown(ctx, invocation.guild_id(), options.names[0]).await {
+            let cooldown = format_duration(chrono::Duration::seconds(seconds as i64));
+            embed = embed.field(self.text("help.cooldown", &[]), self.text("help.cooldown_per", &[("cooldown", &cooldown), ("scope", scope.name())]), true);
+        }
+
+        if !options.sub_commands.is_empty() {
+            let sub_commands = options.sub_commands.iter().map(|sub| format!("`{}`", sub.options.names[0])).collect::<Vec<_>>();
+            embed = embed.field(self.text("help.sub_commands", &[]), sub_commands.join(" "), false);
+        }
+
+        embed
+    }
+}
+
+/// Ranks every visible command against the query and lists the best matches.
+async fn help_search(ctx: &Context, invocation: &Invocation<'_>, view: &HelpView, query: &str) -> CommandResult {
+    if query.is_empty() {
+        let embed = view.branding.embed()
+            .title(view.text("help.search_title", &[]))
+            .description(view.text("help.search_empty", &[]));
+
+        invocation.respond(ctx, embed).await?;
+        return Ok(());
+    }
+
+    let matches = view.search(query);
+
+    let no_help = view.text("help.no_help", &[]);
+
+    let description = if matches.is_empty() {
+        view.text("help.no_match", &[("query", query)])
+    } else {
+        matches.iter()
+            .take(MAX_SEARCH_RESULTS)
+            .map(|(_, category, name, command)| {
+                let usage = command.options.usage.map_or_else(String::new, |usage| format!(" {usage}"));
+                let summary = command.options.desc.unwrap_or(&no_help);
+
+                format!("**{}{name}**{usage} ({category})\n{summary}", view.prefix)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    let embed = view.branding.embed()
+        .title(view.text("help.search_results_title", &[("query", query)]))
+        .description(description);
+
+    invocation.respond(ctx, embed).await?;
+
+    Ok(())
+}
+
+/// Walks commands and their sub-commands, collecting `(score, category, full name, command)`
+/// for every match.
+fn collect_matches(
+    query: &str,
+    category: &'static str,
+    parent: &str,
+    commands: &[&'static Command],
+    is_owner: bool,
+    matches: &mut Vec<(usize, &'static str, String, &'static Command)>
+) {
+    for command in commands {
+        if !command.options.help_available || (command.options.owners_only && !is_owner) {
+            continue;
+        }
+
+        let full_name = format!("{parent}{}", command.options.names[0]);
+
+        if let Some(score) = command_match_score(query, command.options.names, command.options.desc) {
+            matches.push((score, category, full_name.clone(), *command));
+        }
+
+        collect_matches(query, category, &format!("{full_name} "), command.options.sub_commands, is_owner, matches);
+    }
+}
@@ -0,0 +1,146 @@
+use serenity::builder::{CreateAttachment, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::{ArgumentError, TypedArgs};
+use crate::utilities::images::{Effect, MAX_CAPTION_LENGTH, avatar_png_url, download_image, is_supported_attachment, render_effect};
+
+#[command]
+#[aliases(avatareffect)]
+#[description = "Applies an effect to an image. Attach a PNG or reply to a message with one, or name a user to use their avatar, otherwise your own avatar is used. Effects are `grayscale`, `invert`, `sepia`, `pixelate`, `blur` and `mirror`."]
+#[usage = "<effect> [user]"]
+#[example = "pixelate @someone"]
+#[min_args(1)]
+#[max_args(2)]
+async fn effect(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.text("effect")?;
+
+    let Some(effect) = Effect::parse(&name) else {
+        msg.reply(ctx, format!("There's no effect called `{name}`. Pick one of {}.", Effect::NAMES.map(|name| format!("`{name}`")).join(", "))).await?;
+        return Ok(());
+    };
+
+    let user_id = optional_user(&mut args)?;
+
+    send_effect(ctx, msg, user_id, effect, "effect").await
+}
+
+#[command]
+#[description = "Adds a caption above an image. Attach a PNG or reply to a message with one, otherwise your own avatar is used. Only letters and numbers are drawn."]
+#[usage = "<text>"]
+#[example = "when the build passes first try"]
+#[min_args(1)]
+async fn caption(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let text = args.rest().trim().to_string();
+
+    if text.chars().count() > MAX_CAPTION_LENGTH {
+        msg.reply(ctx, format!("Captions can be at most {MAX_CAPTION_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    // the caption uses up the arguments, so only attachments and the author's avatar are looked at
+    send_effect(ctx, msg, None, Effect::Caption(text), "caption").await
+}
+
+#[command]
+#[description = "Deep fries an image. Attach a PNG or reply to a message with one, or name a user to use their avatar, otherwise your own avatar is used."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn deepfry(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = optional_user(&mut args)?;
+
+    send_effect(ctx, msg, user_id, Effect::Deepfry, "deepfry").await
+}
+
+#[command]
+#[aliases(pat)]
+#[description = "Gives someone's avatar a pat, or an image's. Attach a PNG or reply to a message with one, otherwise your own avatar is used."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn pet(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = optional_user(&mut args)?;
+
+    send_effect(ctx, msg, user_id, Effect::Pet, "pet").await
+}
+
+#[command]
+#[description = "Puts someone's avatar, or an image, on a wanted poster. Attach a PNG or reply to a message with one, otherwise your own avatar is used."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn wanted(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = optional_user(&mut args)?;
+
+    send_effect(ctx, msg, user_id, Effect::Wanted, "wanted").await
+}
+
+/// Finds the image a command should edit, applies the effect and replies with the result.
+async fn send_effect(ctx: &Context, msg: &Message, user_id: Option<UserId>, effect: Effect, name: &str) -> CommandResult {
+    let url = match image_source(ctx, msg, user_id).await {
+        Ok(url) => url,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    let typing = msg.channel_id.start_typing(&ctx.http);
+
+    let result = match download_image(ctx, &url).await {
+        Ok(data) => render_effect(data, effect).await,
+        Err(why) => Err(why)
+    };
+
+    typing.stop();
+
+    let png = match result {
+        Ok(png) => png,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    let builder = CreateMessage::new()
+        .add_file(CreateAttachment::bytes(png, format!("{name}.png")))
+        .reference_message(msg);
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+/// The user named by the remaining argument, if there is one.
+fn optional_user(args: &mut Args) -> Result<Option<UserId>, ArgumentError> {
+    if args.is_empty() {
+        return Ok(None);
+    }
+
+    args.user_id().map(Some)
+}
+
+/// The link to the image a command should edit: an attachment on the message or the one it replies
+/// to, the avatar of the given user, or else the author's avatar.
+async fn image_source(ctx: &Context, msg: &Message, user_id: Option<UserId>) -> Result<String, String> {
+    let attachments = msg.referenced_message.as_deref()
+        .map_or(&[][..], |referenced| &referenced.attachments[..]);
+
+    let attachments = [&msg.attachments[..], attachments].concat();
+
+    if !attachments.is_empty() {
+        return attachments.iter()
+            .find(|attachment| is_supported_attachment(attachment))
+            .map(|attachment| attachment.url.clone())
+            .ok_or_else(|| "That isn't a supported image, only PNGs can be edited.".to_string());
+    }
+
+    let Some(user_id) = user_id else {
+        return Ok(avatar_png_url(&msg.author));
+    };
+
+    match user_id.to_user(ctx).await {
+        Ok(user) => Ok(avatar_png_url(&user)),
+        Err(_) => Err(format!("Couldn't find a user matching `{user_id}`."))
+    }
+}
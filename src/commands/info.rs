@@ -0,0 +1,381 @@
+use std::env;
+
+use serenity::builder::{CreateEmbed, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::{Branding, branded_embed, guild_branding};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::mod_notes::{note_summary, user_notes};
+use crate::utilities::votes::{get_vote_streak, VOTE_PERK_HOURS, VOTER_DAILY_BONUS};
+
+#[command]
+#[description = "Shows where to vote for the bot and your current vote streak."]
+async fn vote(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let bot_id = ctx.cache.current_user().id;
+    let mut links = format!("[top.gg](https://top.gg/bot/{bot_id}/vote)");
+
+    if let Ok(extra) = env::var("VOTE_LINKS") {
+        // extra links are given as comma separated `name=url` pairs
+        for (name, url) in extra.split(',').filter_map(|link| link.split_once('=')) {
+            links.push_str(&format!(" • [{}]({})", name.trim(), url.trim()));
+        }
+    }
+
+    let status = match get_vote_streak(&database, msg.author.id.get()).await? {
+        Some(streak) => {
+            let perk = if streak.has_active_perk() {
+                format!("Your voter perks are active until <t:{}:t>, `daily` pays {VOTER_DAILY_BONUS} extra coins until then.", streak.perk_expires_at().timestamp())
+            } else {
+                "Your voter perks have expired, vote again to renew them!".to_string()
+            };
+
+            format!(
+                "**Current streak**: {}\n**Best streak**: {}\n**Total votes**: {}\n**Last vote**: <t:{}:R>\n\n{perk}",
+                streak.streak,
+                streak.best_streak,
+                streak.total_votes,
+                streak.last_voted_at.timestamp()
+            )
+        }
+        None => format!("You haven't voted yet. Each vote adds {VOTER_DAILY_BONUS} coins to your `daily` for {VOTE_PERK_HOURS} hours!")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Vote")
+        .description(format!("{links}\n\n{status}"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Permissions worth calling out in info embeds, everything else is taken for granted.
+const KEY_PERMISSIONS: Permissions = Permissions::MANAGE_GUILD
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_CHANNELS)
+    .union(Permissions::MANAGE_MESSAGES)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::MANAGE_GUILD_EXPRESSIONS)
+    .union(Permissions::KICK_MEMBERS)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::MODERATE_MEMBERS)
+    .union(Permissions::MENTION_EVERYONE)
+    .union(Permissions::VIEW_AUDIT_LOG);
+
+/// Roles listed in `userinfo` before the rest are summarised.
+const MAX_LISTED_ROLES: usize = 20;
+
+/// Staff notes shown in `userinfo`, the rest are left to `note list`.
+const MAX_LISTED_NOTES: usize = 3;
+
+#[command]
+#[only_in(guilds)]
+#[aliases("server")]
+#[description = "Shows information about this server."]
+#[num_args(0)]
+async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let Some(embed) = msg.guild(&ctx.cache).map(|guild| server_embed(&branding, &guild)) else {
+        msg.reply(ctx, "This server isn't cached yet, please try again in a moment.").await?;
+        return Ok(());
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("user", "whois")]
+#[description = "Shows information about a member, or yourself. Moderators also see the latest staff notes about them."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn userinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let Ok(user) = user_id.to_user(ctx).await else {
+        msg.reply(ctx, "I couldn't find that user.").await?;
+        return Ok(());
+    };
+
+    let member = guild_id.member(ctx, user_id).await.ok();
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(user.tag())
+        .thumbnail(member.as_ref().map_or_else(|| user.face(), Member::face))
+        .field("Account created", timestamp(user.id.created_at()), true);
+
+    if let Some(member) = member {
+        let (join_position, highest_color, permissions) = match msg.guild(&ctx.cache) {
+            Some(guild) => {
+                // only counts cached members, which is all of them unless the server is large
+                let position = member.joined_at.map(|joined_at| {
+                    guild.members.values().filter(|other| other.joined_at.is_some_and(|other| other < joined_at)).count() + 1
+                });
+
+                let color = guild.member_highest_role(&member).map(|role| role.colour).filter(|colour| colour.0 != 0);
+
+                (position, color, Some(guild.member_permissions(&member)))
+            }
+            None => (None, None, None)
+        };
+
+        let mut roles = member.roles.clone();
+        roles.sort_by_key(|role_id| std::cmp::Reverse(role_id.to_role_cached(&ctx.cache).map_or(0, |role| role.position)));
+
+        let mut listed = roles.iter().take(MAX_LISTED_ROLES).map(|role_id| role_id.mention().to_string()).collect::<Vec<_>>().join(" ");
+
+        if roles.len() > MAX_LISTED_ROLES {
+            listed.push_str(&format!(" and {} more", roles.len() - MAX_LISTED_ROLES));
+        }
+
+        if let Some(nick) = &member.nick {
+            embed = embed.description(format!("Also known as **{nick}**"));
+        }
+
+        if let Some(joined_at) = member.joined_at {
+            embed = embed.field("Joined", timestamp(joined_at), true);
+        }
+
+        if let Some(position) = join_position {
+            embed = embed.field("Join position", format!("#{position}"), true);
+        }
+
+        if let Some(colour) = highest_color {
+            embed = embed.color(colour);
+        }
+
+        if let Some(premium_since) = member.premium_since {
+            embed = embed.field("Boosting since", timestamp(premium_since), true);
+        }
+
+        embed = embed.field(format!("Roles ({})", roles.len()), if listed.is_empty() { "None".to_string() } else { listed }, false);
+
+        if let Some(permissions) = permissions {
+            embed = embed.field("Key permissions", permission_summary(permissions), false);
+        }
+    } else {
+        embed = embed.description("Not a member of this server.");
+    }
+
+    embed = embed.field("ID", user.id.to_string(), true);
+
+    // staff notes stay between moderators
+    let is_moderator = msg.guild(&ctx.cache).is_some_and(|guild| {
+        guild.members.get(&msg.author.id).is_some_and(|author| guild.member_permissions(author).moderate_members())
+    });
+
+    if is_moderator {
+        let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+        let notes = user_notes(&database, guild_id, user_id).await?;
+
+        if !notes.is_empty() {
+            let latest = notes.iter().take(MAX_LISTED_NOTES).map(note_summary).collect::<Vec<_>>().join("\n");
+            embed = embed.field(format!("Staff notes ({})", notes.len()), latest, false);
+        }
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("role")]
+#[description = "Shows information about a role."]
+#[usage = "<role>"]
+#[num_args(1)]
+async fn roleinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let embed = match msg.guild(&ctx.cache) {
+        Some(guild) => role_embed(&branding, &guild, role_id),
+        None => Err("This server isn't cached yet, please try again in a moment.")
+    };
+
+    match embed {
+        Ok(embed) => msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?,
+        Err(why) => msg.reply(ctx, why).await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("channel")]
+#[description = "Shows information about a channel, or the one this is used in."]
+#[usage = "[channel]"]
+#[max_args(1)]
+async fn channelinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = if args.is_empty() { msg.channel_id } else { args.channel(ctx, msg).await?.id };
+
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let embed = match msg.guild(&ctx.cache) {
+        Some(guild) => channel_embed(&branding, &guild, channel_id),
+        None => Err("This server isn't cached yet, please try again in a moment.")
+    };
+
+    match embed {
+        Ok(embed) => msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?,
+        Err(why) => msg.reply(ctx, why).await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[aliases("av", "pfp")]
+#[description = "Shows someone's avatar in full size, or yours. In servers, their server avatar is shown if they have one."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn avatar(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let Ok(user) = user_id.to_user(ctx).await else {
+        msg.reply(ctx, "I couldn't find that user.").await?;
+        return Ok(());
+    };
+
+    let server_avatar = match msg.guild_id {
+        Some(guild_id) => guild_id.member(ctx, user_id).await.ok().and_then(|member| member.avatar_url()),
+        None => None
+    };
+
+    let url = server_avatar.unwrap_or_else(|| user.face());
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("{}'s avatar", user.name))
+        .url(&url)
+        .image(url);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn server_embed(branding: &Branding, guild: &Guild) -> CreateEmbed {
+    let count_channels = |kind: ChannelType| guild.channels.values().filter(|channel| channel.kind == kind).count();
+
+    let mut embed = branding.embed()
+        .title(&guild.name)
+        .field("Owner", guild.owner_id.mention().to_string(), true)
+        .field("Created", timestamp(guild.id.created_at()), true)
+        .field("Members", guild.member_count.to_string(), true)
+        .field("Channels", format!(
+            "{} text, {} voice, {} categories",
+            count_channels(ChannelType::Text),
+            count_channels(ChannelType::Voice),
+            count_channels(ChannelType::Category)
+        ), true)
+        .field("Roles", guild.roles.len().to_string(), true)
+        .field("Emojis", guild.emojis.len().to_string(), true)
+        .field("Boosts", format!("Level {} with {} boosts", u8::from(guild.premium_tier), guild.premium_subscription_count.unwrap_or_default()), true)
+        .field("Verification level", format!("{:?}", guild.verification_level), true)
+        .field("ID", guild.id.to_string(), true);
+
+    if let Some(icon) = guild.icon_url() {
+        embed = embed.thumbnail(icon);
+    }
+
+    embed
+}
+
+fn role_embed(branding: &Branding, guild: &Guild, role_id: RoleId) -> Result<CreateEmbed, &'static str> {
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Err("That role doesn't exist in this server.");
+    };
+
+    let members = guild.members.values().filter(|member| member.roles.contains(&role_id)).count();
+    let colour = if role.colour.0 == 0 { "Default".to_string() } else { format!("#{}", role.colour.hex()) };
+    let yes_no = |value: bool| if value { "Yes" } else { "No" };
+
+    let mut embed = branding.embed()
+        .title(&role.name)
+        .field("Colour", colour, true)
+        .field("Position", role.position.to_string(), true)
+        .field("Members", members.to_string(), true)
+        .field("Shown separately", yes_no(role.hoist), true)
+        .field("Mentionable", yes_no(role.mentionable), true)
+        .field("Managed by an integration", yes_no(role.managed), true)
+        .field("Created", timestamp(role.id.created_at()), true)
+        .field("ID", role.id.to_string(), true)
+        .field("Key permissions", permission_summary(role.permissions), false);
+
+    if role.colour.0 != 0 {
+        embed = embed.color(role.colour);
+    }
+
+    Ok(embed)
+}
+
+fn channel_embed(branding: &Branding, guild: &Guild, channel_id: ChannelId) -> Result<CreateEmbed, &'static str> {
+    let Some(channel) = guild.channels.get(&channel_id) else {
+        return Err("That channel doesn't exist in this server.");
+    };
+
+    let mut embed = branding.embed()
+        .title(format!("#{}", channel.name))
+        .field("Type", channel.kind.name(), true)
+        .field("Category", channel.parent_id.map_or_else(|| "None".to_string(), |parent_id| parent_id.mention().to_string()), true)
+        .field("Position", channel.position.to_string(), true)
+        .field("Created", timestamp(channel.id.created_at()), true);
+
+    if let Some(topic) = channel.topic.as_ref().filter(|topic| !topic.is_empty()) {
+        embed = embed.description(topic);
+    }
+
+    if channel.nsfw {
+        embed = embed.field("Age-restricted", "Yes", true);
+    }
+
+    if let Some(slowmode) = channel.rate_limit_per_user.filter(|slowmode| *slowmode > 0) {
+        embed = embed.field("Slowmode", format!("{slowmode} seconds"), true);
+    }
+
+    if let Some(bitrate) = channel.bitrate {
+        embed = embed.field("Bitrate", format!("{} kbps", bitrate / 1000), true);
+    }
+
+    if let Some(user_limit) = channel.user_limit.filter(|user_limit| *user_limit > 0) {
+        embed = embed.field("User limit", user_limit.to_string(), true);
+    }
+
+    Ok(embed.field("ID", channel.id.to_string(), true))
+}
+
+/// A creation or join date, along with how long ago it was.
+fn timestamp(time: Timestamp) -> String {
+    format!("<t:{0}:D> (<t:{0}:R>)", time.unix_timestamp())
+}
+
+/// The notable permissions out of `permissions`, or just "Administrator" since that implies them all.
+fn permission_summary(permissions: Permissions) -> String {
+    if permissions.administrator() {
+        return "Administrator".to_string();
+    }
+
+    let names = (permissions & KEY_PERMISSIONS).get_permission_names();
+
+    if names.is_empty() {
+        "None".to_string()
+    } else {
+        names.join(", ")
+    }
+}
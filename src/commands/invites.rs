@@ -0,0 +1,118 @@
+use serenity::builder::{CreateAllowedMentions, CreateEmbedAuthor, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+
+const LEADERBOARD_SIZE: i64 = 100;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows how many members you've invited to this server, or someone else, and how many are still here. Only joins since I could see the server's invites are counted."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn invites(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, inviter_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let invited = sqlx::query!("SELECT user_id FROM invite_joins WHERE guild_id = ? AND inviter_id = ?", db_guild_id, inviter_id)
+        .fetch_all(&database)
+        .await?;
+
+    let still_here = guild_id.to_guild_cached(&ctx.cache).map_or(0, |guild| {
+        invited.iter().filter(|row| guild.members.contains_key(&UserId::new(row.user_id as u64))).count()
+    });
+
+    let user = user_id.to_user(ctx).await?;
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .author(CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+        .field("Invited", invited.len().to_string(), true)
+        .field("Still here", still_here.to_string(), true)
+        .field("Left", (invited.len() - still_here).to_string(), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows who invited you to this server, or someone else, and with which invite."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn invitedby(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_user_id) = (msg.guild_id.unwrap().get() as i64, user_id.get() as i64);
+
+    let join = sqlx::query!("SELECT inviter_id, code, joined_at FROM invite_joins WHERE guild_id = ? AND user_id = ?", guild_id, db_user_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let reply = match join {
+        None => format!("I didn't see {} join, so I don't know who invited them.", user_id.mention()),
+        Some(join) => {
+            let joined = Timestamp::parse(&join.joined_at).map_or_else(|_| String::new(), |joined_at| format!(" <t:{}:R>", joined_at.unix_timestamp()));
+
+            match (join.inviter_id, join.code) {
+                (Some(inviter_id), Some(code)) => format!("{} joined{joined} with `{code}`, an invite by {}.", user_id.mention(), UserId::new(inviter_id as u64).mention()),
+                (None, Some(code)) => format!("{} joined{joined} with `{code}`, which has no inviter, such as the vanity URL.", user_id.mention()),
+                _ => format!("{} joined{joined}, but I couldn't tell which invite they used.", user_id.mention())
+            }
+        }
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().content(reply).allowed_mentions(CreateAllowedMentions::new())).await?;
+
+    Ok(())
+}
+
+#[command("invites")]
+#[only_in(guilds)]
+#[description = "Shows the members who've invited the most people to this server."]
+#[num_args(0)]
+async fn leaderboard_invites(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let top = sqlx::query!(
+        r#"SELECT inviter_id AS "inviter_id!: i64", COUNT(*) AS invited FROM invite_joins
+        WHERE guild_id = ? AND inviter_id IS NOT NULL
+        GROUP BY inviter_id ORDER BY invited DESC LIMIT ?"#,
+        guild_id,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = top.iter()
+        .enumerate()
+        .map(|(index, row)| format!("**{}.** {} - {} invited", index + 1, UserId::new(row.inviter_id as u64).mention(), row.invited))
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody has invited anyone here yet.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Top inviters");
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
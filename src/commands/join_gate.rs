@@ -0,0 +1,224 @@
+use chrono::Duration;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::{format_duration, parse_duration};
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows this server's join gate. Unlike raid mode, the join gate applies to every new member: accounts that are too young, or have no profile picture if that's required, are kicked or held in a quarantine role, told why by DM and logged to the modlog."]
+#[sub_commands(joingate_on, joingate_off, joingate_age, joingate_avatar, joingate_action)]
+async fn joingate(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT enabled, min_account_age, require_avatar, action, quarantine_role_id FROM join_gate_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(&database).await?;
+
+    let Some(settings) = settings else {
+        msg.reply(ctx, "The join gate isn't set up. Use `joingate age` or `joingate avatar` to set its requirements, then `joingate on`.").await?;
+        return Ok(());
+    };
+
+    let age = if settings.min_account_age > 0 { format_duration(Duration::seconds(settings.min_account_age)) } else { "None".to_string() };
+
+    let action = match settings.quarantine_role_id.filter(|_| settings.action == "quarantine") {
+        Some(role_id) => format!("Quarantine in {}", RoleId::new(role_id as u64).mention()),
+        None => "Kick".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Join gate")
+        .field("Status", if settings.enabled != 0 { "On" } else { "Off" }, true)
+        .field("Minimum account age", age, true)
+        .field("Profile picture required", if settings.require_avatar != 0 { "Yes" } else { "No" }, true)
+        .field("Action", action, true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("on")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns the join gate on."]
+#[num_args(0)]
+async fn joingate_on(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, msg, true).await
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns the join gate off, keeping its requirements for when it's turned back on."]
+#[num_args(0)]
+async fn joingate_off(ctx: &Context, msg: &Message) -> CommandResult {
+    set_enabled(ctx, msg, false).await
+}
+
+async fn set_enabled(ctx: &Context, msg: &Message, enabled: bool) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "INSERT INTO join_gate_settings (guild_id, enabled) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET enabled = excluded.enabled
+        RETURNING min_account_age, require_avatar",
+        guild_id,
+        enabled
+    ).fetch_one(&database).await?;
+
+    if !enabled {
+        msg.reply(ctx, "The join gate is now off.").await?;
+    } else if settings.min_account_age == 0 && settings.require_avatar == 0 {
+        msg.reply(ctx, "The join gate is now on, but has no requirements yet. Use `joingate age` or `joingate avatar` to set them.").await?;
+    } else {
+        msg.reply(ctx, "The join gate is now on. New members who don't meet its requirements will be held back.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("age")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how old accounts have to be to join, or `off` for no minimum age."]
+#[usage = "<duration|off>"]
+#[example = "7d"]
+#[num_args(1)]
+async fn joingate_age(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let arg = args.single::<String>()?;
+
+    let age = if arg.eq_ignore_ascii_case("off") {
+        Duration::zero()
+    } else {
+        let Some(age) = parse_duration(&arg) else {
+            msg.reply(ctx, "Please give a duration, like `12h`, `7d` or `30d`, or `off`.").await?;
+            return Ok(());
+        };
+
+        age
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let min_account_age = age.num_seconds();
+
+    sqlx::query!(
+        "INSERT INTO join_gate_settings (guild_id, min_account_age) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET min_account_age = excluded.min_account_age",
+        guild_id,
+        min_account_age
+    ).execute(&database).await?;
+
+    if age > Duration::zero() {
+        msg.reply(ctx, format!("Accounts younger than {} will now be held back by the join gate.", format_duration(age))).await?;
+    } else {
+        msg.reply(ctx, "The join gate no longer has a minimum account age.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("avatar")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether accounts need a profile picture to join. Accounts still using a default avatar are held back when this is on."]
+#[usage = "<on|off>"]
+#[example = "on"]
+#[num_args(1)]
+async fn joingate_avatar(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let require_avatar = match args.rest().trim().to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO join_gate_settings (guild_id, require_avatar) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET require_avatar = excluded.require_avatar",
+        guild_id,
+        require_avatar
+    ).execute(&database).await?;
+
+    if require_avatar {
+        msg.reply(ctx, "Accounts without a profile picture will now be held back by the join gate.").await?;
+    } else {
+        msg.reply(ctx, "Accounts no longer need a profile picture to join.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("action")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether members held back by the join gate are kicked, or given a quarantine role until a moderator takes it away. Make sure the quarantine role can't see the rest of the server."]
+#[usage = "<kick|quarantine> [role]"]
+#[example = "quarantine @Quarantine"]
+#[min_args(1)]
+#[max_args(2)]
+async fn joingate_action(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let quarantine_role = match args.single::<String>()?.to_lowercase().as_str() {
+        "kick" => None,
+        "quarantine" => {
+            let role_id = args.role(ctx, msg).await?.id;
+
+            if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+                msg.reply(ctx, why).await?;
+                return Ok(());
+            }
+
+            Some(role_id)
+        }
+        _ => {
+            msg.reply(ctx, "Members can be `kick`ed or put in `quarantine`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let action = if quarantine_role.is_some() { "quarantine" } else { "kick" };
+    let quarantine_role_id = quarantine_role.map(|role_id| role_id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO join_gate_settings (guild_id, action, quarantine_role_id) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET action = excluded.action, quarantine_role_id = excluded.quarantine_role_id",
+        db_guild_id,
+        action,
+        quarantine_role_id
+    ).execute(&database).await?;
+
+    match quarantine_role {
+        Some(role_id) => msg.reply(ctx, format!("Members held back by the join gate will now be given {}.", role_id.mention())).await?,
+        None => msg.reply(ctx, "Members held back by the join gate will now be kicked.").await?
+    };
+
+    Ok(())
+}
@@ -0,0 +1,448 @@
+use serenity::builder::{CreateAllowedMentions, CreateAttachment, CreateEmbedAuthor, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::economy::LEADERBOARD_COINS_COMMAND;
+use crate::commands::invites::LEADERBOARD_INVITES_COMMAND;
+use crate::commands::trivia::LEADERBOARD_TRIVIA_COMMAND;
+use crate::commands::voice::LEADERBOARD_VOICE_COMMAND;
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::charts::render_rank_card;
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::levels::{DEFAULT_LEVEL_UP, level_progress, reload_leveling, xp_to_next_level};
+use crate::utilities::templates::{message_context, render_template};
+
+const MAX_LEVEL_UP_LENGTH: usize = 1000;
+const MAX_REWARDS: i32 = 25;
+const MAX_MULTIPLIER: f64 = 5.0;
+const LEADERBOARD_SIZE: i64 = 100;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's leveling settings. Members earn XP for chatting, at most once a minute, and level up as it adds up."]
+#[sub_commands(level_on, level_off, level_announce, level_message, level_multiplier, level_reward, level_unreward)]
+async fn level(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, msg.guild_id.unwrap()).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT leveling_enabled, level_up_announcements, level_up_channel_id, level_up_message FROM guild_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_one(&database).await?;
+
+    let rewards = sqlx::query!("SELECT role_id, level FROM level_rewards WHERE guild_id = ? ORDER BY level", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let multipliers = sqlx::query!("SELECT channel_id, multiplier FROM level_multipliers WHERE guild_id = ?", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let announcements = match (settings.level_up_announcements != 0, settings.level_up_channel_id) {
+        (false, _) => "Off".to_string(),
+        (true, Some(channel_id)) => format!("In {}", ChannelId::new(channel_id as u64).mention()),
+        (true, None) => "Where the member levelled up".to_string()
+    };
+
+    let rewards = if rewards.is_empty() {
+        "None".to_string()
+    } else {
+        rewards.iter()
+            .map(|row| format!("Level {}: {}", row.level, RoleId::new(row.role_id as u64).mention()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let multipliers = if multipliers.is_empty() {
+        "None".to_string()
+    } else {
+        multipliers.iter()
+            .map(|row| format!("{}: {}x", ChannelId::new(row.channel_id as u64).mention(), row.multiplier))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Leveling")
+        .field("Enabled", if settings.leveling_enabled != 0 { "Yes" } else { "No" }, true)
+        .field("Level-up announcements", announcements, true)
+        .field("Level-up message", format!("```{}```", settings.level_up_message.as_deref().unwrap_or(DEFAULT_LEVEL_UP)), false)
+        .field("Role rewards", rewards, true)
+        .field("XP multipliers", multipliers, true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("on")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns leveling on."]
+#[num_args(0)]
+async fn level_on(ctx: &Context, msg: &Message) -> CommandResult {
+    set_leveling(ctx, msg, true).await
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns leveling off. Everyone keeps their XP for when it's turned back on."]
+#[num_args(0)]
+async fn level_off(ctx: &Context, msg: &Message) -> CommandResult {
+    set_leveling(ctx, msg, false).await
+}
+
+async fn set_leveling(ctx: &Context, msg: &Message, enabled: bool) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!("UPDATE guild_settings SET leveling_enabled = ? WHERE guild_id = ?", enabled, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    reload_leveling(ctx, guild_id).await?;
+
+    if enabled {
+        msg.reply(ctx, "Leveling is now on.").await?;
+    } else {
+        msg.reply(ctx, "Leveling is now off.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("announce")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets where level-ups are announced: `here` for wherever the member levelled up, a channel, or `off`."]
+#[usage = "<here|channel|off>"]
+#[example = "#level-ups"]
+#[num_args(1)]
+async fn level_announce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let (enabled, channel_id) = if args.keyword("here") {
+        (true, None)
+    } else if args.keyword("off") {
+        (false, None)
+    } else {
+        (true, Some(args.channel(ctx, msg).await?.id))
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    // turning announcements off keeps the channel for when they're turned back on
+    sqlx::query!(
+        "UPDATE guild_settings SET level_up_announcements = ?, level_up_channel_id = CASE WHEN ? THEN ? ELSE level_up_channel_id END WHERE guild_id = ?",
+        enabled,
+        enabled,
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match (enabled, channel_id) {
+        (false, _) => msg.reply(ctx, "Level-ups will no longer be announced.").await?,
+        (true, Some(channel_id)) => msg.reply(ctx, format!("Level-ups will now be announced in {}.", channel_id.mention())).await?,
+        (true, None) => msg.reply(ctx, "Level-ups will now be announced where the member levelled up.").await?
+    };
+
+    Ok(())
+}
+
+#[command("message")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the level-up message, a template (see `template`) that can also use `{level}`. Use `reset` to go back to the default."]
+#[usage = "<template|reset>"]
+#[example = "GG {user.mention}, you just reached level {level}!"]
+#[min_args(1)]
+async fn level_message(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let message = match args.rest().trim() {
+        "reset" => None,
+        template if template.chars().count() > MAX_LEVEL_UP_LENGTH => {
+            msg.reply(ctx, format!("Messages can be at most {MAX_LEVEL_UP_LENGTH} characters long.")).await?;
+            return Ok(());
+        }
+        template => Some(template.to_string())
+    };
+
+    if let Some(template) = &message {
+        let context = message_context(ctx, msg).await.value("level", 1);
+
+        if let Err(why) = render_template(template, &context) {
+            msg.reply(ctx, format!("That template couldn't be rendered: {why}.")).await?;
+            return Ok(());
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!("UPDATE guild_settings SET level_up_message = ? WHERE guild_id = ?", message, guild_id)
+        .execute(&database)
+        .await?;
+
+    match message {
+        Some(_) => msg.reply(ctx, "Updated the level-up message.").await?,
+        None => msg.reply(ctx, "The level-up message is back to the default.").await?
+    };
+
+    Ok(())
+}
+
+#[command("multiplier")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how much XP messages in a channel are worth, from 0 (none at all) to 5 times as much. 1 goes back to normal."]
+#[usage = "<channel> <multiplier>"]
+#[example = "#spam 0"]
+#[num_args(2)]
+async fn level_multiplier(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let Some(multiplier) = args.single::<f64>().ok().filter(|multiplier| (0.0..=MAX_MULTIPLIER).contains(multiplier)) else {
+        msg.reply(ctx, format!("Please give a multiplier from 0 to {MAX_MULTIPLIER}.")).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.get() as i64);
+
+    if (multiplier - 1.0).abs() < f64::EPSILON {
+        sqlx::query!("DELETE FROM level_multipliers WHERE guild_id = ? AND channel_id = ?", db_guild_id, db_channel_id)
+            .execute(&database)
+            .await?;
+    } else {
+        sqlx::query!(
+            "INSERT INTO level_multipliers (guild_id, channel_id, multiplier) VALUES (?, ?, ?)
+            ON CONFLICT (guild_id, channel_id) DO UPDATE SET multiplier = excluded.multiplier",
+            db_guild_id,
+            db_channel_id,
+            multiplier
+        ).execute(&database).await?;
+    }
+
+    reload_leveling(ctx, guild_id).await?;
+
+    if multiplier == 0.0 {
+        msg.reply(ctx, format!("Messages in {} no longer give XP.", channel_id.mention())).await?;
+    } else {
+        msg.reply(ctx, format!("Messages in {} now give {multiplier}x XP.", channel_id.mention())).await?;
+    }
+
+    Ok(())
+}
+
+#[command("reward")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives members a role once they reach a level. Setting a role again moves it to the new level."]
+#[usage = "<level> <role>"]
+#[example = "10 @Regular"]
+#[num_args(2)]
+async fn level_reward(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(level) = args.single::<i64>().ok().filter(|level| *level >= 1) else {
+        msg.reply(ctx, "Please give a level of 1 or more.").await?;
+        return Ok(());
+    };
+
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM level_rewards WHERE guild_id = ? AND role_id != ?",
+        db_guild_id,
+        db_role_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_REWARDS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_REWARDS} level rewards.")).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO level_rewards (guild_id, role_id, level) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id, role_id) DO UPDATE SET level = excluded.level",
+        db_guild_id,
+        db_role_id,
+        level
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Members will now be given {} at level {level}.", role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("unreward")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Stops giving a role as a level reward. Members who already have it keep it."]
+#[usage = "<role>"]
+#[example = "@Regular"]
+#[num_args(1)]
+async fn level_unreward(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let role_id = args.role_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_role_id) = (msg.guild_id.unwrap().get() as i64, role_id.get() as i64);
+
+    let deleted = sqlx::query!("DELETE FROM level_rewards WHERE guild_id = ? AND role_id = ?", guild_id, db_role_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("{} isn't a level reward.", role_id.mention())).await?;
+    } else {
+        msg.reply(ctx, format!("{} is no longer a level reward.", role_id.mention())).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows your level and rank in this server, or someone else's."]
+#[usage = "[member]"]
+#[sub_commands(rank_card)]
+#[max_args(1)]
+async fn rank(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let Some(standing) = standing(ctx, msg.guild_id.unwrap(), user_id).await? else {
+        msg.reply(ctx, "No XP earned here yet.").await?;
+        return Ok(());
+    };
+
+    let user = user_id.to_user(ctx).await?;
+    let (level, progress) = level_progress(standing.xp);
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .author(CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+        .field("Level", level.to_string(), true)
+        .field("Rank", format!("#{}", standing.rank), true)
+        .field("Total XP", standing.xp.to_string(), true)
+        .field("Next level", format!("{progress} / {} XP", xp_to_next_level(level)), false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("card")]
+#[only_in(guilds)]
+#[description = "Shows your level and rank as an image, or someone else's."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn rank_card(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let Some(standing) = standing(ctx, msg.guild_id.unwrap(), user_id).await? else {
+        msg.reply(ctx, "No XP earned here yet.").await?;
+        return Ok(());
+    };
+
+    let (level, progress) = level_progress(standing.xp);
+    let needed = xp_to_next_level(level);
+    let rank = standing.rank;
+
+    let card = tokio::task::spawn_blocking(move || render_rank_card(level, rank, progress, needed)).await??;
+
+    let builder = CreateMessage::new()
+        .content(format!("Rank card for {}", user_id.mention()))
+        .add_file(CreateAttachment::bytes(card, "rank.png"))
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows the members with the most XP in this server. `leaderboard coins` shows the richest members instead, `leaderboard voice` who's spent the most time in voice, `leaderboard invites` who's invited the most people and `leaderboard trivia` who's scored the most trivia points."]
+#[sub_commands(leaderboard_coins, leaderboard_voice, leaderboard_invites, leaderboard_trivia)]
+#[num_args(0)]
+async fn leaderboard(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let top = sqlx::query!(
+        "SELECT user_id, xp FROM levels WHERE guild_id = ? AND xp > 0 ORDER BY xp DESC LIMIT ?",
+        guild_id,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = top.iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let (level, _) = level_progress(row.xp);
+            format!("**{}.** {} - level {level} ({} XP)", index + 1, UserId::new(row.user_id as u64).mention(), row.xp)
+        })
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody has earned any XP here yet.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Leaderboard");
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+struct Standing {
+    xp: i64,
+    rank: i64
+}
+
+async fn standing(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<Option<Standing>, BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let standing = sqlx::query!(
+        r#"SELECT xp, (SELECT COUNT(*) FROM levels AS others WHERE others.guild_id = levels.guild_id AND others.xp > levels.xp) + 1 AS "rank!: i64"
+        FROM levels WHERE guild_id = ? AND user_id = ?"#,
+        guild_id,
+        user_id
+    ).fetch_optional(&database).await?;
+
+    Ok(standing.map(|row| Standing { xp: row.xp, rank: row.rank }))
+}
@@ -0,0 +1,339 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::automod::{AutomodAction, parse_ids};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::links::{invite_code, normalize_domain, reload_link_settings};
+
+/// Most invites or domains a single list can hold.
+const MAX_LIST_ENTRIES: usize = 50;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Shows this server's link control. Messages with a blocked invite or link, or with any link outside the link channels if there are any, are removed and their author is acted on, including when links are edited in."]
+#[sub_commands(links_invites, links_allow, links_disallow, links_block, links_unblock, links_channels, links_action, links_off)]
+async fn links(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT block_invites, allowed_invites, blocked_domains, link_channels, action FROM link_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(&database).await?;
+
+    let Some(settings) = settings else {
+        msg.reply(ctx, "Link control is off. Use `links invites on`, `links block` or `links channels` to set it up.").await?;
+        return Ok(());
+    };
+
+    let or_none = |list: &str| if list.is_empty() { "None".to_string() } else { list.split(',').map(|item| format!("`{item}`")).collect::<Vec<_>>().join(", ") };
+
+    let channels = parse_ids(&settings.link_channels).into_iter()
+        .filter(|id| *id != 0)
+        .map(|id| ChannelId::new(id).mention().to_string())
+        .collect::<Vec<_>>();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Link control")
+        .field("Invites", if settings.block_invites != 0 { "Blocked" } else { "Allowed" }, true)
+        .field("Action", settings.action.clone(), true)
+        .field("Links allowed in", if channels.is_empty() { "Every channel".to_string() } else { channels.join(" ") }, true)
+        .field("Allowed invites", or_none(&settings.allowed_invites), false)
+        .field("Blocked websites", or_none(&settings.blocked_domains), false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("invites")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether Discord invites are blocked, except the ones allowed with `links allow`."]
+#[usage = "<on|off>"]
+#[example = "on"]
+#[num_args(1)]
+async fn links_invites(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let block_invites = match args.rest().trim().to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO link_settings (guild_id, block_invites) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET block_invites = excluded.block_invites",
+        db_guild_id,
+        block_invites
+    ).execute(&database).await?;
+
+    reload_link_settings(ctx, guild_id).await?;
+
+    if block_invites {
+        msg.reply(ctx, "Discord invites are now blocked, except the ones allowed with `links allow`.").await?;
+    } else {
+        msg.reply(ctx, "Discord invites are no longer blocked.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("allow")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Allows an invite while invites are blocked, like this server's own or a partner's."]
+#[usage = "<invite>"]
+#[example = "discord.gg/rust-lang"]
+#[num_args(1)]
+async fn links_allow(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(code) = invite_code(args.rest().trim()) else {
+        msg.reply(ctx, "Please give an invite link or code.").await?;
+        return Ok(());
+    };
+
+    update_list(ctx, msg, List::AllowedInvites, code, true).await
+}
+
+#[command("disallow")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Stops allowing an invite that was allowed with `links allow`."]
+#[usage = "<invite>"]
+#[example = "discord.gg/rust-lang"]
+#[num_args(1)]
+async fn links_disallow(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(code) = invite_code(args.rest().trim()) else {
+        msg.reply(ctx, "Please give an invite link or code.").await?;
+        return Ok(());
+    };
+
+    update_list(ctx, msg, List::AllowedInvites, code, false).await
+}
+
+#[command("block")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Blocks links to a website, including its subdomains."]
+#[usage = "<domain>"]
+#[example = "example.com"]
+#[num_args(1)]
+async fn links_block(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(domain) = normalize_domain(args.rest()) else {
+        msg.reply(ctx, "Please give a domain, like `example.com`.").await?;
+        return Ok(());
+    };
+
+    update_list(ctx, msg, List::BlockedDomains, domain, true).await
+}
+
+#[command("unblock")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Unblocks a website blocked with `links block`."]
+#[usage = "<domain>"]
+#[example = "example.com"]
+#[num_args(1)]
+async fn links_unblock(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(domain) = normalize_domain(args.rest()) else {
+        msg.reply(ctx, "Please give a domain, like `example.com`.").await?;
+        return Ok(());
+    };
+
+    update_list(ctx, msg, List::BlockedDomains, domain, false).await
+}
+
+#[derive(Clone, Copy)]
+enum List {
+    AllowedInvites,
+    BlockedDomains
+}
+
+/// Adds an entry to, or removes one from, the allowed invites or blocked domains.
+async fn update_list(ctx: &Context, msg: &Message, list: List, entry: String, add: bool) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let current = sqlx::query!("SELECT allowed_invites, blocked_domains FROM link_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?
+        .map(|row| match list {
+            List::AllowedInvites => row.allowed_invites,
+            List::BlockedDomains => row.blocked_domains
+        })
+        .unwrap_or_default();
+
+    let mut entries: Vec<&str> = current.split(',').filter(|existing| !existing.is_empty()).collect();
+    let present = entries.contains(&entry.as_str());
+
+    if add == present {
+        let reply = match (list, add) {
+            (List::AllowedInvites, true) => format!("The invite `{entry}` is already allowed."),
+            (List::AllowedInvites, false) => format!("The invite `{entry}` isn't allowed."),
+            (List::BlockedDomains, true) => format!("`{entry}` is already blocked."),
+            (List::BlockedDomains, false) => format!("`{entry}` isn't blocked.")
+        };
+
+        msg.reply(ctx, reply).await?;
+        return Ok(());
+    }
+
+    if add {
+        if entries.len() >= MAX_LIST_ENTRIES {
+            msg.reply(ctx, format!("That list already has the maximum of {MAX_LIST_ENTRIES} entries.")).await?;
+            return Ok(());
+        }
+
+        entries.push(&entry);
+    } else {
+        entries.retain(|existing| *existing != entry);
+    }
+
+    let updated = entries.join(",");
+
+    match list {
+        List::AllowedInvites => sqlx::query!(
+            "INSERT INTO link_settings (guild_id, allowed_invites) VALUES (?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET allowed_invites = excluded.allowed_invites",
+            db_guild_id,
+            updated
+        ).execute(&database).await?,
+        List::BlockedDomains => sqlx::query!(
+            "INSERT INTO link_settings (guild_id, blocked_domains) VALUES (?, ?)
+            ON CONFLICT (guild_id) DO UPDATE SET blocked_domains = excluded.blocked_domains",
+            db_guild_id,
+            updated
+        ).execute(&database).await?
+    };
+
+    reload_link_settings(ctx, guild_id).await?;
+
+    let reply = match (list, add) {
+        (List::AllowedInvites, true) => format!("The invite `{entry}` is now allowed."),
+        (List::AllowedInvites, false) => format!("The invite `{entry}` is no longer allowed."),
+        (List::BlockedDomains, true) => format!("Links to `{entry}` are now blocked."),
+        (List::BlockedDomains, false) => format!("Links to `{entry}` are no longer blocked.")
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("channels")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Limits links to some channels, removing links posted anywhere else, or `all` to allow links in every channel."]
+#[usage = "<channels...|all>"]
+#[example = "#media #links"]
+#[min_args(1)]
+async fn links_channels(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut channels = Vec::new();
+
+    if !args.keyword("all") {
+        while !args.is_empty() {
+            channels.push(args.channel(ctx, msg).await?.id.to_string());
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let link_channels = channels.join(",");
+
+    sqlx::query!(
+        "INSERT INTO link_settings (guild_id, link_channels) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET link_channels = excluded.link_channels",
+        db_guild_id,
+        link_channels
+    ).execute(&database).await?;
+
+    reload_link_settings(ctx, guild_id).await?;
+
+    if channels.is_empty() {
+        msg.reply(ctx, "Links are now allowed in every channel.").await?;
+    } else {
+        msg.reply(ctx, "Links are now only allowed in those channels.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("action")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets what happens to the author of a message with a blocked link. Their message is always removed: `delete` does nothing more, `warn` (the default) warns them, `mute` mutes them for 10 minutes and `ban` bans them."]
+#[usage = "<delete|warn|mute|ban>"]
+#[example = "delete"]
+#[num_args(1)]
+async fn links_action(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(action) = AutomodAction::parse(&args.single::<String>()?) else {
+        msg.reply(ctx, "Actions are `delete`, `warn`, `mute` and `ban`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+    let action_name = action.as_str();
+
+    sqlx::query!(
+        "INSERT INTO link_settings (guild_id, action) VALUES (?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET action = excluded.action",
+        db_guild_id,
+        action_name
+    ).execute(&database).await?;
+
+    reload_link_settings(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Link control now uses the `{action_name}` action.")).await?;
+
+    Ok(())
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns link control off, clearing its allowed invites, blocked websites and link channels."]
+#[num_args(0)]
+async fn links_off(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM link_settings WHERE guild_id = ?", db_guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if removed == 0 {
+        msg.reply(ctx, "Link control is already off.").await?;
+        return Ok(());
+    }
+
+    reload_link_settings(ctx, guild_id).await?;
+
+    msg.reply(ctx, "Link control is now off.").await?;
+
+    Ok(())
+}
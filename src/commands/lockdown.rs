@@ -0,0 +1,337 @@
+use chrono::Duration;
+use serenity::builder::{CreateMessage, EditChannel};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::lockdown::{is_lockable, lock_channel, unlock_channel};
+use crate::utilities::moderation::{ModAction, record_channel_action};
+use crate::utilities::parsing::{format_duration, parse_channel, parse_duration};
+
+/// Longest slowmode Discord allows, in seconds.
+const MAX_SLOWMODE: i64 = 21_600;
+
+/// Most channels a lockdown can lock.
+const MAX_LOCKDOWN_CHANNELS: usize = 25;
+
+/// Whether a channel in the guild can be locked and slowed down.
+fn lockable_channel(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+    guild_id.to_guild_cached(&ctx.cache).is_some_and(|guild| {
+        guild.channels.get(&channel_id).is_some_and(|channel| is_lockable(channel.kind))
+    })
+}
+
+/// The channel named by the next argument, if it names one, or else the channel the command was
+/// used in. Returns `None` after telling the author if that channel can't be used.
+async fn target_channel(ctx: &Context, msg: &Message, args: &mut Args) -> Result<Option<ChannelId>, SerenityError> {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = match args.current().and_then(parse_channel) {
+        Some(channel_id) => {
+            args.advance();
+            channel_id
+        }
+        None => msg.channel_id
+    };
+
+    if !lockable_channel(ctx, guild_id, channel_id) {
+        msg.reply(ctx, "Please use this in, or mention, a text, voice or forum channel in this server.").await?;
+        return Ok(None);
+    }
+
+    Ok(Some(channel_id))
+}
+
+fn describe_slowmode(seconds: i64) -> String {
+    match seconds {
+        0 => "off".to_string(),
+        seconds => format_duration(Duration::seconds(seconds))
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Sets how long members have to wait between messages in a channel, this one by default. Use `off` or `0` to turn slowmode off."]
+#[usage = "<seconds|duration|off> [channel] [reason]"]
+#[example = "30 #general Heated discussion"]
+#[min_args(1)]
+async fn slowmode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let arg = args.single::<String>()?.to_lowercase();
+
+    let seconds = match (arg.as_str(), arg.parse::<i64>(), parse_duration(&arg)) {
+        ("off", ..) => 0,
+        (_, Ok(seconds), _) => seconds,
+        (_, _, Some(duration)) => duration.num_seconds(),
+        _ => {
+            msg.reply(ctx, "Please give a number of seconds, a duration such as `5m`, or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    if !(0..=MAX_SLOWMODE).contains(&seconds) {
+        msg.reply(ctx, format!("Slowmode can be at most {}.", describe_slowmode(MAX_SLOWMODE))).await?;
+        return Ok(());
+    }
+
+    let Some(channel_id) = target_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    let previous = guild_id.to_guild_cached(&ctx.cache)
+        .and_then(|guild| guild.channels.get(&channel_id).and_then(|channel| channel.rate_limit_per_user))
+        .unwrap_or_default();
+
+    let mut builder = EditChannel::new().rate_limit_per_user(seconds as u16);
+
+    if let Some(reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    channel_id.edit(ctx, builder).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let case = record_channel_action(ctx, &database, guild_id, ModAction::Slowmode, Some(channel_id), msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!(
+        "Slowmode in {} is now {} (was {}, case #{case}).",
+        channel_id.mention(),
+        describe_slowmode(seconds),
+        describe_slowmode(i64::from(previous))
+    )).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Stops everyone from sending messages in a channel, this one by default. Roles allowed to talk there by their own overwrites still can. `unlock` puts the channel's permissions back the way they were."]
+#[usage = "[channel] [reason]"]
+#[example = "#general Raid in progress"]
+async fn lock(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = target_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if !lock_channel(ctx, &database, guild_id, channel_id, false).await? {
+        msg.reply(ctx, format!("{} is already locked.", channel_id.mention())).await?;
+        return Ok(());
+    }
+
+    let case = record_channel_action(ctx, &database, guild_id, ModAction::Lock, Some(channel_id), msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!("Locked {} (case #{case}).", channel_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Unlocks a channel locked with `lock` or a lockdown, this one by default, restoring the permissions it had before."]
+#[usage = "[channel] [reason]"]
+#[example = "#general Raid is over"]
+async fn unlock(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = target_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if !unlock_channel(ctx, &database, channel_id).await? {
+        msg.reply(ctx, format!("{} isn't locked.", channel_id.mention())).await?;
+        return Ok(());
+    }
+
+    let case = record_channel_action(ctx, &database, msg.guild_id.unwrap(), ModAction::Unlock, Some(channel_id), msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!("Unlocked {} (case #{case}).", channel_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Locks every lockdown channel at once, see `lockdown channels` to choose them. Channels that were already locked stay locked when the lockdown ends."]
+#[usage = "[reason]"]
+#[example = "Raid in progress"]
+#[sub_commands(lockdown_end, lockdown_channels)]
+async fn lockdown(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channels = sqlx::query!("SELECT channel_id FROM lockdown_channels WHERE guild_id = ?", db_guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if channels.is_empty() {
+        msg.reply(ctx, "This server has no lockdown channels. Choose them with `lockdown channels`.").await?;
+        return Ok(());
+    }
+
+    let prompt = format!("Lock {} lockdown channel{}?", channels.len(), if channels.len() == 1 { "" } else { "s" });
+
+    if !confirm(ctx, msg, &prompt, CONFIRM_TIMEOUT).await? {
+        return Ok(());
+    }
+
+    let mut locked = 0;
+
+    for row in channels {
+        if lock_channel(ctx, &database, guild_id, ChannelId::new(row.channel_id as u64), true).await? {
+            locked += 1;
+        }
+    }
+
+    if locked == 0 {
+        msg.reply(ctx, "Every lockdown channel is already locked.").await?;
+        return Ok(());
+    }
+
+    let case = record_channel_action(ctx, &database, guild_id, ModAction::Lockdown, None, msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!("Locked down {locked} channels (case #{case}). End the lockdown with `lockdown end`.")).await?;
+
+    Ok(())
+}
+
+#[command("end")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Ends a lockdown, unlocking the channels it locked."]
+#[usage = "[reason]"]
+#[example = "Raid is over"]
+async fn lockdown_end(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channels = sqlx::query!("SELECT channel_id FROM locked_channels WHERE guild_id = ? AND lockdown = 1", db_guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if channels.is_empty() {
+        msg.reply(ctx, "This server isn't in lockdown.").await?;
+        return Ok(());
+    }
+
+    let mut unlocked = 0;
+
+    for row in channels {
+        if unlock_channel(ctx, &database, ChannelId::new(row.channel_id as u64)).await? {
+            unlocked += 1;
+        }
+    }
+
+    let case = record_channel_action(ctx, &database, guild_id, ModAction::EndLockdown, None, msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!("Ended the lockdown, unlocking {unlocked} channels (case #{case}).")).await?;
+
+    Ok(())
+}
+
+#[command("channels")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Shows or sets the channels a lockdown locks. Use `clear` to remove them all."]
+#[usage = "[channels...|clear]"]
+#[example = "#general #media #memes"]
+async fn lockdown_channels(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        let channels = sqlx::query!("SELECT channel_id FROM lockdown_channels WHERE guild_id = ?", db_guild_id)
+            .fetch_all(&database)
+            .await?;
+
+        let description = if channels.is_empty() {
+            "None yet. Set them with `lockdown channels <channels...>`.".to_string()
+        } else {
+            channels.iter()
+                .map(|row| ChannelId::new(row.channel_id as u64).mention().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let embed = branded_embed(ctx, msg.guild_id).await
+            .title("Lockdown channels")
+            .description(description);
+
+        msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+        return Ok(());
+    }
+
+    let mut channels = Vec::new();
+
+    if !args.keyword("clear") {
+        while !args.is_empty() {
+            let channel = args.channel(ctx, msg).await?;
+
+            if !is_lockable(channel.kind) {
+                msg.reply(ctx, format!("{} isn't a text, voice or forum channel.", channel.mention())).await?;
+                return Ok(());
+            }
+
+            if !channels.contains(&channel.id) {
+                channels.push(channel.id);
+            }
+        }
+
+        if channels.len() > MAX_LOCKDOWN_CHANNELS {
+            msg.reply(ctx, format!("A lockdown can lock at most {MAX_LOCKDOWN_CHANNELS} channels.")).await?;
+            return Ok(());
+        }
+    }
+
+    let mut transaction = database.begin().await?;
+
+    sqlx::query!("DELETE FROM lockdown_channels WHERE guild_id = ?", db_guild_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for channel_id in &channels {
+        let channel_id = channel_id.get() as i64;
+
+        sqlx::query!("INSERT INTO lockdown_channels (channel_id, guild_id) VALUES (?, ?)", channel_id, db_guild_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    if channels.is_empty() {
+        msg.reply(ctx, "Cleared the lockdown channels.").await?;
+    } else {
+        msg.reply(ctx, format!("A lockdown will now lock {} channels.", channels.len())).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use serenity::builder::{CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::warn;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::{branded_embed, guild_branding};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::lookups::{LookupError, SafeSearch, define as lookup_definition, search as lookup_search, strip_urban_links, translate as lookup_translation, urban as lookup_urban, wiki as lookup_wiki};
+use crate::utilities::pagination::{Paginator, embed_pages, paginate_lines};
+
+const MAX_TERM_LENGTH: usize = 100;
+const MAX_TRANSLATE_LENGTH: usize = 1000;
+
+/// Meanings and definitions per meaning shown for a word.
+const MAX_MEANINGS: usize = 4;
+const MAX_DEFINITIONS: usize = 3;
+
+/// Urban Dictionary definitions that can be paged through.
+const MAX_URBAN_PAGES: usize = 5;
+
+const RESULTS_PER_PAGE: usize = 5;
+const MAX_RESULTS: usize = 25;
+
+#[command]
+#[description = "Looks up an English word in the dictionary."]
+#[usage = "<word>"]
+#[example = "serendipity"]
+#[min_args(1)]
+async fn define(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let word = args.rest().trim();
+
+    if word.chars().count() > MAX_TERM_LENGTH {
+        msg.reply(ctx, format!("Words can be at most {MAX_TERM_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let entries = match lookup_definition(ctx, word).await {
+        Ok(Some(entries)) => entries,
+        Ok(None) => {
+            msg.reply(ctx, format!("I couldn't find `{word}` in the dictionary.")).await?;
+            return Ok(());
+        }
+        Err(why) => return lookup_failed(ctx, msg, "the dictionary", why).await
+    };
+
+    let Some(entry) = entries.first() else {
+        msg.reply(ctx, format!("I couldn't find `{word}` in the dictionary.")).await?;
+        return Ok(());
+    };
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(&entry.word)
+        .footer(CreateEmbedFooter::new("Definitions from dictionaryapi.dev"));
+
+    if let Some(phonetic) = &entry.phonetic {
+        embed = embed.description(phonetic);
+    }
+
+    // the same word can have several entries, such as a noun and a verb
+    let meanings = entries.iter().flat_map(|entry| &entry.meanings).take(MAX_MEANINGS);
+
+    for meaning in meanings {
+        let definitions = meaning.definitions.iter()
+            .take(MAX_DEFINITIONS)
+            .enumerate()
+            .map(|(index, definition)| match &definition.example {
+                Some(example) => format!("{}. {}\n*\"{example}\"*", index + 1, definition.definition),
+                None => format!("{}. {}", index + 1, definition.definition)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        embed = embed.field(&meaning.part_of_speech, truncate(&definitions, 1024), false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Looks up a term on Urban Dictionary. Only works in age-restricted channels and DMs."]
+#[usage = "<term>"]
+#[example = "yeet"]
+#[min_args(1)]
+async fn urban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let term = args.rest().trim();
+
+    let nsfw = match msg.channel(ctx).await? {
+        Channel::Guild(channel) => channel.nsfw,
+        _ => true
+    };
+
+    if !nsfw {
+        msg.reply(ctx, "Urban Dictionary can only be used in age-restricted channels.").await?;
+        return Ok(());
+    }
+
+    if term.chars().count() > MAX_TERM_LENGTH {
+        msg.reply(ctx, format!("Terms can be at most {MAX_TERM_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let definitions = match lookup_urban(ctx, term).await {
+        Ok(definitions) => definitions,
+        Err(why) => return lookup_failed(ctx, msg, "Urban Dictionary", why).await
+    };
+
+    if definitions.is_empty() {
+        msg.reply(ctx, format!("Urban Dictionary doesn't have a definition for `{term}`.")).await?;
+        return Ok(());
+    }
+
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let pages = definitions.iter()
+        .take(MAX_URBAN_PAGES)
+        .map(|definition| {
+            let mut embed = branding.embed()
+                .title(&definition.word)
+                .url(&definition.permalink)
+                .description(truncate(&strip_urban_links(&definition.definition), 2000))
+                .footer(CreateEmbedFooter::new(format!("By {} | 👍 {} 👎 {}", definition.author, definition.thumbs_up, definition.thumbs_down)));
+
+            if !definition.example.trim().is_empty() {
+                embed = embed.field("Example", truncate(&strip_urban_links(&definition.example), 1024), false);
+            }
+
+            embed
+        })
+        .collect();
+
+    Paginator::new(pages).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Translates text into another language, given by its code. The language the text is in is detected."]
+#[usage = "<language> <text>"]
+#[example = "de Where is the train station?"]
+#[min_args(2)]
+async fn translate(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let language = args.text("language")?;
+    let text = args.rest().trim();
+
+    // language codes like `de` or `pt-BR`
+    if !(2..=8).contains(&language.len()) || !language.chars().all(|character| character.is_ascii_alphabetic() || character == '-') {
+        msg.reply(ctx, "Please give a language code to translate into, such as `en`, `de` or `pt-BR`.").await?;
+        return Ok(());
+    }
+
+    if text.chars().count() > MAX_TRANSLATE_LENGTH {
+        msg.reply(ctx, format!("I can translate at most {MAX_TRANSLATE_LENGTH} characters at once.")).await?;
+        return Ok(());
+    }
+
+    let translation = match lookup_translation(ctx, &language, text).await {
+        Ok(translation) => translation,
+        Err(LookupError::Rejected) => {
+            msg.reply(ctx, format!("I couldn't translate that into `{language}`, check that the language code is right.")).await?;
+            return Ok(());
+        }
+        Err(why) => return lookup_failed(ctx, msg, "the translation service", why).await
+    };
+
+    let from = match translation.source_language {
+        Some(source) => format!("Translated from `{source}` to `{language}`"),
+        None => format!("Translated to `{language}`")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .description(truncate(&translation.text, 4000))
+        .footer(CreateEmbedFooter::new(from));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).reference_message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the summary of the Wikipedia article best matching a search."]
+#[usage = "<search>"]
+#[example = "Graf Zeppelin"]
+#[min_args(1)]
+async fn wiki(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+
+    if query.chars().count() > MAX_TERM_LENGTH {
+        msg.reply(ctx, format!("Searches can be at most {MAX_TERM_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let summary = match lookup_wiki(ctx, query).await {
+        Ok(Some(summary)) => summary,
+        Ok(None) => {
+            msg.reply(ctx, format!("I couldn't find a Wikipedia article for `{query}`.")).await?;
+            return Ok(());
+        }
+        Err(why) => return lookup_failed(ctx, msg, "Wikipedia", why).await
+    };
+
+    let description = if summary.kind == "disambiguation" {
+        format!("{}\n\nThis can mean several things, open the article to pick one.", summary.extract)
+    } else {
+        summary.extract.clone()
+    };
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(&summary.title)
+        .url(&summary.content_urls.desktop.page)
+        .description(truncate(&description, 2000))
+        .footer(CreateEmbedFooter::new("From Wikipedia, the free encyclopedia"));
+
+    if let Some(thumbnail) = &summary.thumbnail {
+        embed = embed.thumbnail(&thumbnail.source);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Searches the web. SafeSearch is strict in channels that aren't age-restricted and off in ones that are."]
+#[usage = "<search>"]
+#[example = "rust async book"]
+#[min_args(1)]
+async fn search(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+
+    if query.chars().count() > MAX_TERM_LENGTH {
+        msg.reply(ctx, format!("Searches can be at most {MAX_TERM_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let safe_search = match msg.channel(ctx).await? {
+        Channel::Guild(channel) if channel.nsfw => SafeSearch::Off,
+        Channel::Guild(_) => SafeSearch::Strict,
+        _ => SafeSearch::Moderate
+    };
+
+    let results = match lookup_search(ctx, query, safe_search).await {
+        Ok(results) => results,
+        Err(why) => return lookup_failed(ctx, msg, "web search", why).await
+    };
+
+    if results.is_empty() {
+        msg.reply(ctx, format!("I couldn't find anything for `{query}`.")).await?;
+        return Ok(());
+    }
+
+    let lines = results.iter()
+        .take(MAX_RESULTS)
+        .map(|result| {
+            let title = truncate(&result.title.replace(['[', ']'], ""), 100);
+
+            match &result.snippet {
+                Some(snippet) => format!("**[{title}]({})**\n{}", result.url, truncate(snippet, 200)),
+                None => format!("**[{title}]({})**", result.url)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Results for {}", truncate(query, 200)));
+
+    Paginator::new(embed_pages(embed, paginate_lines(&lines, RESULTS_PER_PAGE, "\n\n"))).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+async fn lookup_failed(ctx: &Context, msg: &Message, service: &str, why: LookupError) -> CommandResult {
+    let reply = match why {
+        LookupError::NotConfigured => format!("I can't use {service} here, it isn't set up for this bot."),
+        LookupError::Rejected => format!("I couldn't look that up in {service}."),
+        LookupError::Request(why) => {
+            warn!("Request to {service} failed: {why}");
+            format!("I couldn't reach {service}, please try again later.")
+        }
+        LookupError::Data(why) => return Err(why.into())
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    let mut truncated = text.chars().take(length - 3).collect::<String>();
+    truncated.push_str("...");
+
+    truncated
+}
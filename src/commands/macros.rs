@@ -0,0 +1,268 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::Utc;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::dispatch::{resolve_command, invoke_command};
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+
+/// Most steps a single macro can run.
+const MAX_STEPS: usize = 10;
+
+/// Most macros a single guild can define.
+const MAX_MACROS: i32 = 25;
+
+#[command("macro")]
+#[only_in(guilds)]
+#[description = "Lists this server's command macros. Macros run a sequence of commands with one invocation."]
+#[sub_commands(macro_create, macro_delete, macro_list, macro_show, macro_run)]
+async fn command_macro(ctx: &Context, msg: &Message) -> CommandResult {
+    list_macros(ctx, msg).await
+}
+
+#[command("create")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Creates (or replaces) a macro from commands separated by `;`. Prefixes are optional."]
+#[usage = "<name>: <command>; <command>; ..."]
+#[example = "raidcleanup: suggestions off; updates unsubscribe"]
+#[min_args(2)]
+async fn macro_create(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((name, steps)) = args.rest().split_once(':') else {
+        msg.reply(ctx, "Please separate the macro name and its commands with `:`, e.g. `macro create cleanup: suggestions off; updates unsubscribe`.").await?;
+        return Ok(());
+    };
+
+    let name = name.trim().to_lowercase();
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        msg.reply(ctx, "Macro names must be a single word.").await?;
+        return Ok(());
+    }
+
+    let prefix = guild_prefix(ctx, msg.guild_id.unwrap()).await;
+
+    let steps: Vec<String> = steps.split(';')
+        .map(|step| {
+            let step = step.trim();
+            step.strip_prefix(prefix.as_str()).unwrap_or(step).trim().to_string()
+        })
+        .filter(|step| !step.is_empty())
+        .collect();
+
+    if steps.is_empty() || steps.len() > MAX_STEPS {
+        msg.reply(ctx, format!("Macros need between 1 and {MAX_STEPS} commands.")).await?;
+        return Ok(());
+    }
+
+    for step in &steps {
+        match resolve_command(step) {
+            Some(resolved) if resolved.command.options.names.contains(&"macro") => {
+                msg.reply(ctx, "Macros can't run other macros.").await?;
+                return Ok(());
+            }
+            Some(_) => {}
+            None => {
+                msg.reply(ctx, format!("`{step}` doesn't start with a known command.")).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM command_macros WHERE guild_id = ? AND name != ?",
+        guild_id,
+        name
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_MACROS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_MACROS} macros.")).await?;
+        return Ok(());
+    }
+
+    let joined = steps.join("\n");
+    let created_by = msg.author.id.get() as i64;
+    let created_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO command_macros (guild_id, name, steps, created_by, created_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, name) DO UPDATE SET
+            steps = excluded.steps,
+            created_by = excluded.created_by,
+            created_at = excluded.created_at",
+        guild_id,
+        name,
+        joined,
+        created_by,
+        created_at
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Saved macro `{name}` with {} step(s). Run it with `{prefix}macro run {name}`.", steps.len())).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Deletes a macro."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn macro_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM command_macros WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no macro named `{name}`.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted macro `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's macros."]
+async fn macro_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_macros(ctx, msg).await
+}
+
+#[command("show")]
+#[only_in(guilds)]
+#[description = "Shows the commands a macro runs."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn macro_show(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let Some(steps) = fetch_steps(ctx, msg.guild_id.unwrap(), &name).await? else {
+        msg.reply(ctx, format!("There is no macro named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let description = steps.iter()
+        .enumerate()
+        .map(|(index, step)| format!("{}. `{step}`", index + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Macro: {name}"))
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("run")]
+#[only_in(guilds)]
+#[description = "Runs every command in a macro in order, stopping at the first one that fails. Each command is checked against your own permissions."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn macro_run(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let Some(steps) = fetch_steps(ctx, msg.guild_id.unwrap(), &name).await? else {
+        msg.reply(ctx, format!("There is no macro named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let mut report = Vec::with_capacity(steps.len());
+    let mut failed = false;
+
+    for step in &steps {
+        if failed {
+            report.push(format!("⏭️ `{step}` - skipped"));
+            continue;
+        }
+
+        let result = match resolve_command(step) {
+            Some(resolved) => invoke_command(ctx, msg, &resolved).await,
+            None => Err("unknown command".to_string())
+        };
+
+        match result {
+            Ok(()) => report.push(format!("✅ `{step}`")),
+            Err(why) => {
+                report.push(format!("❌ `{step}` - {why}"));
+                failed = true;
+            }
+        }
+    }
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Macro: {name}"))
+        .description(report.join("\n"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+async fn list_macros(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let macros = sqlx::query!(
+        "SELECT name, steps FROM command_macros WHERE guild_id = ? ORDER BY name",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if macros.is_empty() {
+        "This server has no macros yet.".to_string()
+    } else {
+        macros.iter()
+            .map(|row| format!("`{}` - {} step(s)", row.name, row.steps.lines().count()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Macros")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+async fn fetch_steps(ctx: &Context, guild_id: GuildId, name: &str) -> Result<Option<Vec<String>>, BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT steps FROM command_macros WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).fetch_optional(&database).await?;
+
+    Ok(row.map(|row| row.steps.lines().map(str::to_string).collect()))
+}
+
+async fn guild_prefix(ctx: &Context, guild_id: GuildId) -> String {
+    match get_or_create_guild_settings(ctx, guild_id).await {
+        Ok(settings) => settings.read().await.prefix.clone(),
+        Err(_) => "-".to_string()
+    }
+}
@@ -0,0 +1,202 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::media_rules::{MediaMode, format_size, reload_media_rules};
+
+/// Parses a file size like `8mb`, `500kb` or `8` (in megabytes) into bytes.
+fn parse_size(arg: &str) -> Option<u64> {
+    let arg = arg.trim().to_lowercase();
+
+    let (number, multiplier) = if let Some(number) = arg.strip_suffix("kb") {
+        (number, 1_000.0)
+    } else {
+        (arg.strip_suffix("mb").unwrap_or(&arg), 1_000_000.0)
+    };
+
+    let bytes = number.trim().parse::<f64>().ok().filter(|number| number.is_finite() && *number > 0.0)? * multiplier;
+
+    (bytes >= 1.0 && bytes <= u32::MAX as f64).then_some(bytes as u64)
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Lists the channels with media rules. Media rules limit what can be posted in a channel, like only images in an art channel, and remove anything else. Members who can manage messages in the channel aren't affected."]
+#[sub_commands(mediarules_mode, mediarules_maxsize, mediarules_clear)]
+async fn mediarules(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let rules = sqlx::query!("SELECT channel_id, mode, max_file_size FROM media_rules WHERE guild_id = ? ORDER BY channel_id", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let description = if rules.is_empty() {
+        "No channel has media rules yet. Use `mediarules mode` or `mediarules maxsize` to add some.".to_string()
+    } else {
+        rules.iter()
+            .map(|row| {
+                let mut limits = Vec::new();
+
+                if let Some(mode) = row.mode.as_deref().and_then(MediaMode::parse) {
+                    limits.push(mode.describe().to_string());
+                }
+
+                if let Some(max_file_size) = row.max_file_size {
+                    limits.push(format!("attachments up to {}", format_size(max_file_size.max(0) as u64)));
+                }
+
+                format!("{}: {}", ChannelId::new(row.channel_id as u64).mention(), limits.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Media rules")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("mode")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Sets what can be posted in a channel: `media-only` allows only messages with an attachment or a link, `images-only` only messages with images attached, and `no-attachments` no attachments at all. Use `off` to allow anything again."]
+#[usage = "<channel> <media-only|images-only|no-attachments|off>"]
+#[example = "#art images-only"]
+#[num_args(2)]
+async fn mediarules_mode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let arg = args.single::<String>()?;
+
+    let mode = match MediaMode::parse(&arg) {
+        Some(mode) => Some(mode),
+        None if arg.eq_ignore_ascii_case("off") => None,
+        None => {
+            msg.reply(ctx, "The modes are `media-only`, `images-only` and `no-attachments`, or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_channel_id, guild_id) = (channel_id.get() as i64, msg.guild_id.unwrap().get() as i64);
+    let mode_name = mode.map(MediaMode::as_str);
+
+    sqlx::query!(
+        "INSERT INTO media_rules (channel_id, guild_id, mode) VALUES (?, ?, ?)
+        ON CONFLICT (channel_id) DO UPDATE SET mode = excluded.mode",
+        db_channel_id,
+        guild_id,
+        mode_name
+    ).execute(&database).await?;
+
+    forget_empty_rules(&database, db_channel_id).await?;
+    reload_media_rules(ctx, channel_id).await?;
+
+    match mode {
+        Some(mode) => msg.reply(ctx, format!("{} is now `{}`.", channel_id.mention(), mode.as_str())).await?,
+        None => msg.reply(ctx, format!("Anything can be posted in {} again.", channel_id.mention())).await?
+    };
+
+    Ok(())
+}
+
+#[command("maxsize")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Sets how large attachments posted in a channel can be, or `off` for no limit beyond Discord's own."]
+#[usage = "<channel> <size|off>"]
+#[example = "#memes 8mb"]
+#[num_args(2)]
+async fn mediarules_maxsize(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let arg = args.single::<String>()?;
+
+    let max_file_size = if arg.eq_ignore_ascii_case("off") {
+        None
+    } else {
+        let Some(bytes) = parse_size(&arg) else {
+            msg.reply(ctx, "Please give a size, like `8mb` or `500kb`, or `off`.").await?;
+            return Ok(());
+        };
+
+        Some(bytes)
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_channel_id, guild_id) = (channel_id.get() as i64, msg.guild_id.unwrap().get() as i64);
+    let db_max_file_size = max_file_size.map(|bytes| bytes as i64);
+
+    sqlx::query!(
+        "INSERT INTO media_rules (channel_id, guild_id, max_file_size) VALUES (?, ?, ?)
+        ON CONFLICT (channel_id) DO UPDATE SET max_file_size = excluded.max_file_size",
+        db_channel_id,
+        guild_id,
+        db_max_file_size
+    ).execute(&database).await?;
+
+    forget_empty_rules(&database, db_channel_id).await?;
+    reload_media_rules(ctx, channel_id).await?;
+
+    match max_file_size {
+        Some(bytes) => msg.reply(ctx, format!("Attachments in {} can now be at most {}.", channel_id.mention(), format_size(bytes))).await?,
+        None => msg.reply(ctx, format!("Attachments in {} no longer have a size limit.", channel_id.mention())).await?
+    };
+
+    Ok(())
+}
+
+#[command("clear")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_CHANNELS)]
+#[description = "Removes all media rules from a channel."]
+#[usage = "<channel>"]
+#[example = "#art"]
+#[num_args(1)]
+async fn mediarules_clear(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM media_rules WHERE channel_id = ?", db_channel_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if removed == 0 {
+        msg.reply(ctx, format!("{} has no media rules.", channel_id.mention())).await?;
+        return Ok(());
+    }
+
+    reload_media_rules(ctx, channel_id).await?;
+
+    msg.reply(ctx, format!("Removed the media rules of {}.", channel_id.mention())).await?;
+
+    Ok(())
+}
+
+/// Deletes a channel's row once neither of its rules is set anymore.
+async fn forget_empty_rules(database: &sqlx::SqlitePool, channel_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM media_rules WHERE channel_id = ? AND mode IS NULL AND max_file_size IS NULL", channel_id)
+        .execute(database)
+        .await?;
+
+    Ok(())
+}
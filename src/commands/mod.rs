@@ -1,4 +1,72 @@
 pub mod math;
 pub mod utilities;
 pub mod owner;
-pub mod info;
\ No newline at end of file
+pub mod info;
+pub mod premium;
+pub mod announcements;
+pub mod macros;
+pub mod scripts;
+pub mod templates;
+pub mod stats;
+pub mod counters;
+pub mod autoresponses;
+pub mod schedule;
+pub mod slash;
+pub mod moderation;
+pub mod warnings;
+pub mod purge;
+pub mod automod;
+pub mod antispam;
+pub mod antiraid;
+pub mod welcome;
+pub mod autoroles;
+pub mod role_menus;
+pub mod starboard;
+pub mod tags;
+pub mod custom_commands;
+pub mod reminders;
+pub mod polls;
+pub mod levels;
+pub mod economy;
+pub mod tickets;
+pub mod modmail;
+pub mod cooldowns;
+pub mod command_rules;
+pub mod permissions;
+pub mod settings;
+pub mod voice;
+pub mod invites;
+pub mod afk;
+pub mod sticky;
+pub mod lockdown;
+pub mod nicknames;
+pub mod bans;
+pub mod verification;
+pub mod join_gate;
+pub mod filters;
+pub mod links;
+pub mod media_rules;
+pub mod expressions;
+pub mod roles;
+pub mod quarantine;
+pub mod pin_archive;
+pub mod transcripts;
+pub mod help;
+pub mod fun;
+pub mod images;
+pub mod trivia;
+pub mod word_games;
+pub mod sfx;
+pub mod tts;
+pub mod reddit;
+pub mod github;
+pub mod weather;
+pub mod lookups;
+pub mod birthdays;
+pub mod timezones;
+pub mod events;
+pub mod chain_games;
+pub mod quotes;
+pub mod reports;
+pub mod mod_notes;
+pub mod watchlist;
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::mod_notes::{MAX_NOTE_LENGTH, user_notes};
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+
+#[command]
+#[aliases("notes")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Lists the notes staff keep about a member. Notes are only shown to moderators, in `userinfo` and when the member is the target of a moderation command."]
+#[usage = "<user>"]
+#[example = "@user"]
+#[sub_commands(note_add, note_list, note_remove)]
+#[num_args(1)]
+async fn note(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    list_notes(ctx, msg, args).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Keeps a note about a member for other moderators to see."]
+#[usage = "<user> <note>"]
+#[example = "@user Was warned verbally about spoilers in #general"]
+#[min_args(2)]
+async fn note_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+    let content = args.rest().trim();
+
+    if content.is_empty() {
+        msg.reply(ctx, "Please give the note to keep.").await?;
+        return Ok(());
+    }
+
+    if content.chars().count() > MAX_NOTE_LENGTH {
+        msg.reply(ctx, format!("Notes can be at most {MAX_NOTE_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id, moderator_id, created_at) = (msg.guild_id.unwrap().get() as i64, target_id.get() as i64, msg.author.id.get() as i64, Utc::now().to_rfc3339());
+
+    let id = sqlx::query!(
+        "INSERT INTO mod_notes (guild_id, user_id, moderator_id, content, created_at) VALUES (?, ?, ?, ?, ?)",
+        guild_id,
+        user_id,
+        moderator_id,
+        content,
+        created_at
+    ).execute(&database).await?.last_insert_rowid();
+
+    msg.reply(ctx, format!("Added note #{id} about **{}**.", target_id.to_user(ctx).await.map_or_else(|_| target_id.to_string(), |user| user.tag()))).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Lists the notes staff keep about a member, newest first."]
+#[usage = "<user>"]
+#[example = "@user"]
+#[num_args(1)]
+async fn note_list(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    list_notes(ctx, msg, args).await
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Removes a note by its number, as shown by `note list`."]
+#[usage = "<note>"]
+#[example = "4"]
+#[num_args(1)]
+async fn note_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!("DELETE FROM mod_notes WHERE id = ? AND guild_id = ?", id, guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no note #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Removed note #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+async fn list_notes(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let notes = user_notes(&database, msg.guild_id.unwrap(), target_id).await?;
+
+    let lines: Vec<String> = notes.iter()
+        .map(|note| {
+            let written = DateTime::parse_from_rfc3339(&note.created_at)
+                .map_or_else(|_| note.created_at.clone(), |time| format!("<t:{}:R>", time.timestamp()));
+
+            format!("**#{}** {} - by {} {written}", note.id, note.content, note.moderator_id.mention())
+        })
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec![format!("There are no notes about {}.", target_id.mention())]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Notes ({})", notes.len()));
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
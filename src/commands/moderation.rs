@@ -0,0 +1,435 @@
+use chrono::Duration;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings, update_guild_settings};
+use crate::utilities::mod_notes::show_notes;
+use crate::utilities::moderation::{ModAction, MuteType, MAX_TIMEOUT, apply_action, check_target, update_case_reason};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::server_log::server_log_channel;
+
+/// Longest temporary ban, longer ones should be permanent.
+const MAX_TEMPBAN: Duration = Duration::days(365);
+const MAX_TEMPMUTE: Duration = Duration::days(365);
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(KICK_MEMBERS)]
+#[description = "Kicks a member from the server. They're sent the reason in a DM."]
+#[usage = "<user> [reason]"]
+#[example = "@user Spamming in #general"]
+#[min_args(1)]
+async fn kick(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    moderate(ctx, msg, args, ModAction::Kick).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Bans a user from the server, even if they aren't a member. They're sent the reason in a DM."]
+#[usage = "<user> [reason]"]
+#[example = "@user Raiding"]
+#[min_args(1)]
+async fn ban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    moderate(ctx, msg, args, ModAction::Ban).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Bans a user for a while, e.g. `12h`, `7d` or `4w`. They're unbanned automatically, even if the bot restarts in between."]
+#[usage = "<user> <duration> [reason]"]
+#[example = "@user 7d Repeated spam"]
+#[min_args(2)]
+async fn tempban(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let duration = args.duration()?;
+
+    if duration > MAX_TEMPBAN {
+        msg.reply(ctx, format!("Temporary bans can last at most {}. Use `ban` for longer ones.", format_duration(MAX_TEMPBAN))).await?;
+        return Ok(());
+    }
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Tempban, Permissions::BAN_MEMBERS).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    show_notes(ctx, msg, target_id).await;
+
+    let target = target_id.to_user(ctx).await?;
+    let case = apply_action(ctx, guild_id, &target, &msg.author, ModAction::Tempban, Some(duration), reason).await?;
+
+    msg.reply(ctx, format!("Banned **{}** for {} (case #{case}).", target.tag(), format_duration(duration))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Lifts a user's ban."]
+#[usage = "<user ID> [reason]"]
+#[example = "123456789012345678 Appealed"]
+#[min_args(1)]
+async fn unban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    moderate(ctx, msg, args, ModAction::Unban).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(BAN_MEMBERS)]
+#[description = "Bans and immediately unbans a member, removing them and their messages from the last 7 days."]
+#[usage = "<user> [reason]"]
+#[example = "@user Spam bot"]
+#[min_args(1)]
+async fn softban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    moderate(ctx, msg, args, ModAction::Softban).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Mutes a member until they're unmuted, with a timeout or the mute role depending on `mutetype`. Timeouts can last at most 28 days."]
+#[usage = "<user> [reason]"]
+#[example = "@user Arguing in #general"]
+#[min_args(1)]
+async fn mute(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    mute_member(ctx, msg, target_id, None, args.rest()).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Mutes a member for a while, e.g. `10m`, `2h` or `3d`. They're unmuted automatically, even if the bot restarts in between."]
+#[usage = "<user> <duration> [reason]"]
+#[example = "@user 2h Spamming"]
+#[min_args(2)]
+async fn tempmute(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let duration = args.duration()?;
+
+    mute_member(ctx, msg, target_id, Some(duration), args.rest()).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Unmutes a member, removing their timeout or mute role."]
+#[usage = "<user> [reason]"]
+#[example = "@user"]
+#[min_args(1)]
+async fn unmute(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_mute(ctx, msg, target_id, ModAction::Unmute).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let target = target_id.to_user(ctx).await?;
+    let case = apply_action(ctx, guild_id, &target, &msg.author, ModAction::Unmute, None, reason).await?;
+
+    msg.reply(ctx, format!("Unmuted **{}** (case #{case}).", target.tag())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets how members are muted: with a Discord `timeout` (the default), or by giving them a mute `role`."]
+#[usage = "[timeout|role <role>]"]
+#[example = "role @Muted"]
+#[max_args(2)]
+async fn mutetype(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let (mute_style, mute_role) = match args.single::<String>().ok().map(|arg| arg.to_lowercase()).as_deref() {
+        None => {
+            let description = match MuteType::for_guild(ctx, guild_id).await {
+                Ok(MuteType::Timeout) => "Members are muted with a **timeout**.".to_string(),
+                Ok(MuteType::Role(role_id)) => format!("Members are muted by giving them {}.", role_id.mention()),
+                Err(why) => why
+            };
+
+            msg.reply(ctx, description).await?;
+            return Ok(());
+        }
+        Some("timeout") => ("timeout", 0),
+        Some("role") => {
+            let role_id = args.role(ctx, msg).await?.id;
+
+            if let Err(why) = MuteType::Role(role_id).check_role(ctx, guild_id) {
+                msg.reply(ctx, why).await?;
+                return Ok(());
+            }
+
+            ("role", role_id.get())
+        }
+        Some(_) => {
+            msg.reply(ctx, "Please specify either `timeout` or `role <role>`.").await?;
+            return Ok(());
+        }
+    };
+
+    update_guild_settings(ctx, guild_id, |settings| {
+        settings.mute_type = mute_style.to_string();
+        settings.mute_role = mute_role;
+    }).await?;
+
+    if mute_role == 0 {
+        msg.reply(ctx, "Members will now be muted with a timeout.").await?;
+    } else {
+        msg.reply(ctx, format!("Members will now be muted by giving them {}.", RoleId::new(mute_role).mention())).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the channel every moderation case is posted to. Use `off` to stop posting cases."]
+#[usage = "[channel|off]"]
+#[example = "#mod-log"]
+#[max_args(1)]
+async fn modlog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        let current = sqlx::query!("SELECT modlog_channel_id FROM guild_settings WHERE guild_id = ?", db_guild_id)
+            .fetch_optional(&database)
+            .await?
+            .and_then(|row| row.modlog_channel_id);
+
+        match current {
+            Some(channel_id) => msg.reply(ctx, format!("Cases are posted to {}.", ChannelId::new(channel_id as u64).mention())).await?,
+            None => msg.reply(ctx, "This server has no modlog channel.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET modlog_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Cases will now be posted to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Cases will no longer be posted.").await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the channel edited and deleted messages are logged to. Use `off` to stop logging them."]
+#[usage = "[channel|off]"]
+#[example = "#message-log"]
+#[max_args(1)]
+async fn messagelog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        let current = get_or_create_guild_settings(ctx, guild_id).await?.read().await.message_log_channel;
+
+        match current {
+            Some(channel_id) => msg.reply(ctx, format!("Edited and deleted messages are logged to {}.", ChannelId::new(channel_id).mention())).await?,
+            None => msg.reply(ctx, "This server has no message log channel.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    // turning the log off keeps the channel around once it's written back
+    update_guild_settings(ctx, guild_id, |settings| settings.message_log_channel = channel_id.map(ChannelId::get)).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Edited and deleted messages will now be logged to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Edited and deleted messages will no longer be logged.").await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the channel changes to roles, channels, emojis, webhooks and members' roles are logged to, with who made them when I can see the audit log. Use `off` to stop logging them."]
+#[usage = "[channel|off]"]
+#[example = "#server-log"]
+#[max_args(1)]
+async fn serverlog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        match server_log_channel(&database, guild_id).await? {
+            Some(channel_id) => msg.reply(ctx, format!("Server changes are logged to {}.", channel_id.mention())).await?,
+            None => msg.reply(ctx, "This server has no server log channel.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET server_log_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Server changes will now be logged to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Server changes will no longer be logged.").await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Changes the reason of a moderation case, updating its modlog message too."]
+#[usage = "<case> <reason>"]
+#[example = "12 Posting scam links"]
+#[min_args(2)]
+async fn reason(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let case = args.single::<i64>()?;
+    let reason = args.rest().trim();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if update_case_reason(ctx, &database, msg.guild_id.unwrap(), case, reason).await? {
+        msg.reply(ctx, format!("Updated the reason of case #{case}.")).await?;
+    } else {
+        msg.reply(ctx, format!("There is no case #{case}.")).await?;
+    }
+
+    Ok(())
+}
+
+/// Mutes a member the way the guild is set up to, until unmuted or for `duration`.
+async fn mute_member(ctx: &Context, msg: &Message, target_id: UserId, duration: Option<Duration>, reason: &str) -> CommandResult {
+    let reason = Some(reason.trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+    let action = if duration.is_some() { ModAction::Tempmute } else { ModAction::Mute };
+
+    let mute_type = match check_mute(ctx, msg, target_id, action).await {
+        Ok(mute_type) => mute_type,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    if matches!(mute_type, MuteType::Timeout) && duration.is_some_and(|duration| duration > MAX_TIMEOUT) {
+        msg.reply(ctx, format!("Timeouts can last at most {}.", format_duration(MAX_TIMEOUT))).await?;
+        return Ok(());
+    }
+
+    if duration.is_some_and(|duration| duration > MAX_TEMPMUTE) {
+        msg.reply(ctx, format!("Temporary mutes can last at most {}. Use `mute` for longer ones.", format_duration(MAX_TEMPMUTE))).await?;
+        return Ok(());
+    }
+
+    show_notes(ctx, msg, target_id).await;
+
+    let target = target_id.to_user(ctx).await?;
+    let case = apply_action(ctx, guild_id, &target, &msg.author, action, duration, reason).await?;
+    let length = duration.map_or_else(String::new, |duration| format!(" for {}", format_duration(duration)));
+
+    msg.reply(ctx, format!("Muted **{}**{length} (case #{case}).", target.tag())).await?;
+
+    Ok(())
+}
+
+/// The guild's mute type, once it's been checked that the author may (un)mute the target with it.
+async fn check_mute(ctx: &Context, msg: &Message, target_id: UserId, action: ModAction) -> Result<MuteType, String> {
+    let guild_id = msg.guild_id.unwrap();
+    let mute_type = MuteType::for_guild(ctx, guild_id).await?;
+
+    check_target(ctx, guild_id, msg.author.id, target_id, action, mute_type.required_permission()).await?;
+    mute_type.check_role(ctx, guild_id)?;
+
+    Ok(mute_type)
+}
+
+async fn moderate(ctx: &Context, msg: &Message, mut args: Args, action: ModAction) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    let permission = match action {
+        ModAction::Kick => Permissions::KICK_MEMBERS,
+        _ => Permissions::BAN_MEMBERS
+    };
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, action, permission).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    show_notes(ctx, msg, target_id).await;
+
+    let target = target_id.to_user(ctx).await?;
+
+    // bans can't be taken back without the user rejoining, so they're confirmed first
+    if matches!(action, ModAction::Ban | ModAction::Softban) {
+        let prompt = format!("{} **{}**?", capitalize(action.as_str()), target.tag());
+
+        if !confirm(ctx, msg, &prompt, CONFIRM_TIMEOUT).await? {
+            return Ok(());
+        }
+    }
+
+    let case = apply_action(ctx, guild_id, &target, &msg.author, action, None, reason).await?;
+
+    msg.reply(ctx, format!("{} **{}** (case #{case}).", capitalize(action.past_tense()), target.tag())).await?;
+
+    Ok(())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut characters = word.chars();
+
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new()
+    }
+}
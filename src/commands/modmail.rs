@@ -0,0 +1,146 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::modmail::close_modmail;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's modmail settings. Members DM me to reach the staff: each conversation gets a thread in the modmail channel, and messages sent there are relayed back."]
+#[sub_commands(modmail_channel, modmail_anonymous, modmail_close)]
+async fn modmail(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, msg.guild_id.unwrap()).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!("SELECT modmail_channel_id, modmail_anonymous FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_one(&database)
+        .await?;
+
+    let open = sqlx::query!("SELECT COUNT(*) AS count FROM modmail_threads WHERE guild_id = ? AND closed_at IS NULL", guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    let channel = settings.modmail_channel_id.map_or_else(|| "Off".to_string(), |channel_id| ChannelId::new(channel_id as u64).mention().to_string());
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Modmail")
+        .field("Channel", channel, true)
+        .field("Anonymous replies", if settings.modmail_anonymous != 0 { "Yes" } else { "No" }, true)
+        .field("Open conversations", open.to_string(), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the staff channel modmail threads are opened in, or `off` to turn modmail off. Open conversations carry on until they're closed."]
+#[usage = "<channel|off>"]
+#[example = "#modmail"]
+#[num_args(1)]
+async fn modmail_channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") {
+        None
+    } else {
+        let channel = args.channel(ctx, msg).await?;
+
+        if channel.kind != ChannelType::Text {
+            msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+            return Ok(());
+        }
+
+        Some(channel.id)
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET modmail_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Modmail threads will now be opened in {}. Make sure only staff can see it.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Modmail is now off.").await?
+    };
+
+    Ok(())
+}
+
+#[command("anonymous")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets whether staff replies are relayed as coming from \"Staff\" instead of whoever wrote them."]
+#[usage = "<on|off>"]
+#[num_args(1)]
+async fn modmail_anonymous(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let anonymous = match args.single::<String>()?.to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!("UPDATE guild_settings SET modmail_anonymous = ? WHERE guild_id = ?", anonymous, guild_id)
+        .execute(&database)
+        .await?;
+
+    if anonymous {
+        msg.reply(ctx, "Staff replies will now be anonymous.").await?;
+    } else {
+        msg.reply(ctx, "Staff replies will now show who wrote them.").await?;
+    }
+
+    Ok(())
+}
+
+#[command("close")]
+#[description = "Ends a modmail conversation. Staff use this in the conversation's thread, members in their DMs with me."]
+#[num_args(0)]
+async fn modmail_close(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let thread_id = if msg.guild_id.is_some() {
+        Some(msg.channel_id)
+    } else {
+        let user_id = msg.author.id.get() as i64;
+
+        sqlx::query!("SELECT thread_id FROM modmail_threads WHERE user_id = ? AND closed_at IS NULL", user_id)
+            .fetch_optional(&database)
+            .await?
+            .map(|row| ChannelId::new(row.thread_id as u64))
+    };
+
+    let closed = match thread_id {
+        Some(thread_id) => close_modmail(ctx, &database, thread_id, msg.author.id).await?,
+        None => false
+    };
+
+    if !closed {
+        msg.reply(ctx, "There's no open modmail conversation here.").await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::moderation::{ModAction, check_target, record_action};
+use crate::utilities::nicknames::{MAX_NICKNAME_LENGTH, decancer as decancered, set_nickname};
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_NICKNAMES)]
+#[description = "Changes a member's nickname to readable ASCII, replacing fancy unicode letters and removing zalgo and characters used to hoist them to the top of the member list. Names with nothing readable left fall back to their username."]
+#[usage = "<user> [reason]"]
+#[example = "@user"]
+#[min_args(1)]
+#[sub_commands(decancer_auto)]
+async fn decancer(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Decancer, Permissions::MANAGE_NICKNAMES).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let member = guild_id.member(ctx, target_id).await?;
+    let nickname = decancered(&member.user, member.nick.as_deref());
+
+    if member.display_name() == nickname {
+        msg.reply(ctx, format!("**{}**'s name is already readable.", member.user.tag())).await?;
+        return Ok(());
+    }
+
+    set_nickname(ctx, guild_id, target_id, Some(&nickname), &format!("Decancered by {}", msg.author.tag())).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let case = record_action(ctx, &database, guild_id, ModAction::Decancer, target_id, msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!("Renamed **{}** to **{nickname}** (case #{case}).", member.user.tag())).await?;
+
+    Ok(())
+}
+
+#[command("auto")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets whether members are decancered automatically when they join or change their name."]
+#[usage = "[on|off]"]
+#[example = "on"]
+#[max_args(1)]
+async fn decancer_auto(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let enabled = match args.rest().trim().to_lowercase().as_str() {
+        "" => {
+            let enabled = sqlx::query!("SELECT auto_decancer FROM guild_settings WHERE guild_id = ?", db_guild_id)
+                .fetch_one(&database)
+                .await?
+                .auto_decancer != 0;
+
+            msg.reply(ctx, format!("Automatic decancering is {}.", if enabled { "on" } else { "off" })).await?;
+            return Ok(());
+        }
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    sqlx::query!("UPDATE guild_settings SET auto_decancer = ? WHERE guild_id = ?", enabled, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    if enabled {
+        msg.reply(ctx, "Members will now be decancered when they join or change their name.").await?;
+    } else {
+        msg.reply(ctx, "Members will no longer be decancered automatically.").await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_NICKNAMES)]
+#[description = "Gives a member a nickname they can't change: it's put back whenever they change it or rejoin. Use `off` to let them change it again, which also clears it."]
+#[usage = "<user> <nickname|off>"]
+#[example = "@user Please read the rules"]
+#[min_args(2)]
+async fn forcenick(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let nickname = Some(args.rest().trim()).filter(|nickname| !nickname.eq_ignore_ascii_case("off"));
+
+    if nickname.is_some_and(|nickname| nickname.chars().count() > MAX_NICKNAME_LENGTH) {
+        msg.reply(ctx, format!("Nicknames can be at most {MAX_NICKNAME_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Forcenick, Permissions::MANAGE_NICKNAMES).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_user_id, moderator_id) = (guild_id.get() as i64, target_id.get() as i64, msg.author.id.get() as i64);
+
+    match nickname {
+        Some(nickname) => {
+            sqlx::query!(
+                "INSERT INTO forced_nicknames (guild_id, user_id, nickname, moderator_id) VALUES (?, ?, ?, ?)
+                ON CONFLICT (guild_id, user_id) DO UPDATE SET nickname = excluded.nickname, moderator_id = excluded.moderator_id",
+                db_guild_id,
+                db_user_id,
+                nickname,
+                moderator_id
+            ).execute(&database).await?;
+        }
+        None => {
+            let removed = sqlx::query!("DELETE FROM forced_nicknames WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id)
+                .execute(&database)
+                .await?
+                .rows_affected();
+
+            if removed == 0 {
+                msg.reply(ctx, "That member has no forced nickname.").await?;
+                return Ok(());
+            }
+        }
+    }
+
+    set_nickname(ctx, guild_id, target_id, nickname, &format!("Forced by {}", msg.author.tag())).await?;
+
+    let reason = nickname.map_or_else(|| "Forced nickname removed".to_string(), |nickname| format!("Nickname forced to {nickname}"));
+    let case = record_action(ctx, &database, guild_id, ModAction::Forcenick, target_id, msg.author.id, Some(&reason)).await?;
+    let target = target_id.to_user(ctx).await?;
+
+    match nickname {
+        Some(nickname) => msg.reply(ctx, format!("**{}**'s nickname is now forced to **{nickname}** (case #{case}).", target.tag())).await?,
+        None => msg.reply(ctx, format!("**{}** can change their nickname again (case #{case}).", target.tag())).await?
+    };
+
+    Ok(())
+}
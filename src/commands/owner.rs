@@ -1,23 +1,707 @@
+use serenity::builder::CreateMessage;
 use serenity::framework::standard::macros::command;
-use serenity::framework::standard::CommandResult;
+use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
+use serenity::utils::shard_id;
+use serenity::gateway::ActivityData;
+use sqlx::{ConnectOptions, Connection, Column, Row, ValueRef, TypeInfo};
+use sqlx::sqlite::SqliteRow;
+use chrono::Utc;
 
-use crate::utilities::global_data::ShardManagerContainer;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::shutdown::shutdown as shutdown_bot;
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::blacklist::BlacklistKind;
+use crate::utilities::global_data::{ShardManagerContainer, AllowlistContainer, BlacklistContainer, DatabaseConnectionContainer, GuildSettingsContainer, ActivityOverrideContainer, OwnersContainer};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+
+/// Rows shown by `sql`, the rest are only counted.
+const MAX_SQL_ROWS: usize = 20;
+
+/// Longest value shown in a cell of `sql`'s table before it's cut off.
+const MAX_CELL_WIDTH: usize = 32;
+
+/// Leaves room for the code block around `sql`'s table within Discord's message limit.
+const MAX_TABLE_LENGTH: usize = 1900;
+
+#[command]
+#[owners_only]
+#[aliases("quit")]
+#[description = "Writes out buffered analytics and settings changes, then shuts every shard down."]
+#[num_args(0)]
+async fn shutdown(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(ctx, "Shutting down!").await?;
+
+    shutdown_bot(ctx).await;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Runs a read-only SQL query against the database and shows the results as a table."]
+#[usage = "<query>"]
+#[example = "SELECT guild_id, prefix FROM guild_settings LIMIT 5"]
+#[min_args(1)]
+async fn sql(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest()
+        .trim()
+        .trim_start_matches("```sql")
+        .trim_matches('`')
+        .trim();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    // a separate read-only connection, so nothing can be written however the query is phrased
+    let mut connection = database.connect_options().as_ref().clone().read_only(true).connect().await?;
+    let result = sqlx::query(query).fetch_all(&mut connection).await;
+
+    drop(connection.close().await);
+
+    match result {
+        Ok(rows) => msg.reply(ctx, format!("```\n{}\n```", format_table(&rows))).await?,
+        Err(why) => msg.reply(ctx, format!("```\n{why}\n```")).await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Writes back any pending settings changes, then reloads every guild's cached settings from the database."]
+#[num_args(0)]
+async fn reloadsettings(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_settings = get_data::<GuildSettingsContainer>(ctx).await?;
+
+    let count = guild_settings.reload().await?;
+
+    msg.reply(ctx, format!("Cleared the cached settings of {count} guilds, they'll be loaded from the database as they're used.")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Sets the bot's activity on every shard, replacing the guild count until it's reset."]
+#[usage = "<playing|watching|listening|competing> <text> or reset"]
+#[example = "watching over the servers"]
+#[min_args(1)]
+async fn setactivity(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = args.single::<String>()?.to_lowercase();
+    let text = args.rest().trim();
+
+    let activity = match kind.as_str() {
+        "reset" => None,
+        _ if text.is_empty() => {
+            msg.reply(ctx, "Please give the activity's text.").await?;
+            return Ok(());
+        }
+        "playing" => Some(ActivityData::playing(text)),
+        "watching" => Some(ActivityData::watching(text)),
+        "listening" => Some(ActivityData::listening(text)),
+        "competing" => Some(ActivityData::competing(text)),
+        _ => {
+            msg.reply(ctx, "The activity has to be `playing`, `watching`, `listening` or `competing`.").await?;
+            return Ok(());
+        }
+    };
+
+    let shard_manager = get_data::<ShardManagerContainer>(ctx).await?;
+    let activity_override = get_data::<ActivityOverrideContainer>(ctx).await?;
+
+    *activity_override.write().await = activity.clone();
+
+    // once reset, the usual guild count activity comes back on its own
+    if activity.is_some() {
+        for runner in shard_manager.runners.lock().await.values() {
+            runner.runner_tx.set_activity(activity.clone());
+        }
+
+        msg.reply(ctx, "Activity set.").await?;
+    } else {
+        msg.reply(ctx, "Activity reset.").await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[aliases("shard")]
+#[description = "Shows every shard's connection stage, latency and how many guilds it serves."]
+#[sub_commands(shards_restart)]
+async fn shards(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = get_data::<ShardManagerContainer>(ctx).await?;
+
+    let shard_count = ctx.cache.shard_count();
+    let mut guild_counts = vec![0; shard_count as usize];
+
+    for guild_id in ctx.cache.guilds() {
+        if let Some(count) = guild_counts.get_mut(shard_id(guild_id, shard_count) as usize) {
+            *count += 1;
+        }
+    }
+
+    let lines = {
+        let runners = shard_manager.runners.lock().await;
+
+        let mut runners: Vec<_> = runners.iter().collect();
+        runners.sort_by_key(|(id, _)| **id);
+
+        runners.into_iter()
+            .map(|(id, runner)| {
+                let latency = runner.latency.map_or_else(|| "n/a".to_string(), |latency| format!("{}ms", latency.as_millis()));
+                let guilds = guild_counts.get(id.0 as usize).copied().unwrap_or_default();
+                let current = if *id == ctx.shard_id { " (this shard)" } else { "" };
+
+                format!("**Shard {id}**{current}: {}, {latency}, {guilds} guilds", runner.stage)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Shards ({} of {shard_count} running)", lines.len()))
+        .description(lines.join("\n"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("restart")]
+#[owners_only]
+#[description = "Restarts a shard, reconnecting it to the gateway."]
+#[usage = "<shard ID>"]
+#[num_args(1)]
+async fn shards_restart(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = ShardId(args.single::<u32>()?);
+
+    let shard_manager = get_data::<ShardManagerContainer>(ctx).await?;
+
+    // the lock has to be released before restarting, which takes it again
+    let exists = shard_manager.runners.lock().await.contains_key(&id);
+
+    if !exists {
+        msg.reply(ctx, format!("There is no running shard {id}.")).await?;
+        return Ok(());
+    }
+
+    // the reply has to go out first in case this is the shard being restarted
+    msg.reply(ctx, format!("Restarting shard {id}.")).await?;
+    shard_manager.restart(id).await;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Shows this shard's gateway latency."]
+#[sub_commands(latency_all)]
+async fn latency(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = get_data::<ShardManagerContainer>(ctx).await?;
+
+    let latency = shard_manager.runners.lock().await
+        .get(&ctx.shard_id)
+        .and_then(|runner| runner.latency);
+
+    match latency {
+        Some(latency) => msg.reply(ctx, format!("Shard {} has a latency of {}ms.", ctx.shard_id, latency.as_millis())).await?,
+        None => msg.reply(ctx, format!("Shard {} hasn't measured its latency yet.", ctx.shard_id)).await?
+    };
+
+    Ok(())
+}
+
+#[command("all")]
+#[owners_only]
+#[description = "Shows every shard's gateway latency, along with the average."]
+async fn latency_all(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = get_data::<ShardManagerContainer>(ctx).await?;
+
+    let mut latencies: Vec<(ShardId, Option<u128>)> = shard_manager.runners.lock().await
+        .iter()
+        .map(|(id, runner)| (*id, runner.latency.map(|latency| latency.as_millis())))
+        .collect();
+
+    latencies.sort_by_key(|(id, _)| *id);
+
+    let measured: Vec<u128> = latencies.iter().filter_map(|(_, latency)| *latency).collect();
+
+    let average = if measured.is_empty() {
+        "n/a".to_string()
+    } else {
+        format!("{}ms", measured.iter().sum::<u128>() / measured.len() as u128)
+    };
+
+    let lines = latencies.iter()
+        .map(|(id, latency)| format!("**Shard {id}**: {}", latency.map_or_else(|| "n/a".to_string(), |latency| format!("{latency}ms"))))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Shard Latencies")
+        .description(format!("{lines}\n\n**Average**: {average}"));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[description = "Shows whether allowlist mode is enabled. While enabled, the bot leaves any guild that isn't on the allowlist."]
+#[sub_commands(allowlist_add, allowlist_remove, allowlist_list, allowlist_enable, allowlist_disable)]
+async fn allowlist(ctx: &Context, msg: &Message) -> CommandResult {
+    let (enabled, count) = {
+        let allowlist = get_data::<AllowlistContainer>(ctx).await?;
+        let allowlist = allowlist.read().await;
+
+        (allowlist.enabled, allowlist.guilds.len())
+    };
+
+    let state = if enabled { "enabled" } else { "disabled" };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Allowlist")
+        .description(format!("Allowlist mode is **{state}** with {count} guild(s) on the allowlist."));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("add")]
+#[owners_only]
+#[description = "Adds a guild to the allowlist. Defaults to the current guild."]
+#[usage = "[guild id]"]
+#[max_args(1)]
+async fn allowlist_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match target_guild(msg, &mut args) {
+        Some(guild_id) => guild_id,
+        None => {
+            msg.reply(ctx, "Please provide a valid guild ID.").await?;
+            return Ok(());
+        }
+    };
+
+    {
+        let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+        let db_guild_id = guild_id as i64;
+        let added_by = msg.author.id.get() as i64;
+        let added_at = Utc::now().to_rfc3339();
+
+        sqlx::query!(
+            "INSERT INTO guild_allowlist (guild_id, added_by, added_at) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+            db_guild_id,
+            added_by,
+            added_at
+        ).execute(&database).await?;
+
+        get_data::<AllowlistContainer>(ctx).await?.write().await.guilds.insert(guild_id);
+    }
+
+    msg.reply(ctx, format!("Added guild `{guild_id}` to the allowlist.")).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[owners_only]
+#[description = "Removes a guild from the allowlist. Defaults to the current guild."]
+#[usage = "[guild id]"]
+#[max_args(1)]
+async fn allowlist_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match target_guild(msg, &mut args) {
+        Some(guild_id) => guild_id,
+        None => {
+            msg.reply(ctx, "Please provide a valid guild ID.").await?;
+            return Ok(());
+        }
+    };
+
+    let removed = {
+        let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+        let db_guild_id = guild_id as i64;
+
+        sqlx::query!(
+            "DELETE FROM guild_allowlist WHERE guild_id = ?",
+            db_guild_id
+        ).execute(&database).await?;
+
+        let allowlist = get_data::<AllowlistContainer>(ctx).await?;
+        let mut allowlist = allowlist.write().await;
+        allowlist.guilds.remove(&guild_id)
+    };
+
+    if removed {
+        msg.reply(ctx, format!("Removed guild `{guild_id}` from the allowlist.")).await?;
+    } else {
+        msg.reply(ctx, format!("Guild `{guild_id}` is not on the allowlist.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("list")]
+#[owners_only]
+#[description = "Lists every guild on the allowlist."]
+async fn allowlist_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut guilds: Vec<u64> = {
+        let allowlist = get_data::<AllowlistContainer>(ctx).await?;
+        let allowlist = allowlist.read().await;
+
+        allowlist.guilds.iter().copied().collect()
+    };
+    guilds.sort_unstable();
+
+    let description = if guilds.is_empty() {
+        "The allowlist is empty.".to_string()
+    } else {
+        guilds.iter()
+            .map(|id| match GuildId::new(*id).name(&ctx.cache) {
+                Some(name) => format!("`{id}` - {name}"),
+                None => format!("`{id}` - *not connected*")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Allowlisted Guilds")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("enable")]
+#[owners_only]
+#[description = "Enables allowlist mode. Guilds not on the allowlist are left the next time they connect."]
+async fn allowlist_enable(ctx: &Context, msg: &Message) -> CommandResult {
+    set_allowlist_enabled(ctx, true).await?;
+
+    let unlisted = {
+        let allowlist = get_data::<AllowlistContainer>(ctx).await?;
+        let allowlist = allowlist.read().await;
+
+        ctx.cache.guilds().into_iter().filter(|id| !allowlist.guilds.contains(&id.get())).count()
+    };
+
+    msg.reply(ctx, format!(
+        "Allowlist mode enabled. {unlisted} connected guild(s) are not on the allowlist and will be left when they next connect."
+    )).await?;
+
+    Ok(())
+}
+
+#[command("disable")]
+#[owners_only]
+#[description = "Disables allowlist mode."]
+async fn allowlist_disable(ctx: &Context, msg: &Message) -> CommandResult {
+    set_allowlist_enabled(ctx, false).await?;
+
+    msg.reply(ctx, "Allowlist mode disabled.").await?;
+
+    Ok(())
+}
+
+async fn set_allowlist_enabled(ctx: &Context, enabled: bool) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let value = enabled as i64;
+
+    sqlx::query!(
+        "UPDATE bot_settings SET allowlist_enabled = ? WHERE id = 0",
+        value
+    ).execute(&database).await?;
+
+    get_data::<AllowlistContainer>(ctx).await?.write().await.enabled = enabled;
+
+    Ok(())
+}
 
 #[command]
 #[owners_only]
-async fn quit(ctx: &Context, msg: &Message) -> CommandResult {
-    let data = ctx.data.read().await;
+#[description = "Lists the blacklisted users and guilds. The bot ignores blacklisted users everywhere and leaves blacklisted guilds as soon as it's in them."]
+#[sub_commands(blacklist_user, blacklist_guild)]
+#[num_args(0)]
+async fn blacklist(ctx: &Context, msg: &Message) -> CommandResult {
+    list_blacklist(ctx, msg, None).await
+}
+
+#[command("user")]
+#[owners_only]
+#[description = "Lists the blacklisted users. Use `add` or `remove` to change them."]
+#[sub_commands(blacklist_user_add, blacklist_user_remove)]
+#[num_args(0)]
+async fn blacklist_user(ctx: &Context, msg: &Message) -> CommandResult {
+    list_blacklist(ctx, msg, Some(BlacklistKind::User)).await
+}
+
+#[command("add")]
+#[owners_only]
+#[description = "Blacklists a user, so the bot ignores their commands, messages and interactions everywhere."]
+#[usage = "<user> [reason]"]
+#[example = "123456789012345678 Spamming commands"]
+#[min_args(1)]
+async fn blacklist_user_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = args.user_id()?;
+
+    let is_owner = {
+        let data = ctx.data.read().await;
+        data.get::<OwnersContainer>().is_some_and(|owners| owners.contains(&user_id))
+    };
+
+    if is_owner || user_id == ctx.cache.current_user().id {
+        msg.reply(ctx, "The bot and its owners can't be blacklisted.").await?;
+        return Ok(());
+    }
 
-    if let Some(manager) = data.get::<ShardManagerContainer>() {
-        msg.reply(ctx, "Shutting down!").await?;
-        manager.shutdown_all().await;
+    let added = add_to_blacklist(ctx, msg, BlacklistKind::User, user_id.get(), args.rest()).await?;
+
+    if added {
+        msg.reply(ctx, format!("Blacklisted user `{user_id}`.")).await?;
     } else {
-        msg.reply(ctx, "There was a problem getting the shard manager").await?;
+        msg.reply(ctx, format!("User `{user_id}` is already blacklisted.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("remove")]
+#[owners_only]
+#[description = "Takes a user off the blacklist."]
+#[usage = "<user>"]
+#[num_args(1)]
+async fn blacklist_user_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = args.user_id()?;
+
+    if remove_from_blacklist(ctx, BlacklistKind::User, user_id.get()).await? {
+        msg.reply(ctx, format!("Removed user `{user_id}` from the blacklist.")).await?;
+    } else {
+        msg.reply(ctx, format!("User `{user_id}` isn't blacklisted.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("guild")]
+#[owners_only]
+#[description = "Lists the blacklisted guilds. Use `add` or `remove` to change them."]
+#[sub_commands(blacklist_guild_add, blacklist_guild_remove)]
+#[num_args(0)]
+async fn blacklist_guild(ctx: &Context, msg: &Message) -> CommandResult {
+    list_blacklist(ctx, msg, Some(BlacklistKind::Guild)).await
+}
+
+#[command("add")]
+#[owners_only]
+#[description = "Blacklists a guild. The bot leaves it right away if it's in it, and again whenever it's added back."]
+#[usage = "<guild id> [reason]"]
+#[example = "123456789012345678 Used for raids"]
+#[min_args(1)]
+async fn blacklist_guild_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(guild_id) = args.single::<u64>().ok().filter(|id| *id != 0).map(GuildId::new) else {
+        msg.reply(ctx, "Please provide a valid guild ID.").await?;
+        return Ok(());
+    };
+
+    if !add_to_blacklist(ctx, msg, BlacklistKind::Guild, guild_id.get(), args.rest()).await? {
+        msg.reply(ctx, format!("Guild `{guild_id}` is already blacklisted.")).await?;
+        return Ok(());
+    }
+
+    // the reply goes out first in case the command was used in the guild being left
+    msg.reply(ctx, format!("Blacklisted guild `{guild_id}`.")).await?;
 
+    if ctx.cache.guild(guild_id).is_some() {
+        guild_id.leave(ctx).await?;
+    }
+
+    Ok(())
+}
+
+#[command("remove")]
+#[owners_only]
+#[description = "Takes a guild off the blacklist, so the bot can be added to it again."]
+#[usage = "<guild id>"]
+#[num_args(1)]
+async fn blacklist_guild_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Ok(guild_id) = args.single::<u64>() else {
+        msg.reply(ctx, "Please provide a valid guild ID.").await?;
         return Ok(());
+    };
+
+    if remove_from_blacklist(ctx, BlacklistKind::Guild, guild_id).await? {
+        msg.reply(ctx, format!("Removed guild `{guild_id}` from the blacklist.")).await?;
+    } else {
+        msg.reply(ctx, format!("Guild `{guild_id}` isn't blacklisted.")).await?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Blacklists a user or guild. Returns false if it already was.
+async fn add_to_blacklist(ctx: &Context, msg: &Message, kind: BlacklistKind, id: u64, reason: &str) -> Result<bool, BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let reason = Some(reason.trim()).filter(|reason| !reason.is_empty());
+    let (kind_name, db_id, added_by, added_at) = (kind.name(), id as i64, msg.author.id.get() as i64, Utc::now().to_rfc3339());
+
+    let added = sqlx::query!(
+        "INSERT INTO blacklist (kind, id, reason, added_by, added_at) VALUES (?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+        kind_name,
+        db_id,
+        reason,
+        added_by,
+        added_at
+    ).execute(&database).await?.rows_affected() > 0;
+
+    get_data::<BlacklistContainer>(ctx).await?.write().await.entries_mut(kind).insert(id);
+
+    Ok(added)
+}
+
+/// Takes a user or guild off the blacklist. Returns false if it wasn't on it.
+async fn remove_from_blacklist(ctx: &Context, kind: BlacklistKind, id: u64) -> Result<bool, BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (kind_name, db_id) = (kind.name(), id as i64);
+
+    sqlx::query!("DELETE FROM blacklist WHERE kind = ? AND id = ?", kind_name, db_id)
+        .execute(&database)
+        .await?;
+
+    let blacklist = get_data::<BlacklistContainer>(ctx).await?;
+    let removed = blacklist.write().await.entries_mut(kind).remove(&id);
+
+    Ok(removed)
+}
+
+/// Lists the blacklisted users and guilds, or just one kind of them.
+async fn list_blacklist(ctx: &Context, msg: &Message, kind: Option<BlacklistKind>) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let kind_name = kind.map(BlacklistKind::name);
+
+    let rows = sqlx::query!(
+        "SELECT kind, id, reason, added_by FROM blacklist WHERE ? IS NULL OR kind = ? ORDER BY kind DESC, added_at",
+        kind_name,
+        kind_name
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = rows.iter()
+        .map(|row| {
+            let name = match row.kind.as_str() {
+                "guild" => GuildId::new(row.id as u64).name(&ctx.cache).unwrap_or_else(|| "*not connected*".to_string()),
+                _ => format!("<@{}>", row.id)
+            };
+
+            format!("{} `{}` - {name} - {} (by <@{}>)", row.kind, row.id, row.reason.as_deref().unwrap_or("No reason given."), row.added_by)
+        })
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nothing is blacklisted.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Blacklist ({})", rows.len()));
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+/// Takes the guild ID from the arguments, falling back to the guild the message was sent in.
+fn target_guild(msg: &Message, args: &mut Args) -> Option<u64> {
+    if args.is_empty() {
+        msg.guild_id.map(|id| id.get())
+    } else {
+        args.single::<u64>().ok()
+    }
+}
+
+/// Lays query results out as a plain text table, cutting long values and extra rows.
+fn format_table(rows: &[SqliteRow]) -> String {
+    let Some(first) = rows.first() else {
+        return "No rows.".to_string();
+    };
+
+    let headers: Vec<String> = first.columns().iter().map(|column| column.name().to_string()).collect();
+
+    let cells: Vec<Vec<String>> = rows.iter()
+        .take(MAX_SQL_ROWS)
+        .map(|row| (0..headers.len()).map(|index| truncate(&cell(row, index))).collect())
+        .collect();
+
+    let widths: Vec<usize> = headers.iter()
+        .enumerate()
+        .map(|(index, header)| cells.iter().map(|row| row[index].chars().count()).chain([header.chars().count()]).max().unwrap_or_default())
+        .collect();
+
+    let line = |values: &[String]| values.iter()
+        .zip(&widths)
+        .map(|(value, width)| format!("{value:<width$}"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let mut table = vec![line(&headers), widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("-+-")];
+    table.extend(cells.iter().map(|row| line(row)));
+
+    let mut output = String::new();
+    let mut shown: usize = 0;
+
+    for row in &table {
+        if output.len() + row.len() + 1 > MAX_TABLE_LENGTH {
+            break;
+        }
+
+        output.push_str(row);
+        output.push('\n');
+        shown += 1;
+    }
+
+    // the header and separator aren't rows
+    let hidden = rows.len() - shown.saturating_sub(2);
+
+    if hidden > 0 {
+        output.push_str(&format!("... and {hidden} more rows"));
+    }
+
+    output.trim_end().to_string()
+}
+
+fn cell(row: &SqliteRow, index: usize) -> String {
+    let Ok(value) = row.try_get_raw(index) else {
+        return "?".to_string();
+    };
+
+    if value.is_null() {
+        return "NULL".to_string();
+    }
+
+    let value = match value.type_info().name() {
+        "INTEGER" => row.try_get::<i64, _>(index).map(|value| value.to_string()),
+        "REAL" => row.try_get::<f64, _>(index).map(|value| value.to_string()),
+        "BLOB" => row.try_get::<Vec<u8>, _>(index).map(|value| format!("<{} bytes>", value.len())),
+        _ => row.try_get::<String, _>(index)
+    };
+
+    value.unwrap_or_else(|_| "?".to_string())
+}
+
+fn truncate(value: &str) -> String {
+    let value = value.replace('\n', " ");
+
+    if value.chars().count() > MAX_CELL_WIDTH {
+        format!("{}…", value.chars().take(MAX_CELL_WIDTH - 1).collect::<String>())
+    } else {
+        value
+    }
+}
@@ -0,0 +1,162 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandError, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::command_permissions::reload_command_permissions;
+use crate::utilities::command_rules::rule_name;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+
+#[command]
+#[only_in(guilds)]
+#[aliases("permissions")]
+#[description = "Lists the roles needed to use commands in this server. Once roles are mapped to a command, a group such as `moderation`, or `all`, only members with one of them (and administrators) can use it; the most specific mapping wins."]
+#[sub_commands(perms_allow, perms_remove, perms_clear)]
+async fn perms(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let rows = sqlx::query!("SELECT name, role_id FROM command_permissions WHERE guild_id = ? ORDER BY name", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if rows.is_empty() {
+        msg.reply(ctx, "No roles are required for any commands in this server.").await?;
+        return Ok(());
+    }
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title("Command permissions");
+
+    let mut names = rows.iter().map(|row| row.name.as_str()).collect::<Vec<_>>();
+    names.dedup();
+
+    for name in names {
+        let roles = rows.iter()
+            .filter(|row| row.name == name)
+            .map(|row| RoleId::new(row.role_id as u64).mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        embed = embed.field(format!("`{name}`"), roles, false);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("allow")]
+#[only_in(guilds)]
+#[required_permissions(ADMINISTRATOR)]
+#[description = "Lets members with a role use a command, group or `all`. Members without any of its roles no longer can."]
+#[usage = "<role> <command|group|all>"]
+#[example = "@Moderators moderation"]
+#[num_args(2)]
+async fn perms_allow(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some((role_id, name)) = parse_perms_args(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO command_permissions (guild_id, name, role_id) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+        db_guild_id,
+        name,
+        db_role_id
+    ).execute(&database).await?;
+
+    reload_command_permissions(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("Members with {} can now use `{name}`.", role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("deny")]
+#[only_in(guilds)]
+#[required_permissions(ADMINISTRATOR)]
+#[description = "Stops a role from granting a command, group or `all`. Once no roles are left, anyone can use it again."]
+#[usage = "<role> <command|group|all>"]
+#[num_args(2)]
+async fn perms_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some((role_id, name)) = parse_perms_args(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    let deleted = sqlx::query!("DELETE FROM command_permissions WHERE guild_id = ? AND name = ? AND role_id = ?", db_guild_id, name, db_role_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("{} isn't allowed to use `{name}` specifically.", role_id.mention())).await?;
+        return Ok(());
+    }
+
+    reload_command_permissions(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("{} no longer grants `{name}`.", role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("clear")]
+#[only_in(guilds)]
+#[required_permissions(ADMINISTRATOR)]
+#[description = "Removes every role from a command, group or `all`, so anyone can use it again."]
+#[usage = "<command|group|all>"]
+#[num_args(1)]
+async fn perms_clear(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let given = args.single::<String>()?;
+
+    let Some(name) = rule_name(&given) else {
+        msg.reply(ctx, format!("There's no command or group called `{given}`.")).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!("DELETE FROM command_permissions WHERE guild_id = ? AND name = ?", db_guild_id, name)
+        .execute(&database)
+        .await?;
+
+    reload_command_permissions(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("`{name}` no longer needs a role.")).await?;
+
+    Ok(())
+}
+
+/// Reads the role and rule name `allow` and `remove` take, replying if either is invalid.
+async fn parse_perms_args(ctx: &Context, msg: &Message, args: &mut Args) -> Result<Option<(RoleId, String)>, CommandError> {
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let given = args.single::<String>().unwrap_or_default();
+
+    match rule_name(&given) {
+        Some(name) => Ok(Some((role_id, name))),
+        None => {
+            msg.reply(ctx, format!("There's no command or group called `{given}`.")).await?;
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::pin_archive::{MAX_PINS, archive_pins, pin_archive_channel};
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the pin archive channel. Once a channel reaches Discord's limit of 50 pins, its oldest pins are copied there and unpinned to make room. Use `off` to leave pins alone."]
+#[usage = "[channel|off]"]
+#[example = "#pin-archive"]
+#[max_args(1)]
+async fn pinarchive(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        match pin_archive_channel(&database, guild_id).await? {
+            Some(channel_id) => msg.reply(ctx, format!("Pins are archived to {}.", channel_id.mention())).await?,
+            None => msg.reply(ctx, "This server has no pin archive.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET pin_archive_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Pins will now be archived to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Pins will no longer be archived.").await?
+    };
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Copies the oldest pins of this channel to the pin archive and unpins them. Reply to a pinned message to archive just that one."]
+#[usage = "[count]"]
+#[example = "5"]
+#[max_args(1)]
+async fn archivepin(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(archive_id) = pin_archive_channel(&database, guild_id).await? else {
+        msg.reply(ctx, "This server has no pin archive. Set one with `pinarchive <channel>`.").await?;
+        return Ok(());
+    };
+
+    if archive_id == msg.channel_id {
+        msg.reply(ctx, "Pins in the pin archive itself can't be archived.").await?;
+        return Ok(());
+    }
+
+    let pins = match msg.referenced_message.as_deref() {
+        Some(replied) if !replied.pinned => {
+            msg.reply(ctx, "That message isn't pinned.").await?;
+            return Ok(());
+        }
+        Some(replied) => vec![replied.clone()],
+        None => {
+            let count = match args.rest().trim() {
+                "" => 1,
+                count => match count.parse::<usize>().ok().filter(|count| (1..=MAX_PINS).contains(count)) {
+                    Some(count) => count,
+                    None => {
+                        msg.reply(ctx, format!("Please give a number of pins from 1 to {MAX_PINS}.")).await?;
+                        return Ok(());
+                    }
+                }
+            };
+
+            // pins come newest first
+            let mut pins = msg.channel_id.pins(ctx).await?;
+            pins.reverse();
+            pins.truncate(count);
+            pins
+        }
+    };
+
+    if pins.is_empty() {
+        msg.reply(ctx, "This channel has no pins.").await?;
+        return Ok(());
+    }
+
+    archive_pins(ctx, guild_id, archive_id, &pins).await?;
+
+    msg.reply(ctx, format!(
+        "Archived {} pin{} to {}.",
+        pins.len(),
+        if pins.len() == 1 { "" } else { "s" },
+        archive_id.mention()
+    )).await?;
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use chrono::Utc;
+use serenity::builder::{CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::{format_duration, parse_duration};
+use crate::utilities::polls::{close_poll, poll_components};
+use crate::utilities::premium::guild_tier;
+use crate::utilities::scheduler::{Job, When, format_run_time, schedule_job};
+
+const MIN_OPTIONS: usize = 2;
+const MAX_OPTIONS: usize = 10;
+const MAX_QUESTION_LENGTH: usize = 256;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Starts a poll members vote on with buttons. Add `multi` to allow picking several options, and a duration such as `1d` to close the poll and post its results automatically."]
+#[usage = "[multi] [duration] <question> | <option> | <option> ..."]
+#[example = "multi 1d Which games should we play? | Minecraft | Terraria | Factorio"]
+#[sub_commands(poll_close)]
+#[min_args(1)]
+async fn poll(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let multiple_choice = args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("multi"));
+
+    if multiple_choice {
+        args.advance();
+    }
+
+    let duration = args.current().and_then(parse_duration);
+
+    if duration.is_some() {
+        args.advance();
+    }
+
+    let max_duration = guild_tier(ctx, msg.guild_id).await.max_timed_duration();
+
+    if duration.is_some_and(|duration| duration > max_duration) {
+        msg.reply(ctx, format!("Polls in this server can stay open for at most {}.", format_duration(max_duration))).await?;
+        return Ok(());
+    }
+
+    let mut parts = args.rest().split('|').map(str::trim);
+    let question = parts.next().unwrap_or_default().to_string();
+    let options: Vec<&str> = parts.filter(|option| !option.is_empty()).collect();
+
+    if question.is_empty() || !(MIN_OPTIONS..=MAX_OPTIONS).contains(&options.len()) {
+        msg.reply(ctx, format!("Please give a question and {MIN_OPTIONS} to {MAX_OPTIONS} options, separated by `|`.")).await?;
+        return Ok(());
+    }
+
+    if question.chars().count() > MAX_QUESTION_LENGTH {
+        msg.reply(ctx, format!("Questions can be at most {MAX_QUESTION_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let closes_at = duration.and_then(|duration| Utc::now().checked_add_signed(duration));
+
+    let description = options.iter()
+        .enumerate()
+        .map(|(index, option)| format!("**{}.** {option}", index + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let how = if multiple_choice { "Pick as many options as you like." } else { "Pick one option." };
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(&question)
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!("{how} Click an option again to take your vote back.")));
+
+    if let Some(closes_at) = closes_at {
+        embed = embed.field("Closes", format!("<t:{}:R>", closes_at.timestamp()), true);
+    }
+
+    let poll = msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(poll_components(&options, false))).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let (message_id, db_guild_id, channel_id, created_by) = (poll.id.get() as i64, guild_id.get() as i64, msg.channel_id.get() as i64, msg.author.id.get() as i64);
+    let stored_options = options.join("\n");
+    let stored_closes_at = closes_at.map(format_run_time);
+
+    sqlx::query!(
+        "INSERT INTO polls (message_id, guild_id, channel_id, question, options, multiple_choice, closes_at, created_by) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        message_id,
+        db_guild_id,
+        channel_id,
+        question,
+        stored_options,
+        multiple_choice,
+        stored_closes_at,
+        created_by
+    ).execute(&database).await?;
+
+    if let Some(closes_at) = closes_at {
+        let job = Job::ClosePoll { message_id: poll.id.get() };
+        schedule_job(&database, Some(guild_id), &job, &When { first_run: closes_at, repeat: None }, msg.author.id.get()).await?;
+    }
+
+    Ok(())
+}
+
+#[command("close")]
+#[only_in(guilds)]
+#[description = "Closes a poll early and posts its results. Polls can be closed by whoever started them, or by anyone who can manage messages."]
+#[usage = "<message ID>"]
+#[num_args(1)]
+async fn poll_close(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let message_id = args.single::<u64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_message_id, guild_id) = (message_id as i64, msg.guild_id.unwrap().get() as i64);
+
+    let Some(poll) = sqlx::query!(
+        "SELECT channel_id, created_by FROM polls WHERE message_id = ? AND guild_id = ?",
+        db_message_id,
+        guild_id
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, format!("There is no poll with the message ID `{message_id}`.")).await?;
+        return Ok(());
+    };
+
+    let channel = ChannelId::new(poll.channel_id as u64).to_channel(ctx).await.ok().and_then(Channel::guild);
+
+    let can_manage = channel.zip(msg.guild(&ctx.cache)).is_some_and(|(channel, guild)| {
+        guild.members.get(&msg.author.id)
+            .is_some_and(|member| guild.user_permissions_in(&channel, member).manage_messages())
+    });
+
+    if poll.created_by != msg.author.id.get() as i64 && !can_manage {
+        msg.reply(ctx, "Only whoever started this poll, or someone who can manage messages, can close it.").await?;
+        return Ok(());
+    }
+
+    if !close_poll(ctx, &database, MessageId::new(message_id)).await? {
+        msg.reply(ctx, "That poll is already closed.").await?;
+    }
+
+    Ok(())
+}
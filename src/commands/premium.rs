@@ -0,0 +1,117 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::{Duration, Utc};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{GuildPremium, PremiumContainer};
+use crate::utilities::parsing::{format_duration, parse_guild};
+use crate::utilities::premium::{PremiumTier, guild_tier, set_guild_premium, clear_guild_premium};
+
+/// Longest a manual premium grant can be given for, in days.
+const MAX_PREMIUM_DAYS: i64 = 3650;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's premium tier and the limits that come with it."]
+#[sub_commands(premium_set)]
+async fn premium(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let tier = guild_tier(ctx, Some(guild_id)).await;
+
+    let expiry = {
+        let premium = get_data::<PremiumContainer>(ctx).await?;
+        let premium = premium.read().await;
+
+        premium.get(&guild_id.get())
+            .filter(|grant| grant.effective_tier() != PremiumTier::Free)
+            .and_then(|grant| grant.expires_at)
+    };
+
+    let mut description = format!("This server is on the **{}** tier.", tier.name());
+
+    if let Some(expiry) = expiry {
+        description.push_str(&format!("\nRenews or expires <t:{}:R>.", expiry.timestamp()));
+    }
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Premium")
+        .description(description)
+        .field("Role panels", tier.max_role_panels().to_string(), true)
+        .field("Longest timed poll", format_duration(tier.max_timed_duration()), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[owners_only]
+#[description = "Sets a guild's premium tier (free, premium or plus), optionally for a number of days."]
+#[usage = "<guild id> <tier> [days]"]
+#[min_args(2)]
+#[max_args(3)]
+async fn premium_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_arg = args.single::<String>()?;
+
+    let Some(guild_id) = parse_guild(&guild_arg) else {
+        msg.reply(ctx, format!("`{guild_arg}` is not a guild ID.")).await?;
+        return Ok(());
+    };
+
+    let tier = match args.single::<String>()?.to_lowercase().as_str() {
+        "free" | "0" => PremiumTier::Free,
+        "premium" | "1" => PremiumTier::Premium,
+        "plus" | "premium+" | "2" => PremiumTier::PremiumPlus,
+        other => {
+            msg.reply(ctx, format!("`{other}` is not a tier. Use `free`, `premium` or `plus`.")).await?;
+            return Ok(());
+        }
+    };
+
+    // no days at all means the grant doesn't expire, anything else has to be a sensible number of days
+    let expires_at = match args.single::<String>() {
+        Ok(days) => {
+            let expiry = days.parse::<i64>().ok()
+                .filter(|days| (1..=MAX_PREMIUM_DAYS).contains(days))
+                .and_then(Duration::try_days)
+                .and_then(|duration| Utc::now().checked_add_signed(duration));
+
+            let Some(expiry) = expiry else {
+                msg.reply(ctx, format!("`{days}` is not a number of days from 1 to {MAX_PREMIUM_DAYS}.")).await?;
+                return Ok(());
+            };
+
+            Some(expiry)
+        }
+        Err(_) => None
+    };
+
+    if tier == PremiumTier::Free {
+        clear_guild_premium(ctx, guild_id).await?;
+        msg.reply(ctx, format!("Guild `{guild_id}` is now on the free tier.")).await?;
+
+        return Ok(());
+    }
+
+    let premium = GuildPremium {
+        tier,
+        source: "manual".to_string(),
+        entitlement_id: None,
+        expires_at
+    };
+
+    set_guild_premium(ctx, guild_id, premium).await?;
+
+    let until = match expires_at {
+        Some(expiry) => format!(" until <t:{}:f>", expiry.timestamp()),
+        None => String::new()
+    };
+
+    msg.reply(ctx, format!("Guild `{guild_id}` is now on the **{}** tier{until}.", tier.name())).await?;
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use regex::{Regex, RegexBuilder};
+use serenity::builder::GetMessages;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::parsing::parse_user;
+
+/// Most messages a single purge deletes.
+const MAX_PURGE: u64 = 1000;
+
+/// Most messages looked through to find ones matching the filters.
+const MAX_SCANNED: usize = 5000;
+
+/// Discord only bulk deletes messages younger than two weeks. The margin keeps messages that
+/// cross the limit mid-purge from failing the whole batch.
+const BULK_DELETE_AGE: Duration = Duration::days(14);
+const BULK_DELETE_MARGIN: Duration = Duration::minutes(5);
+
+/// Upper bound on the compiled size of a `regex:` filter.
+const MAX_PATTERN_SIZE: usize = 1 << 16;
+
+/// Which messages a purge deletes. Every given filter has to match.
+#[derive(Default)]
+struct PurgeFilter {
+    users: Vec<UserId>,
+    bots: bool,
+    links: bool,
+    attachments: bool,
+    pattern: Option<Regex>,
+    before: Option<MessageId>,
+    after: Option<MessageId>
+}
+
+impl PurgeFilter {
+    fn parse(args: &mut Args) -> Result<PurgeFilter, String> {
+        let mut filter = PurgeFilter::default();
+
+        for arg in args.iter::<String>().quoted() {
+            let arg = arg.map_err(|_| "I couldn't read the filters.".to_string())?;
+
+            match arg.split_once(':') {
+                Some(("regex", pattern)) => {
+                    let pattern = RegexBuilder::new(pattern)
+                        .size_limit(MAX_PATTERN_SIZE)
+                        .build()
+                        .map_err(|why| format!("That regex isn't valid:\n```{why}```"))?;
+
+                    filter.pattern = Some(pattern);
+                }
+                Some(("before", id)) => filter.before = Some(parse_message_id(id)?),
+                Some(("after", id)) => filter.after = Some(parse_message_id(id)?),
+                _ => match arg.to_lowercase().as_str() {
+                    "bots" => filter.bots = true,
+                    "links" => filter.links = true,
+                    "attachments" | "files" => filter.attachments = true,
+                    _ => match parse_user(&arg) {
+                        Some(user_id) => filter.users.push(user_id),
+                        None => return Err(format!("`{arg}` isn't a filter. See `help purge` for the filters."))
+                    }
+                }
+            }
+        }
+
+        Ok(filter)
+    }
+
+    fn matches(&self, message: &Message) -> bool {
+        let content = message.content.to_lowercase();
+
+        (self.users.is_empty() || self.users.contains(&message.author.id))
+            && (!self.bots || message.author.bot)
+            && (!self.links || content.contains("http://") || content.contains("https://") || content.contains("discord.gg/"))
+            && (!self.attachments || !message.attachments.is_empty())
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(&message.content))
+            && self.after.is_none_or(|after| message.id > after)
+    }
+}
+
+fn parse_message_id(id: &str) -> Result<MessageId, String> {
+    id.parse::<u64>().ok()
+        .filter(|id| *id != 0)
+        .map(MessageId::new)
+        .ok_or_else(|| format!("`{id}` isn't a message ID."))
+}
+
+#[command]
+#[aliases("clear")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Deletes up to 1000 recent messages in this channel. Filters narrow down which: users, `bots`, `links`, `attachments`, `regex:<pattern>`, `before:<message ID>` and `after:<message ID>`. Quote regexes with spaces."]
+#[usage = "<amount> [filters...]"]
+#[example = "50 @user links"]
+#[min_args(1)]
+async fn purge(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(amount) = args.single::<u64>().ok().filter(|amount| (1..=MAX_PURGE).contains(amount)) else {
+        msg.reply(ctx, format!("Please give the number of messages to delete, up to {MAX_PURGE}.")).await?;
+        return Ok(());
+    };
+
+    let filter = match PurgeFilter::parse(&mut args) {
+        Ok(filter) => filter,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    let channel = msg.channel(ctx).await.ok().and_then(Channel::guild);
+
+    let bot_can_manage = channel.zip(msg.guild(&ctx.cache)).is_some_and(|(channel, guild)| {
+        guild.members.get(&ctx.cache.current_user().id)
+            .is_some_and(|member| guild.user_permissions_in(&channel, member).manage_messages())
+    });
+
+    if !bot_can_manage {
+        msg.reply(ctx, "I need the Manage Messages permission in this channel to do that.").await?;
+        return Ok(());
+    }
+
+    // look back from the given message, or from the command itself so it isn't counted
+    let mut cursor = filter.before.unwrap_or(msg.id);
+    let mut scanned = 0;
+    let mut matched = Vec::new();
+
+    while (matched.len() as u64) < amount && scanned < MAX_SCANNED {
+        let page = msg.channel_id.messages(ctx, GetMessages::new().before(cursor).limit(100)).await?;
+
+        let Some(oldest) = page.last() else {
+            break;
+        };
+
+        cursor = oldest.id;
+        scanned += page.len();
+
+        let reached_after = filter.after.is_some_and(|after| oldest.id <= after);
+
+        matched.extend(page.iter().filter(|message| filter.matches(message)).map(|message| (message.id, *message.timestamp)));
+
+        if reached_after {
+            break;
+        }
+    }
+
+    matched.truncate(amount as usize);
+
+    if matched.is_empty() {
+        msg.reply(ctx, "No messages match that.").await?;
+        return Ok(());
+    }
+
+    let plural = if matched.len() == 1 { "" } else { "s" };
+
+    if !confirm(ctx, msg, &format!("Delete {} message{plural}?", matched.len()), CONFIRM_TIMEOUT).await? {
+        return Ok(());
+    }
+
+    let bulk_cutoff = Utc::now() - BULK_DELETE_AGE + BULK_DELETE_MARGIN;
+    let (recent, old): (Vec<_>, Vec<_>) = matched.iter().partition(|(_, sent)| *sent > bulk_cutoff);
+
+    for chunk in recent.chunks(100) {
+        msg.channel_id.delete_messages(ctx, chunk.iter().map(|(id, _)| *id)).await?;
+    }
+
+    // older messages can only be deleted one at a time
+    for (id, _) in &old {
+        msg.channel_id.delete_message(ctx, *id).await?;
+    }
+
+    let confirmation = msg.channel_id.say(ctx, format!("Deleted {} message{plural}.", matched.len())).await?;
+
+    // clean up the command and confirmation once there's been time to read it
+    tokio::time::sleep(StdDuration::from_secs(5)).await;
+    drop(msg.delete(ctx).await);
+    drop(confirmation.delete(ctx).await);
+
+    Ok(())
+}
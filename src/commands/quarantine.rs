@@ -0,0 +1,187 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::moderation::{ModAction, audit_reason, check_target, notify_target, record_action};
+use crate::utilities::mod_notes::show_notes;
+use crate::utilities::quarantine::{is_quarantined, quarantine_member, quarantine_role, release_member};
+
+/// Most quarantined members listed at once.
+const MAX_LISTED: i64 = 25;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Quarantines a member: takes all their roles away and gives them the quarantine role, so a possibly compromised account can be looked into without banning it. Their roles are saved and given back with `unquarantine`, and members who leave while quarantined are quarantined again if they rejoin."]
+#[usage = "<user> [reason]"]
+#[example = "@user Sending scam links"]
+#[min_args(1)]
+#[sub_commands(quarantine_role_set, quarantine_list)]
+async fn quarantine(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Quarantine, Permissions::MANAGE_ROLES).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    show_notes(ctx, msg, target_id).await;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(role_id) = quarantine_role(&database, guild_id).await? else {
+        msg.reply(ctx, "This server has no quarantine role. Set one with `quarantine role <role>`.").await?;
+        return Ok(());
+    };
+
+    if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    if is_quarantined(&database, guild_id, target_id).await? {
+        msg.reply(ctx, "That member is already quarantined.").await?;
+        return Ok(());
+    }
+
+    let member = guild_id.member(ctx, target_id).await?;
+    let taken = quarantine_member(ctx, &database, &member, role_id, msg.author.id, &audit_reason(&msg.author, reason)).await?;
+
+    notify_target(ctx, guild_id, &member.user, ModAction::Quarantine, reason).await;
+    let case = record_action(ctx, &database, guild_id, ModAction::Quarantine, target_id, msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!(
+        "Quarantined **{}** (case #{case}). {taken} role{} will be given back with `unquarantine`.",
+        member.user.tag(),
+        if taken == 1 { "" } else { "s" }
+    )).await?;
+
+    Ok(())
+}
+
+#[command("role")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the role quarantined members are given. It should be denied access to every channel except where moderators can talk to them."]
+#[usage = "[role]"]
+#[example = "@Quarantined"]
+#[max_args(1)]
+async fn quarantine_role_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        match quarantine_role(&database, guild_id).await? {
+            Some(role_id) => msg.reply(ctx, format!("Quarantined members are given {}.", role_id.mention())).await?,
+            None => msg.reply(ctx, "This server has no quarantine role.").await?
+        };
+
+        return Ok(());
+    }
+
+    let role_id = args.role(ctx, msg).await?.id;
+
+    if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET quarantine_role_id = ? WHERE guild_id = ?", db_role_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, format!("Quarantined members will now be given {}.", role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Lists the members who are quarantined."]
+#[num_args(0)]
+async fn quarantine_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let members = sqlx::query!(
+        "SELECT user_id, moderator_id, quarantined_at FROM quarantined_members WHERE guild_id = ? ORDER BY quarantined_at LIMIT ?",
+        guild_id,
+        MAX_LISTED
+    ).fetch_all(&database).await?;
+
+    let description = if members.is_empty() {
+        "Nobody is quarantined.".to_string()
+    } else {
+        members.iter()
+            .map(|row| {
+                let since = Timestamp::parse(&row.quarantined_at).map_or_else(|_| row.quarantined_at.clone(), |since| format!("<t:{}:R>", since.unix_timestamp()));
+                format!("<@{}>, by <@{}> {since}", row.user_id, row.moderator_id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Quarantined members")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Lets a member out of quarantine, giving back the roles they had. Roles deleted since, or now above the bot's highest role, can't be given back."]
+#[usage = "<user> [reason]"]
+#[example = "@user Account recovered"]
+#[min_args(1)]
+async fn unquarantine(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Unquarantine, Permissions::MANAGE_ROLES).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(restored) = release_member(ctx, &database, guild_id, target_id, &audit_reason(&msg.author, reason)).await? else {
+        msg.reply(ctx, "That member isn't quarantined.").await?;
+        return Ok(());
+    };
+
+    let target = target_id.to_user(ctx).await?;
+
+    notify_target(ctx, guild_id, &target, ModAction::Unquarantine, reason).await;
+    let case = record_action(ctx, &database, guild_id, ModAction::Unquarantine, target_id, msg.author.id, reason).await?;
+
+    msg.reply(ctx, format!(
+        "Let **{}** out of quarantine (case #{case}) and gave back {restored} role{}.",
+        target.tag(),
+        if restored == 1 { "" } else { "s" }
+    )).await?;
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::warn;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::guild_branding;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::quotes::{add_quote, get_quote, message_image, quote_embed, quotes_channel, random_quote};
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows a saved quote by its number. `quote add` saves a message as a quote and `quote random` shows a random one."]
+#[usage = "<id>"]
+#[example = "12"]
+#[sub_commands(quote_add, quote_random, quote_remove, quote_channel)]
+#[num_args(1)]
+async fn quote(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Ok(id) = args.single::<i64>() else {
+        msg.reply(ctx, "Please give the number of a quote, or use `quote random`.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    match get_quote(&database, msg.guild_id.unwrap(), id).await? {
+        Some(quote) => msg.channel_id.send_message(ctx, CreateMessage::new().embed(quote_embed(&guild_branding(ctx, msg.guild_id).await, &quote))).await?,
+        None => msg.reply(ctx, format!("There is no quote #{id}.")).await?
+    };
+
+    Ok(())
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[description = "Saves a message in this server as a quote. Reply to the message, or give a link to it."]
+#[usage = "[message link]"]
+#[example = "https://discord.com/channels/123/456/789"]
+#[max_args(1)]
+async fn quote_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let message = match msg.referenced_message.as_deref() {
+        Some(replied) if args.is_empty() => replied.clone(),
+        _ => {
+            let link = args.message_link(msg)?;
+
+            if link.guild_id != Some(guild_id) {
+                msg.reply(ctx, "Only messages from this server can be quoted.").await?;
+                return Ok(());
+            }
+
+            match link.channel_id.message(ctx, link.message_id).await {
+                Ok(message) => message,
+                Err(_) => {
+                    msg.reply(ctx, "I couldn't find that message, check that I can see its channel.").await?;
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    if message.author.bot {
+        msg.reply(ctx, "Messages from bots can't be quoted.").await?;
+        return Ok(());
+    }
+
+    if message.content.trim().is_empty() && message_image(&message).is_none() {
+        msg.reply(ctx, "That message has no text or image to quote.").await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(id) = add_quote(&database, guild_id, &message, msg.author.id).await? else {
+        msg.reply(ctx, "That message is already quoted.").await?;
+        return Ok(());
+    };
+
+    let quote = get_quote(&database, guild_id, id).await?.expect("the quote was just added");
+    let embed = quote_embed(&guild_branding(ctx, msg.guild_id).await, &quote);
+
+    // the quote is saved either way, so a quotes channel that can't be posted in isn't an error
+    if let Some(channel_id) = quotes_channel(&database, guild_id).await?.filter(|channel_id| *channel_id != msg.channel_id) {
+        if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed.clone())).await {
+            warn!("Couldn't mirror quote #{id} to channel {channel_id}: {why}");
+        }
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().content(format!("Saved as quote #{id}.")).embed(embed).reference_message(msg)).await?;
+
+    Ok(())
+}
+
+#[command("random")]
+#[only_in(guilds)]
+#[description = "Shows a random quote from this server, or from one member."]
+#[usage = "[member]"]
+#[example = "@Kanzoey"]
+#[max_args(1)]
+async fn quote_random(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let author_id = if args.is_empty() { None } else { Some(args.user_id()?) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    match random_quote(&database, msg.guild_id.unwrap(), author_id).await? {
+        Some(quote) => msg.channel_id.send_message(ctx, CreateMessage::new().embed(quote_embed(&guild_branding(ctx, msg.guild_id).await, &quote))).await?,
+        None if author_id.is_some() => msg.reply(ctx, "That member hasn't been quoted yet.").await?,
+        None => msg.reply(ctx, "This server has no quotes yet. Save one with `quote add`.").await?
+    };
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[description = "Removes a quote. The member quoted and whoever saved it can remove it, as can members who can manage messages."]
+#[usage = "<id>"]
+#[example = "12"]
+#[num_args(1)]
+async fn quote_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(quote) = get_quote(&database, guild_id, id).await? else {
+        msg.reply(ctx, format!("There is no quote #{id}.")).await?;
+        return Ok(());
+    };
+
+    let channel = msg.channel_id.to_channel(ctx).await.ok().and_then(Channel::guild);
+
+    let can_manage = channel.zip(msg.guild(&ctx.cache)).is_some_and(|(channel, guild)| {
+        guild.members.get(&msg.author.id)
+            .is_some_and(|member| guild.user_permissions_in(&channel, member).manage_messages())
+    });
+
+    if !can_manage && msg.author.id != quote.author_id && msg.author.id != quote.quoted_by {
+        msg.reply(ctx, "Only the member quoted, whoever saved the quote or moderators can remove it.").await?;
+        return Ok(());
+    }
+
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!("DELETE FROM quotes WHERE guild_id = ? AND id = ?", db_guild_id, id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, format!("Removed quote #{id}.")).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets a channel new quotes are also posted in. Use `off` to only keep them for `quote`."]
+#[usage = "<channel|off>"]
+#[example = "#quotes"]
+#[num_args(1)]
+async fn quote_channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.map(|channel_id| channel_id.get() as i64));
+
+    sqlx::query!(
+        "UPDATE guild_settings SET quotes_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("New quotes will be posted in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "New quotes won't be posted anywhere anymore.").await?
+    };
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, ReqwestClientContainer};
+use crate::utilities::reddit::{FetchError, SORTS, fetch_posts, is_subreddit_name, newest_post};
+
+/// Most subreddits a guild can watch, which keeps polling Reddit cheap.
+const MAX_SUBSCRIPTIONS: i32 = 10;
+const MAX_FLAIRS: usize = 10;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists the subreddits mirrored into this server's channels. `reddit watch` posts new posts from a subreddit into a channel."]
+#[sub_commands(reddit_watch, reddit_unwatch, reddit_flairs)]
+#[num_args(0)]
+async fn reddit(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let subscriptions = sqlx::query!("SELECT channel_id, subreddit, sort, flairs FROM reddit_subscriptions WHERE guild_id = ? ORDER BY subreddit", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let description = if subscriptions.is_empty() {
+        "No subreddits are watched in this server yet, add one with `reddit watch`.".to_string()
+    } else {
+        subscriptions.iter()
+            .map(|subscription| {
+                let flairs = match &subscription.flairs {
+                    Some(flairs) => format!(", only {}", flairs.replace(',', ", ")),
+                    None => String::new()
+                };
+
+                format!("r/{} in <#{}> ({}{flairs})", subscription.subreddit, subscription.channel_id, subscription.sort)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Subreddit feeds ({} of {MAX_SUBSCRIPTIONS})", subscriptions.len()))
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("watch")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts new posts from a subreddit into a channel, checking every few minutes. Watching `hot` posts the ones that reach the front page instead of every new one. Posts marked NSFW are only posted in age-restricted channels."]
+#[usage = "<subreddit> <#channel> [new|hot]"]
+#[example = "rust #news"]
+#[min_args(2)]
+#[max_args(3)]
+async fn reddit_watch(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let subreddit = subreddit_name(&args.text("subreddit")?);
+    let channel = args.channel(ctx, msg).await?;
+
+    let sort = match args.single::<String>() {
+        Ok(sort) => sort.to_lowercase(),
+        Err(_) => SORTS[0].to_string()
+    };
+
+    if !is_subreddit_name(&subreddit) {
+        msg.reply(ctx, "Please give the name of a subreddit, such as `rust` or `r/rust`.").await?;
+        return Ok(());
+    }
+
+    if !SORTS.contains(&sort.as_str()) {
+        msg.reply(ctx, "Subreddits can be watched by `new` or `hot` posts.").await?;
+        return Ok(());
+    }
+
+    if !matches!(channel.kind, ChannelType::Text | ChannelType::News) {
+        msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+
+    let (guild_id, channel_id) = (msg.guild_id.unwrap().get() as i64, channel.id.get() as i64);
+
+    let existing = sqlx::query!("SELECT COUNT(*) AS count FROM reddit_subscriptions WHERE guild_id = ? AND NOT (channel_id = ? AND subreddit = ?)", guild_id, channel_id, subreddit)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if existing >= MAX_SUBSCRIPTIONS {
+        msg.reply(ctx, format!("This server already watches the maximum of {MAX_SUBSCRIPTIONS} subreddits.")).await?;
+        return Ok(());
+    }
+
+    // only posts made after this are mirrored, rather than the whole current page
+    let cursor = match fetch_posts(&client, &subreddit, &sort).await {
+        Ok(posts) => newest_post(&posts),
+        Err(FetchError::Unavailable) => {
+            msg.reply(ctx, format!("r/{subreddit} doesn't exist, or is private or banned.")).await?;
+            return Ok(());
+        }
+        Err(FetchError::Request(_)) => {
+            msg.reply(ctx, "I couldn't reach Reddit, please try again later.").await?;
+            return Ok(());
+        }
+    };
+
+    sqlx::query!(
+        "INSERT INTO reddit_subscriptions (guild_id, channel_id, subreddit, sort, last_post_created) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (channel_id, subreddit) DO UPDATE SET sort = excluded.sort, last_post_created = excluded.last_post_created",
+        guild_id,
+        channel_id,
+        subreddit,
+        sort,
+        cursor
+    ).execute(&database).await?;
+
+    let mut reply = format!("New {sort} posts from r/{subreddit} will be posted in {}.", channel.mention());
+
+    if !channel.nsfw {
+        reply.push_str(" Posts marked NSFW will be left out, since the channel isn't age-restricted.");
+    }
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("unwatch")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Stops posting a subreddit's posts into a channel."]
+#[usage = "<subreddit> <#channel>"]
+#[example = "rust #news"]
+#[num_args(2)]
+async fn reddit_unwatch(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let subreddit = subreddit_name(&args.text("subreddit")?);
+    let channel = args.channel(ctx, msg).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channel_id = channel.id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM reddit_subscriptions WHERE channel_id = ? AND subreddit = ?", channel_id, subreddit)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    let reply = if removed == 0 {
+        format!("r/{subreddit} isn't watched in {}.", channel.mention())
+    } else {
+        format!("Posts from r/{subreddit} won't be posted in {} anymore.", channel.mention())
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("flairs")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Only posts a watched subreddit's posts with one of the given flairs, separated by commas. `clear` posts every flair again."]
+#[usage = "<subreddit> <#channel> <flair, flair...|clear>"]
+#[example = "rust #news announcement, news"]
+#[min_args(3)]
+async fn reddit_flairs(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let subreddit = subreddit_name(&args.text("subreddit")?);
+    let channel = args.channel(ctx, msg).await?;
+    let rest = args.rest().trim();
+
+    let flairs = if rest.eq_ignore_ascii_case("clear") {
+        None
+    } else {
+        let flairs = rest.split(',')
+            .map(|flair| flair.trim().to_lowercase())
+            .filter(|flair| !flair.is_empty())
+            .collect::<Vec<_>>();
+
+        if flairs.is_empty() || flairs.len() > MAX_FLAIRS {
+            msg.reply(ctx, format!("Please give between 1 and {MAX_FLAIRS} flairs, separated by commas.")).await?;
+            return Ok(());
+        }
+
+        Some(flairs.join(","))
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channel_id = channel.id.get() as i64;
+
+    let updated = sqlx::query!("UPDATE reddit_subscriptions SET flairs = ? WHERE channel_id = ? AND subreddit = ?", flairs, channel_id, subreddit)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    let reply = match (updated, flairs) {
+        (0, _) => format!("r/{subreddit} isn't watched in {}.", channel.mention()),
+        (_, Some(flairs)) => format!("Only posts from r/{subreddit} flaired {} will be posted in {}.", flairs.replace(',', ", "), channel.mention()),
+        (_, None) => format!("Posts from r/{subreddit} with any flair will be posted in {}.", channel.mention())
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// A subreddit as it's stored, without an `r/` in front and in lowercase.
+fn subreddit_name(arg: &str) -> String {
+    let arg = arg.trim_start_matches('/');
+    let name = arg.strip_prefix("r/").or_else(|| arg.strip_prefix("R/")).unwrap_or(arg);
+
+    name.to_lowercase()
+}
@@ -0,0 +1,164 @@
+use chrono::DateTime;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::scheduler::{Job, parse_when, schedule_job};
+use crate::utilities::timezones::local_timezone;
+
+/// Most pending reminders a single user can have.
+const MAX_REMINDERS: i32 = 25;
+const MAX_REMINDER_LENGTH: usize = 1000;
+
+#[command]
+#[description = "Reminds you of something later, in this channel (or by DM if set in DMs). `when` is a delay (`2h`, `in 1d12h`) or a time (`2024-01-31 09:00`) in your timezone (see `timezone`)."]
+#[usage = "me <when> [to] <reminder>"]
+#[example = "me in 2h to take out the trash"]
+#[min_args(2)]
+async fn remind(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    // "me" and "to" are only there to read naturally
+    if args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("me")) {
+        args.advance();
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let timezone = local_timezone(&database, msg.author.id, msg.guild_id).await?;
+    let mut when = args.single_quoted::<String>()?;
+
+    // delays and times may also be given unquoted, as two words
+    if when == "in" || (parse_when(&when, &timezone).is_none() && args.current().is_some_and(|time| time.contains(':'))) {
+        when = format!("{when} {}", args.single::<String>()?);
+    }
+
+    let Some(when) = parse_when(&when, &timezone).filter(|when| when.repeat.is_none()) else {
+        msg.reply(ctx, format!("I couldn't understand `{when}` as a time. Use a delay such as `2h` or a time such as `2024-01-31 09:00`.")).await?;
+        return Ok(());
+    };
+
+    if args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("to")) {
+        args.advance();
+    }
+
+    let content = args.rest().trim().to_string();
+
+    if content.is_empty() {
+        msg.reply(ctx, "Please say what to remind you of.").await?;
+        return Ok(());
+    }
+
+    if content.chars().count() > MAX_REMINDER_LENGTH {
+        msg.reply(ctx, format!("Reminders can be at most {MAX_REMINDER_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let user_id = msg.author.id.get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM scheduled_jobs WHERE created_by = ? AND kind = 'reminder'",
+        user_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_REMINDERS {
+        msg.reply(ctx, format!("You already have the maximum of {MAX_REMINDERS} reminders. Cancel one with `reminders cancel <id>`.")).await?;
+        return Ok(());
+    }
+
+    let job = Job::Reminder {
+        user_id: msg.author.id.get(),
+        channel_id: msg.guild_id.map(|_| msg.channel_id.get()),
+        content,
+        link: msg.link()
+    };
+
+    let id = schedule_job(&database, msg.guild_id, &job, &when, msg.author.id.get()).await?;
+
+    msg.reply(ctx, format!("Got it, I'll remind you <t:{}:R>. (reminder #{id})", when.first_run.timestamp())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Lists your pending reminders."]
+#[sub_commands(reminders_list, reminders_cancel)]
+async fn reminders(ctx: &Context, msg: &Message) -> CommandResult {
+    list_reminders(ctx, msg).await
+}
+
+#[command("list")]
+#[description = "Lists your pending reminders."]
+#[num_args(0)]
+async fn reminders_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_reminders(ctx, msg).await
+}
+
+#[command("cancel")]
+#[description = "Cancels one of your reminders."]
+#[usage = "<id>"]
+#[example = "12"]
+#[num_args(1)]
+async fn reminders_cancel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let user_id = msg.author.id.get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE id = ? AND created_by = ? AND kind = 'reminder'",
+        id,
+        user_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("You have no reminder #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Cancelled reminder #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+async fn list_reminders(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let user_id = msg.author.id.get() as i64;
+
+    let jobs = sqlx::query!(
+        "SELECT id, payload, next_run FROM scheduled_jobs WHERE created_by = ? AND kind = 'reminder' ORDER BY next_run",
+        user_id
+    ).fetch_all(&database).await?;
+
+    let description = if jobs.is_empty() {
+        "You have no pending reminders. Set one with `remind me in 2h to ...`.".to_string()
+    } else {
+        jobs.iter()
+            .map(|row| {
+                let content = match serde_json::from_str::<Job>(&row.payload) {
+                    Ok(Job::Reminder { content, .. }) if content.chars().count() > 80 => format!("{}...", content.chars().take(80).collect::<String>()),
+                    Ok(Job::Reminder { content, .. }) => content,
+                    _ => "unknown reminder".to_string()
+                };
+
+                let next_run = DateTime::parse_from_rfc3339(&row.next_run)
+                    .map_or_else(|_| row.next_run.clone(), |time| format!("<t:{}:R>", time.timestamp()));
+
+                format!("**#{}** {next_run} - {content}", row.id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Your reminders")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::reports::reports_channel;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the channel messages reported with the `Report Message` app command are sent to, with buttons to delete the message, warn or ban its author, or dismiss the report. Use `off` to stop taking reports."]
+#[usage = "[channel|off]"]
+#[example = "#reports"]
+#[max_args(1)]
+async fn reports(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        match reports_channel(&database, guild_id).await? {
+            Some(channel_id) => msg.reply(ctx, format!("Reports are sent to {}.", channel_id.mention())).await?,
+            None => msg.reply(ctx, "This server doesn't take reports.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET reports_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Reported messages will now be sent to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Messages can no longer be reported.").await?
+    };
+
+    Ok(())
+}
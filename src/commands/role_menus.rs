@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::premium::guild_tier;
+use crate::utilities::role_menus::role_menu_components;
+
+/// How long the role picker waits for a choice.
+const PICKER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Discord's limits on select menu options and embed titles.
+const MAX_MENU_ROLES: u8 = 25;
+const MAX_TITLE_LENGTH: usize = 256;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's role menus, messages with a menu members pick their own roles from."]
+#[sub_commands(rolemenu_create, rolemenu_delete)]
+async fn rolemenu(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let menus = sqlx::query!(
+        r#"SELECT role_menus.message_id, channel_id, title, COUNT(role_id) AS "roles!: i64"
+        FROM role_menus LEFT JOIN role_menu_roles ON role_menu_roles.message_id = role_menus.message_id
+        WHERE guild_id = ? GROUP BY role_menus.message_id ORDER BY role_menus.message_id"#,
+        db_guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if menus.is_empty() {
+        "This server has no role menus yet. Use `rolemenu create <channel> <title>` to make one.".to_string()
+    } else {
+        menus.iter()
+            .map(|row| {
+                let link = MessageId::new(row.message_id as u64).link(ChannelId::new(row.channel_id as u64), Some(guild_id));
+                format!("**[{}]({link})** - {} roles (`{}`)", row.title, row.roles, row.message_id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Role menus")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("create")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Creates a role menu in a channel. You'll be asked to pick its roles, up to 25."]
+#[usage = "<channel> <title>"]
+#[example = "#roles Pick your pronouns"]
+#[min_args(2)]
+async fn rolemenu_create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let title = args.rest().trim().to_string();
+
+    if title.chars().count() > MAX_TITLE_LENGTH {
+        msg.reply(ctx, format!("Titles can be at most {MAX_TITLE_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM role_menus WHERE guild_id = ?", db_guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    let max_menus = guild_tier(ctx, Some(guild_id)).await.max_role_panels();
+
+    if count as usize >= max_menus {
+        msg.reply(ctx, format!("This server already has the maximum of {max_menus} role menus. Delete one with `rolemenu delete` first.")).await?;
+        return Ok(());
+    }
+
+    let picker = CreateSelectMenu::new("rolemenu_roles", CreateSelectMenuKind::Role { default_roles: None })
+        .placeholder("Choose roles")
+        .min_values(1)
+        .max_values(MAX_MENU_ROLES);
+
+    let builder = CreateMessage::new()
+        .content(format!("Which roles should members be able to pick? Choose up to {MAX_MENU_ROLES}."))
+        .components(vec![CreateActionRow::SelectMenu(picker)])
+        .reference_message(msg);
+
+    let mut prompt = msg.channel_id.send_message(ctx, builder).await?;
+
+    let roles = loop {
+        let interaction = prompt.await_component_interaction(&ctx.shard)
+            .author_id(msg.author.id)
+            .timeout(PICKER_TIMEOUT)
+            .await;
+
+        let Some(interaction) = interaction else {
+            prompt.edit(ctx, EditMessage::new().content("Creating the role menu timed out.").components(vec![])).await?;
+            return Ok(());
+        };
+
+        let ComponentInteractionDataKind::RoleSelect { values } = &interaction.data.kind else {
+            continue;
+        };
+
+        let problems: Vec<String> = values.iter()
+            .filter_map(|role_id| check_assignable(ctx, guild_id, *role_id).err())
+            .collect();
+
+        if problems.is_empty() {
+            let response = CreateInteractionResponseMessage::new()
+                .content("Creating the role menu...")
+                .components(vec![]);
+
+            interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+
+            break values.clone();
+        }
+
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("Some of those roles can't be given out:\n{}\nPlease choose again.", problems.join("\n")));
+
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+    };
+
+    // highest roles first, as in the member list
+    let mut named_roles: Vec<(RoleId, String, u16)> = {
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+            prompt.edit(ctx, EditMessage::new().content("This server isn't cached yet, please try again in a moment.")).await?;
+            return Ok(());
+        };
+
+        roles.iter()
+            .filter_map(|role_id| guild.roles.get(role_id).map(|role| (*role_id, role.name.clone(), role.position)))
+            .collect()
+    };
+
+    named_roles.sort_by_key(|(_, _, position)| std::cmp::Reverse(*position));
+    let named_roles: Vec<(RoleId, String)> = named_roles.into_iter().map(|(role_id, name, _)| (role_id, name)).collect();
+
+    let role_list = named_roles.iter().map(|(role_id, _)| role_id.mention().to_string()).collect::<Vec<_>>().join("\n");
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(title.clone())
+        .description(format!("Pick your roles from the menu below. Leave a role out to have it taken away.\n\n{role_list}"));
+
+    let menu = channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(role_menu_components(&named_roles))).await?;
+
+    let (message_id, db_channel_id, created_by) = (menu.id.get() as i64, channel_id.get() as i64, msg.author.id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO role_menus (message_id, guild_id, channel_id, title, created_by) VALUES (?, ?, ?, ?, ?)",
+        message_id,
+        db_guild_id,
+        db_channel_id,
+        title,
+        created_by
+    ).execute(&database).await?;
+
+    for (role_id, _) in &named_roles {
+        let role_id = role_id.get() as i64;
+
+        sqlx::query!("INSERT INTO role_menu_roles (message_id, role_id) VALUES (?, ?)", message_id, role_id)
+            .execute(&database)
+            .await?;
+    }
+
+    prompt.edit(ctx, EditMessage::new().content(format!("Created the role menu in {}.", channel_id.mention()))).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Deletes a role menu by its message ID, as shown by `rolemenu`."]
+#[usage = "<message ID>"]
+#[num_args(1)]
+async fn rolemenu_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let message_id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM role_menus WHERE message_id = ? AND guild_id = ? RETURNING channel_id",
+        message_id,
+        guild_id
+    ).fetch_optional(&database).await?;
+
+    let Some(deleted) = deleted else {
+        msg.reply(ctx, format!("There is no role menu with the message ID `{message_id}`.")).await?;
+        return Ok(());
+    };
+
+    sqlx::query!("DELETE FROM role_menu_roles WHERE message_id = ?", message_id)
+        .execute(&database)
+        .await?;
+
+    // the message may already be gone, which is fine
+    drop(ChannelId::new(deleted.channel_id as u64).delete_message(ctx, MessageId::new(message_id as u64)).await);
+
+    msg.reply(ctx, "Deleted the role menu.").await?;
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use chrono::Utc;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::guild_branding;
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::moderation::audit_reason;
+use crate::utilities::parsing::format_duration;
+use crate::utilities::roles::{RoleTarget, cancel_temprole, check_role_change, progress_embed, run_role_job};
+use crate::utilities::scheduler::{Job, When, schedule_job};
+
+/// Checks that the author may change the member's role, replying if they can't.
+async fn can_change_role(ctx: &Context, msg: &Message, member: &Member, role_id: RoleId) -> Result<bool, SerenityError> {
+    match check_role_change(ctx, member.guild_id, msg.author.id, Some(member.user.id), role_id).await {
+        Ok(()) => Ok(true),
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            Ok(false)
+        }
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Shows the bulk role change running in this server, if there is one. You can only give out and take away roles below your highest role, and only change the roles of members below you."]
+#[sub_commands(role_add, role_remove, role_all, role_bots, role_cancel)]
+async fn role(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let job = sqlx::query!("SELECT role_id, target, processed, changed, failed FROM role_jobs WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let Some(job) = job else {
+        msg.reply(ctx, "No bulk role change is running. Use `role all` or `role bots` to start one.").await?;
+        return Ok(());
+    };
+
+    let target = RoleTarget::parse(&job.target).unwrap_or(RoleTarget::All);
+    let embed = progress_embed(ctx, &guild_branding(ctx, Some(guild_id)).await, guild_id, RoleId::new(job.role_id as u64), target, (job.processed, job.changed, job.failed), "Running");
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives a member a role."]
+#[usage = "<user> <role> [reason]"]
+#[example = "@user @Helper"]
+#[min_args(2)]
+async fn role_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let member = args.member(ctx, msg).await?;
+    let role_id = args.role(ctx, msg).await?.id;
+
+    if !can_change_role(ctx, msg, &member, role_id).await? {
+        return Ok(());
+    }
+
+    if member.roles.contains(&role_id) {
+        msg.reply(ctx, format!("**{}** already has {}.", member.user.tag(), role_id.mention())).await?;
+        return Ok(());
+    }
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let audit = audit_reason(&msg.author, reason);
+
+    ctx.http.add_member_role(member.guild_id, member.user.id, role_id, Some(&audit)).await?;
+
+    msg.reply(ctx, format!("Gave **{}** {}.", member.user.tag(), role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Takes a role away from a member. If it was a temporary role, it won't be taken away again when it would have expired."]
+#[usage = "<user> <role> [reason]"]
+#[example = "@user @Helper"]
+#[min_args(2)]
+async fn role_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let member = args.member(ctx, msg).await?;
+    let role_id = args.role(ctx, msg).await?.id;
+
+    if !can_change_role(ctx, msg, &member, role_id).await? {
+        return Ok(());
+    }
+
+    if !member.roles.contains(&role_id) {
+        msg.reply(ctx, format!("**{}** doesn't have {}.", member.user.tag(), role_id.mention())).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let audit = audit_reason(&msg.author, reason);
+
+    ctx.http.remove_member_role(member.guild_id, member.user.id, role_id, Some(&audit)).await?;
+    cancel_temprole(&database, member.guild_id, member.user.id, role_id).await?;
+
+    msg.reply(ctx, format!("Took {} away from **{}**.", role_id.mention(), member.user.tag())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives a member a role for a while, e.g. `1h`, `3d` or `2w`. It's taken away automatically, even if the bot restarts in between. Giving someone a temporary role they already have sets when it expires."]
+#[usage = "<user> <duration> <role> [reason]"]
+#[example = "@user 7d @Winner"]
+#[min_args(3)]
+async fn temprole(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let member = args.member(ctx, msg).await?;
+    let duration = args.duration()?;
+
+    let Some(first_run) = Utc::now().checked_add_signed(duration) else {
+        msg.reply(ctx, "That's too long, please give a shorter duration.").await?;
+        return Ok(());
+    };
+
+    let role_id = args.role(ctx, msg).await?.id;
+
+    if !can_change_role(ctx, msg, &member, role_id).await? {
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if !member.roles.contains(&role_id) {
+        let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+        let audit = audit_reason(&msg.author, reason);
+
+        ctx.http.add_member_role(member.guild_id, member.user.id, role_id, Some(&audit)).await?;
+    }
+
+    // a new expiry replaces whatever was pending for the same role
+    cancel_temprole(&database, member.guild_id, member.user.id, role_id).await?;
+
+    let job = Job::RemoveRole { user_id: member.user.id.get(), role_id: role_id.get() };
+    schedule_job(&database, Some(member.guild_id), &job, &When { first_run, repeat: None }, msg.author.id.get()).await?;
+
+    msg.reply(ctx, format!("Gave **{}** {} for {}.", member.user.tag(), role_id.mention(), format_duration(duration))).await?;
+
+    Ok(())
+}
+
+#[command("all")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives a role to every member who doesn't have it yet. This runs in the background and reports its progress, picking up where it left off if the bot restarts. Only one bulk role change can run at a time."]
+#[usage = "<role>"]
+#[example = "@Member"]
+#[num_args(1)]
+async fn role_all(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    start_role_job(ctx, msg, args, RoleTarget::All).await
+}
+
+#[command("bots")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Gives a role to every bot that doesn't have it yet. This runs in the background like `role all`."]
+#[usage = "<role>"]
+#[example = "@Bots"]
+#[num_args(1)]
+async fn role_bots(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    start_role_job(ctx, msg, args, RoleTarget::Bots).await
+}
+
+async fn start_role_job(ctx: &Context, msg: &Message, mut args: Args, target: RoleTarget) -> CommandResult {
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_role_change(ctx, guild_id, msg.author.id, None, role_id).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let running = sqlx::query!("SELECT role_id FROM role_jobs WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    if running.is_some() {
+        msg.reply(ctx, "A bulk role change is already running in this server. Wait for it to finish or stop it with `role cancel`.").await?;
+        return Ok(());
+    }
+
+    let prompt = match target {
+        RoleTarget::All => format!("Give {} to every member who doesn't have it?", role_id.mention()),
+        RoleTarget::Bots => format!("Give {} to every bot that doesn't have it?", role_id.mention())
+    };
+
+    if !confirm(ctx, msg, &prompt, CONFIRM_TIMEOUT).await? {
+        return Ok(());
+    }
+
+    let embed = progress_embed(ctx, &guild_branding(ctx, Some(guild_id)).await, guild_id, role_id, target, (0, 0, 0), "Starting");
+    let progress = msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    let (db_role_id, channel_id, message_id, requested_by) = (role_id.get() as i64, msg.channel_id.get() as i64, progress.id.get() as i64, msg.author.id.get() as i64);
+    let target_name = target.as_str();
+
+    let started = sqlx::query!(
+        "INSERT INTO role_jobs (guild_id, role_id, target, channel_id, message_id, requested_by) VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id) DO NOTHING",
+        db_guild_id,
+        db_role_id,
+        target_name,
+        channel_id,
+        message_id,
+        requested_by
+    ).execute(&database).await?.rows_affected();
+
+    if started == 0 {
+        progress.delete(ctx).await?;
+        msg.reply(ctx, "A bulk role change is already running in this server. Wait for it to finish or stop it with `role cancel`.").await?;
+        return Ok(());
+    }
+
+    tokio::spawn(run_role_job(Context::clone(ctx), guild_id));
+
+    Ok(())
+}
+
+#[command("cancel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[description = "Stops the bulk role change running in this server. Members who were already given the role keep it."]
+#[num_args(0)]
+async fn role_cancel(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let cancelled = sqlx::query!("DELETE FROM role_jobs WHERE guild_id = ?", guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if cancelled == 0 {
+        msg.reply(ctx, "No bulk role change is running.").await?;
+    } else {
+        msg.reply(ctx, "Stopping the bulk role change.").await?;
+    }
+
+    Ok(())
+}
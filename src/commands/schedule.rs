@@ -0,0 +1,231 @@
+use chrono::DateTime;
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::{format_duration, parse_channel};
+use crate::utilities::scheduler::{Job, Repeat, MIN_REPEAT_INTERVAL, parse_when, schedule_job};
+use crate::utilities::timezones::guild_timezone_or_utc;
+
+/// Most scheduled messages a single guild can have.
+const MAX_SCHEDULED_MESSAGES: i32 = 25;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's scheduled messages. `schedule add` (or `schedule message`) schedules one and `schedule remove` (or `schedule delete`) deletes one."]
+#[sub_commands(schedule_message, schedule_list, schedule_pause, schedule_resume, schedule_delete)]
+async fn schedule(ctx: &Context, msg: &Message) -> CommandResult {
+    list_schedules(ctx, msg).await
+}
+
+#[command("message")]
+#[aliases("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Schedules a message. `when` is a delay (`2h`, `in 1d`), a time (`\"2024-01-31 09:00\"`), an interval (`\"every 1d\"`) or a quoted cron expression (`\"0 9 * * MON\"`), with times in the server's timezone (see `timezone server`), and may also come before the channel. The content is a template (see `template`)."]
+#[usage = "<channel> <when> <content> | <when> <channel> <content>"]
+#[example = "#rules \"0 9 * * MON\" Weekly reminder: please read the rules, {server} thanks you!"]
+#[example = "every 1d #general Good morning, {server}!"]
+#[example = "\"2024-01-31 09:00\" #events The event starts in an hour!"]
+#[min_args(3)]
+async fn schedule_message(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    // the channel comes either first or right after `when`
+    let channel_first = args.current().and_then(parse_channel);
+
+    if channel_first.is_some() {
+        args.advance();
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let timezone = guild_timezone_or_utc(&database, guild_id).await?;
+
+    // `every` and `in` may also be given unquoted, followed by the duration
+    let mut when = args.single_quoted::<String>()?;
+
+    if when == "every" || when == "in" {
+        when = format!("{when} {}", args.single::<String>()?);
+    }
+
+    let Some(when) = parse_when(&when, &timezone) else {
+        msg.reply(ctx, format!("I couldn't understand `{when}` as a time. See `help schedule message` for the formats.")).await?;
+        return Ok(());
+    };
+
+    let channel_id = match channel_first {
+        Some(channel_id) => channel_id,
+        None => args.channel(ctx, msg).await?.id
+    };
+
+    if when.repeat.as_deref().and_then(Repeat::parse).is_some_and(|repeat| repeat.is_frequent()) {
+        msg.reply(ctx, format!("Scheduled messages can repeat at most once every {}.", format_duration(MIN_REPEAT_INTERVAL))).await?;
+        return Ok(());
+    }
+
+    let content = args.rest().trim().to_string();
+
+    if content.is_empty() {
+        msg.reply(ctx, "Please give the message to post.").await?;
+        return Ok(());
+    }
+
+    if guild_id.to_guild_cached(&ctx.cache).is_none_or(|guild| !guild.channels.contains_key(&channel_id)) {
+        msg.reply(ctx, "That channel isn't in this server.").await?;
+        return Ok(());
+    }
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM scheduled_jobs WHERE guild_id = ? AND kind = 'message'",
+        db_guild_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_SCHEDULED_MESSAGES {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_SCHEDULED_MESSAGES} scheduled messages.")).await?;
+        return Ok(());
+    }
+
+    let job = Job::ChannelMessage { channel_id: channel_id.get(), content };
+    let id = schedule_job(&database, Some(guild_id), &job, &when, msg.author.id.get()).await?;
+
+    let repeat = when.repeat.as_deref().map_or_else(String::new, |repeat| format!(", repeating `{repeat}`"));
+
+    msg.reply(ctx, format!(
+        "Scheduled message #{id} in {}, first posting <t:{}:R>{repeat}.",
+        channel_id.mention(),
+        when.first_run.timestamp()
+    )).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's scheduled messages."]
+async fn schedule_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_schedules(ctx, msg).await
+}
+
+#[command("pause")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Pauses a scheduled message until it's resumed."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn schedule_pause(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+    set_paused(ctx, msg, id, true).await
+}
+
+#[command("resume")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Resumes a paused scheduled message. Runs missed while paused are posted once straight away."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn schedule_resume(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+    set_paused(ctx, msg, id, false).await
+}
+
+#[command("delete")]
+#[aliases("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Deletes a scheduled message."]
+#[usage = "<id>"]
+#[num_args(1)]
+async fn schedule_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE id = ? AND guild_id = ? AND kind = 'message'",
+        id,
+        guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no scheduled message #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted scheduled message #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+async fn set_paused(ctx: &Context, msg: &Message, id: i64, paused: bool) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let paused_value = i64::from(paused);
+
+    let updated = sqlx::query!(
+        "UPDATE scheduled_jobs SET paused = ? WHERE id = ? AND guild_id = ? AND kind = 'message'",
+        paused_value,
+        id,
+        guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if updated == 0 {
+        msg.reply(ctx, format!("There is no scheduled message #{id}.")).await?;
+    } else if paused {
+        msg.reply(ctx, format!("Paused scheduled message #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Resumed scheduled message #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+async fn list_schedules(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let jobs = sqlx::query!(
+        "SELECT id, payload, next_run, repeat, paused FROM scheduled_jobs WHERE guild_id = ? AND kind = 'message' ORDER BY next_run",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if jobs.is_empty() {
+        "This server has no scheduled messages yet.".to_string()
+    } else {
+        jobs.iter()
+            .map(|row| {
+                let channel = match serde_json::from_str::<Job>(&row.payload) {
+                    Ok(Job::ChannelMessage { channel_id, .. }) => ChannelId::new(channel_id).mention().to_string(),
+                    _ => "unknown channel".to_string()
+                };
+
+                let next_run = DateTime::parse_from_rfc3339(&row.next_run)
+                    .map_or_else(|_| row.next_run.clone(), |time| format!("<t:{}:R>", time.timestamp()));
+
+                let status = if row.paused != 0 { " (paused)".to_string() } else { String::new() };
+                let repeat = row.repeat.as_deref().map_or_else(|| "once".to_string(), |repeat| format!("`{repeat}`"));
+
+                format!("**#{}** {channel} - next {next_run}, {repeat}{status}", row.id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Scheduled messages")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::dispatch::resolve_command;
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::scripting::{ScriptInput, compile_script, run_script};
+
+/// Most scripts a single guild can store.
+const MAX_SCRIPTS: i32 = 50;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's scripted custom commands. Scripts are written in Rhai (https://rhai.rs) and can be run by name like any other command."]
+#[sub_commands(script_set, script_delete, script_list, script_show, script_run)]
+async fn script(ctx: &Context, msg: &Message) -> CommandResult {
+    list_scripts(ctx, msg).await
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Creates or replaces a script. Scripts can read `content`, `args`, `author` (`id`, `name`, `mention`), `channel_id` and `guild_id`, and answer with `reply(text)`."]
+#[usage = "<name> <code>"]
+#[example = "greet reply(`Hello ${author.mention}!`);"]
+#[min_args(2)]
+async fn script_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let source = strip_code_block(args.rest());
+
+    if resolve_command(&name).is_some() {
+        msg.reply(ctx, format!("`{name}` is already the name of a built-in command.")).await?;
+        return Ok(());
+    }
+
+    if let Err(why) = compile_script(&source) {
+        msg.reply(ctx, format!("The script has a syntax error:\n```{why}```")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM guild_scripts WHERE guild_id = ? AND name != ?",
+        guild_id,
+        name
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_SCRIPTS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_SCRIPTS} scripts.")).await?;
+        return Ok(());
+    }
+
+    let created_by = msg.author.id.get() as i64;
+    let created_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO guild_scripts (guild_id, name, source, created_by, created_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, name) DO UPDATE SET
+            source = excluded.source,
+            created_by = excluded.created_by,
+            created_at = excluded.created_at",
+        guild_id,
+        name,
+        source,
+        created_by,
+        created_at
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Saved script `{name}`.")).await?;
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Deletes a script."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn script_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM guild_scripts WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no script named `{name}`.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted script `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's scripts."]
+async fn script_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_scripts(ctx, msg).await
+}
+
+#[command("show")]
+#[only_in(guilds)]
+#[description = "Shows a script's source code."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn script_show(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let Some(script) = sqlx::query!(
+        "SELECT source, uses FROM guild_scripts WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, format!("There is no script named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Script: {name}"))
+        .description(format!("```rust\n{}\n```", script.source))
+        .field("Uses", script.uses.to_string(), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("run")]
+#[only_in(guilds)]
+#[description = "Runs a script. Scripts can also be run directly by name."]
+#[usage = "<name> [args]"]
+#[min_args(1)]
+async fn script_run(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    if !run_guild_script(ctx, msg, &name, args.rest()).await? {
+        msg.reply(ctx, format!("There is no script named `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+/// Whether the guild has a script by this name.
+pub async fn guild_script_exists(database: &SqlitePool, guild_id: GuildId, name: &str) -> Result<bool, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let exists = sqlx::query!("SELECT name FROM guild_scripts WHERE guild_id = ? AND name = ?", guild_id, name)
+        .fetch_optional(database)
+        .await?
+        .is_some();
+
+    Ok(exists)
+}
+
+/// Runs the guild's script with the given name, if there is one. Returns whether a script was
+/// found, so callers can fall back to other handling.
+pub async fn run_guild_script(ctx: &Context, msg: &Message, name: &str, args: &str) -> Result<bool, BotError> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(false);
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let Some(script) = sqlx::query!(
+        "SELECT source FROM guild_scripts WHERE guild_id = ? AND name = ?",
+        db_guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        return Ok(false);
+    };
+
+    sqlx::query!(
+        "UPDATE guild_scripts SET uses = uses + 1 WHERE guild_id = ? AND name = ?",
+        db_guild_id,
+        name
+    ).execute(&database).await?;
+
+    let input = ScriptInput {
+        content: msg.content.clone(),
+        args: args.split_whitespace().map(str::to_string).collect(),
+        author_id: msg.author.id.get(),
+        author_name: msg.author.name.clone(),
+        channel_id: msg.channel_id.get(),
+        guild_id: guild_id.get()
+    };
+
+    let response = match run_script(script.source, input).await {
+        Ok(replies) => replies,
+        Err(why) => vec![format!("The `{name}` script failed: {why}")]
+    };
+
+    for reply in response {
+        // scripts can't ping everyone or roles, only the people they mention
+        let builder = CreateMessage::new()
+            .content(reply)
+            .allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+        drop(msg.channel_id.send_message(ctx, builder).await);
+    }
+
+    Ok(true)
+}
+
+async fn list_scripts(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let scripts = sqlx::query!(
+        "SELECT name, uses FROM guild_scripts WHERE guild_id = ? ORDER BY name",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if scripts.is_empty() {
+        "This server has no scripts yet.".to_string()
+    } else {
+        scripts.iter()
+            .map(|row| format!("`{}` - used {} time(s)", row.name, row.uses))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Scripts")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Removes a surrounding Discord code block (```rhai ... ```) if there is one.
+fn strip_code_block(source: &str) -> String {
+    let source = source.trim();
+
+    match source.strip_prefix("```").and_then(|inner| inner.strip_suffix("```")) {
+        Some(inner) => {
+            // drop the language tag on the first line, if any
+            match inner.split_once('\n') {
+                Some((first, rest)) if !first.contains(' ') => rest.to_string(),
+                _ => inner.to_string()
+            }
+        }
+        None => source.to_string()
+    }
+}
@@ -0,0 +1,529 @@
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandError, CommandResult};
+use serenity::model::application::ButtonStyle;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::starboard::MAX_THRESHOLD;
+use crate::utilities::branding::{MAX_FOOTER_LENGTH, branded_embed, guild_branding, parse_color};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, GuildSettingsContainer, get_or_create_guild_settings, update_guild_settings};
+use crate::utilities::i18n::{LANGUAGES, find_language, guild_language, translate};
+use crate::utilities::moderation::MuteType;
+use crate::utilities::parsing::{parse_channel, parse_role};
+
+/// How long the page buttons and setting menu keep working after the last use.
+const MENU_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait for a new value after a setting is picked from the menu.
+const VALUE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Every setting `settings set` can change, with what it takes.
+const SETTINGS: &[(&str, &str)] = &[
+    ("prefix", "The prefix commands start with, without spaces"),
+    ("suggestions", "`on` or `off`"),
+    ("language", "The language of errors, help, command suggestions and the server and message logs: `en`, `de`, `fr`, `es`, `pt` or `nl`"),
+    ("mute", "`timeout`, or a role to mute with"),
+    ("modlog", "A channel for moderation cases, or `off`"),
+    ("messagelog", "A channel for edited and deleted messages, or `off`"),
+    ("serverlog", "A channel for role, channel, emoji and webhook changes, or `off`"),
+    ("voicelog", "A channel for voice channel activity, or `off`"),
+    ("decancer", "`on` or `off`, to decancer names as members join or change them"),
+    ("updates", "A channel for bot updates, or `off`"),
+    ("welcome", "A channel to welcome new members in, or `off`"),
+    ("goodbye", "A channel to say goodbye in, or `off`"),
+    ("starboard", "A channel for starred messages, or `off`"),
+    ("stars", "How many stars make it onto the starboard")
+];
+
+const PAGES: usize = 4;
+
+#[command]
+#[aliases("config")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows this server's settings, with buttons to flip through them and a menu to change them."]
+#[sub_commands(settings_set, settings_branding)]
+async fn settings(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let mut page = 0;
+
+    let builder = CreateMessage::new()
+        .embed(settings_page(ctx, guild_id, page).await?)
+        .components(settings_components(page));
+
+    let mut view = msg.channel_id.send_message(ctx, builder).await?;
+
+    while let Some(interaction) = view.await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(MENU_TIMEOUT)
+        .await
+    {
+        match (interaction.data.custom_id.as_str(), &interaction.data.kind) {
+            ("settings_previous", _) => page = (page + PAGES - 1) % PAGES,
+            ("settings_next", _) => page = (page + 1) % PAGES,
+            ("settings_change", ComponentInteractionDataKind::StringSelect { values }) => {
+                let Some(&(key, hint)) = values.first().and_then(|value| SETTINGS.iter().find(|(key, _)| key == value)) else {
+                    continue;
+                };
+
+                let prompt = CreateInteractionResponseMessage::new()
+                    .content(format!("Send the new value for `{key}`: {hint}. Send `cancel` to leave it as it is."))
+                    .ephemeral(true);
+
+                interaction.create_response(ctx, CreateInteractionResponse::Message(prompt)).await?;
+
+                let reply = msg.author.await_reply(&ctx.shard)
+                    .channel_id(msg.channel_id)
+                    .timeout(VALUE_TIMEOUT)
+                    .await;
+
+                let outcome = match reply {
+                    Some(reply) if reply.content.trim().eq_ignore_ascii_case("cancel") => format!("`{key}` was left as it is."),
+                    Some(reply) => {
+                        let outcome = apply_setting(ctx, guild_id, key, reply.content.trim()).await?;
+                        drop(reply.delete(ctx).await);
+                        outcome.unwrap_or_else(|why| why)
+                    }
+                    None => format!("Changing `{key}` timed out.")
+                };
+
+                interaction.edit_response(ctx, EditInteractionResponse::new().content(outcome)).await?;
+
+                let edit = EditMessage::new()
+                    .embed(settings_page(ctx, guild_id, page).await?)
+                    .components(settings_components(page));
+
+                view.edit(ctx, edit).await?;
+
+                continue;
+            }
+            _ => continue
+        }
+
+        let response = CreateInteractionResponseMessage::new()
+            .embed(settings_page(ctx, guild_id, page).await?)
+            .components(settings_components(page));
+
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+    }
+
+    drop(view.edit(ctx, EditMessage::new().components(vec![])).await);
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Changes one of this server's settings: `prefix`, `suggestions`, `language`, `mute`, `modlog`, `messagelog`, `serverlog`, `voicelog`, `decancer`, `updates`, `welcome`, `goodbye`, `starboard` or `stars`."]
+#[usage = "<setting> <value>"]
+#[example = "modlog #mod-log"]
+#[min_args(2)]
+async fn settings_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let key = args.single::<String>()?.to_lowercase();
+
+    if !SETTINGS.iter().any(|(setting, _)| *setting == key) {
+        let keys = SETTINGS.iter().map(|(setting, _)| format!("`{setting}`")).collect::<Vec<_>>().join(", ");
+        msg.reply(ctx, format!("There's no setting called `{key}`. Settings are {keys}.")).await?;
+        return Ok(());
+    }
+
+    let outcome = apply_setting(ctx, msg.guild_id.unwrap(), &key, args.rest().trim()).await?;
+    msg.reply(ctx, outcome.unwrap_or_else(|why| why)).await?;
+
+    Ok(())
+}
+
+#[command("branding")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows how this server's embeds look, with a preview. Change their color, footer and thumbnail with the sub-commands."]
+#[sub_commands(branding_color, branding_footer, branding_thumbnail)]
+#[num_args(0)]
+async fn settings_branding(ctx: &Context, msg: &Message) -> CommandResult {
+    let branding = guild_branding(ctx, msg.guild_id).await;
+
+    let embed = branding.embed()
+        .title("Branding")
+        .description("Every embed I send in this server looks like this one. Embeds with a footer or thumbnail of their own, such as page numbers or avatars, keep theirs.")
+        .field("Color", format!("`#{:06x}`{}", branding.color(), if branding.color.is_none() { " (default)" } else { "" }), true)
+        .field("Footer", branding.footer.as_deref().unwrap_or("None"), true)
+        .field("Thumbnail", branding.thumbnail.as_deref().unwrap_or("None"), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("color")]
+#[aliases("colour")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the color of this server's embeds, as hex. Use `default` to go back to the default."]
+#[usage = "<color|default>"]
+#[example = "#5865f2"]
+#[num_args(1)]
+async fn branding_color(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let color = match args.rest().trim() {
+        value if value.eq_ignore_ascii_case("default") => None,
+        value => match parse_color(value) {
+            Some(color) => Some(color),
+            None => {
+                msg.reply(ctx, "Please give a color as hex, e.g. `#5865f2`, or use `default`.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    update_guild_settings(ctx, msg.guild_id.unwrap(), |settings| settings.branding.color = color).await?;
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .description(color.map_or_else(|| "Embeds are back to the default color.".to_string(), |color| format!("Embeds will now be `#{color:06x}`.")));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("footer")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets a footer shown on this server's embeds that don't have one of their own. Use `off` to remove it."]
+#[usage = "<text|off>"]
+#[example = "Powered by the Example community"]
+#[min_args(1)]
+async fn branding_footer(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let footer = match args.rest().trim() {
+        value if value.eq_ignore_ascii_case("off") => None,
+        value if value.chars().count() > MAX_FOOTER_LENGTH => {
+            msg.reply(ctx, format!("Footers can be at most {MAX_FOOTER_LENGTH} characters long.")).await?;
+            return Ok(());
+        }
+        value => Some(value.to_string())
+    };
+
+    let removed = footer.is_none();
+
+    update_guild_settings(ctx, msg.guild_id.unwrap(), |settings| settings.branding.footer = footer).await?;
+
+    if removed {
+        msg.reply(ctx, "Embeds won't have a footer anymore.").await?;
+    } else {
+        let embed = branded_embed(ctx, msg.guild_id).await.description("Embeds will now have this footer.");
+        msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    }
+
+    Ok(())
+}
+
+#[command("thumbnail")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets an image shown in the corner of this server's embeds that don't have one of their own. Use `off` to remove it."]
+#[usage = "<image url|off>"]
+#[example = "https://example.com/logo.png"]
+#[num_args(1)]
+async fn branding_thumbnail(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let thumbnail = match args.rest().trim() {
+        value if value.eq_ignore_ascii_case("off") => None,
+        value => match reqwest::Url::parse(value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Some(url.to_string()),
+            _ => {
+                msg.reply(ctx, "Please give a link to an image, or use `off`.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let removed = thumbnail.is_none();
+
+    update_guild_settings(ctx, msg.guild_id.unwrap(), |settings| settings.branding.thumbnail = thumbnail).await?;
+
+    if removed {
+        msg.reply(ctx, "Embeds won't have a thumbnail anymore.").await?;
+    } else {
+        let embed = branded_embed(ctx, msg.guild_id).await.description("Embeds will now have this thumbnail.");
+        msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    }
+
+    Ok(())
+}
+
+fn settings_components(page: usize) -> Vec<CreateActionRow> {
+    let options = SETTINGS.iter()
+        .map(|(key, hint)| CreateSelectMenuOption::new(*key, *key).description(hint.replace('`', "")))
+        .collect();
+
+    let menu = CreateSelectMenu::new("settings_change", CreateSelectMenuKind::String { options })
+        .placeholder("Change a setting");
+
+    let buttons = vec![
+        CreateButton::new("settings_previous").label("Previous").style(ButtonStyle::Secondary),
+        CreateButton::new("settings_next").label(format!("Next ({}/{PAGES})", page + 1)).style(ButtonStyle::Secondary)
+    ];
+
+    vec![CreateActionRow::Buttons(buttons), CreateActionRow::SelectMenu(menu)]
+}
+
+/// Builds one page of the settings view, reading everything fresh from the database.
+async fn settings_page(ctx: &Context, guild_id: GuildId, page: usize) -> Result<CreateEmbed, CommandError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let guild_settings = get_data::<GuildSettingsContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .footer(CreateEmbedFooter::new(format!("Page {}/{PAGES}", page + 1)));
+
+    // makes sure the row exists, then writes back changes that haven't been yet so the page shows them
+    get_or_create_guild_settings(ctx, guild_id).await?;
+    guild_settings.flush().await?;
+
+    let settings = sqlx::query!("SELECT * FROM guild_settings WHERE guild_id = ?", db_guild_id).fetch_one(&database).await?;
+
+    let channel = |channel_id: Option<i64>| channel_id.map_or_else(|| "Off".to_string(), |channel_id| ChannelId::new(channel_id as u64).mention().to_string());
+    let toggled = |enabled: i64, channel_id: Option<i64>| channel(channel_id.filter(|_| enabled != 0));
+    let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+
+    let embed = match page {
+        0 => embed
+            .title("Settings: General")
+            .field("Prefix", format!("`{}`", settings.prefix), true)
+            .field("Command suggestions", on_off(settings.command_suggestions != 0), true)
+            .field("Language", LANGUAGES.iter().find(|(code, _)| *code == settings.language).map_or(settings.language.as_str(), |(_, name)| name), true)
+            .field("Updates channel", channel(settings.updates_channel_id), true),
+        1 => {
+            let automod_rules = sqlx::query!("SELECT COUNT(*) AS count FROM automod_rules WHERE guild_id = ?", db_guild_id)
+                .fetch_one(&database)
+                .await?
+                .count;
+
+            let antispam = sqlx::query!("SELECT enabled FROM antispam_settings WHERE guild_id = ?", db_guild_id)
+                .fetch_optional(&database)
+                .await?
+                .is_some_and(|row| row.enabled != 0);
+
+            let raid_mode = sqlx::query!("SELECT mode FROM antiraid_settings WHERE guild_id = ?", db_guild_id)
+                .fetch_optional(&database)
+                .await?
+                .map_or_else(|| "off".to_string(), |row| row.mode);
+
+            let mute = match (settings.mute_style.as_str(), settings.mute_role_id) {
+                ("role", Some(role_id)) => RoleId::new(role_id as u64).mention().to_string(),
+                ("role", None) => "Role (none set)".to_string(),
+                _ => "Timeout".to_string()
+            };
+
+            embed
+                .title("Settings: Moderation")
+                .field("Mute", mute, true)
+                .field("Modlog channel", channel(settings.modlog_channel_id), true)
+                .field("Message log", toggled(settings.message_log_enabled, settings.message_log_channel_id), true)
+                .field("Server log", channel(settings.server_log_channel_id), true)
+                .field("Voice log", channel(settings.voice_log_channel_id), true)
+                .field("Automatic decancer", on_off(settings.auto_decancer != 0), true)
+                .field("Automod rules", automod_rules.to_string(), true)
+                .field("Anti-spam", on_off(antispam), true)
+                .field("Raid mode", raid_mode, true)
+        }
+        2 => embed
+            .title("Settings: Members")
+            .field("Welcome channel", toggled(settings.welcome_enabled, settings.welcome_channel_id), true)
+            .field("Welcome message", if settings.welcome_message.is_some() { "Custom" } else { "Default" }, true)
+            .field("Goodbye channel", toggled(settings.goodbye_enabled, settings.goodbye_channel_id), true)
+            .field("Goodbye message", if settings.goodbye_message.is_some() { "Custom" } else { "Default" }, true)
+            .field("Autoroles after screening", on_off(settings.autorole_after_screening != 0), true),
+        _ => embed
+            .title("Settings: Features")
+            .field("Starboard", channel(settings.starboard_channel_id), true)
+            .field("Stars needed", settings.starboard_threshold.to_string(), true)
+            .field("Leveling", on_off(settings.leveling_enabled != 0), true)
+            .field("Ticket support role", settings.ticket_support_role_id.map_or_else(|| "None".to_string(), |role_id| RoleId::new(role_id as u64).mention().to_string()), true)
+            .field("Modmail channel", channel(settings.modmail_channel_id), true)
+    };
+
+    Ok(embed)
+}
+
+/// Changes a setting in the database, or in the settings cache for the ones kept there, which
+/// writes them back on its own. Returns what changed, or why the value was rejected.
+async fn apply_setting(ctx: &Context, guild_id: GuildId, key: &str, value: &str) -> Result<Result<String, String>, CommandError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    // the rest are written straight to the database, so the row has to exist
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    // settings that take a channel, or `off`
+    let channel_id = match key {
+        "modlog" | "messagelog" | "serverlog" | "voicelog" | "updates" | "welcome" | "goodbye" | "starboard" => match value.to_lowercase().as_str() {
+            "off" => None,
+            channel => match parse_channel(channel) {
+                Some(channel_id) if guild_id.to_guild_cached(&ctx.cache).is_some_and(|guild| guild.channels.contains_key(&channel_id)) => Some(channel_id),
+                _ => return Ok(Err("Please mention a channel in this server, or use `off`.".to_string()))
+            }
+        },
+        _ => None
+    };
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+    let enabled = channel_id.is_some();
+
+    let described = |name: &str| match channel_id {
+        Some(channel_id) => format!("{name} set to {}.", channel_id.mention()),
+        None => format!("{name} turned off.")
+    };
+
+    let outcome = match key {
+        "prefix" => {
+            if value.is_empty() || value.contains(char::is_whitespace) {
+                return Ok(Err("Prefixes can't be empty or contain spaces.".to_string()));
+            }
+
+            update_guild_settings(ctx, guild_id, |settings| settings.prefix = value.to_string()).await?;
+
+            format!("Prefix set to `{value}`.")
+        }
+        "suggestions" => {
+            let enabled = match value.to_lowercase().as_str() {
+                "on" => true,
+                "off" => false,
+                _ => return Ok(Err("Please use `on` or `off`.".to_string()))
+            };
+
+            update_guild_settings(ctx, guild_id, |settings| settings.command_suggestions = enabled).await?;
+
+            format!("Command suggestions turned {}.", if enabled { "on" } else { "off" })
+        }
+        "language" => {
+            let Some(language) = find_language(value) else {
+                let languages = LANGUAGES.iter().map(|(code, name)| format!("`{code}` ({name})")).collect::<Vec<_>>().join(", ");
+                return Ok(Err(translate(&guild_language(ctx, Some(guild_id)).await, "settings.language_unknown", &[("language", value), ("languages", &languages)])));
+            };
+
+            update_guild_settings(ctx, guild_id, |settings| settings.language = language.to_string()).await?;
+
+            // confirmed in the new language, so whoever changed it can tell it worked
+            translate(language, "settings.language_set", &[])
+        }
+        "mute" => {
+            let mute_role = if value.eq_ignore_ascii_case("timeout") {
+                None
+            } else {
+                let Some(role_id) = parse_role(value) else {
+                    return Ok(Err("Please use `timeout`, or mention the mute role or give its ID.".to_string()));
+                };
+
+                if let Err(why) = MuteType::Role(role_id).check_role(ctx, guild_id) {
+                    return Ok(Err(why));
+                }
+
+                Some(role_id)
+            };
+
+            let mute_style = if mute_role.is_some() { "role" } else { "timeout" };
+
+            update_guild_settings(ctx, guild_id, |settings| {
+                settings.mute_type = mute_style.to_string();
+                settings.mute_role = mute_role.map_or(0, RoleId::get);
+            }).await?;
+
+            match mute_role {
+                Some(role_id) => format!("Members will now be muted by giving them {}.", role_id.mention()),
+                None => "Members will now be muted with a timeout.".to_string()
+            }
+        }
+        "modlog" => {
+            sqlx::query!("UPDATE guild_settings SET modlog_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            described("Modlog channel")
+        }
+        "messagelog" => {
+            update_guild_settings(ctx, guild_id, |settings| settings.message_log_channel = channel_id.map(ChannelId::get)).await?;
+
+            described("Message log")
+        }
+        "serverlog" => {
+            sqlx::query!("UPDATE guild_settings SET server_log_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            described("Server log")
+        }
+        "voicelog" => {
+            sqlx::query!("UPDATE guild_settings SET voice_log_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            described("Voice log")
+        }
+        "decancer" => {
+            let enabled = match value.to_lowercase().as_str() {
+                "on" => true,
+                "off" => false,
+                _ => return Ok(Err("Please use `on` or `off`.".to_string()))
+            };
+
+            sqlx::query!("UPDATE guild_settings SET auto_decancer = ? WHERE guild_id = ?", enabled, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            format!("Automatic decancering turned {}.", if enabled { "on" } else { "off" })
+        }
+        "updates" => {
+            sqlx::query!("UPDATE guild_settings SET updates_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            described("Updates channel")
+        }
+        "welcome" => {
+            sqlx::query!(
+                "UPDATE guild_settings SET welcome_channel_id = COALESCE(?, welcome_channel_id), welcome_enabled = ? WHERE guild_id = ?",
+                db_channel_id,
+                enabled,
+                db_guild_id
+            ).execute(&database).await?;
+
+            described("Welcome channel")
+        }
+        "goodbye" => {
+            sqlx::query!(
+                "UPDATE guild_settings SET goodbye_channel_id = COALESCE(?, goodbye_channel_id), goodbye_enabled = ? WHERE guild_id = ?",
+                db_channel_id,
+                enabled,
+                db_guild_id
+            ).execute(&database).await?;
+
+            described("Goodbye channel")
+        }
+        "starboard" => {
+            sqlx::query!("UPDATE guild_settings SET starboard_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            described("Starboard")
+        }
+        "stars" => {
+            let Some(threshold) = value.parse::<u32>().ok().filter(|threshold| (1..=MAX_THRESHOLD).contains(threshold)) else {
+                return Ok(Err(format!("Please give a number of stars from 1 to {MAX_THRESHOLD}.")));
+            };
+
+            sqlx::query!("UPDATE guild_settings SET starboard_threshold = ? WHERE guild_id = ?", threshold, db_guild_id)
+                .execute(&database)
+                .await?;
+
+            format!("Messages now need {threshold} stars to make it onto the starboard.")
+        }
+        _ => return Ok(Err(format!("There's no setting called `{key}`.")))
+    };
+
+    Ok(Ok(outcome))
+}
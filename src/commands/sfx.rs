@@ -0,0 +1,273 @@
+use chrono::Utc;
+use serenity::builder::{CreateActionRow, CreateButton, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::application::ButtonStyle;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::playback::{Playback, member_voice_channel, play_in_voice};
+use crate::utilities::sfx::{CLIP_TYPES, SFX_COOLDOWN, SFX_ID_PREFIX, clip_directory, clip_input, count_play, get_clip, start_cooldown};
+
+/// Largest clip that can be uploaded, which keeps clips short.
+const MAX_CLIP_SIZE: u32 = 1024 * 1024;
+
+/// Most clips a guild can have, as many as fit on a panel of buttons.
+const MAX_CLIPS: i32 = 25;
+const MAX_NAME_LENGTH: usize = 32;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Plays a sound effect from this server's soundboard in your voice channel. Without a name, lists the sound effects. Members can play one every 10 seconds."]
+#[usage = "[name]"]
+#[example = "airhorn"]
+#[sub_commands(sfx_play, sfx_add, sfx_remove, sfx_list, sfx_panel)]
+async fn sfx(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    if args.is_empty() {
+        return list_clips(ctx, msg).await;
+    }
+
+    play_clip(ctx, msg, &args.rest().trim().to_lowercase()).await
+}
+
+#[command("play")]
+#[only_in(guilds)]
+#[description = "Plays a sound effect from this server's soundboard in your voice channel."]
+#[usage = "<name>"]
+#[example = "airhorn"]
+#[num_args(1)]
+async fn sfx_play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    play_clip(ctx, msg, &args.rest().trim().to_lowercase()).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Adds an attached MP3, OGG or WAV clip of up to 1 MB to this server's soundboard."]
+#[usage = "<name> (with the clip attached)"]
+#[example = "airhorn"]
+#[num_args(1)]
+async fn sfx_add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().trim().to_lowercase();
+
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || !name.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_') {
+        msg.reply(ctx, format!("Sound effect names can be up to {MAX_NAME_LENGTH} letters, numbers, dashes and underscores.")).await?;
+        return Ok(());
+    }
+
+    let Some(attachment) = msg.attachments.first() else {
+        msg.reply(ctx, "Please attach the clip to add.").await?;
+        return Ok(());
+    };
+
+    let content_type = attachment.content_type.as_deref().unwrap_or_default().split(';').next().unwrap_or_default().trim();
+
+    let Some((_, extension)) = CLIP_TYPES.iter().find(|(kind, _)| *kind == content_type) else {
+        msg.reply(ctx, "Clips have to be MP3, OGG or WAV files.").await?;
+        return Ok(());
+    };
+
+    if attachment.size > MAX_CLIP_SIZE {
+        msg.reply(ctx, format!("Clips can be at most {} KB.", MAX_CLIP_SIZE / 1024)).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let id = guild_id.get() as i64;
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM sfx_clips WHERE guild_id = ?", id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count >= MAX_CLIPS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_CLIPS} sound effects.")).await?;
+        return Ok(());
+    }
+
+    if get_clip(&database, guild_id, &name).await?.is_some() {
+        msg.reply(ctx, format!("There's already a sound effect named `{name}`.")).await?;
+        return Ok(());
+    }
+
+    let data = attachment.download().await?;
+
+    // the size on the attachment is only what Discord reported
+    if data.len() > MAX_CLIP_SIZE as usize {
+        msg.reply(ctx, format!("Clips can be at most {} KB.", MAX_CLIP_SIZE / 1024)).await?;
+        return Ok(());
+    }
+
+    let file_name = format!("{name}.{extension}");
+    let directory = clip_directory(guild_id);
+
+    tokio::fs::create_dir_all(&directory).await?;
+    tokio::fs::write(directory.join(&file_name), &data).await?;
+
+    let (size, uploaded_by, created_at) = (data.len() as i64, msg.author.id.get() as i64, Utc::now().to_rfc3339());
+
+    sqlx::query!(
+        "INSERT INTO sfx_clips (guild_id, name, file_name, size, uploaded_by, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        id,
+        name,
+        file_name,
+        size,
+        uploaded_by,
+        created_at
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Added the sound effect `{name}`.")).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Removes a sound effect from this server's soundboard."]
+#[usage = "<name>"]
+#[example = "airhorn"]
+#[num_args(1)]
+async fn sfx_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.rest().trim().to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let Some(clip) = get_clip(&database, guild_id, &name).await? else {
+        msg.reply(ctx, format!("There is no sound effect named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let id = guild_id.get() as i64;
+
+    sqlx::query!("DELETE FROM sfx_clips WHERE guild_id = ? AND name = ?", id, clip.name)
+        .execute(&database)
+        .await?;
+
+    match tokio::fs::remove_file(clip_directory(guild_id).join(&clip.file_name)).await {
+        Err(why) if why.kind() != std::io::ErrorKind::NotFound => return Err(why.into()),
+        _ => {}
+    }
+
+    msg.reply(ctx, format!("Removed the sound effect `{name}`.")).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's sound effects."]
+#[num_args(0)]
+async fn sfx_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_clips(ctx, msg).await
+}
+
+#[command("panel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts a panel with a button for each sound effect, which members can press to play it in their voice channel."]
+#[num_args(0)]
+async fn sfx_panel(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let clips = sqlx::query!("SELECT name FROM sfx_clips WHERE guild_id = ? ORDER BY name", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    if clips.is_empty() {
+        msg.reply(ctx, "This server doesn't have any sound effects yet, add some with `sfx add`.").await?;
+        return Ok(());
+    }
+
+    let rows = clips.chunks(5)
+        .map(|chunk| {
+            CreateActionRow::Buttons(chunk.iter()
+                .map(|clip| CreateButton::new(format!("{SFX_ID_PREFIX}{}", clip.name)).label(&clip.name).style(ButtonStyle::Secondary))
+                .collect())
+        })
+        .collect();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Soundboard")
+        .description(format!("Join a voice channel and press a button to play a sound effect there. You can play one every {} seconds.", SFX_COOLDOWN.as_secs()));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(rows)).await?;
+
+    Ok(())
+}
+
+async fn play_clip(ctx: &Context, msg: &Message, name: &str) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let Some(clip) = get_clip(&database, guild_id, name).await? else {
+        msg.reply(ctx, format!("There is no sound effect named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let Some(channel_id) = member_voice_channel(ctx, guild_id, msg.author.id) else {
+        msg.reply(ctx, "Join a voice channel first, then I'll play it there.").await?;
+        return Ok(());
+    };
+
+    if let Some(remaining) = start_cooldown(ctx, guild_id, msg.author.id).await? {
+        msg.reply(ctx, format!("You can play another sound effect in {} seconds.", remaining.as_secs() + 1)).await?;
+        return Ok(());
+    }
+
+    let input = match clip_input(guild_id, &clip).await {
+        Ok(input) => input,
+        Err(_) => {
+            msg.reply(ctx, format!("The file for `{name}` is missing, ask a moderator to upload it again.")).await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = play_in_voice(ctx, guild_id, channel_id, input, Playback::Overlap).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    count_play(&database, guild_id, &clip.name).await?;
+
+    msg.react(ctx, '🔊').await?;
+
+    Ok(())
+}
+
+async fn list_clips(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let clips = sqlx::query!("SELECT name, size, uses FROM sfx_clips WHERE guild_id = ? ORDER BY name", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let description = if clips.is_empty() {
+        "This server doesn't have any sound effects yet, add some with `sfx add`.".to_string()
+    } else {
+        clips.iter()
+            .map(|clip| format!("`{}` - {} KB, played {} times", clip.name, clip.size / 1024, clip.uses))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Sound effects ({} of {MAX_CLIPS})", clips.len()))
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use serenity::builder::{CreateCommand, CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage, EditInteractionResponse};
+use serenity::framework::standard::CommandResult;
+use serenity::model::application::{Command, CommandInteraction, CommandOptionType, CommandType};
+use serenity::model::id::UserId;
+use serenity::prelude::Context;
+use tracing::{error, info};
+
+use crate::COMMAND_GROUPS;
+use crate::commands::help::run_help;
+use crate::commands::utilities::{latency_embed, run_prefix};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::command_permissions::user_allowed;
+use crate::utilities::command_rules::command_allowed_in;
+use crate::utilities::cooldowns::{check_user_cooldown, cooldown_remaining};
+use crate::utilities::errors::{error_chain, get_data, user_message};
+use crate::utilities::global_data::{CommandCountsContainer, OwnersContainer};
+use crate::utilities::i18n::{guild_language, translate};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::reports::{REPORT_COMMAND, report_message};
+
+/// The slash commands registered with Discord. Each one shares its logic with the prefix command
+/// of the same name, apart from the message context menu for reporting messages.
+fn slash_commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new("ping").description("Checks Discord's API / message latency."),
+        CreateCommand::new("prefix")
+            .description("Sets the bot's guild prefix or views the current prefix.")
+            .add_option(CreateCommandOption::new(CommandOptionType::String, "prefix", "The new prefix, leave it out to view the current one")),
+        CreateCommand::new("help")
+            .description("Lists the bot's commands or shows help for one of them.")
+            .add_option(CreateCommandOption::new(CommandOptionType::String, "command", "A command to show help for, or words to search for")),
+        CreateCommand::new(REPORT_COMMAND).kind(CommandType::Message).dm_permission(false)
+    ]
+}
+
+/// Registers the slash commands globally, replacing any that are no longer defined.
+pub async fn register_slash_commands(ctx: &Context) {
+    match Command::set_global_commands(&ctx.http, slash_commands()).await {
+        Ok(commands) => info!("Registered {} slash commands", commands.len()),
+        Err(why) => error!("Failed to register slash commands: {why}")
+    }
+}
+
+/// Runs a slash command invoked by a user. Slash commands go through the same command rules, role
+/// permissions and cooldowns as the prefix commands they share their logic with, and their errors
+/// are reported the same way.
+pub async fn run_slash_command(ctx: &Context, command: &CommandInteraction) {
+    let invocation = Invocation::Slash(command);
+    let name = command.data.name.as_str();
+
+    if let Err(why) = slash_allowed(ctx, &invocation, name).await {
+        send_error(ctx, command, why).await;
+        return;
+    }
+
+    let result = match name {
+        "ping" => slash_ping(ctx, command).await,
+        "prefix" => run_prefix(ctx, &invocation, string_option(command, "prefix").unwrap_or_default()).await,
+        "help" => {
+            let is_owner = {
+                let data = ctx.data.read().await;
+                data.get::<OwnersContainer>().is_some_and(|owners: &HashSet<UserId>| owners.contains(&command.user.id))
+            };
+
+            run_help(ctx, &invocation, string_option(command, "command"), is_owner).await
+        }
+        REPORT_COMMAND => report_message(ctx, command).await,
+        name => {
+            error!("Received unknown slash command /{name}");
+            return;
+        }
+    };
+
+    match get_data::<CommandCountsContainer>(ctx).await {
+        Ok(command_counts) => *command_counts.lock().await.entry(name.to_string()).or_default() += 1,
+        Err(why) => error!("Couldn't count a use of slash command /{name}: {why}")
+    }
+
+    if let Err(why) = result {
+        error!("Slash command /{name} returned error for user {}: {}", command.user.id, error_chain(why.as_ref()));
+        let language = guild_language(ctx, command.guild_id).await;
+        send_error(ctx, command, user_message(why.as_ref(), &language)).await;
+    }
+}
+
+/// Applies the checks the `before` hook applies to prefix commands, returning why the command
+/// can't be used if it can't.
+async fn slash_allowed(ctx: &Context, invocation: &Invocation<'_>, name: &str) -> Result<(), String> {
+    let language = guild_language(ctx, invocation.guild_id()).await;
+
+    let root = COMMAND_GROUPS.iter()
+        .flat_map(|group| group.options.commands.iter().map(move |root| (*group, *root)))
+        .find(|(_, root)| root.options.names[0] == name);
+
+    if let (Some((group, root)), Some(guild_id)) = (root, invocation.guild_id()) {
+        if !command_allowed_in(ctx, guild_id, invocation.channel_id(), group, root).await {
+            return Err(translate(&language, "error.not_here", &[("command", name)]));
+        }
+    }
+
+    if !user_allowed(ctx, invocation, name).await {
+        return Err(translate(&language, "error.missing_role", &[("command", name)]));
+    }
+
+    match check_user_cooldown(ctx, invocation.guild_id(), invocation.user_id(), name).await {
+        Some(remaining) => Err(translate(&language, "error.cooldown", &[("command", name), ("remaining", &cooldown_remaining(remaining))])),
+        None => Ok(())
+    }
+}
+
+/// Reports a failed slash command with an error embed only the user sees, as a follow-up when
+/// the command already responded.
+async fn send_error(ctx: &Context, command: &CommandInteraction, description: String) {
+    let language = guild_language(ctx, command.guild_id).await;
+
+    let embed = branded_embed(ctx, command.guild_id).await
+        .title(translate(&language, "error.title", &[]))
+        .description(description);
+
+    let response = CreateInteractionResponseMessage::new().embed(embed.clone()).ephemeral(true);
+
+    if command.create_response(ctx, CreateInteractionResponse::Message(response)).await.is_ok() {
+        return;
+    }
+
+    if let Err(why) = command.create_followup(ctx, CreateInteractionResponseFollowup::new().embed(embed).ephemeral(true)).await {
+        error!("Unable to report an error for slash command /{}: {why}", command.data.name);
+    }
+}
+
+async fn slash_ping(ctx: &Context, command: &CommandInteraction) -> CommandResult {
+    let start = Utc::now();
+    let pinging = CreateInteractionResponseMessage::new().content(":ping_pong: Pinging!");
+    command.create_response(ctx, CreateInteractionResponse::Message(pinging)).await?;
+    let api_response = (Utc::now() - start).num_milliseconds();
+
+    let edit = match latency_embed(ctx, command.guild_id, api_response).await {
+        Ok(embed) => EditInteractionResponse::new().content("").embed(embed),
+        Err(why) => EditInteractionResponse::new().content(why)
+    };
+
+    command.edit_response(ctx, edit).await?;
+
+    Ok(())
+}
+
+fn string_option<'a>(command: &'a CommandInteraction, name: &str) -> Option<&'a str> {
+    command.data.options.iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.value.as_str())
+}
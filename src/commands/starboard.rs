@@ -0,0 +1,87 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::starboard::STAR;
+
+/// Most stars a starboard can require.
+pub const MAX_THRESHOLD: u32 = 100;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the starboard channel, where messages are reposted once they get enough ⭐ reactions. Use `off` to turn the starboard off."]
+#[usage = "[channel|off]"]
+#[example = "#starboard"]
+#[sub_commands(starboard_threshold)]
+#[max_args(1)]
+async fn starboard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        let current = sqlx::query!("SELECT starboard_channel_id, starboard_threshold FROM guild_settings WHERE guild_id = ?", db_guild_id)
+            .fetch_optional(&database)
+            .await?;
+
+        match current.and_then(|row| row.starboard_channel_id.map(|channel_id| (channel_id, row.starboard_threshold))) {
+            Some((channel_id, threshold)) => {
+                msg.reply(ctx, format!("Messages with {threshold} {STAR} are reposted to {}.", ChannelId::new(channel_id as u64).mention())).await?
+            }
+            None => msg.reply(ctx, "This server has no starboard.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET starboard_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Starred messages will now be reposted to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "The starboard is now off.").await?
+    };
+
+    Ok(())
+}
+
+#[command("threshold")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets how many ⭐ reactions a message needs to make it onto the starboard."]
+#[usage = "<stars>"]
+#[example = "5"]
+#[num_args(1)]
+async fn starboard_threshold(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(threshold) = args.single::<u32>().ok().filter(|threshold| (1..=MAX_THRESHOLD).contains(threshold)) else {
+        msg.reply(ctx, format!("Please give a number of stars from 1 to {MAX_THRESHOLD}.")).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!(
+        "UPDATE guild_settings SET starboard_threshold = ? WHERE guild_id = ?",
+        threshold,
+        guild_id
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Messages now need {threshold} {STAR} to make it onto the starboard.")).await?;
+
+    Ok(())
+}
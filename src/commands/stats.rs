@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::application::ButtonStyle;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::{Days, Utc};
+
+use crate::utilities::analytics::{MAX_RANGE_DAYS, USER_STATS_DAYS, WordSource, daily_stats, hourly_totals, set_opt_out, top_channels, top_words, user_activity};
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::charts::{render_bar_chart, render_word_cloud};
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::{DatabaseConnectionContainer, PrivacyOptOutsContainer};
+
+/// Time ranges offered by the range buttons, in days.
+const RANGES: [u64; 3] = [7, 30, MAX_RANGE_DAYS];
+
+/// How long the range buttons keep working after the last use.
+const BUTTON_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's message activity, active members and join/leave trends, with buttons to switch between 7, 30 and 90 days. Hours are in UTC."]
+#[usage = "[days]"]
+#[example = "30"]
+#[max_args(1)]
+async fn serverstats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut days = if args.is_empty() { RANGES[0] } else { args.single::<u64>()? };
+
+    if !RANGES.contains(&days) {
+        msg.reply(ctx, format!("Please pick one of the available ranges: {}.", range_list())).await?;
+        return Ok(());
+    }
+
+    let guild_id = msg.guild_id.unwrap();
+    let (embed, chart) = stats_report(ctx, guild_id, days).await?;
+
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .add_file(chart)
+        .components(range_buttons(days));
+
+    let mut report = msg.channel_id.send_message(ctx, builder).await?;
+
+    while let Some(interaction) = report.await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(BUTTON_TIMEOUT)
+        .await
+    {
+        let Some(selected) = interaction.data.custom_id.strip_prefix("serverstats_").and_then(|days| days.parse().ok()) else {
+            continue;
+        };
+
+        days = selected;
+        let (embed, chart) = stats_report(ctx, guild_id, days).await?;
+
+        let response = CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .files([chart])
+            .components(range_buttons(days));
+
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+    }
+
+    drop(report.edit(ctx, EditMessage::new().components(vec![])).await);
+
+    Ok(())
+}
+
+/// Builds the stats embed and its daily message chart for the last `days` days.
+async fn stats_report(ctx: &Context, guild_id: GuildId, days: u64) -> Result<(CreateEmbed, CreateAttachment), Box<dyn std::error::Error + Send + Sync>> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let stats = daily_stats(&database, guild_id, days).await?;
+    let since = stats.first().map(|stat| stat.day).unwrap_or_default();
+    let channels = top_channels(&database, guild_id, since, 5).await?;
+    let hours = hourly_totals(&database, guild_id, since).await?;
+
+    let messages: i64 = stats.iter().map(|stat| stat.messages).sum();
+    let joins: i64 = stats.iter().map(|stat| stat.joins).sum();
+    let leaves: i64 = stats.iter().map(|stat| stat.leaves).sum();
+    let average_active = stats.iter().map(|stat| stat.active_members).sum::<i64>() / stats.len().max(1) as i64;
+    let peak_active = stats.iter().map(|stat| stat.active_members).max().unwrap_or_default();
+
+    let member_trend = match (stats.iter().find(|stat| stat.member_count > 0), stats.last()) {
+        (Some(first), Some(last)) if last.member_count > 0 => format!("{} → {}", first.member_count, last.member_count),
+        _ => "No data yet".to_string()
+    };
+
+    let top_channels = if channels.is_empty() {
+        "No messages yet".to_string()
+    } else {
+        channels.iter()
+            .map(|(channel_id, count)| format!("{} - {count}", ChannelId::new(*channel_id).mention()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut busiest: Vec<(usize, i64)> = hours.iter().copied().enumerate().filter(|(_, count)| *count > 0).collect();
+    busiest.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let busiest_hours = if busiest.is_empty() {
+        "No messages yet".to_string()
+    } else {
+        busiest.iter()
+            .take(3)
+            .map(|(hour, count)| format!("{hour:02}:00 - {count}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let until = stats.last().map(|stat| stat.day).unwrap_or_default();
+
+    let chart = render_bar_chart(&stats.iter().map(|stat| stat.messages).collect::<Vec<_>>())?;
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .title(format!("Server activity: last {days} days"))
+        .description(format!("Messages per day, {since} to {until}."))
+        .field("Messages", messages.to_string(), true)
+        .field("Active members", format!("{average_active}/day (peak {peak_active})"), true)
+        .field("Members", member_trend, true)
+        .field("Joins", joins.to_string(), true)
+        .field("Leaves", leaves.to_string(), true)
+        .field("Net change", format!("{:+}", joins - leaves), true)
+        .field("Top channels", top_channels, true)
+        .field("Busiest hours (UTC)", busiest_hours, true)
+        .image("attachment://serverstats.png");
+
+    Ok((embed, CreateAttachment::bytes(chart, "serverstats.png")))
+}
+
+fn range_buttons(selected: u64) -> Vec<CreateActionRow> {
+    let buttons = RANGES.iter()
+        .map(|days| {
+            let style = if *days == selected { ButtonStyle::Primary } else { ButtonStyle::Secondary };
+
+            CreateButton::new(format!("serverstats_{days}"))
+                .label(format!("{days} days"))
+                .style(style)
+                .disabled(*days == selected)
+        })
+        .collect();
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+fn range_list() -> String {
+    RANGES.iter().map(u64::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Builds a word cloud from the last 30 days of messages in this server, a channel, or from one member. Members who opted out with `privacy optout` are never included."]
+#[usage = "[user|channel]"]
+#[example = "#general"]
+#[max_args(1)]
+async fn wordcloud(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let (source, title) = if args.is_empty() {
+        (WordSource::Guild, "this server".to_string())
+    } else if let Ok(channel) = args.clone().channel(ctx, msg).await {
+        (WordSource::Channel(channel.id.get()), channel.mention().to_string())
+    } else {
+        let user_id = args.user_id()?;
+
+        if is_opted_out(ctx, user_id).await? {
+            msg.reply(ctx, "That member has opted out of message statistics.").await?;
+            return Ok(());
+        }
+
+        (WordSource::User(user_id.get()), user_id.mention().to_string())
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let since = Utc::now().date_naive() - Days::new(USER_STATS_DAYS);
+    let words = top_words(&database, guild_id, source, since, 60).await?;
+
+    if words.is_empty() {
+        msg.reply(ctx, "There aren't any recent messages to build a word cloud from yet.").await?;
+        return Ok(());
+    }
+
+    let cloud = tokio::task::spawn_blocking(move || render_word_cloud(&words)).await??;
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Word cloud")
+        .description(format!("The most used words from {title} over the last {USER_STATS_DAYS} days."))
+        .image("attachment://wordcloud.png");
+
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .add_file(CreateAttachment::bytes(cloud, "wordcloud.png"));
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows when a member has been sending messages over the last 30 days, by hour of the day (UTC) and by day."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn messagestats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    if is_opted_out(ctx, user_id).await? {
+        msg.reply(ctx, "That member has opted out of message statistics.").await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let since = Utc::now().date_naive() - Days::new(USER_STATS_DAYS - 1);
+    let (hours, days) = user_activity(&database, msg.guild_id.unwrap(), user_id.get(), since).await?;
+
+    let total: i64 = days.iter().map(|(_, messages)| messages).sum();
+
+    if total == 0 {
+        msg.reply(ctx, format!("{} hasn't sent any messages here recently.", user_id.mention())).await?;
+        return Ok(());
+    }
+
+    let busiest_hour = hours.iter().enumerate().max_by_key(|(_, count)| **count).map_or(0, |(hour, _)| hour);
+    let (busiest_day, busiest_day_count) = days.iter().max_by_key(|(_, count)| *count).copied().unwrap_or_default();
+    let active_days = days.iter().filter(|(_, count)| *count > 0).count();
+
+    let daily: Vec<i64> = days.iter().map(|(_, count)| *count).collect();
+    let (hourly_chart, daily_chart) = (render_bar_chart(&hours)?, render_bar_chart(&daily)?);
+
+    let hourly_embed = branded_embed(ctx, msg.guild_id).await
+        .title("Message statistics")
+        .description(format!("{}'s messages over the last {USER_STATS_DAYS} days.", user_id.mention()))
+        .field("Messages", total.to_string(), true)
+        .field("Active days", format!("{active_days}/{}", days.len()), true)
+        .field("Busiest day", format!("{busiest_day} ({busiest_day_count})"), true)
+        .field("By hour (UTC)", format!("00:00 to 23:00, busiest at {busiest_hour:02}:00"), false)
+        .image("attachment://hourly.png");
+
+    let daily_embed = branded_embed(ctx, msg.guild_id).await
+        .field("By day", format!("{since} to today"), false)
+        .image("attachment://daily.png");
+
+    let builder = CreateMessage::new()
+        .embeds(vec![hourly_embed, daily_embed])
+        .add_file(CreateAttachment::bytes(hourly_chart, "hourly.png"))
+        .add_file(CreateAttachment::bytes(daily_chart, "daily.png"));
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows whether your messages are counted in `messagestats` and `wordcloud`. Opting out deletes your existing statistics in every server."]
+#[usage = "[optout|optin]"]
+#[max_args(1)]
+async fn privacy(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let reply = match args.rest().trim().to_lowercase().as_str() {
+        "optout" => {
+            set_opt_out(ctx, msg.author.id.get(), true).await?;
+            "You've opted out. Your message statistics have been deleted and won't be recorded anymore."
+        }
+        "optin" => {
+            set_opt_out(ctx, msg.author.id.get(), false).await?;
+            "You've opted back in. Your messages will count towards statistics from now on."
+        }
+        _ if is_opted_out(ctx, msg.author.id).await? => "You're opted out of message statistics. Use `privacy optin` to opt back in.",
+        _ => "Your messages count towards `messagestats` and `wordcloud`. Use `privacy optout` to opt out and delete your statistics."
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+async fn is_opted_out(ctx: &Context, user_id: UserId) -> Result<bool, BotError> {
+    let opt_outs = get_data::<PrivacyOptOutsContainer>(ctx).await?;
+    let opted_out = opt_outs.read().await.contains(&user_id.get());
+
+    Ok(opted_out)
+}
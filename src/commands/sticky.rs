@@ -0,0 +1,243 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandError, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::{format_duration, parse_duration};
+use crate::utilities::sticky::{Repost, reload_sticky, repost_sticky};
+
+/// Most sticky messages a single guild can have.
+const MAX_STICKIES: i32 = 10;
+
+/// Discord's limit on the length of a message.
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Most messages that can be sent before a sticky message is reposted.
+const MAX_EVERY_MESSAGES: i64 = 100;
+
+/// Shortest time between reposts of a sticky message on a timer, in seconds.
+const MIN_EVERY_SECONDS: i64 = 30;
+
+/// The text channel in this guild named by an argument, or `None` after telling the author.
+async fn sticky_channel(ctx: &Context, msg: &Message, args: &mut Args) -> Result<Option<ChannelId>, CommandError> {
+    let channel = args.channel(ctx, msg).await?;
+
+    if channel.kind != ChannelType::Text {
+        msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+        return Ok(None);
+    }
+
+    Ok(Some(channel.id))
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists this server's sticky messages. A sticky message is kept at the bottom of its channel by reposting it, deleting the previous copy."]
+#[sub_commands(sticky_set, sticky_every, sticky_remove)]
+async fn sticky(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let stickies = sqlx::query!(
+        "SELECT channel_id, content, every_messages, every_seconds FROM sticky_messages WHERE guild_id = ? ORDER BY channel_id",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    if stickies.is_empty() {
+        msg.reply(ctx, "This server has no sticky messages. Add one with `sticky set`.").await?;
+        return Ok(());
+    }
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title("Sticky messages");
+
+    // the cached guild can't be held across awaits
+    {
+        let guild = msg.guild_id.unwrap().to_guild_cached(&ctx.cache);
+
+        for row in stickies {
+            let every = match row.every_seconds {
+                0 => format!("every {} messages", row.every_messages),
+                seconds => format!("every {} messages or {}", row.every_messages, format_duration(chrono::Duration::seconds(seconds)))
+            };
+
+            let preview: String = row.content.chars().take(200).collect();
+
+            let name = guild.as_ref()
+                .and_then(|guild| guild.channels.get(&ChannelId::new(row.channel_id as u64)))
+                .map_or_else(|| row.channel_id.to_string(), |channel| channel.name.clone());
+
+            embed = embed.field(format!("#{name}"), format!("Reposted {every}\n{preview}"), false);
+        }
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Sets a channel's sticky message and posts it. By default it's reposted after every 5 messages, see `sticky every` to change that."]
+#[usage = "<channel> <message>"]
+#[example = "#general Please keep it friendly, and read the rules in #rules!"]
+#[min_args(2)]
+async fn sticky_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = sticky_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let content = args.rest().trim();
+
+    if content.chars().count() > MAX_CONTENT_LENGTH {
+        msg.reply(ctx, format!("Sticky messages can be at most {MAX_CONTENT_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, db_channel_id) = (msg.guild_id.unwrap().get() as i64, channel_id.get() as i64);
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM sticky_messages WHERE guild_id = ? AND channel_id != ?",
+        guild_id,
+        db_channel_id
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_STICKIES {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_STICKIES} sticky messages.")).await?;
+        return Ok(());
+    }
+
+    // replacing a sticky message keeps how often it's reposted
+    sqlx::query!(
+        "INSERT INTO sticky_messages (channel_id, guild_id, content) VALUES (?, ?, ?)
+        ON CONFLICT (channel_id) DO UPDATE SET content = excluded.content",
+        db_channel_id,
+        guild_id,
+        content
+    ).execute(&database).await?;
+
+    reload_sticky(ctx, channel_id).await?;
+    repost_sticky(ctx, channel_id, Repost::Set).await;
+
+    msg.reply(ctx, format!("Set the sticky message of {}.", channel_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("every")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Sets how often a channel's sticky message is reposted: after a number of messages, or once a duration has passed since the last copy and someone has talked. Use `off` to only repost after messages."]
+#[usage = "<channel> <messages|duration|off>"]
+#[example = "#general 10"]
+#[num_args(2)]
+async fn sticky_every(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = sticky_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+
+    let every = args.single::<String>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    // a repost timer of zero is turned off
+    let duration = match every.to_lowercase().as_str() {
+        "off" => Some(chrono::Duration::zero()),
+        every => parse_duration(every)
+    };
+
+    let (updated, reply) = match (every.parse::<i64>(), duration) {
+        (Ok(messages), _) if (1..=MAX_EVERY_MESSAGES).contains(&messages) => {
+            let updated = sqlx::query!("UPDATE sticky_messages SET every_messages = ? WHERE channel_id = ?", messages, db_channel_id)
+                .execute(&database)
+                .await?
+                .rows_affected();
+
+            (updated, format!("The sticky message of {} will now be reposted after every {messages} messages.", channel_id.mention()))
+        }
+        (Ok(_), _) => {
+            msg.reply(ctx, format!("Sticky messages can be reposted after between 1 and {MAX_EVERY_MESSAGES} messages.")).await?;
+            return Ok(());
+        }
+        (_, Some(duration)) if duration.num_seconds() == 0 || duration.num_seconds() >= MIN_EVERY_SECONDS => {
+            let seconds = duration.num_seconds();
+
+            let updated = sqlx::query!("UPDATE sticky_messages SET every_seconds = ? WHERE channel_id = ?", seconds, db_channel_id)
+                .execute(&database)
+                .await?
+                .rows_affected();
+
+            let reply = match seconds {
+                0 => format!("The sticky message of {} will now only be reposted after messages.", channel_id.mention()),
+                _ => format!("The sticky message of {} will now also be reposted {} after the last copy.", channel_id.mention(), format_duration(duration))
+            };
+
+            (updated, reply)
+        }
+        (_, Some(_)) => {
+            msg.reply(ctx, format!("Sticky messages can be reposted at most every {MIN_EVERY_SECONDS} seconds.")).await?;
+            return Ok(());
+        }
+        (_, None) => {
+            msg.reply(ctx, "Please give a number of messages, a duration such as `5m`, or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    if updated == 0 {
+        msg.reply(ctx, format!("{} has no sticky message.", channel_id.mention())).await?;
+        return Ok(());
+    }
+
+    reload_sticky(ctx, channel_id).await?;
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Removes a channel's sticky message, deleting its latest copy."]
+#[usage = "<channel>"]
+#[num_args(1)]
+async fn sticky_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = args.channel_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM sticky_messages WHERE channel_id = ? RETURNING message_id", db_channel_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let Some(removed) = removed else {
+        msg.reply(ctx, format!("{} has no sticky message.", channel_id.mention())).await?;
+        return Ok(());
+    };
+
+    reload_sticky(ctx, channel_id).await?;
+
+    // the copy may have been deleted by hand already
+    if let Some(message_id) = removed.message_id {
+        let _ = channel_id.delete_message(ctx, MessageId::new(message_id as u64)).await;
+    }
+
+    msg.reply(ctx, format!("Removed the sticky message of {}.", channel_id.mention())).await?;
+
+    Ok(())
+}
@@ -0,0 +1,399 @@
+use serenity::builder::{CreateAllowedMentions, CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::dispatch::resolve_command;
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{Paginator, embed_pages, paginate_lines};
+
+/// Most tags a single guild can store.
+const MAX_TAGS: i32 = 200;
+
+/// Tags are sent as a single message, or a single embed description.
+const MAX_TAG_LENGTH: usize = 2000;
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Tags listed per page by `tag list`.
+const TAGS_PER_PAGE: usize = 50;
+
+/// Most results `tag search` shows.
+const MAX_SEARCH_RESULTS: i64 = 20;
+
+/// Names taken by the `tag` sub-commands, which would make the tags unreachable through `tag <name>`.
+const RESERVED_NAMES: [&str; 7] = ["create", "edit", "delete", "info", "list", "search", "embed"];
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows a tag, a canned response set up by this server's moderators. Without a name, lists the tags. Tags can also be shown directly by name."]
+#[usage = "[name]"]
+#[example = "rules"]
+#[sub_commands(tag_create, tag_edit, tag_delete, tag_info, tag_list, tag_search, tag_embed)]
+async fn tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if args.is_empty() {
+        return list_tags(ctx, msg).await;
+    }
+
+    let name = args.single::<String>()?.to_lowercase();
+
+    if !run_guild_tag(ctx, msg, &name).await? {
+        msg.reply(ctx, format!("There is no tag named `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("create")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Creates a tag."]
+#[usage = "<name> <content>"]
+#[example = "rules Please read #rules before posting!"]
+#[min_args(2)]
+async fn tag_create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let content = args.rest().trim().to_string();
+
+    if let Err(why) = check_tag(&name, &content) {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM tags WHERE guild_id = ?", guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count >= MAX_TAGS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_TAGS} tags.")).await?;
+        return Ok(());
+    }
+
+    let created_by = msg.author.id.get() as i64;
+    let created_at = Utc::now().to_rfc3339();
+
+    let created = sqlx::query!(
+        "INSERT INTO tags (guild_id, name, content, created_by, created_at) VALUES (?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+        guild_id,
+        name,
+        content,
+        created_by,
+        created_at
+    ).execute(&database).await?.rows_affected();
+
+    if created == 0 {
+        msg.reply(ctx, format!("There already is a tag named `{name}`, use `tag edit` to change it.")).await?;
+    } else {
+        msg.reply(ctx, format!("Created tag `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("edit")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Changes a tag's content."]
+#[usage = "<name> <content>"]
+#[example = "rules Please read #rules and #faq before posting!"]
+#[min_args(2)]
+async fn tag_edit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+    let content = args.rest().trim().to_string();
+
+    if content.chars().count() > MAX_TAG_LENGTH {
+        msg.reply(ctx, format!("Tags can be at most {MAX_TAG_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let updated_at = Utc::now().to_rfc3339();
+
+    let updated = sqlx::query!(
+        "UPDATE tags SET content = ?, updated_at = ? WHERE guild_id = ? AND name = ?",
+        content,
+        updated_at,
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    if updated == 0 {
+        msg.reply(ctx, format!("There is no tag named `{name}`.")).await?;
+    } else {
+        msg.reply(ctx, format!("Updated tag `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Deletes a tag."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn tag_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM tags WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no tag named `{name}`.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted tag `{name}`.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command("embed")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Sets whether a tag is shown as an embed instead of plain text."]
+#[usage = "<name> <on|off>"]
+#[example = "rules on"]
+#[num_args(2)]
+async fn tag_embed(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let embed = match args.single::<String>()?.to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(ctx, "Please use `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let updated = sqlx::query!(
+        "UPDATE tags SET embed = ? WHERE guild_id = ? AND name = ?",
+        embed,
+        guild_id,
+        name
+    ).execute(&database).await?.rows_affected();
+
+    match (updated, embed) {
+        (0, _) => msg.reply(ctx, format!("There is no tag named `{name}`.")).await?,
+        (_, true) => msg.reply(ctx, format!("Tag `{name}` will now be shown as an embed.")).await?,
+        (_, false) => msg.reply(ctx, format!("Tag `{name}` will now be shown as plain text.")).await?
+    };
+
+    Ok(())
+}
+
+#[command("info")]
+#[only_in(guilds)]
+#[description = "Shows who made a tag, when, and how often it's been used."]
+#[usage = "<name>"]
+#[num_args(1)]
+async fn tag_info(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single::<String>()?.to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let Some(tag) = sqlx::query!(
+        "SELECT embed, created_by, created_at, updated_at, uses FROM tags WHERE guild_id = ? AND name = ?",
+        guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, format!("There is no tag named `{name}`.")).await?;
+        return Ok(());
+    };
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Tag: {name}"))
+        .field("Created by", UserId::new(tag.created_by as u64).mention().to_string(), true)
+        .field("Created", discord_timestamp(&tag.created_at), true)
+        .field("Uses", tag.uses.to_string(), true)
+        .field("Shown as", if tag.embed != 0 { "Embed" } else { "Plain text" }, true);
+
+    if let Some(updated_at) = &tag.updated_at {
+        embed = embed.field("Last edited", discord_timestamp(updated_at), true);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "Lists this server's tags, most used first."]
+#[num_args(0)]
+async fn tag_list(ctx: &Context, msg: &Message) -> CommandResult {
+    list_tags(ctx, msg).await
+}
+
+#[command("search")]
+#[only_in(guilds)]
+#[description = "Finds tags whose name or content contains some text."]
+#[usage = "<text>"]
+#[example = "rules"]
+#[min_args(1)]
+async fn tag_search(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim().to_lowercase();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    // % and _ are wildcards in LIKE, so they're escaped to be searched for literally
+    let pattern = format!("%{}%", query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+
+    let tags = sqlx::query!(
+        r"SELECT name, uses FROM tags
+        WHERE guild_id = ? AND (name LIKE ? ESCAPE '\' OR content LIKE ? ESCAPE '\')
+        ORDER BY uses DESC, name LIMIT ?",
+        guild_id,
+        pattern,
+        pattern,
+        MAX_SEARCH_RESULTS
+    ).fetch_all(&database).await?;
+
+    if tags.is_empty() {
+        msg.reply(ctx, format!("No tags match `{query}`.")).await?;
+        return Ok(());
+    }
+
+    let description = tags.iter()
+        .map(|row| format!("`{}` - used {} time(s)", row.name, row.uses))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Tags matching \"{query}\""))
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Whether the guild has a tag by this name.
+pub async fn tag_exists(database: &SqlitePool, guild_id: GuildId, name: &str) -> Result<bool, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let exists = sqlx::query!("SELECT name FROM tags WHERE guild_id = ? AND name = ?", guild_id, name)
+        .fetch_optional(database)
+        .await?
+        .is_some();
+
+    Ok(exists)
+}
+
+/// Shows the guild's tag with the given name, if there is one. Returns whether a tag was found,
+/// so callers can fall back to other handling.
+pub async fn run_guild_tag(ctx: &Context, msg: &Message, name: &str) -> Result<bool, BotError> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(false);
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let name = name.to_lowercase();
+
+    let Some(tag) = sqlx::query!(
+        "UPDATE tags SET uses = uses + 1 WHERE guild_id = ? AND name = ? RETURNING content, embed",
+        db_guild_id,
+        name
+    ).fetch_optional(&database).await? else {
+        return Ok(false);
+    };
+
+    // tags can't ping everyone or roles, only the people they mention
+    let builder = CreateMessage::new().allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+    let builder = if tag.embed != 0 {
+        builder.embed(branded_embed(ctx, msg.guild_id).await.description(tag.content))
+    } else {
+        builder.content(tag.content)
+    };
+
+    drop(msg.channel_id.send_message(ctx, builder).await);
+
+    Ok(true)
+}
+
+async fn list_tags(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let tags = sqlx::query!(
+        "SELECT name, uses FROM tags WHERE guild_id = ? ORDER BY uses DESC, name",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let names: Vec<String> = tags.iter()
+        .map(|row| format!("`{}` ({})", row.name, row.uses))
+        .collect();
+
+    let descriptions = if names.is_empty() {
+        vec!["This server has no tags yet. Use `tag create <name> <content>` to make one.".to_string()]
+    } else {
+        paginate_lines(&names, TAGS_PER_PAGE, ", ")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Tags")
+        .footer(CreateEmbedFooter::new("Uses are shown in brackets."));
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+/// Checks that a new tag has a usable name and isn't too long.
+fn check_tag(name: &str, content: &str) -> Result<(), String> {
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Tag names can be at most {MAX_NAME_LENGTH} characters long."));
+    }
+
+    if RESERVED_NAMES.contains(&name) {
+        return Err(format!("`{name}` can't be a tag name, it's already a `tag` sub-command."));
+    }
+
+    if resolve_command(name).is_some() {
+        return Err(format!("`{name}` is already the name of a built-in command."));
+    }
+
+    if content.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!("Tags can be at most {MAX_TAG_LENGTH} characters long."));
+    }
+
+    Ok(())
+}
+
+/// Formats an RFC 3339 timestamp as a Discord timestamp, shown in each reader's own timezone.
+fn discord_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map_or_else(|_| timestamp.to_string(), |time| format!("<t:{}:R>", time.timestamp()))
+}
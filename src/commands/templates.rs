@@ -0,0 +1,71 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::templates::{message_context, render_template};
+
+#[command]
+#[description = "Explains the template syntax used by welcome messages, level-up messages and announcements."]
+#[sub_commands(template_test)]
+async fn template(ctx: &Context, msg: &Message) -> CommandResult {
+    let context = message_context(ctx, msg).await;
+    let (values, flags) = context.describe();
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Templates")
+        .description("Welcome messages, level-up messages and announcements are written as templates.\n\n\
+            `{user.mention}` - replaced with a value, unknown placeholders are left as-is\n\
+            `{if booster}...{else}...{endif}` - only shown when a flag is set, `{if !booster}` for the opposite\n\
+            `{random:Hi|Hello|Hey}` - picks one of the choices at random\n\
+            `{{` and `}}` - literal braces\n\n\
+            Try one out with `template test <template>`.")
+        .field("Placeholders", values.iter().map(|value| format!("`{{{value}}}`")).collect::<Vec<_>>().join(" "), false)
+        .field("Flags", flags_or_none(&flags), false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("test")]
+#[description = "Renders a template as if it were sent for you in this channel."]
+#[usage = "<template>"]
+#[example = "{random:Welcome|Hey} {user.mention}!{if booster} Thanks for boosting {server}!{endif}"]
+#[min_args(1)]
+async fn template_test(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let context = message_context(ctx, msg).await;
+
+    let embed = match render_template(args.rest(), &context) {
+        Ok(rendered) => branded_embed(ctx, msg.guild_id).await
+            .title("Template preview")
+            .description(rendered),
+        Err(why) => {
+            let (_, flags) = context.describe();
+
+            branded_embed(ctx, msg.guild_id).await
+                .title("Template error")
+                .description(format!("The template couldn't be rendered: {why}."))
+                .field("Your flags", flags_or_none(&flags), false)
+        }
+    };
+
+    // previews never ping anyone
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
+
+fn flags_or_none(flags: &[&str]) -> String {
+    if flags.is_empty() {
+        "none".to_string()
+    } else {
+        flags.iter().map(|flag| format!("`{flag}`")).collect::<Vec<_>>().join(" ")
+    }
+}
@@ -0,0 +1,219 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::tickets::{close_ticket, ticket_panel_components};
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's ticket settings. Members open tickets with the button on a panel posted by `ticket setup`, which gives them a private channel with the support role."]
+#[sub_commands(ticket_setup, ticket_role, ticket_category, ticket_log, ticket_close)]
+async fn ticket(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, msg.guild_id.unwrap()).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT ticket_support_role_id, ticket_category_id, ticket_log_channel_id FROM guild_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_one(&database).await?;
+
+    let open = sqlx::query!("SELECT COUNT(*) AS count FROM tickets WHERE guild_id = ? AND closed_at IS NULL", guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    let support_role = settings.ticket_support_role_id.map_or_else(|| "Not set up".to_string(), |role_id| RoleId::new(role_id as u64).mention().to_string());
+    let category = settings.ticket_category_id.map_or_else(|| "None".to_string(), |channel_id| ChannelId::new(channel_id as u64).mention().to_string());
+    let log_channel = settings.ticket_log_channel_id.map_or_else(|| "Off".to_string(), |channel_id| ChannelId::new(channel_id as u64).mention().to_string());
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Tickets")
+        .field("Support role", support_role, true)
+        .field("Category", category, true)
+        .field("Transcripts", log_channel, true)
+        .field("Open tickets", open.to_string(), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("setup")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Posts a panel with an \"Open Ticket\" button in a channel and sets the role that can see and answer tickets. Any text after the role becomes the panel's message."]
+#[usage = "<channel> <support role> [message]"]
+#[example = "#support @Staff Need a hand? Open a ticket and we'll get back to you."]
+#[min_args(2)]
+async fn ticket_setup(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = args.channel(ctx, msg).await?.id;
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let description = match args.rest().trim() {
+        "" => "Click the button below to open a private ticket with the support team.",
+        description => description
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Support")
+        .description(description);
+
+    channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(ticket_panel_components())).await?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET ticket_support_role_id = ? WHERE guild_id = ?", db_role_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, format!("Posted a ticket panel in {}. Tickets will be handled by {}.", channel_id.mention(), role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("role")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Changes the role that can see and answer new tickets."]
+#[usage = "<role>"]
+#[num_args(1)]
+async fn ticket_role(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let role_id = args.role(ctx, msg).await?.id;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_role_id) = (guild_id.get() as i64, role_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET ticket_support_role_id = ? WHERE guild_id = ?", db_role_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, format!("New tickets will be handled by {}.", role_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("category")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the category new ticket channels are created in, or `none` to create them outside any category."]
+#[usage = "<category|none>"]
+#[num_args(1)]
+async fn ticket_category(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let category_id = if args.keyword("none") {
+        None
+    } else {
+        let category = args.channel(ctx, msg).await?;
+
+        if category.kind != ChannelType::Category {
+            msg.reply(ctx, format!("`{}` isn't a category.", category.name)).await?;
+            return Ok(());
+        }
+
+        Some(category.id)
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let db_category_id = category_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET ticket_category_id = ? WHERE guild_id = ?", db_category_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    match category_id {
+        Some(category_id) => msg.reply(ctx, format!("New tickets will be created in {}.", category_id.mention())).await?,
+        None => msg.reply(ctx, "New tickets will be created outside any category.").await?
+    };
+
+    Ok(())
+}
+
+#[command("log")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channel transcripts of closed tickets are posted to, or `off` to only archive them."]
+#[usage = "<channel|off>"]
+#[example = "#ticket-logs"]
+#[num_args(1)]
+async fn ticket_log(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!("UPDATE guild_settings SET ticket_log_channel_id = ? WHERE guild_id = ?", db_channel_id, db_guild_id)
+        .execute(&database)
+        .await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Ticket transcripts will now be posted in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Transcripts of closed tickets will no longer be posted.").await?
+    };
+
+    Ok(())
+}
+
+#[command("close")]
+#[only_in(guilds)]
+#[description = "Closes the ticket this is used in, archiving its channel so only staff can still see it. Tickets can be closed by whoever opened them, the support role, or anyone who can manage channels."]
+#[num_args(0)]
+async fn ticket_close(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let channel_id = msg.channel_id.get() as i64;
+
+    let Some(ticket) = sqlx::query!(
+        "SELECT tickets.user_id, guild_settings.ticket_support_role_id FROM tickets
+        LEFT JOIN guild_settings ON guild_settings.guild_id = tickets.guild_id
+        WHERE tickets.channel_id = ? AND tickets.closed_at IS NULL",
+        channel_id
+    ).fetch_optional(&database).await? else {
+        msg.reply(ctx, "This isn't an open ticket.").await?;
+        return Ok(());
+    };
+
+    let is_support = ticket.ticket_support_role_id
+        .zip(msg.member.as_ref())
+        .is_some_and(|(role_id, member)| member.roles.contains(&RoleId::new(role_id as u64)));
+
+    let channel = msg.channel_id.to_channel(ctx).await.ok().and_then(Channel::guild);
+
+    let can_manage = channel.zip(msg.guild(&ctx.cache)).is_some_and(|(channel, guild)| {
+        guild.members.get(&msg.author.id)
+            .is_some_and(|member| guild.user_permissions_in(&channel, member).manage_channels())
+    });
+
+    if ticket.user_id != msg.author.id.get() as i64 && !is_support && !can_manage {
+        msg.reply(ctx, "Only whoever opened this ticket, the support role, or someone who can manage channels can close it.").await?;
+        return Ok(());
+    }
+
+    if !close_ticket(ctx, &database, msg.channel_id, msg.author.id).await? {
+        msg.reply(ctx, "This ticket is already closed.").await?;
+    }
+
+    Ok(())
+}
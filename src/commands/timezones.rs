@@ -0,0 +1,165 @@
+use chrono::Utc;
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::parsing::format_utc_offset;
+use crate::utilities::timezones::{AMBIGUOUS_ABBREVIATIONS, Timezone, format_local_time, guild_timezone, parse_timezone, user_timezone};
+
+const TIMEZONE_HELP: &str = "Please give a timezone name such as `Europe/Berlin` or `America/New_York`, an offset from UTC such as `UTC+2` or `UTC-5:30`, or an abbreviation such as `CET` or `EST`.";
+
+/// Explains why a timezone wasn't understood.
+fn timezone_error(arg: &str) -> String {
+    let arg = arg.trim().to_uppercase();
+
+    if AMBIGUOUS_ABBREVIATIONS.contains(&arg.as_str()) {
+        format!("`{arg}` is used for more than one timezone. Please give the timezone's name instead, such as `Asia/Kolkata` or `Europe/Dublin`.")
+    } else {
+        TIMEZONE_HELP.to_string()
+    }
+}
+
+/// Describes a timezone for replies, with its current offset if it's a named zone.
+fn describe_timezone(timezone: &Timezone) -> String {
+    match timezone.zone_name() {
+        Some(name) => format!("{name} ({})", format_utc_offset(timezone.current_offset())),
+        None => timezone.to_string()
+    }
+}
+
+#[command]
+#[description = "Shows your timezone and the server's. Times you give reminders are read in yours, or the server's if you haven't set one, and the server's is used for birthdays and scheduled messages."]
+#[sub_commands(timezone_set, timezone_remove, timezone_server)]
+#[num_args(0)]
+async fn timezone(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let mut lines = vec![match user_timezone(&database, msg.author.id).await? {
+        Some(timezone) => format!("Your timezone is {}.", describe_timezone(&timezone)),
+        None => "You haven't set a timezone, use `timezone set`.".to_string()
+    }];
+
+    if let Some(guild_id) = msg.guild_id {
+        lines.push(match guild_timezone(&database, guild_id).await? {
+            Some(timezone) => format!("This server's timezone is {}.", describe_timezone(&timezone)),
+            None => "This server's timezone is UTC.".to_string()
+        });
+    }
+
+    msg.reply(ctx, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[description = "Sets your timezone, as a name from the timezone database, an offset from UTC or a common abbreviation. Named timezones follow summer time on their own, offsets and abbreviations need changing when your clocks do."]
+#[usage = "<timezone>"]
+#[example = "Europe/Berlin"]
+#[num_args(1)]
+async fn timezone_set(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(timezone) = parse_timezone(args.rest()) else {
+        msg.reply(ctx, timezone_error(args.rest())).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (user_id, seconds, name) = (msg.author.id.get() as i64, timezone.current_offset().local_minus_utc(), timezone.zone_name());
+
+    sqlx::query!(
+        "INSERT INTO user_timezones (user_id, utc_offset, timezone) VALUES (?, ?, ?)
+        ON CONFLICT (user_id) DO UPDATE SET utc_offset = excluded.utc_offset, timezone = excluded.timezone",
+        user_id,
+        seconds,
+        name
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("Your timezone is set to {}, where it's {}.", describe_timezone(&timezone), format_local_time(Utc::now(), &timezone))).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[description = "Removes your timezone, so times you give are read in the server's timezone."]
+#[num_args(0)]
+async fn timezone_remove(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let user_id = msg.author.id.get() as i64;
+
+    sqlx::query!("DELETE FROM user_timezones WHERE user_id = ?", user_id)
+        .execute(&database)
+        .await?;
+
+    msg.reply(ctx, "Your timezone was removed.").await?;
+
+    Ok(())
+}
+
+#[command("server")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets this server's timezone, which birthdays and scheduled messages follow and members without their own timezone give times in."]
+#[usage = "<timezone>"]
+#[example = "Europe/Berlin"]
+#[num_args(1)]
+async fn timezone_server(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(timezone) = parse_timezone(args.rest()) else {
+        msg.reply(ctx, timezone_error(args.rest())).await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, seconds, name) = (msg.guild_id.unwrap().get() as i64, timezone.current_offset().local_minus_utc(), timezone.zone_name());
+
+    sqlx::query!(
+        "INSERT INTO guild_timezones (guild_id, utc_offset, timezone) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET utc_offset = excluded.utc_offset, timezone = excluded.timezone",
+        guild_id,
+        seconds,
+        name
+    ).execute(&database).await?;
+
+    // scheduled messages keep the timezone they were made in, so only new ones follow the change
+    msg.reply(ctx, format!(
+        "This server's timezone is set to {}. Scheduled messages that already exist keep the timezone they were made in.",
+        describe_timezone(&timezone)
+    )).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the time for a member who set their timezone, or in a timezone. Shows yours without either."]
+#[usage = "[member|timezone]"]
+#[example = "@Kanzoey"]
+#[max_args(1)]
+async fn time(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let now = Utc::now();
+
+    if let Some(timezone) = args.current().and_then(parse_timezone) {
+        msg.reply(ctx, format!("It's {} in {}.", format_local_time(now, &timezone), describe_timezone(&timezone))).await?;
+        return Ok(());
+    }
+
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let reply = match (user_timezone(&database, user_id).await?, user_id == msg.author.id) {
+        (Some(timezone), true) => format!("It's {} for you ({}).", format_local_time(now, &timezone), describe_timezone(&timezone)),
+        (Some(timezone), false) => format!("It's {} for {} ({}).", format_local_time(now, &timezone), user_id.mention(), describe_timezone(&timezone)),
+        (None, true) => "You haven't set a timezone yet, use `timezone set`.".to_string(),
+        (None, false) => format!("{} hasn't set a timezone.", user_id.mention())
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().content(reply).reference_message(msg).allowed_mentions(CreateAllowedMentions::new())).await?;
+
+    Ok(())
+}
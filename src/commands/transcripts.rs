@@ -0,0 +1,103 @@
+use serenity::builder::{CreateAttachment, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::transcripts::{MAX_TRANSCRIPT_MESSAGES, TranscriptFormat, TranscriptRange, fetch_messages, render_transcript};
+
+/// Messages exported when no count is given.
+const DEFAULT_COUNT: usize = 100;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[description = "Exports a channel's messages to a file, with their authors, times, attachment links and a summary of their embeds. Exports the latest 100 messages by default, or a number of them, or every message after a message (given by ID or link), up to 1000. Files are Markdown unless `html` is given."]
+#[usage = "<channel> [count|after <message>] [markdown|html]"]
+#[example = "#general after 1196543210987654321 html"]
+#[min_args(1)]
+#[max_args(4)]
+async fn transcript(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let mut range = TranscriptRange::Latest(DEFAULT_COUNT);
+    let mut format = TranscriptFormat::Markdown;
+
+    while let Ok(arg) = args.single::<String>() {
+        if let Some(parsed) = TranscriptFormat::parse(&arg) {
+            format = parsed;
+        } else if arg.eq_ignore_ascii_case("after") {
+            let after = args.message_link(msg)?;
+
+            // bare message IDs come back as links to this channel, whichever channel is exported
+            if after.guild_id != Some(guild_id) || (after.channel_id != channel_id && after.channel_id != msg.channel_id) {
+                msg.reply(ctx, format!("That message isn't in {}.", channel_id.mention())).await?;
+                return Ok(());
+            }
+
+            range = TranscriptRange::After(after.message_id);
+        } else {
+            let Some(count) = arg.parse::<usize>().ok().filter(|count| (1..=MAX_TRANSCRIPT_MESSAGES).contains(count)) else {
+                msg.reply(ctx, format!("Please give a number of messages from 1 to {MAX_TRANSCRIPT_MESSAGES}, or `after <message>`.")).await?;
+                return Ok(());
+            };
+
+            range = TranscriptRange::Latest(count);
+        }
+    }
+
+    let author = guild_id.member(ctx, msg.author.id).await?;
+    let bot_id = ctx.cache.current_user().id;
+
+    // moderators can only export channels they can read themselves
+    let checked = match guild_id.to_guild_cached(&ctx.cache) {
+        None => Err("This server isn't cached yet, please try again in a moment."),
+        Some(guild) => match guild.channels.get(&channel_id) {
+            None => Err("Please mention a channel in this server."),
+            Some(channel) => {
+                let can_read = |member: &Member| {
+                    let permissions = guild.user_permissions_in(channel, member);
+                    permissions.view_channel() && permissions.read_message_history()
+                };
+
+                if !can_read(&author) {
+                    Err("You can't read that channel.")
+                } else if !guild.members.get(&bot_id).is_some_and(can_read) {
+                    Err("I need to be able to read that channel's message history.")
+                } else {
+                    Ok(channel.name.clone())
+                }
+            }
+        }
+    };
+
+    let name = match checked {
+        Ok(name) => name,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    };
+
+    let messages = fetch_messages(ctx, channel_id, range).await?;
+
+    if messages.is_empty() {
+        msg.reply(ctx, "There are no messages to export.").await?;
+        return Ok(());
+    }
+
+    let transcript = render_transcript(&messages, format, &format!("#{name}"));
+    let file = CreateAttachment::bytes(transcript.into_bytes(), format!("transcript-{name}.{}", format.extension()));
+
+    let builder = CreateMessage::new()
+        .content(format!("Exported {} message{} from {}.", messages.len(), if messages.len() == 1 { "" } else { "s" }, channel_id.mention()))
+        .add_file(file)
+        .reference_message(msg);
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::application::ButtonStyle;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tokio::time::Instant;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::games::{claim_channel, release_channel};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::trivia::{Question, TriviaAnswer, pick_questions, record_answers};
+
+const DEFAULT_ROUNDS: usize = 5;
+const MAX_ROUNDS: usize = 15;
+
+/// How long each question stays open for answers.
+const ROUND_TIME: Duration = Duration::from_secs(20);
+
+/// The pause after the answer is revealed before the next question.
+const ROUND_BREAK: Duration = Duration::from_secs(4);
+
+/// Games end early after this many questions in a row nobody answered.
+const MAX_IDLE_ROUNDS: usize = 2;
+
+/// Points for a right answer, and the bonus for whoever answered right first.
+const CORRECT_POINTS: i64 = 10;
+const FIRST_BONUS: i64 = 5;
+
+const LEADERBOARD_SIZE: i64 = 100;
+const ANSWER_LABELS: [&str; 4] = ["A", "B", "C", "D"];
+
+#[command]
+#[only_in(guilds)]
+#[description = "Starts a game of trivia in this channel. Anyone can answer each question with the buttons, and right answers earn points, with a bonus for the first. Points add up across games, see `leaderboard trivia`."]
+#[usage = "[rounds]"]
+#[example = "10"]
+#[max_args(1)]
+async fn trivia(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let rounds = if args.is_empty() {
+        DEFAULT_ROUNDS
+    } else {
+        match args.single::<usize>() {
+            Ok(rounds) if (1..=MAX_ROUNDS).contains(&rounds) => rounds,
+            _ => {
+                msg.reply(ctx, format!("Games can have from 1 to {MAX_ROUNDS} questions.")).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if let Some(running) = claim_channel(ctx, msg.channel_id, "trivia").await? {
+        msg.reply(ctx, format!("There's already a game of {running} running in this channel.")).await?;
+        return Ok(());
+    }
+
+    let result = run_game(ctx, msg, &database, rounds).await;
+
+    release_channel(ctx, msg.channel_id).await?;
+
+    result
+}
+
+#[command("trivia")]
+#[only_in(guilds)]
+#[description = "Shows the members with the most trivia points in this server."]
+#[num_args(0)]
+async fn leaderboard_trivia(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let top = sqlx::query!(
+        "SELECT user_id, points, correct, answered FROM trivia_scores WHERE guild_id = ? AND points > 0 ORDER BY points DESC LIMIT ?",
+        guild_id,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = top.iter()
+        .enumerate()
+        .map(|(index, row)| format!("**{}.** {} - {} points ({} of {} right)", index + 1, UserId::new(row.user_id as u64).mention(), row.points, row.correct, row.answered))
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody has scored any trivia points here yet.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Trivia leaderboard");
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+async fn run_game(ctx: &Context, msg: &Message, database: &sqlx::SqlitePool, rounds: usize) -> CommandResult {
+    let questions = pick_questions(rounds);
+    let mut scores: HashMap<UserId, i64> = HashMap::new();
+    let mut idle_rounds = 0;
+
+    for (round, question) in questions.iter().enumerate() {
+        let answers = play_round(ctx, msg, question, round, questions.len()).await?;
+
+        if answers.is_empty() {
+            idle_rounds += 1;
+        } else {
+            idle_rounds = 0;
+            record_answers(database, msg.guild_id.unwrap(), &answers).await?;
+        }
+
+        for answer in answers {
+            *scores.entry(answer.user_id).or_default() += answer.points;
+        }
+
+        if idle_rounds >= MAX_IDLE_ROUNDS {
+            msg.channel_id.say(ctx, "Nobody seems to be playing, so the game is over.").await?;
+            break;
+        }
+
+        if round + 1 < questions.len() {
+            tokio::time::sleep(ROUND_BREAK).await;
+        }
+    }
+
+    let mut standings: Vec<(UserId, i64)> = scores.into_iter().collect();
+    standings.sort_by_key(|(_, points)| std::cmp::Reverse(*points));
+
+    let description = if standings.is_empty() {
+        "Nobody answered any questions.".to_string()
+    } else {
+        standings.iter()
+            .enumerate()
+            .map(|(index, (user_id, points))| format!("**{}.** {} - {points} points", index + 1, user_id.mention()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Trivia results")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Asks one question and collects answers until time runs out, then reveals the right answer.
+/// Everyone gets one answer, and only right answers earn points.
+async fn play_round(ctx: &Context, msg: &Message, question: &Question, round: usize, rounds: usize) -> Result<Vec<TriviaAnswer>, SerenityError> {
+    let (answers, correct) = question.shuffled_answers();
+
+    let choices = answers.iter()
+        .zip(ANSWER_LABELS)
+        .map(|(answer, label)| format!("**{label}.** {answer}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Question {} of {rounds} - {}", round + 1, question.category))
+        .description(format!("{}\n\n{choices}", question.question))
+        .footer(CreateEmbedFooter::new(format!("You have {} seconds to answer.", ROUND_TIME.as_secs())));
+
+    let buttons = ANSWER_LABELS.iter()
+        .enumerate()
+        .map(|(index, label)| CreateButton::new(format!("trivia_{index}")).label(*label).style(ButtonStyle::Primary))
+        .collect::<Vec<_>>();
+
+    let mut prompt = msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed.clone()).components(vec![CreateActionRow::Buttons(buttons)])).await?;
+
+    let deadline = Instant::now() + ROUND_TIME;
+    let mut picks: Vec<(UserId, usize)> = Vec::new();
+
+    while let Some(interaction) = prompt.await_component_interaction(&ctx.shard)
+        .timeout(deadline.saturating_duration_since(Instant::now()))
+        .await
+    {
+        let Some(pick) = interaction.data.custom_id.strip_prefix("trivia_").and_then(|index| index.parse::<usize>().ok()).filter(|index| *index < answers.len()) else {
+            continue;
+        };
+
+        let content = if picks.iter().any(|(user_id, _)| *user_id == interaction.user.id) {
+            "You've already answered this question.".to_string()
+        } else {
+            picks.push((interaction.user.id, pick));
+            format!("You answered **{}**.", answers[pick])
+        };
+
+        let response = CreateInteractionResponseMessage::new().content(content).ephemeral(true);
+        interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+    }
+
+    // answers are in the order they came in, so the first right one gets the bonus
+    let mut first = true;
+
+    let results: Vec<TriviaAnswer> = picks.iter()
+        .map(|(user_id, pick)| {
+            let is_correct = *pick == correct;
+            let points = if is_correct && first { CORRECT_POINTS + FIRST_BONUS } else if is_correct { CORRECT_POINTS } else { 0 };
+            first &= !is_correct;
+
+            TriviaAnswer { user_id: *user_id, correct: is_correct, points }
+        })
+        .collect();
+
+    let winners = results.iter()
+        .filter(|answer| answer.correct)
+        .map(|answer| answer.user_id.mention().to_string())
+        .collect::<Vec<_>>();
+
+    let outcome = if winners.is_empty() {
+        "Nobody got it right.".to_string()
+    } else {
+        format!("Got it right: {}", winners.join(", "))
+    };
+
+    let revealed = embed
+        .field("Answer", format!("**{}.** {}", ANSWER_LABELS[correct], answers[correct]), false)
+        .field("Results", outcome, false)
+        .footer(CreateEmbedFooter::new(format!("{} answered.", picks.len())));
+
+    drop(prompt.edit(ctx, EditMessage::new().embed(revealed).components(vec![])).await);
+
+    Ok(results)
+}
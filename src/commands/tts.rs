@@ -0,0 +1,218 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::playback::{Playback, member_voice_channel, play_in_voice};
+use crate::utilities::tts::{MAX_TTS_LENGTH, reload_tts_readers, synthesize, tts_voice};
+
+const MAX_VOICE_LENGTH: usize = 64;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's text-to-speech settings. `tts say` reads text aloud in your voice channel, and `tts read` reads every message sent in a channel aloud in a voice channel."]
+#[sub_commands(tts_say, tts_voice_set, tts_language, tts_read)]
+#[num_args(0)]
+async fn tts(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let voice = tts_voice(&database, guild_id).await?;
+
+    let id = guild_id.get() as i64;
+
+    let reading = sqlx::query!("SELECT read_channel_id, voice_channel_id FROM tts_settings WHERE guild_id = ?", id)
+        .fetch_optional(&database)
+        .await?
+        .and_then(|row| Some((ChannelId::new(row.read_channel_id? as u64), ChannelId::new(row.voice_channel_id? as u64))));
+
+    let reading = match reading {
+        Some((read_channel_id, voice_channel_id)) => format!("{} into {}", read_channel_id.mention(), voice_channel_id.mention()),
+        None => "Off".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Text-to-speech")
+        .field("Voice", voice.voice.unwrap_or_else(|| "The engine's default".to_string()), true)
+        .field("Language", voice.language, true)
+        .field("Reading messages", reading, false);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("say")]
+#[only_in(guilds)]
+#[description = "Reads text aloud in your voice channel, in this server's voice and language."]
+#[usage = "<text>"]
+#[example = "Movie night starts in five minutes!"]
+#[min_args(1)]
+async fn tts_say(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let text = args.rest().trim();
+
+    if text.chars().count() > MAX_TTS_LENGTH {
+        msg.reply(ctx, format!("Text-to-speech can read at most {MAX_TTS_LENGTH} characters at once.")).await?;
+        return Ok(());
+    }
+
+    let guild_id = msg.guild_id.unwrap();
+
+    let Some(channel_id) = member_voice_channel(ctx, guild_id, msg.author.id) else {
+        msg.reply(ctx, "Join a voice channel first, then I'll read it there.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let voice = tts_voice(&database, guild_id).await?;
+    let typing = msg.channel_id.start_typing(&ctx.http);
+
+    let result = synthesize(ctx, text, &voice).await;
+
+    typing.stop();
+
+    let played = match result {
+        Ok(audio) => play_in_voice(ctx, guild_id, channel_id, audio.into(), Playback::Queue).await,
+        Err(why) => Err(why)
+    };
+
+    match played {
+        Ok(()) => msg.react(ctx, '🔊').await.map(|_| ())?,
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command("voice")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the voice text-to-speech uses in this server, by the name the engine knows it by. `reset` goes back to the engine's default voice."]
+#[usage = "<voice|reset>"]
+#[example = "en-US-JennyNeural"]
+#[num_args(1)]
+async fn tts_voice_set(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let voice = args.rest().trim();
+
+    if voice.len() > MAX_VOICE_LENGTH {
+        msg.reply(ctx, format!("Voice names can be at most {MAX_VOICE_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let voice = Some(voice).filter(|voice| !voice.eq_ignore_ascii_case("reset"));
+
+    sqlx::query!(
+        "INSERT INTO tts_settings (guild_id, voice) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET voice = excluded.voice",
+        guild_id,
+        voice
+    ).execute(&database).await?;
+
+    reload_tts_readers(ctx).await?;
+
+    let reply = match voice {
+        Some(voice) => format!("Text-to-speech will use the voice `{voice}`."),
+        None => "Text-to-speech will use the engine's default voice.".to_string()
+    };
+
+    msg.reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+#[command("language")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the language text-to-speech reads in, as a language code."]
+#[usage = "<language>"]
+#[example = "de"]
+#[num_args(1)]
+async fn tts_language(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let language = args.rest().trim().to_string();
+
+    // language tags like `en` or `pt-BR`
+    if !(2..=8).contains(&language.len()) || !language.chars().all(|character| character.is_ascii_alphabetic() || character == '-') {
+        msg.reply(ctx, "Please give a language code, such as `en`, `de` or `pt-BR`.").await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    sqlx::query!(
+        "INSERT INTO tts_settings (guild_id, language) VALUES (?, ?) ON CONFLICT (guild_id) DO UPDATE SET language = excluded.language",
+        guild_id,
+        language
+    ).execute(&database).await?;
+
+    reload_tts_readers(ctx).await?;
+
+    msg.reply(ctx, format!("Text-to-speech will read in `{language}`.")).await?;
+
+    Ok(())
+}
+
+#[command("read")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Reads every message sent in a text channel aloud in a voice channel, whenever someone's there to hear it. `off` stops reading messages."]
+#[usage = "<#text-channel> <#voice-channel> | off"]
+#[example = "#no-mic #General"]
+#[min_args(1)]
+#[max_args(2)]
+async fn tts_read(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    if args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("off")) {
+        sqlx::query!("UPDATE tts_settings SET read_channel_id = NULL, voice_channel_id = NULL WHERE guild_id = ?", guild_id)
+            .execute(&database)
+            .await?;
+
+        reload_tts_readers(ctx).await?;
+
+        msg.reply(ctx, "Messages won't be read out anymore.").await?;
+        return Ok(());
+    }
+
+    let read_channel = args.channel(ctx, msg).await?;
+    let voice_channel = args.channel(ctx, msg).await?;
+
+    if read_channel.kind != ChannelType::Text {
+        msg.reply(ctx, format!("{} isn't a text channel.", read_channel.mention())).await?;
+        return Ok(());
+    }
+
+    if voice_channel.kind != ChannelType::Voice {
+        msg.reply(ctx, format!("{} isn't a voice channel.", voice_channel.mention())).await?;
+        return Ok(());
+    }
+
+    let (read_channel_id, voice_channel_id) = (read_channel.id.get() as i64, voice_channel.id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO tts_settings (guild_id, read_channel_id, voice_channel_id) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET read_channel_id = excluded.read_channel_id, voice_channel_id = excluded.voice_channel_id",
+        guild_id,
+        read_channel_id,
+        voice_channel_id
+    ).execute(&database).await?;
+
+    reload_tts_readers(ctx).await?;
+
+    msg.reply(ctx, format!("Messages sent in {} will be read aloud in {} while someone's there.", read_channel.mention(), voice_channel.mention())).await?;
+
+    Ok(())
+}
@@ -1,41 +1,46 @@
-use std::collections::HashSet;
-
 use serenity::builder::{CreateEmbed, EditMessage, CreateEmbedFooter, CreateMessage};
-use serenity::framework::standard::macros::{command, help};
-use serenity::framework::standard::{CommandResult, help_commands, Args, HelpOptions, CommandGroup};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{CommandResult, Args};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 use chrono::{Duration, Utc};
 
-use crate::utilities::global_data::{ShardManagerContainer, GuildSettingsContainer, DatabaseConnectionContainer, GuildSettings};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{ShardManagerContainer, DatabaseConnectionContainer, BootTimeContainer, CommandCountsContainer, MessageLogContainer, get_or_create_guild_settings, update_guild_settings};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::schema::schema_version;
 
 #[command]
 #[description= "Checks Discord's API / message latency."]
 async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
     let start = Utc::now();
-    let start_ts = start.timestamp();
-    let start_ts_ss = start.timestamp_subsec_millis() as i64;
     let mut ping: Message = msg.channel_id.say(ctx, ":ping_pong: Pinging!").await?;
-    let end = Utc::now();
-    let end_ts = end.timestamp();
-    let end_ts_ss = end.timestamp_subsec_millis() as i64;
-    let api_response = ((end_ts - start_ts) * 1000) + (end_ts_ss - start_ts_ss);
-    let ctx_data = ctx.data.read().await;
-    let shard_manager = match ctx_data.get::<ShardManagerContainer>() {
-        Some(shard) => shard,
-        None => {
-            msg.reply(ctx, "I encountered a problem while getting the shard manager.").await?;
-            return Ok(());
+    let api_response = (Utc::now() - start).num_milliseconds();
+
+    match latency_embed(ctx, msg.guild_id, api_response).await {
+        Ok(embed) => {
+            ping.edit(ctx, EditMessage::new().embed(embed)).await?;
         }
+        Err(why) => {
+            msg.reply(ctx, why).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the latency report shown by `ping`, given how long the API took to respond.
+pub async fn latency_embed(ctx: &Context, guild_id: Option<GuildId>, api_response: i64) -> Result<CreateEmbed, &'static str> {
+    let ctx_data = ctx.data.read().await;
+    let Some(shard_manager) = ctx_data.get::<ShardManagerContainer>() else {
+        return Err("I encountered a problem while getting the shard manager.");
     };
 
     let runners = shard_manager.runners.lock().await;
-    let runner = match runners.get(&ctx.shard_id) {
-        Some(runner) => runner,
-        None => {
-            msg.reply(ctx, "Could not find a shard").await?;
-            return Ok(());
-        }
+    let Some(runner) = runners.get(&ctx.shard_id) else {
+        return Err("Could not find a shard");
     };
 
     let shard_response = match runner.latency {
@@ -56,12 +61,96 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
         **Shard Response Time**: {shard_response}"
     );
 
-    let embed = CreateEmbed::new().color(0x008b_0000).title("Discord Latency Information").description(response);
-    ping.edit(ctx, EditMessage::new().embed(embed)).await?;
+    Ok(branded_embed(ctx, guild_id).await.title("Discord Latency Information").description(response))
+}
+
+/// Commands listed by `stats`, most used first.
+const TOP_COMMANDS: usize = 5;
+
+/// Clock ticks per second used by `/proc/self/stat`, which is 100 on practically every Linux system.
+const CLOCK_TICKS_PER_SECOND: f64 = 100.0;
+
+#[command]
+#[aliases("botinfo", "uptime")]
+#[description = "Shows the bot's uptime, reach, resource usage and the commands run since it started."]
+#[num_args(0)]
+async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
+    let boot_time = get_data::<BootTimeContainer>(ctx).await?;
+    let command_counts = get_data::<CommandCountsContainer>(ctx).await?;
+    let message_cache = get_data::<MessageLogContainer>(ctx).await?;
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let uptime = Utc::now() - boot_time;
+
+    let guilds = ctx.cache.guilds();
+    let channels: usize = guilds.iter()
+        .filter_map(|guild_id| guild_id.to_guild_cached(&ctx.cache).map(|guild| guild.channels.len()))
+        .sum();
+
+    let cached_messages = message_cache.lock().await.message_count();
+
+    let (total_commands, top_commands) = {
+        let command_counts = command_counts.lock().await;
+
+        let mut counts: Vec<(&String, &u64)> = command_counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let top = counts.iter()
+            .take(TOP_COMMANDS)
+            .map(|(name, count)| format!("`{name}`: {count}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        (command_counts.values().sum::<u64>(), top)
+    };
+
+    let resources = match process_usage() {
+        Some((memory_kb, cpu_seconds)) => {
+            let average_cpu = cpu_seconds / (uptime.num_milliseconds().max(1) as f64 / 1000.0) * 100.0;
+            format!("{:.1} MiB memory\n{average_cpu:.1}% CPU on average", memory_kb as f64 / 1024.0)
+        }
+        None => "Unavailable".to_string()
+    };
+
+    let schema = schema_version(&database).await.map_or_else(|| "unknown".to_string(), |version| version.to_string());
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Bot Statistics")
+        .field("Uptime", format!("{} (since <t:{}:f>)", format_duration(uptime), boot_time.timestamp()), false)
+        .field("Servers", guilds.len().to_string(), true)
+        .field("Users", ctx.cache.user_count().to_string(), true)
+        .field("Channels", channels.to_string(), true)
+        .field("Shards", ctx.cache.shard_count().to_string(), true)
+        .field("Cached messages", cached_messages.to_string(), true)
+        .field("Resources", resources, true)
+        .field(format!("Commands run ({total_commands})"), if top_commands.is_empty() { "None yet".to_string() } else { top_commands }, false)
+        .footer(CreateEmbedFooter::new(format!("serenity {} • sqlx {} • schema {schema}", env!("SERENITY_VERSION"), env!("SQLX_VERSION"))));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
 
     Ok(())
 }
 
+/// The process's resident memory in KiB and the CPU time it has used in seconds, read from
+/// `/proc`, so only available on Linux.
+fn process_usage() -> Option<(u64, f64)> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+
+    let memory_kb = status.lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|value| value.parse::<u64>().ok())?;
+
+    // the process name can contain spaces, so fields are counted from after it
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+
+    let user_ticks = fields.get(11)?.parse::<u64>().ok()?;
+    let system_ticks = fields.get(12)?.parse::<u64>().ok()?;
+
+    Some((memory_kb, (user_ticks + system_ticks) as f64 / CLOCK_TICKS_PER_SECOND))
+}
+
 #[command("prefix")]
 //#[aliases("setprefix", "prefixset")]
 #[description = "Sets the bot's guild prefix or views the current prefix."]
@@ -70,131 +159,105 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
 #[min_args(0)]
 #[max_args(1)]
 async fn prefix(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    if msg.is_private() {
-        let embed = CreateEmbed::new()
-            .color(0x008b_0000)
+    let mut arg = args.clone();
+    run_prefix(ctx, &Invocation::Message(msg), arg.trimmed().rest()).await
+}
+
+/// Shows the guild's prefix, or sets it to `prefix` when one is given.
+pub async fn run_prefix(ctx: &Context, invocation: &Invocation<'_>, prefix: &str) -> CommandResult {
+    let Some(guild_id) = invocation.guild_id() else {
+        let embed = branded_embed(ctx, invocation.guild_id()).await
             .title("Prefix")
             .description("The bot's default prefix is ```-```")
             .footer(CreateEmbedFooter::new("Use `-setprefix <new prefix>` to change it in a server."));
 
-        msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+        invocation.respond(ctx, embed).await?;
 
         return Ok(());
-    }
-
-    let is_admin = {
-        let author = msg.guild_id.unwrap()
-            .member(&ctx.http, msg.author.id).await.unwrap()
-            .permissions(&ctx.cache).expect("Failed to get permissions").administrator();
-        author.clone()
     };
 
-    if !is_admin {
-        let embed = CreateEmbed::new()
-            .color(0x008b_0000)
+    if !invocation.has_command_access(ctx, "prefix").await {
+        let embed = branded_embed(ctx, invocation.guild_id()).await
             .title("Prefix")
-            .description("You must be an administrator to use this command.")
+            .description("You must be an administrator, or have a role allowed to use `prefix`, to use this command.")
             .footer(CreateEmbedFooter::new("Use `-prefix <new prefix>` to change it in a server."));
 
-        msg.channel_id.send_message(&ctx.http, CreateMessage::new().embed(embed)).await?;
+        invocation.respond(ctx, embed).await?;
 
         return Ok(());
     }
 
-    let mut arg = args.clone();
-    let prefix = arg.trimmed();
+    let prefix = prefix.trim();
 
     if prefix.is_empty() {
-        let guild_prefix = {
-            let data = ctx.data.read().await;
-            let guild_settings = data.get::<GuildSettingsContainer>().unwrap();
-            let pf = guild_settings.read().await;
-            pf.get(&msg.guild_id.unwrap().get()).unwrap().prefix.clone()
-        };
-
-        let embed = CreateEmbed::new()
-            .color(0x008b_0000)
+        let guild_prefix = invocation.prefix(ctx).await;
+
+        let embed = branded_embed(ctx, invocation.guild_id()).await
             .title("Prefix")
             .description(format!("The bot's default prefix is ```{guild_prefix}```"))
             .footer(CreateEmbedFooter::new(format!("Use `{guild_prefix}prefix <new prefix>` to change it in a server.")));
 
-        msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+        invocation.respond(ctx, embed).await?;
 
         return Ok(());
     }
 
-    let set = prefix.parse::<String>().unwrap();
+    let set = prefix.to_string();
 
     if set.contains(" ") {
-        let embed = CreateEmbed::new()
-            .color(0x008b_0000)
+        let embed = branded_embed(ctx, invocation.guild_id()).await
             .title("Prefix")
             .description("Prefixes cannot contain spaces.");
-        
-        let builder = CreateMessage::new().embed(embed);
 
-        msg.channel_id.send_message(&ctx.http, builder).await.unwrap();
+        invocation.respond(ctx, embed).await?;
 
         return Ok(());
     }
 
-    let new_prefix = {
+    // written back to the database in the background
+    update_guild_settings(ctx, guild_id, |settings| settings.prefix = set.clone()).await?;
 
-        let guild_settings = {
-            let data = ctx.data.read().await;
-            let guild_settings = data.get::<GuildSettingsContainer>().unwrap();
+    let new_prefix = set;
 
-            guild_settings.clone()
-        };
+    let embed = branded_embed(ctx, invocation.guild_id()).await
+        .title("Prefix")
+        .description(format!("Prefix set to ```{new_prefix}```"));
 
-        let mut lock = guild_settings.write().await;
+    invocation.respond(ctx, embed).await?;
 
-        // update guild settings
-        let setting = GuildSettings {
-            prefix: set.clone(),
-            owner_id: msg.author.id.get(),
-            mute_type: "timeout".to_string(),
-            mute_role: 0
-        };
+    Ok(())
+}
 
-        let guild_setting = lock.entry(msg.guild_id.unwrap().get()).or_insert(setting);
-        guild_setting.prefix = set;
+#[command("suggestions")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns \"did you mean\" suggestions for mistyped commands on or off, or shows the current setting."]
+#[usage = "[on|off]"]
+#[max_args(1)]
+async fn suggestions(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
 
-        guild_setting.prefix.clone()
-    };
+    let enabled = match args.single::<String>().ok().as_deref() {
+        None => {
+            let enabled = get_or_create_guild_settings(ctx, guild_id).await?.read().await.command_suggestions;
 
-    {
-        let data = ctx.data.read().await;
-        let database = data.get::<DatabaseConnectionContainer>().unwrap().clone();
-        let guild_id = msg.guild_id.unwrap().get() as i64;
+            let state = if enabled { "on" } else { "off" };
+            msg.reply(ctx, format!("Command suggestions are currently **{state}**.")).await?;
 
-        sqlx::query!(
-            "UPDATE guild_settings SET prefix = ? WHERE guild_id = ?",
-            new_prefix,
-            guild_id
-        ).execute(&database).await.unwrap();
-    }
+            return Ok(());
+        }
+        Some("on") => true,
+        Some("off") => false,
+        Some(_) => {
+            msg.reply(ctx, "Please specify either `on` or `off`.").await?;
+            return Ok(());
+        }
+    };
 
-    let embed = CreateEmbed::new()
-        .color(0x008b_0000)
-        .title("Prefix")
-        .description(format!("Prefix set to ```{new_prefix}```"));
+    update_guild_settings(ctx, guild_id, |settings| settings.command_suggestions = enabled).await?;
 
-    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    let state = if enabled { "enabled" } else { "disabled" };
+    msg.reply(ctx, format!("Command suggestions {state}.")).await?;
 
     Ok(())
 }
-
-#[help]
-#[max_levenshtein_distance(3)]
-#[indention_prefix = "+"]
-#[lacking_permissions = "Hide"]
-#[lacking_role = "Nothing"]
-#[wrong_channel = "Strike"]
-#[no_help_available_text("No help information available.")]
-#[individual_command_tip = "Hello! こんにちは！Hola! Bonjour! 您好! 안녕하세요~\n\n\
-If you want more information about a specific command, just pass the command as argument."]
-async fn help(ctx: &Context, msg: &Message, args: Args, opts: &'static HelpOptions, groups: &[&'static CommandGroup], owners: HashSet<UserId>) -> CommandResult {
-    let _ = help_commands::with_embeds(ctx, msg, args, opts, groups, owners).await;
-    Ok(())
-}
\ No newline at end of file
@@ -0,0 +1,173 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::verification::verify_components;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows how new members verify in this server, and how many haven't yet. Members who haven't verified keep the unverified role and only get their autoroles once they do."]
+#[sub_commands(verify_setup, verify_off)]
+async fn verify(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let settings = sqlx::query!(
+        "SELECT channel_id, unverified_role_id, verified_role_id, mode FROM verification_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(&database).await?;
+
+    let Some(settings) = settings else {
+        msg.reply(ctx, "Verification isn't set up. Use `verify setup` to set it up.").await?;
+        return Ok(());
+    };
+
+    let pending = sqlx::query!("SELECT COUNT(*) AS count FROM pending_verifications WHERE guild_id = ?", guild_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    let mode = if settings.mode == "captcha" { "Button, then a captcha by DM" } else { "Button" };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Verification")
+        .field("Channel", ChannelId::new(settings.channel_id as u64).mention().to_string(), true)
+        .field("Mode", mode, true)
+        .field("Waiting to verify", pending.to_string(), true)
+        .field("Unverified role", RoleId::new(settings.unverified_role_id as u64).mention().to_string(), true)
+        .field("Verified role", settings.verified_role_id.map_or_else(|| "None".to_string(), |role_id| RoleId::new(role_id as u64).mention().to_string()), true);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("setup")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets up verification: new members get the unverified role until they press the Verify button posted in the channel, or answer a captcha by DM after pressing it in `captcha` mode. Make sure the unverified role can only see the verification channel. Members who were already here aren't affected."]
+#[usage = "<channel> <unverified role> [verified role] [button|captcha]"]
+#[example = "#verify @Unverified @Member captcha"]
+#[min_args(2)]
+#[max_args(4)]
+async fn verify_setup(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel = args.channel(ctx, msg).await?;
+
+    if channel.kind != ChannelType::Text {
+        msg.reply(ctx, format!("{} isn't a text channel.", channel.mention())).await?;
+        return Ok(());
+    }
+
+    let channel_id = channel.id;
+    let unverified_role = args.role(ctx, msg).await?.id;
+
+    let (mut verified_role, mut captcha) = (None, false);
+
+    while !args.is_empty() {
+        if args.keyword("button") {
+            captcha = false;
+        } else if args.keyword("captcha") {
+            captcha = true;
+        } else {
+            verified_role = Some(args.role(ctx, msg).await?.id);
+        }
+    }
+
+    if verified_role == Some(unverified_role) {
+        msg.reply(ctx, "The verified and unverified roles have to be different.").await?;
+        return Ok(());
+    }
+
+    for role_id in std::iter::once(unverified_role).chain(verified_role) {
+        if let Err(why) = check_assignable(ctx, guild_id, role_id) {
+            msg.reply(ctx, why).await?;
+            return Ok(());
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let previous = sqlx::query!("SELECT channel_id, message_id FROM verification_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let description = if captcha {
+        "Press **Verify** below, then answer the question I send you by DM to get access to the rest of the server."
+    } else {
+        "Press **Verify** below to get access to the rest of the server."
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Verification")
+        .description(description);
+
+    let message = channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(verify_components())).await?;
+
+    let (db_channel_id, message_id, unverified_role_id) = (channel_id.get() as i64, message.id.get() as i64, unverified_role.get() as i64);
+    let verified_role_id = verified_role.map(|role_id| role_id.get() as i64);
+    let mode = if captcha { "captcha" } else { "button" };
+
+    sqlx::query!(
+        "INSERT INTO verification_settings (guild_id, channel_id, message_id, unverified_role_id, verified_role_id, mode) VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET channel_id = excluded.channel_id, message_id = excluded.message_id,
+        unverified_role_id = excluded.unverified_role_id, verified_role_id = excluded.verified_role_id, mode = excluded.mode",
+        db_guild_id,
+        db_channel_id,
+        message_id,
+        unverified_role_id,
+        verified_role_id,
+        mode
+    ).execute(&database).await?;
+
+    // the old button keeps working, but one is enough
+    if let Some(previous) = previous {
+        drop(ChannelId::new(previous.channel_id as u64).delete_message(ctx, MessageId::new(previous.message_id as u64)).await);
+    }
+
+    msg.reply(ctx, format!("Verification is set up in {}. New members will get {} until they verify.", channel_id.mention(), unverified_role.mention())).await?;
+
+    Ok(())
+}
+
+#[command("off")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Turns verification off and deletes the verify button. Members waiting to verify keep the unverified role until it's taken away."]
+#[num_args(0)]
+async fn verify_off(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let removed = sqlx::query!("DELETE FROM verification_settings WHERE guild_id = ? RETURNING channel_id, message_id", guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let Some(removed) = removed else {
+        msg.reply(ctx, "Verification isn't set up.").await?;
+        return Ok(());
+    };
+
+    sqlx::query!("DELETE FROM pending_verifications WHERE guild_id = ?", guild_id)
+        .execute(&database)
+        .await?;
+
+    drop(ChannelId::new(removed.channel_id as u64).delete_message(ctx, MessageId::new(removed.message_id as u64)).await);
+
+    msg.reply(ctx, "Verification is now off.").await?;
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use serenity::builder::{CreateEmbedAuthor, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::voice::{uncounted_voice_time, voice_log_channel};
+
+const LEADERBOARD_SIZE: i64 = 100;
+
+fn voice_time(seconds: i64) -> String {
+    format_duration(chrono::Duration::seconds(seconds))
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows how long you've spent in this server's voice channels, or someone else."]
+#[usage = "[member]"]
+#[max_args(1)]
+async fn voicestats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let (db_guild_id, db_user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let standing = sqlx::query!(
+        r#"SELECT seconds, (SELECT COUNT(*) FROM voice_time AS others WHERE others.guild_id = voice_time.guild_id AND others.seconds > voice_time.seconds) + 1 AS "rank!: i64"
+        FROM voice_time WHERE guild_id = ? AND user_id = ?"#,
+        db_guild_id,
+        db_user_id
+    ).fetch_optional(&database).await?;
+
+    let current = uncounted_voice_time(ctx, guild_id, user_id).await;
+
+    if standing.is_none() && current.is_none() {
+        msg.reply(ctx, "No time spent in voice here yet.").await?;
+        return Ok(());
+    }
+
+    let user = user_id.to_user(ctx).await?;
+    let seconds = standing.as_ref().map_or(0, |row| row.seconds) + current.map_or(0, |current| current.as_secs() as i64);
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .author(CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+        .field("Time in voice", voice_time(seconds), true);
+
+    if let Some(standing) = standing {
+        embed = embed.field("Rank", format!("#{}", standing.rank), true);
+    }
+
+    if current.is_some() {
+        embed = embed.field("Status", "In a voice channel now", true);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("voice")]
+#[only_in(guilds)]
+#[description = "Shows the members who've spent the most time in this server's voice channels."]
+#[num_args(0)]
+async fn leaderboard_voice(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let top = sqlx::query!(
+        "SELECT user_id, seconds FROM voice_time WHERE guild_id = ? AND seconds > 0 ORDER BY seconds DESC LIMIT ?",
+        guild_id,
+        LEADERBOARD_SIZE
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = top.iter()
+        .enumerate()
+        .map(|(index, row)| format!("**{}.** {} - {}", index + 1, UserId::new(row.user_id as u64).mention(), voice_time(row.seconds)))
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody has spent time in voice here yet.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Most time in voice");
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Shows or sets the channel members joining, leaving and moving between voice channels are logged to, along with mutes and deafens. Use `off` to stop logging them."]
+#[usage = "[channel|off]"]
+#[example = "#voice-log"]
+#[max_args(1)]
+async fn voicelog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    if args.is_empty() {
+        match voice_log_channel(&database, guild_id).await? {
+            Some(channel_id) => msg.reply(ctx, format!("Voice activity is logged to {}.", channel_id.mention())).await?,
+            None => msg.reply(ctx, "This server has no voice log channel.").await?
+        };
+
+        return Ok(());
+    }
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+
+    sqlx::query!(
+        "UPDATE guild_settings SET voice_log_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Voice activity will now be logged to {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Voice activity will no longer be logged.").await?
+    };
+
+    Ok(())
+}
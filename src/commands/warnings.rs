@@ -0,0 +1,309 @@
+use chrono::{DateTime, Duration};
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::confirmation::{CONFIRM_TIMEOUT, confirm};
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::invocation::Invocation;
+use crate::utilities::mod_notes::show_notes;
+use crate::utilities::moderation::{ModAction, MAX_TIMEOUT, add_warning, check_target};
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::parsing::{format_duration, parse_duration};
+
+/// Most escalation steps a single guild can have.
+const MAX_ESCALATIONS: i32 = 10;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Warns a member. Reaching a number of warnings set up with `warnescalation` mutes, kicks or bans them automatically."]
+#[usage = "<user> [reason]"]
+#[example = "@user Please keep #general on topic"]
+#[min_args(1)]
+async fn warn(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    if let Err(why) = check_target(ctx, guild_id, msg.author.id, target_id, ModAction::Warn, Permissions::empty()).await {
+        msg.reply(ctx, why).await?;
+        return Ok(());
+    }
+
+    show_notes(ctx, msg, target_id).await;
+
+    let target = target_id.to_user(ctx).await?;
+    let outcome = add_warning(ctx, guild_id, &target, &msg.author, reason).await?;
+    let (case, count) = (outcome.case, outcome.warnings);
+
+    let plural = if count == 1 { "" } else { "s" };
+    msg.reply(ctx, format!("Warned **{}** (case #{case}). They now have {count} warning{plural}.", target.tag())).await?;
+
+    match outcome.escalation {
+        Some((action, Ok(case))) => {
+            msg.channel_id.say(ctx, format!("**{}** was automatically {} for reaching {count} warnings (case #{case}).", target.tag(), action.past_tense())).await?;
+        }
+        Some((action, Err(why))) => {
+            msg.channel_id.say(ctx, format!("**{}** reached {count} warnings, but I couldn't {} them: {why}", target.tag(), action.as_str())).await?;
+        }
+        None => {}
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Lists a member's warnings."]
+#[usage = "<user>"]
+#[num_args(1)]
+async fn warnings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (msg.guild_id.unwrap().get() as i64, target_id.get() as i64);
+
+    let warnings = sqlx::query!(
+        "SELECT id, moderator_id, reason, created_at FROM warnings WHERE guild_id = ? AND user_id = ? ORDER BY id",
+        guild_id,
+        user_id
+    ).fetch_all(&database).await?;
+
+    let lines: Vec<String> = warnings.iter()
+        .map(|row| {
+            let issued = DateTime::parse_from_rfc3339(&row.created_at)
+                .map_or_else(|_| row.created_at.clone(), |time| format!("<t:{}:R>", time.timestamp()));
+
+            format!(
+                "**#{}** {} - by {} {issued}",
+                row.id,
+                row.reason.as_deref().unwrap_or("No reason given."),
+                UserId::new(row.moderator_id as u64).mention()
+            )
+        })
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec![format!("{} has no warnings.", target_id.mention())]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Warnings ({})", warnings.len()));
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Deletes a single warning by its number, as shown by `warnings`."]
+#[usage = "<warning>"]
+#[num_args(1)]
+async fn delwarn(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM warnings WHERE id = ? AND guild_id = ?",
+        id,
+        guild_id
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no warning #{id}.")).await?;
+    } else {
+        msg.reply(ctx, format!("Deleted warning #{id}.")).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Deletes all of a member's warnings."]
+#[usage = "<user>"]
+#[num_args(1)]
+async fn clearwarn(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (guild_id, user_id) = (msg.guild_id.unwrap().get() as i64, target_id.get() as i64);
+
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM warnings WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_one(&database)
+        .await?
+        .count;
+
+    if count == 0 {
+        msg.reply(ctx, format!("{} has no warnings.", target_id.mention())).await?;
+        return Ok(());
+    }
+
+    let prompt = format!("Delete {count} warning{} from {}?", if count == 1 { "" } else { "s" }, target_id.mention());
+
+    if !confirm(ctx, msg, &prompt, CONFIRM_TIMEOUT).await? {
+        return Ok(());
+    }
+
+    let deleted = sqlx::query!(
+        "DELETE FROM warnings WHERE guild_id = ? AND user_id = ?",
+        guild_id,
+        user_id
+    ).execute(&database).await?.rows_affected();
+
+    let plural = if deleted == 1 { "" } else { "s" };
+    msg.reply(ctx, format!("Cleared {deleted} warning{plural} from {}.", target_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Lists what happens automatically when members reach a number of warnings."]
+#[sub_commands(warnescalation_set, warnescalation_remove)]
+async fn warnescalation(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let escalations = sqlx::query!(
+        "SELECT warnings, action, duration FROM warning_escalations WHERE guild_id = ? ORDER BY warnings",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let description = if escalations.is_empty() {
+        "This server has no warning escalations yet.".to_string()
+    } else {
+        escalations.iter()
+            .map(|row| {
+                let length = row.duration.map_or_else(String::new, |seconds| format!(" for {}", format_duration(Duration::seconds(seconds))));
+                format!("**{} warnings** → {}{length}", row.warnings, row.action)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Warning escalations")
+        .description(description);
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Mutes, kicks or bans members automatically once they reach a number of warnings. Mutes can be given a duration."]
+#[usage = "<warnings> <mute [duration]|kick|ban>"]
+#[example = "3 mute 1h"]
+#[min_args(2)]
+#[max_args(3)]
+async fn warnescalation_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(warnings) = args.single::<u32>().ok().filter(|warnings| *warnings > 0) else {
+        msg.reply(ctx, "Please give the number of warnings that triggers the action.").await?;
+        return Ok(());
+    };
+
+    let action = args.single::<String>()?.to_lowercase();
+
+    let duration = match (action.as_str(), args.single::<String>().ok()) {
+        ("mute", Some(duration_arg)) => match parse_duration(&duration_arg).filter(|duration| *duration > Duration::zero() && *duration <= MAX_TIMEOUT) {
+            Some(duration) => Some(duration.num_seconds()),
+            None => {
+                msg.reply(ctx, format!("Please give a duration of at most {}, like `10m`, `2h` or `3d`.", format_duration(MAX_TIMEOUT))).await?;
+                return Ok(());
+            }
+        },
+        ("mute" | "kick" | "ban", None) => None,
+        ("kick" | "ban", Some(_)) => {
+            msg.reply(ctx, "Only mutes can be given a duration.").await?;
+            return Ok(());
+        }
+        _ => {
+            msg.reply(ctx, "Escalations can `mute`, `kick` or `ban`.").await?;
+            return Ok(());
+        }
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+    let warnings = i64::from(warnings);
+
+    let count = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM warning_escalations WHERE guild_id = ? AND warnings != ?",
+        guild_id,
+        warnings
+    ).fetch_one(&database).await?.count;
+
+    if count >= MAX_ESCALATIONS {
+        msg.reply(ctx, format!("This server already has the maximum of {MAX_ESCALATIONS} escalations.")).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO warning_escalations (guild_id, warnings, action, duration) VALUES (?, ?, ?, ?)
+        ON CONFLICT (guild_id, warnings) DO UPDATE SET action = excluded.action, duration = excluded.duration",
+        guild_id,
+        warnings,
+        action,
+        duration
+    ).execute(&database).await?;
+
+    let length = duration.map_or_else(String::new, |seconds| format!(" for {}", format_duration(Duration::seconds(seconds))));
+    msg.reply(ctx, format!("Members reaching {warnings} warnings will now be {}{length}.", match action.as_str() {
+        "kick" => "kicked",
+        "ban" => "banned",
+        _ => "muted"
+    })).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Removes the escalation for a number of warnings."]
+#[usage = "<warnings>"]
+#[num_args(1)]
+async fn warnescalation_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let warnings = args.single::<i64>()?;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM warning_escalations WHERE guild_id = ? AND warnings = ?",
+        guild_id,
+        warnings
+    ).execute(&database).await?.rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("There is no escalation at {warnings} warnings.")).await?;
+    } else {
+        msg.reply(ctx, format!("Removed the escalation at {warnings} warnings.")).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use chrono::{DateTime, Duration, Utc};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::pagination::{LINES_PER_PAGE, Paginator, embed_pages, paginate_lines};
+use crate::utilities::parsing::{format_duration, parse_duration};
+use crate::utilities::scheduler::{Job, When, schedule_job};
+use crate::utilities::watchlist::{cancel_unwatches, reload_watchlist};
+
+/// Longest a user can be watched for before it has to be indefinite.
+const MAX_WATCH: Duration = Duration::days(365);
+
+/// Most sensitive channels a single guild can have.
+const MAX_SENSITIVE_CHANNELS: i32 = 25;
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Puts a user on the watchlist, optionally for a while, e.g. `7d` or `4w`. Staff are alerted in the watchlist channel when they join, post in a sensitive channel or change their nickname."]
+#[usage = "<user> [duration] [reason]"]
+#[example = "@user 30d Alt account of a banned member"]
+#[sub_commands(watch_remove, watch_list, watch_channel, watch_sensitive)]
+#[min_args(1)]
+async fn watch(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+
+    let duration = args.current().and_then(parse_duration);
+
+    if duration.is_some() {
+        args.advance();
+    }
+
+    if duration.is_some_and(|duration| duration > MAX_WATCH) {
+        msg.reply(ctx, format!("Users can be watched for at most {}, leave the duration out to watch them until they're removed.", format_duration(MAX_WATCH))).await?;
+        return Ok(());
+    }
+
+    let reason = Some(args.rest().trim()).filter(|reason| !reason.is_empty());
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let now = Utc::now();
+    let expires_at = duration.map(|duration| now + duration);
+
+    let (db_guild_id, user_id, moderator_id) = (guild_id.get() as i64, target_id.get() as i64, msg.author.id.get() as i64);
+    let (created_at, db_expires_at) = (now.to_rfc3339(), expires_at.map(|expires_at| expires_at.to_rfc3339()));
+
+    sqlx::query!(
+        "INSERT INTO watchlist (guild_id, user_id, moderator_id, reason, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET moderator_id = excluded.moderator_id, reason = excluded.reason, created_at = excluded.created_at, expires_at = excluded.expires_at",
+        db_guild_id,
+        user_id,
+        moderator_id,
+        reason,
+        created_at,
+        db_expires_at
+    ).execute(&database).await?;
+
+    // watching someone again replaces when their previous watch would have ended
+    cancel_unwatches(&database, guild_id, target_id).await?;
+
+    if let Some(expires_at) = expires_at {
+        let job = Job::Unwatch { user_id: target_id.get() };
+        let when = When { first_run: expires_at, repeat: None };
+
+        schedule_job(&database, Some(guild_id), &job, &when, msg.author.id.get()).await?;
+    }
+
+    reload_watchlist(ctx, guild_id).await?;
+
+    let length = duration.map_or_else(String::new, |duration| format!(" for {}", format_duration(duration)));
+    let mut content = format!("Now watching {}{length}.", target_id.mention());
+
+    let has_channel = sqlx::query!("SELECT watchlist_channel_id FROM guild_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?
+        .is_some_and(|row| row.watchlist_channel_id.is_some());
+
+    if !has_channel {
+        content.push_str(" Alerts won't be sent until a channel is set with `watch channel`.");
+    }
+
+    msg.reply(ctx, content).await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[aliases("unwatch")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Takes a user off the watchlist."]
+#[usage = "<user>"]
+#[example = "@user"]
+#[num_args(1)]
+async fn watch_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target_id = args.user_id()?;
+    let guild_id = msg.guild_id.unwrap();
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, user_id) = (guild_id.get() as i64, target_id.get() as i64);
+
+    let deleted = sqlx::query!("DELETE FROM watchlist WHERE guild_id = ? AND user_id = ?", db_guild_id, user_id)
+        .execute(&database)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        msg.reply(ctx, format!("{} isn't on the watchlist.", target_id.mention())).await?;
+        return Ok(());
+    }
+
+    cancel_unwatches(&database, guild_id, target_id).await?;
+    reload_watchlist(ctx, guild_id).await?;
+
+    msg.reply(ctx, format!("{} is no longer watched.", target_id.mention())).await?;
+
+    Ok(())
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[description = "Lists the watched users, where alerts are sent and the sensitive channels."]
+#[num_args(0)]
+async fn watch_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap().get() as i64;
+
+    let watched = sqlx::query!(
+        "SELECT user_id, moderator_id, reason, expires_at FROM watchlist WHERE guild_id = ? ORDER BY created_at",
+        guild_id
+    ).fetch_all(&database).await?;
+
+    let alert_channel = sqlx::query!("SELECT watchlist_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(&database)
+        .await?
+        .and_then(|row| row.watchlist_channel_id);
+
+    let sensitive = sqlx::query!("SELECT channel_id AS \"channel_id!\" FROM watchlist_channels WHERE guild_id = ? ORDER BY channel_id", guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let lines: Vec<String> = watched.iter()
+        .map(|row| {
+            let until = row.expires_at.as_deref()
+                .and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+                .map_or_else(|| "indefinitely".to_string(), |expires_at| format!("until <t:{}:R>", expires_at.timestamp()));
+
+            format!("<@{}> - {} - by <@{}>, {until}", row.user_id, row.reason.as_deref().unwrap_or("No reason given."), row.moderator_id)
+        })
+        .collect();
+
+    let descriptions = if lines.is_empty() {
+        vec!["Nobody is being watched.".to_string()]
+    } else {
+        paginate_lines(&lines, LINES_PER_PAGE, "\n")
+    };
+
+    let sensitive = if sensitive.is_empty() {
+        "None".to_string()
+    } else {
+        sensitive.iter().map(|row| format!("<#{}>", row.channel_id)).collect::<Vec<_>>().join(" ")
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title(format!("Watchlist ({})", watched.len()))
+        .field("Alerts", alert_channel.map_or_else(|| "Off".to_string(), |channel_id| format!("<#{channel_id}>")), true)
+        .field("Sensitive channels", sensitive, true);
+
+    Paginator::new(embed_pages(embed, descriptions)).send(ctx, &Invocation::Message(msg)).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the staff channel watchlist alerts are posted in. Use `off` to stop alerting."]
+#[usage = "<channel|off>"]
+#[example = "#mod-alerts"]
+#[num_args(1)]
+async fn watch_channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    get_or_create_guild_settings(ctx, guild_id).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.map(|channel_id| channel_id.get() as i64));
+
+    sqlx::query!(
+        "UPDATE guild_settings SET watchlist_channel_id = ? WHERE guild_id = ?",
+        db_channel_id,
+        db_guild_id
+    ).execute(&database).await?;
+
+    reload_watchlist(ctx, guild_id).await?;
+
+    match channel_id {
+        Some(channel_id) => msg.reply(ctx, format!("Watchlist alerts will be posted in {}.", channel_id.mention())).await?,
+        None => msg.reply(ctx, "Watchlist alerts won't be posted anymore.").await?
+    };
+
+    Ok(())
+}
+
+#[command("sensitive")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Marks a channel as sensitive, so watched users posting there are alerted about, or unmarks it if it already is. Alerts for the same user and channel are sent at most every 10 minutes."]
+#[usage = "<channel>"]
+#[example = "#support"]
+#[num_args(1)]
+async fn watch_sensitive(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = args.channel(ctx, msg).await?.id;
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_channel_id) = (guild_id.get() as i64, channel_id.get() as i64);
+
+    let removed = sqlx::query!("DELETE FROM watchlist_channels WHERE channel_id = ? AND guild_id = ?", db_channel_id, db_guild_id)
+        .execute(&database)
+        .await?
+        .rows_affected() > 0;
+
+    if !removed {
+        let count = sqlx::query!("SELECT COUNT(*) AS count FROM watchlist_channels WHERE guild_id = ?", db_guild_id)
+            .fetch_one(&database)
+            .await?
+            .count;
+
+        if count >= MAX_SENSITIVE_CHANNELS {
+            msg.reply(ctx, format!("This server already has the maximum of {MAX_SENSITIVE_CHANNELS} sensitive channels.")).await?;
+            return Ok(());
+        }
+
+        sqlx::query!("INSERT INTO watchlist_channels (channel_id, guild_id) VALUES (?, ?)", db_channel_id, db_guild_id)
+            .execute(&database)
+            .await?;
+    }
+
+    reload_watchlist(ctx, guild_id).await?;
+
+    if removed {
+        msg.reply(ctx, format!("{} is no longer sensitive.", channel_id.mention())).await?;
+    } else {
+        msg.reply(ctx, format!("Watched users posting in {} will now be alerted about.", channel_id.mention())).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::warn;
+
+use crate::utilities::branding::guild_branding;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{DatabaseConnectionContainer, ReqwestClientContainer};
+use crate::utilities::weather::{Units, forecast, geocode, user_units, weather_embed};
+
+const MAX_LOCATION_LENGTH: usize = 100;
+
+#[command]
+#[description = "Shows the current weather and the forecast for the next few days in a place. `weather units` picks whether you see it in metric or imperial units."]
+#[usage = "<location>"]
+#[example = "Berlin"]
+#[sub_commands(weather_units)]
+#[min_args(1)]
+async fn weather(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let location = args.rest().trim();
+
+    if location.chars().count() > MAX_LOCATION_LENGTH {
+        msg.reply(ctx, format!("Locations can be at most {MAX_LOCATION_LENGTH} characters long.")).await?;
+        return Ok(());
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+
+    let units = user_units(&database, msg.author.id).await?;
+
+    let place = match geocode(&client, location).await {
+        Ok(Some(place)) => place,
+        Ok(None) => {
+            msg.reply(ctx, format!("I couldn't find a place called `{location}`.")).await?;
+            return Ok(());
+        }
+        Err(why) => {
+            warn!("Failed to look up the location {location}: {why}");
+            msg.reply(ctx, "I couldn't reach the weather service, please try again later.").await?;
+            return Ok(());
+        }
+    };
+
+    let forecast = match forecast(&client, &place, units).await {
+        Ok(forecast) => forecast,
+        Err(why) => {
+            warn!("Failed to fetch the weather for {location}: {why}");
+            msg.reply(ctx, "I couldn't reach the weather service, please try again later.").await?;
+            return Ok(());
+        }
+    };
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(weather_embed(&guild_branding(ctx, msg.guild_id).await, &place, &forecast, units))).await?;
+
+    Ok(())
+}
+
+#[command("units")]
+#[description = "Sets whether you see the weather in metric or imperial units."]
+#[usage = "<metric|imperial>"]
+#[example = "imperial"]
+#[num_args(1)]
+async fn weather_units(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(units) = Units::parse(args.rest().trim()) else {
+        msg.reply(ctx, "Please choose `metric` or `imperial` units.").await?;
+        return Ok(());
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (user_id, name) = (msg.author.id.get() as i64, units.name());
+
+    sqlx::query!(
+        "INSERT INTO weather_preferences (user_id, units) VALUES (?, ?) ON CONFLICT (user_id) DO UPDATE SET units = excluded.units",
+        user_id,
+        name
+    ).execute(&database).await?;
+
+    msg.reply(ctx, format!("You'll see the weather in {name} units.")).await?;
+
+    Ok(())
+}
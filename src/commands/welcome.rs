@@ -0,0 +1,195 @@
+use serenity::builder::{CreateAllowedMentions, CreateEmbedFooter, CreateMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::branded_embed;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::templates::render_template;
+use crate::utilities::welcome::{Greeting, greeting_context, greeting_settings};
+
+/// Longest welcome or goodbye message template.
+const MAX_GREETING_LENGTH: usize = 1500;
+
+#[command]
+#[only_in(guilds)]
+#[description = "Shows this server's welcome and goodbye messages. They're templates, see `template` for placeholders like `{user}`, `{server}` and `{membercount}`."]
+#[sub_commands(welcome_channel, welcome_message, welcome_goodbyechannel, welcome_goodbyemessage, welcome_test)]
+async fn welcome(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let settings = greeting_settings(&database, msg.guild_id.unwrap()).await?;
+
+    let describe = |greeting: &Greeting| match greeting.channel_id {
+        Some(channel_id) => format!("Sent in {}:\n```{}```", channel_id.mention(), greeting.message),
+        None => "Off".to_string()
+    };
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Welcome and goodbye messages")
+        .description(format!("**Welcome**\n{}\n\n**Goodbye**\n{}", describe(&settings.welcome), describe(&settings.goodbye)))
+        .footer(CreateEmbedFooter::new("Preview them with `welcome test`."));
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command("channel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channel new members are welcomed in. Use `off` to stop welcoming them."]
+#[usage = "<channel|off>"]
+#[example = "#welcome"]
+#[num_args(1)]
+async fn welcome_channel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_channel(ctx, msg, args, false).await
+}
+
+#[command("goodbyechannel")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the channel goodbyes are said in when members leave. Use `off` to stop saying goodbye."]
+#[usage = "<channel|off>"]
+#[example = "#welcome"]
+#[num_args(1)]
+async fn welcome_goodbyechannel(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_channel(ctx, msg, args, true).await
+}
+
+async fn set_channel(ctx: &Context, msg: &Message, mut args: Args, goodbye: bool) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel_id = if args.keyword("off") { None } else { Some(args.channel(ctx, msg).await?.id) };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let db_channel_id = channel_id.map(|channel_id| channel_id.get() as i64);
+    let enabled = channel_id.is_some();
+
+    // turning a greeting off keeps its channel around
+    if goodbye {
+        sqlx::query!(
+            "UPDATE guild_settings SET goodbye_channel_id = COALESCE(?, goodbye_channel_id), goodbye_enabled = ? WHERE guild_id = ?",
+            db_channel_id,
+            enabled,
+            db_guild_id
+        ).execute(&database).await?;
+    } else {
+        sqlx::query!(
+            "UPDATE guild_settings SET welcome_channel_id = COALESCE(?, welcome_channel_id), welcome_enabled = ? WHERE guild_id = ?",
+            db_channel_id,
+            enabled,
+            db_guild_id
+        ).execute(&database).await?;
+    }
+
+    match (channel_id, goodbye) {
+        (Some(channel_id), true) => msg.reply(ctx, format!("Goodbyes will now be said in {}.", channel_id.mention())).await?,
+        (Some(channel_id), false) => msg.reply(ctx, format!("New members will now be welcomed in {}.", channel_id.mention())).await?,
+        (None, true) => msg.reply(ctx, "Goodbyes are now off.").await?,
+        (None, false) => msg.reply(ctx, "Welcome messages are now off.").await?
+    };
+
+    Ok(())
+}
+
+#[command("message")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the template new members are welcomed with. Use `reset` to go back to the default."]
+#[usage = "<template|reset>"]
+#[example = "Welcome to {server}, {user}! You're member #{membercount}."]
+#[min_args(1)]
+async fn welcome_message(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_message(ctx, msg, args, false).await
+}
+
+#[command("goodbyemessage")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[description = "Sets the template goodbyes are said with. Use `reset` to go back to the default."]
+#[usage = "<template|reset>"]
+#[example = "{user.name} has left us. {membercount} members remain."]
+#[min_args(1)]
+async fn welcome_goodbyemessage(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_message(ctx, msg, args, true).await
+}
+
+async fn set_message(ctx: &Context, msg: &Message, args: Args, goodbye: bool) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let message = match args.rest().trim() {
+        "reset" => None,
+        template if template.chars().count() > MAX_GREETING_LENGTH => {
+            msg.reply(ctx, format!("Messages can be at most {MAX_GREETING_LENGTH} characters long.")).await?;
+            return Ok(());
+        }
+        template => Some(template.to_string())
+    };
+
+    if let Some(template) = &message {
+        let context = greeting_context(ctx, guild_id, &msg.author, None);
+
+        if let Err(why) = render_template(template, &context) {
+            msg.reply(ctx, format!("That template couldn't be rendered: {why}.")).await?;
+            return Ok(());
+        }
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    if goodbye {
+        sqlx::query!("UPDATE guild_settings SET goodbye_message = ? WHERE guild_id = ?", message, db_guild_id)
+            .execute(&database)
+            .await?;
+    } else {
+        sqlx::query!("UPDATE guild_settings SET welcome_message = ? WHERE guild_id = ?", message, db_guild_id)
+            .execute(&database)
+            .await?;
+    }
+
+    let greeting = if goodbye { "goodbye" } else { "welcome" };
+
+    match message {
+        Some(_) => msg.reply(ctx, format!("Updated the {greeting} message. Preview it with `welcome test`.")).await?,
+        None => msg.reply(ctx, format!("The {greeting} message is back to the default.")).await?
+    };
+
+    Ok(())
+}
+
+#[command("test")]
+#[only_in(guilds)]
+#[description = "Previews the welcome and goodbye messages as if you'd just joined or left."]
+#[num_args(0)]
+async fn welcome_test(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let guild_id = msg.guild_id.unwrap();
+    let settings = greeting_settings(&database, guild_id).await?;
+    let member = msg.member(ctx).await.ok();
+    let context = greeting_context(ctx, guild_id, &msg.author, member.as_ref());
+
+    let render = |greeting: &Greeting| render_template(&greeting.message, &context)
+        .unwrap_or_else(|why| format!("The template couldn't be rendered: {why}."));
+
+    let embed = branded_embed(ctx, msg.guild_id).await
+        .title("Welcome and goodbye preview")
+        .description(format!("**Welcome**\n{}\n\n**Goodbye**\n{}", render(&settings.welcome), render(&settings.goodbye)));
+
+    // previews never ping anyone
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    msg.channel_id.send_message(ctx, builder).await?;
+
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage, EditMessage};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::utilities::arguments::TypedArgs;
+use crate::utilities::branding::{Branding, branded_embed, guild_branding};
+use crate::utilities::errors::get_data;
+use crate::utilities::games::{claim_channel, record_result, release_channel};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::word_games::{Hangman, LetterGuess, LetterScore, WORDLE_GUESSES, WORDLE_LENGTH, random_wordle_word, score_wordle, wordle_row};
+
+/// How long a game waits for the next guess before it's given up as lost.
+const TURN_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// What the player who started a game can say to end it early.
+const GIVE_UP: &str = "give up";
+
+#[command]
+#[only_in(guilds)]
+#[description = "Starts a game of hangman in this channel. Anyone can guess by sending a letter or the whole word, and everyone who guessed wins or loses together. Whoever started the game can say `give up` to end it."]
+#[num_args(0)]
+async fn hangman(ctx: &Context, msg: &Message) -> CommandResult {
+    if let Some(running) = claim_channel(ctx, msg.channel_id, "hangman").await? {
+        msg.reply(ctx, format!("There's already a game of {running} running in this channel.")).await?;
+        return Ok(());
+    }
+
+    let result = play_hangman(ctx, msg).await;
+
+    release_channel(ctx, msg.channel_id).await?;
+
+    result
+}
+
+#[command]
+#[only_in(guilds)]
+#[description = "Starts a game of wordle in this channel. Guess the five letter word in six tries by sending words: green squares are right letters in the right place, yellow ones are in the word somewhere else. Say `give up` to end the game."]
+#[num_args(0)]
+async fn wordle(ctx: &Context, msg: &Message) -> CommandResult {
+    if let Some(running) = claim_channel(ctx, msg.channel_id, "wordle").await? {
+        msg.reply(ctx, format!("There's already a game of {running} running in this channel.")).await?;
+        return Ok(());
+    }
+
+    let result = play_wordle(ctx, msg).await;
+
+    release_channel(ctx, msg.channel_id).await?;
+
+    result
+}
+
+#[command]
+#[description = "Shows how many games of hangman and wordle someone has won and lost, or you."]
+#[usage = "[user]"]
+#[max_args(1)]
+async fn gamestats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = if args.is_empty() { msg.author.id } else { args.user_id()? };
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let id = user_id.get() as i64;
+
+    let stats = sqlx::query!("SELECT game, wins, losses FROM game_stats WHERE user_id = ? ORDER BY game", id)
+        .fetch_all(&database)
+        .await?;
+
+    let description = if stats.is_empty() {
+        format!("{} hasn't played any games yet.", user_id.mention())
+    } else {
+        format!("{}'s wins and losses.", user_id.mention())
+    };
+
+    let mut embed = branded_embed(ctx, msg.guild_id).await
+        .title("Game stats")
+        .description(description);
+
+    for row in stats {
+        let played = row.wins + row.losses;
+        let rate = if played > 0 { row.wins * 100 / played } else { 0 };
+        embed = embed.field(capitalize(&row.game), format!("{} won, {} lost ({rate}% won)", row.wins, row.losses), true);
+    }
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+async fn play_hangman(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let branding = guild_branding(ctx, msg.guild_id).await;
+    let mut game = Hangman::random();
+    let length = game.word.len();
+    let starter = msg.author.id;
+
+    let mut board = msg.channel_id.send_message(ctx, CreateMessage::new().embed(hangman_embed(&branding, &game, "Send a letter or the whole word to guess."))).await?;
+    let mut players = HashSet::from([starter]);
+
+    let won = loop {
+        let guess = msg.channel_id.await_reply(&ctx.shard)
+            .filter(move |reply| {
+                let content = reply.content.trim().to_lowercase();
+                !reply.author.bot && (is_guess(&content, 1) || is_guess(&content, length) || (reply.author.id == starter && content == GIVE_UP))
+            })
+            .timeout(TURN_TIMEOUT)
+            .await;
+
+        let Some(guess) = guess else {
+            msg.channel_id.say(ctx, format!("Nobody guessed for a while, so the game is over. The word was **{}**.", game.word)).await?;
+            break false;
+        };
+
+        let content = guess.content.trim().to_lowercase();
+
+        if content == GIVE_UP {
+            msg.channel_id.say(ctx, format!("Game over, the word was **{}**.", game.word)).await?;
+            break false;
+        }
+
+        players.insert(guess.author.id);
+
+        let reaction = match content.chars().collect::<Vec<_>>()[..] {
+            [letter] => match game.guess_letter(letter) {
+                LetterGuess::Correct => '✅',
+                LetterGuess::Wrong => '❌',
+                LetterGuess::Repeated => '🔁'
+            },
+            _ => if game.guess_word(&content) { '✅' } else { '❌' }
+        };
+
+        drop(guess.react(ctx, reaction).await);
+
+        let status = if game.is_solved() {
+            format!("{} got it, the word was **{}**!", guess.author.mention(), game.word)
+        } else if game.is_lost() {
+            format!("Out of guesses, the word was **{}**.", game.word)
+        } else {
+            "Send a letter or the whole word to guess.".to_string()
+        };
+
+        drop(board.edit(ctx, EditMessage::new().embed(hangman_embed(&branding, &game, &status))).await);
+
+        if game.is_solved() || game.is_lost() {
+            break game.is_solved();
+        }
+    };
+
+    for player in players {
+        record_result(&database, player, "hangman", won).await?;
+    }
+
+    Ok(())
+}
+
+async fn play_wordle(ctx: &Context, msg: &Message) -> CommandResult {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let branding = guild_branding(ctx, msg.guild_id).await;
+    let answer = random_wordle_word();
+    let mut rows: Vec<String> = Vec::new();
+
+    let mut board = msg.channel_id.send_message(ctx, CreateMessage::new().embed(wordle_embed(&branding, &rows, "Send a five letter word to guess."))).await?;
+
+    let won = loop {
+        let guess = msg.channel_id.await_reply(&ctx.shard)
+            .author_id(msg.author.id)
+            .filter(|reply| {
+                let content = reply.content.trim().to_lowercase();
+                is_guess(&content, WORDLE_LENGTH) || content == GIVE_UP
+            })
+            .timeout(TURN_TIMEOUT)
+            .await;
+
+        let Some(guess) = guess else {
+            msg.channel_id.say(ctx, format!("{} took too long to guess, so the game is over. The word was **{answer}**.", msg.author.mention())).await?;
+            break false;
+        };
+
+        let content = guess.content.trim().to_lowercase();
+
+        if content == GIVE_UP {
+            msg.channel_id.say(ctx, format!("Game over, the word was **{answer}**.")).await?;
+            break false;
+        }
+
+        let scores = score_wordle(&content, answer);
+        rows.push(wordle_row(&content, &scores));
+
+        let solved = scores.iter().all(|score| *score == LetterScore::Right);
+
+        let status = if solved {
+            format!("Solved in {} of {WORDLE_GUESSES}!", rows.len())
+        } else if rows.len() >= WORDLE_GUESSES {
+            format!("Out of guesses, the word was **{answer}**.")
+        } else {
+            format!("{} guesses left.", WORDLE_GUESSES - rows.len())
+        };
+
+        drop(guess.delete(ctx).await);
+        drop(board.edit(ctx, EditMessage::new().embed(wordle_embed(&branding, &rows, &status))).await);
+
+        if solved || rows.len() >= WORDLE_GUESSES {
+            break solved;
+        }
+    };
+
+    record_result(&database, msg.author.id, "wordle", won).await?;
+
+    Ok(())
+}
+
+fn hangman_embed(branding: &Branding, game: &Hangman, status: &str) -> CreateEmbed {
+    branding.embed()
+        .title("Hangman")
+        .description(game.board())
+        .footer(CreateEmbedFooter::new(status))
+}
+
+fn wordle_embed(branding: &Branding, rows: &[String], status: &str) -> CreateEmbed {
+    let board = if rows.is_empty() { "No guesses yet.".to_string() } else { rows.join("\n") };
+
+    branding.embed()
+        .title("Wordle")
+        .description(board)
+        .footer(CreateEmbedFooter::new(status))
+}
+
+/// Whether a message is a guess of exactly `length` letters.
+fn is_guess(content: &str, length: usize) -> bool {
+    content.len() == length && content.chars().all(|character| character.is_ascii_lowercase())
+}
+
+fn capitalize(text: &str) -> String {
+    let mut characters = text.chars();
+
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new()
+    }
+}
@@ -1,18 +1,67 @@
 pub mod event_handler {
+    use std::collections::HashMap;
     use std::sync::Arc;
     use std::sync::atomic::{Ordering, AtomicBool};
     use std::time::Duration;
 
     use serenity::async_trait;
-    use serenity::builder::CreateAllowedMentions;
+    use serenity::builder::{CreateAllowedMentions, CreateMessage};
     use serenity::client::EventHandler;
     use serenity::gateway::ActivityData;
     use serenity::model::channel::Message;
     use serenity::model::gateway::Ready;
-    use serenity::all::{Context, ResumedEvent, Guild, UnavailableGuild, GuildChannel, GuildId};
-    use tracing::info;
+    use serenity::model::guild::audit_log::{Action, MemberAction};
+    use serenity::all::{Context, ResumedEvent, Guild, UnavailableGuild, GuildChannel, GuildId, ChannelId, MessageId, MessageUpdateEvent, GuildMemberUpdateEvent, Reaction, Entitlement, Member, User, UserId, Interaction, Role, RoleId, Emoji, EmojiId, VoiceState, InviteCreateEvent, InviteDeleteEvent, ChannelPinsUpdateEvent, ScheduledEvent};
+    use tracing::{error, info, warn};
 
-    use crate::utilities::global_data::{DatabaseConnectionContainer, GuildSettingsContainer, GuildSettings};
+    use crate::utilities::branding::branded_embed;
+    use crate::utilities::errors::{BotError, error_chain, get_data};
+    use crate::utilities::global_data::{DatabaseConnectionContainer, GuildSettingsContainer, flush_guild_settings_loop, get_or_create_guild_settings, AllowlistContainer, GuildPremium, PremiumContainer, ActivityOverrideContainer};
+    use crate::commands::slash::{register_slash_commands, run_slash_command};
+    use crate::utilities::bot_lists::{BotList, post_stats_loop};
+    use crate::utilities::incidents::monitor_shards;
+    use crate::utilities::counters::refresh_counters_loop;
+    use crate::utilities::reddit::reddit_feed_loop;
+    use crate::utilities::birthdays::birthday_loop;
+    use crate::utilities::scheduler::run_scheduler;
+    use crate::utilities::roles::resume_role_jobs;
+    use crate::utilities::quarantine::handle_quarantine_rejoin;
+    use crate::utilities::autoresponses::handle_auto_responses;
+    use crate::utilities::levels::handle_xp;
+    use crate::utilities::afk::handle_afk;
+    use crate::utilities::sticky::{handle_sticky, reload_sticky};
+    use crate::utilities::nicknames::enforce_nickname;
+    use crate::utilities::sfx::{SFX_ID_PREFIX, handle_sfx_button};
+    use crate::utilities::tts::handle_tts_reader;
+    use crate::utilities::chain_games::{handle_chain_game, reload_chain_game};
+    use crate::utilities::verification::{VERIFY_ID, forget_verification, handle_captcha_dm, handle_verification_join, handle_verify_button};
+    use crate::utilities::automod::handle_automod;
+    use crate::utilities::filters::{handle_filters, handle_filters_edit};
+    use crate::utilities::links::{handle_links, handle_links_edit};
+    use crate::utilities::media_rules::{handle_media_rules, reload_media_rules};
+    use crate::utilities::antispam::handle_antispam;
+    use crate::utilities::antiraid::handle_member_join;
+    use crate::utilities::join_gate::handle_join_gate;
+    use crate::utilities::welcome::{send_goodbye, send_welcome};
+    use crate::utilities::autoroles::{handle_autoroles_join, handle_autoroles_screening};
+    use crate::utilities::role_menus::{ROLE_MENU_ID, handle_role_menu};
+    use crate::utilities::polls::{POLL_ID_PREFIX, handle_poll_vote};
+    use crate::utilities::tickets::{TICKET_OPEN_ID, handle_ticket_open};
+    use crate::utilities::reports::{REPORT_ID_PREFIX, handle_report_action};
+    use crate::utilities::blacklist::is_blacklisted;
+    use crate::utilities::watchlist::{handle_watchlist_join, handle_watchlist_message, handle_watchlist_nickname, reload_watchlist};
+    use crate::utilities::modmail::{handle_modmail_dm, handle_modmail_reply};
+    use crate::utilities::starboard::{handle_star_reaction, starred_guild, update_starboard};
+    use crate::utilities::pin_archive::handle_pins_update;
+    use crate::utilities::message_log::{cache_message, log_message_delete, log_message_edit};
+    use crate::utilities::events::{handle_event_create, handle_event_delete, handle_event_update};
+    use crate::utilities::invites::{cache_guild_invites, forget_guild_invites, handle_invite_create, handle_invite_delete, handle_invite_join};
+    use crate::utilities::voice::{flush_voice_time_loop, handle_voice_state, start_voice_sessions};
+    use crate::utilities::server_log::{log_channel_create, log_channel_delete, log_channel_update, log_emojis_update, log_member_roles, log_role_create, log_role_delete, log_role_update, log_webhook_update};
+    use crate::utilities::shutdown::shutdown_on_ctrl_c;
+    use crate::utilities::playback::leave_if_alone;
+    use crate::utilities::analytics::{record_message, record_member_change, flush_activity_loop};
+    use crate::utilities::premium::{PremiumTier, set_guild_premium, clear_guild_premium};
     pub struct Handler {
         pub database: sqlx::SqlitePool,
         pub is_loop_running: AtomicBool,
@@ -28,23 +77,62 @@ pub mod event_handler {
         async fn message(&self, _ctx: Context, msg: Message) {
             // TODO: add advanced command handler + database connection
 
+            cache_message(&_ctx, &msg).await;
+
             // ignore all bots, including the bot itself
             if msg.author.bot {
                 return;
             }
 
+            let blacklisted = is_blacklisted(&_ctx, Some(msg.author.id), msg.guild_id).await;
+
+            // DMs to the bot are captcha answers or modmail
+            if msg.guild_id.is_none() {
+                if !blacklisted && !handle_captcha_dm(&_ctx, &msg).await {
+                    handle_modmail_dm(&_ctx, &msg).await;
+                }
+
+                return;
+            }
+
+            if !blacklisted {
+                record_message(&_ctx, &msg).await;
+                handle_watchlist_message(&_ctx, &msg).await;
+            }
+
+            // removed messages don't get auto-responses, neither do modmail replies
+            if handle_antispam(&_ctx, &msg).await || handle_automod(&_ctx, &msg).await || handle_filters(&_ctx, &msg).await || handle_links(&_ctx, &msg).await || handle_media_rules(&_ctx, &msg).await || handle_chain_game(&_ctx, &msg).await || handle_modmail_reply(&_ctx, &msg).await {
+                return;
+            }
+
+            // blacklisted users are still moderated, but the bot otherwise ignores them
+            if blacklisted {
+                return;
+            }
+
+            handle_auto_responses(&_ctx, &msg).await;
+            handle_xp(&_ctx, &msg).await;
+            handle_afk(&_ctx, &msg).await;
+            handle_sticky(&_ctx, &msg).await;
+            handle_tts_reader(&_ctx, &msg).await;
+
             // trim the end to make it easier for mobile users
             let content = msg.content.trim_end();
 
             if content == "<@!1183487567094632638>" || content == "<@1183487567094632638>" {
-                let prefix = {
-                    let data = _ctx.data.read().await;
-                    let guild_settings = data.get::<GuildSettingsContainer>().unwrap();
-                    let pf = guild_settings.read().await;
-                    pf.get(&msg.guild_id.unwrap().get()).unwrap().prefix.clone()
+                // settings that can't be loaded fall back to the default prefix
+                let prefix = match msg.guild_id {
+                    Some(guild_id) => match get_or_create_guild_settings(&_ctx, guild_id).await {
+                        Ok(settings) => settings.read().await.prefix.clone(),
+                        Err(why) => {
+                            error!("Failed to load settings for guild {guild_id}: {}", error_chain(&why));
+                            "-".to_string()
+                        }
+                    },
+                    None => "-".to_string()
                 };
 
-                let embed = serenity::builder::CreateEmbed::new()
+                let embed = branded_embed(&_ctx, msg.guild_id).await
                 .title("**Hello!**")
                 .description(format!("```To see the list of commands type {}help```", prefix));
 
@@ -53,79 +141,289 @@ pub mod event_handler {
                 .allowed_mentions(CreateAllowedMentions::new().users(vec![msg.author.id]))
                 .reference_message(&msg);
 
-                msg.channel_id.send_message(&_ctx, builder).await.unwrap();
+                if let Err(why) = msg.channel_id.send_message(&_ctx, builder).await {
+                    warn!("Couldn't answer a mention in channel {}: {why}", msg.channel_id);
+                }
+            }
+        }
+
+        async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+            let (user_id, guild_id) = match &interaction {
+                Interaction::Command(command) => (Some(command.user.id), command.guild_id),
+                Interaction::Component(component) => (Some(component.user.id), component.guild_id),
+                _ => (None, None)
+            };
+
+            if is_blacklisted(&ctx, user_id, guild_id).await {
+                return;
+            }
+
+            // other component interactions are handled by the collectors waiting on them
+            match interaction {
+                Interaction::Command(command) => run_slash_command(&ctx, &command).await,
+                Interaction::Component(component) if component.data.custom_id == ROLE_MENU_ID => handle_role_menu(&ctx, &component).await,
+                Interaction::Component(component) if component.data.custom_id.starts_with(POLL_ID_PREFIX) => handle_poll_vote(&ctx, &component).await,
+                Interaction::Component(component) if component.data.custom_id == TICKET_OPEN_ID => handle_ticket_open(&ctx, &component).await,
+                Interaction::Component(component) if component.data.custom_id == VERIFY_ID => handle_verify_button(&ctx, &component).await,
+                Interaction::Component(component) if component.data.custom_id.starts_with(SFX_ID_PREFIX) => handle_sfx_button(&ctx, &component).await,
+                Interaction::Component(component) if component.data.custom_id.starts_with(REPORT_ID_PREFIX) => handle_report_action(&ctx, &component).await,
+                _ => {}
+            }
+        }
+
+        async fn message_update(&self, ctx: Context, _: Option<Message>, new: Option<Message>, event: MessageUpdateEvent) {
+            log_message_edit(&ctx, &event).await;
+
+            if !handle_filters_edit(&ctx, new.as_ref(), &event).await {
+                handle_links_edit(&ctx, new.as_ref(), &event).await;
+            }
+        }
+
+        async fn message_delete(&self, ctx: Context, channel_id: ChannelId, deleted_message_id: MessageId, guild_id: Option<GuildId>) {
+            log_message_delete(&ctx, channel_id, deleted_message_id, guild_id).await;
+        }
+
+        async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+            if !is_blacklisted(&ctx, reaction.user_id, reaction.guild_id).await {
+                handle_star_reaction(&ctx, &reaction).await;
+            }
+        }
+
+        async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+            if !is_blacklisted(&ctx, reaction.user_id, reaction.guild_id).await {
+                handle_star_reaction(&ctx, &reaction).await;
+            }
+        }
+
+        async fn reaction_remove_emoji(&self, ctx: Context, reaction: Reaction) {
+            handle_star_reaction(&ctx, &reaction).await;
+        }
+
+        async fn reaction_remove_all(&self, ctx: Context, channel_id: ChannelId, message_id: MessageId) {
+            // only messages already on the starboard can lose anything
+            if let Some(guild_id) = starred_guild(&ctx, message_id).await {
+                update_starboard(&ctx, guild_id, channel_id, message_id).await;
+            }
+        }
+
+        async fn channel_pins_update(&self, ctx: Context, pin: ChannelPinsUpdateEvent) {
+            handle_pins_update(&ctx, &pin).await;
+        }
+
+        async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+            record_member_change(&ctx, new_member.guild_id, true).await;
+
+            let blacklisted = is_blacklisted(&ctx, Some(new_member.user.id), Some(new_member.guild_id)).await;
+
+            if !blacklisted {
+                handle_invite_join(&ctx, &new_member).await;
+                handle_watchlist_join(&ctx, &new_member).await;
+            }
+
+            // members removed as raiders, held back by the join gate or still quarantined aren't welcomed
+            if !handle_member_join(&ctx, &new_member).await && !handle_join_gate(&ctx, &new_member).await && !handle_quarantine_rejoin(&ctx, &new_member).await {
+                // blacklisted members still have to verify and keep to the nickname rules, but
+                // get no autoroles or welcome; members who have to verify get their autoroles
+                // once they do
+                if !handle_verification_join(&ctx, &new_member).await && !blacklisted {
+                    handle_autoroles_join(&ctx, &new_member).await;
+                }
+
+                if !blacklisted {
+                    send_welcome(&ctx, &new_member).await;
+                }
+
+                enforce_nickname(&ctx, new_member.guild_id, &new_member.user, new_member.nick.as_deref()).await;
             }
         }
 
+        async fn guild_member_update(&self, ctx: Context, old: Option<Member>, _: Option<Member>, event: GuildMemberUpdateEvent) {
+            log_member_roles(&ctx, old.as_ref(), &event).await;
+
+            if !is_blacklisted(&ctx, Some(event.user.id), Some(event.guild_id)).await {
+                handle_autoroles_screening(&ctx, old.as_ref(), &event).await;
+                handle_watchlist_nickname(&ctx, old.as_ref(), &event).await;
+            }
+
+            // role changes and the like leave names alone
+            if old.is_none_or(|old| old.nick != event.nick || old.user.name != event.user.name || old.user.global_name != event.user.global_name) {
+                enforce_nickname(&ctx, event.guild_id, &event.user, event.nick.as_deref()).await;
+            }
+        }
+
+        async fn guild_member_removal(&self, ctx: Context, guild_id: GuildId, user: User, _: Option<Member>) {
+            record_member_change(&ctx, guild_id, false).await;
+
+            if !is_blacklisted(&ctx, Some(user.id), Some(guild_id)).await {
+                send_goodbye(&ctx, guild_id, &user).await;
+            }
+
+            forget_verification(&ctx, guild_id, user.id).await;
+        }
+
         async fn thread_create(&self, ctx: Context, thread: GuildChannel) {
             if let Err(err) = thread.id.join_thread(ctx.http).await {
                 let thread_id = thread.id;
                 info!("Failed to succesfully join thread (ID: {thread_id}): {err}")
             } else {
                 let name = &thread.name;
-                let guild = &thread.guild(&ctx.cache).unwrap().name;
+                let guild = thread.guild(&ctx.cache).map_or_else(|| thread.guild_id.to_string(), |guild| guild.name.clone());
                 let id = thread.id.get();
                 info!("Joined new thread: {name} (Server: {guild}, ID: {id})")
             }
         }
 
-        async fn guild_create(&self, ctx: Context, guild: Guild, _: Option<bool>) {
-            // write into database and hashmap
-            info!("Connected to guild: {}", guild.name);
-            info!("Guild ID: {}", guild.id);
-            info!("Guild Owner ID: {}", guild.owner_id);
-            info!("Guild Members: {}", guild.member_count);
+        async fn invite_create(&self, ctx: Context, data: InviteCreateEvent) {
+            handle_invite_create(&ctx, &data).await;
+        }
 
-            let data = ctx.data.read().await;
-            let database = data.get::<DatabaseConnectionContainer>().unwrap().clone();
-            let (guild_id, owner_id) = {
-                let guild_id = i64::from(guild.id);
-                let owner_id = i64::from(guild.owner_id);
+        async fn invite_delete(&self, ctx: Context, data: InviteDeleteEvent) {
+            handle_invite_delete(&ctx, &data).await;
+        }
 
-                (guild_id, owner_id)
-            };
+        async fn guild_scheduled_event_create(&self, ctx: Context, event: ScheduledEvent) {
+            handle_event_create(&ctx, &event).await;
+        }
 
-            sqlx::query!(
-                "INSERT INTO guild_settings (
-                    guild_id,
-                    prefix,
-                    owner_id
-                ) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
-                guild_id,
-                "-",
-                owner_id
-            ).execute(&database).await.unwrap();
+        async fn guild_scheduled_event_update(&self, ctx: Context, event: ScheduledEvent) {
+            handle_event_update(&ctx, &event).await;
+        }
+
+        async fn guild_scheduled_event_delete(&self, ctx: Context, event: ScheduledEvent) {
+            handle_event_delete(&ctx, &event).await;
+        }
 
-            let owner_id_u64 = owner_id as u64;
-            let guild_id_u64 = guild_id as u64;
+        async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+            handle_voice_state(&ctx, old.as_ref(), &new).await;
 
-            let data_to_set = GuildSettings {
-                prefix: "-".to_string(),
-                owner_id: owner_id_u64,
-                mute_type: "timeout".to_string(),
-                mute_role: 0
+            // someone left or moved, which may leave the bot playing to an empty channel
+            if let (Some(guild_id), Some(old)) = (new.guild_id, &old) {
+                if old.channel_id.is_some() && old.channel_id != new.channel_id {
+                    leave_if_alone(&ctx, guild_id).await;
+                }
+            }
+        }
+
+        async fn guild_role_create(&self, ctx: Context, new: Role) {
+            log_role_create(&ctx, &new).await;
+        }
+
+        async fn guild_role_delete(&self, ctx: Context, guild_id: GuildId, removed_role_id: RoleId, removed_role: Option<Role>) {
+            log_role_delete(&ctx, guild_id, removed_role_id, removed_role.as_ref()).await;
+        }
+
+        async fn guild_role_update(&self, ctx: Context, old: Option<Role>, new: Role) {
+            log_role_update(&ctx, old.as_ref(), &new).await;
+        }
+
+        async fn guild_emojis_update(&self, ctx: Context, guild_id: GuildId, current_state: HashMap<EmojiId, Emoji>) {
+            log_emojis_update(&ctx, guild_id, &current_state).await;
+        }
+
+        async fn webhook_update(&self, ctx: Context, guild_id: GuildId, channel_id: ChannelId) {
+            log_webhook_update(&ctx, guild_id, channel_id).await;
+        }
+
+        async fn channel_create(&self, ctx: Context, channel: GuildChannel) {
+            log_channel_create(&ctx, &channel).await;
+        }
+
+        async fn channel_update(&self, ctx: Context, old: Option<GuildChannel>, new: GuildChannel) {
+            log_channel_update(&ctx, old.as_ref(), &new).await;
+        }
+
+        async fn channel_delete(&self, ctx: Context, channel: GuildChannel, _: Option<Vec<Message>>) {
+            log_channel_delete(&ctx, &channel).await;
+
+            // forget counters, sticky messages, locks, media rules, games and watched channels whose channel was deleted by hand
+            let database = match get_data::<DatabaseConnectionContainer>(&ctx).await {
+                Ok(database) => database,
+                Err(why) => {
+                    error!("Couldn't forget counters, sticky messages, locks, media rules, games and watched channels of deleted channel {}: {why}", channel.id);
+                    return;
+                }
             };
 
-            {
-                let mut guild_settings = data.get::<GuildSettingsContainer>().unwrap().write().await;
-                guild_settings.insert(guild_id_u64, data_to_set);
+            let channel_id = channel.id.get() as i64;
+
+            if let Err(err) = sqlx::query!("DELETE FROM counter_channels WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove counter for deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM sticky_messages WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove sticky message for deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = reload_sticky(&ctx, channel.id).await {
+                error!("Failed to forget sticky message of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM locked_channels WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove lock of deleted channel {}: {err}", channel.id);
             }
 
-            info!("Guild settings set complete for guild {}", guild.name);
+            if let Err(err) = sqlx::query!("DELETE FROM lockdown_channels WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove deleted channel {} from its lockdown: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM media_rules WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove media rules of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = reload_media_rules(&ctx, channel.id).await {
+                error!("Failed to forget media rules of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM chain_games WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove the game of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM chain_game_words WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove the used words of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = reload_chain_game(&ctx, channel.id).await {
+                error!("Failed to forget the game of deleted channel {}: {err}", channel.id);
+            }
+
+            if let Err(err) = sqlx::query!("DELETE FROM watchlist_channels WHERE channel_id = ?", channel_id).execute(&database).await {
+                error!("Failed to remove deleted channel {} from its watchlist: {err}", channel.id);
+            }
+
+            if let Err(err) = reload_watchlist(&ctx, channel.guild_id).await {
+                error!("Failed to reload the watchlist of guild {} after channel {} was deleted: {err}", channel.guild_id, channel.id);
+            }
         }
 
-        async fn guild_delete(&self, ctx: Context, _: UnavailableGuild, g: Option<Guild>) {
-            let guild = g.unwrap();
-            info!("Left guild: {}", guild.name);
+        async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
             // write into database and hashmap
-            {
-                let data = ctx.data.read().await;
-                let database = data.get::<DatabaseConnectionContainer>().unwrap().clone();
-                let guild_id = i64::from(guild.id);
-                sqlx::query!(
-                    "DELETE FROM guild_settings WHERE guild_id = ?",
-                    guild_id
-                ).execute(&database).await.unwrap();
+            info!("Connected to guild: {}", guild.name);
+            info!("Guild ID: {}", guild.id);
+            info!("Guild Owner ID: {}", guild.owner_id);
+            info!("Guild Members: {}", guild.member_count);
+
+            // Discord only says whether the bot just joined when the cache knew the guild's list
+            match register_guild(&ctx, &guild, is_new.unwrap_or(false)).await {
+                Ok(()) => info!("Guild settings set complete for guild {}", guild.name),
+                Err(why) => error!("Failed to set up settings for guild {}: {}", guild.id, error_chain(&why))
+            }
+
+            // to tell which invite members join with
+            cache_guild_invites(&ctx, guild.id).await;
+        }
+
+        async fn guild_delete(&self, ctx: Context, incomplete: UnavailableGuild, g: Option<Guild>) {
+            // guilds going unavailable during an outage haven't been left
+            if incomplete.unavailable {
+                warn!("Guild {} became unavailable", incomplete.id);
+                return;
+            }
+
+            let name = g.map_or_else(|| incomplete.id.to_string(), |guild| guild.name);
+            info!("Left guild: {name}");
+
+            if let Err(why) = forget_guild(&ctx, incomplete.id).await {
+                error!("Failed to remove settings of guild {}: {}", incomplete.id, error_chain(&why));
             }
         }
 
@@ -138,22 +436,40 @@ pub mod event_handler {
             let http = &context.http;
 
             let api_version = ready.version;
-            let bot_gateway = http.get_bot_gateway().await.unwrap();
-            let bot_owner = http.get_current_application_info().await.unwrap().owner.expect("Couldn't get bot owner");
-            let t_sessions = bot_gateway.session_start_limit.total;
-            let r_sessions = bot_gateway.session_start_limit.remaining;
-            let shard_info = ready.shard.unwrap();
+            let (shard_id, shard_total) = ready.shard.map_or((context.shard_id, 1), |shard_info| (shard_info.id, shard_info.total));
 
             info!("Successfully logged into Discord as the following user:");
             info!("Bot username: {}", ready.user.tag());
             info!("Bot user ID: {}", ready.user.id);
-            info!("Bot owner: {}", bot_owner.tag());
+
+            // these are only logged, so failing to fetch them shouldn't stop the shard
+            match http.get_current_application_info().await {
+                Ok(info) => match info.owner {
+                    Some(owner) => info!("Bot owner: {}", owner.tag()),
+                    None => warn!("The application has no owner")
+                },
+                Err(why) => warn!("Couldn't fetch the application info: {why}")
+            }
 
             let guild_count = ready.guilds.len();
 
-            info!("Connected to shard {} out of a total of {} shards.", shard_info.id, shard_info.total);
-            info!("Connected to the Discord API (version {api_version}) with {r_sessions}/{t_sessions} sessions remaining.");
+            info!("Connected to shard {shard_id} out of a total of {shard_total} shards.");
+
+            match http.get_bot_gateway().await {
+                Ok(bot_gateway) => info!(
+                    "Connected to the Discord API (version {api_version}) with {}/{} sessions remaining.",
+                    bot_gateway.session_start_limit.remaining,
+                    bot_gateway.session_start_limit.total
+                ),
+                Err(why) => warn!("Connected to the Discord API (version {api_version}), but couldn't fetch the session limits: {why}")
+            }
+
             info!("Connected to and serving a total of {guild_count} guild(s).");
+
+            // slash commands are global, so registering them once from the first shard is enough
+            if shard_id.0 == 0 {
+                register_slash_commands(&context).await;
+            }
         }
 
         async fn cache_ready(&self, ctx: Context, guilds: Vec<GuildId>) {
@@ -162,6 +478,9 @@ pub mod event_handler {
             // It's safe to clone Context, but Arc is cheaper for this use case.
             // Untested claim, just theoretically. :P
             let ctx = Arc::new(ctx);
+
+            // members who were already in voice channels are counted from now on
+            start_voice_sessions(&ctx, &guilds).await;
     
             // We need to check that the loop is not already running when this event triggers, as this
             // event triggers every time the bot enters or leaves a guild, along every time the ready
@@ -174,22 +493,229 @@ pub mod event_handler {
                 // And of course, we can run more than one thread at different timings.
                 let ctx2 = Arc::clone(&ctx);
                 tokio::spawn(async move {
+                    let activity_override = get_data::<ActivityOverrideContainer>(&ctx2).await.ok();
+
                     loop {
-                        set_activity(&ctx2, guilds.len());
+                        // an activity set by an owner stays until they reset it
+                        let overridden = match &activity_override {
+                            Some(activity_override) => activity_override.read().await.is_some(),
+                            None => false
+                        };
+
+                        if !overridden {
+                            set_activity(&ctx2, guilds.len());
+                        }
+
                         tokio::time::sleep(Duration::from_secs(3)).await;
                     }
                 });
     
+                // Post guild and shard counts to every bot list with a configured token.
+                for list in BotList::ALL {
+                    tokio::spawn(post_stats_loop(Context::clone(&ctx), list));
+                }
+
+                // Declare incidents automatically when shards stay disconnected.
+                tokio::spawn(monitor_shards(Context::clone(&ctx)));
+
+                // Write buffered server activity into the stats tables.
+                tokio::spawn(flush_activity_loop(Context::clone(&ctx)));
+                tokio::spawn(flush_guild_settings_loop(Context::clone(&ctx)));
+
+                // Periodically count time spent in voice so far.
+                tokio::spawn(flush_voice_time_loop(Context::clone(&ctx)));
+
+                // Write everything buffered out before stopping on ctrl+c.
+                tokio::spawn(shutdown_on_ctrl_c(Context::clone(&ctx)));
+
+                // Keep member count channels up to date.
+                tokio::spawn(refresh_counters_loop(Context::clone(&ctx)));
+
+                // Mirror new posts from watched subreddits.
+                tokio::spawn(reddit_feed_loop(Context::clone(&ctx)));
+
+                // Announce birthdays as each server's day starts.
+                tokio::spawn(birthday_loop(Context::clone(&ctx)));
+
+                // Run scheduled jobs, such as recurring channel messages.
+                tokio::spawn(run_scheduler(Context::clone(&ctx)));
+
+                // Pick bulk role changes back up where they were when the bot stopped.
+                tokio::spawn(resume_role_jobs(Context::clone(&ctx)));
+
                 // Now that the loop is running, we set the bool to true
                 self.is_loop_running.swap(true, Ordering::Relaxed);
             }
         }
 
+        async fn entitlement_create(&self, ctx: Context, entitlement: Entitlement) {
+            apply_entitlement(&ctx, entitlement).await;
+        }
+
+        async fn entitlement_update(&self, ctx: Context, entitlement: Entitlement) {
+            apply_entitlement(&ctx, entitlement).await;
+        }
+
+        async fn entitlement_delete(&self, ctx: Context, entitlement: Entitlement) {
+            let Some(guild_id) = entitlement.guild_id else {
+                return;
+            };
+
+            // only revoke premium that was granted by this entitlement, not manual grants
+            let granted_by_entitlement = match get_data::<PremiumContainer>(&ctx).await {
+                Ok(premium) => premium.read().await.get(&guild_id.get()).is_some_and(|grant| grant.entitlement_id == Some(entitlement.id.get())),
+                Err(why) => {
+                    error!("Couldn't check premium of guild {guild_id}: {why}");
+                    return;
+                }
+            };
+
+            if granted_by_entitlement {
+                match clear_guild_premium(&ctx, guild_id).await {
+                    Ok(()) => info!("Removed premium from guild {guild_id} after entitlement {} was deleted", entitlement.id),
+                    Err(err) => error!("Failed to remove premium from guild {guild_id}: {err}")
+                }
+            }
+        }
+
         async fn resume(&self, _: Context, _: ResumedEvent) {
             info!("Resumed!");
         }
     }
 
+    // Stores default settings for a guild the bot joined, unless it's blacklisted or isn't
+    // allowlisted, in which case the guild is left instead.
+    async fn register_guild(ctx: &Context, guild: &Guild, is_new: bool) -> Result<(), BotError> {
+        if is_blacklisted(ctx, None, Some(guild.id)).await {
+            match guild.id.leave(&ctx.http).await {
+                Ok(()) => info!("Left guild {} (ID: {}) as it is blacklisted", guild.name, guild.id),
+                Err(err) => error!("Failed to leave blacklisted guild {} (ID: {}): {err}", guild.name, guild.id)
+            }
+
+            return Ok(());
+        }
+
+        let allowed = {
+            let allowlist = get_data::<AllowlistContainer>(ctx).await?;
+            let allowlist = allowlist.read().await;
+
+            !allowlist.enabled || allowlist.guilds.contains(&guild.id.get())
+        };
+
+        if !allowed {
+            leave_unlisted_guild(ctx, guild, is_new).await;
+            return Ok(());
+        }
+
+        let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+        let (guild_id, owner_id) = (i64::from(guild.id), i64::from(guild.owner_id));
+
+        sqlx::query!(
+            "INSERT INTO guild_settings (
+                guild_id,
+                prefix,
+                owner_id
+            ) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+            guild_id,
+            "-",
+            owner_id
+        ).execute(&database).await?;
+
+        // the settings are loaded into the cache the first time they're used, so existing ones
+        // aren't replaced with defaults
+        Ok(())
+    }
+
+    // Deletes the settings of a guild the bot left.
+    async fn forget_guild(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+        let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+        let db_guild_id = i64::from(guild_id);
+
+        sqlx::query!("DELETE FROM guild_settings WHERE guild_id = ?", db_guild_id)
+            .execute(&database)
+            .await?;
+
+        get_data::<GuildSettingsContainer>(ctx).await?.remove(guild_id.get()).await;
+        forget_guild_invites(ctx, guild_id).await;
+
+        Ok(())
+    }
+
+    // Grants (or refreshes) the premium tier tied to a guild subscription entitlement.
+    async fn apply_entitlement(ctx: &Context, entitlement: Entitlement) {
+        let (Some(guild_id), Some(tier)) = (entitlement.guild_id, PremiumTier::from_sku(entitlement.sku_id)) else {
+            return;
+        };
+
+        let premium = GuildPremium {
+            tier,
+            source: "entitlement".to_string(),
+            entitlement_id: Some(entitlement.id.get()),
+            expires_at: entitlement.ends_at.map(|ends_at| *ends_at)
+        };
+
+        match set_guild_premium(ctx, guild_id, premium).await {
+            Ok(()) => info!("Guild {guild_id} is now on the {} tier through entitlement {}", tier.name(), entitlement.id),
+            Err(err) => error!("Failed to apply entitlement {} to guild {guild_id}: {err}", entitlement.id)
+        }
+    }
+
+    // Sends a short explanation before leaving a guild that isn't allowlisted, preferring a DM to
+    // whoever added the bot and falling back to the system channel, then a DM to the guild owner.
+    // Leaves a guild that isn't allowlisted, telling whoever added the bot why. Guilds the bot
+    // was already in, e.g. when the allowlist was turned on since, only get a note to the owner
+    // rather than a message in their system channel.
+    async fn leave_unlisted_guild(ctx: &Context, guild: &Guild, is_new: bool) {
+        let notice = if is_new {
+            format!(
+                "Hello! Thanks for adding me to **{}**, but this is a private instance of the bot and that server isn't on its \
+                allowlist, so I'll be leaving now. Please contact the bot's owner if you think this is a mistake.",
+                guild.name
+            )
+        } else {
+            format!(
+                "Hello! This instance of the bot is now private, and **{}** isn't on its allowlist, so I've left it. Please \
+                contact the bot's owner if you think this is a mistake.",
+                guild.name
+            )
+        };
+
+        let mut sent = false;
+
+        if is_new {
+            if let Some(inviter) = guild_inviter(ctx, guild.id).await {
+                sent = inviter.direct_message(&ctx.http, CreateMessage::new().content(&notice)).await.is_ok();
+            }
+
+            if !sent {
+                if let Some(channel_id) = guild.system_channel_id {
+                    sent = channel_id.send_message(&ctx.http, CreateMessage::new().content(&notice)).await.is_ok();
+                }
+            }
+        }
+
+        if !sent {
+            if let Err(err) = guild.owner_id.direct_message(&ctx.http, CreateMessage::new().content(&notice)).await {
+                warn!("Couldn't notify the owner of guild {} before leaving: {err}", guild.id);
+            }
+        }
+
+        match guild.id.leave(&ctx.http).await {
+            Ok(()) => info!("Left guild {} (ID: {}) as it is not on the allowlist", guild.name, guild.id),
+            Err(err) => error!("Failed to leave non-allowlisted guild {} (ID: {}): {err}", guild.name, guild.id)
+        }
+    }
+
+    // Who added the bot to a guild, if the bot can read its audit log.
+    async fn guild_inviter(ctx: &Context, guild_id: GuildId) -> Option<UserId> {
+        let bot_id = ctx.cache.current_user().id;
+        let logs = guild_id.audit_logs(&ctx.http, Some(Action::Member(MemberAction::BotAdd)), None, None, Some(10)).await.ok()?;
+
+        logs.entries.into_iter()
+            .find(|entry| entry.target_id.is_some_and(|id| id.get() == bot_id.get()))
+            .map(|entry| entry.user_id)
+    }
+
     fn set_activity(ctx: &Context, guild_count: usize) {
         let presence = format!("Monitoring a total of {guild_count} guilds | -help");
         
@@ -1,67 +1,264 @@
+use std::time::Duration;
+
 use serenity::{
-    client::Context,
-    framework::standard::{macros::hook, CommandResult, DispatchError},
-    model::channel::Message
+    builder::{CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage},
+    client::{Context, FullEvent},
+    framework::{Framework, standard::{macros::hook, Command, CommandGroup, CommandResult, DispatchError}},
+    model::{application::ButtonStyle, channel::Message}
 };
 use tracing::error;
 
+use crate::COMMAND_GROUPS;
+use crate::commands::custom_commands::{custom_command_exists, run_custom_command};
+use crate::commands::scripts::{guild_script_exists, run_guild_script};
+use crate::commands::tags::{run_guild_tag, tag_exists};
+use crate::utilities::branding::branded_embed;
+use crate::utilities::fuzzy::levenshtein;
+use crate::utilities::command_permissions::member_allowed;
+use crate::utilities::command_rules::{command_allowed, find_root};
+use crate::utilities::cooldowns::{check_cooldown, cooldown_remaining};
+use crate::utilities::errors::{BotError, error_chain, get_data, user_message};
+use crate::utilities::global_data::{CommandCountsContainer, DatabaseConnectionContainer, FrameworkContainer, GuildSettingsContainer, get_or_create_guild_settings};
+use crate::utilities::i18n::{DEFAULT_LANGUAGE, guild_language, translate};
+
+/// Suggestions further away than this are more likely to be noise than typos.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+#[hook]
+pub async fn before(context: &Context, message: &Message, command: &str) -> bool {
+    command_usable(context, message, find_root(command, &message.content), command, None).await
+}
+
+/// Checks a server's command rules, role permissions and cooldowns for a command, replying with
+/// why it can't be used. `cooldown` is the command whose cooldown it counts against, and `shown`
+/// the name used in replies instead of the command's own.
+async fn command_usable(context: &Context, message: &Message, root: Option<(&CommandGroup, &Command)>, cooldown: &str, shown: Option<&str>) -> bool {
+    let language = guild_language(context, message.guild_id).await;
+
+    if let Some((group, root)) = root {
+        let name = shown.unwrap_or(root.options.names[0]);
+
+        if !command_allowed(context, message, group, root).await {
+            send_error(context, message, translate(&language, "error.not_here", &[("command", name)])).await;
+            return false;
+        }
+
+        if !member_allowed(context, message, root.options.names[0]).await {
+            send_error(context, message, translate(&language, "error.missing_role", &[("command", name)])).await;
+            return false;
+        }
+    }
+
+    let Some(remaining) = check_cooldown(context, message, cooldown).await else {
+        return true;
+    };
+
+    send_error(context, message, translate(&language, "error.cooldown", &[("command", shown.unwrap_or(cooldown)), ("remaining", &cooldown_remaining(remaining))])).await;
+
+    false
+}
+
 #[hook]
 pub async fn after(context: &Context, message: &Message, command: &str, error: CommandResult) {
-    if let Err(why) = &error {
-        error!("Error while running command {}", &command);
-        error!("{:?}", &error);
-        if message.channel_id.say(context, why.to_string()).await.is_err() {
-            let channel = &message.channel_id.name(&context).await.unwrap();
-            error!("Unable to send messages to channel {}", &channel);
-        };
+    match get_data::<CommandCountsContainer>(context).await {
+        Ok(command_counts) => *command_counts.lock().await.entry(command.to_string()).or_default() += 1,
+        Err(why) => error!("Couldn't count a use of command {command}: {why}")
     }
+
+    let Err(why) = error else {
+        return;
+    };
+
+    error!("Error while running command {command} for user {}: {}", message.author.id, error_chain(why.as_ref()));
+
+    let language = guild_language(context, message.guild_id).await;
+    send_error(context, message, user_message(why.as_ref(), &language)).await;
 }
 
 #[hook]
 pub async fn dispatch_error(context: &Context, message: &Message, error: DispatchError, command: &str) {
-    let error_response: String;
-    match error {
-        DispatchError::Ratelimited(secs) => {
-            error_response = format!("This command has been rate limited. Try again in {} second(s).", secs.as_secs());
-            drop(message.channel_id.say(context, error_response).await);
-        }
-        DispatchError::CommandDisabled => {
-            error_response = format!("The `{command}` command has been disabled and cannot be used.");
-            drop(message.channel_id.say(context, error_response).await);
-        }
-        DispatchError::OnlyForDM => {
-            error_response = "This command is only available in Direct Messages.".to_string();
-            drop(message.channel_id.say(context, error_response).await);
-        }
-        DispatchError::OnlyForGuilds => {
-            error_response = "This command is only available in guilds.".to_string();
-            drop(message.channel_id.say(context, error_response).await);
-        }
-        DispatchError::OnlyForOwners => {
-            error_response = "This command is restricted to bot owners.".to_string();
-            drop(message.channel_id.say(context, error_response).await);
-        }
-        DispatchError::LackingRole => {
-            error_response = "You lack the necessary role to use this command.".to_string();
-            drop(message.channel_id.say(context, error_response).await);
+    let language = guild_language(context, message.guild_id).await;
+
+    let error_response = match error {
+        DispatchError::Ratelimited(info) => translate(&language, "dispatch.ratelimited", &[("seconds", &info.as_secs().to_string())]),
+        DispatchError::CommandDisabled => translate(&language, "dispatch.disabled", &[("command", command)]),
+        DispatchError::OnlyForDM => translate(&language, "dispatch.dm_only", &[]),
+        DispatchError::OnlyForGuilds => translate(&language, "dispatch.guild_only", &[]),
+        DispatchError::OnlyForOwners => translate(&language, "dispatch.owner_only", &[]),
+        DispatchError::LackingRole => translate(&language, "dispatch.lacking_role", &[]),
+        DispatchError::LackingPermissions(perms) => translate(&language, "dispatch.lacking_permissions", &[("permissions", &perms.to_string())]),
+        DispatchError::NotEnoughArguments { min, given } => translate(&language, "dispatch.not_enough_arguments", &[("command", command), ("min", &min.to_string()), ("given", &given.to_string())]),
+        DispatchError::TooManyArguments { max, given } => translate(&language, "dispatch.too_many_arguments", &[("max", &max.to_string()), ("given", &given.to_string())]),
+        _ => {
+            tracing::warn!("Unhandled Dispatch error: {:?}", error);
+            return;
         }
-        DispatchError::LackingPermissions(perms) => {
-            error_response = format!("You lack the permissions required to use this command. Permissions needed: {perms}");
-            drop(message.channel_id.say(context, error_response).await);
+    };
+
+    send_error(context, message, error_response).await;
+}
+
+/// Replies to a failed command with an error embed, titled in the guild's language.
+async fn send_error(context: &Context, message: &Message, description: String) {
+    let language = guild_language(context, message.guild_id).await;
+
+    let embed = branded_embed(context, message.guild_id).await
+        .title(translate(&language, "error.title", &[]))
+        .description(description);
+
+    if let Err(why) = message.channel_id.send_message(context, CreateMessage::new().embed(embed).reference_message(message)).await {
+        error!("Unable to send an error message to channel {}: {why}", message.channel_id);
+    }
+}
+
+#[hook]
+pub async fn prefix_only(context: &Context, message: &Message) {
+    let language = guild_language(context, message.guild_id).await;
+    drop(message.channel_id.say(&context, translate(&language, "prefix_only", &[])).await);
+}
+
+/// Resolves the prefix for a message: the default `-` in DMs, otherwise the guild's prefix,
+/// creating default settings for guilds that have none yet.
+#[hook]
+pub async fn guild_prefix(context: &Context, message: &Message) -> Option<String> {
+    let Some(guild_id) = message.guild_id else {
+        return Some("-".to_string());
+    };
+
+    match get_or_create_guild_settings(context, guild_id).await {
+        Ok(settings) => Some(settings.read().await.prefix.clone()),
+        Err(why) => {
+            error!("Failed to load settings for guild {guild_id}: {}", error_chain(&why));
+            Some("-".to_string())
         }
-        DispatchError::NotEnoughArguments { min, given } => {
-            error_response = format!("The `{command}` command needs {min} arguments, but got {given}.");
-            drop(message.channel_id.say(context, error_response).await);
+    }
+}
+
+/// The built-in command that manages a guild script, tag or custom command by this name, in
+/// that order, if the guild has one.
+async fn stored_command(context: &Context, message: &Message, name: &str) -> Result<Option<&'static str>, BotError> {
+    let Some(guild_id) = message.guild_id else {
+        return Ok(None);
+    };
+
+    let database = get_data::<DatabaseConnectionContainer>(context).await?;
+
+    if guild_script_exists(&database, guild_id, name).await? {
+        Ok(Some("script"))
+    } else if tag_exists(&database, guild_id, name).await? {
+        Ok(Some("tag"))
+    } else if custom_command_exists(&database, guild_id, name).await? {
+        Ok(Some("customcommand"))
+    } else {
+        Ok(None)
+    }
+}
+
+#[hook]
+pub async fn unrecognised_command(context: &Context, message: &Message, command: &str) {
+    // guild scripts, tags and custom commands are invoked by name, just like built-in commands,
+    // and go by the rules, role permissions and cooldowns of the command that manages them
+    let args = message.content.split_once(command).map_or("", |(_, rest)| rest);
+
+    match stored_command(context, message, command).await {
+        Ok(Some(parent)) => {
+            let root = COMMAND_GROUPS.iter()
+                .flat_map(|group| group.options.commands.iter().map(move |root| (*group, *root)))
+                .find(|(_, root)| root.options.names[0] == parent);
+
+            if !command_usable(context, message, root, parent, Some(command)).await {
+                return;
+            }
+
+            let result = match parent {
+                "script" => run_guild_script(context, message, command, args).await,
+                "tag" => run_guild_tag(context, message, command).await,
+                _ => run_custom_command(context, message, command, args).await
+            };
+
+            if let Err(why) = result {
+                error!("Failed to run {parent} {command}: {}", error_chain(&why));
+            }
+
+            return;
         }
-        DispatchError::TooManyArguments { max, given } => {
-            error_response = format!("Max arguments allowed is {max}, but got {given}.");
-            drop(message.channel_id.say(context, error_response).await);
+        Ok(None) => {}
+        Err(why) => error!("Failed to look up guild scripts, tags and custom commands: {}", error_chain(&why))
+    }
+
+    let (enabled, prefix, language) = match message.guild_id {
+        Some(guild_id) => {
+            let Ok(guild_settings) = get_data::<GuildSettingsContainer>(context).await else {
+                return;
+            };
+
+            guild_settings.read(guild_id.get(), |settings| (settings.command_suggestions, settings.prefix.clone(), settings.language.clone())).await
+                .unwrap_or_else(|| (true, "-".to_string(), DEFAULT_LANGUAGE.to_string()))
         }
-        _ => tracing::warn!("Unhandled Dispatch error: {:?}", error)
+        None => (true, "-".to_string(), DEFAULT_LANGUAGE.to_string())
+    };
+
+    if !enabled {
+        return;
     }
+
+    let Some(suggestion) = closest_command(command, COMMAND_GROUPS) else {
+        return;
+    };
+
+    // keep whatever arguments were given to the mistyped command
+    let rest = message.content.split_once(command).map_or("", |(_, rest)| rest);
+    let corrected = format!("{prefix}{suggestion}{rest}");
+
+    let button = CreateButton::new("run_suggestion")
+        .label(translate(&language, "suggestion.run", &[("command", &format!("{prefix}{suggestion}"))]))
+        .style(ButtonStyle::Primary);
+
+    let builder = CreateMessage::new()
+        .content(translate(&language, "suggestion.question", &[("command", &format!("{prefix}{suggestion}"))]))
+        .components(vec![CreateActionRow::Buttons(vec![button])])
+        .reference_message(message);
+
+    let Ok(mut reply) = message.channel_id.send_message(context, builder).await else {
+        return;
+    };
+
+    let interaction = reply.await_component_interaction(&context.shard)
+        .author_id(message.author.id)
+        .timeout(Duration::from_secs(30))
+        .await;
+
+    let Some(interaction) = interaction else {
+        drop(reply.edit(context, EditMessage::new().components(vec![])).await);
+        return;
+    };
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(translate(&language, "suggestion.running", &[("command", &corrected)]))
+        .components(vec![]);
+    drop(interaction.create_response(context, CreateInteractionResponse::UpdateMessage(response)).await);
+
+    let Ok(framework) = get_data::<FrameworkContainer>(context).await else {
+        return;
+    };
+
+    // dispatch through the framework so the suggested command goes through the usual checks
+    let mut new_message = message.clone();
+    new_message.content = corrected;
+    framework.dispatch(context.clone(), FullEvent::Message { new_message }).await;
 }
 
-#[hook]
-pub async fn prefix_only(context: &Context, message: &Message) {
-    drop(message.channel_id.say(&context, "For info on my features, run the help command.").await);
+/// Finds the command name (or alias) closest to `name`, skipping owner-only commands.
+fn closest_command(name: &str, groups: &[&'static CommandGroup]) -> Option<&'static str> {
+    let name = name.to_lowercase();
+
+    groups.iter()
+        .filter(|group| !group.options.owners_only)
+        .flat_map(|group| group.options.commands.iter())
+        .filter(|command| !command.options.owners_only)
+        .flat_map(|command| command.options.names.iter().copied())
+        .map(|candidate| (levenshtein(&name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
 }
\ No newline at end of file
@@ -1 +1,2 @@
-pub mod event_handler;
\ No newline at end of file
+pub mod event_handler;
+pub mod hooks;
\ No newline at end of file
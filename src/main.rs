@@ -5,35 +5,152 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use serenity::framework::StandardFramework;
 use serenity::framework::standard::Configuration;
+use serenity::framework::standard::CommandGroup;
 use serenity::framework::standard::macros::group;
-use reqwest::Client as Reqwest;
 use tokio;
+use chrono::Utc;
 use serenity::http::Http;
 use serenity::prelude::*;
+use songbird::SerenityInit;
 use utilities::global_data::*;
+use utilities::premium::{PremiumTier, parse_expiry};
+use utilities::analytics::ActivityBuffer;
+use utilities::autoresponses::load_auto_responses;
+use utilities::analytics::load_opt_outs;
+use utilities::automod::load_automod_rules;
+use utilities::filters::load_filters;
+use utilities::links::load_link_settings;
+use utilities::media_rules::load_media_rules;
+use utilities::antispam::load_antispam;
+use utilities::levels::load_leveling;
+use utilities::modmail::load_modmail_threads;
+use utilities::cooldowns::load_cooldowns;
+use utilities::command_rules::load_command_rules;
+use utilities::command_permissions::load_command_permissions;
+use utilities::afk::load_afk;
+use utilities::sticky::load_stickies;
+use utilities::tts::load_tts_readers;
+use utilities::chain_games::load_chain_games;
+use utilities::watchlist::load_watchlists;
+use utilities::blacklist::load_blacklist;
+use utilities::antiraid::load_antiraid;
+use utilities::counters::presences_enabled;
+use utilities::message_log::MessageLogCache;
+use utilities::lookups::LookupCache;
+use utilities::schema::run_migrations;
 use crate::handlers::event_handler::event_handler::Handler;
+use crate::handlers::hooks::*;
 use tracing::error;
 
 mod handlers;
 mod commands;
 mod utilities;
+mod webhooks;
 
 use crate::commands::math::*;
 use crate::commands::utilities::*;
 use crate::commands::owner::*;
+use crate::commands::premium::*;
+use crate::commands::info::*;
+use crate::commands::announcements::*;
+use crate::commands::macros::*;
+use crate::commands::scripts::*;
+use crate::commands::templates::*;
+use crate::commands::stats::*;
+use crate::commands::counters::*;
+use crate::commands::autoresponses::*;
+use crate::commands::schedule::*;
+use crate::commands::moderation::*;
+use crate::commands::warnings::*;
+use crate::commands::purge::*;
+use crate::commands::automod::*;
+use crate::commands::filters::*;
+use crate::commands::links::*;
+use crate::commands::media_rules::*;
+use crate::commands::roles::*;
+use crate::commands::quarantine::*;
+use crate::commands::pin_archive::*;
+use crate::commands::transcripts::*;
+use crate::commands::help::*;
+use crate::commands::fun::*;
+use crate::commands::images::*;
+use crate::commands::trivia::*;
+use crate::commands::word_games::*;
+use crate::commands::sfx::*;
+use crate::commands::tts::*;
+use crate::commands::reddit::*;
+use crate::commands::github::*;
+use crate::commands::weather::*;
+use crate::commands::lookups::*;
+use crate::commands::birthdays::*;
+use crate::commands::timezones::*;
+use crate::commands::events::*;
+use crate::commands::chain_games::*;
+use crate::commands::quotes::*;
+use crate::commands::reports::*;
+use crate::commands::mod_notes::*;
+use crate::commands::watchlist::*;
+use crate::commands::antispam::*;
+use crate::commands::antiraid::*;
+use crate::commands::join_gate::*;
+use crate::commands::welcome::*;
+use crate::commands::autoroles::*;
+use crate::commands::role_menus::*;
+use crate::commands::starboard::*;
+use crate::commands::tags::*;
+use crate::commands::custom_commands::*;
+use crate::commands::reminders::*;
+use crate::commands::polls::*;
+use crate::commands::levels::*;
+use crate::commands::economy::*;
+use crate::commands::tickets::*;
+use crate::commands::modmail::*;
+use crate::commands::cooldowns::*;
+use crate::commands::command_rules::*;
+use crate::commands::permissions::*;
+use crate::commands::settings::*;
+use crate::commands::voice::*;
+use crate::commands::invites::*;
+use crate::commands::afk::*;
+use crate::commands::sticky::*;
+use crate::commands::expressions::*;
+use crate::commands::lockdown::*;
+use crate::commands::nicknames::*;
+use crate::commands::bans::*;
+use crate::commands::verification::*;
 
 #[group]
-#[commands(multiply, quit)]
+#[commands(multiply, remind, reminders, poll, balance, daily, give, shop, afk, weather, define, urban, translate, wiki, search, birthday, timezone, time, events, quote)]
 struct General;
 
 #[group]
-#[commands(ping)]
+#[commands(ping, vote, changelog, serverstats, wordcloud, messagestats, privacy, rank, leaderboard, voicestats, invites, invitedby, stats, serverinfo, userinfo, roleinfo, channelinfo, avatar)]
 struct Info;
 
 #[group]
-#[commands(prefix)]
+#[commands(eight_ball, coinflip, roll, choose, rps, trivia, hangman, wordle, gamestats, gamechannel, sfx, tts)]
+struct Fun;
+
+#[group]
+#[commands(effect, caption, deepfry, pet, wanted)]
+struct Images;
+
+#[group]
+#[commands(prefix, premium, updates, suggestions, command_macro, script, template, counter, autoresponse, schedule, welcome, verify, autorole, rolemenu, starboard, pinarchive, tag, customcommand, level, sticky, emoji, sticker, ticket, modmail, cooldown, command_rules, perms, settings, reddit, github)]
 struct Settings;
 
+#[group]
+#[commands(kick, ban, tempban, hackban, unban, softban, banlist, bansync, mute, tempmute, unmute, mutetype, role, temprole, warn, warnings, delwarn, clearwarn, warnescalation, note, watch, purge, archivepin, transcript, slowmode, lock, unlock, lockdown, decancer, forcenick, quarantine, unquarantine, modlog, reason, automod, filter, links, mediarules, antispam, raidmode, joingate, messagelog, serverlog, voicelog, reports)]
+struct Moderation;
+
+#[group]
+#[owners_only]
+#[commands(allowlist, blacklist, incident, shards, latency, sql, reloadsettings, setactivity, shutdown)]
+struct Owner;
+
+// Every command group registered with the framework, also used to look up commands by name.
+pub static COMMAND_GROUPS: &[&CommandGroup] = &[&GENERAL_GROUP, &INFO_GROUP, &FUN_GROUP, &IMAGES_GROUP, &SETTINGS_GROUP, &MODERATION_GROUP, &OWNER_GROUP];
+
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().expect("Failed to load .env file");
@@ -45,15 +162,26 @@ async fn main() {
     // In this case, a good default is setting the environment variable `RUST_LOG` to `debug`.
     tracing_subscriber::fmt::init();
 
-    let intents = GatewayIntents::GUILD_MESSAGES
+    let mut intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT
         | GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::GUILD_MODERATION
         | GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_EMOJIS_AND_STICKERS
+        | GatewayIntents::GUILD_WEBHOOKS
+        | GatewayIntents::GUILD_VOICE_STATES
+        | GatewayIntents::GUILD_INVITES
+        | GatewayIntents::GUILD_SCHEDULED_EVENTS
         | GatewayIntents::AUTO_MODERATION_CONFIGURATION
         | GatewayIntents::AUTO_MODERATION_EXECUTION;
 
+    // presences are privileged and only used by the `online` counter, so they're opt-in
+    if presences_enabled() {
+        intents |= GatewayIntents::GUILD_PRESENCES;
+    }
+
     let http = Http::new(&token);
 
     // Initiate a connection to the database file, creating the file if required.
@@ -70,7 +198,7 @@ async fn main() {
     let connection = database.clone();
 
     // Run migrations, which updates the database's schema to the latest version.
-    sqlx::migrate!("./migrations").run(&database).await.expect("Couldn't run database migrations");
+    run_migrations(&database).await.expect("Couldn't run database migrations");
 
     let handler = Handler {
         database,
@@ -94,109 +222,195 @@ async fn main() {
     };
 
     // Create the framework
-    let framework = StandardFramework::new()
+    let mut framework = StandardFramework::new()
         .help(&HELP)
-        .group(&GENERAL_GROUP)
-        .group(&INFO_GROUP)
-        .group(&SETTINGS_GROUP);
+        .before(before)
+        .after(after)
+        .on_dispatch_error(dispatch_error)
+        .prefix_only(prefix_only)
+        .unrecognised_command(unrecognised_command);
+
+    for group in COMMAND_GROUPS {
+        framework = framework.group(group);
+    }
 
     // Configure the client with the appropriate options
     framework.configure(
         Configuration::new()
-        .owners(owners)
-        .dynamic_prefix(|ctx, msg| {
-            Box::pin(async move {
-                if msg.is_private() { // if private message, return default prefix
-                    Some("-".to_string())
-                } else {
-                    // if guild message, return guild prefix
-                    // implement guild settings hashmap and return prefix
-
-                    let prefix = {
-                        let data = ctx.data.read().await;
-                        let guild_settings = data.get::<GuildSettingsContainer>().unwrap();
-                        let pf = guild_settings.read().await;
-                        let database = data.get::<DatabaseConnectionContainer>().unwrap().clone();
-
-                        match pf.get(&msg.guild_id.unwrap().get()) {
-                            Some(guild) => guild.prefix.clone(),
-                            None => {
-                                
-                                // if no guild settings found, 
-                                // create new database entry and return default prefix
-
-                                let (guild_id, owner_id) = {
-                                    let guild = msg.guild(&ctx.cache).unwrap();
-                                
-                                    (i64::from(guild.id), i64::from(guild.owner_id))
-                                };
-
-                                sqlx::query!(
-                                    "INSERT INTO guild_settings (
-                                        guild_id,
-                                        prefix,
-                                        owner_id
-                                    ) VALUES (?, ?, ?)",
-                                    guild_id,
-                                    "-",
-                                    owner_id
-                                ).execute(&database).await.unwrap();
-
-                                "-".to_string()
-                            }
-                        }
-                    };
-
-                    Some(prefix)
-                }
-            })
-        })
+        .owners(owners.clone())
+        .dynamic_prefix(guild_prefix)
         .prefix("")
         .on_mention(Some(bot_id))
     );
 
+    let framework = Arc::new(framework);
+
     let mut client =
         Client::builder(&token, intents)
-        .framework(framework)
+        .framework(SharedFramework(Arc::clone(&framework)))
+        .register_songbird()
         .event_handler(handler).await.expect("Err creating client");
 
-    let guild_settings = sqlx::query!("SELECT * FROM guild_settings")
+    let allowlist_enabled = sqlx::query!("SELECT allowlist_enabled FROM bot_settings WHERE id = 0")
+        .fetch_one(&connection)
+        .await
+        .expect("Couldn't fetch bot settings")
+        .allowlist_enabled != 0;
+
+    let allowlisted_guilds = sqlx::query!("SELECT guild_id FROM guild_allowlist")
         .fetch_all(&connection)
         .await
-        .expect("Couldn't fetch guild settings");
+        .expect("Couldn't fetch guild allowlist")
+        .into_iter()
+        .map(|row| row.guild_id as u64)
+        .collect();
+
+    let allowlist = Allowlist {
+        enabled: allowlist_enabled,
+        guilds: allowlisted_guilds
+    };
+
+    let blacklist = load_blacklist(&connection)
+        .await
+        .expect("Couldn't fetch the blacklist");
+
+    let premium_rows = sqlx::query!("SELECT * FROM guild_premium")
+        .fetch_all(&connection)
+        .await
+        .expect("Couldn't fetch guild premium tiers");
 
-    let mut guild_settings_map = HashMap::new();
+    let mut premium_map = HashMap::new();
 
-    for guild_setting in guild_settings {
-        let guild_id = guild_setting.guild_id as u64;
-        let guild_settings = GuildSettings {
-            prefix: guild_setting.prefix,
-            owner_id: guild_setting.owner_id as u64,
-            mute_type: guild_setting.mute_style,
-            mute_role: guild_setting.mute_role_id.unwrap_or_default() as u64
+    for row in premium_rows {
+        let premium = GuildPremium {
+            tier: PremiumTier::from_level(row.tier),
+            source: row.source,
+            entitlement_id: row.entitlement_id.map(|id| id as u64),
+            expires_at: parse_expiry(row.expires_at)
         };
 
-        guild_settings_map.insert(guild_id, guild_settings);
+        premium_map.insert(row.guild_id as u64, premium);
     }
 
-    let reqwest_client = Reqwest::new();
+    let auto_responses = load_auto_responses(&connection)
+        .await
+        .expect("Couldn't fetch auto-responses");
+
+    let opt_outs = load_opt_outs(&connection)
+        .await
+        .expect("Couldn't fetch privacy opt-outs");
+
+    let automod_rules = load_automod_rules(&connection)
+        .await
+        .expect("Couldn't fetch automod rules");
+
+    let filters = load_filters(&connection)
+        .await
+        .expect("Couldn't fetch word filters");
+
+    let link_settings = load_link_settings(&connection)
+        .await
+        .expect("Couldn't fetch link settings");
+
+    let media_rules = load_media_rules(&connection)
+        .await
+        .expect("Couldn't fetch media rules");
+
+    let antispam = load_antispam(&connection)
+        .await
+        .expect("Couldn't fetch anti-spam settings");
+
+    let antiraid = load_antiraid(&connection)
+        .await
+        .expect("Couldn't fetch anti-raid settings");
+
+    let leveling = load_leveling(&connection)
+        .await
+        .expect("Couldn't fetch leveling settings");
+
+    let modmail_threads = load_modmail_threads(&connection)
+        .await
+        .expect("Couldn't fetch modmail threads");
+
+    let cooldowns = load_cooldowns(&connection)
+        .await
+        .expect("Couldn't fetch command cooldowns");
+
+    let command_rules = load_command_rules(&connection)
+        .await
+        .expect("Couldn't fetch command rules");
+
+    let command_permissions = load_command_permissions(&connection)
+        .await
+        .expect("Couldn't fetch command permissions");
+
+    let afk_statuses = load_afk(&connection)
+        .await
+        .expect("Couldn't fetch AFK statuses");
+
+    let stickies = load_stickies(&connection)
+        .await
+        .expect("Couldn't fetch sticky messages");
+
+    let tts_readers = load_tts_readers(&connection)
+        .await
+        .expect("Couldn't fetch text-to-speech channels");
+
+    let chain_games = load_chain_games(&connection)
+        .await
+        .expect("Couldn't fetch counting and word chain channels");
+
+    let watchlists = load_watchlists(&connection)
+        .await
+        .expect("Couldn't fetch watchlists");
+
+    let reqwest_client = build_http_client().expect("Couldn't build the HTTP client");
+
+    // Receives bot list votes and other external webhooks, if configured.
+    tokio::spawn(webhooks::serve(connection.clone(), client.http.clone()));
 
     {
         let mut data = client.data.write().await;
         data.clear();
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+        data.insert::<GuildSettingsContainer>(Arc::new(GuildSettingsStore::new(connection.clone())));
         data.insert::<DatabaseConnectionContainer>(connection);
-        data.insert::<GuildSettingsContainer>(Arc::new(RwLock::new(guild_settings_map)));
         data.insert::<ReqwestClientContainer>(Arc::new(reqwest_client));
+        data.insert::<AllowlistContainer>(Arc::new(RwLock::new(allowlist)));
+        data.insert::<BlacklistContainer>(Arc::new(RwLock::new(blacklist)));
+        data.insert::<PremiumContainer>(Arc::new(RwLock::new(premium_map)));
+        data.insert::<FrameworkContainer>(framework);
+        data.insert::<OwnersContainer>(owners);
+        data.insert::<ActivityContainer>(Arc::new(Mutex::new(ActivityBuffer::default())));
+        data.insert::<AutoResponsesContainer>(Arc::new(RwLock::new(auto_responses)));
+        data.insert::<PrivacyOptOutsContainer>(Arc::new(RwLock::new(opt_outs)));
+        data.insert::<AutomodContainer>(Arc::new(RwLock::new(automod_rules)));
+        data.insert::<FiltersContainer>(Arc::new(RwLock::new(filters)));
+        data.insert::<LinksContainer>(Arc::new(RwLock::new(link_settings)));
+        data.insert::<MediaRulesContainer>(Arc::new(RwLock::new(media_rules)));
+        data.insert::<AntispamContainer>(Arc::new(Mutex::new(antispam)));
+        data.insert::<AntiraidContainer>(Arc::new(Mutex::new(antiraid)));
+        data.insert::<MessageLogContainer>(Arc::new(Mutex::new(MessageLogCache::default())));
+        data.insert::<LevelingContainer>(Arc::new(Mutex::new(leveling)));
+        data.insert::<ModmailContainer>(Arc::new(RwLock::new(modmail_threads)));
+        data.insert::<BootTimeContainer>(Utc::now());
+        data.insert::<CommandCountsContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<ActivityOverrideContainer>(Arc::new(RwLock::new(None)));
+        data.insert::<CooldownsContainer>(Arc::new(Mutex::new(cooldowns)));
+        data.insert::<CommandRulesContainer>(Arc::new(RwLock::new(command_rules)));
+        data.insert::<CommandPermissionsContainer>(Arc::new(RwLock::new(command_permissions)));
+        data.insert::<VoiceSessionsContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<InvitesContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<AfkContainer>(Arc::new(RwLock::new(afk_statuses)));
+        data.insert::<StickyContainer>(Arc::new(Mutex::new(stickies)));
+        data.insert::<ChannelGamesContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<SfxCooldownsContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<TtsReadersContainer>(Arc::new(RwLock::new(tts_readers)));
+        data.insert::<LookupCacheContainer>(Arc::new(Mutex::new(LookupCache::default())));
+        data.insert::<ChainGamesContainer>(Arc::new(Mutex::new(chain_games)));
+        data.insert::<WatchlistContainer>(Arc::new(Mutex::new(watchlists)));
     }
 
-    let shard_manager = client.shard_manager.clone();
-
-    tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.expect("Could not register ctrl+c handler");
-        shard_manager.shutdown_all().await;
-    });
-
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
     }
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{AfkContainer, DatabaseConnectionContainer};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::errors::{get_data, BotError};
+
+/// Most AFK members named in a reply to a single message.
+const MAX_NOTICES: usize = 5;
+
+#[derive(Clone)]
+pub struct AfkStatus {
+    pub reason: Option<String>,
+    pub since: DateTime<Utc>
+}
+
+pub async fn load_afk(database: &SqlitePool) -> Result<HashMap<(u64, u64), AfkStatus>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, user_id, reason, since FROM afk_statuses")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter()
+        .filter_map(|row| {
+            let since = DateTime::parse_from_rfc3339(&row.since).ok()?.with_timezone(&Utc);
+            Some(((row.guild_id as u64, row.user_id as u64), AfkStatus { reason: row.reason, since }))
+        })
+        .collect())
+}
+
+/// Marks a member as AFK in a guild, replacing any status they had.
+pub async fn set_afk(ctx: &Context, guild_id: GuildId, user_id: UserId, status: AfkStatus) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let statuses = get_data::<AfkContainer>(ctx).await?;
+
+    let (db_guild_id, db_user_id, since) = (guild_id.get() as i64, user_id.get() as i64, status.since.to_rfc3339());
+
+    sqlx::query!(
+        "INSERT INTO afk_statuses (guild_id, user_id, reason, since) VALUES (?, ?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET reason = excluded.reason, since = excluded.since",
+        db_guild_id,
+        db_user_id,
+        status.reason,
+        since
+    ).execute(&database).await?;
+
+    statuses.write().await.insert((guild_id.get(), user_id.get()), status);
+
+    Ok(())
+}
+
+/// How long ago something happened, e.g. `2h15m`.
+fn elapsed_since(since: DateTime<Utc>) -> String {
+    format_duration(Utc::now() - since)
+}
+
+/// Welcomes back an AFK author, clearing their status, and tells them about any AFK members they
+/// mentioned.
+pub async fn handle_afk(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    let (database, statuses) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<AfkContainer>(ctx).await) {
+        (Ok(database), Ok(statuses)) => (database, statuses),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't check AFK statuses for a message in {}: {why}", msg.id);
+            return;
+        }
+    };
+
+    let key = (guild_id.get(), msg.author.id.get());
+
+    // the message setting the status isn't one that clears it
+    let returned = {
+        let mut statuses = statuses.write().await;
+
+        match statuses.get(&key) {
+            Some(status) if *msg.timestamp > status.since => statuses.remove(&key),
+            _ => None
+        }
+    };
+
+    if let Some(status) = returned {
+        let (db_guild_id, user_id) = (guild_id.get() as i64, msg.author.id.get() as i64);
+
+        if let Err(why) = sqlx::query!("DELETE FROM afk_statuses WHERE guild_id = ? AND user_id = ?", db_guild_id, user_id).execute(&database).await {
+            error!("Failed to clear the AFK status of user {} in guild {guild_id}: {why}", msg.author.id);
+        }
+
+        if let Err(why) = msg.reply(ctx, format!("Welcome back! You were AFK for {}.", elapsed_since(status.since))).await {
+            warn!("Couldn't welcome back user {} in channel {}: {why}", msg.author.id, msg.channel_id);
+        }
+    }
+
+    let notices: Vec<String> = {
+        let statuses = statuses.read().await;
+
+        msg.mentions.iter()
+            .filter(|user| user.id != msg.author.id)
+            .filter_map(|user| {
+                let status = statuses.get(&(guild_id.get(), user.id.get()))?;
+                let reason = status.reason.as_deref().map_or_else(String::new, |reason| format!(": {reason}"));
+
+                Some(format!("**{}** is AFK{reason} ({} ago)", user.display_name(), elapsed_since(status.since)))
+            })
+            .take(MAX_NOTICES)
+            .collect()
+    };
+
+    if notices.is_empty() {
+        return;
+    }
+
+    let builder = CreateMessage::new()
+        .content(notices.join("\n"))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .reference_message(msg);
+
+    if let Err(why) = msg.channel_id.send_message(ctx, builder).await {
+        warn!("Couldn't send AFK notices in channel {}: {why}", msg.channel_id);
+    }
+}
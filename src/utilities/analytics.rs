@@ -0,0 +1,487 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::{Days, NaiveDate, Timelike, Utc};
+use serenity::model::channel::Message;
+use serenity::model::id::GuildId;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::utilities::global_data::{ActivityContainer, DatabaseConnectionContainer, PrivacyOptOutsContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// How often buffered activity is written to the database.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Old rows are pruned once every this many flushes.
+const PRUNE_EVERY: u32 = 60;
+
+/// How long hourly message counts are kept, which is also the longest range `serverstats` shows.
+pub const MAX_RANGE_DAYS: u64 = 90;
+
+/// How long daily rollups are kept.
+const ROLLUP_RETENTION_DAYS: u64 = 365;
+
+/// How long per-user statistics and word counts are kept.
+pub const USER_STATS_DAYS: u64 = 30;
+
+/// Most words indexed from a single message.
+const MAX_WORDS_PER_MESSAGE: usize = 50;
+
+/// Common words left out of word counts.
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "you", "that", "was", "for", "are", "with", "his", "they", "this", "have", "from",
+    "one", "had", "but", "not", "what", "all", "were", "when", "your", "can", "there", "use", "how",
+    "she", "which", "their", "will", "other", "about", "out", "then", "them", "these", "some", "her",
+    "would", "him", "has", "its", "who", "did", "get", "just", "like", "dont", "too"
+];
+
+/// Activity seen since the last flush, aggregated in memory so busy guilds don't cause a write
+/// per message.
+#[derive(Default)]
+pub struct ActivityBuffer {
+    // (guild, channel, day, hour) -> messages
+    messages: HashMap<(u64, u64, NaiveDate, u32), i64>,
+    // (guild, day, user)
+    active: HashSet<(u64, NaiveDate, u64)>,
+    // (guild, day) -> (joins, leaves)
+    members: HashMap<(u64, NaiveDate), (i64, i64)>,
+    // (guild, user, day, hour) -> messages, for users who haven't opted out
+    user_messages: HashMap<(u64, u64, NaiveDate, u32), i64>,
+    // (guild, channel, user, day, word) -> uses, for users who haven't opted out
+    words: HashMap<(u64, u64, u64, NaiveDate, String), i64>
+}
+
+/// One day of a guild's activity. Days without any recorded activity are filled with zeroes.
+pub struct DailyStats {
+    pub day: NaiveDate,
+    pub messages: i64,
+    pub active_members: i64,
+    pub joins: i64,
+    pub leaves: i64,
+    pub member_count: i64
+}
+
+pub async fn record_message(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    let now = Utc::now();
+    let day = now.date_naive();
+
+    let (buffer, opt_outs) = match (get_data::<ActivityContainer>(ctx).await, get_data::<PrivacyOptOutsContainer>(ctx).await) {
+        (Ok(buffer), Ok(opt_outs)) => (buffer, opt_outs),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't record activity for message {}: {why}", msg.id);
+            return;
+        }
+    };
+
+    let opted_out = opt_outs.read().await.contains(&msg.author.id.get());
+
+    let (guild_id, channel_id, user_id) = (guild_id.get(), msg.channel_id.get(), msg.author.id.get());
+
+    let mut buffer = buffer.lock().await;
+    *buffer.messages.entry((guild_id, channel_id, day, now.hour())).or_default() += 1;
+    buffer.active.insert((guild_id, day, user_id));
+
+    // server-wide counts are anonymous, anything tied to a user respects their opt-out
+    if opted_out {
+        return;
+    }
+
+    *buffer.user_messages.entry((guild_id, user_id, day, now.hour())).or_default() += 1;
+
+    for word in index_words(&msg.content) {
+        *buffer.words.entry((guild_id, channel_id, user_id, day, word)).or_default() += 1;
+    }
+}
+
+/// Splits a message into the words counted for word clouds: lowercase ASCII letters and digits,
+/// 3 to 24 characters long, skipping links, mentions, custom emojis and common words.
+fn index_words(content: &str) -> Vec<String> {
+    content.split_whitespace()
+        .filter(|token| !token.contains("://") && !token.starts_with('<'))
+        .map(|token| token.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_lowercase())
+        .filter(|word| (3..=24).contains(&word.len()) && !word.chars().all(|character| character.is_ascii_digit()))
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .take(MAX_WORDS_PER_MESSAGE)
+        .collect()
+}
+
+pub async fn record_member_change(ctx: &Context, guild_id: GuildId, joined: bool) {
+    let day = Utc::now().date_naive();
+
+    let buffer = match get_data::<ActivityContainer>(ctx).await {
+        Ok(buffer) => buffer,
+        Err(why) => {
+            error!("Couldn't record a member change in guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    let mut buffer = buffer.lock().await;
+    let (joins, leaves) = buffer.members.entry((guild_id.get(), day)).or_default();
+
+    if joined {
+        *joins += 1;
+    } else {
+        *leaves += 1;
+    }
+}
+
+/// Writes buffered activity right away, e.g. before shutting down.
+pub async fn flush_pending_activity(ctx: &Context) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let buffer = get_data::<ActivityContainer>(ctx).await?;
+
+    let pending = std::mem::take(&mut *buffer.lock().await);
+
+    Ok(flush_activity(ctx, &database, pending).await?)
+}
+
+/// Periodically writes buffered activity into the hourly and daily tables, pruning old rows.
+pub async fn flush_activity_loop(ctx: Context) {
+    let (database, buffer) = match (get_data::<DatabaseConnectionContainer>(&ctx).await, get_data::<ActivityContainer>(&ctx).await) {
+        (Ok(database), Ok(buffer)) => (database, buffer),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't start writing server activity: {why}");
+            return;
+        }
+    };
+
+    let mut flushes: u32 = 0;
+
+    loop {
+        tokio::time::sleep(FLUSH_INTERVAL).await;
+
+        let pending = std::mem::take(&mut *buffer.lock().await);
+
+        if let Err(why) = flush_activity(&ctx, &database, pending).await {
+            error!("Failed to write server activity: {why}");
+        }
+
+        flushes = flushes.wrapping_add(1);
+
+        if flushes.is_multiple_of(PRUNE_EVERY) {
+            if let Err(why) = prune_activity(&database).await {
+                error!("Failed to prune old server activity: {why}");
+            }
+        }
+    }
+}
+
+async fn flush_activity(ctx: &Context, database: &SqlitePool, buffer: ActivityBuffer) -> Result<(), sqlx::Error> {
+    // (guild, day) -> (messages, joins, leaves)
+    let mut rollups: HashMap<(u64, NaiveDate), (i64, i64, i64)> = HashMap::new();
+
+    for ((guild_id, _, day, _), count) in &buffer.messages {
+        rollups.entry((*guild_id, *day)).or_default().0 += count;
+    }
+
+    for ((guild_id, day), (joins, leaves)) in &buffer.members {
+        let rollup = rollups.entry((*guild_id, *day)).or_default();
+        rollup.1 += joins;
+        rollup.2 += leaves;
+    }
+
+    if rollups.is_empty() {
+        return Ok(());
+    }
+
+    let mut transaction = database.begin().await?;
+
+    for ((guild_id, channel_id, day, hour), count) in buffer.messages {
+        let (guild_id, channel_id, day, hour) = (guild_id as i64, channel_id as i64, day.to_string(), i64::from(hour));
+
+        sqlx::query!(
+            "INSERT INTO message_stats (guild_id, channel_id, day, hour, messages) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (guild_id, channel_id, day, hour) DO UPDATE SET messages = messages + excluded.messages",
+            guild_id,
+            channel_id,
+            day,
+            hour,
+            count
+        ).execute(&mut *transaction).await?;
+    }
+
+    for (guild_id, day, user_id) in buffer.active {
+        let (guild_id, day, user_id) = (guild_id as i64, day.to_string(), user_id as i64);
+
+        sqlx::query!(
+            "INSERT INTO active_members (guild_id, day, user_id) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+            guild_id,
+            day,
+            user_id
+        ).execute(&mut *transaction).await?;
+    }
+
+    for ((guild_id, user_id, day, hour), count) in buffer.user_messages {
+        let (guild_id, user_id, day, hour) = (guild_id as i64, user_id as i64, day.to_string(), i64::from(hour));
+
+        sqlx::query!(
+            "INSERT INTO user_message_stats (guild_id, user_id, day, hour, messages) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (guild_id, user_id, day, hour) DO UPDATE SET messages = messages + excluded.messages",
+            guild_id,
+            user_id,
+            day,
+            hour,
+            count
+        ).execute(&mut *transaction).await?;
+    }
+
+    for ((guild_id, channel_id, user_id, day, word), uses) in buffer.words {
+        let (guild_id, channel_id, user_id, day) = (guild_id as i64, channel_id as i64, user_id as i64, day.to_string());
+
+        sqlx::query!(
+            "INSERT INTO word_stats (guild_id, channel_id, user_id, day, word, uses) VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (guild_id, channel_id, user_id, day, word) DO UPDATE SET uses = uses + excluded.uses",
+            guild_id,
+            channel_id,
+            user_id,
+            day,
+            word,
+            uses
+        ).execute(&mut *transaction).await?;
+    }
+
+    for ((guild_id, day), (messages, joins, leaves)) in rollups {
+        // unknown when the guild isn't cached, in which case the previous count is kept
+        let member_count = ctx.cache.guild(GuildId::new(guild_id)).map(|guild| guild.member_count as i64);
+        let (guild_id, day) = (guild_id as i64, day.to_string());
+
+        sqlx::query!(
+            "INSERT INTO daily_stats (guild_id, day, messages, joins, leaves, member_count) VALUES (?, ?, ?, ?, ?, COALESCE(?, 0))
+            ON CONFLICT (guild_id, day) DO UPDATE SET
+                messages = messages + excluded.messages,
+                joins = joins + excluded.joins,
+                leaves = leaves + excluded.leaves,
+                member_count = CASE WHEN excluded.member_count > 0 THEN excluded.member_count ELSE member_count END",
+            guild_id,
+            day,
+            messages,
+            joins,
+            leaves,
+            member_count
+        ).execute(&mut *transaction).await?;
+
+        sqlx::query!(
+            "UPDATE daily_stats SET active_members = (
+                SELECT COUNT(*) FROM active_members WHERE guild_id = daily_stats.guild_id AND day = daily_stats.day
+            ) WHERE guild_id = ? AND day = ?",
+            guild_id,
+            day
+        ).execute(&mut *transaction).await?;
+    }
+
+    transaction.commit().await
+}
+
+async fn prune_activity(database: &SqlitePool) -> Result<(), sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let cutoff = |days: u64| (today - Days::new(days)).to_string();
+
+    let (hourly, active, rollups, user_stats) = (cutoff(MAX_RANGE_DAYS), cutoff(1), cutoff(ROLLUP_RETENTION_DAYS), cutoff(USER_STATS_DAYS));
+
+    sqlx::query!("DELETE FROM message_stats WHERE day < ?", hourly).execute(database).await?;
+    // yesterday is kept so its count is still correct if activity from just before midnight arrives late
+    sqlx::query!("DELETE FROM active_members WHERE day < ?", active).execute(database).await?;
+    sqlx::query!("DELETE FROM daily_stats WHERE day < ?", rollups).execute(database).await?;
+    sqlx::query!("DELETE FROM user_message_stats WHERE day < ?", user_stats).execute(database).await?;
+    sqlx::query!("DELETE FROM word_stats WHERE day < ?", user_stats).execute(database).await?;
+
+    Ok(())
+}
+
+/// The guild's daily rollups for the last `days` days, oldest first and including today.
+pub async fn daily_stats(database: &SqlitePool, guild_id: GuildId, days: u64) -> Result<Vec<DailyStats>, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let first = today - Days::new(days.saturating_sub(1));
+    let (db_guild_id, since) = (guild_id.get() as i64, first.to_string());
+
+    let rows = sqlx::query!(
+        "SELECT day, messages, active_members, joins, leaves, member_count FROM daily_stats WHERE guild_id = ? AND day >= ?",
+        db_guild_id,
+        since
+    ).fetch_all(database).await?;
+
+    let rows: HashMap<String, _> = rows.into_iter().map(|row| (row.day.clone(), row)).collect();
+
+    let mut stats = Vec::new();
+    let mut member_count = 0;
+
+    for day in first.iter_days().take_while(|day| *day <= today) {
+        let stat = match rows.get(&day.to_string()) {
+            Some(row) => {
+                // carry the member count over days the bot didn't see any activity
+                if row.member_count > 0 {
+                    member_count = row.member_count;
+                }
+
+                DailyStats {
+                    day,
+                    messages: row.messages,
+                    active_members: row.active_members,
+                    joins: row.joins,
+                    leaves: row.leaves,
+                    member_count
+                }
+            }
+            None => DailyStats { day, messages: 0, active_members: 0, joins: 0, leaves: 0, member_count }
+        };
+
+        stats.push(stat);
+    }
+
+    Ok(stats)
+}
+
+/// The channels with the most messages since `since`, busiest first.
+pub async fn top_channels(database: &SqlitePool, guild_id: GuildId, since: NaiveDate, limit: i64) -> Result<Vec<(u64, i64)>, sqlx::Error> {
+    let (guild_id, since) = (guild_id.get() as i64, since.to_string());
+
+    let rows = sqlx::query!(
+        r#"SELECT channel_id, SUM(messages) AS "messages!: i64" FROM message_stats
+        WHERE guild_id = ? AND day >= ?
+        GROUP BY channel_id ORDER BY 2 DESC LIMIT ?"#,
+        guild_id,
+        since,
+        limit
+    ).fetch_all(database).await?;
+
+    Ok(rows.into_iter().map(|row| (row.channel_id as u64, row.messages)).collect())
+}
+
+/// Messages sent during each hour of the day (UTC) since `since`.
+pub async fn hourly_totals(database: &SqlitePool, guild_id: GuildId, since: NaiveDate) -> Result<[i64; 24], sqlx::Error> {
+    let (guild_id, since) = (guild_id.get() as i64, since.to_string());
+
+    let rows = sqlx::query!(
+        r#"SELECT hour, SUM(messages) AS "messages!: i64" FROM message_stats
+        WHERE guild_id = ? AND day >= ?
+        GROUP BY hour"#,
+        guild_id,
+        since
+    ).fetch_all(database).await?;
+
+    let mut totals = [0; 24];
+
+    for row in rows {
+        if let Some(total) = totals.get_mut(row.hour as usize) {
+            *total = row.messages;
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Which messages a word cloud is built from.
+pub enum WordSource {
+    Guild,
+    Channel(u64),
+    User(u64)
+}
+
+/// The most used words in a guild since `since`, most used first.
+pub async fn top_words(database: &SqlitePool, guild_id: GuildId, source: WordSource, since: NaiveDate, limit: i64) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let (guild_id, since) = (guild_id.get() as i64, since.to_string());
+
+    // -1 never matches a real ID, so it stands in for "no filter"
+    let (channel_id, user_id) = match source {
+        WordSource::Guild => (-1, -1),
+        WordSource::Channel(channel_id) => (channel_id as i64, -1),
+        WordSource::User(user_id) => (-1, user_id as i64)
+    };
+
+    let rows = sqlx::query!(
+        r#"SELECT word, SUM(uses) AS "uses!: i64" FROM word_stats
+        WHERE guild_id = ? AND day >= ? AND (? = -1 OR channel_id = ?) AND (? = -1 OR user_id = ?)
+        GROUP BY word ORDER BY 2 DESC LIMIT ?"#,
+        guild_id,
+        since,
+        channel_id,
+        channel_id,
+        user_id,
+        user_id,
+        limit
+    ).fetch_all(database).await?;
+
+    Ok(rows.into_iter().map(|row| (row.word, row.uses)).collect())
+}
+
+/// A user's messages in a guild since `since`, by hour of the day (UTC) and by day, oldest first.
+pub async fn user_activity(database: &SqlitePool, guild_id: GuildId, user_id: u64, since: NaiveDate) -> Result<([i64; 24], Vec<(NaiveDate, i64)>), sqlx::Error> {
+    let (db_guild_id, db_user_id, since_day) = (guild_id.get() as i64, user_id as i64, since.to_string());
+
+    let rows = sqlx::query!(
+        "SELECT day, hour, messages FROM user_message_stats WHERE guild_id = ? AND user_id = ? AND day >= ?",
+        db_guild_id,
+        db_user_id,
+        since_day
+    ).fetch_all(database).await?;
+
+    let mut hours = [0; 24];
+    let mut days: HashMap<String, i64> = HashMap::new();
+
+    for row in rows {
+        if let Some(total) = hours.get_mut(row.hour as usize) {
+            *total += row.messages;
+        }
+
+        *days.entry(row.day).or_default() += row.messages;
+    }
+
+    let today = Utc::now().date_naive();
+    let days = since.iter_days()
+        .take_while(|day| *day <= today)
+        .map(|day| (day, days.get(&day.to_string()).copied().unwrap_or_default()))
+        .collect();
+
+    Ok((hours, days))
+}
+
+/// Every user who opted out of per-user statistics.
+pub async fn load_opt_outs(database: &SqlitePool) -> Result<HashSet<u64>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT user_id FROM privacy_opt_outs").fetch_all(database).await?;
+
+    Ok(rows.into_iter().map(|row| row.user_id as u64).collect())
+}
+
+/// Opts a user out of (or back into) per-user statistics. Opting out also deletes everything
+/// already recorded about them, in every guild.
+pub async fn set_opt_out(ctx: &Context, user_id: u64, opted_out: bool) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let opt_outs = get_data::<PrivacyOptOutsContainer>(ctx).await?;
+    let buffer = get_data::<ActivityContainer>(ctx).await?;
+
+    let db_user_id = user_id as i64;
+
+    if opted_out {
+        let opted_out_at = Utc::now().to_rfc3339();
+        let mut transaction = database.begin().await?;
+
+        sqlx::query!(
+            "INSERT INTO privacy_opt_outs (user_id, opted_out_at) VALUES (?, ?) ON CONFLICT DO NOTHING",
+            db_user_id,
+            opted_out_at
+        ).execute(&mut *transaction).await?;
+
+        sqlx::query!("DELETE FROM user_message_stats WHERE user_id = ?", db_user_id).execute(&mut *transaction).await?;
+        sqlx::query!("DELETE FROM word_stats WHERE user_id = ?", db_user_id).execute(&mut *transaction).await?;
+
+        transaction.commit().await?;
+        opt_outs.write().await.insert(user_id);
+
+        // drop anything recorded since the last flush as well
+        let mut buffer = buffer.lock().await;
+        buffer.user_messages.retain(|(_, user, _, _), _| *user != user_id);
+        buffer.words.retain(|(_, _, user, _, _), _| *user != user_id);
+    } else {
+        sqlx::query!("DELETE FROM privacy_opt_outs WHERE user_id = ?", db_user_id).execute(&database).await?;
+        opt_outs.write().await.remove(&user_id);
+    }
+
+    Ok(())
+}
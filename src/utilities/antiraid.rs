@@ -0,0 +1,291 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{Duration, Utc};
+use serenity::builder::CreateMessage;
+use serenity::framework::standard::CommandError;
+use serenity::model::guild::Member;
+use serenity::model::id::GuildId;
+use serenity::model::user::User;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::{AntiraidContainer, DatabaseConnectionContainer};
+use crate::utilities::moderation::{ModAction, apply_action, modlog_channel, notify_target, record_action};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::quarantine::{quarantine_member, quarantine_role};
+use crate::utilities::errors::{get_data, BotError};
+
+/// How long raid mode stays on after the last burst of joins, when it was turned on automatically.
+const RAID_MODE_DURATION: StdDuration = StdDuration::from_secs(10 * 60);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RaidMode {
+    Off,
+    On,
+    Auto
+}
+
+impl RaidMode {
+    pub fn parse(mode: &str) -> Option<RaidMode> {
+        match mode.to_lowercase().as_str() {
+            "off" => Some(RaidMode::Off),
+            "on" => Some(RaidMode::On),
+            "auto" => Some(RaidMode::Auto),
+            _ => None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RaidMode::Off => "off",
+            RaidMode::On => "on",
+            RaidMode::Auto => "auto"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RaidAction {
+    Kick,
+    Quarantine
+}
+
+impl RaidAction {
+    pub fn parse(action: &str) -> Option<RaidAction> {
+        match action.to_lowercase().as_str() {
+            "kick" => Some(RaidAction::Kick),
+            "quarantine" => Some(RaidAction::Quarantine),
+            _ => None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RaidAction::Kick => "kick",
+            RaidAction::Quarantine => "quarantine"
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            RaidAction::Kick => "kicked",
+            RaidAction::Quarantine => "quarantined"
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct AntiraidSettings {
+    mode: RaidMode,
+    max_joins: usize,
+    window: StdDuration,
+    min_account_age: Duration,
+    action: RaidAction
+}
+
+impl AntiraidSettings {
+    fn from_row(mode: &str, max_joins: i64, window_seconds: i64, min_account_age: i64, action: &str) -> AntiraidSettings {
+        AntiraidSettings {
+            mode: RaidMode::parse(mode).unwrap_or(RaidMode::Off),
+            max_joins: max_joins.max(1) as usize,
+            window: StdDuration::from_secs(window_seconds.max(1) as u64),
+            min_account_age: Duration::seconds(min_account_age.max(0)),
+            action: RaidAction::parse(action).unwrap_or(RaidAction::Kick)
+        }
+    }
+}
+
+/// Every guild's anti-raid settings, recent joins and, for guilds under a detected raid, when
+/// raid mode ends.
+#[derive(Default)]
+pub struct AntiraidState {
+    settings: HashMap<u64, AntiraidSettings>,
+    joins: HashMap<u64, VecDeque<Instant>>,
+    raids: HashMap<u64, Instant>
+}
+
+impl AntiraidState {
+    /// Whether raid mode is on for a guild, whether it was turned on by hand or by a raid.
+    pub fn is_raid_mode(&self, guild_id: GuildId) -> bool {
+        match self.settings.get(&guild_id.get()).map(|settings| settings.mode) {
+            Some(RaidMode::On) => true,
+            Some(RaidMode::Auto) => self.raids.get(&guild_id.get()).is_some_and(|until| *until > Instant::now()),
+            _ => false
+        }
+    }
+}
+
+pub async fn load_antiraid(database: &SqlitePool) -> Result<AntiraidState, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, mode, max_joins, window_seconds, min_account_age, action FROM antiraid_settings")
+        .fetch_all(database)
+        .await?;
+
+    let settings = rows.into_iter()
+        .map(|row| {
+            let settings = AntiraidSettings::from_row(&row.mode, row.max_joins, row.window_seconds, row.min_account_age, &row.action);
+            (row.guild_id as u64, settings)
+        })
+        .collect();
+
+    Ok(AntiraidState { settings, ..AntiraidState::default() })
+}
+
+/// Reloads one guild's anti-raid settings from the database after they've been changed. Changing
+/// the mode ends a raid detected earlier.
+pub async fn reload_antiraid(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let state = get_data::<AntiraidContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT mode, max_joins, window_seconds, min_account_age, action FROM antiraid_settings WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(&database).await?;
+
+    let mut state = state.lock().await;
+
+    let settings = row.map(|row| AntiraidSettings::from_row(&row.mode, row.max_joins, row.window_seconds, row.min_account_age, &row.action));
+    let old_mode = state.settings.get(&guild_id.get()).map(|settings| settings.mode);
+
+    if settings.map(|settings| settings.mode) != old_mode {
+        state.raids.remove(&guild_id.get());
+    }
+
+    match settings {
+        Some(settings) => {
+            state.settings.insert(guild_id.get(), settings);
+        }
+        None => {
+            state.settings.remove(&guild_id.get());
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks a new member's join. In `auto` mode, too many joins too quickly turn raid mode on for a
+/// while and alert the modlog channel. While raid mode is on, accounts younger than the guild's
+/// minimum age are kicked or quarantined. Returns whether the member was.
+pub async fn handle_member_join(ctx: &Context, member: &Member) -> bool {
+    let guild_id = member.guild_id;
+
+    let state = match get_data::<AntiraidContainer>(ctx).await {
+        Ok(state) => state,
+        Err(why) => {
+            error!("Couldn't check member {} for a raid: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    let (settings, raid_detected, raid_mode) = {
+        let mut state = state.lock().await;
+
+        let Some(settings) = state.settings.get(&guild_id.get()).copied().filter(|settings| settings.mode != RaidMode::Off) else {
+            return false;
+        };
+
+        let now = Instant::now();
+        let joins = state.joins.entry(guild_id.get()).or_default();
+
+        while joins.front().is_some_and(|joined| now.duration_since(*joined) > settings.window) {
+            joins.pop_front();
+        }
+
+        joins.push_back(now);
+
+        let join_count = joins.len();
+        let mut raid_detected = None;
+
+        if settings.mode == RaidMode::Auto && join_count > settings.max_joins {
+            let already_raided = state.is_raid_mode(guild_id);
+
+            // every further burst keeps raid mode on for longer
+            state.raids.insert(guild_id.get(), now + RAID_MODE_DURATION);
+
+            if !already_raided {
+                raid_detected = Some(join_count);
+            }
+        }
+
+        (settings, raid_detected, state.is_raid_mode(guild_id))
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check member {} for a raid: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    if let Some(join_count) = raid_detected {
+        let description = format!(
+            "{join_count} members joined within {} seconds, so raid mode is on for the next {} minutes. New accounts younger than {} will be {}.",
+            settings.window.as_secs(),
+            RAID_MODE_DURATION.as_secs() / 60,
+            format_duration(settings.min_account_age),
+            settings.action.past_tense()
+        );
+
+        let embed = branded_embed(ctx, Some(guild_id)).await
+            .title("Raid detected")
+            .description(description);
+
+        match modlog_channel(&database, guild_id).await {
+            Ok(Some(channel_id)) => {
+                if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+                    warn!("Couldn't post a raid alert to the modlog of guild {guild_id}: {why}");
+                }
+            }
+            Ok(None) => {}
+            Err(why) => warn!("Couldn't find the modlog channel of guild {guild_id}: {why}")
+        }
+    }
+
+    let account_age = Utc::now() - *member.user.created_at();
+
+    if !raid_mode || account_age >= settings.min_account_age {
+        return false;
+    }
+
+    let reason = format!("Anti-raid: Account is younger than {}", format_duration(settings.min_account_age));
+    let bot = User::from(ctx.cache.current_user().clone());
+
+    let role_id = match settings.action {
+        RaidAction::Kick => None,
+        RaidAction::Quarantine => match quarantine_role(&database, guild_id).await {
+            Ok(Some(role_id)) => Some(role_id),
+            Ok(None) => {
+                warn!("Guild {guild_id} has no quarantine role, so anti-raid is kicking user {} instead", member.user.id);
+                None
+            }
+            Err(why) => {
+                error!("Couldn't find the quarantine role of guild {guild_id}: {why}");
+                return false;
+            }
+        }
+    };
+
+    // quarantined members keep their saved roles until a moderator lets them out
+    let result = match role_id {
+        None => apply_action(ctx, guild_id, &member.user, &bot, ModAction::Kick, None, Some(&reason)).await.map(drop),
+        Some(role_id) => async {
+            quarantine_member(ctx, &database, member, role_id, bot.id, &reason).await?;
+            notify_target(ctx, guild_id, &member.user, ModAction::Quarantine, Some(&reason)).await;
+            record_action(ctx, &database, guild_id, ModAction::Quarantine, member.user.id, bot.id, Some(&reason)).await?;
+
+            Ok::<(), CommandError>(())
+        }.await
+    };
+
+    if let Err(why) = result {
+        warn!("Anti-raid couldn't {} user {} in guild {guild_id}: {why}", settings.action.as_str(), member.user.id);
+        return false;
+    }
+
+    true
+}
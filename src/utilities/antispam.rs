@@ -0,0 +1,197 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{Duration, Utc};
+use serenity::builder::EditMember;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{AntispamContainer, DatabaseConnectionContainer};
+use crate::utilities::moderation::{ModAction, notify_target, record_action};
+use crate::utilities::errors::{get_data, BotError};
+
+/// How far back identical messages are counted.
+const DUPLICATE_WINDOW: StdDuration = StdDuration::from_secs(30);
+
+/// Once this many users are tracked, users who haven't sent anything recently are forgotten.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Clone, Copy)]
+pub struct AntispamSettings {
+    pub enabled: bool,
+    pub max_messages: usize,
+    pub window: StdDuration,
+    pub max_duplicates: usize,
+    pub timeout: Duration
+}
+
+impl AntispamSettings {
+    fn from_row(enabled: i64, max_messages: i64, window_seconds: i64, max_duplicates: i64, timeout_seconds: i64) -> AntispamSettings {
+        AntispamSettings {
+            enabled: enabled != 0,
+            max_messages: max_messages.max(1) as usize,
+            window: StdDuration::from_secs(window_seconds.max(1) as u64),
+            max_duplicates: max_duplicates.max(1) as usize,
+            timeout: Duration::seconds(timeout_seconds.max(1))
+        }
+    }
+}
+
+struct RecentMessage {
+    sent: Instant,
+    id: MessageId,
+    channel_id: ChannelId,
+    content: String
+}
+
+/// Every guild's anti-spam settings, along with each user's recent messages.
+#[derive(Default)]
+pub struct AntispamState {
+    settings: HashMap<u64, AntispamSettings>,
+    recent: HashMap<(u64, u64), VecDeque<RecentMessage>>
+}
+
+pub async fn load_antispam(database: &SqlitePool) -> Result<AntispamState, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, enabled, max_messages, window_seconds, max_duplicates, timeout_seconds FROM antispam_settings")
+        .fetch_all(database)
+        .await?;
+
+    let settings = rows.into_iter()
+        .map(|row| {
+            let settings = AntispamSettings::from_row(row.enabled, row.max_messages, row.window_seconds, row.max_duplicates, row.timeout_seconds);
+            (row.guild_id as u64, settings)
+        })
+        .collect();
+
+    Ok(AntispamState { settings, recent: HashMap::new() })
+}
+
+/// Reloads one guild's anti-spam settings from the database after they've been changed.
+pub async fn reload_antispam(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let state = get_data::<AntispamContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT enabled, max_messages, window_seconds, max_duplicates, timeout_seconds FROM antispam_settings WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(&database).await?;
+
+    let mut state = state.lock().await;
+
+    match row {
+        Some(row) => {
+            let settings = AntispamSettings::from_row(row.enabled, row.max_messages, row.window_seconds, row.max_duplicates, row.timeout_seconds);
+            state.settings.insert(guild_id.get(), settings);
+        }
+        None => {
+            state.settings.remove(&guild_id.get());
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks the message and, if its author is sending messages too quickly or repeating
+/// themselves, deletes their recent messages and times them out. Returns whether the message was
+/// spam.
+pub async fn handle_antispam(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let state = match get_data::<AntispamContainer>(ctx).await {
+        Ok(state) => state,
+        Err(why) => {
+            error!("Couldn't check message {} for spam: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let (spam, settings, reason) = {
+        let mut state = state.lock().await;
+
+        let Some(settings) = state.settings.get(&guild_id.get()).copied().filter(|settings| settings.enabled) else {
+            return false;
+        };
+
+        let now = Instant::now();
+        let kept_for = settings.window.max(DUPLICATE_WINDOW);
+
+        if state.recent.len() > PRUNE_THRESHOLD {
+            state.recent.retain(|_, messages| messages.back().is_some_and(|message| now.duration_since(message.sent) <= kept_for));
+        }
+
+        let messages = state.recent.entry((guild_id.get(), msg.author.id.get())).or_default();
+
+        while messages.front().is_some_and(|message| now.duration_since(message.sent) > kept_for) {
+            messages.pop_front();
+        }
+
+        messages.push_back(RecentMessage { sent: now, id: msg.id, channel_id: msg.channel_id, content: msg.content.clone() });
+
+        let in_window = messages.iter().filter(|message| now.duration_since(message.sent) <= settings.window).count();
+        let duplicates = if msg.content.is_empty() {
+            0
+        } else {
+            messages.iter().filter(|message| message.content == msg.content).count()
+        };
+
+        let reason = if in_window > settings.max_messages {
+            format!("Sent {in_window} messages in {} seconds", settings.window.as_secs())
+        } else if duplicates > settings.max_duplicates {
+            format!("Sent the same message {duplicates} times")
+        } else {
+            return false;
+        };
+
+        // start over, so the same burst isn't punished twice
+        let spam: Vec<(ChannelId, MessageId)> = messages.drain(..).map(|message| (message.channel_id, message.id)).collect();
+
+        (spam, settings, reason)
+    };
+
+    let mut by_channel: HashMap<ChannelId, Vec<MessageId>> = HashMap::new();
+
+    for (channel_id, message_id) in spam {
+        by_channel.entry(channel_id).or_default().push(message_id);
+    }
+
+    for (channel_id, message_ids) in by_channel {
+        if let Err(why) = channel_id.delete_messages(ctx, &message_ids).await {
+            warn!("Anti-spam couldn't delete messages in channel {channel_id}: {why}");
+        }
+    }
+
+    let reason = format!("Anti-spam: {reason}");
+    let until = Utc::now() + settings.timeout;
+    let builder = EditMember::new().disable_communication_until_datetime(until.into()).audit_log_reason(&reason);
+
+    if let Err(why) = guild_id.edit_member(ctx, msg.author.id, builder).await {
+        warn!("Anti-spam couldn't time out user {} in guild {guild_id}: {why}", msg.author.id);
+        return true;
+    }
+
+    notify_target(ctx, guild_id, &msg.author, ModAction::Tempmute, Some(&reason)).await;
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check message {} for spam: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let bot_id = ctx.cache.current_user().id;
+
+    // recording the timeout posts it to the modlog channel
+    if let Err(why) = record_action(ctx, &database, guild_id, ModAction::Tempmute, msg.author.id, bot_id, Some(&reason)).await {
+        error!("Failed to record anti-spam timeout of user {} in guild {guild_id}: {why}", msg.author.id);
+    }
+
+    true
+}
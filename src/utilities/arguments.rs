@@ -0,0 +1,182 @@
+use std::fmt;
+
+use chrono::Duration;
+use serenity::framework::standard::Args;
+use serenity::model::channel::{GuildChannel, Message};
+use serenity::model::guild::{Member, Role};
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use serenity::prelude::Context;
+use serenity::utils::ArgumentConvert;
+
+use crate::utilities::parsing::{parse_channel, parse_duration, parse_role, parse_user};
+
+/// Why an argument couldn't be read. The messages are meant for the user, so commands can return
+/// these with `?` and the `after` hook shows them as they are.
+#[derive(Debug)]
+pub enum ArgumentError {
+    /// Nothing was given where an argument of this kind was expected.
+    Missing(&'static str),
+    /// The argument was understood but nothing it names exists, e.g. a member who left.
+    NotFound(&'static str, String),
+    /// The argument couldn't be understood as this kind at all.
+    Invalid(&'static str, String)
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentError::Missing(kind) => write!(f, "Please give a {kind}."),
+            ArgumentError::NotFound(kind, arg) => write!(f, "Couldn't find a {kind} matching `{arg}`."),
+            ArgumentError::Invalid("duration", arg) => write!(f, "Couldn't understand `{arg}` as a duration. Try something like `10m`, `2h` or `1d12h`."),
+            ArgumentError::Invalid(kind, arg) => write!(f, "`{arg}` isn't a valid {kind}.")
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+/// A link to a message, e.g. `https://discord.com/channels/<guild>/<channel>/<message>`.
+#[derive(Clone, Copy, Debug)]
+pub struct MessageLink {
+    /// None for messages in DMs.
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId
+}
+
+impl MessageLink {
+    pub fn parse(link: &str) -> Option<MessageLink> {
+        let path = link.trim_start_matches('<').trim_end_matches('>')
+            .strip_prefix("https://")?
+            .trim_start_matches("www.")
+            .trim_start_matches("ptb.")
+            .trim_start_matches("canary.");
+
+        let path = path.strip_prefix("discord.com/channels/").or_else(|| path.strip_prefix("discordapp.com/channels/"))?;
+        let mut parts = path.split('/');
+
+        let guild_id = match parts.next()? {
+            "@me" => None,
+            guild => Some(GuildId::new(parse_id(guild)?))
+        };
+
+        let channel_id = ChannelId::new(parse_id(parts.next()?)?);
+        let message_id = MessageId::new(parse_id(parts.next()?)?);
+
+        parts.next().is_none().then_some(MessageLink { guild_id, channel_id, message_id })
+    }
+}
+
+fn parse_id(arg: &str) -> Option<u64> {
+    arg.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+/// Typed arguments for prefix commands. Each method reads the next argument, which may be quoted
+/// to include spaces, and fails with an error that says what was wrong with it.
+pub trait TypedArgs {
+    /// A quoted or single-word string.
+    fn text(&mut self, kind: &'static str) -> Result<String, ArgumentError>;
+
+    /// Whether the next argument is `keyword` in any case, such as `off`, consuming it if so.
+    fn keyword(&mut self, keyword: &str) -> bool;
+
+    /// A user by mention or ID, whether or not they're in the server.
+    fn user_id(&mut self) -> Result<UserId, ArgumentError>;
+
+    /// A channel by mention or ID, whether or not it still exists.
+    fn channel_id(&mut self) -> Result<ChannelId, ArgumentError>;
+
+    /// A role by mention or ID, whether or not it still exists.
+    fn role_id(&mut self) -> Result<RoleId, ArgumentError>;
+
+    /// A duration such as `10m` or `1d2h`.
+    fn duration(&mut self) -> Result<Duration, ArgumentError>;
+
+    /// A message link, or the ID of a message in the channel the command was used in.
+    fn message_link(&mut self, msg: &Message) -> Result<MessageLink, ArgumentError>;
+
+    /// A member of the server by mention, ID, username or nickname.
+    async fn member(&mut self, ctx: &Context, msg: &Message) -> Result<Member, ArgumentError>;
+
+    /// A role of the server by mention, ID or name.
+    async fn role(&mut self, ctx: &Context, msg: &Message) -> Result<Role, ArgumentError>;
+
+    /// A channel of the server by mention, ID or name.
+    async fn channel(&mut self, ctx: &Context, msg: &Message) -> Result<GuildChannel, ArgumentError>;
+}
+
+impl TypedArgs for Args {
+    fn text(&mut self, kind: &'static str) -> Result<String, ArgumentError> {
+        self.single_quoted::<String>()
+            .ok()
+            .filter(|text| !text.is_empty())
+            .ok_or(ArgumentError::Missing(kind))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let matches = self.current().is_some_and(|arg| arg.eq_ignore_ascii_case(keyword));
+
+        if matches {
+            self.advance();
+        }
+
+        matches
+    }
+
+    fn user_id(&mut self) -> Result<UserId, ArgumentError> {
+        let arg = self.text("user")?;
+        parse_user(&arg).ok_or(ArgumentError::NotFound("user", arg))
+    }
+
+    fn channel_id(&mut self) -> Result<ChannelId, ArgumentError> {
+        let arg = self.text("channel")?;
+        parse_channel(&arg).ok_or(ArgumentError::NotFound("channel", arg))
+    }
+
+    fn role_id(&mut self) -> Result<RoleId, ArgumentError> {
+        let arg = self.text("role")?;
+        parse_role(&arg).ok_or(ArgumentError::NotFound("role", arg))
+    }
+
+    fn duration(&mut self) -> Result<Duration, ArgumentError> {
+        let arg = self.text("duration")?;
+        parse_duration(&arg).ok_or(ArgumentError::Invalid("duration", arg))
+    }
+
+    fn message_link(&mut self, msg: &Message) -> Result<MessageLink, ArgumentError> {
+        let arg = self.text("message link")?;
+
+        if let Some(message_id) = parse_id(&arg) {
+            return Ok(MessageLink { guild_id: msg.guild_id, channel_id: msg.channel_id, message_id: MessageId::new(message_id) });
+        }
+
+        MessageLink::parse(&arg).ok_or(ArgumentError::Invalid("message link", arg))
+    }
+
+    async fn member(&mut self, ctx: &Context, msg: &Message) -> Result<Member, ArgumentError> {
+        let arg = self.text("member")?;
+
+        match Member::convert(ctx, msg.guild_id, Some(msg.channel_id), &arg).await {
+            Ok(member) => Ok(member),
+            Err(_) => Err(ArgumentError::NotFound("member", arg))
+        }
+    }
+
+    async fn role(&mut self, ctx: &Context, msg: &Message) -> Result<Role, ArgumentError> {
+        let arg = self.text("role")?;
+
+        match Role::convert(ctx, msg.guild_id, Some(msg.channel_id), &arg).await {
+            Ok(role) => Ok(role),
+            Err(_) => Err(ArgumentError::NotFound("role", arg))
+        }
+    }
+
+    async fn channel(&mut self, ctx: &Context, msg: &Message) -> Result<GuildChannel, ArgumentError> {
+        let arg = self.text("channel")?;
+
+        match GuildChannel::convert(ctx, msg.guild_id, Some(msg.channel_id), &arg).await {
+            Ok(channel) if Some(channel.guild_id) == msg.guild_id => Ok(channel),
+            _ => Err(ArgumentError::NotFound("channel", arg))
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration as StdDuration;
+
+use chrono::Duration;
+use regex::Regex;
+use serenity::model::channel::Message;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::id::GuildId;
+use serenity::model::user::User;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::global_data::{AutomodContainer, DatabaseConnectionContainer};
+use crate::utilities::moderation::{ModAction, add_warning, apply_action};
+use crate::utilities::errors::{get_data, BotError};
+
+/// How long the `mute` action mutes for.
+const AUTOMOD_MUTE: Duration = Duration::minutes(10);
+
+/// How long the notice that a message was removed stays up.
+const NOTICE_LIFETIME: StdDuration = StdDuration::from_secs(5);
+
+/// Messages with fewer letters than this are never treated as excessive capitals.
+const MIN_CAPS_LETTERS: usize = 10;
+
+static INVITE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(discord\.gg|discord(app)?\.com/invite)/[a-z0-9-]+").expect("the invite pattern is valid")
+});
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    Words,
+    Invites,
+    Mentions,
+    Caps,
+    Zalgo
+}
+
+impl RuleKind {
+    pub const ALL: [RuleKind; 5] = [RuleKind::Words, RuleKind::Invites, RuleKind::Mentions, RuleKind::Caps, RuleKind::Zalgo];
+
+    pub fn parse(kind: &str) -> Option<RuleKind> {
+        RuleKind::ALL.into_iter().find(|candidate| candidate.as_str() == kind.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuleKind::Words => "words",
+            RuleKind::Invites => "invites",
+            RuleKind::Mentions => "mentions",
+            RuleKind::Caps => "caps",
+            RuleKind::Zalgo => "zalgo"
+        }
+    }
+
+    /// The threshold a new rule starts with, for rules that have one.
+    pub fn default_threshold(self) -> Option<i64> {
+        match self {
+            RuleKind::Mentions => Some(5),
+            RuleKind::Caps => Some(70),
+            RuleKind::Zalgo => Some(10),
+            RuleKind::Words | RuleKind::Invites => None
+        }
+    }
+
+    /// What the threshold of the rule counts, for rules that have one.
+    pub fn threshold_unit(self) -> Option<&'static str> {
+        match self {
+            RuleKind::Mentions => Some("mentions per message"),
+            RuleKind::Caps => Some("percent capital letters"),
+            RuleKind::Zalgo => Some("combining marks per message"),
+            RuleKind::Words | RuleKind::Invites => None
+        }
+    }
+
+    fn reason(self) -> &'static str {
+        match self {
+            RuleKind::Words => "Used a banned word",
+            RuleKind::Invites => "Posted an invite link",
+            RuleKind::Mentions => "Mentioned too many users",
+            RuleKind::Caps => "Used excessive capitals",
+            RuleKind::Zalgo => "Used zalgo text"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AutomodAction {
+    Delete,
+    Warn,
+    Mute,
+    Ban
+}
+
+impl AutomodAction {
+    pub const ALL: [AutomodAction; 4] = [AutomodAction::Delete, AutomodAction::Warn, AutomodAction::Mute, AutomodAction::Ban];
+
+    pub fn parse(action: &str) -> Option<AutomodAction> {
+        AutomodAction::ALL.into_iter().find(|candidate| candidate.as_str() == action.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AutomodAction::Delete => "delete",
+            AutomodAction::Warn => "warn",
+            AutomodAction::Mute => "mute",
+            AutomodAction::Ban => "ban"
+        }
+    }
+}
+
+pub struct AutomodRule {
+    kind: RuleKind,
+    action: AutomodAction,
+    words: Vec<String>,
+    threshold: usize,
+    exempt_channels: Vec<u64>,
+    exempt_roles: Vec<u64>
+}
+
+impl AutomodRule {
+    fn is_exempt(&self, msg: &Message) -> bool {
+        let roles = msg.member.as_ref().map_or(&[][..], |member| &member.roles[..]);
+
+        self.exempt_channels.contains(&msg.channel_id.get())
+            || roles.iter().any(|role| self.exempt_roles.contains(&role.get()))
+    }
+
+    fn is_broken_by(&self, msg: &Message) -> bool {
+        match self.kind {
+            RuleKind::Words => msg.content
+                .split(|character: char| !character.is_alphanumeric())
+                .any(|word| self.words.iter().any(|banned| banned.eq_ignore_ascii_case(word))),
+            RuleKind::Invites => INVITE_LINK.is_match(&msg.content),
+            RuleKind::Mentions => msg.mentions.len() + msg.mention_roles.len() >= self.threshold,
+            RuleKind::Caps => {
+                let letters = msg.content.chars().filter(|character| character.is_alphabetic()).count();
+                let capitals = msg.content.chars().filter(|character| character.is_uppercase()).count();
+
+                letters >= MIN_CAPS_LETTERS && capitals * 100 >= letters * self.threshold
+            }
+            RuleKind::Zalgo => msg.content.chars().filter(|character| is_combining_mark(*character)).count() >= self.threshold
+        }
+    }
+}
+
+/// Whether a character is a combining mark, which zalgo text stacks on top of letters.
+fn is_combining_mark(character: char) -> bool {
+    matches!(character, '\u{0300}'..='\u{036F}' | '\u{0489}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}')
+}
+
+/// Parses a comma separated list of IDs, skipping anything that isn't one.
+pub fn parse_ids(ids: &str) -> Vec<u64> {
+    ids.split(',').filter_map(|id| id.trim().parse().ok()).collect()
+}
+
+/// Every guild's automod rules, keyed by guild ID.
+pub async fn load_automod_rules(database: &SqlitePool) -> Result<HashMap<u64, Vec<AutomodRule>>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, kind, action, words, threshold, exempt_channels, exempt_roles FROM automod_rules")
+        .fetch_all(database)
+        .await?;
+
+    let mut rules: HashMap<u64, Vec<AutomodRule>> = HashMap::new();
+
+    for row in rows {
+        if let Some(rule) = build_rule(&row.kind, &row.action, &row.words, row.threshold, &row.exempt_channels, &row.exempt_roles) {
+            rules.entry(row.guild_id as u64).or_default().push(rule);
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Reloads one guild's automod rules from the database after they've been changed.
+pub async fn reload_automod_rules(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let cache = get_data::<AutomodContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let rules: Vec<AutomodRule> = sqlx::query!(
+        "SELECT kind, action, words, threshold, exempt_channels, exempt_roles FROM automod_rules WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_all(&database).await?
+        .into_iter()
+        .filter_map(|row| build_rule(&row.kind, &row.action, &row.words, row.threshold, &row.exempt_channels, &row.exempt_roles))
+        .collect();
+
+    let mut cache = cache.write().await;
+
+    if rules.is_empty() {
+        cache.remove(&guild_id.get());
+    } else {
+        cache.insert(guild_id.get(), rules);
+    }
+
+    Ok(())
+}
+
+fn build_rule(kind: &str, action: &str, words: &str, threshold: i64, exempt_channels: &str, exempt_roles: &str) -> Option<AutomodRule> {
+    Some(AutomodRule {
+        kind: RuleKind::parse(kind)?,
+        action: AutomodAction::parse(action)?,
+        words: words.split(',').map(str::trim).filter(|word| !word.is_empty()).map(str::to_string).collect(),
+        threshold: threshold.max(1) as usize,
+        exempt_channels: parse_ids(exempt_channels),
+        exempt_roles: parse_ids(exempt_roles)
+    })
+}
+
+/// Checks a message against its guild's automod rules. The message is removed if it breaks one,
+/// and the author is warned, muted or banned if the rule says so. Returns whether a rule was
+/// broken.
+pub async fn handle_automod(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let cache = match get_data::<AutomodContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't check message {} against automod rules: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let broken = {
+        let cache = cache.read().await;
+        let Some(rules) = cache.get(&guild_id.get()) else {
+            return false;
+        };
+
+        rules.iter()
+            .find(|rule| !rule.is_exempt(msg) && rule.is_broken_by(msg))
+            .map(|rule| (rule.kind, rule.action))
+    };
+
+    let Some((kind, action)) = broken else {
+        return false;
+    };
+
+    enforce(ctx, guild_id, msg, action, "Automod", kind.reason()).await;
+
+    true
+}
+
+/// Removes a message that broke a rule, acts on its author as the rule says, and leaves a short
+/// notice of why. `source` names what removed it in logs and case reasons.
+pub async fn enforce(ctx: &Context, guild_id: GuildId, msg: &Message, action: AutomodAction, source: &str, why_removed: &str) {
+    if let Err(why) = msg.delete(ctx).await {
+        warn!("{source} couldn't delete message {} in channel {}: {why}", msg.id, msg.channel_id);
+    }
+
+    let reason = format!("{source}: {why_removed}");
+    let bot = User::from(ctx.cache.current_user().clone());
+
+    let result = match action {
+        AutomodAction::Delete => Ok(()),
+        AutomodAction::Warn => add_warning(ctx, guild_id, &msg.author, &bot, Some(&reason)).await.map(drop),
+        AutomodAction::Mute => apply_action(ctx, guild_id, &msg.author, &bot, ModAction::Tempmute, Some(AUTOMOD_MUTE), Some(&reason)).await.map(drop),
+        AutomodAction::Ban => apply_action(ctx, guild_id, &msg.author, &bot, ModAction::Ban, None, Some(&reason)).await.map(drop)
+    };
+
+    if let Err(why) = result {
+        warn!("{source} couldn't {} user {} in guild {guild_id}: {why}", action.as_str(), msg.author.id);
+    }
+
+    let notice = format!("{}, your message was removed. {why_removed}.", msg.author.mention());
+
+    if let Ok(notice) = msg.channel_id.say(ctx, notice).await {
+        let http = ctx.http.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(NOTICE_LIFETIME).await;
+            drop(notice.delete(http).await);
+        });
+    }
+}
+
+/// The full message behind an edit that has to be removed, from the cache if it's there.
+pub async fn edited_message(ctx: &Context, new: Option<&Message>, event: &MessageUpdateEvent) -> Option<Message> {
+    if let Some(msg) = new {
+        return Some(msg.clone());
+    }
+
+    match event.channel_id.message(ctx, event.id).await {
+        Ok(msg) => Some(msg),
+        Err(why) => {
+            warn!("Couldn't fetch edited message {} in channel {}: {why}", event.id, event.channel_id);
+            None
+        }
+    }
+}
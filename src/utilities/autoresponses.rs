@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::id::GuildId;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{AutoResponsesContainer, DatabaseConnectionContainer, GuildSettingsContainer};
+use crate::utilities::templates::{message_context, render_template};
+use crate::utilities::errors::{get_data, BotError};
+
+/// Upper bound on the compiled size of a trigger, so one pattern can't use a lot of memory.
+const MAX_PATTERN_SIZE: usize = 1 << 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    Exact,
+    Contains,
+    Regex,
+    Wildcard
+}
+
+impl MatchMode {
+    pub const ALL: [MatchMode; 4] = [MatchMode::Exact, MatchMode::Contains, MatchMode::Regex, MatchMode::Wildcard];
+
+    pub fn parse(mode: &str) -> Option<MatchMode> {
+        MatchMode::ALL.into_iter().find(|candidate| candidate.as_str() == mode.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchMode::Exact => "exact",
+            MatchMode::Contains => "contains",
+            MatchMode::Regex => "regex",
+            MatchMode::Wildcard => "wildcard"
+        }
+    }
+
+    /// Compiles a trigger into a regex. Every mode except `regex` ignores case, and wildcard
+    /// triggers support `*` (anything) and `?` (any single character).
+    pub fn compile(self, pattern: &str) -> Result<Regex, regex::Error> {
+        let expression = match self {
+            MatchMode::Exact => format!("^{}$", regex::escape(pattern)),
+            MatchMode::Contains => regex::escape(pattern),
+            MatchMode::Regex => pattern.to_string(),
+            MatchMode::Wildcard => {
+                let translated: String = pattern.chars()
+                    .map(|character| match character {
+                        '*' => ".*".to_string(),
+                        '?' => ".".to_string(),
+                        other => regex::escape(&other.to_string())
+                    })
+                    .collect();
+
+                format!("^{translated}$")
+            }
+        };
+
+        RegexBuilder::new(&expression)
+            .case_insensitive(self != MatchMode::Regex)
+            .size_limit(MAX_PATTERN_SIZE)
+            .build()
+    }
+}
+
+pub struct AutoResponse {
+    id: i64,
+    response: String,
+    cooldown: Duration,
+    channels: Vec<u64>,
+    matcher: Regex,
+    last_fired: Option<Instant>
+}
+
+impl AutoResponse {
+    fn is_ready(&self, channel_id: u64) -> bool {
+        let in_channel = self.channels.is_empty() || self.channels.contains(&channel_id);
+        let cooled_down = self.last_fired.is_none_or(|fired| fired.elapsed() >= self.cooldown);
+
+        in_channel && cooled_down
+    }
+}
+
+/// Every guild's auto-responses, keyed by guild ID. Triggers that no longer compile are skipped.
+pub async fn load_auto_responses(database: &SqlitePool) -> Result<HashMap<u64, Vec<AutoResponse>>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT id, guild_id, pattern, match_mode, response, cooldown, channels FROM auto_responses ORDER BY id")
+        .fetch_all(database)
+        .await?;
+
+    let mut responses: HashMap<u64, Vec<AutoResponse>> = HashMap::new();
+
+    for row in rows {
+        if let Some(response) = build_auto_response(row.id, &row.pattern, &row.match_mode, &row.response, row.cooldown, &row.channels) {
+            responses.entry(row.guild_id as u64).or_default().push(response);
+        }
+    }
+
+    Ok(responses)
+}
+
+/// Reloads one guild's auto-responses from the database after they've been changed.
+pub async fn reload_auto_responses(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let cache = get_data::<AutoResponsesContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let rows = sqlx::query!(
+        "SELECT id, pattern, match_mode, response, cooldown, channels FROM auto_responses WHERE guild_id = ? ORDER BY id",
+        db_guild_id
+    ).fetch_all(&database).await?;
+
+    let mut cache = cache.write().await;
+    let previous = cache.remove(&guild_id.get()).unwrap_or_default();
+
+    let responses: Vec<AutoResponse> = rows.into_iter()
+        .filter_map(|row| {
+            let mut response = build_auto_response(row.id, &row.pattern, &row.match_mode, &row.response, row.cooldown, &row.channels)?;
+            // editing a response shouldn't reset its cooldown
+            response.last_fired = previous.iter().find(|old| old.id == response.id).and_then(|old| old.last_fired);
+            Some(response)
+        })
+        .collect();
+
+    if !responses.is_empty() {
+        cache.insert(guild_id.get(), responses);
+    }
+
+    Ok(())
+}
+
+fn build_auto_response(id: i64, pattern: &str, mode: &str, response: &str, cooldown: i64, channels: &str) -> Option<AutoResponse> {
+    let mode = MatchMode::parse(mode)?;
+
+    let matcher = match mode.compile(pattern) {
+        Ok(matcher) => matcher,
+        Err(why) => {
+            warn!("Skipping auto-response {id}, its trigger doesn't compile: {why}");
+            return None;
+        }
+    };
+
+    Some(AutoResponse {
+        id,
+        response: response.to_string(),
+        cooldown: Duration::from_secs(cooldown.max(0) as u64),
+        channels: channels.split(',').filter_map(|channel| channel.trim().parse().ok()).collect(),
+        matcher,
+        last_fired: None
+    })
+}
+
+/// Sends the first auto-response whose trigger matches the message, if any. Commands never
+/// trigger auto-responses.
+pub async fn handle_auto_responses(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    let (cache, guild_settings) = match (get_data::<AutoResponsesContainer>(ctx).await, get_data::<GuildSettingsContainer>(ctx).await) {
+        (Ok(cache), Ok(guild_settings)) => (cache, guild_settings),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't check message {} for auto responses: {why}", msg.id);
+            return;
+        }
+    };
+
+    let prefix = guild_settings.read(guild_id.get(), |settings| settings.prefix.clone()).await.unwrap_or_else(|| "-".to_string());
+
+    if msg.content.starts_with(&prefix) {
+        return;
+    }
+
+    let template = {
+        let mut cache = cache.write().await;
+        let Some(responses) = cache.get_mut(&guild_id.get()) else {
+            return;
+        };
+
+        let Some(response) = responses.iter_mut()
+            .find(|response| response.is_ready(msg.channel_id.get()) && response.matcher.is_match(&msg.content)) else {
+            return;
+        };
+
+        response.last_fired = Some(Instant::now());
+        response.response.clone()
+    };
+
+    let context = message_context(ctx, msg).await;
+    let content = render_template(&template, &context).unwrap_or(template);
+
+    // auto-responses can't ping everyone or roles
+    let builder = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+    if let Err(why) = msg.channel_id.send_message(ctx, builder).await {
+        error!("Failed to send auto-response in channel {}: {why}", msg.channel_id);
+    }
+}
@@ -0,0 +1,118 @@
+use serenity::model::event::GuildMemberUpdateEvent;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::verification::awaiting_verification;
+use crate::utilities::errors::get_data;
+
+/// Checks that the bot can hand out a role: it can't be managed by an integration and has to sit
+/// below the bot's highest role. Returns a user-facing reason when it can't.
+pub fn check_assignable(ctx: &Context, guild_id: GuildId, role_id: RoleId) -> Result<(), String> {
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return Err("This server isn't cached yet, please try again in a moment.".to_string());
+    };
+
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Err("That role doesn't exist in this server.".to_string());
+    };
+
+    if role.managed || role.id.get() == guild_id.get() {
+        return Err(format!("{} can't be given out by hand.", role.name));
+    }
+
+    let bot_position = guild.members.get(&ctx.cache.current_user().id)
+        .and_then(|member| guild.member_highest_role(member))
+        .map_or(0, |highest| highest.position);
+
+    if bot_position <= role.position {
+        return Err(format!("My highest role must be above {} to give it out.", role.name));
+    }
+
+    Ok(())
+}
+
+pub async fn assign_autoroles(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<(), sqlx::Error> {
+    let db_guild_id = guild_id.get() as i64;
+
+    let roles = sqlx::query!("SELECT role_id FROM autoroles WHERE guild_id = ?", db_guild_id)
+        .fetch_all(database)
+        .await?;
+
+    for row in roles {
+        let role_id = RoleId::new(row.role_id as u64);
+
+        if let Err(why) = ctx.http.add_member_role(guild_id, user_id, role_id, Some("Autorole")).await {
+            warn!("Couldn't give autorole {role_id} to user {user_id} in guild {guild_id}: {why}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn waits_for_screening(database: &SqlitePool, guild_id: GuildId) -> Result<bool, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let waits = sqlx::query!("SELECT autorole_after_screening FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .is_some_and(|row| row.autorole_after_screening != 0);
+
+    Ok(waits)
+}
+
+/// Gives a new member their guild's autoroles, unless they have to pass membership screening first.
+pub async fn handle_autoroles_join(ctx: &Context, member: &Member) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't give autoroles to member {}: {why}", member.user.id);
+            return;
+        }
+    };
+
+    let result = async {
+        if member.pending && waits_for_screening(&database, member.guild_id).await? {
+            return Ok(());
+        }
+
+        assign_autoroles(ctx, &database, member.guild_id, member.user.id).await
+    }.await;
+
+    if let Err(why) = result {
+        warn!("Couldn't give autoroles to user {} in guild {}: {why}", member.user.id, member.guild_id);
+    }
+}
+
+/// Gives a member their guild's autoroles once they pass membership screening, if the guild
+/// waits for it.
+pub async fn handle_autoroles_screening(ctx: &Context, old: Option<&Member>, event: &GuildMemberUpdateEvent) {
+    // without the old member there's no telling whether screening was just passed
+    if event.pending || !old.is_some_and(|old| old.pending) {
+        return;
+    }
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't give autoroles to member {} after screening: {why}", event.user.id);
+            return;
+        }
+    };
+
+    let result = async {
+        // members who still have to verify get their autoroles once they do
+        if waits_for_screening(&database, event.guild_id).await? && !awaiting_verification(&database, event.guild_id, event.user.id).await? {
+            assign_autoroles(ctx, &database, event.guild_id, event.user.id).await?;
+        }
+
+        Ok::<(), sqlx::Error>(())
+    }.await;
+
+    if let Err(why) = result {
+        warn!("Couldn't give autoroles to user {} in guild {}: {why}", event.user.id, event.guild_id);
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::scheduler::{Job, When, schedule_job};
+use crate::utilities::timezones::guild_timezone_or_utc;
+use crate::utilities::errors::get_data;
+
+/// How often guilds are checked for a new day having started.
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(5 * 60);
+
+/// How long the birthday role is kept.
+const ROLE_DURATION: Duration = Duration::hours(24);
+
+const MONTHS: [&str; 12] = ["january", "february", "march", "april", "may", "june", "july", "august", "september", "october", "november", "december"];
+
+/// A birthday as a member gave it, with the year only if they included it.
+#[derive(Clone, Copy)]
+pub struct Birthday {
+    pub month: u32,
+    pub day: u32,
+    pub year: Option<i32>
+}
+
+impl Birthday {
+    /// Parses `2000-03-14`, `03-14`, `March 14` or `14 March`. Month names can be shortened.
+    pub fn parse(arg: &str) -> Option<Birthday> {
+        let arg = arg.trim().to_lowercase();
+        let parts = arg.split(['-', '/', ' ', ',']).filter(|part| !part.is_empty()).collect::<Vec<_>>();
+
+        let birthday = match parts.as_slice() {
+            [year, month, day] if year.len() == 4 => Birthday { month: month.parse().ok()?, day: day.parse().ok()?, year: Some(year.parse().ok()?) },
+            [first, second] => match (parse_month(first), parse_month(second)) {
+                (Some(month), None) => Birthday { month, day: second.parse().ok()?, year: None },
+                (None, Some(month)) => Birthday { month, day: first.parse().ok()?, year: None },
+                _ => Birthday { month: first.parse().ok()?, day: second.parse().ok()?, year: None }
+            },
+            _ => return None
+        };
+
+        // 2000 was a leap year, so the 29th of February is allowed without a year
+        NaiveDate::from_ymd_opt(birthday.year.unwrap_or(2000), birthday.month, birthday.day)?;
+
+        let this_year = Utc::now().year();
+        birthday.year.is_none_or(|year| (1900..=this_year).contains(&year)).then_some(birthday)
+    }
+
+    /// Whether the birthday is on a date. Birthdays on the 29th of February are on the 28th in
+    /// other years.
+    pub fn is_on(&self, date: NaiveDate) -> bool {
+        if (self.month, self.day) == (2, 29) && !is_leap_year(date.year()) {
+            return (date.month(), date.day()) == (2, 28);
+        }
+
+        (date.month(), date.day()) == (self.month, self.day)
+    }
+
+    /// The next date the birthday is on, counting today.
+    pub fn next_after(&self, today: NaiveDate) -> NaiveDate {
+        (0..=366)
+            .filter_map(|days| today.checked_add_signed(Duration::days(days)))
+            .find(|date| self.is_on(*date))
+            .unwrap_or(today)
+    }
+}
+
+impl std::fmt::Display for Birthday {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let month = MONTHS[self.month as usize - 1];
+        let month = format!("{}{}", month[..1].to_uppercase(), &month[1..]);
+
+        match self.year {
+            Some(year) => write!(f, "{month} {}, {year}", self.day),
+            None => write!(f, "{month} {}", self.day)
+        }
+    }
+}
+
+fn parse_month(arg: &str) -> Option<u32> {
+    if arg.len() < 3 {
+        return None;
+    }
+
+    MONTHS.iter().position(|month| month.starts_with(arg)).map(|index| index as u32 + 1)
+}
+
+fn is_leap_year(year: i32) -> bool {
+    NaiveDate::from_ymd_opt(year, 2, 29).is_some()
+}
+
+pub async fn get_birthday(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<Option<Birthday>, sqlx::Error> {
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let row = sqlx::query!("SELECT month, day, year FROM birthdays WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.map(|row| Birthday { month: row.month as u32, day: row.day as u32, year: row.year.map(|year| year as i32) }))
+}
+
+/// Announces birthdays once each guild's local day starts (see `timezone server`), and gives out the birthday role.
+pub async fn birthday_loop(ctx: Context) {
+    let database = match get_data::<DatabaseConnectionContainer>(&ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't start announcing birthdays: {why}");
+            return;
+        }
+    };
+
+    loop {
+        if let Err(why) = announce_birthdays(&ctx, &database).await {
+            error!("Failed to announce birthdays: {why}");
+        }
+
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn announce_birthdays(ctx: &Context, database: &SqlitePool) -> Result<(), sqlx::Error> {
+    let guilds = sqlx::query!(
+        "SELECT guild_id, channel_id, role_id, last_announced FROM birthday_settings WHERE channel_id IS NOT NULL OR role_id IS NOT NULL"
+    ).fetch_all(database).await?;
+
+    for settings in guilds {
+        let guild_id = GuildId::new(settings.guild_id as u64);
+        let today = Utc::now().with_timezone(&guild_timezone_or_utc(database, guild_id).await?).date_naive();
+        let date = today.format("%Y-%m-%d").to_string();
+
+        if settings.last_announced.as_deref() == Some(date.as_str()) {
+            continue;
+        }
+
+        let birthdays = sqlx::query!("SELECT user_id, month, day FROM birthdays WHERE guild_id = ?", settings.guild_id)
+            .fetch_all(database)
+            .await?;
+
+        let mut celebrated = Vec::new();
+
+        for row in birthdays {
+            let birthday = Birthday { month: row.month as u32, day: row.day as u32, year: None };
+
+            if !birthday.is_on(today) {
+                continue;
+            }
+
+            // members who left keep their birthday in case they come back, but aren't celebrated
+            let user_id = UserId::new(row.user_id as u64);
+
+            if guild_id.member(ctx, user_id).await.is_ok() {
+                celebrated.push(user_id);
+            }
+        }
+
+        if let Some(role_id) = settings.role_id.map(|role_id| RoleId::new(role_id as u64)) {
+            for user_id in &celebrated {
+                give_birthday_role(ctx, database, guild_id, *user_id, role_id).await;
+            }
+        }
+
+        if let (Some(channel_id), false) = (settings.channel_id, celebrated.is_empty()) {
+            let channel_id = ChannelId::new(channel_id as u64);
+            let mentions = celebrated.iter().map(|user_id| user_id.mention().to_string()).collect::<Vec<_>>().join(", ");
+
+            let builder = CreateMessage::new()
+                .content(format!("🎂 Happy birthday {mentions}!"))
+                .allowed_mentions(CreateAllowedMentions::new().users(celebrated.clone()));
+
+            if let Err(why) = channel_id.send_message(ctx, builder).await {
+                warn!("Couldn't announce birthdays in channel {channel_id}: {why}");
+            }
+        }
+
+        sqlx::query!("UPDATE birthday_settings SET last_announced = ? WHERE guild_id = ?", date, settings.guild_id)
+            .execute(database)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Gives a member the birthday role, and schedules taking it back.
+async fn give_birthday_role(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId, role_id: RoleId) {
+    if let Err(why) = ctx.http.add_member_role(guild_id, user_id, role_id, Some("Birthday")).await {
+        warn!("Couldn't give the birthday role {role_id} to user {user_id} in guild {guild_id}: {why}");
+        return;
+    }
+
+    let job = Job::RemoveRole { user_id: user_id.get(), role_id: role_id.get() };
+    let when = When { first_run: Utc::now() + ROLE_DURATION, repeat: None };
+    let bot_id = ctx.cache.current_user().id.get();
+
+    if let Err(why) = schedule_job(database, Some(guild_id), &job, &when, bot_id).await {
+        error!("Failed to schedule removing the birthday role from user {user_id} in guild {guild_id}: {why}");
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::utilities::global_data::BlacklistContainer;
+use crate::utilities::errors::get_data;
+
+/// Users and guilds the bot ignores everywhere.
+#[derive(Default)]
+pub struct Blacklist {
+    pub users: HashSet<u64>,
+    pub guilds: HashSet<u64>
+}
+
+/// What can be blacklisted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlacklistKind {
+    User,
+    Guild
+}
+
+impl BlacklistKind {
+    /// The name stored in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            BlacklistKind::User => "user",
+            BlacklistKind::Guild => "guild"
+        }
+    }
+}
+
+impl Blacklist {
+    pub fn entries_mut(&mut self, kind: BlacklistKind) -> &mut HashSet<u64> {
+        match kind {
+            BlacklistKind::User => &mut self.users,
+            BlacklistKind::Guild => &mut self.guilds
+        }
+    }
+}
+
+pub async fn load_blacklist(database: &SqlitePool) -> Result<Blacklist, sqlx::Error> {
+    let rows = sqlx::query!("SELECT kind, id FROM blacklist")
+        .fetch_all(database)
+        .await?;
+
+    let mut blacklist = Blacklist::default();
+
+    for row in rows {
+        match row.kind.as_str() {
+            "user" => blacklist.users.insert(row.id as u64),
+            "guild" => blacklist.guilds.insert(row.id as u64),
+            _ => continue
+        };
+    }
+
+    Ok(blacklist)
+}
+
+/// Whether the bot should ignore something done by `user_id`, or in `guild_id`, because either
+/// is blacklisted. This is the one check every entry point runs before handling anything.
+pub async fn is_blacklisted(ctx: &Context, user_id: Option<UserId>, guild_id: Option<GuildId>) -> bool {
+    let blacklist = match get_data::<BlacklistContainer>(ctx).await {
+        Ok(blacklist) => blacklist,
+        Err(why) => {
+            error!("Couldn't check the blacklist: {why}");
+            return false;
+        }
+    };
+
+    let blacklist = blacklist.read().await;
+
+    user_id.is_some_and(|user_id| blacklist.users.contains(&user_id.get()))
+        || guild_id.is_some_and(|guild_id| blacklist.guilds.contains(&guild_id.get()))
+}
@@ -0,0 +1,104 @@
+use std::env;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use serenity::prelude::Context;
+use tracing::{info, warn, error};
+
+use crate::utilities::global_data::ReqwestClientContainer;
+use crate::utilities::errors::get_data;
+
+/// How often statistics are posted to each bot list.
+const POST_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// First retry delay after a failed post, doubled on every consecutive failure up to `POST_INTERVAL`.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy)]
+pub enum BotList {
+    TopGg,
+    DiscordBotsGg
+}
+
+impl BotList {
+    pub const ALL: [BotList; 2] = [BotList::TopGg, BotList::DiscordBotsGg];
+
+    fn name(self) -> &'static str {
+        match self {
+            BotList::TopGg => "top.gg",
+            BotList::DiscordBotsGg => "discord.bots.gg"
+        }
+    }
+
+    /// Environment variable holding the list's API token.
+    fn token_key(self) -> &'static str {
+        match self {
+            BotList::TopGg => "TOPGG_TOKEN",
+            BotList::DiscordBotsGg => "DISCORD_BOTS_GG_TOKEN"
+        }
+    }
+
+    fn url(self, bot_id: u64) -> String {
+        match self {
+            BotList::TopGg => format!("https://top.gg/api/bots/{bot_id}/stats"),
+            BotList::DiscordBotsGg => format!("https://discord.bots.gg/api/v1/bots/{bot_id}/stats")
+        }
+    }
+
+    fn body(self, guild_count: usize, shard_count: u32) -> Value {
+        match self {
+            BotList::TopGg => json!({ "server_count": guild_count, "shard_count": shard_count }),
+            BotList::DiscordBotsGg => json!({ "guildCount": guild_count, "shardCount": shard_count })
+        }
+    }
+}
+
+/// Periodically posts the guild and shard counts to a bot list. Returns straight away if no token
+/// is configured for the list.
+pub async fn post_stats_loop(ctx: Context, list: BotList) {
+    let Ok(token) = env::var(list.token_key()) else {
+        return;
+    };
+
+    let client = match get_data::<ReqwestClientContainer>(&ctx).await {
+        Ok(client) => client,
+        Err(why) => {
+            error!("Couldn't start posting stats: {why}");
+            return;
+        }
+    };
+
+    let mut failures = 0;
+
+    loop {
+        let bot_id = ctx.cache.current_user().id.get();
+        let body = list.body(ctx.cache.guild_count(), ctx.cache.shard_count());
+
+        let result = client.post(list.url(bot_id))
+            .header("Authorization", &token)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        let delay = match result {
+            Ok(_) => {
+                if failures > 0 {
+                    info!("Posting stats to {} succeeded after {failures} failed attempt(s)", list.name());
+                }
+
+                failures = 0;
+                POST_INTERVAL
+            }
+            Err(err) => {
+                failures += 1;
+                let delay = RETRY_DELAY.saturating_mul(2u32.saturating_pow(failures - 1)).min(POST_INTERVAL);
+                warn!("Failed to post stats to {} ({failures} in a row), retrying in {}s: {err}", list.name(), delay.as_secs());
+
+                delay
+            }
+        };
+
+        tokio::time::sleep(delay).await;
+    }
+}
@@ -0,0 +1,78 @@
+use serenity::builder::{CreateEmbed, CreateEmbedFooter};
+use serenity::model::id::GuildId;
+use serenity::prelude::Context;
+use tracing::error;
+
+use crate::utilities::global_data::GuildSettingsContainer;
+use crate::utilities::errors::get_data;
+
+/// The color of every embed in guilds that haven't picked their own.
+pub const DEFAULT_EMBED_COLOR: u32 = 0x008b_0000;
+
+/// Longest footer a guild can put on its embeds, well under Discord's limit so commands' own
+/// footers still fit when they're joined.
+pub const MAX_FOOTER_LENGTH: usize = 200;
+
+/// How a guild's embeds look.
+#[derive(Clone, Default)]
+pub struct Branding {
+    pub color: Option<u32>,
+    pub footer: Option<String>,
+    pub thumbnail: Option<String>
+}
+
+impl Branding {
+    pub fn color(&self) -> u32 {
+        self.color.unwrap_or(DEFAULT_EMBED_COLOR)
+    }
+
+    /// A new embed in the guild's color, with its footer and thumbnail if it has them. Commands
+    /// that set a footer or thumbnail of their own, such as page numbers or an avatar, replace
+    /// the guild's.
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::new().color(self.color());
+
+        if let Some(footer) = &self.footer {
+            embed = embed.footer(CreateEmbedFooter::new(footer));
+        }
+
+        if let Some(thumbnail) = &self.thumbnail {
+            embed = embed.thumbnail(thumbnail);
+        }
+
+        embed
+    }
+}
+
+/// A guild's branding. Direct messages, and guilds without settings, get the default.
+pub async fn guild_branding(ctx: &Context, guild_id: Option<GuildId>) -> Branding {
+    let Some(guild_id) = guild_id else {
+        return Branding::default();
+    };
+
+    let guild_settings = match get_data::<GuildSettingsContainer>(ctx).await {
+        Ok(guild_settings) => guild_settings,
+        Err(why) => {
+            error!("Couldn't read the branding of guild {guild_id}: {why}");
+            return Branding::default();
+        }
+    };
+
+    guild_settings.read(guild_id.get(), |settings| settings.branding.clone()).await.unwrap_or_default()
+}
+
+/// A new embed in a guild's branding, which every embed the bot sends starts from.
+pub async fn branded_embed(ctx: &Context, guild_id: Option<GuildId>) -> CreateEmbed {
+    guild_branding(ctx, guild_id).await.embed()
+}
+
+/// A color given as hex, such as `#8b0000` or `8b0000`.
+pub fn parse_color(arg: &str) -> Option<u32> {
+    let hex = arg.trim_start_matches('#');
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok()
+}
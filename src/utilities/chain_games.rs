@@ -0,0 +1,320 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::{Message, ReactionType};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{ChainGamesContainer, DatabaseConnectionContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// The games a channel can be set up for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ChainKind {
+    /// Members count up from 1, one number each.
+    Counting,
+    /// Members take turns saying a word starting with the last letter of the previous one.
+    Words
+}
+
+impl ChainKind {
+    pub fn parse(arg: &str) -> Option<ChainKind> {
+        match arg.to_lowercase().as_str() {
+            "counting" | "count" => Some(ChainKind::Counting),
+            "words" | "word" | "wordchain" | "shiritori" => Some(ChainKind::Words),
+            _ => None
+        }
+    }
+
+    /// The name stored in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            ChainKind::Counting => "counting",
+            ChainKind::Words => "words"
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ChainKind::Counting => "Counting",
+            ChainKind::Words => "Word chain"
+        }
+    }
+}
+
+/// A game channel and where its current streak is at.
+pub struct ChainGame {
+    pub guild_id: GuildId,
+    pub kind: ChainKind,
+    streak: i64,
+    last_user_id: Option<UserId>,
+    last_word: Option<String>,
+    record: i64,
+    /// Words used in the current chain.
+    used_words: HashSet<String>
+}
+
+impl ChainGame {
+    fn new(guild_id: GuildId, kind: ChainKind, record: i64) -> ChainGame {
+        ChainGame { guild_id, kind, streak: 0, last_user_id: None, last_word: None, record, used_words: HashSet::new() }
+    }
+
+    /// Checks a message against the game, moving the streak on or breaking it.
+    fn play(&mut self, user_id: UserId, content: &str) -> Turn {
+        let content = content.trim();
+
+        let word = match self.kind {
+            ChainKind::Counting => {
+                // anything after the number is chatter
+                let Some(number) = content.split_whitespace().next().and_then(|number| number.parse::<i64>().ok()) else {
+                    return Turn::Ignored;
+                };
+
+                if self.last_user_id == Some(user_id) {
+                    return self.break_streak("can't count twice in a row");
+                }
+
+                if number != self.streak + 1 {
+                    return self.break_streak("counted the wrong number");
+                }
+
+                None
+            }
+            ChainKind::Words => {
+                // messages that aren't a single word are chatter
+                if content.chars().count() < 2 || !content.chars().all(char::is_alphabetic) {
+                    return Turn::Ignored;
+                }
+
+                let word = content.to_lowercase();
+
+                if self.last_user_id == Some(user_id) {
+                    return self.break_streak("can't add two words in a row");
+                }
+
+                if self.last_word.as_deref().and_then(|last| last.chars().last()).is_some_and(|last| !word.starts_with(last)) {
+                    return self.break_streak("didn't start with the last letter of the previous word");
+                }
+
+                if self.used_words.contains(&word) {
+                    return self.break_streak("used a word that's already in this chain");
+                }
+
+                self.used_words.insert(word.clone());
+
+                Some(word)
+            }
+        };
+
+        let previous_record = self.record;
+
+        self.streak += 1;
+        self.record = self.record.max(self.streak);
+        self.last_user_id = Some(user_id);
+        self.last_word.clone_from(&word);
+
+        // only the message that beats the record is celebrated, not every one after it
+        Turn::Continued { streak: self.streak, word, new_record: previous_record > 0 && self.streak == previous_record + 1 }
+    }
+
+    fn break_streak(&mut self, reason: &'static str) -> Turn {
+        let streak = self.streak;
+
+        self.streak = 0;
+        self.last_user_id = None;
+        self.last_word = None;
+        self.used_words.clear();
+
+        Turn::Broken { streak, record: self.record, reason }
+    }
+}
+
+enum Turn {
+    Ignored,
+    Continued { streak: i64, word: Option<String>, new_record: bool },
+    Broken { streak: i64, record: i64, reason: &'static str }
+}
+
+pub async fn load_chain_games(database: &SqlitePool) -> Result<HashMap<u64, ChainGame>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT channel_id, guild_id, kind, streak, last_user_id, last_word, record FROM chain_games")
+        .fetch_all(database)
+        .await?;
+
+    let mut games = rows.into_iter()
+        .filter_map(|row| {
+            let mut game = ChainGame::new(GuildId::new(row.guild_id as u64), ChainKind::parse(&row.kind)?, row.record);
+            (game.streak, game.last_user_id, game.last_word) = (row.streak, row.last_user_id.map(|id| UserId::new(id as u64)), row.last_word);
+
+            Some((row.channel_id as u64, game))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let words = sqlx::query!("SELECT channel_id, word FROM chain_game_words")
+        .fetch_all(database)
+        .await?;
+
+    for row in words {
+        if let Some(game) = games.get_mut(&(row.channel_id as u64)) {
+            game.used_words.insert(row.word);
+        }
+    }
+
+    Ok(games)
+}
+
+/// Reloads one channel's game from the database after it's been set up or removed.
+pub async fn reload_chain_game(ctx: &Context, channel_id: ChannelId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let games = get_data::<ChainGamesContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    let row = sqlx::query!("SELECT guild_id, kind, record FROM chain_games WHERE channel_id = ?", db_channel_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let mut games = games.lock().await;
+
+    match row.and_then(|row| Some(ChainGame::new(GuildId::new(row.guild_id as u64), ChainKind::parse(&row.kind)?, row.record))) {
+        Some(game) => {
+            games.insert(channel_id.get(), game);
+        }
+        None => {
+            games.remove(&channel_id.get());
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays a message sent in a game channel. Returns whether it broke the streak and was deleted.
+pub async fn handle_chain_game(ctx: &Context, msg: &Message) -> bool {
+    let (database, games) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<ChainGamesContainer>(ctx).await) {
+        (Ok(database), Ok(games)) => (database, games),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't check message {} for a chain game: {why}", msg.id);
+            return false;
+        }
+    };
+
+    // the game stays locked until it's saved, so quick messages are saved in order
+    let (kind, turn) = {
+        let mut games = games.lock().await;
+
+        let Some(game) = games.get_mut(&msg.channel_id.get()) else {
+            return false;
+        };
+
+        let (kind, turn) = (game.kind, game.play(msg.author.id, &msg.content));
+
+        if let Err(why) = save_turn(&database, game.guild_id, msg.channel_id, msg.author.id, kind, &turn).await {
+            error!("Failed to save the {} game in channel {}: {why}", kind.name(), msg.channel_id);
+        }
+
+        (kind, turn)
+    };
+
+    match turn {
+        Turn::Ignored => false,
+        Turn::Continued { new_record, .. } => {
+            if new_record {
+                let _ = msg.react(ctx, ReactionType::Unicode("🏆".to_string())).await;
+            }
+
+            false
+        }
+        Turn::Broken { streak, record, reason } => {
+            if let Err(why) = msg.delete(ctx).await {
+                warn!("Couldn't delete a message that broke the {} game in channel {}: {why}", kind.name(), msg.channel_id);
+            }
+
+            let restart = match kind {
+                ChainKind::Counting => "Start again from **1**.",
+                ChainKind::Words => "Start a new chain with any word."
+            };
+
+            let content = match streak {
+                0 => format!("{} {reason}. {restart}", msg.author.id.mention()),
+                _ => format!("{} {reason}, ending the streak at **{streak}** (record: **{record}**). {restart}", msg.author.id.mention())
+            };
+
+            let builder = CreateMessage::new()
+                .content(content)
+                .allowed_mentions(CreateAllowedMentions::new().users([msg.author.id]));
+
+            if let Err(why) = msg.channel_id.send_message(ctx, builder).await {
+                warn!("Couldn't announce a broken streak in channel {}: {why}", msg.channel_id);
+            }
+
+            true
+        }
+    }
+}
+
+async fn save_turn(database: &SqlitePool, guild_id: GuildId, channel_id: ChannelId, user_id: UserId, kind: ChainKind, turn: &Turn) -> Result<(), sqlx::Error> {
+    let (guild_id, channel_id, user_id, kind_name) = (guild_id.get() as i64, channel_id.get() as i64, user_id.get() as i64, kind.name());
+
+    match turn {
+        Turn::Ignored => {}
+        Turn::Continued { streak, word, .. } => {
+            sqlx::query!(
+                "UPDATE chain_games SET streak = ?, last_user_id = ?, last_word = ?, record = MAX(record, ?) WHERE channel_id = ?",
+                streak,
+                user_id,
+                word,
+                streak,
+                channel_id
+            ).execute(database).await?;
+
+            if let Some(word) = word {
+                sqlx::query!("INSERT OR IGNORE INTO chain_game_words (channel_id, word) VALUES (?, ?)", channel_id, word)
+                    .execute(database)
+                    .await?;
+            }
+
+            sqlx::query!(
+                "INSERT INTO chain_game_players (guild_id, user_id, kind, correct) VALUES (?, ?, ?, 1)
+                ON CONFLICT (guild_id, user_id, kind) DO UPDATE SET correct = correct + 1",
+                guild_id,
+                user_id,
+                kind_name
+            ).execute(database).await?;
+        }
+        Turn::Broken { streak, .. } => {
+            if *streak > 0 {
+                let ended_at = Utc::now().to_rfc3339();
+
+                sqlx::query!(
+                    "INSERT INTO chain_game_streaks (guild_id, channel_id, kind, length, broken_by, ended_at) VALUES (?, ?, ?, ?, ?, ?)",
+                    guild_id,
+                    channel_id,
+                    kind_name,
+                    streak,
+                    user_id,
+                    ended_at
+                ).execute(database).await?;
+            }
+
+            sqlx::query!("UPDATE chain_games SET streak = 0, last_user_id = NULL, last_word = NULL WHERE channel_id = ?", channel_id)
+                .execute(database)
+                .await?;
+
+            sqlx::query!("DELETE FROM chain_game_words WHERE channel_id = ?", channel_id)
+                .execute(database)
+                .await?;
+
+            sqlx::query!(
+                "INSERT INTO chain_game_players (guild_id, user_id, kind, mistakes) VALUES (?, ?, ?, 1)
+                ON CONFLICT (guild_id, user_id, kind) DO UPDATE SET mistakes = mistakes + 1",
+                guild_id,
+                user_id,
+                kind_name
+            ).execute(database).await?;
+        }
+    }
+
+    Ok(())
+}
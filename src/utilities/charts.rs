@@ -0,0 +1,238 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgb, RgbImage};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 300;
+const PADDING: u32 = 20;
+
+const BACKGROUND: Rgb<u8> = Rgb([0x2b, 0x2d, 0x31]);
+const GRID: Rgb<u8> = Rgb([0x3f, 0x41, 0x47]);
+const BAR: Rgb<u8> = Rgb([0x8b, 0x00, 0x00]);
+
+/// Word colors, from the most used word down.
+const WORD_COLORS: [Rgb<u8>; 5] = [
+    Rgb([0xe0, 0x40, 0x40]),
+    Rgb([0xf0, 0xa0, 0x40]),
+    Rgb([0xe8, 0xe8, 0xe8]),
+    Rgb([0x80, 0xb0, 0xe0]),
+    Rgb([0x90, 0x90, 0x98])
+];
+
+const CLOUD_HEIGHT: u32 = 400;
+const CARD_HEIGHT: u32 = 200;
+const MIN_SCALE: u32 = 2;
+const MAX_SCALE: u32 = 8;
+const WORD_GAP: u32 = 16;
+
+/// A 5x7 pixel font for `a`-`z` and `0`-`9`, one byte per row with the glyph in the low five bits.
+/// Word clouds only contain those characters, so this avoids shipping a font file.
+const GLYPHS: [[u8; 7]; 36] = [
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // a
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110], // b
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110], // c
+    [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110], // d
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111], // e
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000], // f
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111], // g
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // h
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // i
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // j
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001], // k
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111], // l
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001], // m
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001], // n
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // o
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000], // p
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101], // q
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001], // r
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110], // s
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // t
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // u
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // v
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010], // w
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001], // x
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100], // y
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111], // z
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110], // 0
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // 1
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111], // 2
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110], // 3
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010], // 4
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110], // 5
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110], // 6
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000], // 7
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110], // 8
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]  // 9
+];
+
+/// Draws a bar chart of `values` from left to right and encodes it as a PNG. The chart has no
+/// labels, so callers should describe the axes alongside it.
+pub fn render_bar_chart(values: &[i64]) -> Result<Vec<u8>, image::ImageError> {
+    let mut chart = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+
+    let plot_width = WIDTH - PADDING * 2;
+    let plot_height = HEIGHT - PADDING * 2;
+    let bottom = HEIGHT - PADDING;
+
+    // horizontal grid lines at every quarter of the highest value
+    for quarter in 0..=4 {
+        let y = bottom - plot_height * quarter / 4;
+        fill(&mut chart, PADDING, y, plot_width, 1, GRID);
+    }
+
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as u64;
+    let count = values.len().max(1) as u32;
+    let slot = plot_width / count;
+    // leave a gap between bars while they are wide enough for one
+    let gap = if slot >= 4 { (slot / 5).max(1) } else { 0 };
+
+    for (index, value) in values.iter().enumerate() {
+        let height = ((*value).max(0) as u64 * u64::from(plot_height) / max) as u32;
+
+        if height == 0 {
+            continue;
+        }
+
+        let x = PADDING + slot * index as u32 + gap / 2;
+        fill(&mut chart, x, bottom - height, (slot - gap).max(1), height, BAR);
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    chart.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// Draws a word cloud from `(word, uses)` pairs sorted by uses, most used first, and encodes it
+/// as a PNG. Words are sized by how often they're used and laid out in centered rows, and words
+/// that don't fit are left out.
+pub fn render_word_cloud(words: &[(String, i64)]) -> Result<Vec<u8>, image::ImageError> {
+    let mut cloud = RgbImage::from_pixel(WIDTH, CLOUD_HEIGHT, BACKGROUND);
+
+    let most = words.first().map_or(1, |(_, uses)| *uses).max(1);
+    let least = words.last().map_or(1, |(_, uses)| *uses).min(most);
+
+    let sized: Vec<(&str, u32, Rgb<u8>)> = words.iter()
+        .enumerate()
+        .map(|(rank, (word, uses))| {
+            let scale = if most == least {
+                MAX_SCALE
+            } else {
+                MIN_SCALE + ((uses - least) * i64::from(MAX_SCALE - MIN_SCALE) / (most - least)) as u32
+            };
+
+            (word.as_str(), scale, WORD_COLORS[(rank * WORD_COLORS.len() / words.len().max(1)).min(WORD_COLORS.len() - 1)])
+        })
+        .collect();
+
+    // pack words into rows, each as tall as its largest word
+    let mut rows: Vec<Vec<(&str, u32, Rgb<u8>)>> = Vec::new();
+    let mut row_width = 0;
+
+    for word in sized {
+        let width = text_width(word.0, word.1);
+
+        if width > WIDTH - PADDING * 2 {
+            continue;
+        }
+
+        match rows.last_mut() {
+            Some(row) if row_width + WORD_GAP + width <= WIDTH - PADDING * 2 => {
+                row_width += WORD_GAP + width;
+                row.push(word);
+            }
+            _ => {
+                row_width = width;
+                rows.push(vec![word]);
+            }
+        }
+    }
+
+    let mut y = PADDING;
+
+    for row in rows {
+        let height = row.iter().map(|(_, scale, _)| scale * 7).max().unwrap_or_default();
+
+        if y + height > CLOUD_HEIGHT - PADDING {
+            break;
+        }
+
+        let width = row.iter().map(|(word, scale, _)| text_width(word, *scale)).sum::<u32>() + WORD_GAP * (row.len() as u32 - 1);
+        let mut x = (WIDTH - width) / 2;
+
+        for (word, scale, color) in row {
+            // align words on a shared baseline
+            draw_text(&mut cloud, word, x, y + height - scale * 7, scale, color);
+            x += text_width(word, scale) + WORD_GAP;
+        }
+
+        y += height + WORD_GAP;
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    cloud.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// Draws a rank card showing a member's level and rank, with a bar filled by their progress
+/// towards the next level, and encodes it as a PNG.
+pub fn render_rank_card(level: i64, rank: i64, progress: i64, needed: i64) -> Result<Vec<u8>, image::ImageError> {
+    let mut card = RgbImage::from_pixel(WIDTH, CARD_HEIGHT, BACKGROUND);
+
+    draw_text(&mut card, &format!("level {level}"), PADDING * 2, PADDING * 2, 6, WORD_COLORS[2]);
+
+    let rank = format!("rank {rank}");
+    draw_text(&mut card, &rank, WIDTH - PADDING * 2 - text_width(&rank, 4), PADDING * 2 + 12, 4, WORD_COLORS[1]);
+
+    let bar_width = WIDTH - PADDING * 4;
+    let filled = (progress.clamp(0, needed.max(1)) as u64 * u64::from(bar_width) / needed.max(1) as u64) as u32;
+
+    fill(&mut card, PADDING * 2, 110, bar_width, 28, GRID);
+    fill(&mut card, PADDING * 2, 110, filled, 28, BAR);
+
+    let xp = format!("{progress} of {needed} xp");
+    draw_text(&mut card, &xp, WIDTH - PADDING * 2 - text_width(&xp, 3), 152, 3, WORD_COLORS[4]);
+
+    let mut png = Cursor::new(Vec::new());
+    card.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// How wide `text` is when drawn with `draw_text` at `scale`.
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let characters = text.chars().count() as u32;
+    (characters * 6).saturating_sub(1) * scale
+}
+
+/// Draws `text` in the 5x7 pixel font, skipping characters it has no glyph for.
+pub fn draw_text(image: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32, color: Rgb<u8>) {
+    for (index, character) in text.chars().enumerate() {
+        let glyph = match character {
+            'a'..='z' => GLYPHS[(character as u8 - b'a') as usize],
+            '0'..='9' => GLYPHS[26 + (character as u8 - b'0') as usize],
+            _ => continue
+        };
+
+        let left = x + index as u32 * 6 * scale;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0b10000 >> column) != 0 {
+                    fill(image, left + column * scale, y + row as u32 * scale, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// Fills a rectangle, clipped to the image.
+pub fn fill(chart: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for px in x..(x + width).min(chart.width()) {
+        for py in y..(y + height).min(chart.height()) {
+            chart.put_pixel(px, py, color);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, RoleId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::COMMAND_GROUPS;
+use crate::utilities::command_rules::ALL_COMMANDS;
+use crate::utilities::global_data::{CommandPermissionsContainer, DatabaseConnectionContainer};
+use crate::utilities::invocation::Invocation;
+use crate::utilities::errors::{get_data, BotError};
+
+/// Every guild's command permissions, as (rule name, role) pairs keyed by guild.
+pub async fn load_command_permissions(database: &SqlitePool) -> Result<HashMap<u64, Vec<(String, RoleId)>>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, name, role_id FROM command_permissions")
+        .fetch_all(database)
+        .await?;
+
+    let mut permissions: HashMap<u64, Vec<(String, RoleId)>> = HashMap::new();
+
+    for row in rows {
+        permissions.entry(row.guild_id as u64).or_default().push((row.name, RoleId::new(row.role_id as u64)));
+    }
+
+    Ok(permissions)
+}
+
+/// Reloads one server's command permissions from the database after they've been changed.
+pub async fn reload_command_permissions(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let command_permissions = get_data::<CommandPermissionsContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let permissions = sqlx::query!("SELECT name, role_id FROM command_permissions WHERE guild_id = ?", db_guild_id)
+        .fetch_all(&database)
+        .await?
+        .into_iter()
+        .map(|row| (row.name, RoleId::new(row.role_id as u64)))
+        .collect::<Vec<_>>();
+
+    let mut command_permissions = command_permissions.write().await;
+
+    if permissions.is_empty() {
+        command_permissions.remove(&guild_id.get());
+    } else {
+        command_permissions.insert(guild_id.get(), permissions);
+    }
+
+    Ok(())
+}
+
+/// Whether someone with `roles` may use a top-level command, going by the roles mapped to the
+/// command itself, then its group, then `all`. The most specific name with any roles decides,
+/// and None means no roles are mapped to any of them.
+pub async fn role_access(ctx: &Context, guild_id: GuildId, roles: &[RoleId], command: &str) -> Option<bool> {
+    let group = COMMAND_GROUPS.iter()
+        .find(|group| group.options.commands.iter().any(|root| root.options.names[0] == command))
+        .map(|group| group.name.to_lowercase());
+
+    let command_permissions = match get_data::<CommandPermissionsContainer>(ctx).await {
+        Ok(command_permissions) => command_permissions,
+        Err(why) => {
+            error!("Couldn't read the command permissions of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    let command_permissions = command_permissions.read().await;
+    let permissions = command_permissions.get(&guild_id.get())?;
+
+    let names = [Some(command), group.as_deref(), Some(ALL_COMMANDS)];
+
+    let access = names.into_iter()
+        .flatten()
+        .map(|name| permissions.iter().filter(|(mapped, _)| mapped == name).map(|(_, role_id)| *role_id).collect::<Vec<_>>())
+        .find(|mapped| !mapped.is_empty())
+        .map(|mapped| mapped.iter().any(|role_id| roles.contains(role_id)));
+
+    access
+}
+
+/// Whether the message's author may use a top-level command. Administrators always can, so a
+/// server can't lock itself out of its own settings.
+pub async fn member_allowed(ctx: &Context, msg: &Message, command: &str) -> bool {
+    user_allowed(ctx, &Invocation::Message(msg), command).await
+}
+
+/// Whether whoever ran a prefix or slash command may use a top-level command.
+pub async fn user_allowed(ctx: &Context, invocation: &Invocation<'_>, command: &str) -> bool {
+    let Some(guild_id) = invocation.guild_id() else {
+        return true;
+    };
+
+    match role_access(ctx, guild_id, &invocation.roles(), command).await {
+        Some(false) => invocation.is_administrator(ctx).await,
+        _ => true
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use serenity::framework::standard::{Command, CommandGroup};
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::COMMAND_GROUPS;
+use crate::utilities::global_data::{CommandRulesContainer, DatabaseConnectionContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// The rule name that covers every command.
+pub const ALL_COMMANDS: &str = "all";
+
+/// Turns a command, a group of commands or every command off in a server or a channel, or limits
+/// them to the channels they're allowed in.
+pub struct CommandRule {
+    pub name: String,
+    /// None for the whole server.
+    pub channel_id: Option<ChannelId>,
+    pub allowed: bool
+}
+
+pub async fn load_command_rules(database: &SqlitePool) -> Result<HashMap<u64, Vec<CommandRule>>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, name, channel_id, allowed FROM command_rules")
+        .fetch_all(database)
+        .await?;
+
+    let mut rules: HashMap<u64, Vec<CommandRule>> = HashMap::new();
+
+    for row in rows {
+        rules.entry(row.guild_id as u64).or_default().push(CommandRule {
+            name: row.name,
+            channel_id: (row.channel_id != 0).then(|| ChannelId::new(row.channel_id as u64)),
+            allowed: row.allowed != 0
+        });
+    }
+
+    Ok(rules)
+}
+
+/// Reloads one server's command rules from the database after they've been changed.
+pub async fn reload_command_rules(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let command_rules = get_data::<CommandRulesContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let rows = sqlx::query!("SELECT name, channel_id, allowed FROM command_rules WHERE guild_id = ?", db_guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let rules = rows.into_iter()
+        .map(|row| CommandRule {
+            name: row.name,
+            channel_id: (row.channel_id != 0).then(|| ChannelId::new(row.channel_id as u64)),
+            allowed: row.allowed != 0
+        })
+        .collect::<Vec<_>>();
+
+    let mut command_rules = command_rules.write().await;
+
+    if rules.is_empty() {
+        command_rules.remove(&guild_id.get());
+    } else {
+        command_rules.insert(guild_id.get(), rules);
+    }
+
+    Ok(())
+}
+
+/// Turns what a user typed into a rule name: a top-level command's first name, a group's name
+/// in lowercase, or `all`.
+pub fn rule_name(name: &str) -> Option<String> {
+    let name = name.to_lowercase();
+
+    if name == ALL_COMMANDS {
+        return Some(name);
+    }
+
+    if let Some(group) = COMMAND_GROUPS.iter().find(|group| group.name.eq_ignore_ascii_case(&name)) {
+        return Some(group.name.to_lowercase());
+    }
+
+    COMMAND_GROUPS.iter()
+        .flat_map(|group| group.options.commands.iter())
+        .find(|command| command.options.names.contains(&name.as_str()))
+        .map(|command| command.options.names[0].to_string())
+}
+
+/// Finds the group and top-level command a command the framework ran belongs to. Sub-commands
+/// only pass their own name to hooks, so when several commands have a sub-command by that name,
+/// the one named in the message wins.
+pub fn find_root(command: &str, content: &str) -> Option<(&'static CommandGroup, &'static Command)> {
+    fn contains(command: &'static Command, name: &str) -> bool {
+        command.options.names[0] == name || command.options.sub_commands.iter().any(|sub| contains(sub, name))
+    }
+
+    let words = content.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>();
+
+    let candidates = COMMAND_GROUPS.iter()
+        .flat_map(|group| group.options.commands.iter().map(move |root| (*group, *root)))
+        .filter(|(_, root)| contains(root, command))
+        .collect::<Vec<_>>();
+
+    candidates.iter()
+        .find(|(_, root)| root.options.names.iter().any(|name| words.iter().any(|word| word.ends_with(name))))
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+/// Whether a server's rules let a command be used in the channel a message was sent in. The
+/// `command` command itself is never blocked, so the rules can always be undone.
+pub async fn command_allowed(ctx: &Context, msg: &Message, group: &CommandGroup, root: &Command) -> bool {
+    match msg.guild_id {
+        Some(guild_id) => command_allowed_in(ctx, guild_id, msg.channel_id, group, root).await,
+        None => true
+    }
+}
+
+/// Whether a server's rules let a command be used in a channel.
+pub async fn command_allowed_in(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, group: &CommandGroup, root: &Command) -> bool {
+    let root_name = root.options.names[0];
+
+    if root_name == "command" {
+        return true;
+    }
+
+    let command_rules = match get_data::<CommandRulesContainer>(ctx).await {
+        Ok(command_rules) => command_rules,
+        Err(why) => {
+            error!("Couldn't read the command rules of guild {guild_id}: {why}");
+            return true;
+        }
+    };
+
+    let command_rules = command_rules.read().await;
+
+    let Some(rules) = command_rules.get(&guild_id.get()) else {
+        return true;
+    };
+
+    let group_name = group.name.to_lowercase();
+
+    [root_name, group_name.as_str(), ALL_COMMANDS].iter().all(|name| {
+        let rules = rules.iter().filter(|rule| rule.name == *name).collect::<Vec<_>>();
+
+        let blocked = rules.iter().any(|rule| !rule.allowed && rule.channel_id.is_none_or(|rule_channel_id| rule_channel_id == channel_id));
+        let limited = rules.iter().any(|rule| rule.allowed);
+        let allowed_here = rules.iter().any(|rule| rule.allowed && rule.channel_id == Some(channel_id));
+
+        !blocked && (!limited || allowed_here)
+    })
+}
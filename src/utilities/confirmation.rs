@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::model::application::ButtonStyle;
+use serenity::model::channel::Message;
+use serenity::prelude::Context;
+
+/// How long destructive commands wait to be confirmed before giving up.
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CONFIRM_ID: &str = "confirm_action";
+const CANCEL_ID: &str = "cancel_action";
+
+/// Asks the author of a command to confirm it with a button before it does anything that can't be
+/// undone. The prompt is deleted once confirmed, and otherwise says that nothing was done.
+pub async fn confirm(ctx: &Context, msg: &Message, prompt: &str, timeout: Duration) -> Result<bool, serenity::Error> {
+    let buttons = vec![
+        CreateButton::new(CONFIRM_ID).label("Confirm").style(ButtonStyle::Danger),
+        CreateButton::new(CANCEL_ID).label("Cancel").style(ButtonStyle::Secondary)
+    ];
+
+    let builder = CreateMessage::new()
+        .content(prompt)
+        .components(vec![CreateActionRow::Buttons(buttons)])
+        .reference_message(msg)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    let mut prompt = msg.channel_id.send_message(ctx, builder).await?;
+
+    let interaction = prompt.await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(timeout)
+        .await;
+
+    let Some(interaction) = interaction else {
+        drop(prompt.edit(ctx, EditMessage::new().content("Timed out, nothing was done.").components(vec![])).await);
+        return Ok(false);
+    };
+
+    if interaction.data.custom_id == CONFIRM_ID {
+        interaction.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+        drop(prompt.delete(ctx).await);
+        return Ok(true);
+    }
+
+    let response = CreateInteractionResponseMessage::new()
+        .content("Cancelled, nothing was done.")
+        .components(vec![]);
+
+    interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await?;
+
+    Ok(false)
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serenity::framework::standard::Command;
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+
+use crate::COMMAND_GROUPS;
+use crate::utilities::global_data::{CooldownsContainer, DatabaseConnectionContainer};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::errors::{get_data, BotError};
+
+/// Once this many cooldowns are tracked, expired ones are forgotten.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Who has to wait once a command has been used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CooldownScope {
+    /// Everyone, everywhere.
+    Global,
+    /// Everyone in the server it was used in.
+    Guild,
+    /// Only whoever used it.
+    User
+}
+
+impl CooldownScope {
+    pub fn from_name(name: &str) -> Option<CooldownScope> {
+        match name {
+            "global" => Some(CooldownScope::Global),
+            "guild" | "server" => Some(CooldownScope::Guild),
+            "user" => Some(CooldownScope::User),
+            _ => None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CooldownScope::Global => "global",
+            CooldownScope::Guild => "guild",
+            CooldownScope::User => "user"
+        }
+    }
+}
+
+/// Cooldowns commands have unless a server overrides them, keyed by the command's first name.
+/// Mostly commands that render images or scan a lot of data.
+pub const DEFAULT_COOLDOWNS: &[(&str, CooldownScope, u64)] = &[
+    ("wordcloud", CooldownScope::Guild, 30),
+    ("serverstats", CooldownScope::Guild, 30),
+    ("messagestats", CooldownScope::Guild, 15),
+    ("card", CooldownScope::User, 10),
+    ("poll", CooldownScope::User, 10)
+];
+
+/// Every server's cooldown overrides, along with when each running cooldown ends.
+#[derive(Default)]
+pub struct CooldownState {
+    overrides: HashMap<(u64, String), (u64, CooldownScope)>,
+    expiries: HashMap<(String, CooldownScope, u64), Instant>
+}
+
+impl CooldownState {
+    /// The cooldown a command has in a server, or anywhere else without one.
+    fn cooldown(&self, guild_id: Option<GuildId>, command: &str) -> Option<(u64, CooldownScope)> {
+        guild_id
+            .and_then(|guild_id| self.overrides.get(&(guild_id.get(), command.to_string())).copied())
+            .or_else(|| default_cooldown(command))
+            .filter(|(seconds, _)| *seconds > 0)
+    }
+}
+
+pub async fn load_cooldowns(database: &SqlitePool) -> Result<CooldownState, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, command, seconds, scope FROM command_cooldowns")
+        .fetch_all(database)
+        .await?;
+
+    let overrides = rows.into_iter()
+        .map(|row| ((row.guild_id as u64, row.command), (row.seconds as u64, CooldownScope::from_name(&row.scope).unwrap_or(CooldownScope::User))))
+        .collect();
+
+    Ok(CooldownState { overrides, expiries: HashMap::new() })
+}
+
+/// Reloads one server's cooldown overrides from the database after they've been changed.
+pub async fn reload_cooldowns(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let state = get_data::<CooldownsContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let rows = sqlx::query!("SELECT command, seconds, scope FROM command_cooldowns WHERE guild_id = ?", db_guild_id)
+        .fetch_all(&database)
+        .await?;
+
+    let mut state = state.lock().await;
+
+    state.overrides.retain(|(override_guild_id, _), _| *override_guild_id != guild_id.get());
+
+    for row in rows {
+        let scope = CooldownScope::from_name(&row.scope).unwrap_or(CooldownScope::User);
+        state.overrides.insert((guild_id.get(), row.command), (row.seconds as u64, scope));
+    }
+
+    Ok(())
+}
+
+/// The cooldown a command has in a server, or anywhere else without one.
+pub fn default_cooldown(command: &str) -> Option<(u64, CooldownScope)> {
+    DEFAULT_COOLDOWNS.iter()
+        .find(|(name, _, _)| *name == command)
+        .map(|(_, scope, seconds)| (*seconds, *scope))
+}
+
+/// Starts the command's cooldown for the message's author if it has one, or returns how much
+/// longer they have to wait if it's already running.
+pub async fn check_cooldown(ctx: &Context, msg: &Message, command: &str) -> Option<Duration> {
+    check_user_cooldown(ctx, msg.guild_id, msg.author.id, command).await
+}
+
+/// Starts the command's cooldown for a user if it has one, or returns how much longer they have
+/// to wait if it's already running.
+pub async fn check_user_cooldown(ctx: &Context, guild_id: Option<GuildId>, user_id: UserId, command: &str) -> Option<Duration> {
+    let state = {
+        let data = ctx.data.read().await;
+        data.get::<CooldownsContainer>()?.clone()
+    };
+
+    let mut state = state.lock().await;
+
+    let (seconds, scope) = state.cooldown(guild_id, command)?;
+
+    let target = match scope {
+        CooldownScope::Global => 0,
+        CooldownScope::Guild => guild_id.map_or(user_id.get(), GuildId::get),
+        CooldownScope::User => user_id.get()
+    };
+
+    let now = Instant::now();
+    let key = (command.to_string(), scope, target);
+
+    if let Some(expiry) = state.expiries.get(&key).filter(|expiry| **expiry > now) {
+        return Some(*expiry - now);
+    }
+
+    if state.expiries.len() >= PRUNE_THRESHOLD {
+        state.expiries.retain(|_, expiry| *expiry > now);
+    }
+
+    state.expiries.insert(key, now + Duration::from_secs(seconds));
+
+    None
+}
+
+/// The cooldown a command has in a server, taking the server's override into account.
+pub async fn command_cooldown(ctx: &Context, guild_id: Option<GuildId>, command: &str) -> Option<(u64, CooldownScope)> {
+    let state = {
+        let data = ctx.data.read().await;
+        data.get::<CooldownsContainer>()?.clone()
+    };
+
+    let state = state.lock().await;
+    state.cooldown(guild_id, command)
+}
+
+/// How long is left on a cooldown, rounded up to whole seconds, e.g. `1m5s`.
+pub fn cooldown_remaining(remaining: Duration) -> String {
+    format_duration(chrono::Duration::seconds(remaining.as_secs_f64().ceil() as i64))
+}
+
+/// Finds a command or sub-command by any of its names, to check cooldowns are set on real ones.
+pub fn find_command(name: &str) -> Option<&'static Command> {
+    fn search(commands: &[&'static Command], name: &str) -> Option<&'static Command> {
+        commands.iter().find_map(|command| {
+            if command.options.names.contains(&name) {
+                Some(*command)
+            } else {
+                search(command.options.sub_commands, name)
+            }
+        })
+    }
+
+    COMMAND_GROUPS.iter().find_map(|group| search(group.options.commands, name))
+}
@@ -0,0 +1,145 @@
+use std::env;
+use std::time::Duration;
+
+use serenity::builder::EditChannel;
+use serenity::model::guild::Guild;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::model::user::OnlineStatus;
+use serenity::prelude::Context;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::templates::{TemplateContext, render_template};
+use crate::utilities::errors::get_data;
+
+/// Discord only allows two renames per channel every ten minutes, so counters are never refreshed
+/// more often than this.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Pause between renames, so refreshing many counters doesn't burst against the global rate limit.
+const RENAME_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest name Discord accepts for a channel.
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Whether the bot asks Discord for members' presences, which the `online` counter needs.
+/// Presences are a privileged intent, so they're only asked for when `PRESENCE_INTENT` is `true`.
+pub fn presences_enabled() -> bool {
+    env::var("PRESENCE_INTENT").is_ok_and(|enabled| enabled.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CounterKind {
+    Members,
+    Humans,
+    Bots,
+    Online,
+    Boosts
+}
+
+impl CounterKind {
+    pub const ALL: [CounterKind; 5] = [CounterKind::Members, CounterKind::Humans, CounterKind::Bots, CounterKind::Online, CounterKind::Boosts];
+
+    pub fn parse(kind: &str) -> Option<CounterKind> {
+        CounterKind::ALL.into_iter().find(|candidate| candidate.as_str() == kind.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CounterKind::Members => "members",
+            CounterKind::Humans => "humans",
+            CounterKind::Bots => "bots",
+            CounterKind::Online => "online",
+            CounterKind::Boosts => "boosts"
+        }
+    }
+
+    /// Whether the bot can keep this counter up to date. Without presences, nobody shows as online.
+    pub fn is_available(self) -> bool {
+        self != CounterKind::Online || presences_enabled()
+    }
+
+    pub fn default_template(self) -> &'static str {
+        match self {
+            CounterKind::Members => "Members: {count}",
+            CounterKind::Humans => "Humans: {count}",
+            CounterKind::Bots => "Bots: {count}",
+            CounterKind::Online => "Online: {count}",
+            CounterKind::Boosts => "Boosts: {count}"
+        }
+    }
+
+    /// The current value of this counter. Bot and online counts come from the cache, so they're
+    /// only as complete as the cached member list and presences.
+    pub fn count(self, guild: &Guild) -> u64 {
+        let bots = guild.members.values().filter(|member| member.user.bot).count() as u64;
+
+        match self {
+            CounterKind::Members => guild.member_count,
+            CounterKind::Humans => guild.member_count.saturating_sub(bots),
+            CounterKind::Bots => bots,
+            CounterKind::Online => guild.presences.values().filter(|presence| presence.status != OnlineStatus::Offline).count() as u64,
+            CounterKind::Boosts => guild.premium_subscription_count.unwrap_or_default()
+        }
+    }
+}
+
+/// Renders a counter's channel name from its template, falling back to the raw template when it
+/// doesn't render.
+pub fn counter_name(template: &str, count: u64) -> String {
+    let context = TemplateContext::new().value("count", count);
+    let name = render_template(template, &context).unwrap_or_else(|_| template.to_string());
+
+    name.chars().take(MAX_NAME_LENGTH).collect()
+}
+
+/// Periodically renames every counter channel whose value has changed.
+pub async fn refresh_counters_loop(ctx: Context) {
+    let database = match get_data::<DatabaseConnectionContainer>(&ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't start refreshing counters: {why}");
+            return;
+        }
+    };
+
+    loop {
+        let counters = match sqlx::query!("SELECT channel_id, guild_id, kind, template FROM counter_channels").fetch_all(&database).await {
+            Ok(counters) => counters,
+            Err(why) => {
+                error!("Failed to fetch counter channels: {why}");
+                Vec::new()
+            }
+        };
+
+        for counter in counters {
+            let channel_id = ChannelId::new(counter.channel_id as u64);
+            // counters made before presences were turned off keep their last name
+            let Some(kind) = CounterKind::parse(&counter.kind).filter(|kind| kind.is_available()) else {
+                continue;
+            };
+
+            // only rename channels whose name is actually out of date
+            let name = {
+                let Some(guild) = ctx.cache.guild(GuildId::new(counter.guild_id as u64)) else {
+                    continue;
+                };
+
+                let name = counter_name(&counter.template, kind.count(&guild));
+
+                match guild.channels.get(&channel_id) {
+                    Some(channel) if channel.name != name => name,
+                    _ => continue
+                }
+            };
+
+            if let Err(why) = channel_id.edit(&ctx.http, EditChannel::new().name(name)).await {
+                warn!("Failed to refresh counter channel {channel_id}: {why}");
+            }
+
+            tokio::time::sleep(RENAME_DELAY).await;
+        }
+
+        tokio::time::sleep(REFRESH_INTERVAL).await;
+    }
+}
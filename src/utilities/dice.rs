@@ -0,0 +1,224 @@
+use rand::Rng;
+
+/// Most dice rolled by a single expression, so a roll can't tie the bot up.
+const MAX_DICE: u32 = 100;
+
+/// Most sides a die can have.
+const MAX_SIDES: u32 = 1000;
+
+/// Most dice and numbers added together in one expression.
+const MAX_TERMS: usize = 20;
+
+/// Which dice of a group count towards the total, e.g. the best 3 of `4d6kh3`.
+#[derive(Clone, Copy)]
+enum Keep {
+    All,
+    Highest(u32),
+    Lowest(u32)
+}
+
+enum Term {
+    Dice { count: u32, sides: u32, keep: Keep },
+    Number(i64)
+}
+
+/// The outcome of a roll, with every die shown so it can be checked.
+pub struct DiceRoll {
+    pub total: i64,
+    pub breakdown: String
+}
+
+/// Rolls dice notation: dice like `2d20` or `d6`, optionally keeping the highest or lowest few
+/// (`4d6kh3`, `2d20kl1`), and numbers, added or subtracted, e.g. `2d20+3` or `1d8+1d6-2`.
+pub fn roll_dice(expression: &str) -> Result<DiceRoll, String> {
+    let terms = parse_expression(expression)?;
+    let mut rng = rand::thread_rng();
+
+    let mut total: i64 = 0;
+    let mut parts = Vec::new();
+
+    for (sign, term) in terms {
+        let (value, shown) = match term {
+            Term::Number(number) => (number, number.to_string()),
+            Term::Dice { count, sides, keep } => {
+                let mut rolls: Vec<u32> = (0..count).map(|_| rng.gen_range(1..=sides)).collect();
+                let shown_rolls = rolls.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+
+                rolls.sort_unstable();
+
+                let kept = match keep {
+                    Keep::All => &rolls[..],
+                    Keep::Highest(keep) => &rolls[rolls.len() - keep as usize..],
+                    Keep::Lowest(keep) => &rolls[..keep as usize]
+                };
+
+                let value = kept.iter().map(|roll| i64::from(*roll)).sum::<i64>();
+                (value, format!("[{shown_rolls}]"))
+            }
+        };
+
+        total += sign * value;
+
+        if parts.is_empty() {
+            parts.push(if sign < 0 { format!("-{shown}") } else { shown });
+        } else {
+            parts.push(format!("{} {shown}", if sign < 0 { "-" } else { "+" }));
+        }
+    }
+
+    Ok(DiceRoll { total, breakdown: parts.join(" ") })
+}
+
+fn parse_expression(expression: &str) -> Result<Vec<(i64, Term)>, String> {
+    let expression = expression.chars().filter(|character| !character.is_whitespace()).collect::<String>().to_lowercase();
+
+    if expression.is_empty() {
+        return Err("Please give some dice to roll, e.g. `2d20+3`.".to_string());
+    }
+
+    let mut terms = Vec::new();
+    let mut sign = 1;
+    let mut current = String::new();
+    let mut dice = 0;
+
+    // a trailing sign keeps the loop going once more to flush the last term
+    for character in expression.chars().chain(std::iter::once('+')) {
+        if character != '+' && character != '-' {
+            current.push(character);
+            continue;
+        }
+
+        if current.is_empty() {
+            // only a leading minus may stand alone
+            if !terms.is_empty() || character == '+' {
+                return Err(format!("`{expression}` isn't valid dice notation."));
+            }
+
+            sign = -1;
+            continue;
+        }
+
+        let term = parse_term(&current)?;
+
+        if let Term::Dice { count, .. } = term {
+            dice = count.saturating_add(dice);
+        }
+
+        terms.push((sign, term));
+        sign = if character == '-' { -1 } else { 1 };
+        current.clear();
+    }
+
+    if terms.len() > MAX_TERMS {
+        return Err(format!("Rolls can add up at most {MAX_TERMS} dice and numbers."));
+    }
+
+    if dice > MAX_DICE {
+        return Err(format!("You can roll at most {MAX_DICE} dice at once."));
+    }
+
+    Ok(terms)
+}
+
+fn parse_term(term: &str) -> Result<Term, String> {
+    let invalid = || format!("`{term}` isn't valid dice notation. Try something like `d6`, `2d20+3` or `4d6kh3`.");
+
+    let Some((count, rest)) = term.split_once('d') else {
+        return term.parse::<i64>().ok().filter(|number| number.unsigned_abs() <= 1_000_000).map(Term::Number).ok_or_else(invalid);
+    };
+
+    let count = if count.is_empty() { 1 } else { count.parse::<u32>().map_err(|_| invalid())? };
+
+    if count == 0 {
+        return Err(format!("`{term}` doesn't roll any dice."));
+    }
+
+    if count > MAX_DICE {
+        return Err(format!("You can roll at most {MAX_DICE} dice at once."));
+    }
+
+    let (sides, keep) = match rest.split_once('k') {
+        None => (rest, None),
+        Some((sides, keep)) => (sides, Some(keep))
+    };
+
+    let sides = if sides == "%" { 100 } else { sides.parse::<u32>().map_err(|_| invalid())? };
+
+    let keep = match keep {
+        None => Keep::All,
+        Some(keep) => {
+            let (highest, amount) = match keep.chars().next() {
+                Some('h') => (true, &keep[1..]),
+                Some('l') => (false, &keep[1..]),
+                _ => (true, keep)
+            };
+
+            let amount = amount.parse::<u32>().map_err(|_| invalid())?;
+
+            if amount == 0 || amount > count {
+                return Err(format!("`{term}` keeps more dice than it rolls."));
+            }
+
+            if highest { Keep::Highest(amount) } else { Keep::Lowest(amount) }
+        }
+    };
+
+    if !(2..=MAX_SIDES).contains(&sides) {
+        return Err(format!("Dice can have from 2 to {MAX_SIDES} sides."));
+    }
+
+    Ok(Term::Dice { count, sides, keep })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_within_the_dice_range() {
+        for _ in 0..100 {
+            let roll = roll_dice("2d6+3").unwrap();
+            assert!((5..=15).contains(&roll.total), "{} is out of range", roll.total);
+        }
+    }
+
+    #[test]
+    fn adds_and_subtracts_numbers() {
+        let roll = roll_dice("-2 + 10 - 3").unwrap();
+
+        assert_eq!(roll.total, 5);
+        assert_eq!(roll.breakdown, "-2 + 10 - 3");
+    }
+
+    #[test]
+    fn keeps_the_highest_and_lowest_dice() {
+        for _ in 0..100 {
+            assert!((3..=18).contains(&roll_dice("4d6kh3").unwrap().total));
+            assert!((1..=20).contains(&roll_dice("2d20kl1").unwrap().total));
+        }
+    }
+
+    #[test]
+    fn reads_percentile_and_implicit_counts() {
+        for _ in 0..100 {
+            assert!((1..=100).contains(&roll_dice("d%").unwrap().total));
+            assert!((1..=6).contains(&roll_dice("D6").unwrap().total));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_notation() {
+        for expression in ["", "   ", "abc", "2d", "d", "2d6+", "+2d6", "2d6++3", "2dx", "4d6kh", "4d6kq3"] {
+            assert!(roll_dice(expression).is_err(), "`{expression}` was accepted");
+        }
+    }
+
+    #[test]
+    fn rejects_rolls_past_the_limits() {
+        for expression in ["0d6", "101d6", "2d1", "1d1001", "60d6+60d6", "2d6kh3", "2d6kl0", "2000000"] {
+            assert!(roll_dice(expression).is_err(), "`{expression}` was accepted");
+        }
+
+        assert!(roll_dice(&vec!["1"; MAX_TERMS + 1].join("+")).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+
+use serenity::framework::standard::{Args, Command, CommandGroup, Delimiter, OnlyIn};
+use serenity::model::channel::Message;
+use serenity::model::id::UserId;
+use serenity::model::permissions::Permissions;
+use serenity::prelude::Context;
+
+use crate::COMMAND_GROUPS;
+use crate::utilities::command_permissions::member_allowed;
+use crate::utilities::command_rules::command_allowed;
+use crate::utilities::cooldowns::{check_cooldown, cooldown_remaining};
+use crate::utilities::global_data::OwnersContainer;
+
+/// A command looked up by name, along with the group it belongs to and its remaining arguments.
+pub struct ResolvedCommand<'a> {
+    pub group: &'static CommandGroup,
+    /// The top-level command, which is `command` itself unless that's a sub-command.
+    pub root: &'static Command,
+    pub command: &'static Command,
+    pub args: &'a str
+}
+
+/// Looks up the command named at the start of `content` (without a prefix), descending into
+/// sub-commands while the following words name one.
+pub fn resolve_command(content: &str) -> Option<ResolvedCommand<'_>> {
+    let content = content.trim_start();
+    let (first, mut rest) = content.split_once(char::is_whitespace).unwrap_or((content, ""));
+    let first = first.to_lowercase();
+
+    let (group, root) = COMMAND_GROUPS.iter()
+        .flat_map(|group| group.options.commands.iter().map(move |command| (*group, *command)))
+        .find(|(_, command)| command.options.names.contains(&first.as_str()))?;
+
+    let mut command = root;
+
+    loop {
+        let trimmed = rest.trim_start();
+        let (next, after) = trimmed.split_once(char::is_whitespace).unwrap_or((trimmed, ""));
+        let next = next.to_lowercase();
+
+        match command.options.sub_commands.iter().find(|sub| sub.options.names.contains(&next.as_str())) {
+            Some(sub) => {
+                command = sub;
+                rest = after;
+            }
+            None => break
+        }
+    }
+
+    Some(ResolvedCommand { group, root, command, args: rest.trim() })
+}
+
+/// Runs a resolved command on behalf of the message author, applying the same restrictions the
+/// framework would (owner-only, guild/DM only, permissions, argument counts and checks). Returns
+/// a user-facing reason when the command can't be run or fails.
+pub async fn invoke_command(ctx: &Context, msg: &Message, resolved: &ResolvedCommand<'_>) -> Result<(), String> {
+    let group = resolved.group.options;
+    let options = resolved.command.options;
+
+    let is_owner = {
+        let data = ctx.data.read().await;
+        data.get::<OwnersContainer>().is_some_and(|owners: &HashSet<UserId>| owners.contains(&msg.author.id))
+    };
+
+    if (group.owners_only || options.owners_only) && !is_owner {
+        return Err("this command is restricted to bot owners".to_string());
+    }
+
+    for only_in in [group.only_in, options.only_in] {
+        match only_in {
+            OnlyIn::Guild if msg.guild_id.is_none() => return Err("this command is only available in guilds".to_string()),
+            OnlyIn::Dm if msg.guild_id.is_some() => return Err("this command is only available in DMs".to_string()),
+            _ => {}
+        }
+    }
+
+    let required = group.required_permissions | options.required_permissions;
+
+    if !required.is_empty() && !is_owner {
+        let permissions = member_permissions(ctx, msg).await;
+
+        if !permissions.administrator() && !permissions.contains(required) {
+            return Err(format!("you lack the permissions required for this command ({required})"));
+        }
+    }
+
+    let delimiters = if options.delimiters.is_empty() {
+        vec![Delimiter::Single(' ')]
+    } else {
+        options.delimiters.iter().map(|delimiter| Delimiter::Multiple(delimiter.to_string())).collect()
+    };
+
+    let mut args = Args::new(resolved.args, &delimiters);
+    let given = args.len();
+
+    if options.min_args.is_some_and(|min| given < usize::from(min)) {
+        return Err(format!("not enough arguments (needs at least {})", options.min_args.unwrap_or_default()));
+    }
+
+    if options.max_args.is_some_and(|max| given > usize::from(max)) {
+        return Err(format!("too many arguments (takes at most {})", options.max_args.unwrap_or_default()));
+    }
+
+    if !command_allowed(ctx, msg, resolved.group, resolved.root).await {
+        return Err("this command can't be used here".to_string());
+    }
+
+    if !member_allowed(ctx, msg, resolved.root.options.names[0]).await {
+        return Err("you don't have a role that's allowed to use this command".to_string());
+    }
+
+    if let Some(remaining) = check_cooldown(ctx, msg, options.names[0]).await {
+        return Err(format!("this command is on cooldown, try again in {}", cooldown_remaining(remaining)));
+    }
+
+    for check in group.checks.iter().chain(options.checks) {
+        if (check.function)(ctx, msg, &mut args, options).await.is_err() {
+            return Err(format!("the `{}` check failed", check.name));
+        }
+
+        args.restore();
+    }
+
+    (resolved.command.fun)(ctx, msg, args).await.map_err(|why| why.to_string())
+}
+
+/// The author's permissions in the channel the message was sent in, or none outside of guilds.
+async fn member_permissions(ctx: &Context, msg: &Message) -> Permissions {
+    let (Some(guild_id), Ok(member)) = (msg.guild_id, msg.member(ctx).await) else {
+        return Permissions::empty();
+    };
+
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return Permissions::empty();
+    };
+
+    match guild.channels.get(&msg.channel_id) {
+        Some(channel) => guild.user_permissions_in(channel, &member),
+        None => guild.member_permissions(&member)
+    }
+}
@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::fmt;
+
+use serenity::framework::standard::ArgError;
+use serenity::prelude::{Context, TypeMapKey};
+
+use crate::utilities::i18n::translate;
+
+/// What can go wrong while handling an event or command, beyond a user's own mistakes.
+#[derive(Debug)]
+pub enum BotError {
+    Database(sqlx::Error),
+    Discord(serenity::Error),
+    /// A `TypeMap` entry that's inserted at startup is missing.
+    MissingData(&'static str)
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Database(_) => write!(f, "database error"),
+            BotError::Discord(_) => write!(f, "Discord API error"),
+            BotError::MissingData(name) => write!(f, "{name} is missing from the client's data")
+        }
+    }
+}
+
+impl Error for BotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BotError::Database(why) => Some(why),
+            BotError::Discord(why) => Some(why),
+            BotError::MissingData(_) => None
+        }
+    }
+}
+
+impl From<sqlx::Error> for BotError {
+    fn from(why: sqlx::Error) -> Self {
+        BotError::Database(why)
+    }
+}
+
+impl From<serenity::Error> for BotError {
+    fn from(why: serenity::Error) -> Self {
+        BotError::Discord(why)
+    }
+}
+
+/// Clones an entry out of the client's data, or fails instead of panicking if it's missing.
+pub async fn get_data<T>(ctx: &Context) -> Result<T::Value, BotError>
+where
+    T: TypeMapKey,
+    T::Value: Clone
+{
+    let data = ctx.data.read().await;
+
+    data.get::<T>().cloned().ok_or(BotError::MissingData(std::any::type_name::<T>()))
+}
+
+/// The error followed by everything that caused it, e.g. `database error: pool timed out`.
+pub fn error_chain(error: &(dyn Error + 'static)) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+
+    while let Some(cause) = source {
+        chain.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+
+    chain
+}
+
+/// What to tell a user whose command failed, in their guild's language. Internal failures get a
+/// generic apology, while errors commands raise on purpose, such as bad arguments, are shown as
+/// they are.
+pub fn user_message(error: &(dyn Error + 'static), language: &str) -> String {
+    let internal = || translate(language, "error.internal", &[]);
+    let rejected = || translate(language, "error.discord_rejected", &[]);
+
+    if let Some(why) = error.downcast_ref::<BotError>() {
+        return match why {
+            BotError::Discord(serenity::Error::Http(_)) => rejected(),
+            _ => internal()
+        };
+    }
+
+    if error.is::<sqlx::Error>() {
+        return internal();
+    }
+
+    if let Some(why) = error.downcast_ref::<serenity::Error>() {
+        return match why {
+            serenity::Error::Http(_) => rejected(),
+            _ => internal()
+        };
+    }
+
+    if error.is::<ArgError<std::num::ParseIntError>>() || error.is::<ArgError<std::num::ParseFloatError>>() {
+        return translate(language, "error.invalid_number", &[]);
+    }
+
+    error.to_string()
+}
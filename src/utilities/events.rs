@@ -0,0 +1,223 @@
+use chrono::{DateTime, Duration, Utc};
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage};
+use serenity::model::guild::{ScheduledEvent, ScheduledEventStatus, ScheduledEventType};
+use serenity::model::id::{ChannelId, GuildId, ScheduledEventId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::errors::{BotError, get_data};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::scheduler::{Job, When, schedule_job};
+
+/// Longest an event reminder can be posted ahead of the event.
+pub const MAX_REMINDER_MINUTES: i64 = 7 * 24 * 60;
+
+/// How a guild's scheduled events are announced.
+pub struct EventSettings {
+    pub channel_id: Option<ChannelId>,
+    pub announce: bool,
+    pub reminder_minutes: Option<i64>
+}
+
+pub async fn event_settings(database: &SqlitePool, guild_id: GuildId) -> Result<EventSettings, sqlx::Error> {
+    let id = guild_id.get() as i64;
+
+    let row = sqlx::query!("SELECT channel_id, announce, reminder_minutes FROM event_settings WHERE guild_id = ?", id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(match row {
+        Some(row) => EventSettings {
+            channel_id: row.channel_id.map(|channel_id| ChannelId::new(channel_id as u64)),
+            announce: row.announce != 0,
+            reminder_minutes: row.reminder_minutes
+        },
+        None => EventSettings { channel_id: None, announce: true, reminder_minutes: None }
+    })
+}
+
+/// The link Discord shows an event's page at.
+pub fn event_link(guild_id: GuildId, event_id: ScheduledEventId) -> String {
+    format!("https://discord.com/events/{guild_id}/{event_id}")
+}
+
+pub fn event_start(event: &ScheduledEvent) -> DateTime<Utc> {
+    DateTime::from_timestamp(event.start_time.unix_timestamp(), 0).unwrap_or_default()
+}
+
+/// Where an event takes place, as a channel mention or the location given for external events.
+pub fn event_location(event: &ScheduledEvent) -> Option<String> {
+    match event.kind {
+        ScheduledEventType::External => event.metadata.as_ref().and_then(|metadata| metadata.location.clone()),
+        _ => event.channel_id.map(|channel_id| channel_id.mention().to_string())
+    }
+}
+
+fn event_embed(branding: &Branding, event: &ScheduledEvent, title: String) -> CreateEmbed {
+    let start = event_start(event).timestamp();
+
+    let mut embed = branding.embed()
+        .title(title)
+        .url(event_link(event.guild_id, event.id))
+        .field("Starts", format!("<t:{start}:F> (<t:{start}:R>)"), false);
+
+    if let Some(description) = event.description.as_deref().filter(|description| !description.is_empty()) {
+        embed = embed.description(description.chars().take(1000).collect::<String>());
+    }
+
+    if let Some(location) = event_location(event) {
+        embed = embed.field("Where", location, false);
+    }
+
+    if let Some(count) = event.user_count {
+        embed = embed.footer(CreateEmbedFooter::new(format!("{count} interested")));
+    }
+
+    if let Some(image) = &event.image {
+        embed = embed.image(format!("https://cdn.discordapp.com/guild-events/{}/{image}.png?size=1024", event.id));
+    }
+
+    embed
+}
+
+/// Announces a newly created event, and schedules its reminder.
+pub async fn handle_event_create(ctx: &Context, event: &ScheduledEvent) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't handle new event {}: {why}", event.id);
+            return;
+        }
+    };
+
+    let settings = match event_settings(&database, event.guild_id).await {
+        Ok(settings) => settings,
+        Err(why) => {
+            error!("Couldn't fetch the event settings of guild {}: {why}", event.guild_id);
+            return;
+        }
+    };
+
+    if let (Some(channel_id), true) = (settings.channel_id, settings.announce) {
+        let embed = event_embed(&guild_branding(ctx, Some(event.guild_id)).await, event, format!("New event: {}", event.name));
+
+        if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+            warn!("Couldn't announce event {} in channel {channel_id}: {why}", event.id);
+        }
+    }
+
+    if let Err(why) = schedule_event_reminder(ctx, &database, event, &settings).await {
+        error!("Failed to schedule the reminder for event {} in guild {}: {why}", event.id, event.guild_id);
+    }
+}
+
+/// Moves an event's reminder when it's rescheduled, and drops it once the event starts or is
+/// cancelled.
+pub async fn handle_event_update(ctx: &Context, event: &ScheduledEvent) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't handle update to event {}: {why}", event.id);
+            return;
+        }
+    };
+
+    let rescheduled = match event_settings(&database, event.guild_id).await {
+        Ok(settings) => schedule_event_reminder(ctx, &database, event, &settings).await,
+        Err(why) => Err(why)
+    };
+
+    if let Err(why) = rescheduled {
+        error!("Failed to reschedule the reminder for event {} in guild {}: {why}", event.id, event.guild_id);
+    }
+}
+
+pub async fn handle_event_delete(ctx: &Context, event: &ScheduledEvent) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't handle deleted event {}: {why}", event.id);
+            return;
+        }
+    };
+
+    if let Err(why) = cancel_event_reminder(&database, event.guild_id, event.id).await {
+        error!("Failed to cancel the reminder for event {} in guild {}: {why}", event.id, event.guild_id);
+    }
+}
+
+async fn cancel_event_reminder(database: &SqlitePool, guild_id: GuildId, event_id: ScheduledEventId) -> Result<(), sqlx::Error> {
+    let (guild_id, event_id) = (guild_id.get() as i64, event_id.get() as i64);
+
+    sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE guild_id = ? AND kind = 'event' AND json_extract(payload, '$.event_id') = ?",
+        guild_id,
+        event_id
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// Replaces an event's reminder with one at the guild's reminder time, if the event is still
+/// upcoming and that time hasn't passed yet.
+pub async fn schedule_event_reminder(ctx: &Context, database: &SqlitePool, event: &ScheduledEvent, settings: &EventSettings) -> Result<(), sqlx::Error> {
+    cancel_event_reminder(database, event.guild_id, event.id).await?;
+
+    let (Some(_), Some(minutes)) = (settings.channel_id, settings.reminder_minutes) else {
+        return Ok(());
+    };
+
+    let first_run = event_start(event) - Duration::minutes(minutes);
+
+    if event.status != ScheduledEventStatus::Scheduled || first_run <= Utc::now() {
+        return Ok(());
+    }
+
+    let job = Job::EventReminder { event_id: event.id.get() };
+    let bot_id = ctx.cache.current_user().id.get();
+
+    schedule_job(database, Some(event.guild_id), &job, &When { first_run, repeat: None }, bot_id).await?;
+
+    Ok(())
+}
+
+/// Reschedules the reminders of all of a guild's upcoming events, after its settings changed.
+pub async fn resync_event_reminders(ctx: &Context, database: &SqlitePool, guild_id: GuildId) -> Result<(), BotError> {
+    let settings = event_settings(database, guild_id).await?;
+
+    for event in guild_id.scheduled_events(ctx, false).await? {
+        schedule_event_reminder(ctx, database, &event, &settings).await?;
+    }
+
+    Ok(())
+}
+
+/// Posts a reminder that an event is about to start, run by the scheduler.
+pub async fn send_event_reminder(ctx: &Context, database: &SqlitePool, guild_id: GuildId, event_id: ScheduledEventId) {
+    let channel_id = match event_settings(database, guild_id).await {
+        Ok(EventSettings { channel_id: Some(channel_id), .. }) => channel_id,
+        Ok(_) => return,
+        Err(why) => {
+            error!("Couldn't fetch the event settings of guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    // fetched again for an up to date interested count, and in case it was cancelled while offline
+    let event = match guild_id.scheduled_event(ctx, event_id, true).await {
+        Ok(event) if event.status == ScheduledEventStatus::Scheduled => event,
+        Ok(_) => return,
+        Err(why) => {
+            warn!("Couldn't fetch event {event_id} in guild {guild_id} to remind about it: {why}");
+            return;
+        }
+    };
+
+    let embed = event_embed(&guild_branding(ctx, Some(guild_id)).await, &event, format!("Starting soon: {}", event.name));
+
+    if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't remind about event {event_id} in channel {channel_id}: {why}");
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use regex::{Regex, RegexBuilder};
+use serenity::model::channel::Message;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::utilities::automod::{AutomodAction, edited_message, enforce, parse_ids};
+use crate::utilities::global_data::{DatabaseConnectionContainer, FiltersContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// Upper bound on the compiled size of a filter, so one pattern can't use a lot of memory.
+const MAX_PATTERN_SIZE: usize = 1 << 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Exact,
+    Wildcard,
+    Regex
+}
+
+impl FilterMode {
+    pub const ALL: [FilterMode; 3] = [FilterMode::Exact, FilterMode::Wildcard, FilterMode::Regex];
+
+    pub fn parse(mode: &str) -> Option<FilterMode> {
+        FilterMode::ALL.into_iter().find(|candidate| candidate.as_str() == mode.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FilterMode::Exact => "exact",
+            FilterMode::Wildcard => "wildcard",
+            FilterMode::Regex => "regex"
+        }
+    }
+
+    /// Compiles a filter into a regex. Exact and wildcard filters ignore case and only match whole
+    /// words, so `ass` doesn't catch `class`, and wildcards support `*` (any part of a word) and
+    /// `?` (any single character). Regex filters are used as they are.
+    pub fn compile(self, pattern: &str) -> Result<Regex, regex::Error> {
+        let expression = match self {
+            FilterMode::Exact => whole_words(pattern, regex::escape(pattern)),
+            FilterMode::Wildcard => {
+                let translated: String = pattern.chars()
+                    .map(|character| match character {
+                        '*' => r"\S*".to_string(),
+                        '?' => r"\S".to_string(),
+                        other => regex::escape(&other.to_string())
+                    })
+                    .collect();
+
+                whole_words(pattern, translated)
+            }
+            FilterMode::Regex => pattern.to_string()
+        };
+
+        RegexBuilder::new(&expression)
+            .case_insensitive(self != FilterMode::Regex)
+            .size_limit(MAX_PATTERN_SIZE)
+            .build()
+    }
+}
+
+/// Surrounds an expression with word boundaries, on the sides where the pattern starts or ends
+/// with a letter or digit.
+fn whole_words(pattern: &str, expression: String) -> String {
+    let starts_with_word = pattern.chars().next().is_some_and(char::is_alphanumeric);
+    let ends_with_word = pattern.chars().next_back().is_some_and(char::is_alphanumeric);
+
+    format!("{}{expression}{}", if starts_with_word { r"\b" } else { "" }, if ends_with_word { r"\b" } else { "" })
+}
+
+pub struct Filter {
+    action: AutomodAction,
+    matcher: Regex
+}
+
+#[derive(Default)]
+pub struct GuildFilters {
+    filters: Vec<Filter>,
+    exempt_channels: Vec<u64>,
+    exempt_roles: Vec<u64>
+}
+
+impl GuildFilters {
+    /// The action of the first filter the content matches, unless the channel or one of the
+    /// author's roles is exempt.
+    fn matching(&self, channel_id: ChannelId, roles: &[RoleId], content: &str) -> Option<AutomodAction> {
+        if self.exempt_channels.contains(&channel_id.get()) || roles.iter().any(|role| self.exempt_roles.contains(&role.get())) {
+            return None;
+        }
+
+        self.filters.iter()
+            .find(|filter| filter.matcher.is_match(content))
+            .map(|filter| filter.action)
+    }
+}
+
+/// Every guild's word filters, keyed by guild ID. Filters that no longer compile are skipped.
+pub async fn load_filters(database: &SqlitePool) -> Result<HashMap<u64, GuildFilters>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, pattern, match_mode, action FROM filters ORDER BY id")
+        .fetch_all(database)
+        .await?;
+
+    let mut filters: HashMap<u64, GuildFilters> = HashMap::new();
+
+    for row in rows {
+        if let Some(filter) = build_filter(&row.pattern, &row.match_mode, &row.action) {
+            filters.entry(row.guild_id as u64).or_default().filters.push(filter);
+        }
+    }
+
+    let exemptions = sqlx::query!("SELECT guild_id, exempt_channels, exempt_roles FROM filter_exemptions")
+        .fetch_all(database)
+        .await?;
+
+    for row in exemptions {
+        if let Some(guild_filters) = filters.get_mut(&(row.guild_id as u64)) {
+            guild_filters.exempt_channels = parse_ids(&row.exempt_channels);
+            guild_filters.exempt_roles = parse_ids(&row.exempt_roles);
+        }
+    }
+
+    Ok(filters)
+}
+
+/// Reloads one guild's word filters from the database after they've been changed.
+pub async fn reload_filters(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let cache = get_data::<FiltersContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let filters: Vec<Filter> = sqlx::query!("SELECT pattern, match_mode, action FROM filters WHERE guild_id = ? ORDER BY id", db_guild_id)
+        .fetch_all(&database)
+        .await?
+        .into_iter()
+        .filter_map(|row| build_filter(&row.pattern, &row.match_mode, &row.action))
+        .collect();
+
+    let exemptions = sqlx::query!("SELECT exempt_channels, exempt_roles FROM filter_exemptions WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let mut cache = cache.write().await;
+
+    if filters.is_empty() {
+        cache.remove(&guild_id.get());
+    } else {
+        cache.insert(guild_id.get(), GuildFilters {
+            filters,
+            exempt_channels: exemptions.as_ref().map(|row| parse_ids(&row.exempt_channels)).unwrap_or_default(),
+            exempt_roles: exemptions.as_ref().map(|row| parse_ids(&row.exempt_roles)).unwrap_or_default()
+        });
+    }
+
+    Ok(())
+}
+
+fn build_filter(pattern: &str, mode: &str, action: &str) -> Option<Filter> {
+    let mode = FilterMode::parse(mode)?;
+
+    let matcher = match mode.compile(pattern) {
+        Ok(matcher) => matcher,
+        Err(why) => {
+            error!("Skipping word filter `{pattern}` that doesn't compile: {why}");
+            return None;
+        }
+    };
+
+    Some(Filter { action: AutomodAction::parse(action)?, matcher })
+}
+
+/// The action of the filter a message's content breaks in its guild, if any.
+async fn broken_filter(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, roles: &[RoleId], content: &str) -> Option<AutomodAction> {
+    let cache = match get_data::<FiltersContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't read the filters of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    let cache = cache.read().await;
+    cache.get(&guild_id.get())?.matching(channel_id, roles, content)
+}
+
+/// Checks a new message against its guild's word filters. A message using a filtered word is
+/// removed, and its author is warned, muted or banned if the filter says so. Returns whether a
+/// filter was broken.
+pub async fn handle_filters(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let roles = msg.member.as_ref().map_or(&[][..], |member| &member.roles[..]);
+
+    let Some(action) = broken_filter(ctx, guild_id, msg.channel_id, roles, &msg.content).await else {
+        return false;
+    };
+
+    enforce(ctx, guild_id, msg, action, "Word filter", "Used a filtered word").await;
+
+    true
+}
+
+/// Checks an edited message against its guild's word filters, so filtered words can't be edited
+/// in after the message was sent. Returns whether it was removed.
+pub async fn handle_filters_edit(ctx: &Context, new: Option<&Message>, event: &MessageUpdateEvent) -> bool {
+    // updates without content are embeds being resolved, not edits
+    let (Some(guild_id), Some(content)) = (event.guild_id, event.content.as_deref()) else {
+        return false;
+    };
+
+    if event.author.as_ref().is_some_and(|author| author.bot) {
+        return false;
+    }
+
+    let roles = event.member.as_ref().and_then(|member| member.as_deref()).map_or(&[][..], |member| &member.roles[..]);
+
+    let Some(action) = broken_filter(ctx, guild_id, event.channel_id, roles, content).await else {
+        return false;
+    };
+
+    let Some(msg) = edited_message(ctx, new, event).await else {
+        return false;
+    };
+
+    if msg.author.bot {
+        return false;
+    }
+
+    enforce(ctx, guild_id, &msg, action, "Word filter", "Used a filtered word").await;
+
+    true
+}
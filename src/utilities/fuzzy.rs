@@ -0,0 +1,93 @@
+/// Edit distance between two strings, counted in characters.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Scores how well a search query matches a command, lower is better. Names and aliases are
+/// matched exactly, by prefix, by substring and then by typo distance, with the description only
+/// considered when no name matches.
+pub fn command_match_score(query: &str, names: &[&str], description: Option<&str>) -> Option<usize> {
+    let query = query.trim().to_lowercase();
+
+    let name_score = names.iter()
+        .filter_map(|name| {
+            let name = name.to_lowercase();
+
+            if name == query {
+                Some(0)
+            } else if name.starts_with(&query) {
+                Some(1)
+            } else if name.contains(&query) {
+                Some(2)
+            } else {
+                let distance = levenshtein(&query, &name);
+                (distance <= 2).then_some(2 + distance)
+            }
+        })
+        .min();
+
+    name_score.or_else(|| {
+        let description = description?.to_lowercase();
+
+        if description.contains(&query) {
+            Some(5)
+        } else {
+            query.split_whitespace().all(|word| description.contains(word)).then_some(6)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("ban", "ban"), 0);
+        assert_eq!(levenshtein("", "ban"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("prefix", "perfix"), 2);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn prefers_closer_name_matches() {
+        let names = ["leaderboard", "lb", "top"];
+
+        assert_eq!(command_match_score("lb", &names, None), Some(0));
+        assert_eq!(command_match_score("  LEADER ", &names, None), Some(1));
+        assert_eq!(command_match_score("board", &names, None), Some(2));
+        assert_eq!(command_match_score("leaderbord", &names, None), Some(3));
+    }
+
+    #[test]
+    fn falls_back_to_the_description() {
+        let description = Some("Shows the members with the most XP in this server.");
+
+        assert_eq!(command_match_score("most xp", &["rank"], description), Some(5));
+        assert_eq!(command_match_score("server xp", &["rank"], description), Some(6));
+        assert_eq!(command_match_score("coins", &["rank"], description), None);
+    }
+
+    #[test]
+    fn ignores_distant_typos() {
+        assert_eq!(command_match_score("xyzzy", &["ban", "kick"], None), None);
+        assert_eq!(command_match_score("unbanned", &["ban"], None), None);
+    }
+}
@@ -0,0 +1,46 @@
+use serenity::model::id::{ChannelId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+
+use crate::utilities::errors::{BotError, get_data};
+use crate::utilities::global_data::ChannelGamesContainer;
+
+/// Claims a channel for a game so games never overlap, returning the game already running there
+/// if the channel is taken. Claimed channels must be released with `release_channel` once the
+/// game is over, however it ends.
+pub async fn claim_channel(ctx: &Context, channel_id: ChannelId, game: &'static str) -> Result<Option<&'static str>, BotError> {
+    let games = get_data::<ChannelGamesContainer>(ctx).await?;
+    let mut games = games.lock().await;
+
+    if let Some(running) = games.get(&channel_id.get()) {
+        return Ok(Some(*running));
+    }
+
+    games.insert(channel_id.get(), game);
+
+    Ok(None)
+}
+
+pub async fn release_channel(ctx: &Context, channel_id: ChannelId) -> Result<(), BotError> {
+    let games = get_data::<ChannelGamesContainer>(ctx).await?;
+    games.lock().await.remove(&channel_id.get());
+
+    Ok(())
+}
+
+/// Counts a win or a loss towards a user's stats for a game.
+pub async fn record_result(database: &SqlitePool, user_id: UserId, game: &str, won: bool) -> Result<(), sqlx::Error> {
+    let user_id = user_id.get() as i64;
+    let (wins, losses) = if won { (1, 0) } else { (0, 1) };
+
+    sqlx::query!(
+        "INSERT INTO game_stats (user_id, game, wins, losses) VALUES (?, ?, ?, ?)
+        ON CONFLICT (user_id, game) DO UPDATE SET wins = wins + excluded.wins, losses = losses + excluded.losses",
+        user_id,
+        game,
+        wins,
+        losses
+    ).execute(database).await?;
+
+    Ok(())
+}
@@ -1,19 +1,321 @@
-use std::{sync::Arc, collections::HashMap};
-use tokio::sync::RwLock;
-use serenity::{gateway::ShardManager, prelude::TypeMapKey};
+use std::{sync::Arc, collections::{HashMap, HashSet}, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use serenity::{async_trait, gateway::{ActivityData, ShardManager}, prelude::TypeMapKey};
+use serenity::client::{Context, FullEvent};
+use serenity::framework::{Framework, StandardFramework};
+use serenity::model::id::{GuildId, RoleId, UserId};
 use reqwest::Client;
 use sqlx::SqlitePool;
+use chrono::{DateTime, Utc};
+use tracing::error;
+
+use crate::utilities::afk::AfkStatus;
+use crate::utilities::analytics::ActivityBuffer;
+use crate::utilities::antispam::AntispamState;
+use crate::utilities::antiraid::AntiraidState;
+use crate::utilities::command_rules::CommandRule;
+use crate::utilities::cooldowns::CooldownState;
+use crate::utilities::errors::{BotError, get_data};
+use crate::utilities::invites::CachedInvite;
+use crate::utilities::levels::LevelingState;
+use crate::utilities::lookups::LookupCache;
+use crate::utilities::message_log::MessageLogCache;
+use crate::utilities::autoresponses::AutoResponse;
+use crate::utilities::blacklist::{Blacklist, is_blacklisted};
+use crate::utilities::branding::Branding;
+use crate::utilities::automod::AutomodRule;
+use crate::utilities::filters::GuildFilters;
+use crate::utilities::links::LinkSettings;
+use crate::utilities::media_rules::MediaRule;
+use crate::utilities::premium::PremiumTier;
+use crate::utilities::sticky::Sticky;
+use crate::utilities::chain_games::ChainGame;
+use crate::utilities::tts::TtsReader;
+use crate::utilities::watchlist::Watchlist;
 
 pub struct ShardManagerContainer;
 pub struct ReqwestClientContainer;
 pub struct GuildSettingsContainer;
 pub struct DatabaseConnectionContainer;
+pub struct AllowlistContainer;
+pub struct BlacklistContainer;
+pub struct PremiumContainer;
+pub struct FrameworkContainer;
+pub struct OwnersContainer;
+pub struct ActivityContainer;
+pub struct AutoResponsesContainer;
+pub struct PrivacyOptOutsContainer;
+pub struct AutomodContainer;
+pub struct FiltersContainer;
+pub struct LinksContainer;
+pub struct MediaRulesContainer;
+pub struct AntispamContainer;
+pub struct AntiraidContainer;
+pub struct MessageLogContainer;
+pub struct LevelingContainer;
+pub struct ModmailContainer;
+pub struct BootTimeContainer;
+pub struct CommandCountsContainer;
+pub struct ActivityOverrideContainer;
+pub struct CooldownsContainer;
+pub struct CommandRulesContainer;
+pub struct CommandPermissionsContainer;
+pub struct VoiceSessionsContainer;
+pub struct InvitesContainer;
+pub struct AfkContainer;
+pub struct StickyContainer;
+pub struct ChannelGamesContainer;
+pub struct SfxCooldownsContainer;
+pub struct TtsReadersContainer;
+pub struct LookupCacheContainer;
+pub struct ChainGamesContainer;
+pub struct WatchlistContainer;
 
 pub struct GuildSettings {
     pub prefix: String,
     pub owner_id: u64,
     pub mute_type: String,
-    pub mute_role: u64
+    pub mute_role: u64,
+    pub command_suggestions: bool,
+    pub message_log_channel: Option<u64>,
+    pub language: String,
+    pub branding: Branding
+}
+
+/// How often changed guild settings are written back to the database.
+const SETTINGS_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest any outgoing HTTP request may take, unless the request sets its own timeout.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long idle connections are kept open for reuse by later requests to the same host.
+const HTTP_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Guild settings, loaded from the database the first time each guild's are needed. Changes are
+/// made in memory and written back by `flush_guild_settings_loop`, so commands don't wait on the
+/// database, and each guild has its own lock so one guild's update doesn't hold up the others.
+pub struct GuildSettingsStore {
+    database: SqlitePool,
+    guilds: RwLock<HashMap<u64, Arc<RwLock<GuildSettings>>>>,
+    /// Guilds whose cached settings haven't been written back yet.
+    dirty: Mutex<HashSet<u64>>
+}
+
+impl GuildSettingsStore {
+    pub fn new(database: SqlitePool) -> Self {
+        GuildSettingsStore { database, guilds: RwLock::new(HashMap::new()), dirty: Mutex::new(HashSet::new()) }
+    }
+
+    /// A guild's settings, loaded from the database if they aren't cached yet. None if the guild
+    /// has no settings at all.
+    pub async fn get(&self, guild_id: u64) -> Result<Option<Arc<RwLock<GuildSettings>>>, sqlx::Error> {
+        if let Some(settings) = self.guilds.read().await.get(&guild_id) {
+            return Ok(Some(Arc::clone(settings)));
+        }
+
+        let db_guild_id = guild_id as i64;
+
+        let Some(row) = sqlx::query!(
+            "SELECT prefix, owner_id, mute_style, mute_role_id, command_suggestions, message_log_channel_id, message_log_enabled, language, embed_color, embed_footer, embed_thumbnail FROM guild_settings WHERE guild_id = ?",
+            db_guild_id
+        ).fetch_optional(&self.database).await? else {
+            return Ok(None);
+        };
+
+        let settings = GuildSettings {
+            prefix: row.prefix,
+            owner_id: row.owner_id as u64,
+            mute_type: row.mute_style,
+            mute_role: row.mute_role_id.unwrap_or_default() as u64,
+            command_suggestions: row.command_suggestions != 0,
+            message_log_channel: row.message_log_channel_id.filter(|_| row.message_log_enabled != 0).map(|channel_id| channel_id as u64),
+            language: row.language,
+            branding: Branding { color: row.embed_color.map(|color| color as u32), footer: row.embed_footer, thumbnail: row.embed_thumbnail }
+        };
+
+        // another task may have loaded them in the meantime, in which case theirs are kept
+        let settings = self.guilds.write().await
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new(RwLock::new(settings)))
+            .clone();
+
+        Ok(Some(settings))
+    }
+
+    /// Reads something from a guild's settings. None if the guild has no settings or they
+    /// couldn't be loaded, which is logged.
+    pub async fn read<T>(&self, guild_id: u64, read: impl FnOnce(&GuildSettings) -> T) -> Option<T> {
+        match self.get(guild_id).await {
+            Ok(Some(settings)) => Some(read(&*settings.read().await)),
+            Ok(None) => None,
+            Err(why) => {
+                error!("Failed to load settings for guild {guild_id}: {why}");
+                None
+            }
+        }
+    }
+
+    /// Changes a guild's settings, which are written back to the database shortly after.
+    /// Returns false if the guild has no settings.
+    pub async fn update(&self, guild_id: u64, update: impl FnOnce(&mut GuildSettings)) -> Result<bool, sqlx::Error> {
+        let Some(settings) = self.get(guild_id).await? else {
+            return Ok(false);
+        };
+
+        update(&mut *settings.write().await);
+        self.dirty.lock().await.insert(guild_id);
+
+        Ok(true)
+    }
+
+    /// Forgets a guild's settings, along with any changes that haven't been written back.
+    pub async fn remove(&self, guild_id: u64) {
+        self.guilds.write().await.remove(&guild_id);
+        self.dirty.lock().await.remove(&guild_id);
+    }
+
+    /// Writes every changed guild's settings back to the database, returning how many were
+    /// written. Guilds that couldn't be written are retried on the next flush.
+    pub async fn flush(&self) -> Result<usize, sqlx::Error> {
+        let pending = std::mem::take(&mut *self.dirty.lock().await).into_iter().collect::<Vec<_>>();
+
+        for (index, guild_id) in pending.iter().enumerate() {
+            let Some(settings) = self.guilds.read().await.get(guild_id).cloned() else {
+                continue;
+            };
+
+            let (prefix, mute_style, mute_role, command_suggestions, message_log_channel, language, branding) = {
+                let settings = settings.read().await;
+                (
+                    settings.prefix.clone(),
+                    settings.mute_type.clone(),
+                    Some(settings.mute_role as i64).filter(|role_id| *role_id != 0),
+                    settings.command_suggestions,
+                    settings.message_log_channel.map(|channel_id| channel_id as i64),
+                    settings.language.clone(),
+                    settings.branding.clone()
+                )
+            };
+
+            let (db_guild_id, message_log_enabled) = (*guild_id as i64, message_log_channel.is_some());
+            let embed_color = branding.color.map(i64::from);
+
+            // turning the message log off keeps its channel around
+            let written = sqlx::query!(
+                "UPDATE guild_settings SET prefix = ?, mute_style = ?, mute_role_id = ?, command_suggestions = ?,
+                message_log_channel_id = COALESCE(?, message_log_channel_id), message_log_enabled = ?, language = ?,
+                embed_color = ?, embed_footer = ?, embed_thumbnail = ? WHERE guild_id = ?",
+                prefix,
+                mute_style,
+                mute_role,
+                command_suggestions,
+                message_log_channel,
+                message_log_enabled,
+                language,
+                embed_color,
+                branding.footer,
+                branding.thumbnail,
+                db_guild_id
+            ).execute(&self.database).await;
+
+            if let Err(why) = written {
+                self.dirty.lock().await.extend(&pending[index..]);
+                return Err(why);
+            }
+        }
+
+        Ok(pending.len())
+    }
+
+    /// Writes back any changes, then forgets every cached guild so their settings are loaded
+    /// from the database again. Returns how many guilds were cached.
+    pub async fn reload(&self) -> Result<usize, sqlx::Error> {
+        self.flush().await?;
+
+        let mut guilds = self.guilds.write().await;
+        let count = guilds.len();
+        guilds.clear();
+
+        Ok(count)
+    }
+}
+
+/// A guild's settings, creating default ones first if it has none, such as for guilds the bot
+/// joined while it was offline.
+pub async fn get_or_create_guild_settings(ctx: &Context, guild_id: GuildId) -> Result<Arc<RwLock<GuildSettings>>, BotError> {
+    let guild_settings = get_data::<GuildSettingsContainer>(ctx).await?;
+
+    if let Some(settings) = guild_settings.get(guild_id.get()).await? {
+        return Ok(settings);
+    }
+
+    let owner_id = match guild_id.to_guild_cached(&ctx.cache).map(|guild| guild.owner_id) {
+        Some(owner_id) => owner_id,
+        None => guild_id.to_partial_guild(ctx).await?.owner_id
+    };
+
+    let (db_guild_id, db_owner_id) = (guild_id.get() as i64, owner_id.get() as i64);
+
+    sqlx::query!(
+        "INSERT INTO guild_settings (guild_id, prefix, owner_id) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+        db_guild_id,
+        "-",
+        db_owner_id
+    ).execute(&get_data::<DatabaseConnectionContainer>(ctx).await?).await?;
+
+    guild_settings.get(guild_id.get()).await?.ok_or(BotError::Database(sqlx::Error::RowNotFound))
+}
+
+/// Changes a guild's settings, creating them first if it has none. The change is written back
+/// to the database shortly after.
+pub async fn update_guild_settings(ctx: &Context, guild_id: GuildId, update: impl FnOnce(&mut GuildSettings)) -> Result<(), BotError> {
+    get_or_create_guild_settings(ctx, guild_id).await?;
+    get_data::<GuildSettingsContainer>(ctx).await?.update(guild_id.get(), update).await?;
+
+    Ok(())
+}
+
+/// Writes changed guild settings back to the database every few seconds.
+pub async fn flush_guild_settings_loop(ctx: Context) {
+    let guild_settings = match get_data::<GuildSettingsContainer>(&ctx).await {
+        Ok(guild_settings) => guild_settings,
+        Err(why) => {
+            error!("Couldn't start writing back guild settings: {why}");
+            return;
+        }
+    };
+
+    loop {
+        tokio::time::sleep(SETTINGS_FLUSH_INTERVAL).await;
+
+        if let Err(why) = guild_settings.flush().await {
+            error!("Failed to write back guild settings: {why}");
+        }
+    }
+}
+
+/// The HTTP client shared through `ReqwestClientContainer`. Every request goes through its one
+/// connection pool, so repeated calls to the same API reuse connections.
+pub fn build_http_client() -> Result<Client, reqwest::Error> {
+    Client::builder()
+        .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+        .timeout(HTTP_TIMEOUT)
+        .connect_timeout(Duration::from_secs(10))
+        .pool_idle_timeout(HTTP_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(8)
+        .build()
+}
+
+pub struct GuildPremium {
+    pub tier: PremiumTier,
+    pub source: String,
+    pub entitlement_id: Option<u64>,
+    pub expires_at: Option<DateTime<Utc>>
+}
+
+pub struct Allowlist {
+    pub enabled: bool,
+    pub guilds: HashSet<u64>
 }
 
 
@@ -22,7 +324,7 @@ impl TypeMapKey for ShardManagerContainer {
 }
 
 impl TypeMapKey for GuildSettingsContainer {
-    type Value = Arc<RwLock<HashMap<u64, GuildSettings>>>;
+    type Value = Arc<GuildSettingsStore>;
 }
 
 impl TypeMapKey for ReqwestClientContainer {
@@ -31,4 +333,154 @@ impl TypeMapKey for ReqwestClientContainer {
 
 impl TypeMapKey for DatabaseConnectionContainer {
     type Value = SqlitePool;
+}
+
+impl TypeMapKey for AllowlistContainer {
+    type Value = Arc<RwLock<Allowlist>>;
+}
+
+impl TypeMapKey for BlacklistContainer {
+    type Value = Arc<RwLock<Blacklist>>;
+}
+
+impl TypeMapKey for PremiumContainer {
+    type Value = Arc<RwLock<HashMap<u64, GuildPremium>>>;
+}
+
+impl TypeMapKey for FrameworkContainer {
+    type Value = Arc<StandardFramework>;
+}
+
+impl TypeMapKey for OwnersContainer {
+    type Value = HashSet<UserId>;
+}
+
+impl TypeMapKey for ActivityContainer {
+    type Value = Arc<Mutex<ActivityBuffer>>;
+}
+
+impl TypeMapKey for PrivacyOptOutsContainer {
+    type Value = Arc<RwLock<HashSet<u64>>>;
+}
+
+impl TypeMapKey for AutoResponsesContainer {
+    type Value = Arc<RwLock<HashMap<u64, Vec<AutoResponse>>>>;
+}
+
+impl TypeMapKey for AutomodContainer {
+    type Value = Arc<RwLock<HashMap<u64, Vec<AutomodRule>>>>;
+}
+
+impl TypeMapKey for FiltersContainer {
+    type Value = Arc<RwLock<HashMap<u64, GuildFilters>>>;
+}
+
+impl TypeMapKey for LinksContainer {
+    type Value = Arc<RwLock<HashMap<u64, LinkSettings>>>;
+}
+
+impl TypeMapKey for MediaRulesContainer {
+    type Value = Arc<RwLock<HashMap<u64, MediaRule>>>;
+}
+
+impl TypeMapKey for AntispamContainer {
+    type Value = Arc<Mutex<AntispamState>>;
+}
+
+impl TypeMapKey for AntiraidContainer {
+    type Value = Arc<Mutex<AntiraidState>>;
+}
+
+impl TypeMapKey for MessageLogContainer {
+    type Value = Arc<Mutex<MessageLogCache>>;
+}
+
+impl TypeMapKey for LevelingContainer {
+    type Value = Arc<Mutex<LevelingState>>;
+}
+
+impl TypeMapKey for ModmailContainer {
+    type Value = Arc<RwLock<HashMap<u64, u64>>>;
+}
+
+impl TypeMapKey for BootTimeContainer {
+    type Value = DateTime<Utc>;
+}
+
+impl TypeMapKey for CommandCountsContainer {
+    type Value = Arc<Mutex<HashMap<String, u64>>>;
+}
+
+impl TypeMapKey for ActivityOverrideContainer {
+    type Value = Arc<RwLock<Option<ActivityData>>>;
+}
+
+impl TypeMapKey for CooldownsContainer {
+    type Value = Arc<Mutex<CooldownState>>;
+}
+
+impl TypeMapKey for CommandRulesContainer {
+    type Value = Arc<RwLock<HashMap<u64, Vec<CommandRule>>>>;
+}
+
+impl TypeMapKey for CommandPermissionsContainer {
+    type Value = Arc<RwLock<HashMap<u64, Vec<(String, RoleId)>>>>;
+}
+
+impl TypeMapKey for VoiceSessionsContainer {
+    type Value = Arc<Mutex<HashMap<(u64, u64), Instant>>>;
+}
+
+impl TypeMapKey for InvitesContainer {
+    type Value = Arc<Mutex<HashMap<u64, HashMap<String, CachedInvite>>>>;
+}
+
+impl TypeMapKey for AfkContainer {
+    type Value = Arc<RwLock<HashMap<(u64, u64), AfkStatus>>>;
+}
+
+impl TypeMapKey for StickyContainer {
+    type Value = Arc<Mutex<HashMap<u64, Sticky>>>;
+}
+
+impl TypeMapKey for ChannelGamesContainer {
+    type Value = Arc<Mutex<HashMap<u64, &'static str>>>;
+}
+
+impl TypeMapKey for SfxCooldownsContainer {
+    type Value = Arc<Mutex<HashMap<(u64, u64), Instant>>>;
+}
+
+impl TypeMapKey for TtsReadersContainer {
+    type Value = Arc<RwLock<HashMap<u64, TtsReader>>>;
+}
+
+impl TypeMapKey for LookupCacheContainer {
+    type Value = Arc<Mutex<LookupCache>>;
+}
+
+impl TypeMapKey for ChainGamesContainer {
+    type Value = Arc<Mutex<HashMap<u64, ChainGame>>>;
+}
+
+impl TypeMapKey for WatchlistContainer {
+    type Value = Arc<Mutex<HashMap<u64, Watchlist>>>;
+}
+
+/// Hands the client a shared framework so the same instance can be stored in the
+/// `FrameworkContainer` and used to re-dispatch messages, e.g. when running a suggested command.
+pub struct SharedFramework(pub Arc<StandardFramework>);
+
+#[async_trait]
+impl Framework for SharedFramework {
+    async fn dispatch(&self, ctx: Context, event: FullEvent) {
+        // blacklisted users and guilds can't run any commands
+        if let FullEvent::Message { new_message } = &event {
+            if is_blacklisted(&ctx, Some(new_message.author.id), new_message.guild_id).await {
+                return;
+            }
+        }
+
+        self.0.dispatch(ctx, event).await;
+    }
 }
\ No newline at end of file
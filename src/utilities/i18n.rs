@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use serenity::model::id::GuildId;
+use serenity::prelude::Context;
+use tracing::error;
+
+use crate::utilities::global_data::GuildSettingsContainer;
+use crate::utilities::errors::get_data;
+
+/// The language everything falls back to, and the one every message is written in first.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Every language errors, help, command suggestions and the server and message logs can be shown
+/// in, by code and by its own name. Other replies are only written in English.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "Deutsch"),
+    ("fr", "Français"),
+    ("es", "Español"),
+    ("pt", "Português"),
+    ("nl", "Nederlands")
+];
+
+/// The message catalogs in `locales`, keyed by language code. They're built into the binary and
+/// checked by the tests below, but a catalog that still fails to parse is logged and left out, so
+/// its language falls back to English instead of taking the bot down.
+static CATALOGS: LazyLock<HashMap<&'static str, HashMap<String, String>>> = LazyLock::new(|| {
+    [
+        ("en", include_str!("../../locales/en.json")),
+        ("de", include_str!("../../locales/de.json")),
+        ("fr", include_str!("../../locales/fr.json")),
+        ("es", include_str!("../../locales/es.json")),
+        ("pt", include_str!("../../locales/pt.json")),
+        ("nl", include_str!("../../locales/nl.json"))
+    ]
+    .into_iter()
+    .filter_map(|(language, catalog)| match serde_json::from_str(catalog) {
+        Ok(catalog) => Some((language, catalog)),
+        Err(why) => {
+            error!("Couldn't parse locales/{language}.json, it'll fall back to English: {why}");
+            None
+        }
+    })
+    .collect()
+});
+
+/// The code of a supported language, from its code or its name in any case.
+pub fn find_language(query: &str) -> Option<&'static str> {
+    LANGUAGES.iter()
+        .find(|(code, name)| code.eq_ignore_ascii_case(query) || name.to_lowercase() == query.to_lowercase())
+        .map(|(code, _)| *code)
+}
+
+/// A message in the given language, with each `{name}` replaced by its argument. Messages missing
+/// from a catalog are taken from the English one, and unknown keys are returned as they are so
+/// they stand out instead of leaving a blank.
+pub fn translate(language: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let message = CATALOGS.get(language)
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| CATALOGS.get(DEFAULT_LANGUAGE).and_then(|catalog| catalog.get(key)))
+        .map_or(key, String::as_str);
+
+    args.iter().fold(message.to_string(), |message, (name, value)| message.replace(&format!("{{{name}}}"), value))
+}
+
+/// The language a guild's set for errors, help, command suggestions and logs. Direct messages, and
+/// guilds without settings, get the default.
+pub async fn guild_language(ctx: &Context, guild_id: Option<GuildId>) -> String {
+    let Some(guild_id) = guild_id else {
+        return DEFAULT_LANGUAGE.to_string();
+    };
+
+    let guild_settings = match get_data::<GuildSettingsContainer>(ctx).await {
+        Ok(guild_settings) => guild_settings,
+        Err(why) => {
+            error!("Couldn't read the language of guild {guild_id}: {why}");
+            return DEFAULT_LANGUAGE.to_string();
+        }
+    };
+
+    guild_settings.read(guild_id.get(), |settings| settings.language.clone()).await
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message.split('{').skip(1).filter_map(|part| part.split_once('}')).map(|(name, _)| name).collect()
+    }
+
+    fn read_catalog(path: &Path) -> HashMap<String, String> {
+        let catalog = fs::read_to_string(path).unwrap();
+        serde_json::from_str(&catalog).unwrap_or_else(|why| panic!("{} doesn't parse: {why}", path.display()))
+    }
+
+    #[test]
+    fn catalogs_match_english() {
+        let locales = Path::new(env!("CARGO_MANIFEST_DIR")).join("locales");
+        let english = read_catalog(&locales.join("en.json"));
+
+        for entry in fs::read_dir(&locales).unwrap() {
+            let path = entry.unwrap().path();
+            let catalog = read_catalog(&path);
+
+            let missing: BTreeSet<_> = english.keys().filter(|key| !catalog.contains_key(*key)).collect();
+            let extra: BTreeSet<_> = catalog.keys().filter(|key| !english.contains_key(*key)).collect();
+
+            assert!(missing.is_empty(), "{} is missing {missing:?}", path.display());
+            assert!(extra.is_empty(), "{} has keys English doesn't: {extra:?}", path.display());
+
+            for (key, message) in &catalog {
+                assert_eq!(placeholders(message), placeholders(&english[key]), "{} has other placeholders for {key}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn every_language_has_a_catalog() {
+        for (code, _) in LANGUAGES {
+            assert!(CATALOGS.contains_key(code), "{code} has no catalog");
+        }
+    }
+
+    #[test]
+    fn translates_with_fallbacks() {
+        assert_eq!(translate("de", "log.user_id", &[("id", "1")]), "Benutzer-ID: 1");
+        assert_eq!(translate("xx", "log.user_id", &[("id", "1")]), "User ID: 1");
+        assert_eq!(translate("de", "no.such.key", &[]), "no.such.key");
+    }
+}
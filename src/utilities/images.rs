@@ -0,0 +1,339 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use image::imageops::{self, FilterType};
+use image::io::{Limits, Reader};
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use rand::Rng;
+use serenity::model::channel::Attachment;
+use serenity::model::user::User;
+use serenity::prelude::Context;
+use tokio::sync::Semaphore;
+
+use crate::utilities::charts::{draw_text, fill, text_width};
+use crate::utilities::global_data::ReqwestClientContainer;
+use crate::utilities::errors::get_data;
+
+/// Largest image that will be downloaded for processing.
+const MAX_DOWNLOAD_SIZE: usize = 8 * 1024 * 1024;
+
+/// Largest width or height an image may have before it's even decoded.
+const MAX_DIMENSION: u32 = 4096;
+
+/// Images are scaled down to fit this size before any effect runs, which keeps every effect fast.
+const WORK_SIZE: u32 = 512;
+
+/// How long an effect may run before the command gives up on it.
+const PROCESS_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Longest caption that can be added to an image.
+pub const MAX_CAPTION_LENGTH: usize = 200;
+
+/// Images processed at once across every server, so a burst of commands can't starve the bot's
+/// blocking threads.
+static IMAGE_JOBS: Semaphore = Semaphore::const_new(2);
+
+const PARCHMENT: Rgb<u8> = Rgb([0xe8, 0xd5, 0xa8]);
+const INK: Rgb<u8> = Rgb([0x3b, 0x24, 0x12]);
+const SKIN: Rgb<u8> = Rgb([0xf1, 0xc2, 0x9a]);
+const SKIN_SHADE: Rgb<u8> = Rgb([0xc6, 0x8e, 0x64]);
+const WHITE: Rgb<u8> = Rgb([0xff, 0xff, 0xff]);
+const BLACK: Rgb<u8> = Rgb([0x00, 0x00, 0x00]);
+
+/// Something done to an image by the image commands.
+pub enum Effect {
+    Grayscale,
+    Invert,
+    Sepia,
+    Pixelate,
+    Blur,
+    Mirror,
+    Deepfry,
+    Pet,
+    Wanted,
+    Caption(String)
+}
+
+impl Effect {
+    /// The effects `effect` can apply, by name.
+    pub const NAMES: [&'static str; 6] = ["grayscale", "invert", "sepia", "pixelate", "blur", "mirror"];
+
+    pub fn parse(name: &str) -> Option<Effect> {
+        match name.to_lowercase().as_str() {
+            "grayscale" | "greyscale" | "gray" | "grey" => Some(Effect::Grayscale),
+            "invert" => Some(Effect::Invert),
+            "sepia" => Some(Effect::Sepia),
+            "pixelate" | "pixel" => Some(Effect::Pixelate),
+            "blur" => Some(Effect::Blur),
+            "mirror" => Some(Effect::Mirror),
+            _ => None
+        }
+    }
+}
+
+/// A PNG link to a user's avatar, sized for processing. Discord serves avatars as WebP by default,
+/// which the image commands can't read.
+pub fn avatar_png_url(user: &User) -> String {
+    match &user.avatar {
+        Some(hash) => format!("https://cdn.discordapp.com/avatars/{}/{hash}.png?size={WORK_SIZE}", user.id),
+        None => user.default_avatar_url()
+    }
+}
+
+/// Whether an attachment is an image the image commands can read.
+pub fn is_supported_attachment(attachment: &Attachment) -> bool {
+    attachment.content_type.as_deref().is_some_and(|content_type| content_type == "image/png")
+        || attachment.filename.to_lowercase().ends_with(".png")
+}
+
+/// Downloads an image to process, refusing anything that isn't a PNG or is too large.
+pub async fn download_image(ctx: &Context, url: &str) -> Result<Vec<u8>, String> {
+    let client = get_data::<ReqwestClientContainer>(ctx).await.map_err(|why| why.to_string())?;
+
+    let response = client.get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|_| "I couldn't download that image.".to_string())?;
+
+    let is_png = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("image/png"));
+
+    if !is_png {
+        return Err("That isn't a supported image, only PNGs can be edited.".to_string());
+    }
+
+    let too_large = || format!("That image is too large, it can be at most {} MB.", MAX_DOWNLOAD_SIZE / 1024 / 1024);
+
+    if response.content_length().is_some_and(|length| length as usize > MAX_DOWNLOAD_SIZE) {
+        return Err(too_large());
+    }
+
+    let data = response.bytes().await.map_err(|_| "I couldn't download that image.".to_string())?;
+
+    if data.len() > MAX_DOWNLOAD_SIZE {
+        return Err(too_large());
+    }
+
+    Ok(data.to_vec())
+}
+
+/// Applies an effect to an image on a blocking thread and encodes the result as a PNG. Only a
+/// couple of images are processed at once, and effects that run too long are given up on.
+pub async fn render_effect(data: Vec<u8>, effect: Effect) -> Result<Vec<u8>, String> {
+    let Ok(permit) = IMAGE_JOBS.try_acquire() else {
+        return Err("I'm busy editing other images right now, try again in a moment.".to_string());
+    };
+
+    let task = tokio::task::spawn_blocking(move || {
+        // the permit is held until the effect actually finishes, even if the command stops waiting
+        let _permit = permit;
+        apply_effect(&data, &effect)
+    });
+
+    match tokio::time::timeout(PROCESS_TIMEOUT, task).await {
+        Ok(Ok(Ok(png))) => Ok(png),
+        Ok(Ok(Err(image::ImageError::Limits(_)))) => Err(format!("That image is too large, it can be at most {MAX_DIMENSION}x{MAX_DIMENSION} pixels.")),
+        Ok(Ok(Err(_))) => Err("I couldn't read that image.".to_string()),
+        Ok(Err(_)) => Err("Something went wrong while editing that image.".to_string()),
+        Err(_) => Err("Editing that image took too long.".to_string())
+    }
+}
+
+fn apply_effect(data: &[u8], effect: &Effect) -> Result<Vec<u8>, image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(256 * 1024 * 1024);
+
+    let mut reader = Reader::new(Cursor::new(data)).with_guessed_format()?;
+    reader.limits(limits);
+
+    let decoded = reader.decode()?;
+
+    let decoded = if decoded.width() > WORK_SIZE || decoded.height() > WORK_SIZE {
+        decoded.resize(WORK_SIZE, WORK_SIZE, FilterType::Triangle)
+    } else {
+        decoded
+    };
+
+    let image = flatten(&decoded);
+
+    let result = match effect {
+        Effect::Grayscale => DynamicImage::ImageRgb8(image).grayscale().to_rgb8(),
+        Effect::Invert => {
+            let mut image = image;
+            imageops::invert(&mut image);
+            image
+        }
+        Effect::Sepia => sepia(image),
+        Effect::Pixelate => pixelate(&image),
+        Effect::Blur => imageops::blur(&image, 4.0),
+        Effect::Mirror => mirror(image),
+        Effect::Deepfry => deepfry(&image),
+        Effect::Pet => pet(&image),
+        Effect::Wanted => wanted(&image),
+        Effect::Caption(text) => caption(&image, text)
+    };
+
+    let mut png = Cursor::new(Vec::new());
+    result.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// Drops transparency by laying the image over white.
+fn flatten(image: &DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [red, green, blue, alpha] = rgba.get_pixel(x, y).0;
+        let blend = |channel: u8| ((u32::from(channel) * u32::from(alpha) + 255 * (255 - u32::from(alpha))) / 255) as u8;
+        Rgb([blend(red), blend(green), blend(blue)])
+    })
+}
+
+fn sepia(mut image: RgbImage) -> RgbImage {
+    for pixel in image.pixels_mut() {
+        let [red, green, blue] = pixel.0.map(f32::from);
+
+        pixel.0 = [
+            (red * 0.393 + green * 0.769 + blue * 0.189).min(255.0) as u8,
+            (red * 0.349 + green * 0.686 + blue * 0.168).min(255.0) as u8,
+            (red * 0.272 + green * 0.534 + blue * 0.131).min(255.0) as u8
+        ];
+    }
+
+    image
+}
+
+fn pixelate(image: &RgbImage) -> RgbImage {
+    let (width, height) = image.dimensions();
+    let small = imageops::resize(image, (width / 16).max(1), (height / 16).max(1), FilterType::Triangle);
+    imageops::resize(&small, width, height, FilterType::Nearest)
+}
+
+/// Reflects the left half of the image onto the right.
+fn mirror(mut image: RgbImage) -> RgbImage {
+    let (width, height) = image.dimensions();
+
+    for y in 0..height {
+        for x in 0..width / 2 {
+            let pixel = *image.get_pixel(x, y);
+            image.put_pixel(width - 1 - x, y, pixel);
+        }
+    }
+
+    image
+}
+
+/// Oversaturates, oversharpens and adds noise until the image looks fried.
+fn deepfry(image: &RgbImage) -> RgbImage {
+    let mut fried = imageops::unsharpen(image, 2.0, 1);
+    fried = imageops::contrast(&fried, 60.0);
+
+    let mut rng = rand::thread_rng();
+
+    for pixel in fried.pixels_mut() {
+        let [red, green, blue] = pixel.0.map(i32::from);
+        let gray = (red + green + blue) / 3;
+        let noise = rng.gen_range(-24..=24);
+
+        // push every channel away from gray, then warm the whole image up
+        let saturate = |channel: i32, warmth: i32| ((channel + (channel - gray) * 2 + noise + warmth).clamp(0, 255) / 48 * 48) as u8;
+
+        pixel.0 = [saturate(red, 40), saturate(green, 10), saturate(blue, -30)];
+    }
+
+    fried
+}
+
+/// Squashes the image down a little and draws a hand patting it from above.
+fn pet(image: &RgbImage) -> RgbImage {
+    let size = WORK_SIZE / 2;
+    let mut canvas = RgbImage::from_pixel(size, size, WHITE);
+
+    let squashed = imageops::resize(image, size * 7 / 8, size * 3 / 4, FilterType::Triangle);
+    imageops::overlay(&mut canvas, &squashed, i64::from(size / 16), i64::from(size / 4));
+
+    // a blocky hand: the palm with four fingers reaching down onto the head
+    let palm = (size / 4, size / 40, size / 2, size / 8);
+    fill(&mut canvas, palm.0, palm.1, palm.2, palm.3, SKIN);
+
+    for finger in 0..4 {
+        let x = palm.0 + finger * palm.2 / 4 + 2;
+        fill(&mut canvas, x, palm.1 + palm.3, palm.2 / 4 - 4, size / 10, SKIN);
+        fill(&mut canvas, x + palm.2 / 4 - 6, palm.1 + palm.3, 2, size / 10, SKIN_SHADE);
+    }
+
+    fill(&mut canvas, palm.0, palm.1 + palm.3 - 2, palm.2, 2, SKIN_SHADE);
+
+    canvas
+}
+
+/// Puts the image on an old wanted poster.
+fn wanted(image: &RgbImage) -> RgbImage {
+    let (width, height) = (WORK_SIZE * 3 / 4, WORK_SIZE);
+    let mut poster = RgbImage::from_pixel(width, height, PARCHMENT);
+
+    // a double border around the edge
+    for inset in [8, 16] {
+        fill(&mut poster, inset, inset, width - inset * 2, 3, INK);
+        fill(&mut poster, inset, height - inset - 3, width - inset * 2, 3, INK);
+        fill(&mut poster, inset, inset, 3, height - inset * 2, INK);
+        fill(&mut poster, width - inset - 3, inset, 3, height - inset * 2, INK);
+    }
+
+    let centered = |text: &str, scale: u32| (width - text_width(text, scale)) / 2;
+
+    draw_text(&mut poster, "wanted", centered("wanted", 8), 40, 8, INK);
+
+    let photo_size = width - 96;
+    let photo = sepia(imageops::resize(image, photo_size, photo_size, FilterType::Triangle));
+
+    fill(&mut poster, 44, 110, photo_size + 8, photo_size + 8, INK);
+    imageops::overlay(&mut poster, &photo, 48, 114);
+
+    let below = 110 + photo_size + 28;
+    draw_text(&mut poster, "dead or alive", centered("dead or alive", 3), below, 3, INK);
+
+    let reward = format!("reward {}", rand::thread_rng().gen_range(1..=50) * 1000);
+    draw_text(&mut poster, &reward, centered(&reward, 4), below + 40, 4, INK);
+
+    poster
+}
+
+/// Adds a white band above the image with the caption in black, wrapped to fit.
+fn caption(image: &RgbImage, text: &str) -> RgbImage {
+    let (width, height) = image.dimensions();
+    let scale = (width / 160).max(2);
+    let padding = scale * 4;
+    let line_height = scale * 10;
+
+    let text = text.to_lowercase();
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if text_width(&format!("{line} {word}"), scale) <= width.saturating_sub(padding * 2) => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string())
+        }
+    }
+
+    let band = line_height * lines.len() as u32 + padding * 2;
+    let mut captioned = RgbImage::from_pixel(width, height + band, WHITE);
+
+    for (index, line) in lines.iter().enumerate() {
+        let x = width.saturating_sub(text_width(line, scale)) / 2;
+        draw_text(&mut captioned, line, x, padding + index as u32 * line_height, scale, BLACK);
+    }
+
+    imageops::overlay(&mut captioned, image, 0, i64::from(band));
+
+    captioned
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage, EditMessage};
+use serenity::gateway::ConnectionStage;
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId};
+use serenity::model::Timestamp;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::utilities::global_data::{DatabaseConnectionContainer, ShardManagerContainer};
+use crate::utilities::errors::get_data;
+
+/// How long a shard has to be disconnected before an incident is declared automatically.
+const OUTAGE_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// How often the shard monitor checks every shard's connection stage.
+const MONITOR_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IncidentStatus {
+    Investigating,
+    Identified,
+    Monitoring,
+    Resolved
+}
+
+impl IncidentStatus {
+    pub fn parse(status: &str) -> Option<IncidentStatus> {
+        match status.to_lowercase().as_str() {
+            "investigating" => Some(IncidentStatus::Investigating),
+            "identified" => Some(IncidentStatus::Identified),
+            "monitoring" => Some(IncidentStatus::Monitoring),
+            "resolved" => Some(IncidentStatus::Resolved),
+            _ => None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IncidentStatus::Investigating => "investigating",
+            IncidentStatus::Identified => "identified",
+            IncidentStatus::Monitoring => "monitoring",
+            IncidentStatus::Resolved => "resolved"
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IncidentStatus::Investigating => "🔴 Investigating",
+            IncidentStatus::Identified => "🟠 Identified",
+            IncidentStatus::Monitoring => "🟡 Monitoring",
+            IncidentStatus::Resolved => "🟢 Resolved"
+        }
+    }
+
+    fn color(self) -> u32 {
+        match self {
+            IncidentStatus::Investigating => 0x00e7_4c3c,
+            IncidentStatus::Identified => 0x00e6_7e22,
+            IncidentStatus::Monitoring => 0x00f1_c40f,
+            IncidentStatus::Resolved => 0x002e_cc71
+        }
+    }
+}
+
+/// Opens a new incident and posts it to every subscribed guild, returning its ID.
+pub async fn declare_incident(http: &Http, database: &SqlitePool, title: &str, message: &str, automatic: bool) -> Result<i64, sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    let is_automatic = automatic as i64;
+
+    let incident_id = sqlx::query!(
+        "INSERT INTO incidents (title, automatic, created_at) VALUES (?, ?, ?)",
+        title,
+        is_automatic,
+        now
+    ).execute(database).await?.last_insert_rowid();
+
+    add_incident_update(http, database, incident_id, IncidentStatus::Investigating, message).await?;
+
+    Ok(incident_id)
+}
+
+/// Records a status update on an incident and edits the posted embeds to match.
+pub async fn add_incident_update(http: &Http, database: &SqlitePool, incident_id: i64, status: IncidentStatus, message: &str) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    let status_str = status.as_str();
+    let resolved_at = (status == IncidentStatus::Resolved).then(|| now.clone());
+
+    sqlx::query!(
+        "INSERT INTO incident_updates (incident_id, status, message, created_at) VALUES (?, ?, ?, ?)",
+        incident_id,
+        status_str,
+        message,
+        now
+    ).execute(database).await?;
+
+    sqlx::query!(
+        "UPDATE incidents SET status = ?, resolved_at = ? WHERE id = ?",
+        status_str,
+        resolved_at,
+        incident_id
+    ).execute(database).await?;
+
+    broadcast_incident(http, database, incident_id).await
+}
+
+/// The most recent incident that hasn't been resolved yet.
+pub async fn open_incident(database: &SqlitePool, automatic_only: bool) -> Result<Option<i64>, sqlx::Error> {
+    let automatic = automatic_only as i64;
+
+    let row = sqlx::query!(
+        "SELECT id FROM incidents WHERE resolved_at IS NULL AND (automatic = 1 OR ? = 0) ORDER BY id DESC LIMIT 1",
+        automatic
+    ).fetch_optional(database).await?;
+
+    Ok(row.map(|row| row.id))
+}
+
+async fn render_incident(database: &SqlitePool, incident_id: i64) -> Result<CreateEmbed, sqlx::Error> {
+    let incident = sqlx::query!(
+        "SELECT title, status, created_at FROM incidents WHERE id = ?",
+        incident_id
+    ).fetch_one(database).await?;
+
+    let updates = sqlx::query!(
+        "SELECT status, message, created_at FROM incident_updates WHERE incident_id = ? ORDER BY id DESC",
+        incident_id
+    ).fetch_all(database).await?;
+
+    let status = IncidentStatus::parse(&incident.status).unwrap_or(IncidentStatus::Investigating);
+
+    let mut embed = CreateEmbed::new()
+        .color(status.color())
+        .title(format!("Incident: {}", incident.title))
+        .description(format!("**Current status**: {}", status.label()))
+        .footer(CreateEmbedFooter::new(format!("Incident #{incident_id}")));
+
+    if let Ok(created_at) = Timestamp::parse(&incident.created_at) {
+        embed = embed.timestamp(created_at);
+    }
+
+    // embeds are limited to 25 fields, the newest updates are the most useful ones
+    for update in updates.iter().take(10) {
+        let label = IncidentStatus::parse(&update.status).unwrap_or(status).label();
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&update.created_at).map_or(0, |time| time.timestamp());
+
+        embed = embed.field(format!("{label} • <t:{timestamp}:t>"), &update.message, false);
+    }
+
+    Ok(embed)
+}
+
+/// Posts the incident embed to subscribed guilds that haven't received it yet and edits the
+/// ones that already have.
+async fn broadcast_incident(http: &Http, database: &SqlitePool, incident_id: i64) -> Result<(), sqlx::Error> {
+    let embed = render_incident(database, incident_id).await?;
+
+    let subscribers = sqlx::query!(
+        "SELECT guild_id, updates_channel_id FROM guild_settings WHERE updates_channel_id IS NOT NULL"
+    ).fetch_all(database).await?;
+
+    let posted: HashMap<i64, (i64, i64)> = sqlx::query!(
+        "SELECT guild_id, channel_id, message_id FROM incident_messages WHERE incident_id = ?",
+        incident_id
+    ).fetch_all(database).await?
+        .into_iter()
+        .map(|row| (row.guild_id, (row.channel_id, row.message_id)))
+        .collect();
+
+    for subscriber in subscribers {
+        let Some(channel_id) = subscriber.updates_channel_id else {
+            continue;
+        };
+
+        if let Some((posted_channel, posted_message)) = posted.get(&subscriber.guild_id) {
+            let channel = ChannelId::new(*posted_channel as u64);
+            let message = MessageId::new(*posted_message as u64);
+
+            if let Err(err) = channel.edit_message(http, message, EditMessage::new().embed(embed.clone())).await {
+                warn!("Couldn't edit incident #{incident_id} in guild {}: {err}", subscriber.guild_id);
+            }
+
+            continue;
+        }
+
+        let channel = ChannelId::new(channel_id as u64);
+
+        match channel.send_message(http, CreateMessage::new().embed(embed.clone())).await {
+            Ok(message) => {
+                let message_id = message.id.get() as i64;
+
+                sqlx::query!(
+                    "INSERT INTO incident_messages (incident_id, guild_id, channel_id, message_id) VALUES (?, ?, ?, ?)",
+                    incident_id,
+                    subscriber.guild_id,
+                    channel_id,
+                    message_id
+                ).execute(database).await?;
+            }
+            Err(err) => warn!("Couldn't post incident #{incident_id} in guild {}: {err}", subscriber.guild_id)
+        }
+    }
+
+    Ok(())
+}
+
+/// Watches every shard's connection stage, declaring an incident once a shard has been down for
+/// longer than `OUTAGE_THRESHOLD` and resolving it once every shard is connected again.
+pub async fn monitor_shards(ctx: Context) {
+    let (shard_manager, database) = match (get_data::<ShardManagerContainer>(&ctx).await, get_data::<DatabaseConnectionContainer>(&ctx).await) {
+        (Ok(shard_manager), Ok(database)) => (shard_manager, database),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't start monitoring shards: {why}");
+            return;
+        }
+    };
+
+    let mut down_since = HashMap::new();
+
+    loop {
+        tokio::time::sleep(MONITOR_INTERVAL).await;
+
+        {
+            let runners = shard_manager.runners.lock().await;
+
+            for (shard_id, runner) in runners.iter() {
+                if runner.stage == ConnectionStage::Connected {
+                    down_since.remove(shard_id);
+                } else {
+                    down_since.entry(*shard_id).or_insert_with(Instant::now);
+                }
+            }
+        }
+
+        let mut outages: Vec<u32> = down_since.iter()
+            .filter(|(_, since)| since.elapsed() >= OUTAGE_THRESHOLD)
+            .map(|(shard_id, _)| shard_id.0)
+            .collect();
+        outages.sort_unstable();
+
+        let open = match open_incident(&database, true).await {
+            Ok(open) => open,
+            Err(err) => {
+                error!("Shard monitor couldn't check for open incidents: {err}");
+                continue;
+            }
+        };
+
+        let result = match (open, outages.is_empty()) {
+            (None, false) => {
+                let shards = outages.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
+                info!("Declaring an incident for shard(s) {shards}");
+
+                declare_incident(
+                    &ctx.http,
+                    &database,
+                    "Shard outage",
+                    &format!("Shard(s) {shards} have been disconnected for over {} minutes. Some servers may not receive responses.", OUTAGE_THRESHOLD.as_secs() / 60),
+                    true
+                ).await.map(|_| ())
+            }
+            (Some(incident_id), true) if down_since.is_empty() => {
+                info!("All shards recovered, resolving incident #{incident_id}");
+
+                add_incident_update(&ctx.http, &database, incident_id, IncidentStatus::Resolved, "All shards have reconnected.").await
+            }
+            _ => Ok(())
+        };
+
+        if let Err(err) = result {
+            error!("Shard monitor failed to update incidents: {err}");
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serenity::model::event::{InviteCreateEvent, InviteDeleteEvent};
+use serenity::model::guild::Member;
+use serenity::model::id::GuildId;
+use serenity::model::invite::RichInvite;
+use serenity::prelude::Context;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{DatabaseConnectionContainer, InvitesContainer};
+use crate::utilities::errors::get_data;
+
+/// What's known about one of a guild's invites, to tell which one a member joined with.
+#[derive(Clone, Copy)]
+pub struct CachedInvite {
+    uses: u64,
+    /// 0 for no limit.
+    max_uses: u64,
+    inviter_id: Option<u64>
+}
+
+impl CachedInvite {
+    fn from_invite(invite: &RichInvite) -> CachedInvite {
+        CachedInvite {
+            uses: invite.uses,
+            max_uses: u64::from(invite.max_uses),
+            inviter_id: invite.inviter.as_ref().map(|inviter| inviter.id.get())
+        }
+    }
+
+    /// Whether one more use deletes the invite, in which case Discord may delete it before the
+    /// member who used it is announced.
+    fn last_use(self) -> bool {
+        self.max_uses > 0 && self.uses + 1 >= self.max_uses
+    }
+}
+
+/// The guild's invites by code, or `None` if the bot can't see them, which needs Manage Server.
+async fn fetch_invites(ctx: &Context, guild_id: GuildId) -> Option<HashMap<String, CachedInvite>> {
+    match guild_id.invites(ctx).await {
+        Ok(invites) => Some(invites.iter().map(|invite| (invite.code.clone(), CachedInvite::from_invite(invite))).collect()),
+        Err(why) => {
+            warn!("Couldn't fetch the invites of guild {guild_id}: {why}");
+            None
+        }
+    }
+}
+
+/// Caches a guild's invites so the one new members join with can be found.
+pub async fn cache_guild_invites(ctx: &Context, guild_id: GuildId) {
+    let Some(invites) = fetch_invites(ctx, guild_id).await else {
+        return;
+    };
+
+    let cache = match get_data::<InvitesContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't cache the invites of guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    cache.lock().await.insert(guild_id.get(), invites);
+}
+
+pub async fn forget_guild_invites(ctx: &Context, guild_id: GuildId) {
+    let cache = match get_data::<InvitesContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't forget the invites of guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    cache.lock().await.remove(&guild_id.get());
+}
+
+pub async fn handle_invite_create(ctx: &Context, event: &InviteCreateEvent) {
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+
+    let cache = match get_data::<InvitesContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't cache a new invite: {why}");
+            return;
+        }
+    };
+
+    let invite = CachedInvite {
+        uses: 0,
+        max_uses: u64::from(event.max_uses),
+        inviter_id: event.inviter.as_ref().map(|inviter| inviter.id.get())
+    };
+
+    let mut cache = cache.lock().await;
+
+    // guilds whose invites couldn't be fetched aren't tracked
+    if let Some(invites) = cache.get_mut(&guild_id.get()) {
+        invites.insert(event.code.clone(), invite);
+    }
+}
+
+pub async fn handle_invite_delete(ctx: &Context, event: &InviteDeleteEvent) {
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+
+    let cache = match get_data::<InvitesContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't forget a deleted invite: {why}");
+            return;
+        }
+    };
+
+    let mut cache = cache.lock().await;
+
+    // invites that were used up are kept until the member who used them is seen joining
+    if let Some(invites) = cache.get_mut(&guild_id.get()) {
+        if invites.get(&event.code).is_some_and(|invite| !invite.last_use()) {
+            invites.remove(&event.code);
+        }
+    }
+}
+
+/// The code and inviter of the one invite used between two fetches of a guild's invites.
+fn used_invite(cached: &HashMap<String, CachedInvite>, current: &HashMap<String, CachedInvite>) -> Option<(String, Option<u64>)> {
+    let mut candidates: Vec<_> = current.iter()
+        .filter(|(code, invite)| invite.uses > cached.get(*code).map_or(0, |cached| cached.uses))
+        .map(|(code, invite)| (code.clone(), invite.inviter_id))
+        .collect();
+
+    // an invite that was used up is gone by now
+    if candidates.is_empty() {
+        candidates = cached.iter()
+            .filter(|(code, invite)| invite.last_use() && !current.contains_key(*code))
+            .map(|(code, invite)| (code.clone(), invite.inviter_id))
+            .collect();
+    }
+
+    match candidates.len() {
+        1 => candidates.pop(),
+        _ => None
+    }
+}
+
+/// Works out which invite a new member joined with by comparing the guild's invites with the
+/// cached ones, and records who invited them. The invite is recorded as unknown when more than one
+/// was used since they were last fetched, or the member joined some other way, e.g. through
+/// discovery.
+pub async fn handle_invite_join(ctx: &Context, member: &Member) {
+    if member.user.bot {
+        return;
+    }
+
+    let guild_id = member.guild_id;
+
+    let (database, cache) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<InvitesContainer>(ctx).await) {
+        (Ok(database), Ok(cache)) => (database, cache),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't work out which invite member {} used: {why}", member.user.id);
+            return;
+        }
+    };
+
+    let Some(current) = fetch_invites(ctx, guild_id).await else {
+        return;
+    };
+
+    // nothing to compare with if the guild's invites weren't cached yet
+    let used = cache.lock().await
+        .insert(guild_id.get(), current.clone())
+        .and_then(|cached| used_invite(&cached, &current));
+
+    let (code, inviter_id) = used.map_or((None, None), |(code, inviter_id)| (Some(code), inviter_id.map(|inviter_id| inviter_id as i64)));
+    let (db_guild_id, user_id, joined_at) = (guild_id.get() as i64, member.user.id.get() as i64, Utc::now().to_rfc3339());
+
+    let result = sqlx::query!(
+        "INSERT INTO invite_joins (guild_id, user_id, inviter_id, code, joined_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET inviter_id = excluded.inviter_id, code = excluded.code, joined_at = excluded.joined_at",
+        db_guild_id,
+        user_id,
+        inviter_id,
+        code,
+        joined_at
+    ).execute(&database).await;
+
+    if let Err(why) = result {
+        error!("Failed to record the invite user {} joined guild {guild_id} with: {why}", member.user.id);
+    }
+}
@@ -0,0 +1,128 @@
+use serenity::builder::{CreateActionRow, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage};
+use serenity::model::application::CommandInteraction;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use serenity::model::permissions::Permissions;
+use serenity::prelude::Context;
+
+use crate::utilities::command_permissions::role_access;
+use crate::utilities::global_data::get_or_create_guild_settings;
+
+/// Where a command was run from. Commands available both with a prefix and as slash commands
+/// take one of these, so their logic is written once for both.
+pub enum Invocation<'a> {
+    Message(&'a Message),
+    Slash(&'a CommandInteraction)
+}
+
+impl Invocation<'_> {
+    pub fn guild_id(&self) -> Option<GuildId> {
+        match self {
+            Invocation::Message(msg) => msg.guild_id,
+            Invocation::Slash(command) => command.guild_id
+        }
+    }
+
+    /// The user who ran the command.
+    pub fn user_id(&self) -> UserId {
+        match self {
+            Invocation::Message(msg) => msg.author.id,
+            Invocation::Slash(command) => command.user.id
+        }
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        match self {
+            Invocation::Message(msg) => msg.channel_id,
+            Invocation::Slash(command) => command.channel_id
+        }
+    }
+
+    /// The user's permissions in the guild the command was run in, none outside of guilds.
+    pub async fn permissions(&self, ctx: &Context) -> Permissions {
+        match self {
+            Invocation::Message(msg) => {
+                let Some(guild_id) = msg.guild_id else {
+                    return Permissions::empty();
+                };
+
+                match guild_id.member(ctx, msg.author.id).await {
+                    Ok(member) => member.permissions(&ctx.cache).unwrap_or_default(),
+                    Err(_) => Permissions::empty()
+                }
+            }
+            // interactions already carry the member's resolved permissions
+            Invocation::Slash(command) => command.member.as_ref()
+                .and_then(|member| member.permissions)
+                .unwrap_or_default()
+        }
+    }
+
+    /// Whether the user has the administrator permission in the guild the command was run in.
+    pub async fn is_administrator(&self, ctx: &Context) -> bool {
+        self.permissions(ctx).await.administrator()
+    }
+
+    /// The roles the user has in the guild the command was run in.
+    pub fn roles(&self) -> Vec<RoleId> {
+        match self {
+            Invocation::Message(msg) => msg.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default(),
+            Invocation::Slash(command) => command.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default()
+        }
+    }
+
+    /// Whether the user may use a command that's limited to administrators by default, either by
+    /// being one or by having a role the guild's command permissions allow it for.
+    pub async fn has_command_access(&self, ctx: &Context, command: &str) -> bool {
+        let Some(guild_id) = self.guild_id() else {
+            return false;
+        };
+
+        if self.is_administrator(ctx).await {
+            return true;
+        }
+
+        role_access(ctx, guild_id, &self.roles(), command).await == Some(true)
+    }
+
+    /// The guild's prefix, or the default prefix outside of guilds.
+    pub async fn prefix(&self, ctx: &Context) -> String {
+        match self.guild_id() {
+            Some(guild_id) => match get_or_create_guild_settings(ctx, guild_id).await {
+                Ok(settings) => settings.read().await.prefix.clone(),
+                Err(_) => "-".to_string()
+            }
+            None => "-".to_string()
+        }
+    }
+
+    /// Sends an embed in reply, as a channel message or as the interaction's response.
+    pub async fn respond(&self, ctx: &Context, embed: CreateEmbed) -> Result<(), serenity::Error> {
+        match self {
+            Invocation::Message(msg) => {
+                msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+            }
+            Invocation::Slash(command) => {
+                let response = CreateInteractionResponseMessage::new().embed(embed);
+                command.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends an embed with components in reply and returns the message it was sent as, so
+    /// component interactions on it can be collected.
+    pub async fn respond_with_components(&self, ctx: &Context, embed: CreateEmbed, components: Vec<CreateActionRow>) -> Result<Message, serenity::Error> {
+        match self {
+            Invocation::Message(msg) => {
+                msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed).components(components)).await
+            }
+            Invocation::Slash(command) => {
+                let response = CreateInteractionResponseMessage::new().embed(embed).components(components);
+                command.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+                command.get_response(ctx).await
+            }
+        }
+    }
+}
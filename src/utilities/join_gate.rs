@@ -0,0 +1,105 @@
+use chrono::{Duration, Utc};
+use serenity::framework::standard::CommandError;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId};
+use serenity::model::user::User;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::moderation::{ModAction, apply_action, notify_target, record_action};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::errors::get_data;
+
+pub struct JoinGate {
+    pub min_account_age: Duration,
+    pub require_avatar: bool,
+    /// The role members failing the gate are held in, or `None` to kick them.
+    pub quarantine_role: Option<RoleId>
+}
+
+impl JoinGate {
+    /// Why a new member doesn't meet the gate's requirements, if they don't.
+    pub fn failure(&self, user: &User) -> Option<String> {
+        let account_age = Utc::now() - *user.created_at();
+
+        if account_age < self.min_account_age {
+            return Some(format!("Account is younger than {}", format_duration(self.min_account_age)));
+        }
+
+        if self.require_avatar && user.avatar.is_none() {
+            return Some("Account has no profile picture".to_string());
+        }
+
+        None
+    }
+}
+
+/// The guild's join gate, if it's turned on.
+pub async fn join_gate(database: &SqlitePool, guild_id: GuildId) -> Result<Option<JoinGate>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT min_account_age, require_avatar, action, quarantine_role_id FROM join_gate_settings WHERE guild_id = ? AND enabled = 1",
+        guild_id
+    ).fetch_optional(database).await?;
+
+    Ok(row.map(|row| JoinGate {
+        min_account_age: Duration::seconds(row.min_account_age.max(0)),
+        require_avatar: row.require_avatar != 0,
+        quarantine_role: row.quarantine_role_id.filter(|_| row.action == "quarantine").map(|role_id| RoleId::new(role_id as u64))
+    }))
+}
+
+/// Kicks or quarantines a new member who doesn't meet the guild's join gate, telling them why and
+/// recording it. Returns whether they were.
+pub async fn handle_join_gate(ctx: &Context, member: &Member) -> bool {
+    if member.user.bot {
+        return false;
+    }
+
+    let guild_id = member.guild_id;
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check member {} against the join gate: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    let gate = match join_gate(&database, guild_id).await {
+        Ok(Some(gate)) => gate,
+        Ok(None) => return false,
+        Err(why) => {
+            error!("Failed to fetch the join gate of guild {guild_id}: {why}");
+            return false;
+        }
+    };
+
+    let Some(failure) = gate.failure(&member.user) else {
+        return false;
+    };
+
+    let reason = format!("Join gate: {failure}");
+    let bot = User::from(ctx.cache.current_user().clone());
+
+    let result = match gate.quarantine_role {
+        None => apply_action(ctx, guild_id, &member.user, &bot, ModAction::Kick, None, Some(&reason)).await.map(drop),
+        Some(role_id) => async {
+            ctx.http.add_member_role(guild_id, member.user.id, role_id, Some(&reason)).await?;
+            notify_target(ctx, guild_id, &member.user, ModAction::Quarantine, Some(&reason)).await;
+            record_action(ctx, &database, guild_id, ModAction::Quarantine, member.user.id, bot.id, Some(&reason)).await?;
+
+            Ok::<(), CommandError>(())
+        }.await
+    };
+
+    if let Err(why) = result {
+        warn!("The join gate couldn't hold back user {} in guild {guild_id}: {why}", member.user.id);
+        return false;
+    }
+
+    true
+}
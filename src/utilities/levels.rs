@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{DatabaseConnectionContainer, LevelingContainer};
+use crate::utilities::templates::{message_context, render_template};
+use crate::utilities::errors::{get_data, BotError};
+
+pub const DEFAULT_LEVEL_UP: &str = "{user.mention} reached level {level}!";
+
+/// A member earns XP for at most one message in this long.
+const XP_COOLDOWN: Duration = Duration::from_secs(60);
+const MIN_MESSAGE_XP: i64 = 15;
+const MAX_MESSAGE_XP: i64 = 25;
+
+/// Once this many cooldowns are tracked, expired ones are forgotten.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// XP needed to go from `level` to the next one. Each level takes a little longer than the last.
+pub fn xp_to_next_level(level: i64) -> i64 {
+    5 * level * level + 50 * level + 100
+}
+
+/// The level reached with `xp` in total, along with the XP earned towards the next level.
+pub fn level_progress(xp: i64) -> (i64, i64) {
+    let (mut level, mut remaining) = (0, xp.max(0));
+
+    while remaining >= xp_to_next_level(level) {
+        remaining -= xp_to_next_level(level);
+        level += 1;
+    }
+
+    (level, remaining)
+}
+
+/// Every guild with leveling turned on, with its channel multipliers, along with when each
+/// member last earned XP.
+#[derive(Default)]
+pub struct LevelingState {
+    multipliers: HashMap<u64, HashMap<u64, f64>>,
+    cooldowns: HashMap<(u64, u64), Instant>
+}
+
+pub async fn load_leveling(database: &SqlitePool) -> Result<LevelingState, sqlx::Error> {
+    let guilds = sqlx::query!("SELECT guild_id FROM guild_settings WHERE leveling_enabled != 0")
+        .fetch_all(database)
+        .await?;
+
+    let mut multipliers: HashMap<u64, HashMap<u64, f64>> = guilds.into_iter()
+        .map(|row| (row.guild_id as u64, HashMap::new()))
+        .collect();
+
+    let rows = sqlx::query!("SELECT guild_id, channel_id, multiplier FROM level_multipliers")
+        .fetch_all(database)
+        .await?;
+
+    for row in rows {
+        if let Some(channels) = multipliers.get_mut(&(row.guild_id as u64)) {
+            channels.insert(row.channel_id as u64, row.multiplier);
+        }
+    }
+
+    Ok(LevelingState { multipliers, cooldowns: HashMap::new() })
+}
+
+/// Reloads one guild's leveling settings from the database after they've been changed.
+pub async fn reload_leveling(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let state = get_data::<LevelingContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let enabled = sqlx::query!("SELECT leveling_enabled FROM guild_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(&database)
+        .await?
+        .is_some_and(|row| row.leveling_enabled != 0);
+
+    let channels: HashMap<u64, f64> = sqlx::query!("SELECT channel_id, multiplier FROM level_multipliers WHERE guild_id = ?", db_guild_id)
+        .fetch_all(&database)
+        .await?
+        .into_iter()
+        .map(|row| (row.channel_id as u64, row.multiplier))
+        .collect();
+
+    let mut state = state.lock().await;
+
+    if enabled {
+        state.multipliers.insert(guild_id.get(), channels);
+    } else {
+        state.multipliers.remove(&guild_id.get());
+    }
+
+    Ok(())
+}
+
+/// Gives the message's author XP, unless they earned some too recently, then announces and
+/// rewards any level they reached.
+pub async fn handle_xp(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    let (database, state) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<LevelingContainer>(ctx).await) {
+        (Ok(database), Ok(state)) => (database, state),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't give XP for message {}: {why}", msg.id);
+            return;
+        }
+    };
+
+    let gained = {
+        let mut state = state.lock().await;
+
+        let Some(multiplier) = state.multipliers.get(&guild_id.get()).map(|channels| channels.get(&msg.channel_id.get()).copied().unwrap_or(1.0)) else {
+            return;
+        };
+
+        let now = Instant::now();
+        let key = (guild_id.get(), msg.author.id.get());
+
+        if multiplier <= 0.0 || state.cooldowns.get(&key).is_some_and(|last| now.duration_since(*last) < XP_COOLDOWN) {
+            return;
+        }
+
+        if state.cooldowns.len() >= PRUNE_THRESHOLD {
+            state.cooldowns.retain(|_, last| now.duration_since(*last) < XP_COOLDOWN);
+        }
+
+        state.cooldowns.insert(key, now);
+
+        (rand::thread_rng().gen_range(MIN_MESSAGE_XP..=MAX_MESSAGE_XP) as f64 * multiplier).round() as i64
+    };
+
+    let (db_guild_id, user_id) = (guild_id.get() as i64, msg.author.id.get() as i64);
+
+    let xp = sqlx::query!(
+        "INSERT INTO levels (guild_id, user_id, xp) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET xp = xp + excluded.xp
+        RETURNING xp",
+        db_guild_id,
+        user_id,
+        gained
+    ).fetch_one(&database).await;
+
+    let xp = match xp {
+        Ok(row) => row.xp,
+        Err(why) => {
+            error!("Failed to give XP to user {} in guild {guild_id}: {why}", msg.author.id);
+            return;
+        }
+    };
+
+    let (before, _) = level_progress(xp - gained);
+    let (level, _) = level_progress(xp);
+
+    if level > before {
+        level_up(ctx, &database, msg, level).await;
+    }
+}
+
+async fn level_up(ctx: &Context, database: &SqlitePool, msg: &Message, level: i64) {
+    let guild_id = msg.guild_id.unwrap();
+    let db_guild_id = guild_id.get() as i64;
+
+    match sqlx::query!("SELECT role_id FROM level_rewards WHERE guild_id = ? AND level <= ?", db_guild_id, level).fetch_all(database).await {
+        Ok(rewards) => {
+            let roles = msg.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+
+            for role_id in rewards.into_iter().map(|row| RoleId::new(row.role_id as u64)).filter(|role_id| !roles.contains(role_id)) {
+                if let Err(why) = ctx.http.add_member_role(guild_id, msg.author.id, role_id, Some("Level reward")).await {
+                    warn!("Couldn't give level reward {role_id} to user {} in guild {guild_id}: {why}", msg.author.id);
+                }
+            }
+        }
+        Err(why) => error!("Failed to fetch level rewards of guild {guild_id}: {why}")
+    }
+
+    let settings = match sqlx::query!(
+        "SELECT level_up_announcements, level_up_channel_id, level_up_message FROM guild_settings WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(database).await {
+        Ok(Some(settings)) if settings.level_up_announcements != 0 => settings,
+        Ok(_) => return,
+        Err(why) => {
+            error!("Failed to fetch level-up settings of guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    let template = settings.level_up_message.unwrap_or_else(|| DEFAULT_LEVEL_UP.to_string());
+    let context = message_context(ctx, msg).await.value("level", level);
+    let content = render_template(&template, &context).unwrap_or(template);
+
+    let channel_id = settings.level_up_channel_id.map_or(msg.channel_id, |channel_id| ChannelId::new(channel_id as u64));
+
+    // level-ups only ping the member who levelled up
+    let builder = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().users([msg.author.id]));
+
+    if let Err(why) = channel_id.send_message(ctx, builder).await {
+        warn!("Couldn't announce a level-up in channel {channel_id}: {why}");
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serenity::model::channel::Message;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::utilities::automod::{AutomodAction, edited_message, enforce, parse_ids};
+use crate::utilities::global_data::{DatabaseConnectionContainer, LinksContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bhttps?://([^\s/?#<>:]+)").expect("the link pattern is valid")
+});
+
+static INVITE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:discord\.gg|discord(?:app)?\.com/invite)/([a-z0-9-]+)").expect("the invite pattern is valid")
+});
+
+/// Pulls the code out of an invite link, or returns what it was given if it's already a code.
+pub fn invite_code(invite: &str) -> Option<String> {
+    let code = INVITE_LINK.captures(invite).map_or(invite, |captures| captures.get(1).map_or(invite, |code| code.as_str()));
+
+    (!code.is_empty() && code.chars().all(|character| character.is_ascii_alphanumeric() || character == '-')).then(|| code.to_string())
+}
+
+/// Lowercases a domain and strips what's commonly pasted around it, like the scheme and `www.`.
+pub fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim().to_lowercase();
+    let domain = domain.trim_start_matches("https://").trim_start_matches("http://").trim_start_matches("www.");
+    let domain = domain.split(['/', '?', '#', ':']).next().unwrap_or_default().trim_end_matches('.');
+
+    (domain.contains('.') && !domain.starts_with('.')).then(|| domain.to_string())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    Invite,
+    BlockedDomain,
+    OutsideLinkChannels
+}
+
+impl Violation {
+    fn reason(self) -> &'static str {
+        match self {
+            Violation::Invite => "Posted an invite link",
+            Violation::BlockedDomain => "Posted a link to a blocked website",
+            Violation::OutsideLinkChannels => "Posted a link outside the channels links are allowed in"
+        }
+    }
+}
+
+pub struct LinkSettings {
+    block_invites: bool,
+    allowed_invites: Vec<String>,
+    blocked_domains: Vec<String>,
+    link_channels: Vec<u64>,
+    action: AutomodAction
+}
+
+impl LinkSettings {
+    fn violation(&self, channel_id: ChannelId, content: &str) -> Option<Violation> {
+        if self.block_invites {
+            let blocked_invite = INVITE_LINK.captures_iter(content)
+                .filter_map(|captures| captures.get(1))
+                .any(|code| !self.allowed_invites.iter().any(|allowed| allowed == code.as_str()));
+
+            if blocked_invite {
+                return Some(Violation::Invite);
+            }
+        }
+
+        let mut hosts = LINK.captures_iter(content).filter_map(|captures| captures.get(1)).map(|host| host.as_str().to_lowercase()).peekable();
+
+        if hosts.peek().is_some() && !self.link_channels.is_empty() && !self.link_channels.contains(&channel_id.get()) {
+            return Some(Violation::OutsideLinkChannels);
+        }
+
+        // subdomains of a blocked domain are blocked too
+        let blocked = hosts.any(|host| self.blocked_domains.iter().any(|domain| {
+            host == *domain || host.strip_suffix(domain.as_str()).is_some_and(|subdomain| subdomain.ends_with('.'))
+        }));
+
+        blocked.then_some(Violation::BlockedDomain)
+    }
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect()
+}
+
+fn build_settings(block_invites: i64, allowed_invites: &str, blocked_domains: &str, link_channels: &str, action: &str) -> LinkSettings {
+    LinkSettings {
+        block_invites: block_invites != 0,
+        allowed_invites: split_list(allowed_invites),
+        blocked_domains: split_list(blocked_domains),
+        link_channels: parse_ids(link_channels),
+        action: AutomodAction::parse(action).unwrap_or(AutomodAction::Warn)
+    }
+}
+
+/// Every guild's link settings, keyed by guild ID.
+pub async fn load_link_settings(database: &SqlitePool) -> Result<HashMap<u64, LinkSettings>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT guild_id, block_invites, allowed_invites, blocked_domains, link_channels, action FROM link_settings")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter()
+        .map(|row| (row.guild_id as u64, build_settings(row.block_invites, &row.allowed_invites, &row.blocked_domains, &row.link_channels, &row.action)))
+        .collect())
+}
+
+/// Reloads one guild's link settings from the database after they've been changed.
+pub async fn reload_link_settings(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let cache = get_data::<LinksContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT block_invites, allowed_invites, blocked_domains, link_channels, action FROM link_settings WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(&database).await?;
+
+    let mut cache = cache.write().await;
+
+    match row {
+        Some(row) => cache.insert(guild_id.get(), build_settings(row.block_invites, &row.allowed_invites, &row.blocked_domains, &row.link_channels, &row.action)),
+        None => cache.remove(&guild_id.get())
+    };
+
+    Ok(())
+}
+
+/// What a message's content breaks in its guild's link settings, with the guild's action.
+async fn link_violation(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, content: &str) -> Option<(Violation, AutomodAction)> {
+    let cache = match get_data::<LinksContainer>(ctx).await {
+        Ok(cache) => cache,
+        Err(why) => {
+            error!("Couldn't read the link settings of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    let cache = cache.read().await;
+    let settings = cache.get(&guild_id.get())?;
+
+    settings.violation(channel_id, content).map(|violation| (violation, settings.action))
+}
+
+/// Checks a new message against its guild's link settings. A message with a blocked invite or
+/// link is removed and its author is acted on. Returns whether it was.
+pub async fn handle_links(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let Some((violation, action)) = link_violation(ctx, guild_id, msg.channel_id, &msg.content).await else {
+        return false;
+    };
+
+    enforce(ctx, guild_id, msg, action, "Link control", violation.reason()).await;
+
+    true
+}
+
+/// Checks an edited message against its guild's link settings, so links can't be edited in after
+/// the message was sent. Returns whether it was removed.
+pub async fn handle_links_edit(ctx: &Context, new: Option<&Message>, event: &MessageUpdateEvent) -> bool {
+    // updates without content are embeds being resolved, not edits
+    let (Some(guild_id), Some(content)) = (event.guild_id, event.content.as_deref()) else {
+        return false;
+    };
+
+    if event.author.as_ref().is_some_and(|author| author.bot) {
+        return false;
+    }
+
+    let Some((violation, action)) = link_violation(ctx, guild_id, event.channel_id, content).await else {
+        return false;
+    };
+
+    let Some(msg) = edited_message(ctx, new, event).await else {
+        return false;
+    };
+
+    if msg.author.bot {
+        return false;
+    }
+
+    enforce(ctx, guild_id, &msg, action, "Link control", violation.reason()).await;
+
+    true
+}
@@ -0,0 +1,95 @@
+use serenity::framework::standard::CommandError;
+use serenity::model::channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::model::permissions::Permissions;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+
+/// What @everyone loses in a locked channel.
+const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::CREATE_PRIVATE_THREADS)
+    .union(Permissions::ADD_REACTIONS);
+
+/// Whether channels of a kind can be locked and slowed down.
+pub fn is_lockable(kind: ChannelType) -> bool {
+    matches!(kind, ChannelType::Text | ChannelType::News | ChannelType::Forum | ChannelType::Voice | ChannelType::Stage)
+}
+
+/// Stops @everyone from talking in a channel, remembering its overwrite so `unlock_channel` can put
+/// it back. Returns `false` if the channel is already locked.
+pub async fn lock_channel(ctx: &Context, database: &SqlitePool, guild_id: GuildId, channel_id: ChannelId, lockdown: bool) -> Result<bool, CommandError> {
+    let everyone = PermissionOverwriteType::Role(RoleId::new(guild_id.get()));
+
+    let previous = guild_id.to_guild_cached(&ctx.cache)
+        .and_then(|guild| guild.channels.get(&channel_id)?.permission_overwrites.iter().find(|overwrite| overwrite.kind == everyone).cloned());
+
+    let (db_channel_id, db_guild_id) = (channel_id.get() as i64, guild_id.get() as i64);
+    let previous_allow = previous.as_ref().map(|overwrite| overwrite.allow.bits() as i64);
+    let previous_deny = previous.as_ref().map(|overwrite| overwrite.deny.bits() as i64);
+
+    // an existing row means the channel is locked, and has to keep its original overwrite
+    let inserted = sqlx::query!(
+        "INSERT INTO locked_channels (channel_id, guild_id, previous_allow, previous_deny, lockdown) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (channel_id) DO NOTHING",
+        db_channel_id,
+        db_guild_id,
+        previous_allow,
+        previous_deny,
+        lockdown
+    ).execute(database).await?.rows_affected();
+
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    let (allow, deny) = previous.map_or((Permissions::empty(), Permissions::empty()), |overwrite| (overwrite.allow, overwrite.deny));
+
+    let locked = PermissionOverwrite {
+        allow: allow.difference(LOCKED_PERMISSIONS),
+        deny: deny.union(LOCKED_PERMISSIONS),
+        kind: everyone
+    };
+
+    if let Err(why) = channel_id.create_permission(ctx, locked).await {
+        sqlx::query!("DELETE FROM locked_channels WHERE channel_id = ?", db_channel_id).execute(database).await?;
+        return Err(why.into());
+    }
+
+    Ok(true)
+}
+
+/// Gives a locked channel back the @everyone overwrite it had before it was locked. Returns
+/// `false` if the channel isn't locked.
+pub async fn unlock_channel(ctx: &Context, database: &SqlitePool, channel_id: ChannelId) -> Result<bool, CommandError> {
+    let db_channel_id = channel_id.get() as i64;
+
+    let locked = sqlx::query!(
+        "SELECT guild_id, previous_allow, previous_deny FROM locked_channels WHERE channel_id = ?",
+        db_channel_id
+    ).fetch_optional(database).await?;
+
+    let Some(locked) = locked else {
+        return Ok(false);
+    };
+
+    let everyone = PermissionOverwriteType::Role(RoleId::new(locked.guild_id as u64));
+
+    match (locked.previous_allow, locked.previous_deny) {
+        (Some(allow), Some(deny)) => {
+            let previous = PermissionOverwrite {
+                allow: Permissions::from_bits_truncate(allow as u64),
+                deny: Permissions::from_bits_truncate(deny as u64),
+                kind: everyone
+            };
+
+            channel_id.create_permission(ctx, previous).await?;
+        }
+        _ => channel_id.delete_permission(ctx, everyone).await?
+    }
+
+    sqlx::query!("DELETE FROM locked_channels WHERE channel_id = ?", db_channel_id).execute(database).await?;
+
+    Ok(true)
+}
@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::env;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use serenity::prelude::Context;
+
+use crate::utilities::errors::{BotError, get_data};
+use crate::utilities::global_data::{LookupCacheContainer, ReqwestClientContainer};
+
+/// How long lookup results are kept, so repeated lookups don't count against
+/// the services' rate limits.
+const CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Most results kept per service before the oldest are dropped.
+const MAX_CACHED: usize = 500;
+
+/// Results kept until they're older than the cache's lifetime.
+struct TtlCache<K, V> {
+    entries: HashMap<K, (Instant, V)>
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    fn new() -> Self {
+        TtlCache { entries: HashMap::new() }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        self.entries.get(key)
+            .filter(|(cached_at, _)| cached_at.elapsed() < CACHE_TTL)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() >= MAX_CACHED {
+            self.entries.retain(|_, (cached_at, _)| cached_at.elapsed() < CACHE_TTL);
+        }
+
+        if self.entries.len() >= MAX_CACHED {
+            let oldest = self.entries.iter().min_by_key(|(_, (cached_at, _))| *cached_at).map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(key, (Instant::now(), value));
+    }
+}
+
+/// Recent results from the dictionary, Urban Dictionary, translation, Wikipedia and search
+/// services.
+pub struct LookupCache {
+    definitions: TtlCache<String, Option<Vec<DictionaryEntry>>>,
+    urban: TtlCache<String, Vec<UrbanDefinition>>,
+    translations: TtlCache<(String, String), Translation>,
+    articles: TtlCache<String, Option<WikiSummary>>,
+    searches: TtlCache<(String, SafeSearch), Vec<SearchResult>>
+}
+
+impl Default for LookupCache {
+    fn default() -> Self {
+        LookupCache {
+            definitions: TtlCache::new(),
+            urban: TtlCache::new(),
+            translations: TtlCache::new(),
+            articles: TtlCache::new(),
+            searches: TtlCache::new()
+        }
+    }
+}
+
+pub enum LookupError {
+    /// The service needs configuring before it can be used.
+    NotConfigured,
+    /// The service refused the request, such as for an unknown language.
+    Rejected,
+    Request(reqwest::Error),
+    Data(BotError)
+}
+
+impl From<reqwest::Error> for LookupError {
+    fn from(error: reqwest::Error) -> Self {
+        LookupError::Request(error)
+    }
+}
+
+impl From<BotError> for LookupError {
+    fn from(error: BotError) -> Self {
+        LookupError::Data(error)
+    }
+}
+
+/// A word from the Free Dictionary API, see <https://dictionaryapi.dev>.
+#[derive(Clone, Deserialize)]
+pub struct DictionaryEntry {
+    pub word: String,
+    pub phonetic: Option<String>,
+    #[serde(default)]
+    pub meanings: Vec<Meaning>
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meaning {
+    pub part_of_speech: String,
+    #[serde(default)]
+    pub definitions: Vec<Definition>
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Definition {
+    pub definition: String,
+    pub example: Option<String>
+}
+
+#[derive(Deserialize)]
+struct UrbanResponse {
+    #[serde(default)]
+    list: Vec<UrbanDefinition>
+}
+
+#[derive(Clone, Deserialize)]
+pub struct UrbanDefinition {
+    pub word: String,
+    pub definition: String,
+    #[serde(default)]
+    pub example: String,
+    pub author: String,
+    pub permalink: String,
+    pub thumbs_up: u64,
+    pub thumbs_down: u64
+}
+
+#[derive(Clone)]
+pub struct Translation {
+    pub text: String,
+    /// The language the text was detected to be in, when the service says.
+    pub source_language: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LibreTranslateResponse {
+    translated_text: String,
+    detected_language: Option<LibreTranslateLanguage>
+}
+
+#[derive(Deserialize)]
+struct LibreTranslateLanguage {
+    language: String
+}
+
+#[derive(Deserialize)]
+struct DeeplResponse {
+    translations: Vec<DeeplTranslation>
+}
+
+#[derive(Deserialize)]
+struct DeeplTranslation {
+    detected_source_language: Option<String>,
+    text: String
+}
+
+/// How strictly explicit results are left out of searches.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SafeSearch {
+    Strict,
+    Moderate,
+    Off
+}
+
+/// The summary of a Wikipedia article.
+#[derive(Clone, Deserialize)]
+pub struct WikiSummary {
+    pub title: String,
+    pub extract: String,
+    pub thumbnail: Option<WikiImage>,
+    pub content_urls: WikiUrls,
+    #[serde(rename = "type")]
+    pub kind: String
+}
+
+#[derive(Clone, Deserialize)]
+pub struct WikiImage {
+    pub source: String
+}
+
+#[derive(Clone, Deserialize)]
+pub struct WikiUrls {
+    pub desktop: WikiUrl
+}
+
+#[derive(Clone, Deserialize)]
+pub struct WikiUrl {
+    pub page: String
+}
+
+/// A web search result.
+#[derive(Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: Option<String>
+}
+
+/// An instant answer from DuckDuckGo, see <https://duckduckgo.com/api>.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DuckDuckGoResponse {
+    #[serde(default)]
+    heading: String,
+    #[serde(default)]
+    abstract_text: String,
+    #[serde(rename = "AbstractURL", default)]
+    abstract_url: String,
+    #[serde(default)]
+    answer: String,
+    #[serde(default)]
+    results: Vec<DuckDuckGoTopic>,
+    #[serde(default)]
+    related_topics: Vec<DuckDuckGoTopic>
+}
+
+/// A related link, or a group of them.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DuckDuckGoTopic {
+    text: Option<String>,
+    #[serde(rename = "FirstURL")]
+    first_url: Option<String>,
+    #[serde(default)]
+    topics: Vec<DuckDuckGoTopic>
+}
+
+impl DuckDuckGoTopic {
+    fn collect_into(self, results: &mut Vec<SearchResult>) {
+        if let (Some(text), Some(url)) = (self.text, self.first_url) {
+            results.push(SearchResult { title: text, url, snippet: None });
+        }
+
+        for topic in self.topics {
+            topic.collect_into(results);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    title: String,
+    url: String,
+    content: Option<String>
+}
+
+/// Looks up an English word's definitions, or `None` if the dictionary doesn't know it.
+pub async fn define(ctx: &Context, word: &str) -> Result<Option<Vec<DictionaryEntry>>, LookupError> {
+    let cache = get_data::<LookupCacheContainer>(ctx).await?;
+    let key = word.to_lowercase();
+
+    if let Some(entries) = cache.lock().await.definitions.get(&key) {
+        return Ok(entries);
+    }
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+
+    let mut url = reqwest::Url::parse("https://api.dictionaryapi.dev/api/v2/entries/en/").expect("the dictionary URL is valid");
+    url.path_segments_mut().expect("the dictionary URL has a path").pop_if_empty().push(&key);
+
+    let response = client.get(url).send().await?;
+
+    let entries = if response.status() == StatusCode::NOT_FOUND {
+        None
+    } else {
+        Some(response.error_for_status()?.json::<Vec<DictionaryEntry>>().await?)
+    };
+
+    cache.lock().await.definitions.insert(key, entries.clone());
+
+    Ok(entries)
+}
+
+/// Looks up a term on Urban Dictionary, with the best voted definitions first.
+pub async fn urban(ctx: &Context, term: &str) -> Result<Vec<UrbanDefinition>, LookupError> {
+    let cache = get_data::<LookupCacheContainer>(ctx).await?;
+    let key = term.to_lowercase();
+
+    if let Some(definitions) = cache.lock().await.urban.get(&key) {
+        return Ok(definitions);
+    }
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+
+    let response = client.get("https://api.urbandictionary.com/v0/define")
+        .query(&[("term", &key)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<UrbanResponse>()
+        .await?;
+
+    cache.lock().await.urban.insert(key, response.list.clone());
+
+    Ok(response.list)
+}
+
+/// Translates text into a language, by its code, with the service set by `TRANSLATE_BACKEND`:
+/// LibreTranslate at `TRANSLATE_URL` by default, or `deepl`. `TRANSLATE_API_KEY` is sent along
+/// when set, and is required for DeepL.
+pub async fn translate(ctx: &Context, language: &str, text: &str) -> Result<Translation, LookupError> {
+    let cache = get_data::<LookupCacheContainer>(ctx).await?;
+    let key = (language.to_lowercase(), text.to_string());
+
+    if let Some(translation) = cache.lock().await.translations.get(&key) {
+        return Ok(translation);
+    }
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+    let api_key = env::var("TRANSLATE_API_KEY").ok();
+    let deepl = env::var("TRANSLATE_BACKEND").is_ok_and(|backend| backend.eq_ignore_ascii_case("deepl"));
+
+    let request = if deepl {
+        let api_key = api_key.ok_or(LookupError::NotConfigured)?;
+        let url = env::var("TRANSLATE_URL").unwrap_or_else(|_| "https://api-free.deepl.com".to_string());
+
+        client.post(format!("{}/v2/translate", url.trim_end_matches('/')))
+            .header("Authorization", format!("DeepL-Auth-Key {api_key}"))
+            .json(&json!({ "text": [text], "target_lang": language.to_uppercase() }))
+    } else {
+        let url = env::var("TRANSLATE_URL").map_err(|_| LookupError::NotConfigured)?;
+        let mut body = json!({ "q": text, "source": "auto", "target": language.to_lowercase(), "format": "text" });
+
+        if let Some(api_key) = api_key {
+            body["api_key"] = json!(api_key);
+        }
+
+        client.post(format!("{}/translate", url.trim_end_matches('/'))).json(&body)
+    };
+
+    let response = request.send().await?;
+
+    if response.status() == StatusCode::BAD_REQUEST {
+        return Err(LookupError::Rejected);
+    }
+
+    let response = response.error_for_status()?;
+
+    let translation = if deepl {
+        let translation = response.json::<DeeplResponse>().await?.translations.into_iter().next().ok_or(LookupError::Rejected)?;
+        Translation { text: translation.text, source_language: translation.detected_source_language.map(|language| language.to_lowercase()) }
+    } else {
+        let translation = response.json::<LibreTranslateResponse>().await?;
+        Translation { text: translation.translated_text, source_language: translation.detected_language.map(|language| language.language) }
+    };
+
+    cache.lock().await.translations.insert(key, translation.clone());
+
+    Ok(translation)
+}
+
+/// Urban Dictionary marks links to other definitions with brackets, which are dropped.
+pub fn strip_urban_links(text: &str) -> String {
+    text.replace(['[', ']'], "")
+}
+
+/// Finds the Wikipedia article best matching a query and fetches its summary.
+pub async fn wiki(ctx: &Context, query: &str) -> Result<Option<WikiSummary>, LookupError> {
+    let cache = get_data::<LookupCacheContainer>(ctx).await?;
+    let key = query.to_lowercase();
+
+    if let Some(summary) = cache.lock().await.articles.get(&key) {
+        return Ok(summary);
+    }
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+
+    // opensearch answers with the query, then the titles, descriptions and links it found
+    let (_, titles, _, _) = client.get("https://en.wikipedia.org/w/api.php")
+        .query(&[("action", "opensearch"), ("search", query), ("limit", "1"), ("namespace", "0"), ("format", "json")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<(String, Vec<String>, Vec<String>, Vec<String>)>()
+        .await?;
+
+    let summary = match titles.first() {
+        Some(title) => {
+            let mut url = reqwest::Url::parse("https://en.wikipedia.org/api/rest_v1/page/summary/").expect("the Wikipedia URL is valid");
+            url.path_segments_mut().expect("the Wikipedia URL has a path").pop_if_empty().push(title);
+
+            let response = client.get(url).send().await?;
+
+            if response.status() == StatusCode::NOT_FOUND {
+                None
+            } else {
+                Some(response.error_for_status()?.json::<WikiSummary>().await?)
+            }
+        }
+        None => None
+    };
+
+    cache.lock().await.articles.insert(key, summary.clone());
+
+    Ok(summary)
+}
+
+/// Searches the web with the engine set by `SEARCH_BACKEND`: DuckDuckGo's instant answers by
+/// default, or `searxng` for the SearxNG instance at `SEARXNG_URL`, which has to have its JSON
+/// format enabled.
+pub async fn search(ctx: &Context, query: &str, safe_search: SafeSearch) -> Result<Vec<SearchResult>, LookupError> {
+    let cache = get_data::<LookupCacheContainer>(ctx).await?;
+    let key = (query.to_lowercase(), safe_search);
+
+    if let Some(results) = cache.lock().await.searches.get(&key) {
+        return Ok(results);
+    }
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await?;
+    let searxng = env::var("SEARCH_BACKEND").is_ok_and(|backend| backend.eq_ignore_ascii_case("searxng"));
+
+    let results = if searxng {
+        let url = env::var("SEARXNG_URL").map_err(|_| LookupError::NotConfigured)?;
+
+        let safe_search = match safe_search {
+            SafeSearch::Strict => "2",
+            SafeSearch::Moderate => "1",
+            SafeSearch::Off => "0"
+        };
+
+        client.get(format!("{}/search", url.trim_end_matches('/')))
+            .query(&[("q", query), ("format", "json"), ("safesearch", safe_search)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SearxngResponse>()
+            .await?
+            .results
+            .into_iter()
+            .map(|result| SearchResult { title: result.title, url: result.url, snippet: result.content.filter(|content| !content.is_empty()) })
+            .collect()
+    } else {
+        let safe_search = match safe_search {
+            SafeSearch::Strict => "1",
+            SafeSearch::Moderate => "-1",
+            SafeSearch::Off => "-2"
+        };
+
+        let response = client.get("https://api.duckduckgo.com/")
+            .query(&[("q", query), ("format", "json"), ("no_html", "1"), ("skip_disambig", "1"), ("kp", safe_search)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DuckDuckGoResponse>()
+            .await?;
+
+        let mut results = Vec::new();
+
+        if !response.answer.is_empty() {
+            let url = reqwest::Url::parse_with_params("https://duckduckgo.com/", &[("q", query)]).map(String::from).unwrap_or_default();
+            results.push(SearchResult { title: "Answer".to_string(), url, snippet: Some(response.answer) });
+        }
+
+        if !response.abstract_url.is_empty() {
+            let title = if response.heading.is_empty() { response.abstract_url.clone() } else { response.heading };
+            results.push(SearchResult { title, url: response.abstract_url, snippet: Some(response.abstract_text).filter(|text| !text.is_empty()) });
+        }
+
+        for topic in response.results.into_iter().chain(response.related_topics) {
+            topic.collect_into(&mut results);
+        }
+
+        results
+    };
+
+    cache.lock().await.searches.insert(key, results.clone());
+
+    Ok(results)
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use serenity::model::channel::{Attachment, Message};
+use serenity::model::id::ChannelId;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::utilities::automod::{AutomodAction, enforce};
+use crate::utilities::global_data::{DatabaseConnectionContainer, MediaRulesContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// File extensions treated as images when Discord doesn't say what an attachment is.
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "avif"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MediaMode {
+    MediaOnly,
+    ImagesOnly,
+    NoAttachments
+}
+
+impl MediaMode {
+    pub const ALL: [MediaMode; 3] = [MediaMode::MediaOnly, MediaMode::ImagesOnly, MediaMode::NoAttachments];
+
+    pub fn parse(mode: &str) -> Option<MediaMode> {
+        MediaMode::ALL.into_iter().find(|candidate| candidate.as_str() == mode.to_lowercase())
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaMode::MediaOnly => "media-only",
+            MediaMode::ImagesOnly => "images-only",
+            MediaMode::NoAttachments => "no-attachments"
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            MediaMode::MediaOnly => "Only messages with an attachment or a link",
+            MediaMode::ImagesOnly => "Only messages with images attached",
+            MediaMode::NoAttachments => "No attachments"
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MediaRule {
+    mode: Option<MediaMode>,
+    max_file_size: Option<u64>
+}
+
+impl MediaRule {
+    /// Why a message isn't allowed in the channel, if it isn't.
+    fn violation(&self, msg: &Message) -> Option<String> {
+        if let Some(max_file_size) = self.max_file_size {
+            if msg.attachments.iter().any(|attachment| u64::from(attachment.size) > max_file_size) {
+                return Some(format!("Attachments here can be at most {}", format_size(max_file_size)));
+            }
+        }
+
+        let allowed = match self.mode {
+            None => true,
+            Some(MediaMode::MediaOnly) => !msg.attachments.is_empty() || msg.content.contains("https://") || msg.content.contains("http://"),
+            Some(MediaMode::ImagesOnly) => !msg.attachments.is_empty() && msg.attachments.iter().all(is_image),
+            Some(MediaMode::NoAttachments) => msg.attachments.is_empty()
+        };
+
+        (!allowed).then(|| match self.mode {
+            Some(MediaMode::MediaOnly) => "Only attachments and links can be posted here".to_string(),
+            Some(MediaMode::ImagesOnly) => "Only images can be posted here".to_string(),
+            _ => "Attachments aren't allowed here".to_string()
+        })
+    }
+}
+
+fn is_image(attachment: &Attachment) -> bool {
+    match attachment.content_type.as_deref() {
+        Some(content_type) => content_type.starts_with("image/"),
+        None => attachment.filename.rsplit_once('.').is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+    }
+}
+
+/// Formats a number of bytes in the largest unit it reaches, e.g. `8 MB` or `500 KB`.
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1_000_000 {
+        format!("{} MB", (bytes as f64 / 100_000.0).round() / 10.0)
+    } else {
+        format!("{} KB", bytes.div_ceil(1_000))
+    }
+}
+
+fn build_rule(mode: Option<&str>, max_file_size: Option<i64>) -> MediaRule {
+    MediaRule {
+        mode: mode.and_then(MediaMode::parse),
+        max_file_size: max_file_size.map(|size| size.max(0) as u64)
+    }
+}
+
+/// Every channel's media rules, keyed by channel ID.
+pub async fn load_media_rules(database: &SqlitePool) -> Result<HashMap<u64, MediaRule>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT channel_id, mode, max_file_size FROM media_rules")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter()
+        .map(|row| (row.channel_id as u64, build_rule(row.mode.as_deref(), row.max_file_size)))
+        .collect())
+}
+
+/// Reloads one channel's media rules from the database after they've been changed.
+pub async fn reload_media_rules(ctx: &Context, channel_id: ChannelId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let rules = get_data::<MediaRulesContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    let row = sqlx::query!("SELECT mode, max_file_size FROM media_rules WHERE channel_id = ?", db_channel_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let mut rules = rules.write().await;
+
+    match row {
+        Some(row) => rules.insert(channel_id.get(), build_rule(row.mode.as_deref(), row.max_file_size)),
+        None => rules.remove(&channel_id.get())
+    };
+
+    Ok(())
+}
+
+/// Removes a message that breaks its channel's media rules, unless its author can manage
+/// messages there. Returns whether it was removed.
+pub async fn handle_media_rules(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let rules = match get_data::<MediaRulesContainer>(ctx).await {
+        Ok(rules) => rules,
+        Err(why) => {
+            error!("Couldn't check message {} against media rules: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let Some(rule) = rules.read().await.get(&msg.channel_id.get()).copied() else {
+        return false;
+    };
+
+    let Some(reason) = rule.violation(msg) else {
+        return false;
+    };
+
+    // moderators can still talk in channels limited to media
+    let is_moderator = msg.guild(&ctx.cache).is_some_and(|guild| {
+        guild.channels.get(&msg.channel_id).zip(guild.members.get(&msg.author.id))
+            .is_some_and(|(channel, member)| guild.user_permissions_in(channel, member).manage_messages())
+    });
+
+    if is_moderator {
+        return false;
+    }
+
+    enforce(ctx, guild_id, msg, AutomodAction::Delete, "Media rules", &reason).await;
+
+    true
+}
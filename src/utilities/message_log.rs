@@ -0,0 +1,241 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serenity::builder::{CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::{Context, Mentionable};
+use tokio::sync::Mutex;
+use tracing::{warn, error};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::{GuildSettingsContainer, MessageLogContainer};
+use crate::utilities::errors::get_data;
+use crate::utilities::i18n::{guild_language, translate};
+
+/// Most messages kept around so their content can be logged once they're edited or deleted.
+const MAX_CACHED_MESSAGES: usize = 20_000;
+
+/// Discord's limit on the length of an embed field.
+const MAX_FIELD_LENGTH: usize = 1024;
+
+struct CachedMessage {
+    channel_id: ChannelId,
+    author_id: UserId,
+    author_bot: bool,
+    author_tag: String,
+    author_avatar: String,
+    content: String,
+    attachments: Vec<String>
+}
+
+impl CachedMessage {
+    fn from_message(msg: &Message) -> CachedMessage {
+        CachedMessage {
+            channel_id: msg.channel_id,
+            author_id: msg.author.id,
+            author_bot: msg.author.bot,
+            author_tag: msg.author.tag(),
+            author_avatar: msg.author.face(),
+            content: msg.content.clone(),
+            attachments: msg.attachments.iter().map(|attachment| format!("[{}]({})", attachment.filename, attachment.url)).collect()
+        }
+    }
+}
+
+/// Recent messages in guilds with a message log, oldest first, forgetting the oldest once full.
+#[derive(Default)]
+pub struct MessageLogCache {
+    messages: HashMap<MessageId, CachedMessage>,
+    order: VecDeque<MessageId>
+}
+
+impl MessageLogCache {
+    pub fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    fn insert(&mut self, message_id: MessageId, message: CachedMessage) {
+        if self.messages.insert(message_id, message).is_some() {
+            return;
+        }
+
+        self.order.push_back(message_id);
+
+        if self.order.len() > MAX_CACHED_MESSAGES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.messages.remove(&oldest);
+            }
+        }
+    }
+}
+
+async fn log_channel(ctx: &Context, guild_id: GuildId) -> Option<ChannelId> {
+    let guild_settings = match get_data::<GuildSettingsContainer>(ctx).await {
+        Ok(guild_settings) => guild_settings,
+        Err(why) => {
+            error!("Couldn't read the message log channel of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    guild_settings.read(guild_id.get(), |settings| settings.message_log_channel).await
+        .flatten()
+        .map(ChannelId::new)
+}
+
+async fn message_cache(ctx: &Context) -> Option<Arc<Mutex<MessageLogCache>>> {
+    match get_data::<MessageLogContainer>(ctx).await {
+        Ok(cache) => Some(cache),
+        Err(why) => {
+            error!("Couldn't read the message log cache: {why}");
+            None
+        }
+    }
+}
+
+/// Cuts text down to fit in an embed field, showing a placeholder for empty text.
+fn field_text(text: &str) -> String {
+    if text.is_empty() {
+        return "*No text*".to_string();
+    }
+
+    if text.chars().count() <= MAX_FIELD_LENGTH {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(MAX_FIELD_LENGTH - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Remembers a message so it can be logged later, if its guild has a message log. Bots' messages
+/// are remembered too, only so their deletion isn't logged as that of an unknown message.
+pub async fn cache_message(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    if log_channel(ctx, guild_id).await.is_none() {
+        return;
+    }
+
+    if let Some(cache) = message_cache(ctx).await {
+        cache.lock().await.insert(msg.id, CachedMessage::from_message(msg));
+    }
+}
+
+/// Logs an edited message with its content before and after the edit.
+pub async fn log_message_edit(ctx: &Context, event: &MessageUpdateEvent) {
+    // updates without content are embeds loading in, not edits
+    let (Some(guild_id), Some(content)) = (event.guild_id, event.content.as_ref()) else {
+        return;
+    };
+
+    let Some(log_channel_id) = log_channel(ctx, guild_id).await.filter(|channel_id| *channel_id != event.channel_id) else {
+        return;
+    };
+
+    if event.author.as_ref().is_some_and(|author| author.bot) {
+        return;
+    }
+
+    let (before, author_id, author_tag, author_avatar) = {
+        let Some(cache) = message_cache(ctx).await else {
+            return;
+        };
+        let mut cache = cache.lock().await;
+
+        match cache.messages.get_mut(&event.id) {
+            Some(cached) => {
+                let before = std::mem::replace(&mut cached.content, content.clone());
+                (Some(before), cached.author_id, cached.author_tag.clone(), cached.author_avatar.clone())
+            }
+            None => {
+                let Some(author) = &event.author else {
+                    return;
+                };
+
+                (None, author.id, author.tag(), author.face())
+            }
+        }
+    };
+
+    if before.as_ref() == Some(content) {
+        return;
+    }
+
+    let link = event.id.link(event.channel_id, Some(guild_id));
+    let language = guild_language(ctx, Some(guild_id)).await;
+
+    let description = translate(&language, "log.message_edited_description", &[
+        ("author", &author_id.mention().to_string()),
+        ("link", &link),
+        ("channel", &event.channel_id.mention().to_string())
+    ]);
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .author(CreateEmbedAuthor::new(author_tag).icon_url(author_avatar))
+        .title(translate(&language, "log.message_edited", &[]))
+        .description(description)
+        .field(translate(&language, "log.before", &[]), before.as_deref().map_or_else(|| translate(&language, "log.not_cached", &[]), field_text), false)
+        .field(translate(&language, "log.after", &[]), field_text(content), false)
+        .footer(CreateEmbedFooter::new(translate(&language, "log.user_id", &[("id", &author_id.to_string())])))
+        .timestamp(Timestamp::now());
+
+    if let Err(why) = log_channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't log an edited message in guild {guild_id}: {why}");
+    }
+}
+
+/// Logs a deleted message with its content and attachments, if it was cached.
+pub async fn log_message_delete(ctx: &Context, channel_id: ChannelId, message_id: MessageId, guild_id: Option<GuildId>) {
+    let Some(guild_id) = guild_id else {
+        return;
+    };
+
+    let Some(cache) = message_cache(ctx).await else {
+        return;
+    };
+
+    let cached = cache.lock().await.messages.remove(&message_id);
+
+    let Some(log_channel_id) = log_channel(ctx, guild_id).await.filter(|log_channel_id| *log_channel_id != channel_id) else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(guild_id)).await;
+
+    let mut embed = branded_embed(ctx, Some(guild_id)).await
+        .title(translate(&language, "log.message_deleted", &[]))
+        .timestamp(Timestamp::now());
+
+    match cached {
+        Some(cached) if cached.author_bot => return,
+        Some(cached) => {
+            let description = translate(&language, "log.message_deleted_description", &[
+                ("author", &cached.author_id.mention().to_string()),
+                ("channel", &cached.channel_id.mention().to_string())
+            ]);
+
+            embed = embed
+                .author(CreateEmbedAuthor::new(cached.author_tag).icon_url(cached.author_avatar))
+                .description(description)
+                .field(translate(&language, "log.content", &[]), field_text(&cached.content), false)
+                .footer(CreateEmbedFooter::new(translate(&language, "log.user_id", &[("id", &cached.author_id.to_string())])));
+
+            if !cached.attachments.is_empty() {
+                embed = embed.field(translate(&language, "log.attachments", &[]), field_text(&cached.attachments.join("\n")), false);
+            }
+        }
+        None => {
+            embed = embed.description(translate(&language, "log.message_deleted_uncached", &[("channel", &channel_id.mention().to_string())]));
+        }
+    }
+
+    if let Err(why) = log_channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't log a deleted message in guild {guild_id}: {why}");
+    }
+}
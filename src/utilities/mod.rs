@@ -1 +1,76 @@
-pub mod global_data;
\ No newline at end of file
+pub mod global_data;
+pub mod premium;
+pub mod votes;
+pub mod bot_lists;
+pub mod parsing;
+pub mod incidents;
+pub mod fuzzy;
+pub mod dispatch;
+pub mod scripting;
+pub mod templates;
+pub mod analytics;
+pub mod charts;
+pub mod counters;
+pub mod autoresponses;
+pub mod scheduler;
+pub mod invocation;
+pub mod moderation;
+pub mod automod;
+pub mod antispam;
+pub mod antiraid;
+pub mod message_log;
+pub mod welcome;
+pub mod autoroles;
+pub mod role_menus;
+pub mod starboard;
+pub mod polls;
+pub mod levels;
+pub mod tickets;
+pub mod modmail;
+pub mod errors;
+pub mod shutdown;
+pub mod cooldowns;
+pub mod command_rules;
+pub mod command_permissions;
+pub mod schema;
+pub mod server_log;
+pub mod voice;
+pub mod invites;
+pub mod afk;
+pub mod sticky;
+pub mod lockdown;
+pub mod nicknames;
+pub mod verification;
+pub mod join_gate;
+pub mod filters;
+pub mod links;
+pub mod media_rules;
+pub mod roles;
+pub mod quarantine;
+pub mod pin_archive;
+pub mod transcripts;
+pub mod pagination;
+pub mod confirmation;
+pub mod arguments;
+pub mod dice;
+pub mod images;
+pub mod trivia;
+pub mod games;
+pub mod word_games;
+pub mod sfx;
+pub mod playback;
+pub mod tts;
+pub mod reddit;
+pub mod weather;
+pub mod lookups;
+pub mod birthdays;
+pub mod timezones;
+pub mod events;
+pub mod chain_games;
+pub mod quotes;
+pub mod reports;
+pub mod mod_notes;
+pub mod watchlist;
+pub mod blacklist;
+pub mod i18n;
+pub mod branding;
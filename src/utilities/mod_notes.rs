@@ -0,0 +1,83 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// Longest note that can be kept about a member.
+pub const MAX_NOTE_LENGTH: usize = 1000;
+
+/// A note staff keep about a member.
+pub struct ModNote {
+    pub id: i64,
+    pub moderator_id: UserId,
+    pub content: String,
+    pub created_at: String
+}
+
+/// The notes kept about a user, newest first.
+pub async fn user_notes(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<Vec<ModNote>, sqlx::Error> {
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let rows = sqlx::query!(
+        "SELECT id AS \"id!\", moderator_id, content, created_at FROM mod_notes WHERE guild_id = ? AND user_id = ? ORDER BY id DESC",
+        guild_id,
+        user_id
+    ).fetch_all(database).await?;
+
+    Ok(rows.into_iter()
+        .map(|row| ModNote { id: row.id, moderator_id: UserId::new(row.moderator_id as u64), content: row.content, created_at: row.created_at })
+        .collect())
+}
+
+/// A short line for a note, cut down so several fit in a message or embed field.
+pub fn note_summary(note: &ModNote) -> String {
+    let mut content = note.content.replace('\n', " ");
+
+    if content.chars().count() > 200 {
+        content = content.chars().take(197).collect::<String>() + "...";
+    }
+
+    format!("**#{}** {content} - by {}", note.id, note.moderator_id.mention())
+}
+
+/// Lets a moderator know about the notes kept on the member they're about to act on, if any.
+pub async fn show_notes(ctx: &Context, msg: &Message, target_id: UserId) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't show the notes on user {target_id}: {why}");
+            return;
+        }
+    };
+
+    let notes = match user_notes(&database, msg.guild_id.unwrap(), target_id).await {
+        Ok(notes) => notes,
+        Err(why) => {
+            warn!("Couldn't fetch the notes on user {target_id}: {why}");
+            return;
+        }
+    };
+
+    let Some(latest) = notes.first() else {
+        return;
+    };
+
+    let count = match notes.len() {
+        1 => "a staff note".to_string(),
+        count => format!("{count} staff notes")
+    };
+
+    let content = format!("📝 {} has {count}, the latest being:\n{}\nSee them all with `note list`.", target_id.mention(), note_summary(latest));
+
+    // the member shouldn't be pinged about notes they can't see
+    let builder = CreateMessage::new().content(content).allowed_mentions(CreateAllowedMentions::new());
+
+    if let Err(why) = msg.channel_id.send_message(ctx, builder).await {
+        warn!("Couldn't show the notes on user {target_id} in channel {}: {why}", msg.channel_id);
+    }
+}
@@ -0,0 +1,627 @@
+use chrono::{Duration, Utc};
+use serenity::builder::{CreateEmbed, CreateMessage, EditMember, EditMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use serenity::model::permissions::Permissions;
+use serenity::model::user::User;
+use serenity::model::Timestamp;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::{DatabaseConnectionContainer, get_or_create_guild_settings};
+use crate::utilities::scheduler::{Job, When, schedule_job};
+use crate::utilities::errors::get_data;
+
+/// Longest reason Discord accepts for the audit log.
+const MAX_AUDIT_REASON_LENGTH: usize = 512;
+
+/// Days of messages removed by a softban.
+const SOFTBAN_DELETE_DAYS: u8 = 7;
+
+/// Longest timeout Discord allows, also used for mutes without a duration.
+pub const MAX_TIMEOUT: Duration = Duration::days(28);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ModAction {
+    Kick,
+    Ban,
+    Tempban,
+    Unban,
+    Softban,
+    Mute,
+    Tempmute,
+    Unmute,
+    Warn,
+    Slowmode,
+    Lock,
+    Unlock,
+    Lockdown,
+    EndLockdown,
+    Decancer,
+    Forcenick,
+    Quarantine,
+    Unquarantine
+}
+
+impl ModAction {
+    pub const ALL: [ModAction; 18] = [
+        ModAction::Kick,
+        ModAction::Ban,
+        ModAction::Tempban,
+        ModAction::Unban,
+        ModAction::Softban,
+        ModAction::Mute,
+        ModAction::Tempmute,
+        ModAction::Unmute,
+        ModAction::Warn,
+        ModAction::Slowmode,
+        ModAction::Lock,
+        ModAction::Unlock,
+        ModAction::Lockdown,
+        ModAction::EndLockdown,
+        ModAction::Decancer,
+        ModAction::Forcenick,
+        ModAction::Quarantine,
+        ModAction::Unquarantine
+    ];
+
+    pub fn parse(action: &str) -> Option<ModAction> {
+        ModAction::ALL.into_iter().find(|candidate| candidate.as_str() == action)
+    }
+
+    /// Name stored in `mod_actions.action`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModAction::Kick => "kick",
+            ModAction::Ban => "ban",
+            ModAction::Tempban => "tempban",
+            ModAction::Unban => "unban",
+            ModAction::Softban => "softban",
+            ModAction::Mute => "mute",
+            ModAction::Tempmute => "tempmute",
+            ModAction::Unmute => "unmute",
+            ModAction::Warn => "warn",
+            ModAction::Slowmode => "slowmode",
+            ModAction::Lock => "lock",
+            ModAction::Unlock => "unlock",
+            ModAction::Lockdown => "lockdown",
+            ModAction::EndLockdown => "lockdown_end",
+            ModAction::Decancer => "decancer",
+            ModAction::Forcenick => "forcenick",
+            ModAction::Quarantine => "quarantine",
+            ModAction::Unquarantine => "unquarantine"
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            ModAction::Kick => "kicked",
+            ModAction::Ban | ModAction::Tempban => "banned",
+            ModAction::Unban => "unbanned",
+            ModAction::Softban => "softbanned",
+            ModAction::Mute | ModAction::Tempmute => "muted",
+            ModAction::Unmute => "unmuted",
+            ModAction::Warn => "warned",
+            ModAction::Slowmode => "slowed down",
+            ModAction::Lock | ModAction::Lockdown => "locked",
+            ModAction::Unlock | ModAction::EndLockdown => "unlocked",
+            ModAction::Decancer | ModAction::Forcenick => "renamed",
+            ModAction::Quarantine => "quarantined",
+            ModAction::Unquarantine => "released from quarantine"
+        }
+    }
+
+    /// Name shown on the action's case.
+    pub fn title(self) -> &'static str {
+        match self {
+            ModAction::Kick => "Kick",
+            ModAction::Ban => "Ban",
+            ModAction::Tempban => "Temporary ban",
+            ModAction::Unban => "Unban",
+            ModAction::Softban => "Softban",
+            ModAction::Mute => "Mute",
+            ModAction::Tempmute => "Temporary mute",
+            ModAction::Unmute => "Unmute",
+            ModAction::Warn => "Warning",
+            ModAction::Slowmode => "Slowmode",
+            ModAction::Lock => "Lock",
+            ModAction::Unlock => "Unlock",
+            ModAction::Lockdown => "Lockdown",
+            ModAction::EndLockdown => "Lockdown ended",
+            ModAction::Decancer => "Decancer",
+            ModAction::Forcenick => "Forced nickname",
+            ModAction::Quarantine => "Quarantine",
+            ModAction::Unquarantine => "Quarantine ended"
+        }
+    }
+
+    fn preposition(self) -> &'static str {
+        match self {
+            ModAction::Mute | ModAction::Tempmute | ModAction::Unmute | ModAction::Warn | ModAction::Decancer | ModAction::Forcenick | ModAction::Quarantine | ModAction::Unquarantine => "in",
+            _ => "from"
+        }
+    }
+
+}
+
+/// How a guild mutes members, from its `mute_type` and `mute_role` settings.
+#[derive(Clone, Copy)]
+pub enum MuteType {
+    Timeout,
+    Role(RoleId)
+}
+
+impl MuteType {
+    /// The guild's mute type. Role mutes without a role configured can't be applied.
+    pub async fn for_guild(ctx: &Context, guild_id: GuildId) -> Result<MuteType, String> {
+        let Ok(settings) = get_or_create_guild_settings(ctx, guild_id).await else {
+            return Err("This server's settings couldn't be loaded, please try again in a moment.".to_string());
+        };
+
+        let settings = settings.read().await;
+
+        match (settings.mute_type.as_str(), settings.mute_role) {
+            ("role", 0) => Err("This server mutes with a role, but no mute role is set. Use `mutetype role <role>` to set one.".to_string()),
+            ("role", role_id) => Ok(MuteType::Role(RoleId::new(role_id))),
+            _ => Ok(MuteType::Timeout)
+        }
+    }
+
+    /// Checks that the bot can hand out the mute role: it has to exist and sit below the bot's
+    /// highest role.
+    pub fn check_role(self, ctx: &Context, guild_id: GuildId) -> Result<(), String> {
+        let MuteType::Role(role_id) = self else {
+            return Ok(());
+        };
+
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+            return Err("This server isn't cached yet, please try again in a moment.".to_string());
+        };
+
+        let Some(role) = guild.roles.get(&role_id) else {
+            return Err("The mute role no longer exists. Use `mutetype role <role>` to set a new one.".to_string());
+        };
+
+        let bot_position = guild.members.get(&ctx.cache.current_user().id)
+            .and_then(|member| guild.member_highest_role(member))
+            .map_or(0, |highest| highest.position);
+
+        if bot_position <= role.position {
+            return Err(format!("My highest role must be above {} to give it out.", role.name));
+        }
+
+        Ok(())
+    }
+
+    /// The permission the bot needs to mute and unmute members.
+    pub fn required_permission(self) -> Permissions {
+        match self {
+            MuteType::Timeout => Permissions::MODERATE_MEMBERS,
+            MuteType::Role(_) => Permissions::MANAGE_ROLES
+        }
+    }
+}
+
+/// Checks that `moderator` may take `action` against `target` and that the bot is able to: the
+/// bot needs `permission`, and both the moderator and the bot need a higher role than the
+/// target. Returns a user-facing reason when the action isn't allowed.
+pub async fn check_target(
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator: UserId,
+    target: UserId,
+    action: ModAction,
+    permission: Permissions
+) -> Result<(), String> {
+    let verb = action.as_str();
+    let bot_id = ctx.cache.current_user().id;
+
+    if target == moderator {
+        return Err(format!("You can't {verb} yourself."));
+    }
+
+    if target == bot_id {
+        return Err(format!("I can't {verb} myself."));
+    }
+
+    let target_member = guild_id.member(ctx, target).await.ok();
+    let moderator_member = guild_id.member(ctx, moderator).await.map_err(|_| "I couldn't find you in this server.".to_string())?;
+    let bot_member = guild_id.member(ctx, bot_id).await.map_err(|_| "I couldn't find myself in this server.".to_string())?;
+
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return Err("This server isn't cached yet, please try again in a moment.".to_string());
+    };
+
+    if !guild.member_permissions(&bot_member).contains(permission) {
+        return Err(format!("I need the {permission} permission to do that."));
+    }
+
+    // users who aren't members can still be banned or unbanned, they just have no roles to compare
+    let Some(target_member) = target_member else {
+        return match action {
+            ModAction::Ban | ModAction::Tempban | ModAction::Unban => Ok(()),
+            _ => Err("That user isn't in this server.".to_string())
+        };
+    };
+
+    if target == guild.owner_id {
+        return Err(format!("The server owner can't be {}.", action.past_tense()));
+    }
+
+    let position = |member: &Member| guild.member_highest_role(member).map_or(0, |role| role.position);
+    let target_position = position(&target_member);
+
+    if moderator != guild.owner_id && position(&moderator_member) <= target_position {
+        return Err(format!("Your highest role must be above {}'s to {verb} them.", target_member.user.name));
+    }
+
+    if position(&bot_member) <= target_position {
+        return Err(format!("My highest role must be above {}'s to {verb} them.", target_member.user.name));
+    }
+
+    Ok(())
+}
+
+/// Carries out an action that's already been checked with `check_target`, letting the target
+/// know and recording it. Mutes and temporary bans last for `duration`, or until they're undone.
+/// Returns the case number.
+pub async fn apply_action(
+    ctx: &Context,
+    guild_id: GuildId,
+    target: &User,
+    moderator: &User,
+    action: ModAction,
+    duration: Option<Duration>,
+    reason: Option<&str>
+) -> Result<i64, CommandError> {
+    let audit = audit_reason(moderator, reason);
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    // the target can only be messaged while they still share a server with the bot
+    if matches!(action, ModAction::Kick | ModAction::Ban | ModAction::Tempban | ModAction::Softban) {
+        notify_target(ctx, guild_id, target, action, reason).await;
+    }
+
+    match action {
+        ModAction::Kick => guild_id.kick_with_reason(ctx, target.id, &audit).await?,
+        ModAction::Ban | ModAction::Tempban => {
+            // a new ban replaces whatever unban was pending
+            cancel_unbans(&database, guild_id, target.id).await?;
+
+            guild_id.ban_with_reason(ctx, target.id, 0, &audit).await?;
+
+            if let Some(duration) = duration.filter(|_| action == ModAction::Tempban) {
+                let job = Job::Unban { user_id: target.id.get() };
+                let when = When { first_run: Utc::now().checked_add_signed(duration).ok_or("That duration is too long.")?, repeat: None };
+
+                schedule_job(&database, Some(guild_id), &job, &when, moderator.id.get()).await?;
+            }
+        }
+        ModAction::Softban => {
+            guild_id.ban_with_reason(ctx, target.id, SOFTBAN_DELETE_DAYS, &audit).await?;
+            guild_id.unban(ctx, target.id).await?;
+        }
+        ModAction::Unban => {
+            guild_id.unban(ctx, target.id).await?;
+            cancel_unbans(&database, guild_id, target.id).await?;
+        }
+        ModAction::Mute | ModAction::Tempmute => {
+            // a new mute replaces whatever unmute was pending
+            cancel_unmutes(&database, guild_id, target.id).await?;
+
+            match MuteType::for_guild(ctx, guild_id).await? {
+                MuteType::Timeout => {
+                    let until = Utc::now() + duration.unwrap_or(MAX_TIMEOUT).min(MAX_TIMEOUT);
+                    let builder = EditMember::new().disable_communication_until_datetime(until.into()).audit_log_reason(&audit);
+
+                    guild_id.edit_member(ctx, target.id, builder).await?;
+                }
+                MuteType::Role(role_id) => {
+                    ctx.http.add_member_role(guild_id, target.id, role_id, Some(&audit)).await?;
+
+                    // timeouts end on their own, roles have to be taken away again
+                    if let Some(duration) = duration {
+                        let job = Job::Unmute { user_id: target.id.get(), role_id: role_id.get() };
+                        let when = When { first_run: Utc::now().checked_add_signed(duration).ok_or("That duration is too long.")?, repeat: None };
+
+                        schedule_job(&database, Some(guild_id), &job, &when, moderator.id.get()).await?;
+                    }
+                }
+            }
+        }
+        ModAction::Unmute => {
+            match MuteType::for_guild(ctx, guild_id).await? {
+                MuteType::Timeout => {
+                    guild_id.edit_member(ctx, target.id, EditMember::new().enable_communication().audit_log_reason(&audit)).await?;
+                }
+                MuteType::Role(role_id) => ctx.http.remove_member_role(guild_id, target.id, role_id, Some(&audit)).await?
+            }
+
+            cancel_unmutes(&database, guild_id, target.id).await?;
+        }
+        // warnings are only recorded, and the rest are carried out by their own commands and features
+        ModAction::Warn
+        | ModAction::Slowmode
+        | ModAction::Lock
+        | ModAction::Unlock
+        | ModAction::Lockdown
+        | ModAction::EndLockdown
+        | ModAction::Decancer
+        | ModAction::Forcenick
+        | ModAction::Quarantine
+        | ModAction::Unquarantine => {}
+    }
+
+    if matches!(action, ModAction::Mute | ModAction::Tempmute | ModAction::Unmute | ModAction::Warn) {
+        notify_target(ctx, guild_id, target, action, reason).await;
+    }
+
+    Ok(record_action(ctx, &database, guild_id, action, target.id, moderator.id, reason).await?)
+}
+
+/// What came of issuing a warning.
+pub struct WarningOutcome {
+    pub case: i64,
+    pub warnings: i32,
+    /// The escalation the warning triggered, if any, and its case if it could be carried out.
+    pub escalation: Option<(ModAction, Result<i64, CommandError>)>
+}
+
+/// Warns a member that's already been checked with `check_target`, then carries out the guild's
+/// escalation for their new number of warnings, if it has one.
+pub async fn add_warning(ctx: &Context, guild_id: GuildId, target: &User, moderator: &User, reason: Option<&str>) -> Result<WarningOutcome, CommandError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let (db_guild_id, db_user_id, db_moderator_id) = (guild_id.get() as i64, target.id.get() as i64, moderator.id.get() as i64);
+    let created_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO warnings (guild_id, user_id, moderator_id, reason, created_at) VALUES (?, ?, ?, ?, ?)",
+        db_guild_id,
+        db_user_id,
+        db_moderator_id,
+        reason,
+        created_at
+    ).execute(&database).await?;
+
+    let case = apply_action(ctx, guild_id, target, moderator, ModAction::Warn, None, reason).await?;
+
+    let warnings = sqlx::query!(
+        "SELECT COUNT(*) AS count FROM warnings WHERE guild_id = ? AND user_id = ?",
+        db_guild_id,
+        db_user_id
+    ).fetch_one(&database).await?.count;
+
+    let escalation = sqlx::query!(
+        "SELECT action, duration FROM warning_escalations WHERE guild_id = ? AND warnings = ?",
+        db_guild_id,
+        warnings
+    ).fetch_optional(&database).await?;
+
+    let Some(escalation) = escalation else {
+        return Ok(WarningOutcome { case, warnings, escalation: None });
+    };
+
+    let action = match (escalation.action.as_str(), escalation.duration) {
+        ("kick", _) => ModAction::Kick,
+        ("ban", _) => ModAction::Ban,
+        (_, Some(_)) => ModAction::Tempmute,
+        _ => ModAction::Mute
+    };
+
+    // escalations are carried out by the bot itself
+    let bot = User::from(ctx.cache.current_user().clone());
+    let duration = escalation.duration.map(Duration::seconds);
+    let reason = format!("Reached {warnings} warnings");
+    let result = apply_action(ctx, guild_id, target, &bot, action, duration, Some(&reason)).await;
+
+    Ok(WarningOutcome { case, warnings, escalation: Some((action, result)) })
+}
+
+/// Lets the target know what happened and why. Users who don't accept DMs are skipped silently.
+pub async fn notify_target(ctx: &Context, guild_id: GuildId, target: &User, action: ModAction, reason: Option<&str>) {
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "a server".to_string());
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .title(format!("You were {} {} {guild_name}", action.past_tense(), action.preposition()))
+        .description(format!("**Reason**: {}", reason.unwrap_or("No reason given.")));
+
+    drop(target.direct_message(ctx, CreateMessage::new().embed(embed)).await);
+}
+
+/// The reason shown in the guild's audit log, noting which moderator used the bot.
+pub fn audit_reason(moderator: &User, reason: Option<&str>) -> String {
+    let reason = format!("{}: {}", moderator.tag(), reason.unwrap_or("No reason given."));
+    reason.chars().take(MAX_AUDIT_REASON_LENGTH).collect()
+}
+
+/// Cancels any scheduled unmutes for a user, e.g. once they've been unmuted by hand.
+pub async fn cancel_unmutes(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let user_id = user_id.get() as i64;
+
+    sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE guild_id = ? AND kind = 'unmute' AND json_extract(payload, '$.user_id') = ?",
+        guild_id,
+        user_id
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// Cancels any scheduled unbans for a user, e.g. once they've been unbanned by hand.
+pub async fn cancel_unbans(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let user_id = user_id.get() as i64;
+
+    sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE guild_id = ? AND kind = 'unban' AND json_extract(payload, '$.user_id') = ?",
+        guild_id,
+        user_id
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// Records a moderation action under the guild's next case number and posts it to the guild's
+/// modlog channel. Returns the case number.
+pub async fn record_action(
+    ctx: &Context,
+    database: &SqlitePool,
+    guild_id: GuildId,
+    action: ModAction,
+    target: UserId,
+    moderator: UserId,
+    reason: Option<&str>
+) -> Result<i64, sqlx::Error> {
+    record_case(ctx, database, guild_id, action, target.get(), moderator, reason).await
+}
+
+/// Records an action taken on a channel like `record_action`. Lockdowns, which cover several
+/// channels, have no channel.
+pub async fn record_channel_action(
+    ctx: &Context,
+    database: &SqlitePool,
+    guild_id: GuildId,
+    action: ModAction,
+    channel_id: Option<ChannelId>,
+    moderator: UserId,
+    reason: Option<&str>
+) -> Result<i64, sqlx::Error> {
+    let target = channel_id.map_or(guild_id.get(), ChannelId::get);
+    record_case(ctx, database, guild_id, action, target, moderator, reason).await
+}
+
+/// Records a case against a user, or a channel for channel actions.
+async fn record_case(
+    ctx: &Context,
+    database: &SqlitePool,
+    guild_id: GuildId,
+    action: ModAction,
+    target: u64,
+    moderator: UserId,
+    reason: Option<&str>
+) -> Result<i64, sqlx::Error> {
+    let db_guild_id = guild_id.get() as i64;
+    let action = action.as_str();
+    let target = target as i64;
+    let moderator = moderator.get() as i64;
+    let created_at = Utc::now().to_rfc3339();
+
+    let case = sqlx::query!(
+        r#"INSERT INTO mod_actions (guild_id, action, target_id, moderator_id, reason, created_at, case_number)
+        VALUES (?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(case_number), 0) + 1 FROM mod_actions WHERE guild_id = ?))
+        RETURNING case_number AS "case_number!: i64""#,
+        db_guild_id,
+        action,
+        target,
+        moderator,
+        reason,
+        created_at,
+        db_guild_id
+    ).fetch_one(database).await?.case_number;
+
+    if let Err(why) = post_case(ctx, database, guild_id, case).await {
+        warn!("Failed to post case {case} to the modlog of guild {guild_id}: {why}");
+    }
+
+    Ok(case)
+}
+
+/// The embed describing a case, as posted in the modlog channel.
+pub async fn case_embed(ctx: &Context, database: &SqlitePool, guild_id: GuildId, case: i64) -> Result<Option<CreateEmbed>, sqlx::Error> {
+    let db_guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT action, target_id, moderator_id, reason, created_at FROM mod_actions WHERE guild_id = ? AND case_number = ?",
+        db_guild_id,
+        case
+    ).fetch_optional(database).await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let parsed = ModAction::parse(&row.action);
+    let action = parsed.map_or_else(|| row.action.clone(), |action| action.title().to_string());
+    let reason = row.reason.unwrap_or_else(|| format!("No reason given. Use `reason {case} <reason>` to add one."));
+
+    let (target_name, target) = match parsed {
+        Some(ModAction::Slowmode | ModAction::Lock | ModAction::Unlock) => ("Channel", format!("<#{0}> (`{0}`)", row.target_id)),
+        Some(ModAction::Lockdown | ModAction::EndLockdown) => ("Channels", "The lockdown channels".to_string()),
+        _ => ("User", format!("<@{0}> (`{0}`)", row.target_id))
+    };
+
+    let mut embed = branded_embed(ctx, Some(guild_id)).await
+        .title(format!("Case #{case} | {action}"))
+        .field(target_name, target, true)
+        .field("Moderator", format!("<@{}>", row.moderator_id), true)
+        .field("Reason", reason, false);
+
+    if let Ok(created_at) = Timestamp::parse(&row.created_at) {
+        embed = embed.timestamp(created_at);
+    }
+
+    Ok(Some(embed))
+}
+
+/// The guild's modlog channel, if it has one.
+pub async fn modlog_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT modlog_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.modlog_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+/// Posts a case to the guild's modlog channel, if it has one, remembering the message so the
+/// case can be updated later.
+async fn post_case(ctx: &Context, database: &SqlitePool, guild_id: GuildId, case: i64) -> Result<(), CommandError> {
+    let db_guild_id = guild_id.get() as i64;
+
+    let (Some(channel_id), Some(embed)) = (modlog_channel(database, guild_id).await?, case_embed(ctx, database, guild_id, case).await?) else {
+        return Ok(());
+    };
+
+    let message = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    let message_id = message.id.get() as i64;
+
+    sqlx::query!(
+        "UPDATE mod_actions SET modlog_message_id = ? WHERE guild_id = ? AND case_number = ?",
+        message_id,
+        db_guild_id,
+        case
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// Changes a case's reason and updates its modlog message. Returns `false` if there's no such case.
+pub async fn update_case_reason(ctx: &Context, database: &SqlitePool, guild_id: GuildId, case: i64, reason: &str) -> Result<bool, CommandError> {
+    let db_guild_id = guild_id.get() as i64;
+
+    let updated = sqlx::query!(
+        "UPDATE mod_actions SET reason = ? WHERE guild_id = ? AND case_number = ? RETURNING modlog_message_id",
+        reason,
+        db_guild_id,
+        case
+    ).fetch_optional(database).await?;
+
+    let Some(updated) = updated else {
+        return Ok(false);
+    };
+
+    // the message is gone if the modlog channel has changed since, which is fine
+    if let (Some(channel_id), Some(message_id), Some(embed)) = (modlog_channel(database, guild_id).await?, updated.modlog_message_id, case_embed(ctx, database, guild_id, case).await?) {
+        drop(channel_id.edit_message(ctx, MessageId::new(message_id as u64), EditMessage::new().embed(embed)).await);
+    }
+
+    Ok(true)
+}
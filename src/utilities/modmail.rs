@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serenity::builder::{CreateEmbedAuthor, CreateEmbedFooter, CreateMessage, CreateThread, EditThread};
+use serenity::framework::standard::CommandError;
+use serenity::model::channel::{ChannelType, Message, ReactionType};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::branding::{Branding, branded_embed, guild_branding};
+use crate::utilities::global_data::{DatabaseConnectionContainer, GuildSettingsContainer, ModmailContainer};
+use crate::utilities::errors::get_data;
+
+/// The prefix commands use in DMs, messages starting with it aren't relayed.
+const DM_PREFIX: &str = "-";
+
+/// Open modmail threads, mapped to the user each one is talking to.
+pub async fn load_modmail_threads(database: &SqlitePool) -> Result<HashMap<u64, u64>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT thread_id, user_id FROM modmail_threads WHERE closed_at IS NULL")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter().map(|row| (row.thread_id as u64, row.user_id as u64)).collect())
+}
+
+/// Relays a DM to the user's open modmail thread, or opens one in a server they share with the
+/// bot. With several such servers, the message has to start with the ID of the one to contact.
+pub async fn handle_modmail_dm(ctx: &Context, msg: &Message) {
+    if msg.content.starts_with(DM_PREFIX) {
+        return;
+    }
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't relay DM {} to modmail: {why}", msg.id);
+            return;
+        }
+    };
+
+    if let Err(why) = relay_dm(ctx, &database, msg).await {
+        error!("Failed to relay a modmail DM from user {}: {why}", msg.author.id);
+        drop(msg.reply(ctx, "I couldn't pass your message on to the staff, please try again later.").await);
+    }
+}
+
+async fn relay_dm(ctx: &Context, database: &SqlitePool, msg: &Message) -> Result<(), CommandError> {
+    let user_id = msg.author.id.get() as i64;
+
+    let open = sqlx::query!("SELECT thread_id, guild_id FROM modmail_threads WHERE user_id = ? AND closed_at IS NULL", user_id)
+        .fetch_optional(database)
+        .await?;
+
+    if let Some(open) = open {
+        let branding = guild_branding(ctx, Some(GuildId::new(open.guild_id as u64))).await;
+        ChannelId::new(open.thread_id as u64).send_message(ctx, user_message(&branding, msg, &msg.content)).await?;
+        msg.react(ctx, ReactionType::Unicode("✅".to_string())).await?;
+
+        return Ok(());
+    }
+
+    let settings = sqlx::query!("SELECT guild_id, modmail_channel_id AS \"modmail_channel_id!: i64\" FROM guild_settings WHERE modmail_channel_id IS NOT NULL")
+        .fetch_all(database)
+        .await?;
+
+    let mut candidates = Vec::new();
+
+    for row in settings {
+        let guild_id = GuildId::new(row.guild_id as u64);
+
+        if guild_id.member(ctx, msg.author.id).await.is_ok() {
+            candidates.push((guild_id, ChannelId::new(row.modmail_channel_id as u64)));
+        }
+    }
+
+    let (chosen, content) = match msg.content.split_once(char::is_whitespace) {
+        Some((first, rest)) if candidates.len() > 1 => (candidates.iter().find(|(guild_id, _)| guild_id.to_string() == first), rest.trim_start()),
+        _ => (candidates.first().filter(|_| candidates.len() == 1), msg.content.as_str())
+    };
+
+    let Some(&(guild_id, channel_id)) = chosen else {
+        if candidates.len() > 1 {
+            let servers = candidates.iter()
+                .map(|(guild_id, _)| format!("- {} (`{guild_id}`)", guild_id.name(&ctx.cache).unwrap_or_else(|| "Unknown server".to_string())))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            msg.reply(ctx, format!("We share several servers with modmail, please start your message with the ID of the one you'd like to contact:\n{servers}")).await?;
+        }
+
+        return Ok(());
+    };
+
+    let thread = channel_id.create_thread(ctx, CreateThread::new(msg.author.name.clone()).kind(ChannelType::PublicThread)).await?;
+
+    let (thread_id, db_guild_id, opened_at) = (thread.id.get() as i64, guild_id.get() as i64, Utc::now().to_rfc3339());
+
+    let inserted = sqlx::query!(
+        "INSERT INTO modmail_threads (thread_id, guild_id, user_id, opened_at) VALUES (?, ?, ?, ?)",
+        thread_id,
+        db_guild_id,
+        user_id,
+        opened_at
+    ).execute(database).await;
+
+    // another DM may have opened a thread at the same time
+    if let Err(why) = inserted {
+        drop(thread.delete(ctx).await);
+        return Err(why.into());
+    }
+
+    let threads = get_data::<ModmailContainer>(ctx).await?;
+
+    threads.write().await.insert(thread.id.get(), msg.author.id.get());
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .title("New modmail")
+        .description(format!("{} opened a conversation. Messages sent here are relayed to them, except commands; use `modmail close` once you're done.", msg.author.mention()))
+        .field("Account created", format!("<t:{}:R>", msg.author.created_at().unix_timestamp()), true);
+
+    thread.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    thread.send_message(ctx, user_message(&guild_branding(ctx, Some(guild_id)).await, msg, content)).await?;
+
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    msg.reply(ctx, format!("Your message was sent to the staff of **{guild_name}**, their replies will show up here. Use `{DM_PREFIX}modmail close` to end the conversation.")).await?;
+
+    Ok(())
+}
+
+/// Relays a staff message in an open modmail thread to the user it's talking to. Returns true if
+/// the message was in a modmail thread.
+pub async fn handle_modmail_reply(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return false;
+    };
+
+    let (database, threads, guild_settings) = match (
+        get_data::<DatabaseConnectionContainer>(ctx).await,
+        get_data::<ModmailContainer>(ctx).await,
+        get_data::<GuildSettingsContainer>(ctx).await
+    ) {
+        (Ok(database), Ok(threads), Ok(guild_settings)) => (database, threads, guild_settings),
+        (Err(why), _, _) | (_, Err(why), _) | (_, _, Err(why)) => {
+            error!("Couldn't check message {} for a modmail reply: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let Some(user_id) = threads.read().await.get(&msg.channel_id.get()).copied().map(UserId::new) else {
+        return false;
+    };
+
+    // commands such as `modmail close` stay between staff
+    let is_command = guild_settings.read(guild_id.get(), |settings| msg.content.starts_with(&settings.prefix)).await.unwrap_or(false);
+
+    if is_command {
+        return true;
+    }
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let anonymous = match sqlx::query!("SELECT modmail_anonymous FROM guild_settings WHERE guild_id = ?", db_guild_id).fetch_optional(&database).await {
+        Ok(settings) => settings.is_some_and(|settings| settings.modmail_anonymous != 0),
+        Err(why) => {
+            error!("Failed to fetch modmail settings of guild {guild_id}: {why}");
+            return true;
+        }
+    };
+
+    let author = if anonymous {
+        CreateEmbedAuthor::new("Staff")
+    } else {
+        CreateEmbedAuthor::new(msg.author.tag()).icon_url(msg.author.face())
+    };
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .author(author)
+        .description(with_attachments(msg, &msg.content))
+        .footer(CreateEmbedFooter::new(guild_id.name(&ctx.cache).unwrap_or_default()));
+
+    let relayed = match user_id.create_dm_channel(ctx).await {
+        Ok(channel) => channel.send_message(ctx, CreateMessage::new().embed(embed)).await.map(|_| ()),
+        Err(why) => Err(why)
+    };
+
+    match relayed {
+        Ok(()) => drop(msg.react(ctx, ReactionType::Unicode("✅".to_string())).await),
+        Err(why) => {
+            warn!("Couldn't relay a modmail reply to user {user_id}: {why}");
+            drop(msg.reply(ctx, "I couldn't DM them, they may have left the server or closed their DMs.").await);
+        }
+    }
+
+    true
+}
+
+/// Ends the conversation in a modmail thread, letting the user know and archiving the thread.
+/// Returns false if the thread isn't an open conversation.
+pub async fn close_modmail(ctx: &Context, database: &SqlitePool, thread_id: ChannelId, closed_by: UserId) -> Result<bool, CommandError> {
+    let (db_thread_id, db_closed_by, closed_at) = (thread_id.get() as i64, closed_by.get() as i64, Utc::now().to_rfc3339());
+
+    let Some(session) = sqlx::query!(
+        "UPDATE modmail_threads SET closed_at = ?, closed_by = ? WHERE thread_id = ? AND closed_at IS NULL RETURNING guild_id, user_id",
+        closed_at,
+        db_closed_by,
+        db_thread_id
+    ).fetch_optional(database).await? else {
+        return Ok(false);
+    };
+
+    let threads = get_data::<ModmailContainer>(ctx).await?;
+
+    threads.write().await.remove(&thread_id.get());
+
+    let (guild_id, user_id) = (GuildId::new(session.guild_id as u64), UserId::new(session.user_id as u64));
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+
+    if let Err(why) = user_id.direct_message(ctx, CreateMessage::new().content(format!("Your modmail conversation with **{guild_name}** was closed."))).await {
+        warn!("Couldn't tell user {user_id} their modmail was closed: {why}");
+    }
+
+    thread_id.say(ctx, format!("This conversation was closed by {}.", closed_by.mention())).await?;
+    thread_id.edit_thread(ctx, EditThread::new().archived(true).locked(true)).await?;
+
+    Ok(true)
+}
+
+fn user_message(branding: &Branding, msg: &Message, content: &str) -> CreateMessage {
+    let embed = branding.embed()
+        .author(CreateEmbedAuthor::new(msg.author.tag()).icon_url(msg.author.face()))
+        .description(with_attachments(msg, content))
+        .footer(CreateEmbedFooter::new(format!("User ID: {}", msg.author.id)));
+
+    CreateMessage::new().embed(embed)
+}
+
+fn with_attachments(msg: &Message, content: &str) -> String {
+    msg.attachments.iter().fold(content.to_string(), |text, attachment| format!("{text}\n{}", attachment.url))
+}
@@ -0,0 +1,192 @@
+use serenity::builder::EditMember;
+use serenity::model::id::{GuildId, UserId};
+use serenity::model::user::User;
+use serenity::prelude::{Context, SerenityError};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// Longest nickname Discord allows.
+pub const MAX_NICKNAME_LENGTH: usize = 32;
+
+/// Given to members whose names have nothing readable left once they're decancered.
+const FALLBACK_NICKNAME: &str = "Moderated nickname";
+
+/// Fewest letters or digits a decancered name needs to be kept.
+const MIN_READABLE: usize = 2;
+
+/// Letters from other scripts, and small capitals, that are used to look like latin ones.
+fn confusable(character: char) -> char {
+    match character {
+        'а' | 'α' | 'ᴀ' => 'a',
+        'ʙ' => 'b',
+        'с' | 'ᴄ' => 'c',
+        'ᴅ' => 'd',
+        'е' | 'ε' | 'ᴇ' => 'e',
+        'ғ' => 'f',
+        'ɢ' => 'g',
+        'һ' | 'ʜ' => 'h',
+        'і' | 'ι' | 'ɪ' => 'i',
+        'ј' | 'ᴊ' => 'j',
+        'κ' | 'ᴋ' => 'k',
+        'ʟ' => 'l',
+        'ᴍ' => 'm',
+        'η' | 'ɴ' => 'n',
+        'о' | 'ο' | 'ᴏ' => 'o',
+        'р' | 'ρ' | 'ᴘ' => 'p',
+        'ǫ' => 'q',
+        'ʀ' => 'r',
+        'ѕ' | 'ꜱ' => 's',
+        'τ' | 'ᴛ' => 't',
+        'υ' | 'ᴜ' => 'u',
+        'ν' | 'ᴠ' => 'v',
+        'ᴡ' | 'ω' => 'w',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' | 'ʏ' => 'y',
+        'ᴢ' => 'z',
+        'А' | 'Α' => 'A',
+        'В' | 'Β' => 'B',
+        'С' => 'C',
+        'Е' | 'Ε' => 'E',
+        'Н' | 'Η' => 'H',
+        'І' | 'Ι' => 'I',
+        'Ј' => 'J',
+        'К' | 'Κ' => 'K',
+        'М' | 'Μ' => 'M',
+        'Ν' => 'N',
+        'О' | 'Ο' => 'O',
+        'Р' | 'Ρ' => 'P',
+        'Ѕ' => 'S',
+        'Т' | 'Τ' => 'T',
+        'Υ' => 'Y',
+        'Х' | 'Χ' => 'X',
+        'Ζ' => 'Z',
+        character => character
+    }
+}
+
+/// Turns a name into plain, readable ASCII: fancy unicode letters become the ones they imitate,
+/// zalgo and other marks are dropped, and so are characters at the start that only hoist the name
+/// to the top of the member list. Returns `None` if too little is left to read.
+fn readable(name: &str) -> Option<String> {
+    let ascii: String = name.nfkd()
+        .filter(|character| !is_combining_mark(*character))
+        .map(confusable)
+        .filter(|character| character.is_ascii_graphic() || *character == ' ')
+        .collect();
+
+    let unhoisted = ascii.trim_start_matches(|character: char| !character.is_ascii_alphanumeric());
+    let collapsed = unhoisted.split_whitespace().collect::<Vec<_>>().join(" ");
+    let truncated: String = collapsed.chars().take(MAX_NICKNAME_LENGTH).collect();
+
+    (truncated.chars().filter(char::is_ascii_alphanumeric).count() >= MIN_READABLE).then(|| truncated.trim_end().to_string())
+}
+
+/// The decancered version of a member's name, falling back to their username and then a
+/// placeholder when their name has nothing readable in it.
+pub fn decancer(user: &User, nick: Option<&str>) -> String {
+    let name = nick.or(user.global_name.as_deref()).unwrap_or(&user.name);
+
+    readable(name)
+        .or_else(|| readable(&user.name))
+        .unwrap_or_else(|| FALLBACK_NICKNAME.to_string())
+}
+
+/// Whether the bot's highest role is above a member's, so it can change their nickname.
+pub fn can_rename(ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return false;
+    };
+
+    if user_id == guild.owner_id {
+        return false;
+    }
+
+    let position = |user_id: UserId| guild.members.get(&user_id)
+        .and_then(|member| guild.member_highest_role(member))
+        .map_or(0, |role| role.position);
+
+    position(ctx.cache.current_user().id) > position(user_id)
+}
+
+/// Sets a member's nickname, or clears it with `None`.
+pub async fn set_nickname(ctx: &Context, guild_id: GuildId, user_id: UserId, nickname: Option<&str>, reason: &str) -> Result<(), SerenityError> {
+    let builder = EditMember::new()
+        .nickname(nickname.unwrap_or_default())
+        .audit_log_reason(reason);
+
+    guild_id.edit_member(ctx, user_id, builder).await?;
+
+    Ok(())
+}
+
+/// The nickname a member was given with `forcenick`, if any.
+pub async fn forced_nickname(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<Option<String>, sqlx::Error> {
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let row = sqlx::query!("SELECT nickname FROM forced_nicknames WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.map(|row| row.nickname))
+}
+
+/// Puts back a member's forced nickname, or decancers their name if the guild does that
+/// automatically, after they join or change their nickname.
+pub async fn enforce_nickname(ctx: &Context, guild_id: GuildId, user: &User, nick: Option<&str>) {
+    if user.bot || user.id == ctx.cache.current_user().id {
+        return;
+    }
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check the nickname of user {}: {why}", user.id);
+            return;
+        }
+    };
+
+    let forced = match forced_nickname(&database, guild_id, user.id).await {
+        Ok(forced) => forced,
+        Err(why) => {
+            error!("Failed to fetch the forced nickname of user {} in guild {guild_id}: {why}", user.id);
+            return;
+        }
+    };
+
+    let (nickname, reason) = match forced {
+        Some(forced) => (forced, "Forced nickname"),
+        None => {
+            let db_guild_id = guild_id.get() as i64;
+
+            let enabled = match sqlx::query!("SELECT auto_decancer FROM guild_settings WHERE guild_id = ?", db_guild_id).fetch_optional(&database).await {
+                Ok(row) => row.is_some_and(|row| row.auto_decancer != 0),
+                Err(why) => {
+                    error!("Failed to fetch the decancer setting of guild {guild_id}: {why}");
+                    return;
+                }
+            };
+
+            if !enabled {
+                return;
+            }
+
+            (decancer(user, nick), "Automatic decancer")
+        }
+    };
+
+    // our own change comes back as another update, which ends here
+    let current = nick.or(user.global_name.as_deref()).unwrap_or(&user.name);
+
+    if current == nickname || !can_rename(ctx, guild_id, user.id) {
+        return;
+    }
+
+    if let Err(why) = set_nickname(ctx, guild_id, user.id, Some(&nickname), reason).await {
+        warn!("Couldn't change the nickname of user {} in guild {guild_id}: {why}", user.id);
+    }
+}
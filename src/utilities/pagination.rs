@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage};
+use serenity::model::application::ButtonStyle;
+use serenity::prelude::Context;
+use serenity::utils::CreateQuickModal;
+
+use crate::utilities::invocation::Invocation;
+
+/// How long page buttons keep working after they were last used.
+pub const PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Lines shown per page when a list doesn't need a different number.
+pub const LINES_PER_PAGE: usize = 10;
+
+/// How long the jump to page prompt waits for a page number.
+const JUMP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest text Discord shows in an embed's description.
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+const PREVIOUS_ID: &str = "page_previous";
+const JUMP_ID: &str = "page_jump";
+const NEXT_ID: &str = "page_next";
+
+/// Joins lines into page descriptions of at most `per_page` lines each, starting a new page early
+/// if one would get too long for an embed.
+pub fn paginate_lines(lines: &[String], per_page: usize, separator: &str) -> Vec<String> {
+    let mut pages: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut length = 0;
+
+    for line in lines {
+        let added = line.chars().count() + if current.is_empty() { 0 } else { separator.chars().count() };
+
+        if !current.is_empty() && (current.len() >= per_page || length + added > MAX_DESCRIPTION_LENGTH) {
+            pages.push(current.join(separator));
+            current.clear();
+            length = 0;
+        }
+
+        length += line.chars().count() + if current.is_empty() { 0 } else { separator.chars().count() };
+        current.push(line);
+    }
+
+    if !current.is_empty() {
+        pages.push(current.join(separator));
+    }
+
+    pages
+}
+
+/// One copy of an embed per page description.
+pub fn embed_pages(embed: CreateEmbed, descriptions: Vec<String>) -> Vec<CreateEmbed> {
+    descriptions.into_iter()
+        .map(|description| embed.clone().description(description))
+        .collect()
+}
+
+fn page_buttons(page: usize, pages: usize) -> Vec<CreateActionRow> {
+    let buttons = vec![
+        CreateButton::new(PREVIOUS_ID).label("Previous").style(ButtonStyle::Secondary).disabled(page == 0),
+        CreateButton::new(JUMP_ID).label(format!("Page {}/{pages}", page + 1)).style(ButtonStyle::Secondary),
+        CreateButton::new(NEXT_ID).label("Next").style(ButtonStyle::Secondary).disabled(page + 1 >= pages)
+    ];
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Embeds shown one at a time, with buttons to go to the previous or next page or jump to one.
+/// Only whoever ran the command can use the buttons, which are removed once they haven't been used
+/// for a while.
+pub struct Paginator {
+    pages: Vec<CreateEmbed>
+}
+
+impl Paginator {
+    pub fn new(pages: Vec<CreateEmbed>) -> Self {
+        Paginator { pages }
+    }
+
+    fn page_response(&self, page: usize) -> CreateInteractionResponse {
+        let response = CreateInteractionResponseMessage::new()
+            .embed(self.pages[page].clone())
+            .components(page_buttons(page, self.pages.len()));
+
+        CreateInteractionResponse::UpdateMessage(response)
+    }
+
+    /// Sends the first page in reply to the command and handles the buttons until they time out.
+    pub async fn send(self, ctx: &Context, invocation: &Invocation<'_>) -> Result<(), serenity::Error> {
+        let pages = self.pages.len();
+
+        let Some(first) = self.pages.first() else {
+            return Ok(());
+        };
+
+        if pages == 1 {
+            return invocation.respond(ctx, first.clone()).await;
+        }
+
+        let mut view = invocation.respond_with_components(ctx, first.clone(), page_buttons(0, pages)).await?;
+
+        let author = invocation.user_id();
+        let mut page: usize = 0;
+
+        while let Some(interaction) = view.await_component_interaction(&ctx.shard)
+            .timeout(PAGE_TIMEOUT)
+            .await
+        {
+            if interaction.user.id != author {
+                let response = CreateInteractionResponseMessage::new()
+                    .content("Only whoever used the command can flip through these pages.")
+                    .ephemeral(true);
+
+                interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+                continue;
+            }
+
+            match interaction.data.custom_id.as_str() {
+                PREVIOUS_ID => page = page.saturating_sub(1),
+                NEXT_ID => page = (page + 1).min(pages - 1),
+                JUMP_ID => {
+                    let modal = CreateQuickModal::new("Jump to page")
+                        .timeout(JUMP_TIMEOUT)
+                        .short_field(format!("Page (1 to {pages})"));
+
+                    let Some(submitted) = interaction.quick_modal(ctx, modal).await? else {
+                        continue;
+                    };
+
+                    match submitted.inputs.first().and_then(|input| input.trim().parse::<usize>().ok()).filter(|jump| (1..=pages).contains(jump)) {
+                        Some(jump) => {
+                            page = jump - 1;
+                            submitted.interaction.create_response(ctx, self.page_response(page)).await?;
+                        }
+                        None => {
+                            let response = CreateInteractionResponseMessage::new()
+                                .content(format!("Please give a page from 1 to {pages}."))
+                                .ephemeral(true);
+
+                            submitted.interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+                        }
+                    }
+
+                    continue;
+                }
+                _ => continue
+            }
+
+            interaction.create_response(ctx, self.page_response(page)).await?;
+        }
+
+        drop(view.edit(ctx, EditMessage::new().components(vec![])).await);
+
+        Ok(())
+    }
+}
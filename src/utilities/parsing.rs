@@ -0,0 +1,177 @@
+use chrono::{Duration, FixedOffset};
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use serenity::utils::{parse_channel_mention, parse_role_mention, parse_user_mention};
+
+/// Parses a channel mention (`<#id>`) or a raw channel ID.
+pub fn parse_channel(arg: &str) -> Option<ChannelId> {
+    parse_channel_mention(arg).or_else(|| parse_id(arg).map(ChannelId::new))
+}
+
+/// Parses a user mention (`<@id>` or `<@!id>`) or a raw user ID.
+pub fn parse_user(arg: &str) -> Option<UserId> {
+    parse_user_mention(arg).or_else(|| parse_id(arg).map(UserId::new))
+}
+
+/// Parses a role mention (`<@&id>`) or a raw role ID.
+pub fn parse_role(arg: &str) -> Option<RoleId> {
+    parse_role_mention(arg).or_else(|| parse_id(arg).map(RoleId::new))
+}
+
+/// Parses a raw guild ID.
+pub fn parse_guild(arg: &str) -> Option<GuildId> {
+    parse_id(arg).map(GuildId::new)
+}
+
+fn parse_id(arg: &str) -> Option<u64> {
+    arg.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+/// Parses a duration made of one or more `<number><unit>` parts, e.g. `10m`, `2h` or `1d12h`.
+/// Units are `s`, `m`, `h`, `d` and `w`.
+pub fn parse_duration(arg: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+
+    for character in arg.trim().to_lowercase().chars() {
+        if character.is_ascii_digit() {
+            number.push(character);
+            continue;
+        }
+
+        let amount = number.parse::<i64>().ok()?;
+        number.clear();
+
+        let part = match character {
+            's' => Duration::try_seconds(amount)?,
+            'm' => Duration::try_minutes(amount)?,
+            'h' => Duration::try_hours(amount)?,
+            'd' => Duration::try_days(amount)?,
+            'w' => Duration::try_weeks(amount)?,
+            _ => return None
+        };
+
+        total = total.checked_add(&part)?;
+    }
+
+    // a trailing number without a unit isn't a duration
+    (number.is_empty() && total > Duration::zero()).then_some(total)
+}
+
+/// Formats a duration the way `parse_duration` reads it, using the two largest units.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let units = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+
+    let parts: Vec<String> = units.iter()
+        .scan(seconds, |remaining, (unit, size)| {
+            let amount = *remaining / size;
+            *remaining %= size;
+            Some((amount, unit))
+        })
+        .filter(|(amount, _)| *amount > 0)
+        .take(2)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect();
+
+    if parts.is_empty() { "0s".to_string() } else { parts.join("") }
+}
+
+/// Parses an offset from UTC such as `UTC+2`, `GMT-05:30`, `+0530` or just `UTC`.
+pub fn parse_utc_offset(arg: &str) -> Option<FixedOffset> {
+    let arg = arg.trim().to_uppercase();
+    let offset = arg.strip_prefix("UTC").or_else(|| arg.strip_prefix("GMT")).unwrap_or(&arg).trim();
+
+    if offset.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, offset) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+        (Some(offset), _) => (1, offset),
+        (_, Some(offset)) => (-1, offset),
+        _ => return None
+    };
+
+    if !offset.bytes().all(|byte| byte.is_ascii_digit() || byte == b':') {
+        return None;
+    }
+
+    let (hours, minutes) = match offset.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if offset.len() > 2 => offset.split_at(offset.len() - 2),
+        None => (offset, "0")
+    };
+
+    let (hours, minutes) = (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?);
+
+    if hours > 14 || minutes >= 60 || hours < 0 || minutes < 0 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Formats an offset from UTC the way `parse_utc_offset` reads it, e.g. `UTC+5:30`.
+pub fn format_utc_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let (sign, hours, minutes) = (if seconds < 0 { '-' } else { '+' }, seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+
+    match (hours, minutes) {
+        (0, 0) => "UTC".to_string(),
+        (hours, 0) => format!("UTC{sign}{hours}"),
+        (hours, minutes) => format!("UTC{sign}{hours}:{minutes:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("10m"), Some(Duration::minutes(10)));
+        assert_eq!(parse_duration("1d12h"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration(" 2W "), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("1h30m15s"), Some(Duration::seconds(5415)));
+        assert_eq!(parse_duration("0s5m"), Some(Duration::minutes(5)));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for arg in ["", "10", "m", "10x", "1h30", "-5m", "1.5h", "0m", "10 m", "99999999999999999999s", "9999999999999w", "5é"] {
+            assert_eq!(parse_duration(arg), None, "`{arg}` was accepted");
+        }
+    }
+
+    #[test]
+    fn formats_durations_the_way_they_are_read() {
+        assert_eq!(format_duration(Duration::seconds(5415)), "1h30m");
+        assert_eq!(format_duration(Duration::days(3)), "3d");
+        assert_eq!(format_duration(Duration::zero()), "0s");
+        assert_eq!(parse_duration(&format_duration(Duration::minutes(90))), Some(Duration::minutes(90)));
+    }
+
+    #[test]
+    fn parses_guild_ids() {
+        assert_eq!(parse_guild("81384788765712384"), Some(GuildId::new(81384788765712384)));
+
+        for arg in ["0", "-1", "abc", "<#81384788765712384>", "", "99999999999999999999"] {
+            assert_eq!(parse_guild(arg), None, "`{arg}` was accepted");
+        }
+    }
+
+    #[test]
+    fn parses_utc_offsets() {
+        assert_eq!(parse_utc_offset("UTC"), FixedOffset::east_opt(0));
+        assert_eq!(parse_utc_offset("utc+2"), FixedOffset::east_opt(2 * 3600));
+        assert_eq!(parse_utc_offset("GMT-05:30"), FixedOffset::west_opt(5 * 3600 + 30 * 60));
+        assert_eq!(parse_utc_offset("+0530"), FixedOffset::east_opt(5 * 3600 + 30 * 60));
+        assert_eq!(parse_utc_offset("-3"), FixedOffset::west_opt(3 * 3600));
+    }
+
+    #[test]
+    fn rejects_malformed_utc_offsets() {
+        for arg in ["2", "UTC2", "UTC+15", "+05:60", "+ab", "UTC+", "EST", "++5", "+-5", "Österreich", "+é1", "+1é", "UTC+５"] {
+            assert_eq!(parse_utc_offset(arg), None, "`{arg}` was accepted");
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::channel::Message;
+use serenity::model::event::ChannelPinsUpdateEvent;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// Most pins Discord allows in a channel.
+pub const MAX_PINS: usize = 50;
+
+/// How many of the oldest pins are archived once a channel reaches the limit, so it has room for
+/// a while instead of archiving one pin at a time.
+const ARCHIVED_AT_LIMIT: usize = 10;
+
+/// The guild's pin archive channel, if it has one.
+pub async fn pin_archive_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT pin_archive_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.pin_archive_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+fn pin_embed(branding: &Branding, message: &Message, guild_id: GuildId) -> CreateEmbed {
+    let mut embed = branding.embed()
+        .author(CreateEmbedAuthor::new(message.author.tag()).icon_url(message.author.face()))
+        .description(&message.content)
+        .field("Source", format!("{} [Jump to message]({})", message.channel_id.mention(), message.id.link(message.channel_id, Some(guild_id))), false)
+        .timestamp(message.timestamp);
+
+    let mut attachments = message.attachments.iter().peekable();
+
+    let image = attachments.next_if(|attachment| attachment.content_type.as_deref().is_some_and(|kind| kind.starts_with("image/")));
+
+    if let Some(image) = image {
+        embed = embed.image(&image.url);
+    }
+
+    if attachments.peek().is_some() {
+        let links = attachments.map(|attachment| format!("[{}]({})", attachment.filename, attachment.url)).collect::<Vec<_>>().join("\n");
+        embed = embed.field("Attachments", links, false);
+    }
+
+    // embeds can't be copied as they are, so their titles stand in for them
+    let embeds = message.embeds.iter().filter_map(|embed| embed.title.as_deref().or(embed.url.as_deref())).collect::<Vec<_>>();
+
+    if !embeds.is_empty() {
+        embed = embed.field("Embeds", embeds.join("\n"), false);
+    }
+
+    embed
+}
+
+/// Copies pins to the archive channel and unpins them, oldest first.
+pub async fn archive_pins(ctx: &Context, guild_id: GuildId, archive_id: ChannelId, pins: &[Message]) -> Result<(), CommandError> {
+    for pin in pins {
+        archive_id.send_message(ctx, CreateMessage::new().embed(pin_embed(&guild_branding(ctx, Some(guild_id)).await, pin, guild_id))).await?;
+        pin.unpin(ctx).await?;
+    }
+
+    Ok(())
+}
+
+/// Archives the oldest pins of a channel that has reached the pin limit, if its guild has a pin
+/// archive channel.
+pub async fn handle_pins_update(ctx: &Context, event: &ChannelPinsUpdateEvent) {
+    // unpinning also triggers this, which is what keeps it from archiving in a loop
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't archive pins: {why}");
+            return;
+        }
+    };
+
+    let result = async {
+        let Some(archive_id) = pin_archive_channel(&database, guild_id).await?.filter(|archive_id| *archive_id != event.channel_id) else {
+            return Ok(());
+        };
+
+        let mut pins = event.channel_id.pins(ctx).await?;
+
+        if pins.len() < MAX_PINS {
+            return Ok(());
+        }
+
+        // pins come newest first
+        pins.reverse();
+        pins.truncate(ARCHIVED_AT_LIMIT);
+
+        archive_pins(ctx, guild_id, archive_id, &pins).await
+    }.await;
+
+    if let Err(why) = result {
+        warn!("Couldn't archive the pins of channel {} in guild {guild_id}: {why}", event.channel_id);
+    }
+}
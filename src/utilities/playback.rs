@@ -0,0 +1,99 @@
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::prelude::Context;
+use songbird::input::Input;
+use tracing::warn;
+
+/// How audio is played next to whatever's already playing in the channel.
+#[derive(Clone, Copy)]
+pub enum Playback {
+    /// Right away, over anything else, like sound effects.
+    Overlap,
+    /// After everything queued before it, so speech isn't talked over.
+    Queue
+}
+
+/// The voice channel a member is in, if any.
+pub fn member_voice_channel(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<ChannelId> {
+    ctx.cache.guild(guild_id)?.voice_states.get(&user_id)?.channel_id
+}
+
+/// How many members other than bots are in a voice channel.
+pub fn listeners(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> usize {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return 0;
+    };
+
+    guild.voice_states.values()
+        .filter(|state| state.channel_id == Some(channel_id))
+        .filter(|state| !guild.members.get(&state.user_id).is_some_and(|member| member.user.bot))
+        .count()
+}
+
+/// Plays audio in a voice channel, joining it first. The bot only moves over from another
+/// channel once nobody's left listening there. Errors are worded for whoever asked.
+pub async fn play_in_voice(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, input: Input, playback: Playback) -> Result<(), String> {
+    let Some(manager) = songbird::get(ctx).await else {
+        return Err("Voice isn't set up for this bot.".to_string());
+    };
+
+    let current = match manager.get(guild_id) {
+        Some(call) => call.lock().await.current_channel().map(|channel| ChannelId::new(channel.0.get())),
+        None => None
+    };
+
+    if let Some(current) = current.filter(|current| *current != channel_id) {
+        if listeners(ctx, guild_id, current) > 0 {
+            return Err(format!("I'm already in <#{current}>, come over there or wait until it's empty."));
+        }
+    }
+
+    let call = if current == Some(channel_id) {
+        manager.get(guild_id)
+    } else {
+        match manager.join(guild_id, channel_id).await {
+            Ok(call) => Some(call),
+            Err(why) => {
+                warn!("Couldn't join voice channel {channel_id} in guild {guild_id}: {why}");
+                return Err(format!("I couldn't join <#{channel_id}>, please check I can connect and speak there."));
+            }
+        }
+    };
+
+    let Some(call) = call else {
+        return Err("I lost the voice connection, please try again.".to_string());
+    };
+
+    let mut call = call.lock().await;
+
+    match playback {
+        Playback::Overlap => {
+            call.play_input(input);
+        }
+        Playback::Queue => {
+            call.enqueue_input(input).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Leaves a guild's voice channel once nobody's left listening, after someone leaves or moves.
+pub async fn leave_if_alone(ctx: &Context, guild_id: GuildId) {
+    let Some(manager) = songbird::get(ctx).await else {
+        return;
+    };
+
+    let Some(call) = manager.get(guild_id) else {
+        return;
+    };
+
+    let current = call.lock().await.current_channel().map(|channel| ChannelId::new(channel.0.get()));
+
+    if current.is_some_and(|channel_id| listeners(ctx, guild_id, channel_id) > 0) {
+        return;
+    }
+
+    if let Err(why) = manager.remove(guild_id).await {
+        warn!("Couldn't leave voice in guild {guild_id}: {why}");
+    }
+}
@@ -0,0 +1,181 @@
+use serenity::builder::{CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// The custom IDs of poll buttons start with this, followed by the option's index.
+pub const POLL_ID_PREFIX: &str = "poll:";
+
+/// Discord's limit on button labels.
+const MAX_LABEL_LENGTH: usize = 80;
+const RESULT_BAR_LENGTH: usize = 10;
+
+/// One button per option, five to a row. Closed polls keep their buttons, disabled.
+pub fn poll_components(options: &[&str], closed: bool) -> Vec<CreateActionRow> {
+    let buttons: Vec<CreateButton> = options.iter()
+        .enumerate()
+        .map(|(index, option)| {
+            CreateButton::new(format!("{POLL_ID_PREFIX}{index}"))
+                .label(option.chars().take(MAX_LABEL_LENGTH).collect::<String>())
+                .style(ButtonStyle::Primary)
+                .disabled(closed)
+        })
+        .collect();
+
+    buttons.chunks(5).map(|row| CreateActionRow::Buttons(row.to_vec())).collect()
+}
+
+/// Records a vote from a poll button. Single-choice polls move the voter's vote to the option
+/// they clicked, multiple-choice polls toggle it, and clicking a voted option again takes the
+/// vote back.
+pub async fn handle_poll_vote(ctx: &Context, interaction: &ComponentInteraction) {
+    let Some(option) = interaction.data.custom_id.strip_prefix(POLL_ID_PREFIX).and_then(|index| index.parse::<i64>().ok()) else {
+        return;
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't count a poll vote: {why}");
+            return;
+        }
+    };
+
+    let content = match record_vote(&database, interaction, option).await {
+        Ok(content) => content,
+        Err(why) => {
+            warn!("Couldn't record a vote on poll {}: {why}", interaction.message.id);
+            "I couldn't record your vote, please try again.".to_string()
+        }
+    };
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+
+    if let Err(why) = interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await {
+        warn!("Couldn't respond to a vote on poll {}: {why}", interaction.message.id);
+    }
+}
+
+async fn record_vote(database: &SqlitePool, interaction: &ComponentInteraction, option: i64) -> Result<String, sqlx::Error> {
+    let (message_id, user_id) = (interaction.message.id.get() as i64, interaction.user.id.get() as i64);
+
+    let Some(poll) = sqlx::query!("SELECT options, multiple_choice, closed FROM polls WHERE message_id = ?", message_id)
+        .fetch_optional(database)
+        .await? else {
+        return Ok("This poll no longer exists.".to_string());
+    };
+
+    if poll.closed != 0 {
+        return Ok("This poll is closed.".to_string());
+    }
+
+    let options: Vec<&str> = poll.options.lines().collect();
+
+    let Some(label) = usize::try_from(option).ok().and_then(|index| options.get(index)) else {
+        return Ok("That option doesn't exist.".to_string());
+    };
+
+    let mut transaction = database.begin().await?;
+
+    let removed = sqlx::query!(
+        "DELETE FROM poll_votes WHERE message_id = ? AND user_id = ? AND option = ?",
+        message_id,
+        user_id,
+        option
+    ).execute(&mut *transaction).await?.rows_affected();
+
+    if removed == 0 {
+        if poll.multiple_choice == 0 {
+            sqlx::query!("DELETE FROM poll_votes WHERE message_id = ? AND user_id = ?", message_id, user_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO poll_votes (message_id, user_id, option) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+            message_id,
+            user_id,
+            option
+        ).execute(&mut *transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    if removed == 0 {
+        Ok(format!("You voted for **{label}**."))
+    } else {
+        Ok(format!("You took back your vote for **{label}**."))
+    }
+}
+
+/// Closes a poll, disabling its buttons and posting its results. Returns false if the poll
+/// doesn't exist or was already closed.
+pub async fn close_poll(ctx: &Context, database: &SqlitePool, message_id: MessageId) -> Result<bool, CommandError> {
+    let db_message_id = message_id.get() as i64;
+
+    let Some(poll) = sqlx::query!(
+        "UPDATE polls SET closed = 1 WHERE message_id = ? AND closed = 0 RETURNING guild_id, channel_id, question, options",
+        db_message_id
+    ).fetch_optional(database).await? else {
+        return Ok(false);
+    };
+
+    let counts = sqlx::query!(
+        r#"SELECT option, COUNT(*) AS "votes!: i64" FROM poll_votes WHERE message_id = ? GROUP BY option"#,
+        db_message_id
+    ).fetch_all(database).await?;
+
+    let voters = sqlx::query!(
+        r#"SELECT COUNT(DISTINCT user_id) AS "voters!: i64" FROM poll_votes WHERE message_id = ?"#,
+        db_message_id
+    ).fetch_one(database).await?.voters;
+
+    let options: Vec<&str> = poll.options.lines().collect();
+    let total: i64 = counts.iter().map(|row| row.votes).sum();
+
+    let results = options.iter()
+        .enumerate()
+        .map(|(index, option)| {
+            let votes = counts.iter().find(|row| row.option == index as i64).map_or(0, |row| row.votes);
+            let share = if total == 0 { 0.0 } else { votes as f64 / total as f64 };
+            let filled = (share * RESULT_BAR_LENGTH as f64).round() as usize;
+
+            format!(
+                "**{option}**\n{}{} {votes} vote(s), {:.0}%",
+                "▓".repeat(filled),
+                "░".repeat(RESULT_BAR_LENGTH - filled),
+                share * 100.0
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let channel_id = ChannelId::new(poll.channel_id as u64);
+
+    let embed = branded_embed(ctx, Some(GuildId::new(poll.guild_id as u64))).await
+        .title(format!("Results: {}", poll.question))
+        .description(results)
+        .field("Voters", voters.to_string(), true);
+
+    // the poll message may have been deleted, the results are still worth posting
+    drop(channel_id.edit_message(ctx, message_id, EditMessage::new().components(poll_components(&options, true))).await);
+
+    let builder = CreateMessage::new()
+        .embed(embed.clone())
+        .reference_message((channel_id, message_id));
+
+    if channel_id.send_message(ctx, builder).await.is_err() {
+        channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,152 @@
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::model::id::{GuildId, SkuId};
+use serenity::prelude::Context;
+use tracing::error;
+
+use crate::utilities::global_data::{DatabaseConnectionContainer, GuildPremium, PremiumContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PremiumTier {
+    Free,
+    Premium,
+    PremiumPlus
+}
+
+impl PremiumTier {
+    pub fn from_level(level: i64) -> PremiumTier {
+        match level {
+            2.. => PremiumTier::PremiumPlus,
+            1 => PremiumTier::Premium,
+            _ => PremiumTier::Free
+        }
+    }
+
+    pub fn level(self) -> i64 {
+        match self {
+            PremiumTier::Free => 0,
+            PremiumTier::Premium => 1,
+            PremiumTier::PremiumPlus => 2
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PremiumTier::Free => "Free",
+            PremiumTier::Premium => "Premium",
+            PremiumTier::PremiumPlus => "Premium+"
+        }
+    }
+
+    /// Maximum number of self-assignable role panels a guild can have at once.
+    pub fn max_role_panels(self) -> usize {
+        match self {
+            PremiumTier::Free => 3,
+            PremiumTier::Premium => 10,
+            PremiumTier::PremiumPlus => 25
+        }
+    }
+
+    /// Longest a timed poll can stay open before closing on its own.
+    pub fn max_timed_duration(self) -> Duration {
+        match self {
+            PremiumTier::Free => Duration::days(7),
+            PremiumTier::Premium => Duration::days(30),
+            PremiumTier::PremiumPlus => Duration::days(90)
+        }
+    }
+
+    /// Maps a Discord SKU onto a tier using the `PREMIUM_SKU_ID` and `PREMIUM_PLUS_SKU_ID`
+    /// environment variables.
+    pub fn from_sku(sku_id: SkuId) -> Option<PremiumTier> {
+        let matches = |key: &str| env::var(key).ok().and_then(|id| id.parse::<u64>().ok()) == Some(sku_id.get());
+
+        if matches("PREMIUM_PLUS_SKU_ID") {
+            Some(PremiumTier::PremiumPlus)
+        } else if matches("PREMIUM_SKU_ID") {
+            Some(PremiumTier::Premium)
+        } else {
+            None
+        }
+    }
+}
+
+impl GuildPremium {
+    /// The tier currently in effect, treating expired grants as free.
+    pub fn effective_tier(&self) -> PremiumTier {
+        match self.expires_at {
+            Some(expiry) if expiry <= Utc::now() => PremiumTier::Free,
+            _ => self.tier
+        }
+    }
+}
+
+/// Looks up the premium tier in effect for a guild. Direct messages are always on the free tier.
+pub async fn guild_tier(ctx: &Context, guild_id: Option<GuildId>) -> PremiumTier {
+    let Some(guild_id) = guild_id else {
+        return PremiumTier::Free;
+    };
+
+    let premium = match get_data::<PremiumContainer>(ctx).await {
+        Ok(premium) => premium,
+        Err(why) => {
+            error!("Couldn't read the premium tier of guild {guild_id}: {why}");
+            return PremiumTier::Free;
+        }
+    };
+
+    let premium = premium.read().await;
+
+    premium.get(&guild_id.get()).map(GuildPremium::effective_tier).unwrap_or(PremiumTier::Free)
+}
+
+pub fn parse_expiry(expires_at: Option<String>) -> Option<DateTime<Utc>> {
+    expires_at
+        .and_then(|expiry| DateTime::parse_from_rfc3339(&expiry).ok())
+        .map(|expiry| expiry.with_timezone(&Utc))
+}
+
+/// Writes a guild's premium grant through to both the database and the cache.
+pub async fn set_guild_premium(ctx: &Context, guild_id: GuildId, premium: GuildPremium) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+    let tier = premium.tier.level();
+    let entitlement_id = premium.entitlement_id.map(|id| id as i64);
+    let expires_at = premium.expires_at.map(|expiry| expiry.to_rfc3339());
+
+    sqlx::query!(
+        "INSERT INTO guild_premium (guild_id, tier, source, entitlement_id, expires_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id) DO UPDATE SET
+            tier = excluded.tier,
+            source = excluded.source,
+            entitlement_id = excluded.entitlement_id,
+            expires_at = excluded.expires_at",
+        db_guild_id,
+        tier,
+        premium.source,
+        entitlement_id,
+        expires_at
+    ).execute(&database).await?;
+
+    get_data::<PremiumContainer>(ctx).await?.write().await.insert(guild_id.get(), premium);
+
+    Ok(())
+}
+
+/// Removes a guild's premium grant, returning it to the free tier.
+pub async fn clear_guild_premium(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let db_guild_id = guild_id.get() as i64;
+
+    sqlx::query!(
+        "DELETE FROM guild_premium WHERE guild_id = ?",
+        db_guild_id
+    ).execute(&database).await?;
+
+    get_data::<PremiumContainer>(ctx).await?.write().await.remove(&guild_id.get());
+
+    Ok(())
+}
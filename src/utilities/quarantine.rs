@@ -0,0 +1,154 @@
+use chrono::Utc;
+use serenity::builder::EditMember;
+use serenity::framework::standard::CommandError;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::automod::parse_ids;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// The role the guild holds quarantined members in, if it has one.
+pub async fn quarantine_role(database: &SqlitePool, guild_id: GuildId) -> Result<Option<RoleId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let role_id = sqlx::query!("SELECT quarantine_role_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.quarantine_role_id);
+
+    Ok(role_id.map(|role_id| RoleId::new(role_id as u64)))
+}
+
+/// Whether a member is quarantined in the guild.
+pub async fn is_quarantined(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<bool, sqlx::Error> {
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let row = sqlx::query!("SELECT user_id FROM quarantined_members WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.is_some())
+}
+
+/// Takes every role a member has away, keeping the ones managed by integrations which can't be,
+/// and gives them the quarantine role. The roles are saved first so they can't be lost. Returns
+/// how many roles were taken away.
+pub async fn quarantine_member(ctx: &Context, database: &SqlitePool, member: &Member, role_id: RoleId, moderator: UserId, audit: &str) -> Result<usize, CommandError> {
+    let (kept, taken): (Vec<RoleId>, Vec<RoleId>) = {
+        let guild = ctx.cache.guild(member.guild_id);
+
+        member.roles.iter()
+            .filter(|member_role| **member_role != role_id)
+            .partition(|member_role| guild.as_ref().and_then(|guild| guild.roles.get(member_role)).is_some_and(|role| role.managed))
+    };
+
+    let (guild_id, user_id, moderator_id) = (member.guild_id.get() as i64, member.user.id.get() as i64, moderator.get() as i64);
+    let roles = taken.iter().map(|role| role.get().to_string()).collect::<Vec<_>>().join(",");
+    let quarantined_at = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        "INSERT INTO quarantined_members (guild_id, user_id, roles, moderator_id, quarantined_at) VALUES (?, ?, ?, ?, ?)",
+        guild_id,
+        user_id,
+        roles,
+        moderator_id,
+        quarantined_at
+    ).execute(database).await?;
+
+    let builder = EditMember::new()
+        .roles(kept.into_iter().chain([role_id]).collect::<Vec<_>>())
+        .audit_log_reason(audit);
+
+    if let Err(why) = member.guild_id.edit_member(ctx, member.user.id, builder).await {
+        sqlx::query!("DELETE FROM quarantined_members WHERE guild_id = ? AND user_id = ?", guild_id, user_id).execute(database).await?;
+        return Err(why.into());
+    }
+
+    Ok(taken.len())
+}
+
+/// Lets a member out of quarantine, giving back the roles they had that still exist and can be
+/// given out. Returns `None` if they weren't quarantined, or how many roles were given back.
+pub async fn release_member(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId, audit: &str) -> Result<Option<usize>, CommandError> {
+    let (db_guild_id, db_user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let Some(row) = sqlx::query!("SELECT roles FROM quarantined_members WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id)
+        .fetch_optional(database)
+        .await? else {
+        return Ok(None);
+    };
+
+    let member = guild_id.member(ctx, user_id).await?;
+    let quarantine_role = quarantine_role(database, guild_id).await?;
+    let bot_id = ctx.cache.current_user().id;
+
+    let restored = {
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+            return Err("This server isn't cached yet, please try again in a moment.".into());
+        };
+
+        let bot_position = guild.members.get(&bot_id)
+            .and_then(|bot| guild.member_highest_role(bot))
+            .map_or(0, |highest| highest.position);
+
+        // roles deleted or moved above the bot since can't be given back
+        parse_ids(&row.roles).into_iter()
+            .map(RoleId::new)
+            .filter(|role_id| guild.roles.get(role_id).is_some_and(|role| !role.managed && role.position < bot_position))
+            .collect::<Vec<_>>()
+    };
+
+    let roles = member.roles.iter()
+        .copied()
+        .filter(|role_id| Some(*role_id) != quarantine_role)
+        .chain(restored.iter().copied())
+        .collect::<Vec<_>>();
+
+    member.guild_id.edit_member(ctx, user_id, EditMember::new().roles(roles).audit_log_reason(audit)).await?;
+
+    sqlx::query!("DELETE FROM quarantined_members WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id)
+        .execute(database)
+        .await?;
+
+    Ok(Some(restored.len()))
+}
+
+/// Puts members who left while quarantined straight back into quarantine when they rejoin.
+/// Returns whether they were.
+pub async fn handle_quarantine_rejoin(ctx: &Context, member: &Member) -> bool {
+    let guild_id = member.guild_id;
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check member {} for a quarantine: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    let role_id = match is_quarantined(&database, guild_id, member.user.id).await {
+        Ok(true) => quarantine_role(&database, guild_id).await,
+        Ok(false) => return false,
+        Err(why) => Err(why)
+    };
+
+    let role_id = match role_id {
+        Ok(Some(role_id)) => role_id,
+        Ok(None) => return false,
+        Err(why) => {
+            error!("Failed to check whether user {} is quarantined in guild {guild_id}: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    if let Err(why) = ctx.http.add_member_role(guild_id, member.user.id, role_id, Some("Rejoined while quarantined")).await {
+        warn!("Couldn't quarantine user {} again in guild {guild_id}: {why}", member.user.id);
+        return false;
+    }
+
+    true
+}
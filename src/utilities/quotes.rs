@@ -0,0 +1,118 @@
+use chrono::DateTime;
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter};
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::Timestamp;
+use sqlx::SqlitePool;
+
+use crate::utilities::branding::Branding;
+
+/// A saved quote.
+pub struct Quote {
+    pub id: i64,
+    pub author_id: UserId,
+    pub author_name: String,
+    pub content: String,
+    pub image_url: Option<String>,
+    pub link: String,
+    pub quoted_by: UserId,
+    pub sent_at: String
+}
+
+/// The guild's quotes channel, if it has one.
+pub async fn quotes_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT quotes_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.quotes_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+/// The first image attached to or embedded in a message, which is kept along with its text.
+pub fn message_image(message: &Message) -> Option<String> {
+    message.attachments.iter()
+        .find(|attachment| attachment.content_type.as_deref().is_some_and(|kind| kind.starts_with("image/")))
+        .map(|attachment| attachment.url.clone())
+        .or_else(|| message.embeds.iter().find_map(|embed| embed.image.as_ref().map(|image| image.url.clone())))
+}
+
+/// Saves a message as a quote, returning its ID, or `None` if it was already quoted.
+pub async fn add_quote(database: &SqlitePool, guild_id: GuildId, message: &Message, quoted_by: UserId) -> Result<Option<i64>, sqlx::Error> {
+    let (db_guild_id, message_id, author_id, quoted_by) = (guild_id.get() as i64, message.id.get() as i64, message.author.id.get() as i64, quoted_by.get() as i64);
+    let (author_name, image_url) = (message.author.display_name().to_string(), message_image(message));
+    let (link, sent_at) = (message.id.link(message.channel_id, Some(guild_id)), message.timestamp.to_rfc3339().unwrap_or_default());
+
+    let inserted = sqlx::query!(
+        "INSERT INTO quotes (guild_id, message_id, author_id, author_name, content, image_url, link, quoted_by, sent_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (guild_id, message_id) DO NOTHING",
+        db_guild_id,
+        message_id,
+        author_id,
+        author_name,
+        message.content,
+        image_url,
+        link,
+        quoted_by,
+        sent_at
+    ).execute(database).await?;
+
+    Ok((inserted.rows_affected() > 0).then(|| inserted.last_insert_rowid()))
+}
+
+pub async fn get_quote(database: &SqlitePool, guild_id: GuildId, id: i64) -> Result<Option<Quote>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT id, author_id, author_name, content, image_url, link, quoted_by, sent_at FROM quotes WHERE guild_id = ? AND id = ?",
+        guild_id,
+        id
+    ).fetch_optional(database).await?;
+
+    Ok(row.map(|row| Quote {
+        id: row.id,
+        author_id: UserId::new(row.author_id as u64),
+        author_name: row.author_name,
+        content: row.content,
+        image_url: row.image_url,
+        link: row.link,
+        quoted_by: UserId::new(row.quoted_by as u64),
+        sent_at: row.sent_at
+    }))
+}
+
+/// A random quote from the guild, only from one member if given.
+pub async fn random_quote(database: &SqlitePool, guild_id: GuildId, author_id: Option<UserId>) -> Result<Option<Quote>, sqlx::Error> {
+    let (db_guild_id, author_id) = (guild_id.get() as i64, author_id.map(|author_id| author_id.get() as i64));
+
+    let id = sqlx::query!(
+        "SELECT id AS \"id!\" FROM quotes WHERE guild_id = ? AND (? IS NULL OR author_id = ?) ORDER BY RANDOM() LIMIT 1",
+        db_guild_id,
+        author_id,
+        author_id
+    ).fetch_optional(database).await?;
+
+    match id {
+        Some(row) => get_quote(database, guild_id, row.id).await,
+        None => Ok(None)
+    }
+}
+
+pub fn quote_embed(branding: &Branding, quote: &Quote) -> CreateEmbed {
+    let mut embed = branding.embed()
+        .author(CreateEmbedAuthor::new(&quote.author_name))
+        .description(format!("{}\n\n- <@{}>, quoted by <@{}> | [Jump to message]({})", quote.content, quote.author_id, quote.quoted_by, quote.link))
+        .footer(CreateEmbedFooter::new(format!("Quote #{}", quote.id)));
+
+    if let Some(sent_at) = DateTime::parse_from_rfc3339(&quote.sent_at).ok().and_then(|sent_at| Timestamp::from_unix_timestamp(sent_at.timestamp()).ok()) {
+        embed = embed.timestamp(sent_at);
+    }
+
+    if let Some(image_url) = &quote.image_url {
+        embed = embed.image(image_url);
+    }
+
+    embed
+}
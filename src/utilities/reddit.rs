@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use reqwest::Client as Reqwest;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::model::Timestamp;
+use serenity::model::channel::Channel;
+use serenity::model::id::ChannelId;
+use serenity::prelude::Context;
+use tracing::{error, warn};
+
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::global_data::{DatabaseConnectionContainer, ReqwestClientContainer};
+use crate::utilities::errors::get_data;
+
+/// The orders a subreddit can be watched in.
+pub const SORTS: [&str; 2] = ["new", "hot"];
+
+/// How often every watched subreddit is checked for new posts.
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Pause between subreddits, so a poll doesn't burst against Reddit's rate limit.
+const REQUEST_DELAY: Duration = Duration::from_secs(2);
+
+/// Most posts mirrored from one subreddit per poll, so a busy subreddit can't flood a channel.
+const MAX_POSTS_PER_POLL: usize = 5;
+
+const MAX_TEXT_LENGTH: usize = 400;
+
+/// Reddit rejects requests without a descriptive user agent.
+const USER_AGENT: &str = "graf_zeppelin Discord bot (subreddit feeds)";
+
+#[derive(Deserialize)]
+struct Listing {
+    data: ListingData
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    children: Vec<ListingChild>
+}
+
+#[derive(Deserialize)]
+struct ListingChild {
+    data: Post
+}
+
+/// A post in a subreddit's listing.
+#[derive(Deserialize)]
+pub struct Post {
+    title: String,
+    author: String,
+    permalink: String,
+    url: Option<String>,
+    #[serde(default)]
+    selftext: String,
+    #[serde(default)]
+    over_18: bool,
+    #[serde(default)]
+    stickied: bool,
+    link_flair_text: Option<String>,
+    created_utc: f64
+}
+
+impl Post {
+    /// Whether the post can be mirrored into a channel, going by the channel's age restriction
+    /// and the flairs it's limited to.
+    fn allowed(&self, nsfw_channel: bool, flairs: &[&str]) -> bool {
+        if self.over_18 && !nsfw_channel {
+            return false;
+        }
+
+        flairs.is_empty() || self.link_flair_text.as_deref().is_some_and(|flair| flairs.contains(&flair.trim().to_lowercase().as_str()))
+    }
+
+    fn embed(&self, branding: &Branding, subreddit: &str) -> CreateEmbed {
+        let mut embed = branding.embed()
+            .title(self.title.chars().take(256).collect::<String>())
+            .url(format!("https://www.reddit.com{}", self.permalink))
+            .author(CreateEmbedAuthor::new(format!("u/{}", self.author)))
+            .footer(CreateEmbedFooter::new(format!("r/{subreddit}")));
+
+        if !self.selftext.is_empty() {
+            let mut text = self.selftext.chars().take(MAX_TEXT_LENGTH).collect::<String>();
+
+            if self.selftext.chars().count() > MAX_TEXT_LENGTH {
+                text.push_str("...");
+            }
+
+            embed = embed.description(text);
+        }
+
+        if let Some(flair) = &self.link_flair_text {
+            embed = embed.field("Flair", flair, true);
+        }
+
+        let image = self.url.as_deref().filter(|url| {
+            let url = url.to_lowercase();
+            [".png", ".jpg", ".jpeg", ".gif", ".webp"].iter().any(|extension| url.ends_with(extension))
+        });
+
+        if let Some(image) = image {
+            embed = embed.image(image);
+        }
+
+        if let Ok(created) = Timestamp::from_unix_timestamp(self.created_utc as i64) {
+            embed = embed.timestamp(created);
+        }
+
+        embed
+    }
+}
+
+/// Why a subreddit's posts couldn't be fetched.
+pub enum FetchError {
+    /// The subreddit doesn't exist, or is private or banned.
+    Unavailable,
+    Request(reqwest::Error)
+}
+
+/// Whether a name could be a subreddit: 3 to 21 letters, numbers and underscores.
+pub fn is_subreddit_name(name: &str) -> bool {
+    (3..=21).contains(&name.len()) && name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Fetches the first page of a subreddit in the given order, leaving out pinned posts.
+pub async fn fetch_posts(client: &Reqwest, subreddit: &str, sort: &str) -> Result<Vec<Post>, FetchError> {
+    let response = client.get(format!("https://www.reddit.com/r/{subreddit}/{sort}.json"))
+        .query(&[("limit", "25"), ("raw_json", "1")])
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await
+        .map_err(FetchError::Request)?;
+
+    // missing subreddits redirect to search, and private or banned ones are forbidden
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::FORBIDDEN) || response.url().path().starts_with("/subreddits/search") {
+        return Err(FetchError::Unavailable);
+    }
+
+    let listing = response.error_for_status()
+        .map_err(FetchError::Request)?
+        .json::<Listing>()
+        .await
+        .map_err(FetchError::Request)?;
+
+    Ok(listing.data.children.into_iter().map(|child| child.data).filter(|post| !post.stickied).collect())
+}
+
+/// When the newest of some posts was made, so only posts after it are mirrored.
+pub fn newest_post(posts: &[Post]) -> f64 {
+    posts.iter().map(|post| post.created_utc).fold(0.0, f64::max)
+}
+
+/// Periodically mirrors new posts from every watched subreddit into its channel.
+pub async fn reddit_feed_loop(ctx: Context) {
+    let (database, client) = match (get_data::<DatabaseConnectionContainer>(&ctx).await, get_data::<ReqwestClientContainer>(&ctx).await) {
+        (Ok(database), Ok(client)) => (database, client),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't start mirroring subreddits: {why}");
+            return;
+        }
+    };
+
+    loop {
+        let subscriptions = match sqlx::query!("SELECT channel_id, subreddit, sort, flairs, last_post_created FROM reddit_subscriptions").fetch_all(&database).await {
+            Ok(subscriptions) => subscriptions,
+            Err(why) => {
+                error!("Failed to fetch subreddit subscriptions: {why}");
+                Vec::new()
+            }
+        };
+
+        for subscription in subscriptions {
+            let channel_id = ChannelId::new(subscription.channel_id as u64);
+
+            let (nsfw_channel, guild_id) = match channel_id.to_channel(&ctx).await {
+                Ok(Channel::Guild(channel)) => (channel.nsfw, Some(channel.guild_id)),
+                Ok(_) => (false, None),
+                Err(why) => {
+                    warn!("Couldn't fetch channel {channel_id} watching r/{}: {why}", subscription.subreddit);
+                    continue;
+                }
+            };
+
+            let posts = match fetch_posts(&client, &subscription.subreddit, &subscription.sort).await {
+                Ok(posts) => posts,
+                Err(FetchError::Unavailable) => {
+                    warn!("r/{} watched in channel {channel_id} is unavailable", subscription.subreddit);
+                    continue;
+                }
+                Err(FetchError::Request(why)) => {
+                    warn!("Failed to fetch r/{}: {why}", subscription.subreddit);
+                    continue;
+                }
+            };
+
+            let flairs = subscription.flairs.as_deref().map(|flairs| flairs.split(',').collect::<Vec<_>>()).unwrap_or_default();
+
+            let mut new_posts = posts.iter()
+                .filter(|post| post.created_utc > subscription.last_post_created)
+                .collect::<Vec<_>>();
+
+            // oldest first, so the channel reads in order
+            new_posts.sort_by(|a, b| a.created_utc.total_cmp(&b.created_utc));
+
+            let allowed = new_posts.iter().filter(|post| post.allowed(nsfw_channel, &flairs)).collect::<Vec<_>>();
+            let skipped = allowed.len().saturating_sub(MAX_POSTS_PER_POLL);
+
+            let branding = guild_branding(&ctx, guild_id).await;
+
+            for post in allowed.into_iter().skip(skipped) {
+                if let Err(why) = channel_id.send_message(&ctx, CreateMessage::new().embed(post.embed(&branding, &subscription.subreddit))).await {
+                    warn!("Couldn't mirror a post from r/{} into channel {channel_id}: {why}", subscription.subreddit);
+                }
+            }
+
+            // the cursor moves past filtered posts too, so they aren't looked at again
+            if let Some(last) = new_posts.last() {
+                let (created, subreddit) = (last.created_utc, &subscription.subreddit);
+
+                if let Err(why) = sqlx::query!(
+                    "UPDATE reddit_subscriptions SET last_post_created = ? WHERE channel_id = ? AND subreddit = ?",
+                    created,
+                    subscription.channel_id,
+                    subreddit
+                ).execute(&database).await {
+                    error!("Failed to save the cursor of r/{subreddit} in channel {channel_id}: {why}");
+                }
+            }
+
+            tokio::time::sleep(REQUEST_DELAY).await;
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
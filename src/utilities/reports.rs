@@ -0,0 +1,292 @@
+use chrono::Utc;
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditInteractionResponse, GetMessages};
+use serenity::framework::standard::CommandResult;
+use serenity::model::application::{ButtonStyle, CommandInteraction, ComponentInteraction, ResolvedTarget};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use serenity::model::Permissions;
+use serenity::model::Timestamp;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::moderation::{ModAction, add_warning, apply_action, check_target};
+use crate::utilities::errors::get_data;
+
+/// The name of the message context menu command members report messages with.
+pub const REPORT_COMMAND: &str = "Report Message";
+
+/// Prefix of the action buttons on reports, followed by the action and the report's ID.
+pub const REPORT_ID_PREFIX: &str = "report:";
+
+/// Messages sent before the reported one that are included in the report.
+const CONTEXT_MESSAGES: u8 = 5;
+
+/// The guild's reports channel, if it takes reports.
+pub async fn reports_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT reports_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.reports_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    let mut truncated = text.chars().take(length - 3).collect::<String>();
+    truncated.push_str("...");
+
+    truncated
+}
+
+/// The buttons under an open report. The delete button goes once the message is deleted.
+fn report_components(id: i64, message_deleted: bool) -> Vec<CreateActionRow> {
+    let mut buttons = Vec::new();
+
+    if !message_deleted {
+        buttons.push(CreateButton::new(format!("{REPORT_ID_PREFIX}delete:{id}")).label("Delete message").style(ButtonStyle::Secondary));
+    }
+
+    buttons.extend([
+        CreateButton::new(format!("{REPORT_ID_PREFIX}warn:{id}")).label("Warn author").style(ButtonStyle::Primary),
+        CreateButton::new(format!("{REPORT_ID_PREFIX}ban:{id}")).label("Ban author").style(ButtonStyle::Danger),
+        CreateButton::new(format!("{REPORT_ID_PREFIX}dismiss:{id}")).label("Dismiss").style(ButtonStyle::Secondary)
+    ]);
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Sends the message a member reported through the context menu to the guild's reports channel,
+/// along with the messages before it.
+pub async fn report_message(ctx: &Context, command: &CommandInteraction) -> CommandResult {
+    let (Some(guild_id), Some(ResolvedTarget::Message(message))) = (command.guild_id, command.data.target()) else {
+        return reply(ctx, command, "Messages can only be reported in servers.").await;
+    };
+
+    if message.author.id == command.user.id {
+        return reply(ctx, command, "You can't report your own messages.").await;
+    }
+
+    if message.author.bot {
+        return reply(ctx, command, "Messages from bots can't be reported.").await;
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(channel_id) = reports_channel(&database, guild_id).await? else {
+        return reply(ctx, command, "This server doesn't take reports.").await;
+    };
+
+    let (db_guild_id, message_id, author_id, reporter_id) = (guild_id.get() as i64, message.id.get() as i64, message.author.id.get() as i64, command.user.id.get() as i64);
+
+    let already_reported = sqlx::query!(
+        "SELECT id FROM reports WHERE guild_id = ? AND message_id = ? AND reporter_id = ?",
+        db_guild_id,
+        message_id,
+        reporter_id
+    ).fetch_optional(&database).await?.is_some();
+
+    if already_reported {
+        return reply(ctx, command, "You already reported this message.").await;
+    }
+
+    // fetching the context can take a moment
+    command.defer_ephemeral(ctx).await?;
+
+    let mut context = message.channel_id.messages(ctx, GetMessages::new().before(message.id).limit(CONTEXT_MESSAGES)).await.unwrap_or_default();
+    context.reverse();
+
+    let (db_channel_id, created_at) = (message.channel_id.get() as i64, Utc::now().to_rfc3339());
+
+    let id = sqlx::query!(
+        "INSERT INTO reports (guild_id, channel_id, message_id, author_id, reporter_id, content, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        db_guild_id,
+        db_channel_id,
+        message_id,
+        author_id,
+        reporter_id,
+        message.content,
+        created_at
+    ).execute(&database).await?.last_insert_rowid();
+
+    let content = match message.content.trim() {
+        "" => "*No text*".to_string(),
+        content => truncate(content, 2000)
+    };
+
+    let mut embed = branded_embed(ctx, command.guild_id).await
+        .title(format!("Report #{id}"))
+        .author(CreateEmbedAuthor::new(message.author.tag()).icon_url(message.author.face()))
+        .description(content)
+        .field("Author", format!("{} ({})", message.author.id.mention(), message.author.id), true)
+        .field("Reported by", command.user.id.mention().to_string(), true)
+        .field("Message", format!("{} [Jump to message]({})", message.channel_id.mention(), message.link()), true)
+        .timestamp(Timestamp::now());
+
+    if !message.attachments.is_empty() {
+        let links = message.attachments.iter().map(|attachment| format!("[{}]({})", attachment.filename, attachment.url)).collect::<Vec<_>>().join("\n");
+        embed = embed.field("Attachments", truncate(&links, 1024), false);
+    }
+
+    if !context.is_empty() {
+        let lines = context.iter()
+            .map(|previous| format!("**{}**: {}", previous.author.name, truncate(&previous.content.replace('\n', " "), 150)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        embed = embed.field("Before it", truncate(&lines, 1024), false);
+    }
+
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .components(report_components(id, false));
+
+    let content = match channel_id.send_message(ctx, builder).await {
+        Ok(_) => "Thanks, your report was sent to the moderators.",
+        Err(why) => {
+            warn!("Couldn't send report #{id} to channel {channel_id}: {why}");
+            "I couldn't send your report to the moderators, please let them know another way."
+        }
+    };
+
+    command.edit_response(ctx, EditInteractionResponse::new().content(content)).await?;
+
+    Ok(())
+}
+
+async fn reply(ctx: &Context, command: &CommandInteraction, content: &str) -> CommandResult {
+    let response = CreateInteractionResponseMessage::new().content(content).ephemeral(true);
+    command.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
+
+    Ok(())
+}
+
+/// Carries out the action a moderator picked on a report.
+pub async fn handle_report_action(ctx: &Context, interaction: &ComponentInteraction) {
+    let result = match report_action(ctx, interaction).await {
+        Ok(Ok(response)) => interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(response)).await,
+        Ok(Err(why)) => {
+            let response = CreateInteractionResponseMessage::new().content(why).ephemeral(true);
+            interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await
+        }
+        Err(why) => {
+            warn!("Couldn't act on report message {}: {why}", interaction.message.id);
+
+            let response = CreateInteractionResponseMessage::new().content("Something went wrong, please try again.").ephemeral(true);
+            interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await
+        }
+    };
+
+    if let Err(why) = result {
+        warn!("Couldn't respond to an action on report message {}: {why}", interaction.message.id);
+    }
+}
+
+/// Returns the updated report, or why the action couldn't be taken.
+async fn report_action(ctx: &Context, interaction: &ComponentInteraction) -> Result<Result<CreateInteractionResponseMessage, String>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some((action, id)) = interaction.data.custom_id.strip_prefix(REPORT_ID_PREFIX).and_then(|rest| rest.split_once(':')) else {
+        return Ok(Err("That button isn't valid anymore.".to_string()));
+    };
+
+    let (Some(guild_id), Ok(id)) = (interaction.guild_id, id.parse::<i64>()) else {
+        return Ok(Err("That button isn't valid anymore.".to_string()));
+    };
+
+    let permission = match action {
+        "delete" | "dismiss" => Permissions::MANAGE_MESSAGES,
+        "warn" => Permissions::MODERATE_MEMBERS,
+        "ban" => Permissions::BAN_MEMBERS,
+        _ => return Ok(Err("That button isn't valid anymore.".to_string()))
+    };
+
+    if !interaction.member.as_ref().and_then(|member| member.permissions).is_some_and(|permissions| permissions.contains(permission)) {
+        return Ok(Err(format!("You need the {permission} permission to do that.")));
+    }
+
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await.map_err(|why| why.to_string())?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let Some(report) = sqlx::query!(
+        "SELECT channel_id, message_id, author_id, outcome FROM reports WHERE id = ? AND guild_id = ?",
+        id,
+        db_guild_id
+    ).fetch_optional(&database).await? else {
+        return Ok(Err("This report no longer exists.".to_string()));
+    };
+
+    if report.outcome.is_some() {
+        return Ok(Err("This report was already closed.".to_string()));
+    }
+
+    let moderator = &interaction.user;
+    let author_id = UserId::new(report.author_id as u64);
+    let reason = format!("Reported message (report #{id})");
+
+    let (line, outcome) = match action {
+        "delete" => {
+            let (channel_id, message_id) = (ChannelId::new(report.channel_id as u64), MessageId::new(report.message_id as u64));
+
+            if let Err(why) = channel_id.delete_message(ctx, message_id).await {
+                return Ok(Err(format!("I couldn't delete the message: {why}")));
+            }
+
+            sqlx::query!("UPDATE reports SET message_deleted = 1 WHERE id = ?", id).execute(&database).await?;
+
+            (format!("🗑️ Message deleted by {}", moderator.id.mention()), None)
+        }
+        "warn" => {
+            if let Err(why) = check_target(ctx, guild_id, moderator.id, author_id, ModAction::Warn, Permissions::empty()).await {
+                return Ok(Err(why));
+            }
+
+            let target = author_id.to_user(ctx).await?;
+            let warning = add_warning(ctx, guild_id, &target, moderator, Some(&reason)).await?;
+
+            let escalation = match warning.escalation {
+                Some((action, Ok(case))) => format!(", then automatically {} (case #{case})", action.past_tense()),
+                _ => String::new()
+            };
+
+            (format!("⚠️ Author warned by {} (case #{}){escalation}", moderator.id.mention(), warning.case), Some("warned"))
+        }
+        "ban" => {
+            if let Err(why) = check_target(ctx, guild_id, moderator.id, author_id, ModAction::Ban, Permissions::BAN_MEMBERS).await {
+                return Ok(Err(why));
+            }
+
+            let target = author_id.to_user(ctx).await?;
+            let case = apply_action(ctx, guild_id, &target, moderator, ModAction::Ban, None, Some(&reason)).await?;
+
+            (format!("🔨 Author banned by {} (case #{case})", moderator.id.mention()), Some("banned"))
+        }
+        _ => (format!("Dismissed by {}", moderator.id.mention()), Some("dismissed"))
+    };
+
+    if let Some(outcome) = outcome {
+        let moderator_id = moderator.id.get() as i64;
+
+        sqlx::query!("UPDATE reports SET outcome = ?, handled_by = ? WHERE id = ?", outcome, moderator_id, id)
+            .execute(&database)
+            .await?;
+    }
+
+    let embed = interaction.message.embeds.first()
+        .map_or_else(CreateEmbed::new, |embed| CreateEmbed::from(embed.clone()))
+        .field("Action", line, false);
+
+    // closed reports lose their buttons, and deleting the message leaves the rest
+    let components = match outcome {
+        Some(_) => Vec::new(),
+        None => report_components(id, true)
+    };
+
+    Ok(Ok(CreateInteractionResponseMessage::new().embed(embed).components(components)))
+}
@@ -0,0 +1,104 @@
+use serenity::builder::{CreateActionRow, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption};
+use serenity::model::application::{ComponentInteraction, ComponentInteractionDataKind};
+use serenity::model::id::RoleId;
+use serenity::prelude::{Context, Mentionable};
+use tracing::{warn, error};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// The custom ID of every role menu's select menu.
+pub const ROLE_MENU_ID: &str = "rolemenu";
+
+/// The select menu of a role menu, offering the given roles by name.
+pub fn role_menu_components(roles: &[(RoleId, String)]) -> Vec<CreateActionRow> {
+    let options = roles.iter()
+        .map(|(role_id, name)| CreateSelectMenuOption::new(name, role_id.to_string()))
+        .collect();
+
+    // nothing selected takes every role in the menu away
+    let menu = CreateSelectMenu::new(ROLE_MENU_ID, CreateSelectMenuKind::String { options })
+        .placeholder("Pick your roles")
+        .min_values(0)
+        .max_values(roles.len() as u8);
+
+    vec![CreateActionRow::SelectMenu(menu)]
+}
+
+/// Gives a member the roles they picked from a role menu and takes away the menu's roles they
+/// didn't pick.
+pub async fn handle_role_menu(ctx: &Context, interaction: &ComponentInteraction) {
+    let (Some(guild_id), Some(member)) = (interaction.guild_id, interaction.member.as_ref()) else {
+        return;
+    };
+
+    let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else {
+        return;
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't handle a role menu selection: {why}");
+            return;
+        }
+    };
+
+    let message_id = interaction.message.id.get() as i64;
+
+    let roles: Vec<RoleId> = match sqlx::query!("SELECT role_id FROM role_menu_roles WHERE message_id = ?", message_id).fetch_all(&database).await {
+        Ok(rows) => rows.into_iter().map(|row| RoleId::new(row.role_id as u64)).collect(),
+        Err(why) => {
+            warn!("Couldn't fetch the roles of role menu {message_id}: {why}");
+            return;
+        }
+    };
+
+    let selected: Vec<RoleId> = values.iter().filter_map(|value| value.parse().ok()).map(RoleId::new).collect();
+    let (mut added, mut removed, mut failed) = (Vec::new(), Vec::new(), Vec::new());
+
+    for role_id in roles {
+        let wanted = selected.contains(&role_id);
+        let has = member.roles.contains(&role_id);
+
+        let result = match (wanted, has) {
+            (true, false) => ctx.http.add_member_role(guild_id, member.user.id, role_id, Some("Role menu")).await.map(|()| &mut added),
+            (false, true) => ctx.http.remove_member_role(guild_id, member.user.id, role_id, Some("Role menu")).await.map(|()| &mut removed),
+            _ => continue
+        };
+
+        match result {
+            Ok(changed) => changed.push(role_id.mention().to_string()),
+            Err(why) => {
+                warn!("Role menu {message_id} couldn't update role {role_id} of user {}: {why}", member.user.id);
+                failed.push(role_id.mention().to_string());
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+
+    if !added.is_empty() {
+        lines.push(format!("Gave you {}.", added.join(" ")));
+    }
+
+    if !removed.is_empty() {
+        lines.push(format!("Took away {}.", removed.join(" ")));
+    }
+
+    if !failed.is_empty() {
+        lines.push(format!("I couldn't update {}, please ask a moderator.", failed.join(" ")));
+    }
+
+    if lines.is_empty() {
+        lines.push("Your roles are unchanged.".to_string());
+    }
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(lines.join("\n"))
+        .ephemeral(true);
+
+    if let Err(why) = interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await {
+        warn!("Couldn't respond to role menu {message_id}: {why}");
+    }
+}
@@ -0,0 +1,262 @@
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, EditMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::autoroles::check_assignable;
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// How many members are fetched from Discord at once, the most it allows.
+const PAGE_SIZE: u64 = 1000;
+
+/// How many members are handled between saving a bulk change's progress.
+const PROGRESS_INTERVAL: usize = 50;
+
+/// Which members a bulk role change goes over.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RoleTarget {
+    All,
+    Bots
+}
+
+impl RoleTarget {
+    pub fn parse(target: &str) -> Option<RoleTarget> {
+        match target {
+            "all" => Some(RoleTarget::All),
+            "bots" => Some(RoleTarget::Bots),
+            _ => None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoleTarget::All => "all",
+            RoleTarget::Bots => "bots"
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            RoleTarget::All => "every member",
+            RoleTarget::Bots => "every bot"
+        }
+    }
+
+    fn matches(self, member: &Member) -> bool {
+        match self {
+            RoleTarget::All => true,
+            RoleTarget::Bots => member.user.bot
+        }
+    }
+}
+
+/// Checks that a moderator may give out or take away a role through the bot, and from `target`
+/// if it's about one member. Like Discord itself, the role has to sit below the moderator's
+/// highest role, and so does the member's highest role. Returns a user-facing reason when not.
+pub async fn check_role_change(ctx: &Context, guild_id: GuildId, moderator: UserId, target: Option<UserId>, role_id: RoleId) -> Result<(), String> {
+    check_assignable(ctx, guild_id, role_id)?;
+
+    let moderator_member = guild_id.member(ctx, moderator).await.map_err(|_| "I couldn't find you in this server.".to_string())?;
+
+    let target_member = match target {
+        Some(target) => Some(guild_id.member(ctx, target).await.map_err(|_| "That user isn't in this server.".to_string())?),
+        None => None
+    };
+
+    let bot_id = ctx.cache.current_user().id;
+
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return Err("This server isn't cached yet, please try again in a moment.".to_string());
+    };
+
+    if !guild.members.get(&bot_id).is_some_and(|bot| guild.member_permissions(bot).manage_roles()) {
+        return Err("I need the Manage Roles permission to do that.".to_string());
+    }
+
+    if moderator == guild.owner_id {
+        return Ok(());
+    }
+
+    let position = |member: &Member| guild.member_highest_role(member).map_or(0, |role| role.position);
+    let moderator_position = position(&moderator_member);
+
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Err("That role doesn't exist in this server.".to_string());
+    };
+
+    if moderator_position <= role.position {
+        return Err(format!("Your highest role must be above {} to give it out or take it away.", role.name));
+    }
+
+    if let Some(target_member) = target_member.filter(|member| member.user.id != moderator) {
+        if moderator_position <= position(&target_member) {
+            return Err(format!("Your highest role must be above {}'s to change their roles.", target_member.user.name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Cancels a pending temporary role's expiry, e.g. once the role has been taken away by hand.
+pub async fn cancel_temprole(database: &SqlitePool, guild_id: GuildId, user_id: UserId, role_id: RoleId) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let user_id = user_id.get() as i64;
+    let role_id = role_id.get() as i64;
+
+    sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE guild_id = ? AND kind = 'temprole' AND json_extract(payload, '$.user_id') = ? AND json_extract(payload, '$.role_id') = ?",
+        guild_id,
+        user_id,
+        role_id
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// How far a bulk role change has got, as saved in `role_jobs`.
+struct Progress {
+    last_user_id: i64,
+    processed: i64,
+    changed: i64,
+    failed: i64
+}
+
+/// The message a bulk role change reports its progress in.
+pub fn progress_embed(ctx: &Context, branding: &Branding, guild_id: GuildId, role_id: RoleId, target: RoleTarget, progress: (i64, i64, i64), status: &str) -> CreateEmbed {
+    let (processed, changed, failed) = progress;
+    let members = ctx.cache.guild(guild_id).map(|guild| guild.member_count);
+
+    let checked = match members {
+        Some(members) => format!("{processed} of about {members} members checked"),
+        None => format!("{processed} members checked")
+    };
+
+    branding.embed()
+        .title("Bulk role change")
+        .description(format!("Giving {} to {}.", role_id.mention(), target.describe()))
+        .field("Progress", format!("{checked}\n{changed} given the role\n{failed} failed"), false)
+        .footer(CreateEmbedFooter::new(status))
+}
+
+/// Resumes the bulk role changes that were still running when the bot last stopped.
+pub async fn resume_role_jobs(ctx: Context) {
+    let database = match get_data::<DatabaseConnectionContainer>(&ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't resume role jobs: {why}");
+            return;
+        }
+    };
+
+    let jobs = match sqlx::query!("SELECT guild_id FROM role_jobs").fetch_all(&database).await {
+        Ok(jobs) => jobs,
+        Err(why) => {
+            error!("Failed to fetch bulk role changes to resume: {why}");
+            return;
+        }
+    };
+
+    for job in jobs {
+        tokio::spawn(run_role_job(Context::clone(&ctx), GuildId::new(job.guild_id as u64)));
+    }
+}
+
+/// Runs a guild's bulk role change until it's done or cancelled.
+pub async fn run_role_job(ctx: Context, guild_id: GuildId) {
+    if let Err(why) = process_role_job(&ctx, guild_id).await {
+        error!("Bulk role change in guild {guild_id} failed: {why}");
+    }
+}
+
+/// Walks the guild's members in ID order, giving the role to each one it's meant for. Progress is
+/// saved every few members, so a restart picks up close to where it stopped, and the job stops
+/// once its row is gone, which is how it's cancelled.
+async fn process_role_job(ctx: &Context, guild_id: GuildId) -> Result<(), CommandError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let db_guild_id = guild_id.get() as i64;
+
+    let Some(job) = sqlx::query!(
+        "SELECT role_id, target, channel_id, message_id, requested_by, last_user_id, processed, changed, failed FROM role_jobs WHERE guild_id = ?",
+        db_guild_id
+    ).fetch_optional(&database).await? else {
+        return Ok(());
+    };
+
+    let role_id = RoleId::new(job.role_id as u64);
+    let target = RoleTarget::parse(&job.target).unwrap_or(RoleTarget::All);
+    let (channel_id, message_id) = (ChannelId::new(job.channel_id as u64), MessageId::new(job.message_id as u64));
+    let reason = format!("Bulk role change requested by user {}", job.requested_by);
+
+    let mut progress = Progress { last_user_id: job.last_user_id, processed: job.processed, changed: job.changed, failed: job.failed };
+
+    let branding = guild_branding(ctx, Some(guild_id)).await;
+
+    let report = |progress: &Progress, status: &str| {
+        let embed = progress_embed(ctx, &branding, guild_id, role_id, target, (progress.processed, progress.changed, progress.failed), status);
+        channel_id.edit_message(ctx, message_id, EditMessage::new().embed(embed))
+    };
+
+    loop {
+        let after = (progress.last_user_id != 0).then(|| UserId::new(progress.last_user_id as u64));
+        let members = guild_id.members(&ctx.http, Some(PAGE_SIZE), after).await?;
+
+        if members.is_empty() {
+            break;
+        }
+
+        for chunk in members.chunks(PROGRESS_INTERVAL) {
+            // the role may have been deleted while the job was running
+            if ctx.cache.guild(guild_id).is_some_and(|guild| !guild.roles.contains_key(&role_id)) {
+                sqlx::query!("DELETE FROM role_jobs WHERE guild_id = ? AND message_id = ?", db_guild_id, job.message_id).execute(&database).await?;
+                drop(report(&progress, "Stopped, the role was deleted").await);
+
+                return Ok(());
+            }
+
+            for member in chunk {
+                progress.processed += 1;
+                progress.last_user_id = member.user.id.get() as i64;
+
+                if !target.matches(member) || member.roles.contains(&role_id) {
+                    continue;
+                }
+
+                match ctx.http.add_member_role(guild_id, member.user.id, role_id, Some(&reason)).await {
+                    Ok(()) => progress.changed += 1,
+                    Err(why) => {
+                        warn!("Couldn't give role {role_id} to user {} in guild {guild_id}: {why}", member.user.id);
+                        progress.failed += 1;
+                    }
+                }
+            }
+
+            let saved = sqlx::query!(
+                "UPDATE role_jobs SET last_user_id = ?, processed = ?, changed = ?, failed = ? WHERE guild_id = ? AND message_id = ?",
+                progress.last_user_id,
+                progress.processed,
+                progress.changed,
+                progress.failed,
+                db_guild_id,
+                job.message_id
+            ).execute(&database).await?.rows_affected();
+
+            if saved == 0 {
+                drop(report(&progress, "Cancelled").await);
+                return Ok(());
+            }
+
+            drop(report(&progress, "Running").await);
+        }
+    }
+
+    sqlx::query!("DELETE FROM role_jobs WHERE guild_id = ? AND message_id = ?", db_guild_id, job.message_id).execute(&database).await?;
+    drop(report(&progress, "Done").await);
+
+    Ok(())
+}
@@ -0,0 +1,367 @@
+use std::str::FromStr;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, ScheduledEventId, UserId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::events::send_event_reminder;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::moderation::{ModAction, record_action};
+use crate::utilities::parsing::{format_duration, parse_duration};
+use crate::utilities::polls::close_poll;
+use crate::utilities::templates::{TemplateContext, render_template};
+use crate::utilities::timezones::Timezone;
+use crate::utilities::watchlist::end_watch;
+use crate::utilities::errors::get_data;
+
+/// How often the scheduler looks for due jobs.
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(15);
+
+/// Shortest gap allowed between runs of a repeating job.
+pub const MIN_REPEAT_INTERVAL: Duration = Duration::minutes(10);
+
+/// Work the scheduler can run, stored as JSON alongside the job.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Job {
+    ChannelMessage { channel_id: u64, content: String },
+    Unmute { user_id: u64, role_id: u64 },
+    Unban { user_id: u64 },
+    /// Takes a temporary role back from a member.
+    RemoveRole { user_id: u64, role_id: u64 },
+    /// Reminds a user in the channel they asked in, or by DM for reminders set in DMs.
+    Reminder { user_id: u64, channel_id: Option<u64>, content: String, link: String },
+    ClosePoll { message_id: u64 },
+    /// Reminds a guild that one of its Discord scheduled events is about to start.
+    EventReminder { event_id: u64 },
+    /// Takes a user off the guild's watchlist.
+    Unwatch { user_id: u64 }
+}
+
+impl Job {
+    /// Short name stored next to the payload, so jobs can be listed by kind without decoding them.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::ChannelMessage { .. } => "message",
+            Job::Unmute { .. } => "unmute",
+            Job::Unban { .. } => "unban",
+            Job::RemoveRole { .. } => "temprole",
+            Job::Reminder { .. } => "reminder",
+            Job::ClosePoll { .. } => "poll",
+            Job::EventReminder { .. } => "event",
+            Job::Unwatch { .. } => "unwatch"
+        }
+    }
+}
+
+/// How a job repeats after its first run.
+pub enum Repeat {
+    Every(Duration),
+    /// A cron schedule, read in a timezone.
+    Cron(Box<cron::Schedule>, Timezone)
+}
+
+impl Repeat {
+    /// Parses a stored repeat rule, either `every <duration>` or a five-field cron expression,
+    /// optionally preceded by the timezone it's in (`Europe/Berlin 0 9 * * MON`, `UTC+2 0 9 * * MON`).
+    /// Without one it's in UTC.
+    pub fn parse(rule: &str) -> Option<Repeat> {
+        if let Some(interval) = rule.strip_prefix("every ") {
+            return parse_duration(interval).map(Repeat::Every);
+        }
+
+        // cron fields never start with a letter, so one that does is the timezone
+        let (timezone, expression) = match rule.split_once(' ') {
+            Some((timezone, expression)) if timezone.starts_with(|c: char| c.is_ascii_alphabetic()) => (Timezone::from_stored(timezone)?, expression),
+            _ => (Timezone::utc(), rule)
+        };
+
+        parse_cron(expression).map(|schedule| Repeat::Cron(Box::new(schedule), timezone))
+    }
+
+    /// The first run strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Repeat::Every(interval) => after.checked_add_signed(*interval),
+            Repeat::Cron(schedule, timezone) => next_cron_run(schedule, timezone, after)
+        }
+    }
+
+    /// Whether consecutive runs are closer together than `MIN_REPEAT_INTERVAL`.
+    pub fn is_frequent(&self) -> bool {
+        let now = Utc::now();
+
+        match (self.next_after(now), self.next_after(now).and_then(|first| self.next_after(first))) {
+            (Some(first), Some(second)) => second - first < MIN_REPEAT_INTERVAL,
+            _ => false
+        }
+    }
+}
+
+/// When a job should first run and how it repeats, as given by a user.
+pub struct When {
+    pub first_run: DateTime<Utc>,
+    pub repeat: Option<String>
+}
+
+/// Parses when a job should run, reading times in `timezone`:
+/// - `10m`, `in 2h` or `1d12h` runs once after that long
+/// - `2024-01-31 09:00` runs once at that local time
+/// - `every 1d` repeats at that interval, starting one interval from now
+/// - a five-field cron expression such as `0 9 * * MON` repeats on that schedule in local time
+pub fn parse_when(input: &str, timezone: &Timezone) -> Option<When> {
+    let input = input.trim();
+    let now = Utc::now();
+
+    if let Some(interval) = input.strip_prefix("every ") {
+        let interval = parse_duration(interval)?;
+
+        return Some(When { first_run: now.checked_add_signed(interval)?, repeat: Some(format!("every {}", format_duration(interval))) });
+    }
+
+    let delay = input.strip_prefix("in ").unwrap_or(input);
+
+    if let Some(delay) = parse_duration(delay) {
+        return Some(When { first_run: now.checked_add_signed(delay)?, repeat: None });
+    }
+
+    if let Ok(at) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M") {
+        let first_run = timezone.from_local_datetime(&at).earliest()?.with_timezone(&Utc);
+
+        return Some(When { first_run, repeat: None }).filter(|when| when.first_run > now);
+    }
+
+    let schedule = parse_cron(input)?;
+
+    // rules in UTC are stored bare, the way they were before timezones existed
+    let repeat = if timezone.is_utc() { input.to_string() } else { format!("{timezone} {input}") };
+
+    Some(When { first_run: next_cron_run(&schedule, timezone, now)?, repeat: Some(repeat) })
+}
+
+fn next_cron_run(schedule: &cron::Schedule, timezone: &Timezone, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    schedule.after(&after.with_timezone(timezone)).next().map(|time| time.with_timezone(&Utc))
+}
+
+/// Parses a standard five-field cron expression. The `cron` crate also wants seconds, so every
+/// expression runs at the start of its minute.
+fn parse_cron(expression: &str) -> Option<cron::Schedule> {
+    if expression.split_whitespace().count() != 5 {
+        return None;
+    }
+
+    cron::Schedule::from_str(&format!("0 {expression}")).ok()
+}
+
+/// Formats a time the way it's stored in `scheduled_jobs.next_run`. A fixed format keeps the
+/// stored times comparable as text.
+pub fn format_run_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Adds a job to the scheduler, returning its ID.
+pub async fn schedule_job(database: &SqlitePool, guild_id: Option<GuildId>, job: &Job, when: &When, created_by: u64) -> Result<i64, sqlx::Error> {
+    let guild_id = guild_id.map(|guild_id| guild_id.get() as i64);
+    let kind = job.kind();
+    let payload = serde_json::to_string(job).expect("jobs always serialize");
+    let next_run = format_run_time(when.first_run);
+    let created_by = created_by as i64;
+
+    let id = sqlx::query!(
+        "INSERT INTO scheduled_jobs (guild_id, kind, payload, next_run, repeat, created_by) VALUES (?, ?, ?, ?, ?, ?)",
+        guild_id,
+        kind,
+        payload,
+        next_run,
+        when.repeat,
+        created_by
+    ).execute(database).await?.last_insert_rowid();
+
+    Ok(id)
+}
+
+/// Runs due jobs every few seconds. One-off jobs are removed after running and repeating jobs are
+/// moved to their next run, so jobs survive restarts and anything missed while offline runs once.
+pub async fn run_scheduler(ctx: Context) {
+    let database = match get_data::<DatabaseConnectionContainer>(&ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't start the scheduler: {why}");
+            return;
+        }
+    };
+
+    loop {
+        if let Err(why) = run_due_jobs(&ctx, &database).await {
+            error!("Failed to run scheduled jobs: {why}");
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn run_due_jobs(ctx: &Context, database: &SqlitePool) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    let due = format_run_time(now);
+
+    let jobs = sqlx::query!(
+        "SELECT id, guild_id, payload, repeat FROM scheduled_jobs WHERE paused = 0 AND next_run <= ? ORDER BY next_run",
+        due
+    ).fetch_all(database).await?;
+
+    for row in jobs {
+        match serde_json::from_str::<Job>(&row.payload) {
+            Ok(job) => run_job(ctx, database, row.guild_id.map(|id| GuildId::new(id as u64)), job).await,
+            Err(why) => warn!("Scheduled job {} has an invalid payload: {why}", row.id)
+        }
+
+        let next_run = row.repeat.as_deref()
+            .and_then(Repeat::parse)
+            .and_then(|repeat| repeat.next_after(now))
+            .map(format_run_time);
+
+        match next_run {
+            Some(next_run) => {
+                sqlx::query!("UPDATE scheduled_jobs SET next_run = ? WHERE id = ?", next_run, row.id).execute(database).await?;
+            }
+            None => {
+                sqlx::query!("DELETE FROM scheduled_jobs WHERE id = ?", row.id).execute(database).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_job(ctx: &Context, database: &SqlitePool, guild_id: Option<GuildId>, job: Job) {
+    match job {
+        Job::ChannelMessage { channel_id, content } => {
+            let channel_id = ChannelId::new(channel_id);
+
+            let context = {
+                let context = TemplateContext::new()
+                    .value("channel", channel_id.mention())
+                    .value("date", Utc::now().format("%Y-%m-%d"));
+
+                match guild_id.and_then(|guild_id| ctx.cache.guild(guild_id)) {
+                    Some(guild) => context.with_guild(&guild),
+                    None => context
+                }
+            };
+
+            let content = render_template(&content, &context).unwrap_or(content);
+
+            // scheduled messages can't ping everyone or roles
+            let builder = CreateMessage::new()
+                .content(content)
+                .allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+            if let Err(why) = channel_id.send_message(&ctx.http, builder).await {
+                warn!("Failed to send scheduled message to channel {channel_id}: {why}");
+            }
+        }
+        Job::Unmute { user_id, role_id } => {
+            let Some(guild_id) = guild_id else {
+                warn!("Scheduled unmute for user {user_id} has no guild");
+                return;
+            };
+
+            let (user_id, role_id) = (UserId::new(user_id), RoleId::new(role_id));
+            let reason = "Temporary mute ended";
+
+            if let Err(why) = ctx.http.remove_member_role(guild_id, user_id, role_id, Some(reason)).await {
+                warn!("Failed to unmute user {user_id} in guild {guild_id}: {why}");
+                return;
+            }
+
+            let bot_id = ctx.cache.current_user().id;
+
+            if let Err(why) = record_action(ctx, database, guild_id, ModAction::Unmute, user_id, bot_id, Some(reason)).await {
+                error!("Failed to record unmute of user {user_id} in guild {guild_id}: {why}");
+            }
+        }
+        Job::Unban { user_id } => {
+            let Some(guild_id) = guild_id else {
+                warn!("Scheduled unban for user {user_id} has no guild");
+                return;
+            };
+
+            let user_id = UserId::new(user_id);
+            let reason = "Temporary ban ended";
+
+            if let Err(why) = ctx.http.remove_ban(guild_id, user_id, Some(reason)).await {
+                warn!("Failed to unban user {user_id} in guild {guild_id}: {why}");
+                return;
+            }
+
+            let bot_id = ctx.cache.current_user().id;
+
+            if let Err(why) = record_action(ctx, database, guild_id, ModAction::Unban, user_id, bot_id, Some(reason)).await {
+                error!("Failed to record unban of user {user_id} in guild {guild_id}: {why}");
+            }
+        }
+        Job::RemoveRole { user_id, role_id } => {
+            let Some(guild_id) = guild_id else {
+                warn!("Scheduled removal of role {role_id} from user {user_id} has no guild");
+                return;
+            };
+
+            let (user_id, role_id) = (UserId::new(user_id), RoleId::new(role_id));
+
+            if let Err(why) = ctx.http.remove_member_role(guild_id, user_id, role_id, Some("Temporary role expired")).await {
+                warn!("Failed to remove temporary role {role_id} from user {user_id} in guild {guild_id}: {why}");
+            }
+        }
+        Job::Reminder { user_id, channel_id, content, link } => {
+            let user_id = UserId::new(user_id);
+            let content = format!("{}, you asked me to remind you: {content}\n{link}", user_id.mention());
+
+            // only the user being reminded is pinged
+            let builder = CreateMessage::new()
+                .content(&content)
+                .allowed_mentions(CreateAllowedMentions::new().users([user_id]));
+
+            if let Some(channel_id) = channel_id.map(ChannelId::new) {
+                match channel_id.send_message(&ctx.http, builder.clone()).await {
+                    Ok(_) => return,
+                    Err(why) => warn!("Failed to send reminder to channel {channel_id}, sending it by DM instead: {why}")
+                }
+            }
+
+            let sent = match user_id.create_dm_channel(&ctx.http).await {
+                Ok(dm) => dm.id.send_message(&ctx.http, builder).await.map(|_| ()),
+                Err(why) => Err(why)
+            };
+
+            if let Err(why) = sent {
+                warn!("Failed to send reminder to user {user_id}: {why}");
+            }
+        }
+        Job::ClosePoll { message_id } => {
+            if let Err(why) = close_poll(ctx, database, MessageId::new(message_id)).await {
+                warn!("Failed to close poll {message_id}: {why}");
+            }
+        }
+        Job::EventReminder { event_id } => {
+            let Some(guild_id) = guild_id else {
+                warn!("Scheduled reminder for event {event_id} has no guild");
+                return;
+            };
+
+            send_event_reminder(ctx, database, guild_id, ScheduledEventId::new(event_id)).await;
+        }
+        Job::Unwatch { user_id } => {
+            let Some(guild_id) = guild_id else {
+                warn!("Scheduled unwatch for user {user_id} has no guild");
+                return;
+            };
+
+            end_watch(ctx, database, guild_id, UserId::new(user_id)).await;
+        }
+    }
+}
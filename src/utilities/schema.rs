@@ -0,0 +1,39 @@
+use sqlx::SqlitePool;
+use sqlx::migrate::{MigrateError, Migrator};
+use tracing::info;
+
+/// Every migration in `migrations/`, embedded at compile time. Applied migrations are recorded
+/// in the `_sqlx_migrations` table, which is what the schema version is read from.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// The schema version this build expects: the version of its newest migration.
+pub fn latest_schema_version() -> i64 {
+    MIGRATOR.iter().map(|migration| migration.version).max().unwrap_or_default()
+}
+
+/// The newest migration applied to the database, or None before any have been.
+pub async fn schema_version(database: &SqlitePool) -> Option<i64> {
+    // the table only exists once the migrator has run, so this can't be checked at compile time
+    sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+        .fetch_one(database)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Brings the database's schema up to date, logging each migration that gets applied. Fails,
+/// leaving the database as it is, if it was migrated by a newer build or a migration that's
+/// already been applied has since been edited.
+pub async fn run_migrations(database: &SqlitePool) -> Result<(), MigrateError> {
+    let current = schema_version(database).await;
+
+    for migration in MIGRATOR.iter().filter(|migration| current.is_none_or(|current| migration.version > current)) {
+        info!("Applying migration {} ({})", migration.version, migration.description);
+    }
+
+    MIGRATOR.run(database).await?;
+
+    info!("Database schema is at version {}", latest_schema_version());
+
+    Ok(())
+}
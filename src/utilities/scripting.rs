@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rhai::{Array, Dynamic, Engine, Map, Scope};
+
+/// Wall-clock time a script may run for before it is stopped.
+const TIME_LIMIT: Duration = Duration::from_millis(250);
+
+/// Operations a script may perform before it is stopped, a rough CPU limit.
+const MAX_OPERATIONS: u64 = 200_000;
+
+/// Replies a single run of a script can send.
+const MAX_REPLIES: usize = 3;
+
+/// Longest reply a script can send, matching Discord's message length limit.
+const MAX_REPLY_LENGTH: usize = 2000;
+
+/// What a script can see about the message that invoked it.
+pub struct ScriptInput {
+    pub content: String,
+    pub args: Vec<String>,
+    pub author_id: u64,
+    pub author_name: String,
+    pub channel_id: u64,
+    pub guild_id: u64
+}
+
+/// Builds an engine with every limit applied. Rhai has no file, network or process access, so the
+/// only way for a script to affect the outside world is the `reply` function.
+fn sandboxed_engine(started: Instant) -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(16)
+        .set_max_expr_depths(32, 32)
+        .set_max_string_size(MAX_REPLY_LENGTH * 2)
+        .set_max_array_size(1000)
+        .set_max_map_size(1000)
+        .disable_symbol("eval")
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {})
+        .on_progress(move |_| (started.elapsed() > TIME_LIMIT).then(|| Dynamic::from("time limit exceeded")));
+
+    engine
+}
+
+/// Checks a script for syntax errors without running it.
+pub fn compile_script(source: &str) -> Result<(), String> {
+    sandboxed_engine(Instant::now()).compile(source).map(|_| ()).map_err(|err| err.to_string())
+}
+
+/// Runs a script on a blocking thread and returns the replies it sent.
+pub async fn run_script(source: String, input: ScriptInput) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let replies = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = sandboxed_engine(Instant::now());
+
+        let sink = Rc::clone(&replies);
+        engine.register_fn("reply", move |text: &str| {
+            let mut replies = sink.borrow_mut();
+
+            if replies.len() < MAX_REPLIES && !text.trim().is_empty() {
+                replies.push(text.chars().take(MAX_REPLY_LENGTH).collect::<String>());
+            }
+        });
+
+        let mut author = Map::new();
+        author.insert("id".into(), input.author_id.to_string().into());
+        author.insert("name".into(), input.author_name.into());
+        author.insert("mention".into(), format!("<@{}>", input.author_id).into());
+
+        let args: Array = input.args.into_iter().map(Dynamic::from).collect();
+
+        let mut scope = Scope::new();
+        scope.push_constant("content", input.content);
+        scope.push_constant("args", args);
+        scope.push_constant("author", author);
+        scope.push_constant("channel_id", input.channel_id.to_string());
+        scope.push_constant("guild_id", input.guild_id.to_string());
+
+        engine.run_with_scope(&mut scope, &source).map_err(|err| err.to_string())?;
+
+        let replies = replies.borrow().clone();
+        Ok(replies)
+    })
+    .await
+    .map_err(|err| format!("the script crashed: {err}"))?
+}
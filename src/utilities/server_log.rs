@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use serenity::builder::{CreateEmbed, CreateEmbedFooter, CreateMessage};
+use serenity::model::guild::audit_log::{Action, AuditLogEntry, Change, ChannelAction, EmojiAction, MemberAction, RoleAction, WebhookAction};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::utilities::branding::guild_branding;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::i18n::{guild_language, translate};
+
+/// How many audit log entries are searched for the one behind an event.
+const AUDIT_LOG_LIMIT: u8 = 10;
+
+/// Oldest an audit log entry can be, in seconds, to count as the one behind an event.
+const MAX_ENTRY_AGE: i64 = 15;
+
+/// The guild's server log channel, if it has one.
+pub async fn server_log_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT server_log_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.server_log_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+async fn log_channel(ctx: &Context, guild_id: GuildId) -> Option<ChannelId> {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            warn!("Couldn't find the server log channel of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    match server_log_channel(&database, guild_id).await {
+        Ok(channel_id) => channel_id,
+        Err(why) => {
+            warn!("Couldn't find the server log channel of guild {guild_id}: {why}");
+            None
+        }
+    }
+}
+
+/// The newest recent audit log entry matching an event, if the bot can read the audit log.
+async fn audit_entry(ctx: &Context, guild_id: GuildId, action: Option<Action>, matches: impl Fn(&AuditLogEntry) -> bool) -> Option<AuditLogEntry> {
+    let logs = guild_id.audit_logs(ctx, action, None, None, Some(AUDIT_LOG_LIMIT)).await.ok()?;
+    let now = Timestamp::now().unix_timestamp();
+
+    logs.entries.into_iter()
+        .filter(|entry| now - entry.id.created_at().unix_timestamp() <= MAX_ENTRY_AGE)
+        .find(matches)
+}
+
+/// The newest recent audit log entry for something done to a target.
+async fn target_entry(ctx: &Context, guild_id: GuildId, action: Action, target_id: u64) -> Option<AuditLogEntry> {
+    audit_entry(ctx, guild_id, Some(action), |entry| entry.target_id.is_some_and(|id| id.get() == target_id)).await
+}
+
+/// The name an audit log entry changed something from and to.
+fn name_change(entry: &AuditLogEntry) -> (Option<String>, Option<String>) {
+    entry.changes.iter()
+        .flatten()
+        .find_map(|change| match change {
+            Change::Name { old, new } => Some((old.clone(), new.clone())),
+            _ => None
+        })
+        .unwrap_or_default()
+}
+
+/// Posts an event to the server log, with who did it and why if the audit log says so.
+async fn post(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, language: &str, mut embed: CreateEmbed, entry: Option<AuditLogEntry>) {
+    if let Some(entry) = entry {
+        embed = embed.field(translate(language, "log.by", &[]), entry.user_id.mention().to_string(), true);
+
+        if let Some(reason) = entry.reason {
+            embed = embed.field(translate(language, "log.reason", &[]), reason, true);
+        }
+    }
+
+    let embed = embed.color(guild_branding(ctx, Some(guild_id)).await.color()).timestamp(Timestamp::now());
+
+    if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't post to the server log of guild {guild_id}: {why}");
+    }
+}
+
+fn permission_names(language: &str, permissions: Permissions) -> String {
+    match permissions.get_permission_names() {
+        names if names.is_empty() => translate(language, "log.none", &[]),
+        names => names.join(", ")
+    }
+}
+
+fn yes_no(language: &str, value: bool) -> String {
+    translate(language, if value { "log.yes" } else { "log.no" }, &[])
+}
+
+pub async fn log_role_create(ctx: &Context, role: &Role) {
+    let Some(channel_id) = log_channel(ctx, role.guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(role.guild_id)).await;
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.role_created", &[]))
+        .description(translate(&language, "log.role_created_description", &[("role", &role.mention().to_string())]))
+        .footer(CreateEmbedFooter::new(translate(&language, "log.role_id", &[("id", &role.id.to_string())])));
+
+    let entry = target_entry(ctx, role.guild_id, Action::Role(RoleAction::Create), role.id.get()).await;
+
+    post(ctx, role.guild_id, channel_id, &language, embed, entry).await;
+}
+
+pub async fn log_role_delete(ctx: &Context, guild_id: GuildId, role_id: RoleId, role: Option<&Role>) {
+    let Some(channel_id) = log_channel(ctx, guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(guild_id)).await;
+
+    let description = match role {
+        Some(role) => translate(&language, "log.role_deleted_description", &[("name", &role.name)]),
+        None => translate(&language, "log.role_deleted_unknown", &[])
+    };
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.role_deleted", &[]))
+        .description(description)
+        .footer(CreateEmbedFooter::new(translate(&language, "log.role_id", &[("id", &role_id.to_string())])));
+
+    let entry = target_entry(ctx, guild_id, Action::Role(RoleAction::Delete), role_id.get()).await;
+
+    post(ctx, guild_id, channel_id, &language, embed, entry).await;
+}
+
+/// Logs what changed about a role. Changes to only its position aren't logged, since moving one
+/// role moves every role between.
+pub async fn log_role_update(ctx: &Context, old: Option<&Role>, new: &Role) {
+    let Some(old) = old else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(new.guild_id)).await;
+    let mut changes = Vec::new();
+
+    if old.name != new.name {
+        changes.push(("log.name", format!("`{}` → `{}`", old.name, new.name)));
+    }
+
+    if old.colour != new.colour {
+        changes.push(("log.color", format!("#{} → #{}", old.colour.hex(), new.colour.hex())));
+    }
+
+    if old.hoist != new.hoist {
+        changes.push(("log.hoisted", yes_no(&language, new.hoist)));
+    }
+
+    if old.mentionable != new.mentionable {
+        changes.push(("log.mentionable", yes_no(&language, new.mentionable)));
+    }
+
+    if old.permissions != new.permissions {
+        changes.push(("log.permissions_added", permission_names(&language, new.permissions.difference(old.permissions))));
+        changes.push(("log.permissions_removed", permission_names(&language, old.permissions.difference(new.permissions))));
+    }
+
+    if changes.is_empty() {
+        return;
+    }
+
+    let Some(channel_id) = log_channel(ctx, new.guild_id).await else {
+        return;
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(translate(&language, "log.role_updated", &[]))
+        .description(translate(&language, "log.role_updated_description", &[("role", &new.mention().to_string())]))
+        .footer(CreateEmbedFooter::new(translate(&language, "log.role_id", &[("id", &new.id.to_string())])));
+
+    for (name, value) in changes {
+        embed = embed.field(translate(&language, name, &[]), value, false);
+    }
+
+    let entry = target_entry(ctx, new.guild_id, Action::Role(RoleAction::Update), new.id.get()).await;
+
+    post(ctx, new.guild_id, channel_id, &language, embed, entry).await;
+}
+
+pub async fn log_channel_create(ctx: &Context, channel: &GuildChannel) {
+    let Some(channel_id) = log_channel(ctx, channel.guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(channel.guild_id)).await;
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.channel_created", &[]))
+        .description(translate(&language, "log.channel_created_description", &[("channel", &channel.mention().to_string())]))
+        .field(translate(&language, "log.type", &[]), channel.kind.name(), true)
+        .footer(CreateEmbedFooter::new(translate(&language, "log.channel_id", &[("id", &channel.id.to_string())])));
+
+    let entry = target_entry(ctx, channel.guild_id, Action::Channel(ChannelAction::Create), channel.id.get()).await;
+
+    post(ctx, channel.guild_id, channel_id, &language, embed, entry).await;
+}
+
+pub async fn log_channel_delete(ctx: &Context, channel: &GuildChannel) {
+    let Some(channel_id) = log_channel(ctx, channel.guild_id).await else {
+        return;
+    };
+
+    // nowhere to log to anymore
+    if channel_id == channel.id {
+        return;
+    }
+
+    let language = guild_language(ctx, Some(channel.guild_id)).await;
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.channel_deleted", &[]))
+        .description(translate(&language, "log.channel_deleted_description", &[("name", &channel.name)]))
+        .field(translate(&language, "log.type", &[]), channel.kind.name(), true)
+        .footer(CreateEmbedFooter::new(translate(&language, "log.channel_id", &[("id", &channel.id.to_string())])));
+
+    let entry = target_entry(ctx, channel.guild_id, Action::Channel(ChannelAction::Delete), channel.id.get()).await;
+
+    post(ctx, channel.guild_id, channel_id, &language, embed, entry).await;
+}
+
+/// Logs what changed about a channel. Like with roles, changes to only its position aren't logged.
+pub async fn log_channel_update(ctx: &Context, old: Option<&GuildChannel>, new: &GuildChannel) {
+    let Some(old) = old else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(new.guild_id)).await;
+    let mut changes = Vec::new();
+
+    if old.name != new.name {
+        changes.push(("log.name", format!("`{}` → `{}`", old.name, new.name)));
+    }
+
+    if old.topic != new.topic {
+        let topic = match new.topic.as_deref().filter(|topic| !topic.is_empty()) {
+            Some(topic) => topic.chars().take(1024).collect(),
+            None => format!("*{}*", translate(&language, "log.none", &[]))
+        };
+
+        changes.push(("log.topic", topic));
+    }
+
+    if old.nsfw != new.nsfw {
+        changes.push(("log.age_restricted", yes_no(&language, new.nsfw)));
+    }
+
+    if old.rate_limit_per_user != new.rate_limit_per_user {
+        changes.push(("log.slowmode", format!("{}s", new.rate_limit_per_user.unwrap_or(0))));
+    }
+
+    if old.parent_id != new.parent_id {
+        changes.push(("log.category", new.parent_id.map_or_else(|| translate(&language, "log.none", &[]), |parent_id| parent_id.mention().to_string())));
+    }
+
+    if old.permission_overwrites != new.permission_overwrites {
+        changes.push(("log.permissions", translate(&language, "log.overwrites_changed", &[])));
+    }
+
+    if changes.is_empty() {
+        return;
+    }
+
+    let Some(channel_id) = log_channel(ctx, new.guild_id).await else {
+        return;
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(translate(&language, "log.channel_updated", &[]))
+        .description(translate(&language, "log.channel_updated_description", &[("channel", &new.mention().to_string())]))
+        .footer(CreateEmbedFooter::new(translate(&language, "log.channel_id", &[("id", &new.id.to_string())])));
+
+    for (name, value) in changes {
+        embed = embed.field(translate(&language, name, &[]), value, false);
+    }
+
+    let entry = target_entry(ctx, new.guild_id, Action::Channel(ChannelAction::Update), new.id.get()).await;
+
+    post(ctx, new.guild_id, channel_id, &language, embed, entry).await;
+}
+
+/// Logs a change to the guild's emojis. Discord only sends the emojis there are now, so what
+/// changed comes from the audit log.
+pub async fn log_emojis_update(ctx: &Context, guild_id: GuildId, emojis: &HashMap<EmojiId, Emoji>) {
+    let Some(channel_id) = log_channel(ctx, guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(guild_id)).await;
+    let entry = audit_entry(ctx, guild_id, None, |entry| matches!(entry.action, Action::Emoji(_))).await;
+
+    let description = match &entry {
+        Some(entry) => {
+            let (old_name, new_name) = name_change(entry);
+            let emoji = entry.target_id
+                .and_then(|target_id| emojis.get(&EmojiId::new(target_id.get())))
+                .map(ToString::to_string);
+
+            match entry.action {
+                Action::Emoji(EmojiAction::Create) => match emoji.or(new_name.map(|name| format!("`:{name}:`"))) {
+                    Some(emoji) => translate(&language, "log.emoji_added", &[("emoji", &emoji)]),
+                    None => translate(&language, "log.emoji_added_unknown", &[])
+                },
+                Action::Emoji(EmojiAction::Delete) => match old_name {
+                    Some(name) => translate(&language, "log.emoji_removed", &[("emoji", &format!("`:{name}:`"))]),
+                    None => translate(&language, "log.emoji_removed_unknown", &[])
+                },
+                _ => match (old_name, new_name) {
+                    (Some(old_name), Some(new_name)) => {
+                        let emoji = emoji.map(|emoji| format!("{emoji} ")).unwrap_or_default();
+                        translate(&language, "log.emoji_renamed", &[("emoji", &emoji), ("old", &old_name), ("new", &new_name)])
+                    }
+                    _ => translate(&language, "log.emoji_updated", &[])
+                }
+            }
+        }
+        None => translate(&language, "log.emojis_changed", &[("count", &emojis.len().to_string())])
+    };
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.emojis_updated", &[]))
+        .description(description);
+
+    post(ctx, guild_id, channel_id, &language, embed, entry).await;
+}
+
+/// Logs a change to a channel's webhooks. Like with emojis, what changed comes from the audit log.
+pub async fn log_webhook_update(ctx: &Context, guild_id: GuildId, webhook_channel_id: ChannelId) {
+    let Some(channel_id) = log_channel(ctx, guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(guild_id)).await;
+    let entry = audit_entry(ctx, guild_id, None, |entry| matches!(entry.action, Action::Webhook(_))).await;
+    let channel = webhook_channel_id.mention().to_string();
+
+    let description = match &entry {
+        Some(entry) => {
+            let (old_name, new_name) = name_change(entry);
+
+            match (entry.action, old_name, new_name) {
+                (Action::Webhook(WebhookAction::Create), _, Some(name)) => translate(&language, "log.webhook_created", &[("name", &name), ("channel", &channel)]),
+                (Action::Webhook(WebhookAction::Delete), Some(name), _) => translate(&language, "log.webhook_deleted", &[("name", &name), ("channel", &channel)]),
+                (_, Some(old_name), Some(new_name)) => translate(&language, "log.webhook_renamed", &[("old", &old_name), ("channel", &channel), ("new", &new_name)]),
+                _ => translate(&language, "log.webhook_changed", &[("channel", &channel)])
+            }
+        }
+        None => translate(&language, "log.webhook_changed", &[("channel", &channel)])
+    };
+
+    let embed = CreateEmbed::new()
+        .title(translate(&language, "log.webhooks_updated", &[]))
+        .description(description)
+        .footer(CreateEmbedFooter::new(translate(&language, "log.channel_id", &[("id", &webhook_channel_id.to_string())])));
+
+    post(ctx, guild_id, channel_id, &language, embed, entry).await;
+}
+
+/// Logs roles given to or taken from a member. Only works for members that were cached, since
+/// Discord doesn't say which roles they had before.
+pub async fn log_member_roles(ctx: &Context, old: Option<&Member>, event: &GuildMemberUpdateEvent) {
+    let Some(old) = old else {
+        return;
+    };
+
+    let added: Vec<_> = event.roles.iter().filter(|role_id| !old.roles.contains(role_id)).collect();
+    let removed: Vec<_> = old.roles.iter().filter(|role_id| !event.roles.contains(role_id)).collect();
+
+    if added.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    let Some(channel_id) = log_channel(ctx, event.guild_id).await else {
+        return;
+    };
+
+    let language = guild_language(ctx, Some(event.guild_id)).await;
+    let mentions = |role_ids: &[&RoleId]| role_ids.iter().map(|role_id| role_id.mention().to_string()).collect::<Vec<_>>().join(" ");
+
+    let mut embed = CreateEmbed::new()
+        .title(translate(&language, "log.member_roles_changed", &[]))
+        .description(translate(&language, "log.member_roles_changed_description", &[("member", &event.user.mention().to_string())]))
+        .footer(CreateEmbedFooter::new(translate(&language, "log.user_id", &[("id", &event.user.id.to_string())])));
+
+    if !added.is_empty() {
+        embed = embed.field(translate(&language, "log.added", &[]), mentions(&added), false);
+    }
+
+    if !removed.is_empty() {
+        embed = embed.field(translate(&language, "log.removed", &[]), mentions(&removed), false);
+    }
+
+    let entry = target_entry(ctx, event.guild_id, Action::Member(MemberAction::RoleUpdate), event.user.id.get()).await;
+
+    post(ctx, event.guild_id, channel_id, &language, embed, entry).await;
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::model::application::ComponentInteraction;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use songbird::input::Input;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::utilities::errors::{BotError, get_data};
+use crate::utilities::global_data::{DatabaseConnectionContainer, SfxCooldownsContainer};
+use crate::utilities::playback::{Playback, member_voice_channel, play_in_voice};
+
+/// Buttons on a sound effect panel have this prefix followed by the clip's name.
+pub const SFX_ID_PREFIX: &str = "sfx:";
+
+/// How long members wait between playing sound effects.
+pub const SFX_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// The audio types clips can be uploaded as, with the extension they're saved with.
+pub const CLIP_TYPES: [(&str, &str); 4] = [("audio/mpeg", "mp3"), ("audio/ogg", "ogg"), ("audio/wav", "wav"), ("audio/x-wav", "wav")];
+
+/// A sound effect clip saved for a guild.
+pub struct Clip {
+    pub name: String,
+    pub file_name: String
+}
+
+/// Where clips are saved, one directory per guild. Set with `SFX_DIRECTORY`.
+pub fn clip_directory(guild_id: GuildId) -> PathBuf {
+    let root = env::var("SFX_DIRECTORY").unwrap_or_else(|_| "sfx".to_string());
+    PathBuf::from(root).join(guild_id.to_string())
+}
+
+pub async fn get_clip(database: &SqlitePool, guild_id: GuildId, name: &str) -> Result<Option<Clip>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let clip = sqlx::query!("SELECT name, file_name FROM sfx_clips WHERE guild_id = ? AND name = ?", guild_id, name)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(clip.map(|clip| Clip { name: clip.name, file_name: clip.file_name }))
+}
+
+/// Counts a play of a clip, for `sfx list`.
+pub async fn count_play(database: &SqlitePool, guild_id: GuildId, name: &str) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    sqlx::query!("UPDATE sfx_clips SET uses = uses + 1 WHERE guild_id = ? AND name = ?", guild_id, name)
+        .execute(database)
+        .await?;
+
+    Ok(())
+}
+
+/// Starts a member's cooldown, or returns how long is left of the one they're on.
+pub async fn start_cooldown(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<Option<Duration>, BotError> {
+    let cooldowns = get_data::<SfxCooldownsContainer>(ctx).await?;
+    let mut cooldowns = cooldowns.lock().await;
+
+    let now = Instant::now();
+    cooldowns.retain(|_, played_at| now.duration_since(*played_at) < SFX_COOLDOWN);
+
+    if let Some(played_at) = cooldowns.get(&(guild_id.get(), user_id.get())) {
+        return Ok(Some(SFX_COOLDOWN - now.duration_since(*played_at)));
+    }
+
+    cooldowns.insert((guild_id.get(), user_id.get()), now);
+
+    Ok(None)
+}
+
+/// Reads a clip from disk, ready to be played in voice.
+pub async fn clip_input(guild_id: GuildId, clip: &Clip) -> Result<Input, std::io::Error> {
+    let data = tokio::fs::read(clip_directory(guild_id).join(&clip.file_name)).await?;
+    Ok(Input::from(data))
+}
+
+/// Plays a clip in the voice channel of whoever pressed its button on a sound effect panel.
+pub async fn handle_sfx_button(ctx: &Context, interaction: &ComponentInteraction) {
+    let (Some(guild_id), Some(name)) = (interaction.guild_id, interaction.data.custom_id.strip_prefix(SFX_ID_PREFIX)) else {
+        return;
+    };
+
+    let response = match sfx_button_response(ctx, guild_id, interaction.user.id, name).await {
+        Ok(response) => response,
+        Err(why) => {
+            warn!("Couldn't play sound effect {name} in guild {guild_id}: {why}");
+            CreateInteractionResponseMessage::new().content("I couldn't play that sound effect, please try again.").ephemeral(true)
+        }
+    };
+
+    if let Err(why) = interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await {
+        warn!("Couldn't respond to a sound effect button in guild {guild_id}: {why}");
+    }
+}
+
+async fn sfx_button_response(ctx: &Context, guild_id: GuildId, user_id: UserId, name: &str) -> Result<CreateInteractionResponseMessage, BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+
+    let Some(clip) = get_clip(&database, guild_id, name).await? else {
+        return Ok(CreateInteractionResponseMessage::new().content(format!("The sound effect `{name}` was removed.")).ephemeral(true));
+    };
+
+    let Some(channel_id) = member_voice_channel(ctx, guild_id, user_id) else {
+        return Ok(CreateInteractionResponseMessage::new().content("Join a voice channel first, then I'll play it there.").ephemeral(true));
+    };
+
+    if let Some(remaining) = start_cooldown(ctx, guild_id, user_id).await? {
+        let content = format!("You can play another sound effect in {} seconds.", remaining.as_secs() + 1);
+        return Ok(CreateInteractionResponseMessage::new().content(content).ephemeral(true));
+    }
+
+    let input = match clip_input(guild_id, &clip).await {
+        Ok(input) => input,
+        Err(why) => {
+            warn!("Sound effect {name} in guild {guild_id} is missing its file: {why}");
+            return Ok(CreateInteractionResponseMessage::new().content(format!("The file for `{name}` is missing, ask a moderator to upload it again.")).ephemeral(true));
+        }
+    };
+
+    if let Err(why) = play_in_voice(ctx, guild_id, channel_id, input, Playback::Overlap).await {
+        return Ok(CreateInteractionResponseMessage::new().content(why).ephemeral(true));
+    }
+
+    count_play(&database, guild_id, &clip.name).await?;
+
+    Ok(CreateInteractionResponseMessage::new().content(format!("Playing **{}** in <#{channel_id}>.", clip.name)).ephemeral(true))
+}
@@ -0,0 +1,43 @@
+use serenity::prelude::Context;
+use tracing::{error, info};
+
+use crate::utilities::analytics::flush_pending_activity;
+use crate::utilities::errors::get_data;
+use crate::utilities::global_data::{GuildSettingsContainer, ShardManagerContainer};
+use crate::utilities::voice::flush_voice_time;
+
+/// Writes out buffered analytics, settings changes and voice time, then shuts every shard down.
+pub async fn shutdown(ctx: &Context) {
+    if let Err(why) = flush_pending_activity(ctx).await {
+        error!("Failed to write server activity before shutting down: {why}");
+    }
+
+    match get_data::<GuildSettingsContainer>(ctx).await {
+        Ok(guild_settings) => {
+            if let Err(why) = guild_settings.flush().await {
+                error!("Failed to write back guild settings before shutting down: {why}");
+            }
+        }
+        Err(why) => error!("Failed to write back guild settings before shutting down: {why}")
+    }
+
+    if let Err(why) = flush_voice_time(ctx).await {
+        error!("Failed to write voice time before shutting down: {why}");
+    }
+
+    match get_data::<ShardManagerContainer>(ctx).await {
+        Ok(shard_manager) => shard_manager.shutdown_all().await,
+        Err(why) => error!("Failed to shut the shards down: {why}")
+    }
+}
+
+/// Shuts down the same way as the `shutdown` command once the process is interrupted.
+pub async fn shutdown_on_ctrl_c(ctx: Context) {
+    if let Err(why) = tokio::signal::ctrl_c().await {
+        error!("Could not register ctrl+c handler: {why}");
+        return;
+    }
+
+    info!("Interrupted, shutting down");
+    shutdown(&ctx).await;
+}
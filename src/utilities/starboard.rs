@@ -0,0 +1,166 @@
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateMessage, EditMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::channel::{Message, Reaction, ReactionType};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::branding::{Branding, guild_branding};
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+pub const STAR: &str = "⭐";
+
+pub fn is_star(emoji: &ReactionType) -> bool {
+    matches!(emoji, ReactionType::Unicode(emoji) if emoji == STAR)
+}
+
+fn star_count(message: &Message) -> i64 {
+    message.reactions.iter()
+        .find(|reaction| is_star(&reaction.reaction_type))
+        .map_or(0, |reaction| reaction.count as i64)
+}
+
+fn starboard_content(stars: i64, channel_id: ChannelId) -> String {
+    format!("{STAR} **{stars}** | {}", channel_id.mention())
+}
+
+fn starboard_embed(branding: &Branding, message: &Message, guild_id: GuildId) -> CreateEmbed {
+    let mut embed = branding.embed()
+        .author(CreateEmbedAuthor::new(message.author.tag()).icon_url(message.author.face()))
+        .description(&message.content)
+        .field("Source", format!("[Jump to message]({})", message.id.link(message.channel_id, Some(guild_id))), false)
+        .timestamp(message.timestamp);
+
+    let image = message.attachments.iter()
+        .find(|attachment| attachment.content_type.as_deref().is_some_and(|kind| kind.starts_with("image/")));
+
+    if let Some(image) = image {
+        embed = embed.image(&image.url);
+    }
+
+    embed
+}
+
+/// Updates the starboard after a message's stars change: it's reposted once it reaches the
+/// guild's threshold, its star count is kept up to date, and it's taken down again if it drops
+/// below the threshold.
+pub async fn update_starboard(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, message_id: MessageId) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't update the starboard for message {message_id}: {why}");
+            return;
+        }
+    };
+
+    if let Err(why) = sync_entry(ctx, &database, guild_id, channel_id, message_id).await {
+        warn!("Couldn't update the starboard of guild {guild_id} for message {message_id}: {why}");
+    }
+}
+
+/// Finds the guild a starred message belongs to, for events that don't say.
+pub async fn starred_guild(ctx: &Context, message_id: MessageId) -> Option<GuildId> {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't look up the starboard entry of message {message_id}: {why}");
+            return None;
+        }
+    };
+
+    let db_message_id = message_id.get() as i64;
+
+    sqlx::query!("SELECT guild_id FROM starboard_entries WHERE message_id = ?", db_message_id)
+        .fetch_optional(&database)
+        .await
+        .ok()
+        .flatten()
+        .map(|row| GuildId::new(row.guild_id as u64))
+}
+
+/// Handles a star being added or removed.
+pub async fn handle_star_reaction(ctx: &Context, reaction: &Reaction) {
+    if let (Some(guild_id), true) = (reaction.guild_id, is_star(&reaction.emoji)) {
+        update_starboard(ctx, guild_id, reaction.channel_id, reaction.message_id).await;
+    }
+}
+
+async fn sync_entry(ctx: &Context, database: &SqlitePool, guild_id: GuildId, channel_id: ChannelId, message_id: MessageId) -> Result<(), CommandError> {
+    let db_guild_id = guild_id.get() as i64;
+
+    let settings = sqlx::query!("SELECT starboard_channel_id, starboard_threshold FROM guild_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(database)
+        .await?;
+
+    // stars on the starboard itself don't count
+    let Some((starboard_id, threshold)) = settings
+        .and_then(|row| row.starboard_channel_id.map(|channel| (ChannelId::new(channel as u64), row.starboard_threshold)))
+        .filter(|(starboard_id, _)| *starboard_id != channel_id)
+    else {
+        return Ok(());
+    };
+
+    let message = channel_id.message(ctx, message_id).await?;
+    let stars = star_count(&message);
+    let db_message_id = message_id.get() as i64;
+
+    if stars < threshold {
+        let removed = sqlx::query!("DELETE FROM starboard_entries WHERE message_id = ? RETURNING starboard_message_id", db_message_id)
+            .fetch_optional(database)
+            .await?;
+
+        if let Some(starboard_message_id) = removed.and_then(|row| row.starboard_message_id) {
+            drop(starboard_id.delete_message(ctx, MessageId::new(starboard_message_id as u64)).await);
+        }
+
+        return Ok(());
+    }
+
+    let db_channel_id = channel_id.get() as i64;
+
+    // claiming the entry first keeps two stars at once from reposting the message twice
+    let claimed = sqlx::query!(
+        "INSERT INTO starboard_entries (message_id, guild_id, channel_id, stars) VALUES (?, ?, ?, ?) ON CONFLICT DO NOTHING",
+        db_message_id,
+        db_guild_id,
+        db_channel_id,
+        stars
+    ).execute(database).await?.rows_affected() == 1;
+
+    if claimed {
+        let builder = CreateMessage::new()
+            .content(starboard_content(stars, channel_id))
+            .embed(starboard_embed(&guild_branding(ctx, Some(guild_id)).await, &message, guild_id));
+
+        let post = match starboard_id.send_message(ctx, builder).await {
+            Ok(post) => post,
+            Err(why) => {
+                // let the next star try again
+                sqlx::query!("DELETE FROM starboard_entries WHERE message_id = ?", db_message_id).execute(database).await?;
+                return Err(why.into());
+            }
+        };
+
+        let starboard_message_id = post.id.get() as i64;
+
+        sqlx::query!("UPDATE starboard_entries SET starboard_message_id = ? WHERE message_id = ?", starboard_message_id, db_message_id)
+            .execute(database)
+            .await?;
+
+        return Ok(());
+    }
+
+    let updated = sqlx::query!("UPDATE starboard_entries SET stars = ? WHERE message_id = ? RETURNING starboard_message_id", stars, db_message_id)
+        .fetch_optional(database)
+        .await?;
+
+    // a repost that's still being sent is corrected by the next change in stars
+    if let Some(starboard_message_id) = updated.and_then(|row| row.starboard_message_id) {
+        let builder = EditMessage::new().content(starboard_content(stars, channel_id));
+        starboard_id.edit_message(ctx, MessageId::new(starboard_message_id as u64), builder).await?;
+    }
+
+    Ok(())
+}
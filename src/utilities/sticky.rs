@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, MessageId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::global_data::{DatabaseConnectionContainer, StickyContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// A channel's sticky message, along with how much has happened since its latest copy.
+pub struct Sticky {
+    content: String,
+    message_id: Option<u64>,
+    every_messages: u32,
+    every_seconds: u64,
+    /// Messages sent since the latest copy.
+    pending: u32,
+    posted_at: Instant,
+    /// Whether a repost is waiting on the timer.
+    scheduled: bool,
+    /// Whether a repost is being sent, so messages arriving meanwhile don't send another.
+    reposting: bool
+}
+
+impl Sticky {
+    fn new(content: String, message_id: Option<i64>, every_messages: i64, every_seconds: i64) -> Sticky {
+        Sticky {
+            content,
+            message_id: message_id.map(|message_id| message_id as u64),
+            every_messages: every_messages.max(1) as u32,
+            every_seconds: every_seconds.max(0) as u64,
+            pending: 0,
+            posted_at: Instant::now(),
+            scheduled: false,
+            reposting: false
+        }
+    }
+}
+
+/// Why a sticky message is being reposted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Repost {
+    /// Enough messages were sent after it.
+    Messages,
+    /// Its timer ran out.
+    Timer,
+    /// It was just set, so it's posted even if nothing was sent after the last copy.
+    Set
+}
+
+pub async fn load_stickies(database: &SqlitePool) -> Result<HashMap<u64, Sticky>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT channel_id, content, message_id, every_messages, every_seconds FROM sticky_messages")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter()
+        .map(|row| (row.channel_id as u64, Sticky::new(row.content, row.message_id, row.every_messages, row.every_seconds)))
+        .collect())
+}
+
+/// Reloads one channel's sticky message from the database after it's been changed.
+pub async fn reload_sticky(ctx: &Context, channel_id: ChannelId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let stickies = get_data::<StickyContainer>(ctx).await?;
+
+    let db_channel_id = channel_id.get() as i64;
+
+    let row = sqlx::query!("SELECT content, message_id, every_messages, every_seconds FROM sticky_messages WHERE channel_id = ?", db_channel_id)
+        .fetch_optional(&database)
+        .await?;
+
+    let mut stickies = stickies.lock().await;
+
+    match row {
+        Some(row) => {
+            let mut sticky = Sticky::new(row.content, row.message_id, row.every_messages, row.every_seconds);
+
+            // a repost that's already underway carries on
+            if let Some(current) = stickies.get(&channel_id.get()) {
+                (sticky.pending, sticky.posted_at, sticky.scheduled, sticky.reposting) = (current.pending, current.posted_at, current.scheduled, current.reposting);
+            }
+
+            stickies.insert(channel_id.get(), sticky);
+        }
+        None => {
+            stickies.remove(&channel_id.get());
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts a message sent in a channel with a sticky message, reposting it once enough were sent,
+/// or starting its timer.
+pub async fn handle_sticky(ctx: &Context, msg: &Message) {
+    let stickies = match get_data::<StickyContainer>(ctx).await {
+        Ok(stickies) => stickies,
+        Err(why) => {
+            error!("Couldn't check channel {} for a sticky message: {why}", msg.channel_id);
+            return;
+        }
+    };
+
+    let timer = {
+        let mut stickies = stickies.lock().await;
+
+        let Some(sticky) = stickies.get_mut(&msg.channel_id.get()) else {
+            return;
+        };
+
+        sticky.pending += 1;
+
+        if sticky.pending >= sticky.every_messages {
+            None
+        } else if sticky.every_seconds > 0 && !sticky.scheduled {
+            sticky.scheduled = true;
+            Some((sticky.posted_at + Duration::from_secs(sticky.every_seconds)).saturating_duration_since(Instant::now()))
+        } else {
+            return;
+        }
+    };
+
+    match timer {
+        None => repost_sticky(ctx, msg.channel_id, Repost::Messages).await,
+        Some(delay) => {
+            let (ctx, channel_id) = (ctx.clone(), msg.channel_id);
+
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                repost_sticky(&ctx, channel_id, Repost::Timer).await;
+            });
+        }
+    }
+}
+
+/// Deletes a channel's latest sticky copy and posts a new one at the bottom.
+pub async fn repost_sticky(ctx: &Context, channel_id: ChannelId, reason: Repost) {
+    let (database, stickies) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<StickyContainer>(ctx).await) {
+        (Ok(database), Ok(stickies)) => (database, stickies),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't repost the sticky message in channel {channel_id}: {why}");
+            return;
+        }
+    };
+
+    let (content, previous) = {
+        let mut stickies = stickies.lock().await;
+
+        let Some(sticky) = stickies.get_mut(&channel_id.get()) else {
+            return;
+        };
+
+        if reason == Repost::Timer {
+            sticky.scheduled = false;
+        }
+
+        // nothing was sent below the latest copy since, or another repost beat this one to it
+        if sticky.reposting || (sticky.pending == 0 && reason != Repost::Set) {
+            return;
+        }
+
+        sticky.reposting = true;
+
+        (sticky.content.clone(), sticky.message_id)
+    };
+
+    // the copy may have been deleted by hand already
+    if let Some(previous) = previous {
+        let _ = channel_id.delete_message(ctx, MessageId::new(previous)).await;
+    }
+
+    let builder = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    let message_id = match channel_id.send_message(ctx, builder).await {
+        Ok(message) => Some(message.id.get()),
+        Err(why) => {
+            warn!("Couldn't repost the sticky message in channel {channel_id}: {why}");
+            None
+        }
+    };
+
+    {
+        let mut stickies = stickies.lock().await;
+
+        if let Some(sticky) = stickies.get_mut(&channel_id.get()) {
+            sticky.reposting = false;
+
+            if message_id.is_some() {
+                (sticky.message_id, sticky.pending, sticky.posted_at) = (message_id, 0, Instant::now());
+            }
+        }
+    }
+
+    let Some(message_id) = message_id else {
+        return;
+    };
+
+    let (message_id, db_channel_id) = (message_id as i64, channel_id.get() as i64);
+
+    if let Err(why) = sqlx::query!("UPDATE sticky_messages SET message_id = ? WHERE channel_id = ?", message_id, db_channel_id).execute(&database).await {
+        error!("Failed to remember the sticky message copy in channel {channel_id}: {why}");
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use serenity::model::channel::Message;
+use serenity::model::guild::{Guild, Member};
+use serenity::model::mention::Mentionable;
+use serenity::model::user::User;
+use serenity::prelude::Context;
+
+/// Values and flags available while rendering a template.
+///
+/// Templates support:
+/// - placeholders such as `{user.mention}` or `{server}`, left untouched when unknown
+/// - conditionals, `{if booster}...{else}...{endif}`, negated with `{if !booster}`
+/// - random choices, `{random:Hi|Hello|Hey}`
+/// - literal braces, written as `{{` and `}}`
+#[derive(Default)]
+pub struct TemplateContext {
+    values: HashMap<String, String>,
+    flags: HashSet<String>
+}
+
+impl TemplateContext {
+    pub fn new() -> TemplateContext {
+        TemplateContext::default()
+    }
+
+    pub fn value(mut self, key: &str, value: impl ToString) -> TemplateContext {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn flag(mut self, flag: &str, set: bool) -> TemplateContext {
+        if set {
+            self.flags.insert(flag.to_string());
+        }
+        self
+    }
+
+    /// Adds `{user}`, `{user.mention}`, `{user.name}`, `{user.id}` and `{user.avatar}`, along with
+    /// the `bot` flag.
+    pub fn with_user(self, user: &User) -> TemplateContext {
+        self.value("user", user.mention())
+            .value("user.mention", user.mention())
+            .value("user.name", &user.name)
+            .value("user.id", user.id)
+            .value("user.avatar", user.face())
+            .flag("bot", user.bot)
+    }
+
+    /// Adds everything from `with_user`, plus `{user.nick}` and the `booster` and `nick` flags.
+    pub fn with_member(self, member: &Member) -> TemplateContext {
+        self.with_user(&member.user)
+            .value("user.nick", member.display_name())
+            .flag("booster", member.premium_since.is_some())
+            .flag("nick", member.nick.is_some())
+    }
+
+    /// Adds `{server}`, `{server.name}`, `{server.id}`, `{membercount}` and `{server.boosts}`.
+    pub fn with_guild(self, guild: &Guild) -> TemplateContext {
+        self.value("server", &guild.name)
+            .value("server.name", &guild.name)
+            .value("server.id", guild.id)
+            .value("membercount", guild.member_count)
+            .value("server.boosts", guild.premium_subscription_count.unwrap_or_default())
+    }
+
+    /// Every placeholder and flag set on this context, for showing users what they can use.
+    pub fn describe(&self) -> (Vec<&str>, Vec<&str>) {
+        let mut values: Vec<&str> = self.values.keys().map(String::as_str).collect();
+        let mut flags: Vec<&str> = self.flags.iter().map(String::as_str).collect();
+        values.sort_unstable();
+        flags.sort_unstable();
+
+        (values, flags)
+    }
+}
+
+#[derive(Debug)]
+pub enum TemplateError {
+    UnclosedTag,
+    UnclosedIf(String),
+    UnexpectedTag(String)
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnclosedTag => write!(f, "a `{{` is never closed with `}}`"),
+            TemplateError::UnclosedIf(flag) => write!(f, "`{{if {flag}}}` is missing its `{{endif}}`"),
+            TemplateError::UnexpectedTag(tag) => write!(f, "`{{{tag}}}` doesn't belong to an `{{if}}` block")
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+enum Token<'a> {
+    Text(&'a str),
+    Tag(&'a str)
+}
+
+enum Node<'a> {
+    Text(&'a str),
+    Value(&'a str),
+    Random(Vec<&'a str>),
+    If { flag: &'a str, negated: bool, then: Vec<Node<'a>>, otherwise: Vec<Node<'a>> }
+}
+
+/// A template context describing the message author, and the guild and channel it was sent in.
+pub async fn message_context(ctx: &Context, msg: &Message) -> TemplateContext {
+    let context = TemplateContext::new().value("channel", msg.channel_id.mention());
+
+    let context = match msg.member(ctx).await {
+        Ok(member) => context.with_member(&member),
+        Err(_) => context.with_user(&msg.author)
+    };
+
+    match msg.guild(&ctx.cache) {
+        Some(guild) => context.with_guild(&guild),
+        None => context
+    }
+}
+
+/// Renders a template against a context.
+pub fn render_template(template: &str, context: &TemplateContext) -> Result<String, TemplateError> {
+    let tokens = tokenize(template)?;
+    let mut tokens = tokens.into_iter();
+    let (nodes, end) = parse(&mut tokens)?;
+
+    if let Some(tag) = end {
+        return Err(TemplateError::UnexpectedTag(tag.to_string()));
+    }
+
+    let mut output = String::with_capacity(template.len());
+    render(&nodes, context, &mut output);
+
+    Ok(output)
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("{{") {
+            tokens.push(Token::Text("{"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("}}") {
+            tokens.push(Token::Text("}"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let end = after.find('}').ok_or(TemplateError::UnclosedTag)?;
+            tokens.push(Token::Tag(after[..end].trim()));
+            rest = &after[end + 1..];
+        } else {
+            // text runs up to the next brace, a lone closing brace is kept as text
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..].find(['{', '}']).map_or(rest.len(), |end| end + first);
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses nodes until the end of input or an `else`/`endif` tag, which is returned to the caller.
+fn parse<'a>(tokens: &mut std::vec::IntoIter<Token<'a>>) -> Result<(Vec<Node<'a>>, Option<&'a str>), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag
+        };
+
+        if tag == "else" || tag == "endif" {
+            return Ok((nodes, Some(tag)));
+        }
+
+        if let Some(condition) = tag.strip_prefix("if ") {
+            let condition = condition.trim();
+            let (flag, negated) = match condition.strip_prefix('!') {
+                Some(flag) => (flag.trim(), true),
+                None => (condition, false)
+            };
+
+            let (then, end) = parse(tokens)?;
+            let otherwise = match end {
+                Some("else") => match parse(tokens)? {
+                    (otherwise, Some("endif")) => otherwise,
+                    _ => return Err(TemplateError::UnclosedIf(condition.to_string()))
+                },
+                Some("endif") => Vec::new(),
+                _ => return Err(TemplateError::UnclosedIf(condition.to_string()))
+            };
+
+            nodes.push(Node::If { flag, negated, then, otherwise });
+        } else if let Some(choices) = tag.strip_prefix("random:") {
+            nodes.push(Node::Random(choices.split('|').collect()));
+        } else {
+            nodes.push(Node::Value(tag));
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn render(nodes: &[Node<'_>], context: &TemplateContext, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value(key) => match context.values.get(*key) {
+                Some(value) => output.push_str(value),
+                None => {
+                    output.push('{');
+                    output.push_str(key);
+                    output.push('}');
+                }
+            },
+            Node::Random(choices) => {
+                if let Some(choice) = choices.choose(&mut rand::thread_rng()) {
+                    output.push_str(choice);
+                }
+            }
+            Node::If { flag, negated, then, otherwise } => {
+                let branch = if context.flags.contains(*flag) != *negated { then } else { otherwise };
+                render(branch, context, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext::new()
+            .value("user.name", "Kanzoey")
+            .value("server", "Graf Zeppelin")
+            .flag("booster", true)
+            .flag("bot", false)
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        let rendered = render_template("Welcome {user.name} to { server }!", &context()).unwrap();
+        assert_eq!(rendered, "Welcome Kanzoey to Graf Zeppelin!");
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders() {
+        assert_eq!(render_template("Hi {user.nick}", &context()).unwrap(), "Hi {user.nick}");
+    }
+
+    #[test]
+    fn renders_conditionals() {
+        let template = "{if booster}Thanks for boosting!{else}Hi!{endif}{if !bot} Enjoy.{endif}{if bot} Beep.{endif}";
+        assert_eq!(render_template(template, &context()).unwrap(), "Thanks for boosting! Enjoy.");
+
+        let template = "{if nick}{if booster}both{endif}{else}no nick{endif}";
+        assert_eq!(render_template(template, &context()).unwrap(), "no nick");
+    }
+
+    #[test]
+    fn picks_a_random_choice() {
+        for _ in 0..20 {
+            let rendered = render_template("{random:Hi|Hello|Hey} there", &context()).unwrap();
+            assert!(["Hi there", "Hello there", "Hey there"].contains(&rendered.as_str()), "{rendered}");
+        }
+    }
+
+    #[test]
+    fn escapes_braces() {
+        assert_eq!(render_template("{{server}} is {server}", &context()).unwrap(), "{server} is Graf Zeppelin");
+        assert_eq!(render_template("a } b", &context()).unwrap(), "a } b");
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        assert!(matches!(render_template("Hi {user.name", &context()), Err(TemplateError::UnclosedTag)));
+        assert!(matches!(render_template("{if booster}boost", &context()), Err(TemplateError::UnclosedIf(flag)) if flag == "booster"));
+        assert!(matches!(render_template("{if booster}a{else}b", &context()), Err(TemplateError::UnclosedIf(_))));
+        assert!(matches!(render_template("a{endif}", &context()), Err(TemplateError::UnexpectedTag(tag)) if tag == "endif"));
+        assert!(matches!(render_template("a{else}b", &context()), Err(TemplateError::UnexpectedTag(tag)) if tag == "else"));
+    }
+}
@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use serenity::builder::{CreateActionRow, CreateAttachment, CreateButton, CreateChannel, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditChannel};
+use serenity::framework::standard::CommandError;
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use serenity::model::channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use serenity::model::permissions::Permissions;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::transcripts::{MAX_TRANSCRIPT_MESSAGES, TranscriptFormat, TranscriptRange, fetch_messages, render_transcript};
+use crate::utilities::errors::get_data;
+
+/// The custom ID of the button on ticket panels.
+pub const TICKET_OPEN_ID: &str = "ticket_open";
+
+/// What the opener and support staff can do in a ticket channel.
+const TICKET_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::READ_MESSAGE_HISTORY)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::EMBED_LINKS);
+
+/// The button members click to open a ticket.
+pub fn ticket_panel_components() -> Vec<CreateActionRow> {
+    let button = CreateButton::new(TICKET_OPEN_ID)
+        .label("Open Ticket")
+        .emoji('🎫')
+        .style(ButtonStyle::Primary);
+
+    vec![CreateActionRow::Buttons(vec![button])]
+}
+
+/// Opens a private ticket channel for whoever clicked a ticket panel's button, unless they
+/// already have one open.
+pub async fn handle_ticket_open(ctx: &Context, interaction: &ComponentInteraction) {
+    let Some(guild_id) = interaction.guild_id else {
+        return;
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't open a ticket: {why}");
+            return;
+        }
+    };
+
+    let content = match open_ticket(ctx, &database, guild_id, interaction).await {
+        Ok(content) => content,
+        Err(why) => {
+            warn!("Couldn't open a ticket for user {} in guild {guild_id}: {why}", interaction.user.id);
+            "I couldn't open a ticket, please ask a moderator to check my permissions.".to_string()
+        }
+    };
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+
+    if let Err(why) = interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await {
+        warn!("Couldn't respond to a ticket button in guild {guild_id}: {why}");
+    }
+}
+
+async fn open_ticket(ctx: &Context, database: &SqlitePool, guild_id: GuildId, interaction: &ComponentInteraction) -> Result<String, CommandError> {
+    let (db_guild_id, user_id) = (guild_id.get() as i64, interaction.user.id.get() as i64);
+
+    let settings = sqlx::query!("SELECT ticket_support_role_id, ticket_category_id FROM guild_settings WHERE guild_id = ?", db_guild_id)
+        .fetch_optional(database)
+        .await?;
+
+    let Some((support_role_id, category_id)) = settings
+        .and_then(|row| row.ticket_support_role_id.map(|role_id| (RoleId::new(role_id as u64), row.ticket_category_id))) else {
+        return Ok("Tickets aren't set up in this server.".to_string());
+    };
+
+    let open = sqlx::query!("SELECT channel_id FROM tickets WHERE guild_id = ? AND user_id = ? AND closed_at IS NULL", db_guild_id, user_id)
+        .fetch_optional(database)
+        .await?;
+
+    if let Some(open) = open {
+        return Ok(format!("You already have a ticket open in {}.", ChannelId::new(open.channel_id as u64).mention()));
+    }
+
+    let overwrites = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::VIEW_CHANNEL,
+            kind: PermissionOverwriteType::Role(RoleId::new(guild_id.get()))
+        },
+        PermissionOverwrite {
+            allow: TICKET_PERMISSIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(interaction.user.id)
+        },
+        PermissionOverwrite {
+            allow: TICKET_PERMISSIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(support_role_id)
+        },
+        PermissionOverwrite {
+            allow: TICKET_PERMISSIONS | Permissions::MANAGE_CHANNELS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user().id)
+        }
+    ];
+
+    let mut builder = CreateChannel::new(format!("ticket-{}", interaction.user.name))
+        .kind(ChannelType::Text)
+        .topic(format!("Support ticket for {}", interaction.user.tag()))
+        .permissions(overwrites);
+
+    if let Some(category_id) = category_id {
+        builder = builder.category(ChannelId::new(category_id as u64));
+    }
+
+    let channel = guild_id.create_channel(ctx, builder).await?;
+
+    let (channel_id, opened_at) = (channel.id.get() as i64, Utc::now().to_rfc3339());
+
+    let id = sqlx::query!(
+        "INSERT INTO tickets (guild_id, channel_id, user_id, opened_at) VALUES (?, ?, ?, ?)",
+        db_guild_id,
+        channel_id,
+        user_id,
+        opened_at
+    ).execute(database).await?.last_insert_rowid();
+
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .title(format!("Ticket #{id}"))
+        .description("Thanks for reaching out! Please describe what you need help with and someone will be with you shortly.\n\nUse `ticket close` once you're done.");
+
+    let greeting = CreateMessage::new()
+        .content(format!("{} {}", interaction.user.mention(), support_role_id.mention()))
+        .embed(embed);
+
+    channel.send_message(ctx, greeting).await?;
+
+    Ok(format!("Your ticket is open in {}.", channel.mention()))
+}
+
+/// Closes the ticket in a channel, archiving it so only staff can still see it, and posts its
+/// transcript to the log channel if one is set up. Returns false if the channel isn't an open
+/// ticket.
+pub async fn close_ticket(ctx: &Context, database: &SqlitePool, channel_id: ChannelId, closed_by: UserId) -> Result<bool, CommandError> {
+    let db_channel_id = channel_id.get() as i64;
+    let (closed_at, db_closed_by) = (Utc::now().to_rfc3339(), closed_by.get() as i64);
+
+    let Some(ticket) = sqlx::query!(
+        "UPDATE tickets SET closed_at = ?, closed_by = ? WHERE channel_id = ? AND closed_at IS NULL RETURNING id AS \"id!: i64\", guild_id, user_id, opened_at",
+        closed_at,
+        db_closed_by,
+        db_channel_id
+    ).fetch_optional(database).await? else {
+        return Ok(false);
+    };
+
+    let opener = UserId::new(ticket.user_id as u64);
+
+    channel_id.say(ctx, format!("This ticket was closed by {}.", closed_by.mention())).await?;
+    channel_id.delete_permission(ctx, PermissionOverwriteType::Member(opener)).await?;
+    channel_id.edit(ctx, EditChannel::new().name(format!("closed-{}", ticket.id))).await?;
+
+    let log_channel = sqlx::query!("SELECT ticket_log_channel_id FROM guild_settings WHERE guild_id = ?", ticket.guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.ticket_log_channel_id)
+        .map(|channel_id| ChannelId::new(channel_id as u64));
+
+    let Some(log_channel) = log_channel else {
+        return Ok(true);
+    };
+
+    let messages = fetch_messages(ctx, channel_id, TranscriptRange::Latest(MAX_TRANSCRIPT_MESSAGES)).await?;
+    let transcript = render_transcript(&messages, TranscriptFormat::Markdown, &format!("ticket #{}", ticket.id));
+    let opened_at = DateTime::parse_from_rfc3339(&ticket.opened_at).map_or_else(|_| ticket.opened_at.clone(), |time| format!("<t:{}:f>", time.timestamp()));
+
+    let embed = branded_embed(ctx, Some(GuildId::new(ticket.guild_id as u64))).await
+        .title(format!("Ticket #{} closed", ticket.id))
+        .field("Opened by", opener.mention().to_string(), true)
+        .field("Closed by", closed_by.mention().to_string(), true)
+        .field("Opened", opened_at, true)
+        .field("Channel", channel_id.mention().to_string(), true);
+
+    let builder = CreateMessage::new()
+        .embed(embed)
+        .add_file(CreateAttachment::bytes(transcript.into_bytes(), format!("ticket-{}.{}", ticket.id, TranscriptFormat::Markdown.extension())));
+
+    if let Err(why) = log_channel.send_message(ctx, builder).await {
+        warn!("Couldn't post the transcript of ticket {} to channel {log_channel}: {why}", ticket.id);
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serenity::model::id::{GuildId, UserId};
+use sqlx::SqlitePool;
+
+use crate::utilities::parsing::{format_utc_offset, parse_utc_offset};
+
+/// Common timezone abbreviations and their offsets from UTC in minutes. Offsets are fixed, so
+/// summer time goes by its own abbreviation (`CEST`, `PDT`).
+const ABBREVIATIONS: [(&str, i32); 29] = [
+    ("UTC", 0),
+    ("GMT", 0),
+    ("WET", 0),
+    ("BST", 60),
+    ("WEST", 60),
+    ("CET", 60),
+    ("CEST", 120),
+    ("EET", 120),
+    ("EEST", 180),
+    ("MSK", 180),
+    ("PKT", 300),
+    ("ICT", 420),
+    ("WIB", 420),
+    ("SGT", 480),
+    ("AWST", 480),
+    ("JST", 540),
+    ("KST", 540),
+    ("ACST", 570),
+    ("AEST", 600),
+    ("AEDT", 660),
+    ("NZST", 720),
+    ("NZDT", 780),
+    ("EST", -300),
+    ("MST", -420),
+    ("PST", -480),
+    ("EDT", -240),
+    ("CDT", -300),
+    ("MDT", -360),
+    ("PDT", -420)
+];
+
+/// Abbreviations in common use for more than one timezone, such as `IST` for India, Ireland and
+/// Israel. They're refused rather than guessed at.
+pub const AMBIGUOUS_ABBREVIATIONS: [&str; 3] = ["IST", "CST", "AST"];
+
+/// A timezone times are read and shown in: a zone from the IANA database, which follows summer
+/// time, or a fixed offset from UTC.
+#[derive(Clone, Debug)]
+pub enum Timezone {
+    Fixed(FixedOffset),
+    Zone(Tz)
+}
+
+/// The offset a `Timezone` has at some moment, which chrono keeps alongside each local time.
+#[derive(Clone, Debug)]
+pub struct ZoneOffset {
+    timezone: Timezone,
+    offset: FixedOffset
+}
+
+impl Timezone {
+    pub fn utc() -> Timezone {
+        Timezone::Fixed(FixedOffset::east_opt(0).unwrap())
+    }
+
+    /// Reads a timezone the way `Timezone`'s `Display` writes it, an IANA name or a UTC offset.
+    pub fn from_stored(timezone: &str) -> Option<Timezone> {
+        if timezone.starts_with("UTC") {
+            parse_utc_offset(timezone).map(Timezone::Fixed)
+        } else {
+            find_zone(timezone).map(Timezone::Zone)
+        }
+    }
+
+    /// The IANA name of a zone, `None` for fixed offsets.
+    pub fn zone_name(&self) -> Option<&str> {
+        match self {
+            Timezone::Fixed(_) => None,
+            Timezone::Zone(zone) => Some(zone.name())
+        }
+    }
+
+    /// The offset from UTC in effect right now.
+    pub fn current_offset(&self) -> FixedOffset {
+        self.offset_from_utc_datetime(&Utc::now().naive_utc()).fix()
+    }
+
+    pub fn is_utc(&self) -> bool {
+        matches!(self, Timezone::Fixed(offset) if offset.local_minus_utc() == 0)
+    }
+
+    fn with_offset(&self, seconds: i32) -> ZoneOffset {
+        ZoneOffset { timezone: self.clone(), offset: FixedOffset::east_opt(seconds).unwrap_or(FixedOffset::east_opt(0).unwrap()) }
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Fixed(offset) => f.write_str(&format_utc_offset(*offset)),
+            Timezone::Zone(zone) => f.write_str(zone.name())
+        }
+    }
+}
+
+impl Offset for ZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl fmt::Display for ZoneOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.offset.fmt(f)
+    }
+}
+
+impl TimeZone for Timezone {
+    type Offset = ZoneOffset;
+
+    fn from_offset(offset: &ZoneOffset) -> Timezone {
+        offset.timezone.clone()
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<ZoneOffset> {
+        self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Timezone::Fixed(offset) => MappedLocalTime::Single(self.with_offset(offset.local_minus_utc())),
+            Timezone::Zone(zone) => zone.offset_from_local_datetime(local).map(|offset| self.with_offset(offset.fix().local_minus_utc()))
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+        self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+        match self {
+            Timezone::Fixed(offset) => self.with_offset(offset.local_minus_utc()),
+            Timezone::Zone(zone) => self.with_offset(zone.offset_from_utc_datetime(utc).fix().local_minus_utc())
+        }
+    }
+}
+
+/// Parses a timezone as an IANA name (`Europe/Berlin`), an offset from UTC (`UTC+2`, `-05:30`)
+/// or a common abbreviation (`CET`). Abbreviations that mean several timezones aren't accepted.
+pub fn parse_timezone(arg: &str) -> Option<Timezone> {
+    let upper = arg.trim().to_uppercase();
+
+    if AMBIGUOUS_ABBREVIATIONS.contains(&upper.as_str()) {
+        return None;
+    }
+
+    ABBREVIATIONS.iter()
+        .find(|(name, _)| *name == upper)
+        .and_then(|(_, minutes)| FixedOffset::east_opt(minutes * 60))
+        .or_else(|| parse_utc_offset(&upper))
+        .map(Timezone::Fixed)
+        .or_else(|| find_zone(arg.trim()).map(Timezone::Zone))
+}
+
+/// A zone from the IANA database by its name in any case, such as `europe/berlin`.
+fn find_zone(name: &str) -> Option<Tz> {
+    Tz::from_str_insensitive(name.trim()).ok()
+}
+
+/// The timezone a user set, if any.
+pub async fn user_timezone(database: &SqlitePool, user_id: UserId) -> Result<Option<Timezone>, sqlx::Error> {
+    let user_id = user_id.get() as i64;
+
+    let row = sqlx::query!("SELECT utc_offset, timezone FROM user_timezones WHERE user_id = ?", user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.and_then(|row| stored_timezone(row.timezone, row.utc_offset)))
+}
+
+/// The timezone a guild set, if any.
+pub async fn guild_timezone(database: &SqlitePool, guild_id: GuildId) -> Result<Option<Timezone>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!("SELECT utc_offset, timezone FROM guild_timezones WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.and_then(|row| stored_timezone(row.timezone, row.utc_offset)))
+}
+
+/// The timezone a guild's own features, like birthdays and scheduled messages, follow. UTC unless
+/// the guild set a timezone.
+pub async fn guild_timezone_or_utc(database: &SqlitePool, guild_id: GuildId) -> Result<Timezone, sqlx::Error> {
+    Ok(guild_timezone(database, guild_id).await?.unwrap_or_else(Timezone::utc))
+}
+
+/// The timezone times a user gives are read in: their own timezone, else the guild's they're in,
+/// else UTC.
+pub async fn local_timezone(database: &SqlitePool, user_id: UserId, guild_id: Option<GuildId>) -> Result<Timezone, sqlx::Error> {
+    if let Some(timezone) = user_timezone(database, user_id).await? {
+        return Ok(timezone);
+    }
+
+    match guild_id {
+        Some(guild_id) => guild_timezone_or_utc(database, guild_id).await,
+        None => Ok(Timezone::utc())
+    }
+}
+
+/// Formats a time as it is in a timezone, e.g. `Tuesday 14:05`.
+pub fn format_local_time(time: DateTime<Utc>, timezone: &Timezone) -> String {
+    time.with_timezone(timezone).format("%A %H:%M").to_string()
+}
+
+/// A stored timezone, falling back on its offset if the named zone is no longer in the database.
+fn stored_timezone(name: Option<String>, utc_offset: i64) -> Option<Timezone> {
+    name.and_then(|name| find_zone(&name))
+        .map(Timezone::Zone)
+        .or_else(|| FixedOffset::east_opt(utc_offset as i32).map(Timezone::Fixed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_abbreviations_and_offsets() {
+        let offset = |arg| match parse_timezone(arg) {
+            Some(Timezone::Fixed(offset)) => Some(offset.local_minus_utc()),
+            _ => None
+        };
+
+        assert_eq!(offset("UTC"), Some(0));
+        assert_eq!(offset("cet"), Some(3600));
+        assert_eq!(offset("PDT"), Some(-25_200));
+        assert_eq!(offset("UTC+5:30"), Some(19_800));
+        assert_eq!(offset("-03:00"), Some(-10_800));
+        assert!(parse_timezone("utc").unwrap().is_utc());
+    }
+
+    #[test]
+    fn rejects_ambiguous_and_unknown_timezones() {
+        for arg in ["IST", "cst", "AST", "Mars/Olympus_Mons", "not a zone", "../etc/passwd", "UTC+25"] {
+            assert!(parse_timezone(arg).is_none(), "`{arg}` was accepted");
+        }
+    }
+
+    #[test]
+    fn reads_back_stored_timezones() {
+        for stored in ["UTC+1", "UTC-3", "UTC+5:30", "Europe/Berlin", "America/New_York"] {
+            assert_eq!(Timezone::from_stored(stored).unwrap().to_string(), stored);
+        }
+
+        assert!(Timezone::from_stored("Mars/Olympus_Mons").is_none());
+        assert_eq!(stored_timezone(Some("Mars/Olympus_Mons".to_string()), 3600).unwrap().to_string(), "UTC+1");
+    }
+
+    #[test]
+    fn follows_zone_summer_time() {
+        let berlin = parse_timezone("europe/berlin").unwrap();
+
+        assert_eq!(berlin.zone_name(), Some("Europe/Berlin"));
+
+        let offset = |time| berlin.offset_from_utc_datetime(&time).fix().local_minus_utc();
+        assert_eq!(offset(local(2024, 1, 15, 12, 0)), 3600);
+        assert_eq!(offset(local(2024, 7, 1, 12, 0)), 7200);
+
+        // the clocks skip 02:00 to 03:00 in March and repeat 02:00 to 03:00 in October
+        assert!(matches!(berlin.from_local_datetime(&local(2024, 3, 31, 2, 30)), MappedLocalTime::None));
+        assert!(matches!(berlin.from_local_datetime(&local(2024, 10, 27, 2, 30)), MappedLocalTime::Ambiguous(..)));
+
+        let summer = berlin.from_local_datetime(&local(2024, 7, 1, 14, 0)).single().unwrap();
+        assert_eq!(summer.with_timezone(&Utc), Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn follows_southern_summer_time() {
+        let sydney = parse_timezone("Australia/Sydney").unwrap();
+        let offset = |time| sydney.offset_from_utc_datetime(&time).fix().local_minus_utc();
+
+        assert_eq!(offset(local(2024, 1, 15, 12, 0)), 39_600);
+        assert_eq!(offset(local(2024, 7, 1, 12, 0)), 36_000);
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::Utc;
+use serenity::builder::GetMessages;
+use serenity::framework::standard::CommandError;
+use serenity::model::channel::{Embed, Message};
+use serenity::model::id::{ChannelId, MessageId};
+use serenity::prelude::Context;
+
+/// Transcripts stop after this many messages.
+pub const MAX_TRANSCRIPT_MESSAGES: usize = 1000;
+
+/// Longest embed description kept in a transcript's summary of the embed.
+const MAX_EMBED_SUMMARY_LENGTH: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Markdown,
+    Html
+}
+
+impl TranscriptFormat {
+    pub fn parse(format: &str) -> Option<TranscriptFormat> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Some(TranscriptFormat::Markdown),
+            "html" => Some(TranscriptFormat::Html),
+            _ => None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Html => "html"
+        }
+    }
+}
+
+/// Which messages of a channel go into a transcript.
+#[derive(Clone, Copy)]
+pub enum TranscriptRange {
+    /// The most recent messages.
+    Latest(usize),
+    /// Every message after this one, up to `MAX_TRANSCRIPT_MESSAGES`.
+    After(MessageId)
+}
+
+/// Fetches the messages in a range, oldest first.
+pub async fn fetch_messages(ctx: &Context, channel_id: ChannelId, range: TranscriptRange) -> Result<Vec<Message>, CommandError> {
+    let mut messages: Vec<Message> = Vec::new();
+
+    match range {
+        TranscriptRange::Latest(count) => {
+            let count = count.min(MAX_TRANSCRIPT_MESSAGES);
+            let mut before: Option<MessageId> = None;
+
+            while messages.len() < count {
+                let mut request = GetMessages::new().limit((count - messages.len()).min(100) as u8);
+
+                if let Some(before) = before {
+                    request = request.before(before);
+                }
+
+                let batch = channel_id.messages(ctx, request).await?;
+
+                let Some(oldest) = batch.iter().map(|message| message.id).min() else {
+                    break;
+                };
+
+                before = Some(oldest);
+                messages.extend(batch);
+            }
+        }
+        TranscriptRange::After(after) => {
+            let mut after = after;
+
+            while messages.len() < MAX_TRANSCRIPT_MESSAGES {
+                let limit = (MAX_TRANSCRIPT_MESSAGES - messages.len()).min(100) as u8;
+                let batch = channel_id.messages(ctx, GetMessages::new().after(after).limit(limit)).await?;
+
+                let Some(newest) = batch.iter().map(|message| message.id).max() else {
+                    break;
+                };
+
+                after = newest;
+                messages.extend(batch);
+            }
+        }
+    }
+
+    messages.sort_by_key(|message| message.id);
+
+    Ok(messages)
+}
+
+fn embed_summary(embed: &Embed) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(title) = &embed.title {
+        parts.push(title.clone());
+    }
+
+    if let Some(description) = &embed.description {
+        let mut summary = description.chars().take(MAX_EMBED_SUMMARY_LENGTH).collect::<String>();
+
+        if summary.len() < description.len() {
+            summary.push('…');
+        }
+
+        parts.push(summary);
+    }
+
+    if parts.is_empty() {
+        parts.push(embed.url.clone().unwrap_or_else(|| "Embed".to_string()));
+    }
+
+    parts.join(": ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Renders messages as a transcript titled after where they're from, e.g. `#general`.
+pub fn render_transcript(messages: &[Message], format: TranscriptFormat, title: &str) -> String {
+    let exported = format!("Exported {} UTC, {} message{}", Utc::now().format("%Y-%m-%d %H:%M"), messages.len(), if messages.len() == 1 { "" } else { "s" });
+
+    match format {
+        TranscriptFormat::Markdown => render_markdown(messages, title, &exported),
+        TranscriptFormat::Html => render_html(messages, title, &exported)
+    }
+}
+
+fn render_markdown(messages: &[Message], title: &str, exported: &str) -> String {
+    let mut output = format!("# Transcript of {title}\n{exported}\n");
+
+    for message in messages {
+        output.push_str(&format!("\n**{}** ({}) at {}\n", message.author.tag(), message.author.id, message.timestamp.format("%Y-%m-%d %H:%M")));
+
+        if !message.content.is_empty() {
+            output.push_str(&message.content);
+            output.push('\n');
+        }
+
+        for attachment in &message.attachments {
+            output.push_str(&format!("- Attachment: [{}]({})\n", attachment.filename, attachment.url));
+        }
+
+        for embed in &message.embeds {
+            output.push_str(&format!("- Embed: {}\n", embed_summary(embed)));
+        }
+    }
+
+    output
+}
+
+fn render_html(messages: &[Message], title: &str, exported: &str) -> String {
+    let title = escape_html(title);
+
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Transcript of {title}</title>\n<style>\
+        body {{ background: #313338; color: #dbdee1; font-family: sans-serif; margin: 2em; }}\
+        .message {{ margin: 1em 0; }}\
+        .author {{ font-weight: bold; color: #f2f3f5; }}\
+        .time {{ color: #949ba4; font-size: 0.8em; margin-left: 0.5em; }}\
+        .content {{ white-space: pre-wrap; }}\
+        .embed {{ border-left: 4px solid #8b0000; padding-left: 0.5em; color: #b5bac1; }}\
+        a {{ color: #00a8fc; }} img {{ max-width: 400px; display: block; }}\
+        </style>\n</head>\n<body>\n<h1>Transcript of {title}</h1>\n<p>{}</p>\n",
+        escape_html(exported)
+    );
+
+    for message in messages {
+        output.push_str(&format!(
+            "<div class=\"message\">\n<span class=\"author\" title=\"{}\">{}</span><span class=\"time\">{}</span>\n",
+            message.author.id,
+            escape_html(&message.author.tag()),
+            message.timestamp.format("%Y-%m-%d %H:%M")
+        ));
+
+        if !message.content.is_empty() {
+            output.push_str(&format!("<div class=\"content\">{}</div>\n", escape_html(&message.content)));
+        }
+
+        for attachment in &message.attachments {
+            let url = escape_html(&attachment.url);
+
+            if attachment.content_type.as_deref().is_some_and(|kind| kind.starts_with("image/")) {
+                output.push_str(&format!("<a href=\"{url}\"><img src=\"{url}\" alt=\"{}\"></a>\n", escape_html(&attachment.filename)));
+            } else {
+                output.push_str(&format!("<div><a href=\"{url}\">{}</a></div>\n", escape_html(&attachment.filename)));
+            }
+        }
+
+        for embed in &message.embeds {
+            output.push_str(&format!("<div class=\"embed\">{}</div>\n", escape_html(&embed_summary(embed))));
+        }
+
+        output.push_str("</div>\n");
+    }
+
+    output.push_str("</body>\n</html>\n");
+
+    output
+}
@@ -0,0 +1,108 @@
+use rand::seq::SliceRandom;
+use serenity::model::id::{GuildId, UserId};
+use sqlx::SqlitePool;
+
+/// A multiple choice trivia question with one right answer.
+pub struct Question {
+    pub category: &'static str,
+    pub question: &'static str,
+    pub answer: &'static str,
+    pub wrong: [&'static str; 3]
+}
+
+impl Question {
+    /// The answers in a random order, with the position of the right one.
+    pub fn shuffled_answers(&self) -> (Vec<&'static str>, usize) {
+        let mut answers = vec![self.answer, self.wrong[0], self.wrong[1], self.wrong[2]];
+        answers.shuffle(&mut rand::thread_rng());
+
+        let correct = answers.iter().position(|answer| *answer == self.answer).unwrap_or_default();
+        (answers, correct)
+    }
+}
+
+/// One member's answer to a round, for scoring.
+pub struct TriviaAnswer {
+    pub user_id: UserId,
+    pub correct: bool,
+    pub points: i64
+}
+
+/// The questions games are drawn from, bundled so trivia works without any outside service.
+pub const QUESTIONS: &[Question] = &[
+    Question { category: "Science", question: "What is the chemical symbol for gold?", answer: "Au", wrong: ["Ag", "Gd", "Go"] },
+    Question { category: "Science", question: "Which planet is known as the Red Planet?", answer: "Mars", wrong: ["Venus", "Jupiter", "Mercury"] },
+    Question { category: "Science", question: "What gas do plants mostly absorb from the air for photosynthesis?", answer: "Carbon dioxide", wrong: ["Oxygen", "Nitrogen", "Hydrogen"] },
+    Question { category: "Science", question: "How many bones are in the adult human body?", answer: "206", wrong: ["186", "212", "230"] },
+    Question { category: "Science", question: "What is the hardest natural substance?", answer: "Diamond", wrong: ["Quartz", "Granite", "Topaz"] },
+    Question { category: "Science", question: "Which planet has the most moons confirmed as of 2023?", answer: "Saturn", wrong: ["Jupiter", "Uranus", "Neptune"] },
+    Question { category: "Science", question: "What is the most abundant gas in Earth's atmosphere?", answer: "Nitrogen", wrong: ["Oxygen", "Argon", "Carbon dioxide"] },
+    Question { category: "Science", question: "What part of the cell contains most of its genetic material?", answer: "The nucleus", wrong: ["The ribosome", "The cell membrane", "The mitochondria"] },
+    Question { category: "Science", question: "At what temperature in Celsius does water boil at sea level?", answer: "100", wrong: ["90", "110", "212"] },
+    Question { category: "Science", question: "Which element has the atomic number 1?", answer: "Hydrogen", wrong: ["Helium", "Lithium", "Carbon"] },
+    Question { category: "Geography", question: "What is the capital of Australia?", answer: "Canberra", wrong: ["Sydney", "Melbourne", "Perth"] },
+    Question { category: "Geography", question: "Which is the largest ocean on Earth?", answer: "The Pacific", wrong: ["The Atlantic", "The Indian", "The Arctic"] },
+    Question { category: "Geography", question: "Which country has the largest population in Africa?", answer: "Nigeria", wrong: ["Egypt", "Ethiopia", "South Africa"] },
+    Question { category: "Geography", question: "What is the longest river in South America?", answer: "The Amazon", wrong: ["The Paraná", "The Orinoco", "The Magdalena"] },
+    Question { category: "Geography", question: "What is the capital of Canada?", answer: "Ottawa", wrong: ["Toronto", "Montreal", "Vancouver"] },
+    Question { category: "Geography", question: "Mount Everest sits on the border of Nepal and which other country?", answer: "China", wrong: ["India", "Bhutan", "Pakistan"] },
+    Question { category: "Geography", question: "Which is the smallest country in the world by area?", answer: "Vatican City", wrong: ["Monaco", "San Marino", "Liechtenstein"] },
+    Question { category: "Geography", question: "Which desert is the largest hot desert in the world?", answer: "The Sahara", wrong: ["The Gobi", "The Kalahari", "The Arabian"] },
+    Question { category: "Geography", question: "What is the capital of Japan?", answer: "Tokyo", wrong: ["Kyoto", "Osaka", "Hiroshima"] },
+    Question { category: "Geography", question: "How many continents are there?", answer: "7", wrong: ["5", "6", "8"] },
+    Question { category: "History", question: "In which year did the Berlin Wall fall?", answer: "1989", wrong: ["1987", "1991", "1961"] },
+    Question { category: "History", question: "Who was the first person to walk on the Moon?", answer: "Neil Armstrong", wrong: ["Buzz Aldrin", "Yuri Gagarin", "Michael Collins"] },
+    Question { category: "History", question: "Which ancient civilization built Machu Picchu?", answer: "The Inca", wrong: ["The Maya", "The Aztecs", "The Olmecs"] },
+    Question { category: "History", question: "In which year did the Titanic sink?", answer: "1912", wrong: ["1905", "1915", "1921"] },
+    Question { category: "History", question: "Who painted the Mona Lisa?", answer: "Leonardo da Vinci", wrong: ["Michelangelo", "Raphael", "Donatello"] },
+    Question { category: "History", question: "Which empire was ruled by Julius Caesar's adopted heir Augustus?", answer: "The Roman Empire", wrong: ["The Byzantine Empire", "The Ottoman Empire", "The Persian Empire"] },
+    Question { category: "History", question: "In which year did World War II end?", answer: "1945", wrong: ["1944", "1946", "1939"] },
+    Question { category: "History", question: "What was the name of the ship the Pilgrims sailed to America in 1620?", answer: "The Mayflower", wrong: ["The Santa Maria", "The Endeavour", "The Beagle"] },
+    Question { category: "Technology", question: "What does CPU stand for?", answer: "Central processing unit", wrong: ["Computer personal unit", "Central program utility", "Core processing unit"] },
+    Question { category: "Technology", question: "Which company created the Rust programming language?", answer: "Mozilla", wrong: ["Google", "Microsoft", "Apple"] },
+    Question { category: "Technology", question: "What does HTML stand for?", answer: "HyperText Markup Language", wrong: ["HighText Machine Language", "Hyperlink Text Management Language", "Home Tool Markup Language"] },
+    Question { category: "Technology", question: "How many bits are in a byte?", answer: "8", wrong: ["4", "16", "32"] },
+    Question { category: "Technology", question: "What year was the first iPhone released?", answer: "2007", wrong: ["2005", "2008", "2010"] },
+    Question { category: "Technology", question: "Which number system uses only 0 and 1?", answer: "Binary", wrong: ["Decimal", "Hexadecimal", "Octal"] },
+    Question { category: "Entertainment", question: "Which video game features a plumber named Mario?", answer: "Super Mario Bros.", wrong: ["Sonic the Hedgehog", "The Legend of Zelda", "Metroid"] },
+    Question { category: "Entertainment", question: "How many players are on the field for one team in a game of soccer?", answer: "11", wrong: ["9", "10", "12"] },
+    Question { category: "Entertainment", question: "Which band released the album Abbey Road?", answer: "The Beatles", wrong: ["The Rolling Stones", "Queen", "Pink Floyd"] },
+    Question { category: "Entertainment", question: "In chess, which piece can only move diagonally?", answer: "The bishop", wrong: ["The rook", "The knight", "The queen"] },
+    Question { category: "Entertainment", question: "Who wrote the play Romeo and Juliet?", answer: "William Shakespeare", wrong: ["Charles Dickens", "Jane Austen", "Christopher Marlowe"] },
+    Question { category: "Entertainment", question: "In Minecraft, which material is needed to craft a nether portal frame?", answer: "Obsidian", wrong: ["Netherrack", "Bedrock", "Cobblestone"] },
+    Question { category: "Nature", question: "What is the largest mammal on Earth?", answer: "The blue whale", wrong: ["The African elephant", "The giraffe", "The sperm whale"] },
+    Question { category: "Nature", question: "How many legs does a spider have?", answer: "8", wrong: ["6", "10", "12"] },
+    Question { category: "Nature", question: "What is the fastest land animal?", answer: "The cheetah", wrong: ["The lion", "The pronghorn", "The greyhound"] },
+    Question { category: "Nature", question: "What do bees collect from flowers to make honey?", answer: "Nectar", wrong: ["Pollen", "Sap", "Dew"] },
+    Question { category: "Nature", question: "Which bird is known for being unable to fly and living in Antarctica?", answer: "The emperor penguin", wrong: ["The ostrich", "The kiwi", "The albatross"] }
+];
+
+/// Picks questions for a game, never the same one twice.
+pub fn pick_questions(count: usize) -> Vec<&'static Question> {
+    QUESTIONS.choose_multiple(&mut rand::thread_rng(), count).collect()
+}
+
+/// Adds the answers from a round to each member's lifetime trivia score in the guild.
+pub async fn record_answers(database: &SqlitePool, guild_id: GuildId, answers: &[TriviaAnswer]) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let mut transaction = database.begin().await?;
+
+    for answer in answers {
+        let user_id = answer.user_id.get() as i64;
+        let correct = i64::from(answer.correct);
+
+        sqlx::query!(
+            "INSERT INTO trivia_scores (guild_id, user_id, points, correct, answered) VALUES (?, ?, ?, ?, 1)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET
+                points = points + excluded.points,
+                correct = correct + excluded.correct,
+                answered = answered + 1",
+            guild_id,
+            user_id,
+            answer.points,
+            correct
+        ).execute(&mut *transaction).await?;
+    }
+
+    transaction.commit().await
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use serde::Serialize;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::utilities::errors::{get_data, BotError};
+use crate::utilities::global_data::{DatabaseConnectionContainer, ReqwestClientContainer, TtsReadersContainer};
+use crate::utilities::playback::{Playback, listeners, play_in_voice};
+
+/// Longest text that's spoken at once. Messages being read out are cut off here.
+pub const MAX_TTS_LENGTH: usize = 300;
+
+/// How long the engine gets to answer.
+const TTS_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// The voice and language a guild's speech is read in.
+#[derive(Clone)]
+pub struct TtsVoice {
+    pub voice: Option<String>,
+    pub language: String
+}
+
+/// A channel whose messages are read out, and the voice channel they're read into.
+#[derive(Clone)]
+pub struct TtsReader {
+    pub voice_channel_id: ChannelId,
+    pub voice: TtsVoice
+}
+
+/// What the engine at `TTS_URL` is sent.
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    text: &'a str,
+    voice: Option<&'a str>,
+    language: &'a str
+}
+
+pub async fn tts_voice(database: &SqlitePool, guild_id: GuildId) -> Result<TtsVoice, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!("SELECT voice, language FROM tts_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(match row {
+        Some(row) => TtsVoice { voice: row.voice, language: row.language },
+        None => TtsVoice { voice: None, language: "en".to_string() }
+    })
+}
+
+/// Loads every channel that's read out, by channel.
+pub async fn load_tts_readers(database: &SqlitePool) -> Result<HashMap<u64, TtsReader>, sqlx::Error> {
+    let rows = sqlx::query!("SELECT voice, language, read_channel_id, voice_channel_id FROM tts_settings WHERE read_channel_id IS NOT NULL AND voice_channel_id IS NOT NULL")
+        .fetch_all(database)
+        .await?;
+
+    Ok(rows.into_iter()
+        .filter_map(|row| {
+            let reader = TtsReader {
+                voice_channel_id: ChannelId::new(row.voice_channel_id? as u64),
+                voice: TtsVoice { voice: row.voice, language: row.language }
+            };
+
+            Some((row.read_channel_id? as u64, reader))
+        })
+        .collect())
+}
+
+/// Reloads which channels are read out after a guild's settings change.
+pub async fn reload_tts_readers(ctx: &Context) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let readers = get_data::<TtsReadersContainer>(ctx).await?;
+
+    let loaded = load_tts_readers(&database).await?;
+    *readers.write().await = loaded;
+
+    Ok(())
+}
+
+/// Turns text into speech with the engine at `TTS_URL`, which is sent the text, voice and
+/// language as JSON and answers with the audio. `TTS_API_KEY` is sent as a bearer token if set.
+pub async fn synthesize(ctx: &Context, text: &str, voice: &TtsVoice) -> Result<Vec<u8>, String> {
+    let Ok(url) = env::var("TTS_URL") else {
+        return Err("Text-to-speech isn't set up for this bot.".to_string());
+    };
+
+    let client = get_data::<ReqwestClientContainer>(ctx).await.map_err(|why| why.to_string())?;
+
+    let body = SpeechRequest { text, voice: voice.voice.as_deref(), language: &voice.language };
+    let mut request = client.post(url).json(&body).timeout(TTS_TIMEOUT);
+
+    if let Ok(key) = env::var("TTS_API_KEY") {
+        request = request.bearer_auth(key);
+    }
+
+    let failed = |why: reqwest::Error| {
+        warn!("Text-to-speech request failed: {why}");
+        "The text-to-speech engine couldn't read that, try again later.".to_string()
+    };
+
+    let response = request.send().await.and_then(|response| response.error_for_status()).map_err(failed)?;
+
+    let audio = response.bytes().await.map_err(failed)?;
+
+    Ok(audio.to_vec())
+}
+
+/// Reads a message from a read-out channel aloud in its voice channel, while anyone's there to hear it.
+pub async fn handle_tts_reader(ctx: &Context, msg: &Message) {
+    let Ok(readers) = get_data::<TtsReadersContainer>(ctx).await else {
+        return;
+    };
+
+    let Some(reader) = readers.read().await.get(&msg.channel_id.get()).cloned() else {
+        return;
+    };
+
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    if listeners(ctx, guild_id, reader.voice_channel_id) == 0 {
+        return;
+    }
+
+    let content = msg.content_safe(&ctx.cache);
+
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let name = msg.author_nick(ctx).await.unwrap_or_else(|| msg.author.display_name().to_string());
+    let text = format!("{name} said {}", content.chars().take(MAX_TTS_LENGTH).collect::<String>());
+
+    let audio = match synthesize(ctx, &text, &reader.voice).await {
+        Ok(audio) => audio,
+        Err(why) => {
+            warn!("Couldn't read out a message in channel {}: {why}", msg.channel_id);
+            return;
+        }
+    };
+
+    if let Err(why) = play_in_voice(ctx, guild_id, reader.voice_channel_id, audio.into(), Playback::Queue).await {
+        warn!("Couldn't read out a message in voice channel {}: {why}", reader.voice_channel_id);
+    }
+}
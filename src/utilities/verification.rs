@@ -0,0 +1,296 @@
+use chrono::Utc;
+use rand::Rng;
+use serenity::builder::{CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage};
+use serenity::framework::standard::CommandError;
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use serenity::model::channel::Message;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::autoroles::assign_autoroles;
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::errors::get_data;
+
+/// The custom ID of the verify button.
+pub const VERIFY_ID: &str = "verify";
+
+/// Wrong answers to a captcha before a new one is asked.
+const MAX_ATTEMPTS: i64 = 3;
+
+pub struct VerificationSettings {
+    pub unverified_role: RoleId,
+    pub verified_role: Option<RoleId>,
+    pub captcha: bool
+}
+
+/// The guild's verification settings, if it has verification set up.
+pub async fn verification_settings(database: &SqlitePool, guild_id: GuildId) -> Result<Option<VerificationSettings>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!("SELECT unverified_role_id, verified_role_id, mode FROM verification_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(row.map(|row| VerificationSettings {
+        unverified_role: RoleId::new(row.unverified_role_id as u64),
+        verified_role: row.verified_role_id.map(|role_id| RoleId::new(role_id as u64)),
+        captcha: row.mode == "captcha"
+    }))
+}
+
+/// Whether a member has joined and not verified yet.
+pub async fn awaiting_verification(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<bool, sqlx::Error> {
+    let (guild_id, user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    let pending = sqlx::query!("SELECT user_id FROM pending_verifications WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(pending.is_some())
+}
+
+/// The verify button posted in the verification channel.
+pub fn verify_components() -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![CreateButton::new(VERIFY_ID).label("Verify").style(ButtonStyle::Success)])]
+}
+
+/// A simple sum to answer, with its answer.
+fn captcha() -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let (first, second) = (rng.gen_range(2..=20), rng.gen_range(2..=20));
+
+    (format!("What is {first} + {second}?"), (first + second).to_string())
+}
+
+/// Gives a new member the unverified role if the guild has verification set up, and remembers
+/// they still have to verify. Returns whether they did, so autoroles can wait until they're
+/// verified.
+pub async fn handle_verification_join(ctx: &Context, member: &Member) -> bool {
+    if member.user.bot {
+        return false;
+    }
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check member {} for verification: {why}", member.user.id);
+            return false;
+        }
+    };
+
+    let settings = match verification_settings(&database, member.guild_id).await {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return false,
+        Err(why) => {
+            error!("Failed to fetch the verification settings of guild {}: {why}", member.guild_id);
+            return false;
+        }
+    };
+
+    let (guild_id, user_id, joined_at) = (member.guild_id.get() as i64, member.user.id.get() as i64, Utc::now().to_rfc3339());
+
+    // rejoining starts over
+    let inserted = sqlx::query!(
+        "INSERT INTO pending_verifications (guild_id, user_id, joined_at) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET joined_at = excluded.joined_at, answer = NULL, attempts = 0, asked_at = NULL",
+        guild_id,
+        user_id,
+        joined_at
+    ).execute(&database).await;
+
+    if let Err(why) = inserted {
+        error!("Failed to remember that user {} has to verify in guild {}: {why}", member.user.id, member.guild_id);
+    }
+
+    if let Err(why) = ctx.http.add_member_role(member.guild_id, member.user.id, settings.unverified_role, Some("Awaiting verification")).await {
+        warn!("Couldn't give the unverified role to user {} in guild {}: {why}", member.user.id, member.guild_id);
+    }
+
+    true
+}
+
+/// Forgets a pending verification once the member leaves.
+pub async fn forget_verification(ctx: &Context, guild_id: GuildId, user_id: UserId) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't forget the verification of user {user_id}: {why}");
+            return;
+        }
+    };
+
+    let (db_guild_id, db_user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    if let Err(why) = sqlx::query!("DELETE FROM pending_verifications WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id).execute(&database).await {
+        error!("Failed to forget the pending verification of user {user_id} in guild {guild_id}: {why}");
+    }
+}
+
+/// Swaps the unverified role for the verified one and gives the member their autoroles.
+async fn complete_verification(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId, settings: &VerificationSettings) -> Result<(), CommandError> {
+    if let Some(verified_role) = settings.verified_role {
+        ctx.http.add_member_role(guild_id, user_id, verified_role, Some("Verified")).await?;
+    }
+
+    ctx.http.remove_member_role(guild_id, user_id, settings.unverified_role, Some("Verified")).await?;
+
+    let (db_guild_id, db_user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    sqlx::query!("DELETE FROM pending_verifications WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id)
+        .execute(database)
+        .await?;
+
+    assign_autoroles(ctx, database, guild_id, user_id).await?;
+
+    Ok(())
+}
+
+/// Sends a member a new captcha by DM, remembering its answer. Returns whether it could be sent.
+async fn send_captcha(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<bool, CommandError> {
+    let (question, answer) = captcha();
+    let (db_guild_id, db_user_id, asked_at) = (guild_id.get() as i64, user_id.get() as i64, Utc::now().to_rfc3339());
+
+    // remembered first, so quick answers aren't checked against the previous captcha
+    sqlx::query!(
+        "INSERT INTO pending_verifications (guild_id, user_id, joined_at, answer, attempts, asked_at) VALUES (?, ?, ?, ?, 0, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET answer = excluded.answer, attempts = 0, asked_at = excluded.asked_at",
+        db_guild_id,
+        db_user_id,
+        asked_at,
+        answer,
+        asked_at
+    ).execute(database).await?;
+
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    let content = format!("To verify in **{guild_name}**, reply with the answer to this question:\n**{question}**");
+
+    let sent = match user_id.create_dm_channel(ctx).await {
+        Ok(dm) => dm.id.send_message(ctx, CreateMessage::new().content(content)).await.is_ok(),
+        Err(_) => false
+    };
+
+    Ok(sent)
+}
+
+/// Verifies a member who pressed the verify button, or sends them a captcha if the guild wants
+/// one answered first.
+pub async fn handle_verify_button(ctx: &Context, interaction: &ComponentInteraction) {
+    let (Some(guild_id), Some(member)) = (interaction.guild_id, interaction.member.as_ref()) else {
+        return;
+    };
+
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't handle a verify button: {why}");
+            return;
+        }
+    };
+
+    let result = async {
+        let Some(settings) = verification_settings(&database, guild_id).await? else {
+            return Ok("Verification isn't set up in this server anymore.".to_string());
+        };
+
+        // members who joined while the bot was offline only have the role to go by
+        if !member.roles.contains(&settings.unverified_role) {
+            return Ok("You're already verified.".to_string());
+        }
+
+        if !settings.captcha {
+            complete_verification(ctx, &database, guild_id, member.user.id, &settings).await?;
+            return Ok("You're verified, welcome!".to_string());
+        }
+
+        if send_captcha(ctx, &database, guild_id, member.user.id).await? {
+            Ok("I've sent you a question by DM, reply to it there to verify.".to_string())
+        } else {
+            Ok("I couldn't DM you. Please allow direct messages from server members, then press Verify again.".to_string())
+        }
+    }.await;
+
+    let content = result.unwrap_or_else(|why: CommandError| {
+        warn!("Couldn't verify user {} in guild {guild_id}: {why}", member.user.id);
+        "Something went wrong while verifying you, please ask a moderator.".to_string()
+    });
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+
+    if let Err(why) = interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await {
+        warn!("Couldn't respond to the verify button of user {}: {why}", member.user.id);
+    }
+}
+
+/// Checks a DM answering a captcha, verifying the author if it's right. Returns whether the DM
+/// was an answer, so it isn't relayed to modmail.
+pub async fn handle_captcha_dm(ctx: &Context, msg: &Message) -> bool {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't check DM {} for a captcha answer: {why}", msg.id);
+            return false;
+        }
+    };
+
+    let user_id = msg.author.id.get() as i64;
+
+    // the latest captcha is the one being answered
+    let pending = sqlx::query!(
+        r#"SELECT guild_id, answer AS "answer!: String", attempts FROM pending_verifications
+        WHERE user_id = ? AND answer IS NOT NULL ORDER BY asked_at DESC LIMIT 1"#,
+        user_id
+    ).fetch_optional(&database).await;
+
+    let pending = match pending {
+        Ok(Some(pending)) => pending,
+        Ok(None) => return false,
+        Err(why) => {
+            error!("Failed to fetch the captcha of user {}: {why}", msg.author.id);
+            return false;
+        }
+    };
+
+    let guild_id = GuildId::new(pending.guild_id as u64);
+
+    let result = async {
+        if msg.content.trim() != pending.answer {
+            if pending.attempts + 1 >= MAX_ATTEMPTS {
+                msg.reply(ctx, "That's not right either, so here's a new question.").await?;
+                send_captcha(ctx, &database, guild_id, msg.author.id).await?;
+            } else {
+                sqlx::query!("UPDATE pending_verifications SET attempts = attempts + 1 WHERE guild_id = ? AND user_id = ?", pending.guild_id, user_id)
+                    .execute(&database)
+                    .await?;
+
+                msg.reply(ctx, "That's not right, please try again.").await?;
+            }
+
+            return Ok(());
+        }
+
+        let Some(settings) = verification_settings(&database, guild_id).await? else {
+            sqlx::query!("DELETE FROM pending_verifications WHERE guild_id = ?", pending.guild_id).execute(&database).await?;
+            msg.reply(ctx, "That server doesn't use verification anymore.").await?;
+            return Ok(());
+        };
+
+        complete_verification(ctx, &database, guild_id, msg.author.id, &settings).await?;
+
+        let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+        msg.reply(ctx, format!("That's right, you're now verified in **{guild_name}**!")).await?;
+
+        Ok::<(), CommandError>(())
+    }.await;
+
+    if let Err(why) = result {
+        warn!("Couldn't check the captcha answer of user {} for guild {guild_id}: {why}", msg.author.id);
+        drop(msg.reply(ctx, "Something went wrong while verifying you, please ask a moderator.").await);
+    }
+
+    true
+}
@@ -0,0 +1,215 @@
+use std::time::{Duration, Instant};
+
+use serenity::builder::{CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::{DatabaseConnectionContainer, VoiceSessionsContainer};
+use crate::utilities::parsing::format_duration;
+use crate::utilities::errors::{get_data, BotError};
+
+/// How often time spent in voice so far is written to the database, so little is lost if the
+/// bot stops without shutting down.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Adds time spent in voice to a member's total.
+async fn add_voice_time(database: &SqlitePool, guild_id: u64, user_id: u64, elapsed: Duration) -> Result<(), sqlx::Error> {
+    let (guild_id, user_id, seconds) = (guild_id as i64, user_id as i64, elapsed.as_secs() as i64);
+
+    if seconds == 0 {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO voice_time (guild_id, user_id, seconds) VALUES (?, ?, ?)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET seconds = seconds + excluded.seconds",
+        guild_id,
+        user_id,
+        seconds
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+/// How long a member has been in voice without it being counted yet, if they're in a channel.
+pub async fn uncounted_voice_time(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<Duration> {
+    let sessions = {
+        let data = ctx.data.read().await;
+        data.get::<VoiceSessionsContainer>()?.clone()
+    };
+
+    let sessions = sessions.lock().await;
+
+    sessions.get(&(guild_id.get(), user_id.get())).map(Instant::elapsed)
+}
+
+/// Starts counting the time of members who were already in voice channels when the bot connected.
+pub async fn start_voice_sessions(ctx: &Context, guilds: &[GuildId]) {
+    let sessions = match get_data::<VoiceSessionsContainer>(ctx).await {
+        Ok(sessions) => sessions,
+        Err(why) => {
+            error!("Couldn't start counting voice time: {why}");
+            return;
+        }
+    };
+
+    let now = Instant::now();
+    let mut sessions = sessions.lock().await;
+
+    for guild_id in guilds {
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+            continue;
+        };
+
+        for (user_id, state) in &guild.voice_states {
+            let bot = guild.members.get(user_id).is_some_and(|member| member.user.bot);
+
+            if state.channel_id.is_some() && !bot {
+                sessions.entry((guild_id.get(), user_id.get())).or_insert(now);
+            }
+        }
+    }
+}
+
+/// Counts time spent in voice so far without ending anyone's session, e.g. before shutting down.
+pub async fn flush_voice_time(ctx: &Context) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let sessions = get_data::<VoiceSessionsContainer>(ctx).await?;
+
+    let now = Instant::now();
+
+    let elapsed: Vec<_> = sessions.lock().await
+        .iter_mut()
+        .map(|(&(guild_id, user_id), started)| (guild_id, user_id, now.duration_since(std::mem::replace(started, now))))
+        .collect();
+
+    for (guild_id, user_id, elapsed) in elapsed {
+        add_voice_time(&database, guild_id, user_id, elapsed).await?;
+    }
+
+    Ok(())
+}
+
+/// Periodically counts time spent in voice so far.
+pub async fn flush_voice_time_loop(ctx: Context) {
+    loop {
+        tokio::time::sleep(FLUSH_INTERVAL).await;
+
+        if let Err(why) = flush_voice_time(&ctx).await {
+            error!("Failed to write voice time: {why}");
+        }
+    }
+}
+
+/// The guild's voice log channel, if it has one.
+pub async fn voice_log_channel(database: &SqlitePool, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let channel_id = sqlx::query!("SELECT voice_log_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
+        .fetch_optional(database)
+        .await?
+        .and_then(|row| row.voice_log_channel_id);
+
+    Ok(channel_id.map(|channel_id| ChannelId::new(channel_id as u64)))
+}
+
+/// What changed about a member's voice state while they stayed in the same channel.
+fn state_changes(old: &VoiceState, new: &VoiceState) -> Vec<&'static str> {
+    let toggles = [
+        (old.self_mute, new.self_mute, "muted themselves", "unmuted themselves"),
+        (old.self_deaf, new.self_deaf, "deafened themselves", "undeafened themselves"),
+        (old.mute, new.mute, "was server muted", "was server unmuted"),
+        (old.deaf, new.deaf, "was server deafened", "was server undeafened")
+    ];
+
+    toggles.into_iter()
+        .filter(|(old, new, _, _)| old != new)
+        .map(|(_, new, on, off)| if new { on } else { off })
+        .collect()
+}
+
+/// Counts a member's time in voice as they join, leave or move between channels, and logs it to
+/// the guild's voice log along with mutes and deafens. Bots are left out of both.
+pub async fn handle_voice_state(ctx: &Context, old: Option<&VoiceState>, new: &VoiceState) {
+    let Some(guild_id) = new.guild_id else {
+        return;
+    };
+
+    if new.member.as_ref().is_some_and(|member| member.user.bot) {
+        return;
+    }
+
+    let (database, sessions) = match (get_data::<DatabaseConnectionContainer>(ctx).await, get_data::<VoiceSessionsContainer>(ctx).await) {
+        (Ok(database), Ok(sessions)) => (database, sessions),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Couldn't count voice time: {why}");
+            return;
+        }
+    };
+
+    let key = (guild_id.get(), new.user_id.get());
+
+    // time since the member was last counted, if they just left
+    let left_after = {
+        let mut sessions = sessions.lock().await;
+
+        match new.channel_id {
+            Some(_) => {
+                sessions.entry(key).or_insert_with(Instant::now);
+                None
+            }
+            None => sessions.remove(&key).map(|started| started.elapsed())
+        }
+    };
+
+    if let Some(elapsed) = left_after {
+        if let Err(why) = add_voice_time(&database, guild_id.get(), new.user_id.get(), elapsed).await {
+            error!("Failed to count voice time of user {} in guild {guild_id}: {why}", new.user_id);
+        }
+    }
+
+    let description = match (old.and_then(|old| old.channel_id), new.channel_id) {
+        (None, Some(channel_id)) => format!("joined {}.", channel_id.mention()),
+        (Some(channel_id), None) => match left_after {
+            Some(elapsed) => format!("left {} after {}.", channel_id.mention(), format_duration(chrono::Duration::seconds(elapsed.as_secs() as i64))),
+            None => format!("left {}.", channel_id.mention())
+        },
+        (Some(from), Some(to)) if from != to => format!("moved from {} to {}.", from.mention(), to.mention()),
+        (Some(channel_id), Some(_)) => {
+            let changes = old.map(|old| state_changes(old, new)).unwrap_or_default();
+
+            if changes.is_empty() {
+                return;
+            }
+
+            format!("{} in {}.", changes.join(" and "), channel_id.mention())
+        }
+        (None, None) => return
+    };
+
+    let log_channel_id = match voice_log_channel(&database, guild_id).await {
+        Ok(Some(log_channel_id)) => log_channel_id,
+        Ok(None) => return,
+        Err(why) => {
+            warn!("Couldn't find the voice log channel of guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    let mut embed = branded_embed(ctx, Some(guild_id)).await
+        .title("Voice")
+        .description(format!("{} {description}", new.user_id.mention()))
+        .footer(CreateEmbedFooter::new(format!("User ID: {}", new.user_id)))
+        .timestamp(Timestamp::now());
+
+    if let Some(member) = &new.member {
+        embed = embed.author(CreateEmbedAuthor::new(member.user.tag()).icon_url(member.face()));
+    }
+
+    if let Err(why) = log_channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't log a voice state change in guild {guild_id}: {why}");
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+/// How long a vote keeps the voter perk active.
+pub const VOTE_PERK_HOURS: i64 = 12;
+
+/// Extra coins `daily` pays out while the voter perk is active.
+pub const VOTER_DAILY_BONUS: i64 = 100;
+
+/// Votes further apart than this reset the streak. top.gg allows one vote every 12 hours, so this
+/// leaves a generous window for voting once a day.
+const STREAK_GRACE_HOURS: i64 = 36;
+
+pub struct VoteStreak {
+    pub streak: i64,
+    pub best_streak: i64,
+    pub total_votes: i64,
+    pub last_voted_at: DateTime<Utc>
+}
+
+impl VoteStreak {
+    /// A stored streak, unless its last vote time can't be read.
+    fn from_row(streak: i64, best_streak: i64, total_votes: i64, last_voted_at: &str) -> Option<VoteStreak> {
+        let last_voted_at = DateTime::parse_from_rfc3339(last_voted_at).ok()?.with_timezone(&Utc);
+
+        Some(VoteStreak { streak, best_streak, total_votes, last_voted_at })
+    }
+
+    pub fn perk_expires_at(&self) -> DateTime<Utc> {
+        self.last_voted_at + Duration::hours(VOTE_PERK_HOURS)
+    }
+
+    pub fn has_active_perk(&self) -> bool {
+        self.perk_expires_at() > Utc::now()
+    }
+}
+
+pub async fn get_vote_streak(database: &SqlitePool, user_id: u64) -> Result<Option<VoteStreak>, sqlx::Error> {
+    let db_user_id = user_id as i64;
+
+    let row = sqlx::query!(
+        "SELECT streak, best_streak, total_votes, last_voted_at FROM vote_streaks WHERE user_id = ?",
+        db_user_id
+    ).fetch_optional(database).await?;
+
+    Ok(row.and_then(|row| VoteStreak::from_row(row.streak, row.best_streak, row.total_votes, &row.last_voted_at)))
+}
+
+/// Stores a vote and updates the user's streak, returning the new streak. The vote is stored
+/// before the streak is read, so the transaction holds the write lock and votes arriving at the
+/// same time are counted one after another instead of both building on the same streak.
+pub async fn record_vote(database: &SqlitePool, user_id: u64, is_weekend: bool) -> Result<VoteStreak, sqlx::Error> {
+    let now = Utc::now();
+    let db_user_id = user_id as i64;
+    let voted_at = now.to_rfc3339();
+    let weekend = is_weekend as i64;
+
+    let mut transaction = database.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO votes (user_id, is_weekend, voted_at) VALUES (?, ?, ?)",
+        db_user_id,
+        weekend,
+        voted_at
+    ).execute(&mut *transaction).await?;
+
+    let previous = sqlx::query!(
+        "SELECT streak, best_streak, total_votes, last_voted_at FROM vote_streaks WHERE user_id = ?",
+        db_user_id
+    ).fetch_optional(&mut *transaction).await?
+        .and_then(|row| VoteStreak::from_row(row.streak, row.best_streak, row.total_votes, &row.last_voted_at));
+
+    let streak = match &previous {
+        Some(previous) if now - previous.last_voted_at <= Duration::hours(STREAK_GRACE_HOURS) => previous.streak + 1,
+        _ => 1
+    };
+
+    let vote = VoteStreak {
+        streak,
+        best_streak: previous.as_ref().map_or(streak, |previous| previous.best_streak.max(streak)),
+        total_votes: previous.as_ref().map_or(1, |previous| previous.total_votes + 1),
+        last_voted_at: now
+    };
+
+    sqlx::query!(
+        "INSERT INTO vote_streaks (user_id, streak, best_streak, total_votes, last_voted_at) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (user_id) DO UPDATE SET
+            streak = excluded.streak,
+            best_streak = excluded.best_streak,
+            total_votes = excluded.total_votes,
+            last_voted_at = excluded.last_voted_at",
+        db_user_id,
+        vote.streak,
+        vote.best_streak,
+        vote.total_votes,
+        voted_at
+    ).execute(&mut *transaction).await?;
+
+    transaction.commit().await?;
+
+    Ok(vote)
+}
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serenity::builder::{CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::model::channel::Message;
+use serenity::model::event::GuildMemberUpdateEvent;
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::user::User;
+use serenity::model::Timestamp;
+use serenity::prelude::{Context, Mentionable};
+use sqlx::SqlitePool;
+use tracing::{error, warn};
+
+use crate::utilities::branding::branded_embed;
+use crate::utilities::global_data::{DatabaseConnectionContainer, WatchlistContainer};
+use crate::utilities::errors::{get_data, BotError};
+
+/// Shortest gap between alerts about a watched user posting in the same channel, so a
+/// conversation doesn't flood the watchlist channel.
+const MESSAGE_ALERT_COOLDOWN: Duration = Duration::from_secs(600);
+
+/// Why and until when a user is watched.
+#[derive(Clone)]
+pub struct WatchedUser {
+    pub moderator_id: UserId,
+    pub reason: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>
+}
+
+/// A guild's watched users and where they're alerted about.
+#[derive(Default)]
+pub struct Watchlist {
+    alert_channel: Option<ChannelId>,
+    sensitive_channels: HashSet<u64>,
+    users: HashMap<u64, WatchedUser>,
+    /// When each watched user was last alerted about in each channel.
+    last_alerts: HashMap<(u64, u64), Instant>
+}
+
+impl Watchlist {
+    /// The user's watch and the channel to alert in, unless they aren't watched or there's
+    /// nowhere to alert.
+    fn alert_for(&self, user_id: UserId) -> Option<(ChannelId, WatchedUser)> {
+        let watched = self.users.get(&user_id.get()).filter(|watched| watched.expires_at.is_none_or(|expires_at| expires_at > Utc::now()))?;
+
+        Some((self.alert_channel?, watched.clone()))
+    }
+}
+
+/// Every guild's watchlist, keyed by guild ID, or just one guild's if given.
+async fn fetch_watchlists(database: &SqlitePool, guild_id: Option<GuildId>) -> Result<HashMap<u64, Watchlist>, sqlx::Error> {
+    let guild_id = guild_id.map(|guild_id| guild_id.get() as i64);
+    let mut watchlists: HashMap<u64, Watchlist> = HashMap::new();
+
+    let channels = sqlx::query!(
+        "SELECT guild_id, watchlist_channel_id AS \"channel_id!\" FROM guild_settings WHERE watchlist_channel_id IS NOT NULL AND (? IS NULL OR guild_id = ?)",
+        guild_id,
+        guild_id
+    ).fetch_all(database).await?;
+
+    for row in channels {
+        watchlists.entry(row.guild_id as u64).or_default().alert_channel = Some(ChannelId::new(row.channel_id as u64));
+    }
+
+    let sensitive = sqlx::query!("SELECT guild_id, channel_id AS \"channel_id!\" FROM watchlist_channels WHERE ? IS NULL OR guild_id = ?", guild_id, guild_id)
+        .fetch_all(database)
+        .await?;
+
+    for row in sensitive {
+        watchlists.entry(row.guild_id as u64).or_default().sensitive_channels.insert(row.channel_id as u64);
+    }
+
+    let users = sqlx::query!(
+        "SELECT guild_id, user_id, moderator_id, reason, expires_at FROM watchlist WHERE ? IS NULL OR guild_id = ?",
+        guild_id,
+        guild_id
+    ).fetch_all(database).await?;
+
+    for row in users {
+        let watched = WatchedUser {
+            moderator_id: UserId::new(row.moderator_id as u64),
+            reason: row.reason,
+            expires_at: row.expires_at.and_then(|expires_at| DateTime::parse_from_rfc3339(&expires_at).ok()).map(|expires_at| expires_at.to_utc())
+        };
+
+        watchlists.entry(row.guild_id as u64).or_default().users.insert(row.user_id as u64, watched);
+    }
+
+    Ok(watchlists)
+}
+
+pub async fn load_watchlists(database: &SqlitePool) -> Result<HashMap<u64, Watchlist>, sqlx::Error> {
+    fetch_watchlists(database, None).await
+}
+
+/// Reloads a guild's watchlist from the database after it's been changed.
+pub async fn reload_watchlist(ctx: &Context, guild_id: GuildId) -> Result<(), BotError> {
+    let database = get_data::<DatabaseConnectionContainer>(ctx).await?;
+    let watchlists = get_data::<WatchlistContainer>(ctx).await?;
+
+    let watchlist = fetch_watchlists(&database, Some(guild_id)).await?.remove(&guild_id.get());
+    let mut watchlists = watchlists.lock().await;
+
+    match watchlist {
+        Some(mut watchlist) => {
+            // recent alerts still count, so changing the watchlist doesn't repeat them
+            if let Some(previous) = watchlists.remove(&guild_id.get()) {
+                watchlist.last_alerts = previous.last_alerts;
+            }
+
+            watchlists.insert(guild_id.get(), watchlist);
+        }
+        None => {
+            watchlists.remove(&guild_id.get());
+        }
+    }
+
+    Ok(())
+}
+
+/// Stops watching a user once their watch has run out.
+pub async fn end_watch(ctx: &Context, database: &SqlitePool, guild_id: GuildId, user_id: UserId) {
+    let (db_guild_id, db_user_id) = (guild_id.get() as i64, user_id.get() as i64);
+
+    if let Err(why) = sqlx::query!("DELETE FROM watchlist WHERE guild_id = ? AND user_id = ?", db_guild_id, db_user_id).execute(database).await {
+        error!("Failed to stop watching user {user_id} in guild {guild_id}: {why}");
+        return;
+    }
+
+    if let Err(why) = reload_watchlist(ctx, guild_id).await {
+        error!("Failed to reload the watchlist of guild {guild_id}: {why}");
+    }
+}
+
+/// Cancels the scheduled end of a user's watch, e.g. once they're unwatched by hand.
+pub async fn cancel_unwatches(database: &SqlitePool, guild_id: GuildId, user_id: UserId) -> Result<(), sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+    let user_id = user_id.get() as i64;
+
+    sqlx::query!(
+        "DELETE FROM scheduled_jobs WHERE guild_id = ? AND kind = 'unwatch' AND json_extract(payload, '$.user_id') = ?",
+        guild_id,
+        user_id
+    ).execute(database).await?;
+
+    Ok(())
+}
+
+async fn watched_user(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<(ChannelId, WatchedUser)> {
+    let watchlists = match get_data::<WatchlistContainer>(ctx).await {
+        Ok(watchlists) => watchlists,
+        Err(why) => {
+            error!("Couldn't read the watchlist of guild {guild_id}: {why}");
+            return None;
+        }
+    };
+
+    let watchlists = watchlists.lock().await;
+
+    watchlists.get(&guild_id.get())?.alert_for(user_id)
+}
+
+async fn send_alert(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, user: &User, watched: &WatchedUser, title: &str, description: String) {
+    let embed = branded_embed(ctx, Some(guild_id)).await
+        .author(CreateEmbedAuthor::new(format!("{} ({})", user.tag(), user.id)).icon_url(user.face()))
+        .title(title)
+        .description(description)
+        .field("Watched for", watched.reason.as_deref().unwrap_or("No reason given."), true)
+        .field("Watched by", watched.moderator_id.mention().to_string(), true)
+        .footer(CreateEmbedFooter::new("Watchlist"))
+        .timestamp(Timestamp::now());
+
+    if let Err(why) = channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        warn!("Couldn't send a watchlist alert to channel {channel_id}: {why}");
+    }
+}
+
+/// Alerts staff when a watched user posts in one of the guild's sensitive channels.
+pub async fn handle_watchlist_message(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+
+    let watchlists = match get_data::<WatchlistContainer>(ctx).await {
+        Ok(watchlists) => watchlists,
+        Err(why) => {
+            error!("Couldn't check message {} against the watchlist: {why}", msg.id);
+            return;
+        }
+    };
+
+    let (channel_id, watched) = {
+        let mut watchlists = watchlists.lock().await;
+
+        let Some(watchlist) = watchlists.get_mut(&guild_id.get()) else {
+            return;
+        };
+
+        if !watchlist.sensitive_channels.contains(&msg.channel_id.get()) {
+            return;
+        }
+
+        let Some(alert) = watchlist.alert_for(msg.author.id) else {
+            return;
+        };
+
+        let key = (msg.author.id.get(), msg.channel_id.get());
+
+        if watchlist.last_alerts.get(&key).is_some_and(|last| last.elapsed() < MESSAGE_ALERT_COOLDOWN) {
+            return;
+        }
+
+        watchlist.last_alerts.insert(key, Instant::now());
+        alert
+    };
+
+    let mut content = msg.content.replace('\n', " ");
+
+    if content.chars().count() > 500 {
+        content = content.chars().take(497).collect::<String>() + "...";
+    }
+
+    let description = match content.trim() {
+        "" => format!("Posted in {}. [Jump to message]({})", msg.channel_id.mention(), msg.link()),
+        content => format!("Posted in {}: {content}\n[Jump to message]({})", msg.channel_id.mention(), msg.link())
+    };
+
+    send_alert(ctx, guild_id, channel_id, &msg.author, &watched, "Watched user posted", description).await;
+}
+
+/// Alerts staff when a watched user joins.
+pub async fn handle_watchlist_join(ctx: &Context, member: &Member) {
+    let Some((channel_id, watched)) = watched_user(ctx, member.guild_id, member.user.id).await else {
+        return;
+    };
+
+    let description = format!("{} joined the server.", member.user.id.mention());
+
+    send_alert(ctx, member.guild_id, channel_id, &member.user, &watched, "Watched user joined", description).await;
+}
+
+/// Alerts staff when a watched member changes their nickname. Changes to members that weren't
+/// cached can't be told apart from other updates, so they're skipped.
+pub async fn handle_watchlist_nickname(ctx: &Context, old: Option<&Member>, event: &GuildMemberUpdateEvent) {
+    let Some(old) = old.filter(|old| old.nick != event.nick) else {
+        return;
+    };
+
+    let Some((channel_id, watched)) = watched_user(ctx, event.guild_id, event.user.id).await else {
+        return;
+    };
+
+    let name = |nick: Option<&str>| nick.map_or_else(|| "*none*".to_string(), |nick| format!("**{nick}**"));
+    let description = format!("{} changed their nickname from {} to {}.", event.user.id.mention(), name(old.nick.as_deref()), name(event.nick.as_deref()));
+
+    send_alert(ctx, event.guild_id, channel_id, &event.user, &watched, "Watched user changed nickname", description).await;
+}
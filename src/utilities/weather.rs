@@ -0,0 +1,220 @@
+use chrono::NaiveDate;
+use reqwest::Client as Reqwest;
+use serde::Deserialize;
+use serenity::builder::{CreateEmbed, CreateEmbedFooter};
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+
+use crate::utilities::branding::Branding;
+
+/// Days shown in the forecast, including today.
+const FORECAST_DAYS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Metric,
+    Imperial
+}
+
+impl Units {
+    pub fn parse(arg: &str) -> Option<Units> {
+        match arg.to_lowercase().as_str() {
+            "metric" | "celsius" | "c" => Some(Units::Metric),
+            "imperial" | "fahrenheit" | "f" => Some(Units::Imperial),
+            _ => None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Units::Metric => "metric",
+            Units::Imperial => "imperial"
+        }
+    }
+
+    fn temperature(self) -> &'static str {
+        match self {
+            Units::Metric => "°C",
+            Units::Imperial => "°F"
+        }
+    }
+
+    fn speed(self) -> &'static str {
+        match self {
+            Units::Metric => "km/h",
+            Units::Imperial => "mph"
+        }
+    }
+
+    fn precipitation(self) -> &'static str {
+        match self {
+            Units::Metric => "mm",
+            Units::Imperial => "in"
+        }
+    }
+
+    /// Query parameters asking Open-Meteo for these units. Metric is its default.
+    fn query(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Units::Metric => &[],
+            Units::Imperial => &[("temperature_unit", "fahrenheit"), ("wind_speed_unit", "mph"), ("precipitation_unit", "inch")]
+        }
+    }
+}
+
+/// A place found by geocoding a location.
+#[derive(Deserialize)]
+pub struct Place {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    country: Option<String>,
+    admin1: Option<String>
+}
+
+impl Place {
+    /// The place's name with its region and country, e.g. `Portland, Oregon, United States`.
+    fn label(&self) -> String {
+        [Some(&self.name), self.admin1.as_ref(), self.country.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Deserialize)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<Place>
+}
+
+#[derive(Deserialize)]
+pub struct Forecast {
+    current: Current,
+    daily: Daily
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    relative_humidity_2m: f64,
+    precipitation: f64,
+    weather_code: u32,
+    wind_speed_10m: f64
+}
+
+#[derive(Deserialize)]
+struct Daily {
+    time: Vec<String>,
+    weather_code: Vec<Option<u32>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_probability_max: Vec<Option<f64>>
+}
+
+/// The units a user sees the weather in, metric unless they chose otherwise.
+pub async fn user_units(database: &SqlitePool, user_id: UserId) -> Result<Units, sqlx::Error> {
+    let user_id = user_id.get() as i64;
+
+    let units = sqlx::query!("SELECT units FROM weather_preferences WHERE user_id = ?", user_id)
+        .fetch_optional(database)
+        .await?;
+
+    Ok(units.and_then(|row| Units::parse(&row.units)).unwrap_or(Units::Metric))
+}
+
+/// Finds the place best matching a location with Open-Meteo's geocoding.
+pub async fn geocode(client: &Reqwest, location: &str) -> Result<Option<Place>, reqwest::Error> {
+    let response = client.get("https://geocoding-api.open-meteo.com/v1/search")
+        .query(&[("name", location), ("count", "1"), ("language", "en"), ("format", "json")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<GeocodingResponse>()
+        .await?;
+
+    Ok(response.results.into_iter().next())
+}
+
+/// Fetches the current conditions and the next few days' forecast for a place from Open-Meteo.
+pub async fn forecast(client: &Reqwest, place: &Place, units: Units) -> Result<Forecast, reqwest::Error> {
+    let (latitude, longitude, days) = (place.latitude.to_string(), place.longitude.to_string(), FORECAST_DAYS.to_string());
+
+    client.get("https://api.open-meteo.com/v1/forecast")
+        .query(&[
+            ("latitude", latitude.as_str()),
+            ("longitude", longitude.as_str()),
+            ("current", "temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m"),
+            ("daily", "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max"),
+            ("timezone", "auto"),
+            ("forecast_days", days.as_str())
+        ])
+        .query(units.query())
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Forecast>()
+        .await
+}
+
+/// An emoji and description for a WMO weather code, as Open-Meteo reports conditions.
+fn describe(code: u32) -> (&'static str, &'static str) {
+    match code {
+        0 => ("☀️", "Clear sky"),
+        1 => ("🌤️", "Mainly clear"),
+        2 => ("⛅", "Partly cloudy"),
+        3 => ("☁️", "Overcast"),
+        45 | 48 => ("🌫️", "Fog"),
+        51 | 53 | 55 => ("🌦️", "Drizzle"),
+        56 | 57 => ("🌧️", "Freezing drizzle"),
+        61 | 63 | 65 => ("🌧️", "Rain"),
+        66 | 67 => ("🌧️", "Freezing rain"),
+        71 | 73 | 75 | 77 => ("🌨️", "Snow"),
+        80..=82 => ("🌦️", "Rain showers"),
+        85 | 86 => ("🌨️", "Snow showers"),
+        95 => ("⛈️", "Thunderstorm"),
+        96 | 99 => ("⛈️", "Thunderstorm with hail"),
+        _ => ("🌡️", "Unknown")
+    }
+}
+
+pub fn weather_embed(branding: &Branding, place: &Place, forecast: &Forecast, units: Units) -> CreateEmbed {
+    let current = &forecast.current;
+    let (emoji, conditions) = describe(current.weather_code);
+    let (temperature, speed, precipitation) = (units.temperature(), units.speed(), units.precipitation());
+
+    let daily = &forecast.daily;
+
+    let days = (0..daily.time.len())
+        .map(|day| {
+            let name = match (day, NaiveDate::parse_from_str(&daily.time[day], "%Y-%m-%d")) {
+                (0, _) => "Today".to_string(),
+                (_, Ok(date)) => date.format("%A").to_string(),
+                (_, Err(_)) => daily.time[day].clone()
+            };
+
+            let (emoji, conditions) = describe(daily.weather_code.get(day).copied().flatten().unwrap_or(u32::MAX));
+            let high = daily.temperature_2m_max.get(day).copied().flatten().map_or("?".to_string(), |high| format!("{high:.0}{temperature}"));
+            let low = daily.temperature_2m_min.get(day).copied().flatten().map_or("?".to_string(), |low| format!("{low:.0}{temperature}"));
+
+            let rain = match daily.precipitation_probability_max.get(day).copied().flatten() {
+                Some(chance) => format!(", {chance:.0}% chance of precipitation"),
+                None => String::new()
+            };
+
+            format!("**{name}**: {emoji} {conditions}, {high} / {low}{rain}")
+        })
+        .collect::<Vec<_>>();
+
+    branding.embed()
+        .title(format!("{emoji} Weather in {}", place.label()))
+        .description(format!("**{conditions}**, {:.1}{temperature} (feels like {:.1}{temperature})", current.temperature_2m, current.apparent_temperature))
+        .field("Humidity", format!("{:.0}%", current.relative_humidity_2m), true)
+        .field("Wind", format!("{:.1} {speed}", current.wind_speed_10m), true)
+        .field("Precipitation", format!("{:.1} {precipitation}", current.precipitation), true)
+        .field("Forecast", days.join("\n"), false)
+        .footer(CreateEmbedFooter::new("Weather data by Open-Meteo.com"))
+}
@@ -0,0 +1,128 @@
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::model::user::User;
+use serenity::prelude::Context;
+use sqlx::SqlitePool;
+use tracing::{warn, error};
+
+use crate::utilities::global_data::DatabaseConnectionContainer;
+use crate::utilities::templates::{TemplateContext, render_template};
+use crate::utilities::errors::get_data;
+
+pub const DEFAULT_WELCOME: &str = "Welcome to {server}, {user.mention}! You're member #{membercount}.";
+pub const DEFAULT_GOODBYE: &str = "**{user.name}** has left {server}. We're down to {membercount} members.";
+
+/// Where and what to greet members with, for either joining or leaving.
+pub struct Greeting {
+    pub channel_id: Option<ChannelId>,
+    pub message: String
+}
+
+pub struct GreetingSettings {
+    pub welcome: Greeting,
+    pub goodbye: Greeting
+}
+
+/// A guild's welcome and goodbye settings. Channels are only set while the greeting is enabled.
+pub async fn greeting_settings(database: &SqlitePool, guild_id: GuildId) -> Result<GreetingSettings, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    let row = sqlx::query!(
+        "SELECT welcome_channel_id, welcome_enabled, welcome_message, goodbye_channel_id, goodbye_enabled, goodbye_message
+        FROM guild_settings WHERE guild_id = ?",
+        guild_id
+    ).fetch_optional(database).await?;
+
+    let Some(row) = row else {
+        return Ok(GreetingSettings {
+            welcome: Greeting { channel_id: None, message: DEFAULT_WELCOME.to_string() },
+            goodbye: Greeting { channel_id: None, message: DEFAULT_GOODBYE.to_string() }
+        });
+    };
+
+    Ok(GreetingSettings {
+        welcome: Greeting {
+            channel_id: row.welcome_channel_id.filter(|_| row.welcome_enabled != 0).map(|channel_id| ChannelId::new(channel_id as u64)),
+            message: row.welcome_message.unwrap_or_else(|| DEFAULT_WELCOME.to_string())
+        },
+        goodbye: Greeting {
+            channel_id: row.goodbye_channel_id.filter(|_| row.goodbye_enabled != 0).map(|channel_id| ChannelId::new(channel_id as u64)),
+            message: row.goodbye_message.unwrap_or_else(|| DEFAULT_GOODBYE.to_string())
+        }
+    })
+}
+
+/// A template context for greeting a user in a guild.
+pub fn greeting_context(ctx: &Context, guild_id: GuildId, user: &User, member: Option<&Member>) -> TemplateContext {
+    let context = match member {
+        Some(member) => TemplateContext::new().with_member(member),
+        None => TemplateContext::new().with_user(user)
+    };
+
+    match guild_id.to_guild_cached(&ctx.cache) {
+        Some(guild) => context.with_guild(&guild),
+        None => context
+    }
+}
+
+async fn send_greeting(ctx: &Context, guild_id: GuildId, greeting: Greeting, context: &TemplateContext) {
+    let Some(channel_id) = greeting.channel_id else {
+        return;
+    };
+
+    let content = match render_template(&greeting.message, context) {
+        Ok(content) => content,
+        Err(why) => {
+            warn!("Couldn't render a greeting in guild {guild_id}: {why}");
+            return;
+        }
+    };
+
+    // greetings may ping the member they're about, but never everyone
+    let builder = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().all_users(true));
+
+    if let Err(why) = channel_id.send_message(ctx, builder).await {
+        warn!("Couldn't send a greeting in guild {guild_id}: {why}");
+    }
+}
+
+/// Welcomes a new member in their guild's welcome channel, if it has one.
+pub async fn send_welcome(ctx: &Context, member: &Member) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't welcome member {}: {why}", member.user.id);
+            return;
+        }
+    };
+
+    match greeting_settings(&database, member.guild_id).await {
+        Ok(settings) => {
+            let context = greeting_context(ctx, member.guild_id, &member.user, Some(member));
+            send_greeting(ctx, member.guild_id, settings.welcome, &context).await;
+        }
+        Err(why) => warn!("Couldn't fetch the welcome settings of guild {}: {why}", member.guild_id)
+    }
+}
+
+/// Says goodbye to a member who left in their guild's goodbye channel, if it has one.
+pub async fn send_goodbye(ctx: &Context, guild_id: GuildId, user: &User) {
+    let database = match get_data::<DatabaseConnectionContainer>(ctx).await {
+        Ok(database) => database,
+        Err(why) => {
+            error!("Couldn't say goodbye to user {}: {why}", user.id);
+            return;
+        }
+    };
+
+    match greeting_settings(&database, guild_id).await {
+        Ok(settings) => {
+            let context = greeting_context(ctx, guild_id, user, None);
+            send_greeting(ctx, guild_id, settings.goodbye, &context).await;
+        }
+        Err(why) => warn!("Couldn't fetch the goodbye settings of guild {guild_id}: {why}")
+    }
+}
@@ -0,0 +1,184 @@
+use rand::seq::SliceRandom;
+
+/// Wrong guesses a hangman game allows before it's lost.
+pub const MAX_WRONG_GUESSES: usize = 6;
+
+/// Guesses a wordle game allows.
+pub const WORDLE_GUESSES: usize = 6;
+pub const WORDLE_LENGTH: usize = 5;
+
+const HANGMAN_WORDS: &[&str] = &[
+    "airplane", "alphabet", "astronaut", "avalanche", "backpack", "balloon", "bamboo", "banana", "blizzard", "bracelet",
+    "butterfly", "cactus", "campfire", "castle", "chimney", "compass", "crocodile", "crystal", "dinosaur", "dolphin",
+    "dragon", "elephant", "envelope", "firework", "flamingo", "galaxy", "giraffe", "glacier", "hamburger", "harbor",
+    "helicopter", "hurricane", "igloo", "island", "jellyfish", "journey", "kangaroo", "keyboard", "lantern", "lighthouse",
+    "magnet", "marathon", "meadow", "microphone", "mountain", "mystery", "notebook", "octopus", "orchestra", "parachute",
+    "penguin", "pineapple", "pyramid", "quicksand", "rainbow", "rocket", "sandwich", "satellite", "scarecrow", "skeleton",
+    "snowflake", "spaceship", "squirrel", "submarine", "sunflower", "telescope", "thunder", "tornado", "treasure", "umbrella",
+    "unicorn", "vampire", "volcano", "waterfall", "whistle", "wizard", "xylophone", "yacht", "zeppelin", "zombie"
+];
+
+const WORDLE_WORDS: &[&str] = &[
+    "about", "actor", "adult", "agent", "alarm", "album", "alien", "angle", "apple", "arena",
+    "badge", "beach", "bench", "birth", "black", "blade", "blend", "board", "brain", "bread",
+    "brick", "brush", "cabin", "candy", "chain", "chair", "chalk", "charm", "chess", "chest",
+    "cloud", "coast", "crane", "crown", "dance", "diary", "dream", "dress", "drink", "eagle",
+    "earth", "fairy", "feast", "field", "flame", "float", "flute", "frost", "fruit", "ghost",
+    "giant", "glass", "globe", "grape", "grass", "heart", "honey", "horse", "house", "jelly",
+    "juice", "knife", "lemon", "light", "magic", "maple", "march", "match", "money", "mouse",
+    "music", "night", "ocean", "olive", "paint", "panda", "party", "pearl", "piano", "pilot",
+    "pizza", "plant", "queen", "quiet", "radio", "river", "robot", "salad", "scale", "shark",
+    "sheep", "smile", "snake", "space", "spoon", "storm", "sugar", "table", "tiger", "toast",
+    "tower", "train", "truck", "vivid", "voice", "water", "whale", "wheel", "world", "zebra"
+];
+
+/// The gallows after each wrong guess.
+const GALLOWS: [&str; MAX_WRONG_GUESSES + 1] = [
+    "  +---+\n  |   |\n      |\n      |\n      |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n      |\n      |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n  |   |\n      |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n /|   |\n      |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n /|\\  |\n      |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n /|\\  |\n /    |\n      |\n=========",
+    "  +---+\n  |   |\n  O   |\n /|\\  |\n / \\  |\n      |\n========="
+];
+
+pub enum LetterGuess {
+    Correct,
+    Wrong,
+    Repeated
+}
+
+/// A game of hangman: the hidden word and what's been guessed so far.
+pub struct Hangman {
+    pub word: &'static str,
+    guessed: Vec<char>,
+    wrong: usize
+}
+
+impl Hangman {
+    pub fn random() -> Hangman {
+        Hangman { word: HANGMAN_WORDS.choose(&mut rand::thread_rng()).unwrap(), guessed: Vec::new(), wrong: 0 }
+    }
+
+    pub fn guess_letter(&mut self, letter: char) -> LetterGuess {
+        if self.guessed.contains(&letter) {
+            return LetterGuess::Repeated;
+        }
+
+        self.guessed.push(letter);
+
+        if self.word.contains(letter) {
+            LetterGuess::Correct
+        } else {
+            self.wrong += 1;
+            LetterGuess::Wrong
+        }
+    }
+
+    /// Guesses the whole word, revealing it if right and costing a wrong guess if not.
+    pub fn guess_word(&mut self, word: &str) -> bool {
+        if word == self.word {
+            self.guessed.extend(self.word.chars());
+            return true;
+        }
+
+        self.wrong += 1;
+        false
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.word.chars().all(|letter| self.guessed.contains(&letter))
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.wrong >= MAX_WRONG_GUESSES
+    }
+
+    /// The word with unguessed letters blanked out, e.g. `b _ n _ n _`.
+    pub fn masked(&self) -> String {
+        self.word.chars()
+            .map(|letter| if self.guessed.contains(&letter) { letter } else { '_' })
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The gallows, the masked word and the letters tried so far, for showing in an embed.
+    pub fn board(&self) -> String {
+        let mut missed = self.guessed.iter().filter(|letter| !self.word.contains(**letter)).collect::<Vec<_>>();
+        missed.sort_unstable();
+
+        let missed = if missed.is_empty() {
+            "none yet".to_string()
+        } else {
+            missed.into_iter().map(char::to_string).collect::<Vec<_>>().join(", ")
+        };
+
+        format!(
+            "```\n{}\n```\n`{}`\n\nMissed: {missed} ({} of {MAX_WRONG_GUESSES} wrong guesses)",
+            GALLOWS[self.wrong.min(MAX_WRONG_GUESSES)],
+            self.masked(),
+            self.wrong
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LetterScore {
+    /// The letter is in the word at this position.
+    Right,
+    /// The letter is in the word, somewhere else.
+    Misplaced,
+    Absent
+}
+
+pub fn random_wordle_word() -> &'static str {
+    WORDLE_WORDS.choose(&mut rand::thread_rng()).unwrap()
+}
+
+/// Scores a wordle guess against the answer. Repeated letters are only marked misplaced as many
+/// times as the answer has them left over after the right ones.
+pub fn score_wordle(guess: &str, answer: &str) -> Vec<LetterScore> {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+
+    let mut scores = vec![LetterScore::Absent; guess.len()];
+    let mut unmatched = Vec::new();
+
+    for (index, letter) in answer.iter().enumerate() {
+        if guess.get(index) == Some(letter) {
+            scores[index] = LetterScore::Right;
+        } else {
+            unmatched.push(*letter);
+        }
+    }
+
+    for (index, letter) in guess.iter().enumerate() {
+        if scores[index] == LetterScore::Right {
+            continue;
+        }
+
+        if let Some(position) = unmatched.iter().position(|unmatched| unmatched == letter) {
+            unmatched.swap_remove(position);
+            scores[index] = LetterScore::Misplaced;
+        }
+    }
+
+    scores
+}
+
+/// A scored guess as a row of colored squares over the guess's letters.
+pub fn wordle_row(guess: &str, scores: &[LetterScore]) -> String {
+    let squares = scores.iter()
+        .map(|score| match score {
+            LetterScore::Right => "🟩",
+            LetterScore::Misplaced => "🟨",
+            LetterScore::Absent => "⬛"
+        })
+        .collect::<String>();
+
+    let letters = guess.to_uppercase().chars().map(String::from).collect::<Vec<_>>().join(" ");
+
+    format!("{squares} `{letters}`")
+}
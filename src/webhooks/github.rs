@@ -0,0 +1,241 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use ring::hmac;
+use serde::Deserialize;
+use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage};
+use serenity::model::id::ChannelId;
+use tracing::{error, warn};
+
+use crate::utilities::branding::Branding;
+use crate::webhooks::WebhookState;
+
+/// Most commits listed in a push embed.
+const MAX_COMMITS: usize = 5;
+
+const MAX_BODY_LENGTH: usize = 300;
+
+/// The parts of a webhook payload that pushes, releases, issues and pull requests are shown with,
+/// see <https://docs.github.com/en/webhooks/webhook-events-and-payloads>.
+#[derive(Deserialize)]
+pub struct EventPayload {
+    action: Option<String>,
+    repository: Option<Repository>,
+    sender: Option<Sender>,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    compare: Option<String>,
+    #[serde(default)]
+    commits: Vec<Commit>,
+    #[serde(default)]
+    deleted: bool,
+    release: Option<Release>,
+    issue: Option<Issue>,
+    pull_request: Option<PullRequest>
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    full_name: String,
+    html_url: String
+}
+
+#[derive(Deserialize)]
+struct Sender {
+    login: String,
+    avatar_url: String,
+    html_url: String
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    id: String,
+    message: String,
+    url: String,
+    author: CommitAuthor
+}
+
+#[derive(Deserialize)]
+struct CommitAuthor {
+    name: String
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    name: Option<String>,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    html_url: String,
+    body: Option<String>
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    merged: bool
+}
+
+pub async fn receive_event(State(state): State<WebhookState>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let event = headers.get("X-GitHub-Event").and_then(|event| event.to_str().ok()).unwrap_or_default();
+
+    let Some(signature) = headers.get("X-Hub-Signature-256")
+        .and_then(|signature| signature.to_str().ok())
+        .and_then(|signature| signature.strip_prefix("sha256="))
+        .and_then(|signature| hex::decode(signature).ok()) else {
+        warn!("Rejected a GitHub webhook without a signature");
+        return StatusCode::UNAUTHORIZED;
+    };
+
+    let Ok(payload) = serde_json::from_slice::<EventPayload>(&body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let Some(repository) = &payload.repository else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let name = repository.full_name.to_lowercase();
+
+    let subscriptions = match sqlx::query!(
+        "SELECT github_subscriptions.channel_id, github_subscriptions.secret, guild_settings.embed_color, guild_settings.embed_footer, guild_settings.embed_thumbnail
+        FROM github_subscriptions LEFT JOIN guild_settings ON guild_settings.guild_id = github_subscriptions.guild_id
+        WHERE github_subscriptions.repository = ?",
+        name
+    ).fetch_all(&state.database)
+        .await {
+        Ok(subscriptions) => subscriptions,
+        Err(err) => {
+            error!("Failed to fetch the subscriptions to {name}: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    };
+
+    // every subscription has its own webhook on the repository, signed with its own secret, and
+    // is posted in the branding of the guild that subscribed
+    let channels = subscriptions.into_iter()
+        .filter(|subscription| hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, subscription.secret.as_bytes()), &body, &signature).is_ok())
+        .map(|subscription| {
+            let branding = Branding {
+                color: subscription.embed_color.map(|color| color as u32),
+                footer: subscription.embed_footer,
+                thumbnail: subscription.embed_thumbnail
+            };
+
+            (ChannelId::new(subscription.channel_id as u64), branding)
+        })
+        .collect::<Vec<_>>();
+
+    if channels.is_empty() {
+        warn!("Rejected a GitHub webhook for {name} that no subscription's secret signed");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    for (channel_id, branding) in channels {
+        let Some(embed) = event_embed(event, &payload, &branding) else {
+            // pings and events that aren't shown are still acknowledged
+            return StatusCode::OK;
+        };
+
+        if let Err(err) = channel_id.send_message(&*state.http, CreateMessage::new().embed(embed)).await {
+            warn!("Couldn't post a GitHub event for {name} in channel {channel_id}: {err}");
+        }
+    }
+
+    StatusCode::OK
+}
+
+/// Shows a push, published release, or opened, closed or reopened issue or pull request in a
+/// guild's branding.
+fn event_embed(event: &str, payload: &EventPayload, branding: &Branding) -> Option<CreateEmbed> {
+    let repository = payload.repository.as_ref()?;
+    let action = payload.action.as_deref().unwrap_or_default();
+
+    let (title, url, description) = match event {
+        "push" => {
+            let branch = payload.git_ref.as_deref()?.strip_prefix("refs/heads/")?;
+
+            if payload.deleted || payload.commits.is_empty() {
+                return None;
+            }
+
+            let mut lines = payload.commits.iter()
+                .take(MAX_COMMITS)
+                .map(|commit| {
+                    let summary = commit.message.lines().next().unwrap_or_default();
+                    format!("[`{}`]({}) {} - {}", &commit.id[..7.min(commit.id.len())], commit.url, truncate(summary, 80), commit.author.name)
+                })
+                .collect::<Vec<_>>();
+
+            if payload.commits.len() > MAX_COMMITS {
+                lines.push(format!("and {} more", payload.commits.len() - MAX_COMMITS));
+            }
+
+            let plural = if payload.commits.len() == 1 { "" } else { "s" };
+            let title = format!("[{}:{branch}] {} new commit{plural}", repository.full_name, payload.commits.len());
+
+            (title, payload.compare.clone().unwrap_or_else(|| repository.html_url.clone()), lines.join("\n"))
+        }
+        "release" if action == "published" => {
+            let release = payload.release.as_ref()?;
+            let kind = if release.prerelease { "Pre-release" } else { "Release" };
+            let name = release.name.as_deref().filter(|name| !name.is_empty()).unwrap_or(&release.tag_name);
+
+            (format!("[{}] {kind} published: {name}", repository.full_name), release.html_url.clone(), release.body.clone().unwrap_or_default())
+        }
+        "issues" if matches!(action, "opened" | "closed" | "reopened") => {
+            let issue = payload.issue.as_ref()?;
+            let body = issue.body.clone().filter(|_| action == "opened").unwrap_or_default();
+
+            (format!("[{}] Issue {action}: #{} {}", repository.full_name, issue.number, issue.title), issue.html_url.clone(), body)
+        }
+        "pull_request" if matches!(action, "opened" | "closed" | "reopened") => {
+            let pull_request = payload.pull_request.as_ref()?;
+            let action = if action == "closed" && pull_request.merged { "merged" } else { action };
+            let body = pull_request.body.clone().filter(|_| action == "opened").unwrap_or_default();
+
+            (format!("[{}] Pull request {action}: #{} {}", repository.full_name, pull_request.number, pull_request.title), pull_request.html_url.clone(), body)
+        }
+        _ => return None
+    };
+
+    let mut embed = branding.embed()
+        .title(truncate(&title, 250))
+        .url(url)
+        .footer(CreateEmbedFooter::new("GitHub"));
+
+    // commit lists are already short, other bodies are cut down
+    if event == "push" {
+        embed = embed.description(description);
+    } else if !description.trim().is_empty() {
+        embed = embed.description(truncate(description.trim(), MAX_BODY_LENGTH));
+    }
+
+    if let Some(sender) = &payload.sender {
+        embed = embed.author(CreateEmbedAuthor::new(&sender.login).icon_url(&sender.avatar_url).url(&sender.html_url));
+    }
+
+    Some(embed)
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    let mut truncated = text.chars().take(length).collect::<String>();
+
+    if text.chars().count() > length {
+        truncated.push_str("...");
+    }
+
+    truncated
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::routing::post;
+use serenity::http::Http;
+use sqlx::SqlitePool;
+use tracing::{error, info};
+
+pub mod github;
+pub mod topgg;
+
+/// Shared state handed to every webhook route.
+#[derive(Clone)]
+pub struct WebhookState {
+    pub database: SqlitePool,
+    pub http: Arc<Http>,
+    pub topgg_auth: Option<String>
+}
+
+/// Runs the HTTP server that receives webhooks from external services. Only started when
+/// `WEBHOOK_ADDRESS` (e.g. `0.0.0.0:8080`) is set.
+pub async fn serve(database: SqlitePool, http: Arc<Http>) {
+    let Ok(address) = env::var("WEBHOOK_ADDRESS") else {
+        return;
+    };
+
+    let state = WebhookState {
+        database,
+        http,
+        topgg_auth: env::var("TOPGG_WEBHOOK_AUTH").ok()
+    };
+
+    let app = Router::new()
+        .route("/topgg", post(topgg::receive_vote))
+        .route("/github", post(github::receive_event))
+        .with_state(state);
+
+    let listener = match tokio::net::TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Couldn't bind the webhook server to {address}: {err}");
+            return;
+        }
+    };
+
+    info!("Listening for webhooks on {address}");
+
+    if let Err(err) = axum::serve(listener, app).await {
+        error!("Webhook server error: {err}");
+    }
+}
@@ -0,0 +1,53 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::Deserialize;
+use subtle::ConstantTimeEq;
+use tracing::{error, info, warn};
+
+use crate::utilities::votes::record_vote;
+use crate::webhooks::WebhookState;
+
+/// Vote payload sent by top.gg, see <https://docs.top.gg/resources/webhooks/>.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VotePayload {
+    user: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    is_weekend: bool
+}
+
+pub async fn receive_vote(State(state): State<WebhookState>, headers: HeaderMap, Json(payload): Json<VotePayload>) -> StatusCode {
+    // compared in constant time, so timing doesn't give the secret away
+    let authorized = match (&state.topgg_auth, headers.get("Authorization")) {
+        (Some(expected), Some(given)) => bool::from(given.as_bytes().ct_eq(expected.as_bytes())),
+        _ => false
+    };
+
+    if !authorized {
+        warn!("Rejected a top.gg webhook with a missing or invalid authorization header");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let Ok(user_id) = payload.user.parse::<u64>() else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    if payload.kind == "test" {
+        info!("Received a test vote from top.gg for user {user_id}");
+        return StatusCode::OK;
+    }
+
+    match record_vote(&state.database, user_id, payload.is_weekend).await {
+        Ok(streak) => {
+            info!("User {user_id} voted (streak: {})", streak.streak);
+            StatusCode::OK
+        }
+        Err(err) => {
+            error!("Failed to record vote from user {user_id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}